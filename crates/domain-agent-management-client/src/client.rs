@@ -1,15 +1,21 @@
 use anyhow::Result;
 use domain_agent_management::generated::agent_management::agent_management_service_client::AgentManagementServiceClient;
 use domain_agent_management::server::grpc::{
-    ApproveRequest, DenyRequest, GetAgentRequest, ListAgentsRequest,
+    ApproveRequest, CancelTaskRequest, CreateIngressRouteRequest, DeleteIngressRouteRequest,
+    DenyRequest, Empty, GetAgentRequest, GetTaskRequest, ListAgentsRequest, ListTasksRequest,
+    SubmitTaskRequest,
 };
+use tonic::metadata::MetadataValue;
 use tonic::transport::Channel;
+use tonic::Request;
 
 /// Configuration for connecting to the agent-management gRPC service
 #[derive(Debug, Clone)]
 pub struct Config {
     /// gRPC endpoint address (e.g., "http://localhost:50051")
     pub endpoint: String,
+    /// Bearer token sent with every request, from `POST /api/v1/auth/login`
+    pub token: Option<String>,
 }
 
 impl Config {
@@ -17,21 +23,47 @@ impl Config {
     pub fn new(endpoint: impl Into<String>) -> Self {
         Self {
             endpoint: endpoint.into(),
+            token: None,
         }
     }
+
+    /// Set the bearer token used to authenticate
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
 }
 
 /// Client for the agent-management gRPC service
 #[derive(Debug)]
 pub struct AgentManagementClient {
     inner: AgentManagementServiceClient<Channel>,
+    authorization: Option<MetadataValue<tonic::metadata::Ascii>>,
 }
 
 impl AgentManagementClient {
     /// Connect to the agent-management service
     pub async fn connect(config: Config) -> Result<Self> {
+        let authorization = config
+            .token
+            .map(|token| format!("Bearer {}", token).parse())
+            .transpose()?;
         let inner = AgentManagementServiceClient::connect(config.endpoint).await?;
-        Ok(Self { inner })
+        Ok(Self {
+            inner,
+            authorization,
+        })
+    }
+
+    /// Wrap a message into a request carrying the bearer token
+    fn request<T>(&self, message: T) -> Request<T> {
+        let mut request = Request::new(message);
+        if let Some(authorization) = &self.authorization {
+            request
+                .metadata_mut()
+                .insert("authorization", authorization.clone());
+        }
+        request
     }
 
     /// Get an agent by ID
@@ -39,7 +71,7 @@ impl AgentManagementClient {
         let request = GetAgentRequest {
             agent_id: agent_id.to_string(),
         };
-        let response = self.inner.get_agent(request).await?;
+        let response = self.inner.get_agent(self.request(request)).await?;
         Ok(response.into_inner())
     }
 
@@ -56,7 +88,7 @@ impl AgentManagementClient {
             page_size,
             page_token: None,
         };
-        let response = self.inner.list_agents(request).await?;
+        let response = self.inner.list_agents(self.request(request)).await?;
         Ok(response.into_inner())
     }
 
@@ -72,7 +104,7 @@ impl AgentManagementClient {
             approved_by: approved_by.map(String::from),
             notes: notes.map(String::from),
         };
-        let response = self.inner.approve_agent(request).await?;
+        let response = self.inner.approve_agent(self.request(request)).await?;
         Ok(response.into_inner())
     }
 
@@ -88,7 +120,105 @@ impl AgentManagementClient {
             reason: reason.to_string(),
             denied_by: denied_by.map(String::from),
         };
-        let response = self.inner.deny_agent(request).await?;
+        let response = self.inner.deny_agent(self.request(request)).await?;
         Ok(response.into_inner())
     }
+
+    /// Submit a remote task, `task_json` is the task definition as JSON
+    pub async fn submit_task(
+        &mut self,
+        agent_id: &str,
+        task_json: &str,
+        timeout_seconds: u32,
+        submitted_by: Option<&str>,
+    ) -> Result<crate::proto::Task> {
+        let request = SubmitTaskRequest {
+            agent_id: agent_id.to_string(),
+            task_json: task_json.to_string(),
+            timeout_seconds,
+            submitted_by: submitted_by.map(String::from),
+        };
+        let response = self.inner.submit_task(self.request(request)).await?;
+        Ok(response.into_inner())
+    }
+
+    /// Get a task with its status and output
+    pub async fn get_task(&mut self, task_id: &str) -> Result<crate::proto::Task> {
+        let request = GetTaskRequest {
+            task_id: task_id.to_string(),
+        };
+        let response = self.inner.get_task(self.request(request)).await?;
+        Ok(response.into_inner())
+    }
+
+    /// List the latest tasks of an agent
+    pub async fn list_tasks(
+        &mut self,
+        agent_id: &str,
+        limit: i32,
+    ) -> Result<crate::proto::ListTasksResponse> {
+        let request = ListTasksRequest {
+            agent_id: agent_id.to_string(),
+            limit,
+        };
+        let response = self.inner.list_tasks(self.request(request)).await?;
+        Ok(response.into_inner())
+    }
+
+    /// Cancel a task
+    pub async fn cancel_task(
+        &mut self,
+        task_id: &str,
+        reason: Option<&str>,
+        cancelled_by: Option<&str>,
+    ) -> Result<crate::proto::Task> {
+        let request = CancelTaskRequest {
+            task_id: task_id.to_string(),
+            reason: reason.map(String::from),
+            cancelled_by: cancelled_by.map(String::from),
+        };
+        let response = self.inner.cancel_task(self.request(request)).await?;
+        Ok(response.into_inner())
+    }
+
+    /// Route a hostname to a `host:port` target behind an agent
+    pub async fn create_ingress_route(
+        &mut self,
+        hostname: &str,
+        agent_id: &str,
+        target: &str,
+        tls: bool,
+    ) -> Result<crate::proto::IngressRoute> {
+        let request = CreateIngressRouteRequest {
+            hostname: hostname.to_string(),
+            agent_id: agent_id.to_string(),
+            target: target.to_string(),
+            tls,
+        };
+        let response = self
+            .inner
+            .create_ingress_route(self.request(request))
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    /// List all ingress routes
+    pub async fn list_ingress_routes(&mut self) -> Result<crate::proto::ListIngressRoutesResponse> {
+        let response = self
+            .inner
+            .list_ingress_routes(self.request(Empty {}))
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    /// Delete an ingress route
+    pub async fn delete_ingress_route(&mut self, route_id: &str) -> Result<()> {
+        let request = DeleteIngressRouteRequest {
+            route_id: route_id.to_string(),
+        };
+        self.inner
+            .delete_ingress_route(self.request(request))
+            .await?;
+        Ok(())
+    }
 }
@@ -1,3 +1,4 @@
+import { useState, useEffect } from 'react'
 import { BrowserRouter, Routes, Route } from 'react-router-dom'
 import { Layout } from './components/Layout'
 import { Dashboard } from './pages/Dashboard'
@@ -5,10 +6,12 @@ import { Agents } from './pages/Agents'
 import { Events } from './pages/Events'
 import { Health } from './pages/Health'
 import { Settings } from './pages/Settings'
+import { Login } from './pages/Login'
 import { AgentDetail } from './components/AgentDetail'
 import { useAgents } from './hooks/useAgents'
+import { getToken } from './api/agent'
 
-function App() {
+function AuthenticatedApp() {
   const { agents } = useAgents()
 
   return (
@@ -27,4 +30,20 @@ function App() {
   )
 }
 
+function App() {
+  const [loggedIn, setLoggedIn] = useState(() => Boolean(getToken()))
+
+  useEffect(() => {
+    const handleLogout = () => setLoggedIn(false)
+    window.addEventListener('auth:logout', handleLogout)
+    return () => window.removeEventListener('auth:logout', handleLogout)
+  }, [])
+
+  if (!loggedIn) {
+    return <Login onLogin={() => setLoggedIn(true)} />
+  }
+
+  return <AuthenticatedApp />
+}
+
 export default App
@@ -3,11 +3,49 @@ import axios from 'axios'
 
 const API_BASE = '/api/v1'
 
+const TOKEN_KEY = 'agent-management-token'
+
 const api = axios.create({
   baseURL: API_BASE,
   headers: { 'Content-Type': 'application/json' }
 })
 
+export const getToken = () => localStorage.getItem(TOKEN_KEY)
+
+export const setToken = (token) => {
+  if (token) {
+    localStorage.setItem(TOKEN_KEY, token)
+  } else {
+    localStorage.removeItem(TOKEN_KEY)
+  }
+}
+
+api.interceptors.request.use((config) => {
+  const token = getToken()
+  if (token) {
+    config.headers.Authorization = `Bearer ${token}`
+  }
+  return config
+})
+
+// An expired or revoked token sends the user back to the login page
+api.interceptors.response.use(
+  (response) => response,
+  (error) => {
+    if (error.response?.status === 401) {
+      setToken(null)
+      window.dispatchEvent(new Event('auth:logout'))
+    }
+    return Promise.reject(error)
+  }
+)
+
+export const authApi = {
+  login: (username, password) => api.post('/auth/login', { username, password }),
+  logout: () => api.post('/auth/logout'),
+  me: () => api.get('/auth/me')
+}
+
 export const agentApi = {
   list: () => api.get('/agents'),
   get: (id) => api.get(`/agents/${id}`),
@@ -21,4 +59,17 @@ export const agentApi = {
   getLifecycle: (id) => api.get(`/agents/${id}/lifecycle`)
 }
 
+// Live lifecycle events, health scores and heartbeats via Server-Sent Events.
+// EventSource can't set headers, so the token is sent as a query parameter.
+// Returns a function that closes the stream.
+export const subscribeEvents = ({ agentId, eventTypes } = {}, onEvent) => {
+  const params = new URLSearchParams({ access_token: getToken() || '' })
+  if (agentId) params.set('agent_id', agentId)
+  if (eventTypes?.length) params.set('event_types', eventTypes.join(','))
+
+  const source = new EventSource(`${API_BASE}/events?${params}`)
+  source.onmessage = (message) => onEvent(JSON.parse(message.data))
+  return () => source.close()
+}
+
 export default agentApi
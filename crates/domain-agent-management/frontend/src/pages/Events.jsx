@@ -1,27 +1,68 @@
 // crates/agent-management/frontend/src/pages/Events.jsx
+import { useEffect, useState } from 'react'
 import { Header } from '../components/Header'
+import { subscribeEvents } from '../api/agent'
+import { formatDate } from '../utils/formatters'
+
+const MAX_EVENTS = 200
+
+const eventColors = {
+  agent_registered: '#67c23a',
+  agent_approved: '#409eff',
+  agent_connected: '#409eff',
+  agent_denied: '#f56c6c',
+  agent_error: '#f56c6c',
+  agent_disconnected: '#909399',
+  agent_closed: '#909399',
+  health_score: '#e6a23c'
+}
+
+// Flattens a bus event ({kind, data}) into a row of the event list
+const toRow = ({ kind, data }) => {
+  switch (kind) {
+    case 'lifecycle':
+      return { id: data.event_id, time: data.timestamp, type: data.event_type, agent: data.agent_id, desc: data.reason || '' }
+    case 'health':
+      return { id: data.id, time: data.scored_at, type: 'health_score', agent: data.agent_id, desc: `Score ${Math.round(data.overall_score)}` }
+    default:
+      return { id: data.event_id, time: data.timestamp, type: kind, agent: data.agent_id, desc: data.status }
+  }
+}
 
 export function Events() {
-  const events = [
-    { time: '2026-06-07 14:32', type: 'AgentRegistered', agent: 'agent-worker-01', desc: 'Registered successfully', color: '#67c23a' },
-    { time: '2026-06-07 14:30', type: 'AgentConnected', agent: 'agent-controller-01', desc: 'Connected', color: '#409eff' },
-    { time: '2026-06-07 14:25', type: 'AgentApproved', agent: 'agent-worker-02', desc: 'Approved by admin', color: '#409eff' }
-  ]
+  const [events, setEvents] = useState([])
+  const [showHeartbeats, setShowHeartbeats] = useState(false)
+
+  useEffect(() => {
+    const close = subscribeEvents({}, (event) => {
+      setEvents((current) => [toRow(event), ...current].slice(0, MAX_EVENTS))
+    })
+    return close
+  }, [])
+
+  const visible = showHeartbeats ? events : events.filter((event) => event.type !== 'heartbeat')
 
   return (
     <>
       <Header title="Events" />
       <div className="flex-1 p-6">
         <div className="bg-white rounded-lg border border-gray-200">
-          <div className="px-5 py-4 border-b border-gray-200">
-            <span className="font-semibold text-gray-800">Lifecycle Events</span>
+          <div className="px-5 py-4 border-b border-gray-200 flex justify-between items-center">
+            <span className="font-semibold text-gray-800">Live Events</span>
+            <label className="text-xs text-gray-500 flex items-center gap-2">
+              <input type="checkbox" checked={showHeartbeats} onChange={(e) => setShowHeartbeats(e.target.checked)} />
+              Show heartbeats
+            </label>
           </div>
           <div className="p-5">
-            {events.map((event, i) => (
-              <div key={i} className="flex gap-4 py-3 border-b border-gray-100">
-                <span className="text-xs text-gray-400 w-36">{event.time}</span>
-                <span className="text-xs font-semibold w-36" style={{ color: event.color }}>{event.type}</span>
-                <span className="text-xs text-gray-500 w-36">{event.agent}</span>
+            {visible.length === 0 && (
+              <div className="text-sm text-gray-400">Waiting for events…</div>
+            )}
+            {visible.map((event) => (
+              <div key={event.id} className="flex gap-4 py-3 border-b border-gray-100">
+                <span className="text-xs text-gray-400 w-36">{formatDate(event.time)}</span>
+                <span className="text-xs font-semibold w-36" style={{ color: eventColors[event.type] || '#606266' }}>{event.type}</span>
+                <span className="text-xs text-gray-500 w-36 truncate" title={event.agent}>{event.agent}</span>
                 <span className="text-sm text-gray-600">{event.desc}</span>
               </div>
             ))}
@@ -0,0 +1,52 @@
+// crates/agent-management/frontend/src/pages/Login.jsx
+import { useState } from 'react'
+import { authApi, setToken } from '../api/agent'
+
+export function Login({ onLogin }) {
+  const [username, setUsername] = useState('')
+  const [password, setPassword] = useState('')
+  const [error, setError] = useState(null)
+  const [loading, setLoading] = useState(false)
+
+  const handleSubmit = async (e) => {
+    e.preventDefault()
+    setLoading(true)
+    setError(null)
+    try {
+      const response = await authApi.login(username, password)
+      setToken(response.data.token)
+      onLogin(response.data.user)
+    } catch (err) {
+      setError(err.response?.data?.error || err.message)
+    } finally {
+      setLoading(false)
+    }
+  }
+
+  return (
+    <div className="min-h-screen flex items-center justify-center bg-gray-100">
+      <form onSubmit={handleSubmit} className="bg-white rounded-lg border border-gray-200 w-full max-w-sm p-6">
+        <h2 className="text-xl font-semibold text-gray-800 mb-6 text-center">Agent Management</h2>
+        {error && <p className="text-sm text-danger mb-4">{error}</p>}
+        <input
+          className="w-full px-3 py-2 border border-gray-300 rounded text-sm mb-3 focus:outline-none focus:border-primary"
+          placeholder="Username"
+          autoComplete="username"
+          value={username}
+          onChange={(e) => setUsername(e.target.value)}
+        />
+        <input
+          className="w-full px-3 py-2 border border-gray-300 rounded text-sm mb-4 focus:outline-none focus:border-primary"
+          type="password"
+          placeholder="Password"
+          autoComplete="current-password"
+          value={password}
+          onChange={(e) => setPassword(e.target.value)}
+        />
+        <button type="submit" disabled={loading} className="w-full px-4 py-2 text-sm bg-primary text-white rounded disabled:opacity-50">
+          {loading ? 'Signing in...' : 'Sign in'}
+        </button>
+      </form>
+    </div>
+  )
+}
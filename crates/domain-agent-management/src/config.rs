@@ -22,10 +22,16 @@ impl Default for ServerConfig {
 }
 
 /// Database configuration
+///
+/// The URL scheme selects the backend: `postgres://` for PostgreSQL or
+/// `sqlite:` for SQLite, e.g. `sqlite://agent-management.db?mode=rwc` for a
+/// file created on first start or `sqlite::memory:` for an in-memory database.
 #[derive(Debug, Deserialize, Clone)]
 pub struct DatabaseConfig {
     pub url: String,
+    /// PostgreSQL user, ignored for SQLite
     pub username: Option<String>,
+    /// PostgreSQL password, ignored for SQLite
     pub password: Option<String>,
     pub max_connections: u32,
 }
@@ -33,12 +39,19 @@ pub struct DatabaseConfig {
 impl DatabaseConfig {
     /// Get connection URL with credentials if provided
     pub fn connection_url(&self) -> String {
+        let Some(address) = self
+            .url
+            .strip_prefix("postgres://")
+            .or_else(|| self.url.strip_prefix("postgresql://"))
+        else {
+            return self.url.clone();
+        };
         match (&self.username, &self.password) {
             (Some(user), Some(pass)) => {
-                format!("postgres://{}:{}@{}", user, pass, self.url.trim_start_matches("postgres://"))
+                format!("postgres://{}:{}@{}", user, pass, address)
             }
             (Some(user), None) => {
-                format!("postgres://{}@{}", user, self.url.trim_start_matches("postgres://"))
+                format!("postgres://{}@{}", user, address)
             }
             _ => self.url.clone(),
         }
@@ -48,9 +61,9 @@ impl DatabaseConfig {
 impl Default for DatabaseConfig {
     fn default() -> Self {
         Self {
-            url: "postgres://192.168.3.112:5432/agent_management".to_string(),
-            username: Some("agent_management".into()),
-            password: Some("123456".into()),
+            url: "sqlite://agent-management.db?mode=rwc".to_string(),
+            username: None,
+            password: None,
             max_connections: 10,
         }
     }
@@ -88,6 +101,72 @@ impl Default for RestConfig {
     }
 }
 
+/// Authentication configuration
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct AuthConfig {
+    /// Lifetime of the tokens issued at login
+    pub token_ttl_hours: u64,
+    /// Name of the admin user created when no user exists
+    pub admin_username: String,
+    /// Password of the initial admin user, a random one is generated and logged when unset
+    pub admin_password: Option<String>,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            token_ttl_hours: 12,
+            admin_username: "admin".to_string(),
+            admin_password: None,
+        }
+    }
+}
+
+/// Reverse tunnel configuration
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct TunnelConfig {
+    /// Address the public tunnel ports listen on
+    pub bind_host: String,
+    /// Lowest public port handed out to tunnels, 0 together with `max_port` for any free port
+    pub min_port: u16,
+    /// Highest public port handed out to tunnels
+    pub max_port: u16,
+}
+
+impl Default for TunnelConfig {
+    fn default() -> Self {
+        Self {
+            bind_host: "0.0.0.0".to_string(),
+            min_port: 0,
+            max_port: 0,
+        }
+    }
+}
+
+/// Hostname-based HTTP(S) ingress configuration
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct IngressConfig {
+    /// Address the ingress listens on
+    pub bind_host: String,
+    /// Port of the plain HTTP listener routing by Host header, 0 to disable
+    pub http_port: u16,
+    /// Port of the TLS passthrough listener routing by SNI, 0 to disable
+    pub https_port: u16,
+}
+
+impl Default for IngressConfig {
+    fn default() -> Self {
+        Self {
+            bind_host: "0.0.0.0".to_string(),
+            http_port: 0,
+            https_port: 0,
+        }
+    }
+}
+
 /// Main application configuration
 #[derive(Debug, Deserialize, Clone)]
 pub struct AppConfig {
@@ -95,6 +174,12 @@ pub struct AppConfig {
     pub database: DatabaseConfig,
     pub grpc: GrpcConfig,
     pub rest: RestConfig,
+    #[serde(default)]
+    pub auth: AuthConfig,
+    #[serde(default)]
+    pub tunnel: TunnelConfig,
+    #[serde(default)]
+    pub ingress: IngressConfig,
 }
 
 impl Default for AppConfig {
@@ -104,6 +189,9 @@ impl Default for AppConfig {
             database: DatabaseConfig::default(),
             grpc: GrpcConfig::default(),
             rest: RestConfig::default(),
+            auth: AuthConfig::default(),
+            tunnel: TunnelConfig::default(),
+            ingress: IngressConfig::default(),
         }
     }
 }
@@ -135,5 +223,9 @@ mod tests {
         assert_eq!(config.server.ws_port, 8081);
         assert_eq!(config.grpc.port, 50051);
         assert_eq!(config.rest.port, 8080);
+        assert_eq!(config.auth.token_ttl_hours, 12);
+        assert_eq!(config.auth.admin_username, "admin");
+        assert!(config.auth.admin_password.is_none());
+        assert_eq!(config.ingress.http_port, 0);
     }
 }
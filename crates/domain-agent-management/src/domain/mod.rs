@@ -1,7 +1,10 @@
 //! Domain module for agent management
 //!
-//! Contains core business logic including the lifecycle state machine.
+//! Contains core business logic including the lifecycle state machine and
+//! the role-based access control rules.
 
+pub mod rbac;
 pub mod state_machine;
 
+pub use rbac::{Permission, Role};
 pub use state_machine::{AgentLifecycleState, LifecycleStateMachine};
\ No newline at end of file
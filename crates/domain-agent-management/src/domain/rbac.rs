@@ -0,0 +1,152 @@
+//! Role-based access control
+//!
+//! This module defines the roles users can have and the permissions each role
+//! grants. Roles are ordered: every role has all permissions of the roles
+//! below it.
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Role of a user of the management API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    /// Read-only access to agents and tasks
+    Viewer,
+    /// Viewer access plus running tasks and updating agents
+    Operator,
+    /// Full access including agent approval and user management
+    Admin,
+}
+
+/// Actions that are checked before a REST route or gRPC method runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Permission {
+    /// List and get agents, their system info, health and lifecycle events
+    ViewAgents,
+    /// Update the name or status of an agent
+    UpdateAgent,
+    /// Ask an agent for fresh system information
+    QuerySystemInfo,
+    /// List and get tasks and their events
+    ViewTasks,
+    /// Submit a task to an agent
+    SubmitTask,
+    /// Cancel a task
+    CancelTask,
+    /// Approve or deny an agent registration
+    ApproveAgent,
+    /// Delete an agent
+    DeleteAgent,
+    /// Create, update and delete users
+    ManageUsers,
+    /// Query and export the audit log
+    ViewAudit,
+    /// Create and delete ingress routes, which expose agent services publicly
+    ManageIngress,
+}
+
+/// Error returned when parsing an unknown role name.
+#[derive(Debug, Error)]
+#[error("Unknown role: {0}")]
+pub struct UnknownRole(pub String);
+
+impl Role {
+    /// All roles, from the least to the most privileged.
+    pub const ALL: [Role; 3] = [Role::Viewer, Role::Operator, Role::Admin];
+
+    /// Name of the role as stored in the database.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Viewer => "viewer",
+            Role::Operator => "operator",
+            Role::Admin => "admin",
+        }
+    }
+
+    /// Whether the role grants the permission.
+    pub fn allows(&self, permission: Permission) -> bool {
+        *self >= permission.required_role()
+    }
+
+    /// All permissions granted by the role.
+    pub fn permissions(&self) -> Vec<Permission> {
+        Permission::ALL
+            .into_iter()
+            .filter(|permission| self.allows(*permission))
+            .collect()
+    }
+}
+
+impl std::fmt::Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for Role {
+    type Err = UnknownRole;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Role::ALL
+            .into_iter()
+            .find(|role| role.as_str().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| UnknownRole(s.to_string()))
+    }
+}
+
+impl Permission {
+    /// All permissions.
+    pub const ALL: [Permission; 11] = [
+        Permission::ViewAgents,
+        Permission::UpdateAgent,
+        Permission::QuerySystemInfo,
+        Permission::ViewTasks,
+        Permission::SubmitTask,
+        Permission::CancelTask,
+        Permission::ApproveAgent,
+        Permission::DeleteAgent,
+        Permission::ManageUsers,
+        Permission::ViewAudit,
+        Permission::ManageIngress,
+    ];
+
+    /// The least privileged role that has the permission.
+    pub fn required_role(&self) -> Role {
+        match self {
+            Permission::ViewAgents | Permission::ViewTasks => Role::Viewer,
+            Permission::UpdateAgent
+            | Permission::QuerySystemInfo
+            | Permission::SubmitTask
+            | Permission::CancelTask => Role::Operator,
+            Permission::ApproveAgent
+            | Permission::DeleteAgent
+            | Permission::ManageUsers
+            | Permission::ViewAudit
+            | Permission::ManageIngress => Role::Admin,
+        }
+    }
+
+    /// Name of the permission used in error messages.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Permission::ViewAgents => "view_agents",
+            Permission::UpdateAgent => "update_agent",
+            Permission::QuerySystemInfo => "query_system_info",
+            Permission::ViewTasks => "view_tasks",
+            Permission::SubmitTask => "submit_task",
+            Permission::CancelTask => "cancel_task",
+            Permission::ApproveAgent => "approve_agent",
+            Permission::DeleteAgent => "delete_agent",
+            Permission::ManageUsers => "manage_users",
+            Permission::ViewAudit => "view_audit",
+            Permission::ManageIngress => "manage_ingress",
+        }
+    }
+}
+
+impl std::fmt::Display for Permission {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
@@ -256,8 +256,12 @@ pub struct DiskInfo {
 /// Agent events streaming
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct StreamEventsRequest {
+    /// Empty for all agents
     #[prost(string, tag = "1")]
     pub agent_id: ::prost::alloc::string::String,
+    /// Lifecycle event types, "health_score" or "heartbeat"; empty for all
+    #[prost(string, repeated, tag = "2")]
+    pub event_types: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct AgentEvent {
@@ -293,8 +297,10 @@ pub struct HealthScore {
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct StreamHealthRequest {
+    /// Empty for all agents
     #[prost(string, tag = "1")]
     pub agent_id: ::prost::alloc::string::String,
+    /// Minimum seconds between two scores of the same agent, 0 for every score
     #[prost(int32, tag = "2")]
     pub update_interval_seconds: i32,
 }
@@ -313,8 +319,200 @@ pub struct LifecycleEventsResponse {
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct StreamLifecycleRequest {
+    /// Empty for all agents
     #[prost(string, tag = "1")]
     pub agent_id: ::prost::alloc::string::String,
+    /// Lifecycle event types, e.g. "agent_connected"; empty for all
+    #[prost(string, repeated, tag = "2")]
+    pub event_types: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+/// Remote tasks
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Task {
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub agent_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub task_type: ::prost::alloc::string::String,
+    /// Task definition as JSON, e.g. {"type": "Shell", "data": {"command": "uptime"}}
+    #[prost(string, tag = "4")]
+    pub task_json: ::prost::alloc::string::String,
+    #[prost(string, tag = "5")]
+    pub status: ::prost::alloc::string::String,
+    #[prost(uint32, tag = "6")]
+    pub timeout_seconds: u32,
+    #[prost(string, tag = "7")]
+    pub output: ::prost::alloc::string::String,
+    #[prost(string, optional, tag = "8")]
+    pub error: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(int32, optional, tag = "9")]
+    pub exit_code: ::core::option::Option<i32>,
+    #[prost(uint64, optional, tag = "10")]
+    pub duration_ms: ::core::option::Option<u64>,
+    #[prost(string, optional, tag = "11")]
+    pub submitted_by: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(int64, tag = "12")]
+    pub created_at: i64,
+    #[prost(int64, optional, tag = "13")]
+    pub dispatched_at: ::core::option::Option<i64>,
+    #[prost(int64, optional, tag = "14")]
+    pub finished_at: ::core::option::Option<i64>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SubmitTaskRequest {
+    #[prost(string, tag = "1")]
+    pub agent_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub task_json: ::prost::alloc::string::String,
+    /// 0 uses the agent's default timeout
+    #[prost(uint32, tag = "3")]
+    pub timeout_seconds: u32,
+    #[prost(string, optional, tag = "4")]
+    pub submitted_by: ::core::option::Option<::prost::alloc::string::String>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetTaskRequest {
+    #[prost(string, tag = "1")]
+    pub task_id: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListTasksRequest {
+    #[prost(string, tag = "1")]
+    pub agent_id: ::prost::alloc::string::String,
+    #[prost(int32, tag = "2")]
+    pub limit: i32,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListTasksResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub tasks: ::prost::alloc::vec::Vec<Task>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CancelTaskRequest {
+    #[prost(string, tag = "1")]
+    pub task_id: ::prost::alloc::string::String,
+    #[prost(string, optional, tag = "2")]
+    pub reason: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "3")]
+    pub cancelled_by: ::core::option::Option<::prost::alloc::string::String>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetTaskEventsRequest {
+    #[prost(string, tag = "1")]
+    pub task_id: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TaskEventsResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub events: ::prost::alloc::vec::Vec<AgentEvent>,
+}
+/// Audit log
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AuditEntry {
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+    #[prost(int64, tag = "2")]
+    pub timestamp: i64,
+    #[prost(string, tag = "3")]
+    pub actor: ::prost::alloc::string::String,
+    #[prost(string, optional, tag = "4")]
+    pub actor_role: ::core::option::Option<::prost::alloc::string::String>,
+    /// e.g. "agent.approve" or "task.submit"
+    #[prost(string, tag = "5")]
+    pub action: ::prost::alloc::string::String,
+    #[prost(string, tag = "6")]
+    pub target_type: ::prost::alloc::string::String,
+    #[prost(string, optional, tag = "7")]
+    pub target_id: ::core::option::Option<::prost::alloc::string::String>,
+    /// "rest" or "grpc"
+    #[prost(string, tag = "8")]
+    pub source: ::prost::alloc::string::String,
+    #[prost(string, optional, tag = "9")]
+    pub remote_addr: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(bool, tag = "10")]
+    pub success: bool,
+    #[prost(string, optional, tag = "11")]
+    pub error: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "12")]
+    pub before_json: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "13")]
+    pub after_json: ::core::option::Option<::prost::alloc::string::String>,
+    /// Changed fields as {"field": {"before": .., "after": ..}}
+    #[prost(string, optional, tag = "14")]
+    pub changes_json: ::core::option::Option<::prost::alloc::string::String>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListAuditEntriesRequest {
+    #[prost(string, optional, tag = "1")]
+    pub actor: ::core::option::Option<::prost::alloc::string::String>,
+    /// Action name or prefix, e.g. "agent.approve" or "agent"
+    #[prost(string, optional, tag = "2")]
+    pub action: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "3")]
+    pub target_type: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "4")]
+    pub target_id: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "5")]
+    pub source: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(int64, optional, tag = "6")]
+    pub since: ::core::option::Option<i64>,
+    #[prost(int64, optional, tag = "7")]
+    pub until: ::core::option::Option<i64>,
+    /// 0 returns 50 entries, at most 500
+    #[prost(uint32, tag = "8")]
+    pub limit: u32,
+    #[prost(uint64, tag = "9")]
+    pub offset: u64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListAuditEntriesResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub entries: ::prost::alloc::vec::Vec<AuditEntry>,
+    /// Number of entries matching the filters
+    #[prost(uint64, tag = "2")]
+    pub total: u64,
+}
+/// Ingress routes
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct IngressRoute {
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+    /// Matched against the Host header, or the TLS SNI when tls is set
+    #[prost(string, tag = "2")]
+    pub hostname: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub agent_id: ::prost::alloc::string::String,
+    /// host:port reachable from the agent
+    #[prost(string, tag = "4")]
+    pub target: ::prost::alloc::string::String,
+    #[prost(bool, tag = "5")]
+    pub tls: bool,
+    #[prost(string, optional, tag = "6")]
+    pub created_by: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(int64, tag = "7")]
+    pub created_at: i64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CreateIngressRouteRequest {
+    #[prost(string, tag = "1")]
+    pub hostname: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub agent_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub target: ::prost::alloc::string::String,
+    #[prost(bool, tag = "4")]
+    pub tls: bool,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListIngressRoutesResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub routes: ::prost::alloc::vec::Vec<IngressRoute>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeleteIngressRouteRequest {
+    #[prost(string, tag = "1")]
+    pub route_id: ::prost::alloc::string::String,
 }
 /// Generated client implementations.
 pub mod agent_management_service_client {
@@ -772,78 +970,330 @@ pub mod agent_management_service_client {
                 );
             self.inner.server_streaming(req, path, codec).await
         }
-    }
-}
-/// Generated server implementations.
-pub mod agent_management_service_server {
-    #![allow(
-        unused_variables,
-        dead_code,
-        missing_docs,
-        clippy::wildcard_imports,
-        clippy::let_unit_value,
-    )]
-    use tonic::codegen::*;
-    /// Generated trait containing gRPC methods that should be implemented for use with AgentManagementServiceServer.
-    #[async_trait]
-    pub trait AgentManagementService: std::marker::Send + std::marker::Sync + 'static {
-        /// Agent CRUD operations
-        async fn register_agent(
-            &self,
-            request: tonic::Request<super::RegisterRequest>,
+        /// Remote tasks
+        pub async fn submit_task(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SubmitTaskRequest>,
+        ) -> std::result::Result<tonic::Response<super::Task>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/agent_management.AgentManagementService/SubmitTask",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "agent_management.AgentManagementService",
+                        "SubmitTask",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn get_task(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetTaskRequest>,
+        ) -> std::result::Result<tonic::Response<super::Task>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/agent_management.AgentManagementService/GetTask",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new("agent_management.AgentManagementService", "GetTask"),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn list_tasks(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListTasksRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::RegisterResponse>,
+            tonic::Response<super::ListTasksResponse>,
             tonic::Status,
-        >;
-        async fn get_agent(
-            &self,
-            request: tonic::Request<super::GetAgentRequest>,
-        ) -> std::result::Result<tonic::Response<super::Agent>, tonic::Status>;
-        async fn list_agents(
-            &self,
-            request: tonic::Request<super::ListAgentsRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/agent_management.AgentManagementService/ListTasks",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "agent_management.AgentManagementService",
+                        "ListTasks",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn cancel_task(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CancelTaskRequest>,
+        ) -> std::result::Result<tonic::Response<super::Task>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/agent_management.AgentManagementService/CancelTask",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "agent_management.AgentManagementService",
+                        "CancelTask",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn get_task_events(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetTaskEventsRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::ListAgentsResponse>,
+            tonic::Response<super::TaskEventsResponse>,
             tonic::Status,
-        >;
-        async fn update_agent(
-            &self,
-            request: tonic::Request<super::UpdateAgentRequest>,
-        ) -> std::result::Result<tonic::Response<super::Agent>, tonic::Status>;
-        async fn delete_agent(
-            &self,
-            request: tonic::Request<super::DeleteAgentRequest>,
-        ) -> std::result::Result<tonic::Response<super::Empty>, tonic::Status>;
-        /// Approval management
-        async fn approve_agent(
-            &self,
-            request: tonic::Request<super::ApproveRequest>,
-        ) -> std::result::Result<tonic::Response<super::Agent>, tonic::Status>;
-        async fn deny_agent(
-            &self,
-            request: tonic::Request<super::DenyRequest>,
-        ) -> std::result::Result<tonic::Response<super::Agent>, tonic::Status>;
-        /// Diagnostic info
-        async fn get_agent_system_info(
-            &self,
-            request: tonic::Request<super::GetSystemInfoRequest>,
-        ) -> std::result::Result<tonic::Response<super::SystemInfo>, tonic::Status>;
-        /// Server streaming response type for the StreamAgentEvents method.
-        type StreamAgentEventsStream: tonic::codegen::tokio_stream::Stream<
-                Item = std::result::Result<super::AgentEvent, tonic::Status>,
-            >
-            + std::marker::Send
-            + 'static;
-        /// Event streaming
-        async fn stream_agent_events(
-            &self,
-            request: tonic::Request<super::StreamEventsRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/agent_management.AgentManagementService/GetTaskEvents",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "agent_management.AgentManagementService",
+                        "GetTaskEvents",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        /// Audit log
+        pub async fn list_audit_entries(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListAuditEntriesRequest>,
         ) -> std::result::Result<
-            tonic::Response<Self::StreamAgentEventsStream>,
+            tonic::Response<super::ListAuditEntriesResponse>,
             tonic::Status,
-        >;
-        /// Health scoring
-        async fn get_agent_health(
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/agent_management.AgentManagementService/ListAuditEntries",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "agent_management.AgentManagementService",
+                        "ListAuditEntries",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        /// Ingress routes
+        pub async fn create_ingress_route(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CreateIngressRouteRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::IngressRoute>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/agent_management.AgentManagementService/CreateIngressRoute",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "agent_management.AgentManagementService",
+                        "CreateIngressRoute",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn list_ingress_routes(
+            &mut self,
+            request: impl tonic::IntoRequest<super::Empty>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListIngressRoutesResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/agent_management.AgentManagementService/ListIngressRoutes",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "agent_management.AgentManagementService",
+                        "ListIngressRoutes",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn delete_ingress_route(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DeleteIngressRouteRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::Empty>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/agent_management.AgentManagementService/DeleteIngressRoute",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "agent_management.AgentManagementService",
+                        "DeleteIngressRoute",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+    }
+}
+/// Generated server implementations.
+pub mod agent_management_service_server {
+    #![allow(
+        unused_variables,
+        dead_code,
+        missing_docs,
+        clippy::wildcard_imports,
+        clippy::let_unit_value,
+    )]
+    use tonic::codegen::*;
+    /// Generated trait containing gRPC methods that should be implemented for use with AgentManagementServiceServer.
+    #[async_trait]
+    pub trait AgentManagementService: std::marker::Send + std::marker::Sync + 'static {
+        /// Agent CRUD operations
+        async fn register_agent(
+            &self,
+            request: tonic::Request<super::RegisterRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::RegisterResponse>,
+            tonic::Status,
+        >;
+        async fn get_agent(
+            &self,
+            request: tonic::Request<super::GetAgentRequest>,
+        ) -> std::result::Result<tonic::Response<super::Agent>, tonic::Status>;
+        async fn list_agents(
+            &self,
+            request: tonic::Request<super::ListAgentsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListAgentsResponse>,
+            tonic::Status,
+        >;
+        async fn update_agent(
+            &self,
+            request: tonic::Request<super::UpdateAgentRequest>,
+        ) -> std::result::Result<tonic::Response<super::Agent>, tonic::Status>;
+        async fn delete_agent(
+            &self,
+            request: tonic::Request<super::DeleteAgentRequest>,
+        ) -> std::result::Result<tonic::Response<super::Empty>, tonic::Status>;
+        /// Approval management
+        async fn approve_agent(
+            &self,
+            request: tonic::Request<super::ApproveRequest>,
+        ) -> std::result::Result<tonic::Response<super::Agent>, tonic::Status>;
+        async fn deny_agent(
+            &self,
+            request: tonic::Request<super::DenyRequest>,
+        ) -> std::result::Result<tonic::Response<super::Agent>, tonic::Status>;
+        /// Diagnostic info
+        async fn get_agent_system_info(
+            &self,
+            request: tonic::Request<super::GetSystemInfoRequest>,
+        ) -> std::result::Result<tonic::Response<super::SystemInfo>, tonic::Status>;
+        /// Server streaming response type for the StreamAgentEvents method.
+        type StreamAgentEventsStream: tonic::codegen::tokio_stream::Stream<
+                Item = std::result::Result<super::AgentEvent, tonic::Status>,
+            >
+            + std::marker::Send
+            + 'static;
+        /// Event streaming
+        async fn stream_agent_events(
+            &self,
+            request: tonic::Request<super::StreamEventsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<Self::StreamAgentEventsStream>,
+            tonic::Status,
+        >;
+        /// Health scoring
+        async fn get_agent_health(
             &self,
             request: tonic::Request<super::GetAgentHealthRequest>,
         ) -> std::result::Result<tonic::Response<super::HealthScore>, tonic::Status>;
@@ -881,6 +1331,63 @@ pub mod agent_management_service_server {
             tonic::Response<Self::StreamLifecycleEventsStream>,
             tonic::Status,
         >;
+        /// Remote tasks
+        async fn submit_task(
+            &self,
+            request: tonic::Request<super::SubmitTaskRequest>,
+        ) -> std::result::Result<tonic::Response<super::Task>, tonic::Status>;
+        async fn get_task(
+            &self,
+            request: tonic::Request<super::GetTaskRequest>,
+        ) -> std::result::Result<tonic::Response<super::Task>, tonic::Status>;
+        async fn list_tasks(
+            &self,
+            request: tonic::Request<super::ListTasksRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListTasksResponse>,
+            tonic::Status,
+        >;
+        async fn cancel_task(
+            &self,
+            request: tonic::Request<super::CancelTaskRequest>,
+        ) -> std::result::Result<tonic::Response<super::Task>, tonic::Status>;
+        async fn get_task_events(
+            &self,
+            request: tonic::Request<super::GetTaskEventsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::TaskEventsResponse>,
+            tonic::Status,
+        >;
+        /// Audit log
+        async fn list_audit_entries(
+            &self,
+            request: tonic::Request<super::ListAuditEntriesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListAuditEntriesResponse>,
+            tonic::Status,
+        >;
+        /// Ingress routes
+        async fn create_ingress_route(
+            &self,
+            request: tonic::Request<super::CreateIngressRouteRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::IngressRoute>,
+            tonic::Status,
+        >;
+        async fn list_ingress_routes(
+            &self,
+            request: tonic::Request<super::Empty>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListIngressRoutesResponse>,
+            tonic::Status,
+        >;
+        async fn delete_ingress_route(
+            &self,
+            request: tonic::Request<super::DeleteIngressRouteRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::Empty>,
+            tonic::Status,
+        >;
     }
     /// Agent Management Service
     #[derive(Debug)]
@@ -962,23 +1469,401 @@ pub mod agent_management_service_server {
             match req.uri().path() {
                 "/agent_management.AgentManagementService/RegisterAgent" => {
                     #[allow(non_camel_case_types)]
-                    struct RegisterAgentSvc<T: AgentManagementService>(pub Arc<T>);
+                    struct RegisterAgentSvc<T: AgentManagementService>(pub Arc<T>);
+                    impl<
+                        T: AgentManagementService,
+                    > tonic::server::UnaryService<super::RegisterRequest>
+                    for RegisterAgentSvc<T> {
+                        type Response = super::RegisterResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::RegisterRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as AgentManagementService>::register_agent(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = RegisterAgentSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/agent_management.AgentManagementService/GetAgent" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetAgentSvc<T: AgentManagementService>(pub Arc<T>);
+                    impl<
+                        T: AgentManagementService,
+                    > tonic::server::UnaryService<super::GetAgentRequest>
+                    for GetAgentSvc<T> {
+                        type Response = super::Agent;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetAgentRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as AgentManagementService>::get_agent(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = GetAgentSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/agent_management.AgentManagementService/ListAgents" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListAgentsSvc<T: AgentManagementService>(pub Arc<T>);
+                    impl<
+                        T: AgentManagementService,
+                    > tonic::server::UnaryService<super::ListAgentsRequest>
+                    for ListAgentsSvc<T> {
+                        type Response = super::ListAgentsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListAgentsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as AgentManagementService>::list_agents(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = ListAgentsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/agent_management.AgentManagementService/UpdateAgent" => {
+                    #[allow(non_camel_case_types)]
+                    struct UpdateAgentSvc<T: AgentManagementService>(pub Arc<T>);
+                    impl<
+                        T: AgentManagementService,
+                    > tonic::server::UnaryService<super::UpdateAgentRequest>
+                    for UpdateAgentSvc<T> {
+                        type Response = super::Agent;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::UpdateAgentRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as AgentManagementService>::update_agent(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = UpdateAgentSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/agent_management.AgentManagementService/DeleteAgent" => {
+                    #[allow(non_camel_case_types)]
+                    struct DeleteAgentSvc<T: AgentManagementService>(pub Arc<T>);
+                    impl<
+                        T: AgentManagementService,
+                    > tonic::server::UnaryService<super::DeleteAgentRequest>
+                    for DeleteAgentSvc<T> {
+                        type Response = super::Empty;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DeleteAgentRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as AgentManagementService>::delete_agent(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = DeleteAgentSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/agent_management.AgentManagementService/ApproveAgent" => {
+                    #[allow(non_camel_case_types)]
+                    struct ApproveAgentSvc<T: AgentManagementService>(pub Arc<T>);
+                    impl<
+                        T: AgentManagementService,
+                    > tonic::server::UnaryService<super::ApproveRequest>
+                    for ApproveAgentSvc<T> {
+                        type Response = super::Agent;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ApproveRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as AgentManagementService>::approve_agent(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = ApproveAgentSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/agent_management.AgentManagementService/DenyAgent" => {
+                    #[allow(non_camel_case_types)]
+                    struct DenyAgentSvc<T: AgentManagementService>(pub Arc<T>);
+                    impl<
+                        T: AgentManagementService,
+                    > tonic::server::UnaryService<super::DenyRequest>
+                    for DenyAgentSvc<T> {
+                        type Response = super::Agent;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DenyRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as AgentManagementService>::deny_agent(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = DenyAgentSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/agent_management.AgentManagementService/GetAgentSystemInfo" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetAgentSystemInfoSvc<T: AgentManagementService>(pub Arc<T>);
+                    impl<
+                        T: AgentManagementService,
+                    > tonic::server::UnaryService<super::GetSystemInfoRequest>
+                    for GetAgentSystemInfoSvc<T> {
+                        type Response = super::SystemInfo;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetSystemInfoRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as AgentManagementService>::get_agent_system_info(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = GetAgentSystemInfoSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/agent_management.AgentManagementService/StreamAgentEvents" => {
+                    #[allow(non_camel_case_types)]
+                    struct StreamAgentEventsSvc<T: AgentManagementService>(pub Arc<T>);
                     impl<
                         T: AgentManagementService,
-                    > tonic::server::UnaryService<super::RegisterRequest>
-                    for RegisterAgentSvc<T> {
-                        type Response = super::RegisterResponse;
+                    > tonic::server::ServerStreamingService<super::StreamEventsRequest>
+                    for StreamAgentEventsSvc<T> {
+                        type Response = super::AgentEvent;
+                        type ResponseStream = T::StreamAgentEventsStream;
                         type Future = BoxFuture<
-                            tonic::Response<Self::Response>,
+                            tonic::Response<Self::ResponseStream>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::RegisterRequest>,
+                            request: tonic::Request<super::StreamEventsRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as AgentManagementService>::register_agent(
+                                <T as AgentManagementService>::stream_agent_events(
                                         &inner,
                                         request,
                                     )
@@ -993,7 +1878,7 @@ pub mod agent_management_service_server {
                     let max_encoding_message_size = self.max_encoding_message_size;
                     let inner = self.inner.clone();
                     let fut = async move {
-                        let method = RegisterAgentSvc(inner);
+                        let method = StreamAgentEventsSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1004,30 +1889,33 @@ pub mod agent_management_service_server {
                                 max_decoding_message_size,
                                 max_encoding_message_size,
                             );
-                        let res = grpc.unary(method, req).await;
+                        let res = grpc.server_streaming(method, req).await;
                         Ok(res)
                     };
                     Box::pin(fut)
                 }
-                "/agent_management.AgentManagementService/GetAgent" => {
+                "/agent_management.AgentManagementService/GetAgentHealth" => {
                     #[allow(non_camel_case_types)]
-                    struct GetAgentSvc<T: AgentManagementService>(pub Arc<T>);
+                    struct GetAgentHealthSvc<T: AgentManagementService>(pub Arc<T>);
                     impl<
                         T: AgentManagementService,
-                    > tonic::server::UnaryService<super::GetAgentRequest>
-                    for GetAgentSvc<T> {
-                        type Response = super::Agent;
+                    > tonic::server::UnaryService<super::GetAgentHealthRequest>
+                    for GetAgentHealthSvc<T> {
+                        type Response = super::HealthScore;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::GetAgentRequest>,
+                            request: tonic::Request<super::GetAgentHealthRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as AgentManagementService>::get_agent(&inner, request)
+                                <T as AgentManagementService>::get_agent_health(
+                                        &inner,
+                                        request,
+                                    )
                                     .await
                             };
                             Box::pin(fut)
@@ -1039,7 +1927,7 @@ pub mod agent_management_service_server {
                     let max_encoding_message_size = self.max_encoding_message_size;
                     let inner = self.inner.clone();
                     let fut = async move {
-                        let method = GetAgentSvc(inner);
+                        let method = GetAgentHealthSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1055,25 +1943,29 @@ pub mod agent_management_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/agent_management.AgentManagementService/ListAgents" => {
+                "/agent_management.AgentManagementService/StreamAgentHealth" => {
                     #[allow(non_camel_case_types)]
-                    struct ListAgentsSvc<T: AgentManagementService>(pub Arc<T>);
+                    struct StreamAgentHealthSvc<T: AgentManagementService>(pub Arc<T>);
                     impl<
                         T: AgentManagementService,
-                    > tonic::server::UnaryService<super::ListAgentsRequest>
-                    for ListAgentsSvc<T> {
-                        type Response = super::ListAgentsResponse;
+                    > tonic::server::ServerStreamingService<super::StreamHealthRequest>
+                    for StreamAgentHealthSvc<T> {
+                        type Response = super::HealthScore;
+                        type ResponseStream = T::StreamAgentHealthStream;
                         type Future = BoxFuture<
-                            tonic::Response<Self::Response>,
+                            tonic::Response<Self::ResponseStream>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::ListAgentsRequest>,
+                            request: tonic::Request<super::StreamHealthRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as AgentManagementService>::list_agents(&inner, request)
+                                <T as AgentManagementService>::stream_agent_health(
+                                        &inner,
+                                        request,
+                                    )
                                     .await
                             };
                             Box::pin(fut)
@@ -1085,7 +1977,7 @@ pub mod agent_management_service_server {
                     let max_encoding_message_size = self.max_encoding_message_size;
                     let inner = self.inner.clone();
                     let fut = async move {
-                        let method = ListAgentsSvc(inner);
+                        let method = StreamAgentHealthSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1096,30 +1988,35 @@ pub mod agent_management_service_server {
                                 max_decoding_message_size,
                                 max_encoding_message_size,
                             );
-                        let res = grpc.unary(method, req).await;
+                        let res = grpc.server_streaming(method, req).await;
                         Ok(res)
                     };
                     Box::pin(fut)
                 }
-                "/agent_management.AgentManagementService/UpdateAgent" => {
+                "/agent_management.AgentManagementService/GetAgentLifecycleEvents" => {
                     #[allow(non_camel_case_types)]
-                    struct UpdateAgentSvc<T: AgentManagementService>(pub Arc<T>);
+                    struct GetAgentLifecycleEventsSvc<T: AgentManagementService>(
+                        pub Arc<T>,
+                    );
                     impl<
                         T: AgentManagementService,
-                    > tonic::server::UnaryService<super::UpdateAgentRequest>
-                    for UpdateAgentSvc<T> {
-                        type Response = super::Agent;
+                    > tonic::server::UnaryService<super::GetLifecycleRequest>
+                    for GetAgentLifecycleEventsSvc<T> {
+                        type Response = super::LifecycleEventsResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::UpdateAgentRequest>,
+                            request: tonic::Request<super::GetLifecycleRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as AgentManagementService>::update_agent(&inner, request)
+                                <T as AgentManagementService>::get_agent_lifecycle_events(
+                                        &inner,
+                                        request,
+                                    )
                                     .await
                             };
                             Box::pin(fut)
@@ -1131,7 +2028,7 @@ pub mod agent_management_service_server {
                     let max_encoding_message_size = self.max_encoding_message_size;
                     let inner = self.inner.clone();
                     let fut = async move {
-                        let method = UpdateAgentSvc(inner);
+                        let method = GetAgentLifecycleEventsSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1147,25 +2044,78 @@ pub mod agent_management_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/agent_management.AgentManagementService/DeleteAgent" => {
+                "/agent_management.AgentManagementService/StreamLifecycleEvents" => {
                     #[allow(non_camel_case_types)]
-                    struct DeleteAgentSvc<T: AgentManagementService>(pub Arc<T>);
+                    struct StreamLifecycleEventsSvc<T: AgentManagementService>(
+                        pub Arc<T>,
+                    );
                     impl<
                         T: AgentManagementService,
-                    > tonic::server::UnaryService<super::DeleteAgentRequest>
-                    for DeleteAgentSvc<T> {
-                        type Response = super::Empty;
+                    > tonic::server::ServerStreamingService<
+                        super::StreamLifecycleRequest,
+                    > for StreamLifecycleEventsSvc<T> {
+                        type Response = super::AgentEvent;
+                        type ResponseStream = T::StreamLifecycleEventsStream;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::ResponseStream>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::StreamLifecycleRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as AgentManagementService>::stream_lifecycle_events(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = StreamLifecycleEventsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/agent_management.AgentManagementService/SubmitTask" => {
+                    #[allow(non_camel_case_types)]
+                    struct SubmitTaskSvc<T: AgentManagementService>(pub Arc<T>);
+                    impl<
+                        T: AgentManagementService,
+                    > tonic::server::UnaryService<super::SubmitTaskRequest>
+                    for SubmitTaskSvc<T> {
+                        type Response = super::Task;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::DeleteAgentRequest>,
+                            request: tonic::Request<super::SubmitTaskRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as AgentManagementService>::delete_agent(&inner, request)
+                                <T as AgentManagementService>::submit_task(&inner, request)
                                     .await
                             };
                             Box::pin(fut)
@@ -1177,7 +2127,7 @@ pub mod agent_management_service_server {
                     let max_encoding_message_size = self.max_encoding_message_size;
                     let inner = self.inner.clone();
                     let fut = async move {
-                        let method = DeleteAgentSvc(inner);
+                        let method = SubmitTaskSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1193,28 +2143,25 @@ pub mod agent_management_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/agent_management.AgentManagementService/ApproveAgent" => {
+                "/agent_management.AgentManagementService/GetTask" => {
                     #[allow(non_camel_case_types)]
-                    struct ApproveAgentSvc<T: AgentManagementService>(pub Arc<T>);
+                    struct GetTaskSvc<T: AgentManagementService>(pub Arc<T>);
                     impl<
                         T: AgentManagementService,
-                    > tonic::server::UnaryService<super::ApproveRequest>
-                    for ApproveAgentSvc<T> {
-                        type Response = super::Agent;
+                    > tonic::server::UnaryService<super::GetTaskRequest>
+                    for GetTaskSvc<T> {
+                        type Response = super::Task;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::ApproveRequest>,
+                            request: tonic::Request<super::GetTaskRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as AgentManagementService>::approve_agent(
-                                        &inner,
-                                        request,
-                                    )
+                                <T as AgentManagementService>::get_task(&inner, request)
                                     .await
                             };
                             Box::pin(fut)
@@ -1226,7 +2173,7 @@ pub mod agent_management_service_server {
                     let max_encoding_message_size = self.max_encoding_message_size;
                     let inner = self.inner.clone();
                     let fut = async move {
-                        let method = ApproveAgentSvc(inner);
+                        let method = GetTaskSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1242,25 +2189,25 @@ pub mod agent_management_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/agent_management.AgentManagementService/DenyAgent" => {
+                "/agent_management.AgentManagementService/ListTasks" => {
                     #[allow(non_camel_case_types)]
-                    struct DenyAgentSvc<T: AgentManagementService>(pub Arc<T>);
+                    struct ListTasksSvc<T: AgentManagementService>(pub Arc<T>);
                     impl<
                         T: AgentManagementService,
-                    > tonic::server::UnaryService<super::DenyRequest>
-                    for DenyAgentSvc<T> {
-                        type Response = super::Agent;
+                    > tonic::server::UnaryService<super::ListTasksRequest>
+                    for ListTasksSvc<T> {
+                        type Response = super::ListTasksResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::DenyRequest>,
+                            request: tonic::Request<super::ListTasksRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as AgentManagementService>::deny_agent(&inner, request)
+                                <T as AgentManagementService>::list_tasks(&inner, request)
                                     .await
                             };
                             Box::pin(fut)
@@ -1272,7 +2219,7 @@ pub mod agent_management_service_server {
                     let max_encoding_message_size = self.max_encoding_message_size;
                     let inner = self.inner.clone();
                     let fut = async move {
-                        let method = DenyAgentSvc(inner);
+                        let method = ListTasksSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1288,28 +2235,25 @@ pub mod agent_management_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/agent_management.AgentManagementService/GetAgentSystemInfo" => {
+                "/agent_management.AgentManagementService/CancelTask" => {
                     #[allow(non_camel_case_types)]
-                    struct GetAgentSystemInfoSvc<T: AgentManagementService>(pub Arc<T>);
+                    struct CancelTaskSvc<T: AgentManagementService>(pub Arc<T>);
                     impl<
                         T: AgentManagementService,
-                    > tonic::server::UnaryService<super::GetSystemInfoRequest>
-                    for GetAgentSystemInfoSvc<T> {
-                        type Response = super::SystemInfo;
+                    > tonic::server::UnaryService<super::CancelTaskRequest>
+                    for CancelTaskSvc<T> {
+                        type Response = super::Task;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::GetSystemInfoRequest>,
+                            request: tonic::Request<super::CancelTaskRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as AgentManagementService>::get_agent_system_info(
-                                        &inner,
-                                        request,
-                                    )
+                                <T as AgentManagementService>::cancel_task(&inner, request)
                                     .await
                             };
                             Box::pin(fut)
@@ -1321,7 +2265,7 @@ pub mod agent_management_service_server {
                     let max_encoding_message_size = self.max_encoding_message_size;
                     let inner = self.inner.clone();
                     let fut = async move {
-                        let method = GetAgentSystemInfoSvc(inner);
+                        let method = CancelTaskSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1337,26 +2281,25 @@ pub mod agent_management_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/agent_management.AgentManagementService/StreamAgentEvents" => {
+                "/agent_management.AgentManagementService/GetTaskEvents" => {
                     #[allow(non_camel_case_types)]
-                    struct StreamAgentEventsSvc<T: AgentManagementService>(pub Arc<T>);
+                    struct GetTaskEventsSvc<T: AgentManagementService>(pub Arc<T>);
                     impl<
                         T: AgentManagementService,
-                    > tonic::server::ServerStreamingService<super::StreamEventsRequest>
-                    for StreamAgentEventsSvc<T> {
-                        type Response = super::AgentEvent;
-                        type ResponseStream = T::StreamAgentEventsStream;
+                    > tonic::server::UnaryService<super::GetTaskEventsRequest>
+                    for GetTaskEventsSvc<T> {
+                        type Response = super::TaskEventsResponse;
                         type Future = BoxFuture<
-                            tonic::Response<Self::ResponseStream>,
+                            tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::StreamEventsRequest>,
+                            request: tonic::Request<super::GetTaskEventsRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as AgentManagementService>::stream_agent_events(
+                                <T as AgentManagementService>::get_task_events(
                                         &inner,
                                         request,
                                     )
@@ -1371,7 +2314,7 @@ pub mod agent_management_service_server {
                     let max_encoding_message_size = self.max_encoding_message_size;
                     let inner = self.inner.clone();
                     let fut = async move {
-                        let method = StreamAgentEventsSvc(inner);
+                        let method = GetTaskEventsSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1382,30 +2325,30 @@ pub mod agent_management_service_server {
                                 max_decoding_message_size,
                                 max_encoding_message_size,
                             );
-                        let res = grpc.server_streaming(method, req).await;
+                        let res = grpc.unary(method, req).await;
                         Ok(res)
                     };
                     Box::pin(fut)
                 }
-                "/agent_management.AgentManagementService/GetAgentHealth" => {
+                "/agent_management.AgentManagementService/ListAuditEntries" => {
                     #[allow(non_camel_case_types)]
-                    struct GetAgentHealthSvc<T: AgentManagementService>(pub Arc<T>);
+                    struct ListAuditEntriesSvc<T: AgentManagementService>(pub Arc<T>);
                     impl<
                         T: AgentManagementService,
-                    > tonic::server::UnaryService<super::GetAgentHealthRequest>
-                    for GetAgentHealthSvc<T> {
-                        type Response = super::HealthScore;
+                    > tonic::server::UnaryService<super::ListAuditEntriesRequest>
+                    for ListAuditEntriesSvc<T> {
+                        type Response = super::ListAuditEntriesResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::GetAgentHealthRequest>,
+                            request: tonic::Request<super::ListAuditEntriesRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as AgentManagementService>::get_agent_health(
+                                <T as AgentManagementService>::list_audit_entries(
                                         &inner,
                                         request,
                                     )
@@ -1420,7 +2363,7 @@ pub mod agent_management_service_server {
                     let max_encoding_message_size = self.max_encoding_message_size;
                     let inner = self.inner.clone();
                     let fut = async move {
-                        let method = GetAgentHealthSvc(inner);
+                        let method = ListAuditEntriesSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1436,26 +2379,25 @@ pub mod agent_management_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/agent_management.AgentManagementService/StreamAgentHealth" => {
+                "/agent_management.AgentManagementService/CreateIngressRoute" => {
                     #[allow(non_camel_case_types)]
-                    struct StreamAgentHealthSvc<T: AgentManagementService>(pub Arc<T>);
+                    struct CreateIngressRouteSvc<T: AgentManagementService>(pub Arc<T>);
                     impl<
                         T: AgentManagementService,
-                    > tonic::server::ServerStreamingService<super::StreamHealthRequest>
-                    for StreamAgentHealthSvc<T> {
-                        type Response = super::HealthScore;
-                        type ResponseStream = T::StreamAgentHealthStream;
+                    > tonic::server::UnaryService<super::CreateIngressRouteRequest>
+                    for CreateIngressRouteSvc<T> {
+                        type Response = super::IngressRoute;
                         type Future = BoxFuture<
-                            tonic::Response<Self::ResponseStream>,
+                            tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::StreamHealthRequest>,
+                            request: tonic::Request<super::CreateIngressRouteRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as AgentManagementService>::stream_agent_health(
+                                <T as AgentManagementService>::create_ingress_route(
                                         &inner,
                                         request,
                                     )
@@ -1470,7 +2412,7 @@ pub mod agent_management_service_server {
                     let max_encoding_message_size = self.max_encoding_message_size;
                     let inner = self.inner.clone();
                     let fut = async move {
-                        let method = StreamAgentHealthSvc(inner);
+                        let method = CreateIngressRouteSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1481,32 +2423,30 @@ pub mod agent_management_service_server {
                                 max_decoding_message_size,
                                 max_encoding_message_size,
                             );
-                        let res = grpc.server_streaming(method, req).await;
+                        let res = grpc.unary(method, req).await;
                         Ok(res)
                     };
                     Box::pin(fut)
                 }
-                "/agent_management.AgentManagementService/GetAgentLifecycleEvents" => {
+                "/agent_management.AgentManagementService/ListIngressRoutes" => {
                     #[allow(non_camel_case_types)]
-                    struct GetAgentLifecycleEventsSvc<T: AgentManagementService>(
-                        pub Arc<T>,
-                    );
+                    struct ListIngressRoutesSvc<T: AgentManagementService>(pub Arc<T>);
                     impl<
                         T: AgentManagementService,
-                    > tonic::server::UnaryService<super::GetLifecycleRequest>
-                    for GetAgentLifecycleEventsSvc<T> {
-                        type Response = super::LifecycleEventsResponse;
+                    > tonic::server::UnaryService<super::Empty>
+                    for ListIngressRoutesSvc<T> {
+                        type Response = super::ListIngressRoutesResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::GetLifecycleRequest>,
+                            request: tonic::Request<super::Empty>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as AgentManagementService>::get_agent_lifecycle_events(
+                                <T as AgentManagementService>::list_ingress_routes(
                                         &inner,
                                         request,
                                     )
@@ -1521,7 +2461,7 @@ pub mod agent_management_service_server {
                     let max_encoding_message_size = self.max_encoding_message_size;
                     let inner = self.inner.clone();
                     let fut = async move {
-                        let method = GetAgentLifecycleEventsSvc(inner);
+                        let method = ListIngressRoutesSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1537,29 +2477,25 @@ pub mod agent_management_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/agent_management.AgentManagementService/StreamLifecycleEvents" => {
+                "/agent_management.AgentManagementService/DeleteIngressRoute" => {
                     #[allow(non_camel_case_types)]
-                    struct StreamLifecycleEventsSvc<T: AgentManagementService>(
-                        pub Arc<T>,
-                    );
+                    struct DeleteIngressRouteSvc<T: AgentManagementService>(pub Arc<T>);
                     impl<
                         T: AgentManagementService,
-                    > tonic::server::ServerStreamingService<
-                        super::StreamLifecycleRequest,
-                    > for StreamLifecycleEventsSvc<T> {
-                        type Response = super::AgentEvent;
-                        type ResponseStream = T::StreamLifecycleEventsStream;
+                    > tonic::server::UnaryService<super::DeleteIngressRouteRequest>
+                    for DeleteIngressRouteSvc<T> {
+                        type Response = super::Empty;
                         type Future = BoxFuture<
-                            tonic::Response<Self::ResponseStream>,
+                            tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::StreamLifecycleRequest>,
+                            request: tonic::Request<super::DeleteIngressRouteRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as AgentManagementService>::stream_lifecycle_events(
+                                <T as AgentManagementService>::delete_ingress_route(
                                         &inner,
                                         request,
                                     )
@@ -1574,7 +2510,7 @@ pub mod agent_management_service_server {
                     let max_encoding_message_size = self.max_encoding_message_size;
                     let inner = self.inner.clone();
                     let fut = async move {
-                        let method = StreamLifecycleEventsSvc(inner);
+                        let method = DeleteIngressRouteSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1585,7 +2521,7 @@ pub mod agent_management_service_server {
                                 max_decoding_message_size,
                                 max_encoding_message_size,
                             );
-                        let res = grpc.server_streaming(method, req).await;
+                        let res = grpc.unary(method, req).await;
                         Ok(res)
                     };
                     Box::pin(fut)
@@ -9,7 +9,7 @@
 //! - **Health Monitoring**: Network health scoring based on latency, jitter, packet loss, and bandwidth
 //! - **System Diagnostics**: Collect and query system information from agents
 //! - **Approval Workflow**: Pending/Approved/Denied agent registration workflow
-//! - **PostgreSQL Storage**: Persistent storage with SeaORM
+//! - **PostgreSQL or SQLite Storage**: Persistent storage with SeaORM
 //!
 //! ## Architecture
 //!
@@ -18,7 +18,7 @@
 //! - **Transport Layer**: gRPC (tonic), REST (axum), WebSocket (tungstenite)
 //! - **Service Layer**: Business logic services (AgentService, LifecycleService, HealthService, DiagnosticService)
 //! - **Domain Layer**: State machine for agent lifecycle management
-//! - **Storage Layer**: PostgreSQL via SeaORM
+//! - **Storage Layer**: PostgreSQL or SQLite via SeaORM
 //!
 //! ## Usage
 //!
@@ -2,10 +2,10 @@
 
 use tonic::{Request, Response, Status};
 use async_trait::async_trait;
+use std::collections::HashMap;
 use std::pin::Pin;
-use futures_util::Stream;
-use futures_util::stream;
-use chrono::Utc;
+use futures_util::{future, Stream, StreamExt};
+use chrono::{DateTime, Utc};
 use uuid::Uuid;
 use prost::alloc::string::ToString;
 
@@ -20,9 +20,19 @@ pub use crate::generated::agent_management::{
     EnvironmentInfo, ProcessInfo, NetworkInfo, NetworkInterface, NetworkConnection,
     ResourceInfo, CpuInfo, MemoryInfo, DiskInfo, StreamEventsRequest, AgentEvent,
     GetAgentHealthRequest, HealthScore, StreamHealthRequest, GetLifecycleRequest,
-    LifecycleEventsResponse, StreamLifecycleRequest,
+    LifecycleEventsResponse, StreamLifecycleRequest, Task, SubmitTaskRequest,
+    GetTaskRequest, ListTasksRequest, ListTasksResponse, CancelTaskRequest,
+    GetTaskEventsRequest, TaskEventsResponse, AuditEntry, ListAuditEntriesRequest,
+    ListAuditEntriesResponse, IngressRoute, CreateIngressRouteRequest, ListIngressRoutesResponse,
+    DeleteIngressRouteRequest,
 };
 
+use crate::domain::rbac::Permission;
+use crate::service::audit::{AuditAction, AuditContext, AuditEntryInfo, AuditFilter, AuditSource, NewAuditEntry};
+use crate::service::auth::{parse_bearer, AuthError, UserInfo};
+use crate::service::events::{BusEvent, EventFilter, HEALTH_SCORE_EVENT};
+use crate::service::ingress::{CreateIngressRouteInput, IngressError, IngressRouteInfo};
+use crate::service::task::{SubmitTaskInput, TaskInfo};
 use crate::service::Service;
 
 /// gRPC server for agent management service
@@ -36,6 +46,45 @@ impl GrpcServer {
     pub fn new(service: Service) -> Self {
         Self { service }
     }
+
+    /// Authenticates the `authorization: Bearer <token>` metadata of a request
+    /// and checks that the user's role grants the permission.
+    ///
+    /// Tokens are obtained from the REST login endpoint.
+    async fn authorize<T>(&self, request: &Request<T>, permission: Permission) -> Result<UserInfo, Status> {
+        let token = request.metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_bearer)
+            .ok_or_else(|| Status::unauthenticated("Missing bearer token"))?;
+
+        let user = self.service.auth_service.authenticate(token)
+            .await
+            .map_err(|e| match e {
+                AuthError::InvalidToken => Status::unauthenticated(e.to_string()),
+                e => Status::internal(format!("Failed to authenticate: {}", e)),
+            })?;
+
+        if !user.role.allows(permission) {
+            tracing::warn!("User {} ({}) denied {}", user.username, user.role, permission);
+            return Err(Status::permission_denied(format!(
+                "Permission denied: {} requires the {} role",
+                permission,
+                permission.required_role()
+            )));
+        }
+        Ok(user)
+    }
+}
+
+/// Audit context of a request sent by an authorized user
+fn audit_context<T>(request: &Request<T>, user: &UserInfo) -> AuditContext {
+    AuditContext {
+        actor: user.username.clone(),
+        actor_role: Some(user.role),
+        source: AuditSource::Grpc,
+        remote_addr: request.remote_addr().map(|addr| addr.to_string()),
+    }
 }
 
 impl Default for GrpcServer {
@@ -92,6 +141,7 @@ impl AgentManagementService for GrpcServer {
         &self,
         request: Request<GetAgentRequest>,
     ) -> Result<Response<Agent>, Status> {
+        self.authorize(&request, Permission::ViewAgents).await?;
         let req = request.into_inner();
 
         let agent_id = Uuid::parse_str(&req.agent_id)
@@ -111,6 +161,7 @@ impl AgentManagementService for GrpcServer {
         &self,
         request: Request<ListAgentsRequest>,
     ) -> Result<Response<ListAgentsResponse>, Status> {
+        self.authorize(&request, Permission::ViewAgents).await?;
         let req = request.into_inner();
 
         let filters = crate::service::agent::AgentFilters {
@@ -134,6 +185,8 @@ impl AgentManagementService for GrpcServer {
         &self,
         request: Request<UpdateAgentRequest>,
     ) -> Result<Response<Agent>, Status> {
+        let user = self.authorize(&request, Permission::UpdateAgent).await?;
+        let context = audit_context(&request, &user);
         let req = request.into_inner();
 
         let agent_id = Uuid::parse_str(&req.agent_id)
@@ -152,8 +205,14 @@ impl AgentManagementService for GrpcServer {
             last_seen_at: None,
         };
 
-        let agent_info = self.service.agent_service.update_agent(agent_id, input)
-            .await
+        let before = self.service.agent_service.get_agent(agent_id).await.ok().flatten();
+        let result = self.service.agent_service.update_agent(agent_id, input).await;
+        self.service.audit_service.record(
+            NewAuditEntry::new(context, AuditAction::UpdateAgent, agent_id)
+                .with_before(&before)
+                .with_result(&result, "Agent not found")
+        ).await;
+        let agent_info = result
             .map_err(|e| Status::internal(format!("Failed to update agent: {}", e)))?;
 
         match agent_info {
@@ -166,14 +225,22 @@ impl AgentManagementService for GrpcServer {
         &self,
         request: Request<DeleteAgentRequest>,
     ) -> Result<Response<Empty>, Status> {
+        let user = self.authorize(&request, Permission::DeleteAgent).await?;
+        let context = audit_context(&request, &user);
         let req = request.into_inner();
 
         let agent_id = Uuid::parse_str(&req.agent_id)
             .map_err(|_| Status::invalid_argument("Invalid agent_id format"))?;
 
-        self.service.agent_service.delete_agent(agent_id)
-            .await
-            .map_err(|e| Status::internal(format!("Failed to delete agent: {}", e)))?;
+        let before = self.service.agent_service.get_agent(agent_id).await.ok().flatten();
+        let result = self.service.agent_service.delete_agent(agent_id).await;
+        let entry = NewAuditEntry::new(context, AuditAction::DeleteAgent, agent_id).with_before(&before);
+        self.service.audit_service.record(match &result {
+            Ok(true) => entry,
+            Ok(false) => entry.with_error("Agent not found"),
+            Err(e) => entry.with_error(e),
+        }).await;
+        result.map_err(|e| Status::internal(format!("Failed to delete agent: {}", e)))?;
 
         Ok(Response::new(Empty {}))
     }
@@ -184,16 +251,21 @@ impl AgentManagementService for GrpcServer {
         &self,
         request: Request<ApproveRequest>,
     ) -> Result<Response<Agent>, Status> {
+        let user = self.authorize(&request, Permission::ApproveAgent).await?;
+        let context = audit_context(&request, &user);
         let req = request.into_inner();
 
         let agent_id = Uuid::parse_str(&req.agent_id)
             .map_err(|_| Status::invalid_argument("Invalid agent_id format"))?;
 
-        let approved_by = req.approved_by.as_ref().map(|s| s.to_string()).unwrap_or_default();
-
-        self.service.agent_service.approve_agent(agent_id, approved_by)
-            .await
-            .map_err(|e| Status::internal(format!("Failed to approve agent: {}", e)))?;
+        let before = self.service.agent_service.get_agent(agent_id).await.ok().flatten();
+        let result = self.service.agent_service.approve_agent(agent_id, user.username).await;
+        self.service.audit_service.record(
+            NewAuditEntry::new(context, AuditAction::ApproveAgent, agent_id)
+                .with_before(&before)
+                .with_result(&result, "Agent not found")
+        ).await;
+        result.map_err(|e| Status::internal(format!("Failed to approve agent: {}", e)))?;
 
         // Get updated agent
         let agent_info = self.service.agent_service.get_agent(agent_id)
@@ -210,16 +282,21 @@ impl AgentManagementService for GrpcServer {
         &self,
         request: Request<DenyRequest>,
     ) -> Result<Response<Agent>, Status> {
+        let user = self.authorize(&request, Permission::ApproveAgent).await?;
+        let context = audit_context(&request, &user);
         let req = request.into_inner();
 
         let agent_id = Uuid::parse_str(&req.agent_id)
             .map_err(|_| Status::invalid_argument("Invalid agent_id format"))?;
 
-        let denied_by = req.denied_by.as_ref().map(|s| s.to_string()).unwrap_or_default();
-
-        self.service.agent_service.deny_agent(agent_id, req.reason.to_string(), denied_by)
-            .await
-            .map_err(|e| Status::internal(format!("Failed to deny agent: {}", e)))?;
+        let before = self.service.agent_service.get_agent(agent_id).await.ok().flatten();
+        let result = self.service.agent_service.deny_agent(agent_id, req.reason.to_string(), user.username).await;
+        self.service.audit_service.record(
+            NewAuditEntry::new(context, AuditAction::DenyAgent, agent_id)
+                .with_before(&before)
+                .with_result(&result, "Agent not found")
+        ).await;
+        result.map_err(|e| Status::internal(format!("Failed to deny agent: {}", e)))?;
 
         // Get updated agent
         let agent_info = self.service.agent_service.get_agent(agent_id)
@@ -238,13 +315,21 @@ impl AgentManagementService for GrpcServer {
         &self,
         request: Request<GetSystemInfoRequest>,
     ) -> Result<Response<SystemInfo>, Status> {
+        let user = self.authorize(&request, Permission::ViewAgents).await?;
+        let context = audit_context(&request, &user);
         let req = request.into_inner();
 
         let agent_id = Uuid::parse_str(&req.agent_id)
             .map_err(|_| Status::invalid_argument("Invalid agent_id format"))?;
 
-        let system_info = self.service.diagnostic_service.get_system_info(agent_id)
-            .await
+        let result = self.service.diagnostic_service.get_system_info(agent_id).await;
+        let entry = NewAuditEntry::new(context, AuditAction::GetSystemInfo, agent_id);
+        self.service.audit_service.record(match &result {
+            Ok(Some(_)) => entry,
+            Ok(None) => entry.with_error("System info not found"),
+            Err(e) => entry.with_error(e),
+        }).await;
+        let system_info = result
             .map_err(|e| Status::internal(format!("Failed to get system info: {}", e)))?;
 
         match system_info {
@@ -257,10 +342,18 @@ impl AgentManagementService for GrpcServer {
 
     async fn stream_agent_events(
         &self,
-        _request: Request<StreamEventsRequest>,
+        request: Request<StreamEventsRequest>,
     ) -> Result<Response<Self::StreamAgentEventsStream>, Status> {
-        // Return empty stream for now - can be enhanced later with actual event streaming
-        let output_stream = stream::empty();
+        self.authorize(&request, Permission::ViewAgents).await?;
+        let req = request.into_inner();
+
+        let agent_id = parse_agent_filter(&req.agent_id)
+            .map_err(|_| Status::invalid_argument("Invalid agent_id format"))?;
+        let filter = EventFilter::new(agent_id).with_event_types(req.event_types);
+
+        let output_stream = self.service.events.subscribe(filter)
+            .map(|event| bus_event_to_proto(&event))
+            .map(Ok);
         Ok(Response::new(Box::pin(output_stream)))
     }
 
@@ -270,6 +363,7 @@ impl AgentManagementService for GrpcServer {
         &self,
         request: Request<GetAgentHealthRequest>,
     ) -> Result<Response<HealthScore>, Status> {
+        self.authorize(&request, Permission::ViewAgents).await?;
         let req = request.into_inner();
 
         let agent_id = Uuid::parse_str(&req.agent_id)
@@ -287,10 +381,30 @@ impl AgentManagementService for GrpcServer {
 
     async fn stream_agent_health(
         &self,
-        _request: Request<StreamHealthRequest>,
+        request: Request<StreamHealthRequest>,
     ) -> Result<Response<Self::StreamAgentHealthStream>, Status> {
-        // Return empty stream for now - can be enhanced later with actual health streaming
-        let output_stream = stream::empty();
+        self.authorize(&request, Permission::ViewAgents).await?;
+        let req = request.into_inner();
+
+        let agent_id = parse_agent_filter(&req.agent_id)
+            .map_err(|_| Status::invalid_argument("Invalid agent_id format"))?;
+        let filter = EventFilter::new(agent_id).with_event_types([HEALTH_SCORE_EVENT.to_string()]);
+        let interval = chrono::Duration::seconds(req.update_interval_seconds.max(0) as i64);
+
+        // Time of the last score sent for each agent
+        let mut last_sent: HashMap<Uuid, DateTime<Utc>> = HashMap::new();
+        let output_stream = self.service.events.subscribe(filter)
+            .filter_map(move |event| {
+                let score = match event {
+                    BusEvent::Health(score) => score,
+                    _ => return future::ready(None),
+                };
+                if matches!(last_sent.get(&score.agent_id), Some(sent) if score.scored_at - *sent < interval) {
+                    return future::ready(None);
+                }
+                last_sent.insert(score.agent_id, score.scored_at);
+                future::ready(Some(Ok(health_score_to_proto(&score))))
+            });
         Ok(Response::new(Box::pin(output_stream)))
     }
 
@@ -300,6 +414,7 @@ impl AgentManagementService for GrpcServer {
         &self,
         request: Request<GetLifecycleRequest>,
     ) -> Result<Response<LifecycleEventsResponse>, Status> {
+        self.authorize(&request, Permission::ViewAgents).await?;
         let req = request.into_inner();
 
         let agent_id = Uuid::parse_str(&req.agent_id)
@@ -318,12 +433,256 @@ impl AgentManagementService for GrpcServer {
 
     async fn stream_lifecycle_events(
         &self,
-        _request: Request<StreamLifecycleRequest>,
+        request: Request<StreamLifecycleRequest>,
     ) -> Result<Response<Self::StreamLifecycleEventsStream>, Status> {
-        // Return empty stream for now - can be enhanced later with actual lifecycle streaming
-        let output_stream = stream::empty();
+        self.authorize(&request, Permission::ViewAgents).await?;
+        let req = request.into_inner();
+
+        let agent_id = parse_agent_filter(&req.agent_id)
+            .map_err(|_| Status::invalid_argument("Invalid agent_id format"))?;
+        let filter = EventFilter::new(agent_id).with_event_types(req.event_types);
+
+        let output_stream = self.service.events.subscribe(filter)
+            .filter_map(|event| future::ready(match event {
+                BusEvent::Lifecycle(event) => Some(Ok(lifecycle_event_to_proto(&event))),
+                _ => None,
+            }));
         Ok(Response::new(Box::pin(output_stream)))
     }
+
+    // Remote tasks
+
+    async fn submit_task(
+        &self,
+        request: Request<SubmitTaskRequest>,
+    ) -> Result<Response<Task>, Status> {
+        let user = self.authorize(&request, Permission::SubmitTask).await?;
+        let context = audit_context(&request, &user);
+        let req = request.into_inner();
+
+        let agent_id = Uuid::parse_str(&req.agent_id)
+            .map_err(|_| Status::invalid_argument("Invalid agent_id format"))?;
+        let task = serde_json::from_str(&req.task_json)
+            .map_err(|e| Status::invalid_argument(format!("Invalid task_json: {}", e)))?;
+
+        self.service.agent_service.get_agent(agent_id)
+            .await
+            .map_err(|e| Status::internal(format!("Failed to get agent: {}", e)))?
+            .ok_or_else(|| Status::not_found("Agent not found"))?;
+
+        let input = SubmitTaskInput {
+            agent_id,
+            task,
+            timeout_seconds: req.timeout_seconds,
+            submitted_by: Some(user.username),
+        };
+        let result = self.service.task_service.submit_task(input).await;
+        let entry = NewAuditEntry::new(context, AuditAction::SubmitTask, agent_id);
+        self.service.audit_service.record(match &result {
+            Ok(task) => entry.with_after(&task.audit_state()),
+            Err(e) => entry.with_error(e),
+        }).await;
+        let task = result
+            .map_err(|e| Status::internal(format!("Failed to submit task: {}", e)))?;
+
+        Ok(Response::new(task_to_proto(&task)))
+    }
+
+    async fn get_task(
+        &self,
+        request: Request<GetTaskRequest>,
+    ) -> Result<Response<Task>, Status> {
+        self.authorize(&request, Permission::ViewTasks).await?;
+        let req = request.into_inner();
+
+        let task_id = Uuid::parse_str(&req.task_id)
+            .map_err(|_| Status::invalid_argument("Invalid task_id format"))?;
+
+        let task = self.service.task_service.get_task(task_id)
+            .await
+            .map_err(|e| Status::internal(format!("Failed to get task: {}", e)))?
+            .ok_or_else(|| Status::not_found("Task not found"))?;
+
+        Ok(Response::new(task_to_proto(&task)))
+    }
+
+    async fn list_tasks(
+        &self,
+        request: Request<ListTasksRequest>,
+    ) -> Result<Response<ListTasksResponse>, Status> {
+        self.authorize(&request, Permission::ViewTasks).await?;
+        let req = request.into_inner();
+
+        let agent_id = Uuid::parse_str(&req.agent_id)
+            .map_err(|_| Status::invalid_argument("Invalid agent_id format"))?;
+        let limit = if req.limit > 0 { req.limit.min(500) as u64 } else { 50 };
+
+        let tasks = self.service.task_service.list_tasks(agent_id, limit)
+            .await
+            .map_err(|e| Status::internal(format!("Failed to list tasks: {}", e)))?;
+
+        Ok(Response::new(ListTasksResponse {
+            tasks: tasks.iter().map(task_to_proto).collect(),
+        }))
+    }
+
+    async fn cancel_task(
+        &self,
+        request: Request<CancelTaskRequest>,
+    ) -> Result<Response<Task>, Status> {
+        let user = self.authorize(&request, Permission::CancelTask).await?;
+        let context = audit_context(&request, &user);
+        let req = request.into_inner();
+
+        let task_id = Uuid::parse_str(&req.task_id)
+            .map_err(|_| Status::invalid_argument("Invalid task_id format"))?;
+        let reason = req.reason.unwrap_or_else(|| "Cancelled via gRPC".to_string());
+
+        let before = self.service.task_service.get_task(task_id).await.ok().flatten().map(|task| task.audit_state());
+        let result = self.service.task_service.cancel_task(task_id, reason, Some(user.username)).await;
+        let entry = NewAuditEntry::new(context, AuditAction::CancelTask, task_id).with_before(&before);
+        self.service.audit_service.record(match &result {
+            Ok(Some(task)) => entry.with_after(&task.audit_state()),
+            Ok(None) => entry.with_error("Task not found"),
+            Err(e) => entry.with_error(e),
+        }).await;
+        let task = result
+            .map_err(|e| Status::internal(format!("Failed to cancel task: {}", e)))?
+            .ok_or_else(|| Status::not_found("Task not found"))?;
+
+        Ok(Response::new(task_to_proto(&task)))
+    }
+
+    async fn get_task_events(
+        &self,
+        request: Request<GetTaskEventsRequest>,
+    ) -> Result<Response<TaskEventsResponse>, Status> {
+        self.authorize(&request, Permission::ViewTasks).await?;
+        let req = request.into_inner();
+
+        let task_id = Uuid::parse_str(&req.task_id)
+            .map_err(|_| Status::invalid_argument("Invalid task_id format"))?;
+
+        let events = self.service.task_service.get_events(task_id)
+            .await
+            .map_err(|e| Status::internal(format!("Failed to get task events: {}", e)))?;
+
+        Ok(Response::new(TaskEventsResponse {
+            events: events.iter().map(task_event_to_proto).collect(),
+        }))
+    }
+
+    // Audit log
+
+    async fn list_audit_entries(
+        &self,
+        request: Request<ListAuditEntriesRequest>,
+    ) -> Result<Response<ListAuditEntriesResponse>, Status> {
+        self.authorize(&request, Permission::ViewAudit).await?;
+        let req = request.into_inner();
+
+        // Some(None) when unset, None when out of range
+        let timestamp = |seconds: Option<i64>| match seconds {
+            Some(seconds) => DateTime::from_timestamp(seconds, 0).map(Some),
+            None => Some(None),
+        };
+        let (Some(since), Some(until)) = (timestamp(req.since), timestamp(req.until)) else {
+            return Err(Status::invalid_argument("Invalid since or until timestamp"));
+        };
+        let filter = AuditFilter {
+            actor: req.actor,
+            action: req.action,
+            target_type: req.target_type,
+            target_id: req.target_id,
+            source: req.source,
+            since,
+            until,
+        };
+        let limit = if req.limit > 0 { req.limit as u64 } else { 50 };
+
+        let page = self.service.audit_service.query(&filter, limit, req.offset)
+            .await
+            .map_err(|e| Status::internal(format!("Failed to query audit log: {}", e)))?;
+
+        Ok(Response::new(ListAuditEntriesResponse {
+            entries: page.entries.iter().map(audit_entry_to_proto).collect(),
+            total: page.total,
+        }))
+    }
+
+    // Ingress routes
+
+    async fn create_ingress_route(
+        &self,
+        request: Request<CreateIngressRouteRequest>,
+    ) -> Result<Response<IngressRoute>, Status> {
+        let user = self.authorize(&request, Permission::ManageIngress).await?;
+        let context = audit_context(&request, &user);
+        let req = request.into_inner();
+
+        let agent_id = Uuid::parse_str(&req.agent_id)
+            .map_err(|_| Status::invalid_argument("Invalid agent_id format"))?;
+        let input = CreateIngressRouteInput {
+            hostname: req.hostname.clone(),
+            agent_id,
+            target: req.target,
+            tls: req.tls,
+            created_by: Some(user.username),
+        };
+        let result = self.service.ingress_service.create_route(input).await;
+        self.service.audit_service.record(match &result {
+            Ok(route) => NewAuditEntry::new(context, AuditAction::CreateIngressRoute, route.id).with_after(route),
+            Err(e) => NewAuditEntry::new(context, AuditAction::CreateIngressRoute, &req.hostname).with_error(e),
+        }).await;
+        let route = result.map_err(|e| match e {
+            IngressError::InvalidHostname(_) | IngressError::InvalidTarget(_) => Status::invalid_argument(e.to_string()),
+            IngressError::HostnameTaken(_) => Status::already_exists(e.to_string()),
+            IngressError::AgentNotFound(_) => Status::not_found(e.to_string()),
+            IngressError::Database(e) => Status::internal(format!("Failed to create ingress route: {}", e)),
+        })?;
+
+        Ok(Response::new(ingress_route_to_proto(&route)))
+    }
+
+    async fn list_ingress_routes(
+        &self,
+        request: Request<Empty>,
+    ) -> Result<Response<ListIngressRoutesResponse>, Status> {
+        self.authorize(&request, Permission::ViewAgents).await?;
+
+        let routes = self.service.ingress_service.list_routes()
+            .await
+            .map_err(|e| Status::internal(format!("Failed to list ingress routes: {}", e)))?;
+
+        Ok(Response::new(ListIngressRoutesResponse {
+            routes: routes.iter().map(ingress_route_to_proto).collect(),
+        }))
+    }
+
+    async fn delete_ingress_route(
+        &self,
+        request: Request<DeleteIngressRouteRequest>,
+    ) -> Result<Response<Empty>, Status> {
+        let user = self.authorize(&request, Permission::ManageIngress).await?;
+        let context = audit_context(&request, &user);
+        let req = request.into_inner();
+
+        let route_id = Uuid::parse_str(&req.route_id)
+            .map_err(|_| Status::invalid_argument("Invalid route_id format"))?;
+
+        let result = self.service.ingress_service.delete_route(route_id).await;
+        let entry = NewAuditEntry::new(context, AuditAction::DeleteIngressRoute, route_id);
+        self.service.audit_service.record(match &result {
+            Ok(Some(route)) => entry.with_before(route),
+            Ok(None) => entry.with_error("Ingress route not found"),
+            Err(e) => entry.with_error(e),
+        }).await;
+        result
+            .map_err(|e| Status::internal(format!("Failed to delete ingress route: {}", e)))?
+            .ok_or_else(|| Status::not_found("Ingress route not found"))?;
+
+        Ok(Response::new(Empty {}))
+    }
 }
 
 /// Create and configure the gRPC server
@@ -472,6 +831,30 @@ fn health_score_to_proto(model: &crate::storage::entities::health_score::Model)
     }
 }
 
+/// Agent filter of a streaming request, an empty ID selects all agents
+fn parse_agent_filter(agent_id: &str) -> Result<Option<Uuid>, uuid::Error> {
+    if agent_id.is_empty() {
+        return Ok(None);
+    }
+    Uuid::parse_str(agent_id).map(Some)
+}
+
+/// Converts a bus event, the payload is the event as JSON
+fn bus_event_to_proto(event: &BusEvent) -> AgentEvent {
+    let payload = match event {
+        BusEvent::Lifecycle(event) => serde_json::to_string(event),
+        BusEvent::Health(score) => serde_json::to_string(score),
+        BusEvent::Heartbeat(heartbeat) => serde_json::to_string(heartbeat),
+    };
+    AgentEvent {
+        event_id: event.event_id().to_string(),
+        agent_id: event.agent_id().to_string(),
+        event_type: event.event_type(),
+        payload: payload.unwrap_or_default(),
+        timestamp: event.timestamp().timestamp(),
+    }
+}
+
 fn lifecycle_event_to_proto(event: &domain_agent_protocol::lifecycle::LifecycleEvent) -> AgentEvent {
     AgentEvent {
         event_id: event.event_id.to_string(),
@@ -482,6 +865,72 @@ fn lifecycle_event_to_proto(event: &domain_agent_protocol::lifecycle::LifecycleE
     }
 }
 
+fn task_to_proto(task: &TaskInfo) -> Task {
+    Task {
+        id: task.id.to_string(),
+        agent_id: task.agent_id.to_string(),
+        task_type: task.task.kind().to_string(),
+        task_json: serde_json::to_string(&task.task).unwrap_or_default(),
+        status: task.status.to_string(),
+        timeout_seconds: task.timeout_seconds,
+        output: task.output.clone(),
+        error: task.error.clone(),
+        exit_code: task.exit_code,
+        duration_ms: task.duration_ms,
+        submitted_by: task.submitted_by.clone(),
+        created_at: task.created_at.timestamp(),
+        dispatched_at: task.dispatched_at.map(|t| t.timestamp()),
+        finished_at: task.finished_at.map(|t| t.timestamp()),
+    }
+}
+
+fn task_event_to_proto(event: &domain_agent_protocol::task::TaskEvent) -> AgentEvent {
+    let payload = serde_json::json!({
+        "task_id": event.task_id,
+        "reason": event.reason,
+        "metadata": event.metadata,
+        "triggered_by": event.triggered_by,
+    });
+    AgentEvent {
+        event_id: event.event_id.to_string(),
+        agent_id: event.agent_id.to_string(),
+        event_type: event.event_type.to_string(),
+        payload: payload.to_string(),
+        timestamp: event.timestamp.timestamp(),
+    }
+}
+
+fn audit_entry_to_proto(entry: &AuditEntryInfo) -> AuditEntry {
+    AuditEntry {
+        id: entry.id.to_string(),
+        timestamp: entry.timestamp.timestamp(),
+        actor: entry.actor.clone(),
+        actor_role: entry.actor_role.clone(),
+        action: entry.action.clone(),
+        target_type: entry.target_type.clone(),
+        target_id: entry.target_id.clone(),
+        source: entry.source.clone(),
+        remote_addr: entry.remote_addr.clone(),
+        success: entry.success,
+        error: entry.error.clone(),
+        before_json: entry.before.as_ref().map(|v| v.to_string()),
+        after_json: entry.after.as_ref().map(|v| v.to_string()),
+        changes_json: entry.changes.as_ref().map(|v| v.to_string()),
+    }
+}
+
+fn ingress_route_to_proto(route: &IngressRouteInfo) -> IngressRoute {
+    IngressRoute {
+        id: route.id.to_string(),
+        hostname: route.hostname.clone(),
+        agent_id: route.agent_id.to_string(),
+        target: route.target.clone(),
+        tls: route.tls,
+        created_by: route.created_by.clone(),
+        created_at: route.created_at.timestamp(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     // Tests require a real Service instance, so we skip inline testing here.
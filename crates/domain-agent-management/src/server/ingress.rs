@@ -0,0 +1,329 @@
+//! Hostname-based HTTP(S) ingress
+//!
+//! The ingress accepts public connections and routes them to an agent tunnel
+//! by hostname:
+//!
+//! - the HTTP listener reads the request head and routes by its `Host`
+//!   header, the whole connection then goes to the route of the first
+//!   request
+//! - the HTTPS listener reads the TLS ClientHello and routes by its server
+//!   name (SNI) without terminating TLS, the target holds the certificate
+//!
+//! The bytes read for routing are replayed to the target before the rest of
+//! the connection is bridged to a stream on the agent's multiplexer.
+
+use std::io::Cursor;
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{debug, error, info};
+
+use crate::config::IngressConfig;
+use crate::server::accept_with_backoff;
+use crate::service::{IngressRouteInfo, IngressService};
+
+/// Maximum size of an HTTP request head or TLS ClientHello read for routing
+const MAX_PEEK: usize = 16 * 1024;
+
+/// How long a client may take to send what is needed for routing
+const PEEK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Protocol of an ingress listener
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Listener {
+    Http,
+    Https,
+}
+
+/// Reasons the hostname of a connection could not be read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PeekError {
+    /// More data is needed
+    Incomplete,
+    /// Not an HTTP request or TLS ClientHello
+    Invalid,
+    /// Well-formed, but without a hostname
+    MissingHost,
+}
+
+/// Runs the configured ingress listeners as background tasks
+pub async fn run_ingress(service: IngressService, config: IngressConfig) {
+    let listeners = [
+        (Listener::Http, config.http_port),
+        (Listener::Https, config.https_port),
+    ];
+    for (kind, port) in listeners {
+        if port == 0 {
+            continue;
+        }
+        let addr = format!("{}:{}", config.bind_host, port);
+        match TcpListener::bind(&addr).await {
+            Ok(listener) => {
+                info!("Ingress {:?} listener on {}", kind, addr);
+                tokio::spawn(accept_connections(listener, kind, service.clone()));
+            }
+            Err(e) => error!(
+                "Failed to bind ingress {:?} listener on {}: {}",
+                kind, addr, e
+            ),
+        }
+    }
+}
+
+/// Serves plain HTTP requests routed by Host header on `listener`
+pub async fn serve_http(listener: TcpListener, service: IngressService) {
+    accept_connections(listener, Listener::Http, service).await
+}
+
+/// Serves TLS connections routed by SNI on `listener`
+pub async fn serve_https(listener: TcpListener, service: IngressService) {
+    accept_connections(listener, Listener::Https, service).await
+}
+
+async fn accept_connections(listener: TcpListener, kind: Listener, service: IngressService) {
+    loop {
+        let (connection, peer_addr) = accept_with_backoff(&listener, "Ingress").await;
+        let service = service.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(connection, kind, &service).await {
+                debug!("Ingress connection from {} failed: {}", peer_addr, e);
+            }
+        });
+    }
+}
+
+async fn handle_connection(
+    mut connection: TcpStream,
+    kind: Listener,
+    service: &IngressService,
+) -> std::io::Result<()> {
+    let peek = match kind {
+        Listener::Http => http_host,
+        Listener::Https => tls_server_name,
+    };
+    let (head, host) =
+        match tokio::time::timeout(PEEK_TIMEOUT, read_host(&mut connection, peek)).await {
+            Ok(result) => result?,
+            Err(_) => return reject(connection, kind, 408, "Request Timeout").await,
+        };
+    let host = match host {
+        Ok(host) => host,
+        Err(e) => {
+            debug!("Ingress {:?} connection without a hostname: {:?}", kind, e);
+            return reject(connection, kind, 400, "Bad Request").await;
+        }
+    };
+
+    let route = service
+        .resolve(&host)
+        .await
+        .filter(|route| route.tls == (kind == Listener::Https));
+    let Some(route) = route else {
+        debug!("No ingress route for {}", host);
+        return reject(connection, kind, 404, "Not Found").await;
+    };
+    let Some(stream) = service.open_stream(&route).await else {
+        debug!(
+            "Agent {} of {} is not connected",
+            route.agent_id, route.hostname
+        );
+        return reject(connection, kind, 502, "Bad Gateway").await;
+    };
+    debug!(
+        "Ingress {} -> agent {} {} on stream {}",
+        route.hostname,
+        route.agent_id,
+        route.target,
+        stream.id()
+    );
+    bridge(connection, head, stream, &route).await
+}
+
+/// Replays the bytes read for routing, then bridges the connection to the stream
+async fn bridge(
+    connection: TcpStream,
+    head: Vec<u8>,
+    stream: domain_agent_protocol::mux::MuxStream,
+    route: &IngressRouteInfo,
+) -> std::io::Result<()> {
+    let (reader, writer) = connection.into_split();
+    let io = tokio::io::join(Cursor::new(head).chain(reader), writer);
+    stream.bridge(io).await.inspect_err(|e| {
+        debug!("Ingress connection to {} aborted: {}", route.hostname, e);
+    })
+}
+
+/// Reads until `peek` finds the hostname or fails, returns the bytes read
+async fn read_host(
+    connection: &mut TcpStream,
+    peek: fn(&[u8]) -> Result<String, PeekError>,
+) -> std::io::Result<(Vec<u8>, Result<String, PeekError>)> {
+    let mut head = Vec::with_capacity(4096);
+    let mut buf = [0u8; 4096];
+    loop {
+        let n = connection.read(&mut buf).await?;
+        if n == 0 {
+            return Ok((head, Err(PeekError::Incomplete)));
+        }
+        head.extend_from_slice(&buf[..n]);
+        match peek(&head) {
+            Err(PeekError::Incomplete) if head.len() < MAX_PEEK => continue,
+            result => return Ok((head, result)),
+        }
+    }
+}
+
+/// Answers an HTTP connection with an error page, TLS connections are closed
+async fn reject(
+    mut connection: TcpStream,
+    kind: Listener,
+    status: u16,
+    reason: &str,
+) -> std::io::Result<()> {
+    if kind == Listener::Http {
+        let body = format!("{} {}\n", status, reason);
+        let response = format!(
+            "HTTP/1.1 {} {}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            reason,
+            body.len(),
+            body
+        );
+        connection.write_all(response.as_bytes()).await?;
+    }
+    connection.shutdown().await
+}
+
+/// Host of the first request of an HTTP/1.x connection, without the port
+pub fn http_host(data: &[u8]) -> Result<String, PeekError> {
+    let end = data
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .ok_or(PeekError::Incomplete)?;
+    let head = std::str::from_utf8(&data[..end]).map_err(|_| PeekError::Invalid)?;
+    let mut lines = head.split("\r\n");
+    let request_line = lines.next().unwrap_or_default();
+    if !request_line.contains(" HTTP/1.") {
+        return Err(PeekError::Invalid);
+    }
+    let host = lines
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("host"))
+        .map(|(_, value)| value.trim())
+        .ok_or(PeekError::MissingHost)?;
+    strip_port(host)
+        .filter(|host| !host.is_empty())
+        .map(str::to_string)
+        .ok_or(PeekError::Invalid)
+}
+
+/// Removes the port of a Host header value, IPv6 addresses keep their brackets
+fn strip_port(host: &str) -> Option<&str> {
+    if host.starts_with('[') {
+        let end = host.find(']')?;
+        return Some(&host[..=end]);
+    }
+    Some(host.split(':').next().unwrap_or(host))
+}
+
+/// Server name of a TLS ClientHello, which may span several records
+pub fn tls_server_name(data: &[u8]) -> Result<String, PeekError> {
+    const CONTENT_HANDSHAKE: u8 = 22;
+    const HANDSHAKE_CLIENT_HELLO: u8 = 1;
+
+    // Collects the handshake messages of the records read so far
+    let mut handshake = Vec::new();
+    let mut records = data;
+    let client_hello = loop {
+        if handshake.len() >= 4 {
+            let len = u24(&handshake[1..4]);
+            if handshake[0] != HANDSHAKE_CLIENT_HELLO {
+                return Err(PeekError::Invalid);
+            }
+            if handshake.len() >= 4 + len {
+                break &handshake[4..4 + len];
+            }
+        }
+        let Some(header) = records.get(..5) else {
+            return Err(PeekError::Incomplete);
+        };
+        if header[0] != CONTENT_HANDSHAKE || header[1] != 3 {
+            return Err(PeekError::Invalid);
+        }
+        let len = u16::from_be_bytes([header[3], header[4]]) as usize;
+        let Some(fragment) = records.get(5..5 + len) else {
+            return Err(PeekError::Incomplete);
+        };
+        handshake.extend_from_slice(fragment);
+        records = &records[5 + len..];
+    };
+
+    let mut reader = Reader(client_hello);
+    // Version and random
+    reader.skip(2 + 32)?;
+    let session_id = reader.u8()? as usize;
+    reader.skip(session_id)?;
+    let cipher_suites = reader.u16()? as usize;
+    reader.skip(cipher_suites)?;
+    let compression_methods = reader.u8()? as usize;
+    reader.skip(compression_methods)?;
+    if reader.0.is_empty() {
+        return Err(PeekError::MissingHost);
+    }
+    let extensions_len = reader.u16()? as usize;
+    let mut extensions = Reader(reader.take(extensions_len)?);
+    while !extensions.0.is_empty() {
+        let extension_type = extensions.u16()?;
+        let len = extensions.u16()? as usize;
+        let body = extensions.take(len)?;
+        // server_name, RFC 6066 section 3
+        if extension_type == 0 {
+            let mut list = Reader(body);
+            let list_len = list.u16()? as usize;
+            let mut names = Reader(list.take(list_len)?);
+            while !names.0.is_empty() {
+                let name_type = names.u8()?;
+                let len = names.u16()? as usize;
+                let name = names.take(len)?;
+                if name_type == 0 {
+                    return std::str::from_utf8(name)
+                        .map(str::to_string)
+                        .map_err(|_| PeekError::Invalid);
+                }
+            }
+        }
+    }
+    Err(PeekError::MissingHost)
+}
+
+fn u24(bytes: &[u8]) -> usize {
+    (bytes[0] as usize) << 16 | (bytes[1] as usize) << 8 | bytes[2] as usize
+}
+
+/// Bounds-checked reader of a TLS message
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], PeekError> {
+        if self.0.len() < len {
+            return Err(PeekError::Invalid);
+        }
+        let (head, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(head)
+    }
+
+    fn skip(&mut self, len: usize) -> Result<(), PeekError> {
+        self.take(len).map(|_| ())
+    }
+
+    fn u8(&mut self) -> Result<u8, PeekError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, PeekError> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+}
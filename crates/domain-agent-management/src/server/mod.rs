@@ -1,11 +1,44 @@
 //! Server module for agent management
 //!
-//! Contains gRPC, REST, and WebSocket server implementations.
+//! Contains gRPC, REST, and WebSocket server implementations, and the
+//! hostname-based ingress listeners.
 
 pub mod grpc;
+pub mod ingress;
 pub mod rest;
 pub mod websocket;
 
 pub use grpc::{create_grpc_server, GrpcServer};
 pub use rest::{create_rest_server, RestConfig, AppState};
 pub use websocket::WebSocketServer;
+
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tracing::warn;
+
+const ACCEPT_BACKOFF_MIN: Duration = Duration::from_millis(100);
+const ACCEPT_BACKOFF_MAX: Duration = Duration::from_secs(5);
+
+/// Accepts the next connection on `listener`
+///
+/// Accept errors such as running out of file descriptors persist until
+/// connections are closed, so the accept is retried after a delay that
+/// doubles with each consecutive failure instead of spinning. `name`
+/// identifies the listener in the log.
+pub(crate) async fn accept_with_backoff(
+    listener: &TcpListener,
+    name: &str,
+) -> (TcpStream, SocketAddr) {
+    let mut delay = ACCEPT_BACKOFF_MIN;
+    loop {
+        match listener.accept().await {
+            Ok(accepted) => return accepted,
+            Err(e) => {
+                warn!("{}: accept failed, retrying in {:?}: {}", name, delay, e);
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(ACCEPT_BACKOFF_MAX);
+            }
+        }
+    }
+}
@@ -3,16 +3,32 @@
 use axum::{
     Router,
     routing::{get, patch, post, delete},
-    extract::{Path, State},
-    response::{IntoResponse, Json, Response},
-    http::StatusCode,
+    extract::{rejection::JsonRejection, ConnectInfo, FromRequestParts, Path, Query, State},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Json, Response,
+    },
+    http::{header, request::Parts, StatusCode},
+    async_trait,
 };
+use futures_util::StreamExt;
+use std::marker::PhantomData;
+use std::net::SocketAddr;
+use std::ops::Deref;
 use std::sync::Arc;
 use serde::{Serialize, Deserialize};
 use tracing::info;
 use uuid::Uuid;
 
+use crate::domain::rbac::{Permission, Role};
 use crate::service::agent::{AgentFilters, UpdateAgentInput};
+use crate::service::audit::{AuditAction, AuditContext, AuditFilter, AuditSource, NewAuditEntry};
+use crate::service::auth::{parse_bearer, AuthError, CreateUserInput, UpdateUserInput, UserInfo};
+use crate::service::events::EventFilter;
+use crate::service::ingress::{CreateIngressRouteInput, IngressError, IngressRouteInfo};
+use crate::service::task::{SubmitTaskInput, TaskInfo};
+use domain_agent_protocol::task::TaskType;
+use domain_agent_protocol::tunnel::TunnelMessage;
 use crate::service::Service;
 use crate::web_config::{index, serve_asset};
 
@@ -175,6 +191,137 @@ pub struct QuerySystemInfoRequest {
     pub query: String,
 }
 
+/// The authenticated user is recorded as the submitter, a body that names
+/// one is rejected
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SubmitTaskRequest {
+    pub task: TaskType,
+    /// 0 uses the agent's default timeout
+    #[serde(default)]
+    pub timeout_seconds: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ListTasksQuery {
+    pub limit: Option<u64>,
+}
+
+#[derive(Serialize)]
+pub struct ListTasksResponse {
+    pub tasks: Vec<TaskInfo>,
+    pub total: usize,
+}
+
+/// The authenticated user is recorded as the canceller, a body that names
+/// one is rejected
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CancelTaskRequest {
+    pub reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct LoginRequest {
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Deserialize)]
+pub struct CreateUserRequest {
+    pub username: String,
+    pub password: String,
+    pub role: Role,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateUserRequest {
+    pub password: Option<String>,
+    pub role: Option<Role>,
+    pub disabled: Option<bool>,
+}
+
+#[derive(Serialize)]
+pub struct ListUsersResponse {
+    pub users: Vec<UserInfo>,
+    pub total: usize,
+}
+
+/// Query string of the audit log routes, `limit` and `offset` are ignored by the export
+#[derive(Deserialize)]
+pub struct AuditQuery {
+    pub actor: Option<String>,
+    /// Action name or prefix, e.g. `agent.approve` or `agent`
+    pub action: Option<String>,
+    pub target_type: Option<String>,
+    pub target_id: Option<String>,
+    pub source: Option<String>,
+    pub since: Option<chrono::DateTime<chrono::Utc>>,
+    pub until: Option<chrono::DateTime<chrono::Utc>>,
+    pub limit: Option<u64>,
+    pub offset: Option<u64>,
+}
+
+impl AuditQuery {
+    fn filter(&self) -> AuditFilter {
+        AuditFilter {
+            actor: self.actor.clone(),
+            action: self.action.clone(),
+            target_type: self.target_type.clone(),
+            target_id: self.target_id.clone(),
+            source: self.source.clone(),
+            since: self.since,
+            until: self.until,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct EventsQuery {
+    /// Only events of this agent
+    pub agent_id: Option<String>,
+    /// Comma-separated event types, e.g. `agent_connected,health_score`
+    pub event_types: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct TunnelsQuery {
+    /// Only tunnels of this agent
+    pub agent_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct CreateIngressRouteRequest {
+    pub hostname: String,
+    pub agent_id: String,
+    /// `host:port` reachable from the agent
+    pub target: String,
+    /// Route TLS connections by SNI instead of HTTP requests by Host header
+    #[serde(default)]
+    pub tls: bool,
+}
+
+#[derive(Serialize)]
+pub struct ListIngressRoutesResponse {
+    pub routes: Vec<IngressRouteInfo>,
+    pub total: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TaskEventResponse {
+    pub id: String,
+    pub event_type: String,
+    pub timestamp: String,
+    pub reason: Option<String>,
+    pub metadata: Option<serde_json::Value>,
+    pub triggered_by: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TaskEventsResponse {
+    pub events: Vec<TaskEventResponse>,
+}
+
 // Conversion helpers
 
 fn agent_info_to_response(info: &crate::service::AgentInfo) -> AgentResponse {
@@ -192,9 +339,192 @@ fn parse_uuid(id: &str) -> Result<Uuid, StatusCode> {
     Uuid::parse_str(id).map_err(|_| StatusCode::BAD_REQUEST)
 }
 
+fn auth_error_response(e: AuthError) -> Response {
+    let status = match &e {
+        AuthError::InvalidCredentials | AuthError::InvalidToken => StatusCode::UNAUTHORIZED,
+        AuthError::UserNotFound => StatusCode::NOT_FOUND,
+        AuthError::UserExists(_) | AuthError::LastAdmin => StatusCode::CONFLICT,
+        AuthError::WeakPassword => StatusCode::BAD_REQUEST,
+        AuthError::PasswordHash(_) | AuthError::Database(_) => {
+            tracing::error!("Authentication failed: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+                "error": "Authentication failed"
+            }))).into_response();
+        }
+    };
+    (status, Json(serde_json::json!({
+        "error": e.to_string()
+    }))).into_response()
+}
+
+/// Address of the client that sent a request, if the server records it
+fn remote_addr(parts: &Parts) -> Option<SocketAddr> {
+    parts.extensions
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| *addr)
+}
+
+fn ingress_error_response(e: IngressError) -> Response {
+    let status = match &e {
+        IngressError::InvalidHostname(_) | IngressError::InvalidTarget(_) => StatusCode::BAD_REQUEST,
+        IngressError::HostnameTaken(_) => StatusCode::CONFLICT,
+        IngressError::AgentNotFound(_) => StatusCode::NOT_FOUND,
+        IngressError::Database(_) => {
+            tracing::error!("Failed to create ingress route: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+                "error": "Failed to create ingress route"
+            }))).into_response();
+        }
+    };
+    (status, Json(serde_json::json!({
+        "error": e.to_string()
+    }))).into_response()
+}
+
+/// Query string carrying a token, for requests that can't set headers
+#[derive(Deserialize)]
+struct TokenQuery {
+    access_token: Option<String>,
+}
+
+/// Bearer token of a request, if any
+///
+/// Browsers can't set headers on `EventSource` requests, so the token may
+/// also be passed as the `access_token` query parameter.
+fn bearer_token(parts: &Parts) -> Option<String> {
+    let header_token = parts.headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_bearer);
+    match header_token {
+        Some(token) => Some(token.to_string()),
+        None => Query::<TokenQuery>::try_from_uri(&parts.uri)
+            .ok()
+            .and_then(|Query(query)| query.access_token),
+    }
+}
+
+/// The user authenticated by the `Authorization: Bearer <token>` header.
+///
+/// Handlers taking this extractor reject requests without a valid token with
+/// 401. Handlers that need a permission take [`Require`] instead.
+pub struct AuthUser {
+    pub user: UserInfo,
+    pub token: String,
+    pub remote_addr: Option<SocketAddr>,
+}
+
+#[async_trait]
+impl FromRequestParts<Arc<AppState>> for AuthUser {
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, state: &Arc<AppState>) -> Result<Self, Self::Rejection> {
+        let token = bearer_token(parts)
+            .ok_or_else(|| auth_error_response(AuthError::InvalidToken))?;
+        let user = state.service.auth_service.authenticate(&token)
+            .await
+            .map_err(auth_error_response)?;
+        Ok(Self { user, token, remote_addr: remote_addr(parts) })
+    }
+}
+
+impl AuthUser {
+    /// Starts an audit entry for an action of this user
+    pub fn audit(&self, action: AuditAction, target_id: impl ToString) -> NewAuditEntry {
+        let context = AuditContext {
+            actor: self.user.username.clone(),
+            actor_role: Some(self.user.role),
+            source: AuditSource::Rest,
+            remote_addr: self.remote_addr.map(|addr| addr.to_string()),
+        };
+        NewAuditEntry::new(context, action, target_id)
+    }
+}
+
+/// A permission checked by the [`Require`] extractor
+pub trait RequiredPermission {
+    const PERMISSION: Permission;
+}
+
+/// Marker types for [`Require`], one per [`Permission`]
+pub mod perm {
+    use super::{Permission, RequiredPermission};
+
+    macro_rules! permission_markers {
+        ($($name:ident),* $(,)?) => {
+            $(
+                #[doc = concat!("Requires [`Permission::", stringify!($name), "`]")]
+                pub struct $name;
+
+                impl RequiredPermission for $name {
+                    const PERMISSION: Permission = Permission::$name;
+                }
+            )*
+        };
+    }
+
+    permission_markers!(
+        ViewAgents,
+        UpdateAgent,
+        QuerySystemInfo,
+        ViewTasks,
+        SubmitTask,
+        CancelTask,
+        ApproveAgent,
+        DeleteAgent,
+        ManageUsers,
+        ViewAudit,
+        ManageIngress,
+    );
+}
+
+/// An [`AuthUser`] whose role grants the permission `P`, e.g.
+/// `Require<perm::DeleteAgent>`.
+///
+/// Requests without a valid token are rejected with 401 and users lacking the
+/// permission with 403 before the handler runs, so a route can't forget the
+/// check.
+pub struct Require<P>(pub AuthUser, PhantomData<fn() -> P>);
+
+#[async_trait]
+impl<P: RequiredPermission> FromRequestParts<Arc<AppState>> for Require<P> {
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, state: &Arc<AppState>) -> Result<Self, Self::Rejection> {
+        let user = AuthUser::from_request_parts(parts, state).await?;
+        let permission = P::PERMISSION;
+        if !user.user.role.allows(permission) {
+            tracing::warn!("User {} ({}) denied {}", user.user.username, user.user.role, permission);
+            return Err(PermissionDenied(permission).into_response());
+        }
+        Ok(Self(user, PhantomData))
+    }
+}
+
+impl<P> Deref for Require<P> {
+    type Target = AuthUser;
+
+    fn deref(&self) -> &AuthUser {
+        &self.0
+    }
+}
+
+/// Rejection of a request whose user lacks a permission, answered with 403
+#[derive(Debug)]
+pub struct PermissionDenied(pub Permission);
+
+impl IntoResponse for PermissionDenied {
+    fn into_response(self) -> Response {
+        (StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": format!("Permission denied: {} requires the {} role", self.0, self.0.required_role())
+        }))).into_response()
+    }
+}
+
 /// Handler for GET /api/v1/agents - list all agents
 async fn list_agents(
     State(state): State<Arc<AppState>>,
+    _user: Require<perm::ViewAgents>,
 ) -> Response {
     match state.service.agent_service.list_agents(AgentFilters::default()).await {
         Ok(agents) => {
@@ -216,6 +546,7 @@ async fn list_agents(
 /// Handler for GET /api/v1/agents/:id - get a specific agent
 async fn get_agent(
     State(state): State<Arc<AppState>>,
+    _user: Require<perm::ViewAgents>,
     Path(id): Path<String>,
 ) -> Response {
     let agent_id = match parse_uuid(&id) {
@@ -244,6 +575,7 @@ async fn get_agent(
 /// Handler for PATCH /api/v1/agents/:id - update an agent
 async fn update_agent(
     State(state): State<Arc<AppState>>,
+    user: Require<perm::UpdateAgent>,
     Path(id): Path<String>,
     Json(body): Json<UpdateAgentRequest>,
 ) -> Response {
@@ -265,7 +597,13 @@ async fn update_agent(
         last_seen_at: None,
     };
 
-    match state.service.agent_service.update_agent(agent_id, input).await {
+    let before = state.service.agent_service.get_agent(agent_id).await.ok().flatten();
+    let result = state.service.agent_service.update_agent(agent_id, input).await;
+    state.service.audit_service.record(
+        user.audit(AuditAction::UpdateAgent, agent_id).with_before(&before).with_result(&result, "Agent not found")
+    ).await;
+
+    match result {
         Ok(Some(agent)) => {
             (StatusCode::OK, Json(agent_info_to_response(&agent))).into_response()
         }
@@ -286,6 +624,7 @@ async fn update_agent(
 /// Handler for DELETE /api/v1/agents/:id - delete an agent
 async fn delete_agent(
     State(state): State<Arc<AppState>>,
+    user: Require<perm::DeleteAgent>,
     Path(id): Path<String>,
 ) -> Response {
     let agent_id = match parse_uuid(&id) {
@@ -293,7 +632,16 @@ async fn delete_agent(
         Err(status) => return status.into_response(),
     };
 
-    match state.service.agent_service.delete_agent(agent_id).await {
+    let before = state.service.agent_service.get_agent(agent_id).await.ok().flatten();
+    let result = state.service.agent_service.delete_agent(agent_id).await;
+    let entry = user.audit(AuditAction::DeleteAgent, agent_id).with_before(&before);
+    state.service.audit_service.record(match &result {
+        Ok(true) => entry,
+        Ok(false) => entry.with_error("Agent not found"),
+        Err(e) => entry.with_error(e),
+    }).await;
+
+    match result {
         Ok(true) => {
             (StatusCode::NO_CONTENT).into_response()
         }
@@ -314,15 +662,22 @@ async fn delete_agent(
 /// Handler for POST /api/v1/agents/:id/approve - approve an agent
 async fn approve_agent(
     State(state): State<Arc<AppState>>,
+    user: Require<perm::ApproveAgent>,
     Path(id): Path<String>,
 ) -> Response {
     let agent_id = match parse_uuid(&id) {
         Ok(id) => id,
         Err(status) => return status.into_response(),
     };
-    info!("批准Agent注册！ agentId : {:?}",&agent_id);
+    info!("批准Agent注册！ agentId : {:?}, approvedBy : {}",&agent_id, user.user.username);
 
-    match state.service.agent_service.approve_agent(agent_id, "rest-api".to_string()).await {
+    let before = state.service.agent_service.get_agent(agent_id).await.ok().flatten();
+    let result = state.service.agent_service.approve_agent(agent_id, user.user.username.clone()).await;
+    state.service.audit_service.record(
+        user.audit(AuditAction::ApproveAgent, agent_id).with_before(&before).with_result(&result, "Agent not found")
+    ).await;
+
+    match result {
         Ok(Some(agent)) => {
             (StatusCode::OK, Json(agent_info_to_response(&agent))).into_response()
         }
@@ -343,6 +698,7 @@ async fn approve_agent(
 /// Handler for POST /api/v1/agents/:id/deny - deny an agent
 async fn deny_agent(
     State(state): State<Arc<AppState>>,
+    user: Require<perm::ApproveAgent>,
     Path(id): Path<String>,
 ) -> Response {
     let agent_id = match parse_uuid(&id) {
@@ -350,7 +706,13 @@ async fn deny_agent(
         Err(status) => return status.into_response(),
     };
 
-    match state.service.agent_service.deny_agent(agent_id, "denied via REST API".to_string(), "rest-api".to_string()).await {
+    let before = state.service.agent_service.get_agent(agent_id).await.ok().flatten();
+    let result = state.service.agent_service.deny_agent(agent_id, "denied via REST API".to_string(), user.user.username.clone()).await;
+    state.service.audit_service.record(
+        user.audit(AuditAction::DenyAgent, agent_id).with_before(&before).with_result(&result, "Agent not found")
+    ).await;
+
+    match result {
         Ok(Some(agent)) => {
             (StatusCode::OK, Json(agent_info_to_response(&agent))).into_response()
         }
@@ -371,6 +733,7 @@ async fn deny_agent(
 /// Handler for GET /api/v1/agents/:id/system-info - get system info
 async fn get_system_info(
     State(state): State<Arc<AppState>>,
+    user: Require<perm::ViewAgents>,
     Path(id): Path<String>,
 ) -> Response {
     let agent_id = match parse_uuid(&id) {
@@ -378,7 +741,15 @@ async fn get_system_info(
         Err(status) => return status.into_response(),
     };
 
-    match state.service.diagnostic_service.get_system_info(agent_id).await {
+    let result = state.service.diagnostic_service.get_system_info(agent_id).await;
+    let entry = user.audit(AuditAction::GetSystemInfo, agent_id);
+    state.service.audit_service.record(match &result {
+        Ok(Some(_)) => entry,
+        Ok(None) => entry.with_error("System info not found"),
+        Err(e) => entry.with_error(e),
+    }).await;
+
+    match result {
         Ok(Some(info)) => {
             let os_info = info.os_info.and_then(|j| serde_json::from_value(j).ok()).unwrap_or(OsInfoResponse {
                 os: "Unknown".to_string(),
@@ -439,9 +810,17 @@ async fn get_system_info(
 
 /// Handler for POST /api/v1/agents/:id/system-info/query - query system info
 async fn query_system_info(
+    State(state): State<Arc<AppState>>,
+    user: Require<perm::QuerySystemInfo>,
     Path(id): Path<String>,
-    Json(_body): Json<QuerySystemInfoRequest>,
+    Json(body): Json<QuerySystemInfoRequest>,
 ) -> Response {
+    state.service.audit_service.record(
+        user.audit(AuditAction::QuerySystemInfo, &id)
+            .with_after(&serde_json::json!({ "query": body.query }))
+            .with_error("query_system_info not implemented")
+    ).await;
+
     (StatusCode::NOT_IMPLEMENTED, Json(serde_json::json!({
         "message": "query_system_info not implemented",
         "id": id
@@ -451,6 +830,7 @@ async fn query_system_info(
 /// Handler for GET /api/v1/agents/:id/health - get health score
 async fn get_health_score(
     State(state): State<Arc<AppState>>,
+    _user: Require<perm::ViewAgents>,
     Path(id): Path<String>,
 ) -> Response {
     let agent_id = match parse_uuid(&id) {
@@ -483,6 +863,7 @@ async fn get_health_score(
 /// Handler for GET /api/v1/agents/:id/lifecycle - get lifecycle events
 async fn get_lifecycle_events(
     State(state): State<Arc<AppState>>,
+    _user: Require<perm::ViewAgents>,
     Path(id): Path<String>,
 ) -> Response {
     let agent_id = match parse_uuid(&id) {
@@ -520,6 +901,636 @@ async fn get_lifecycle_events(
     }
 }
 
+/// Handler for POST /api/v1/agents/:id/tasks - submit a task
+async fn submit_task(
+    State(state): State<Arc<AppState>>,
+    user: Require<perm::SubmitTask>,
+    Path(id): Path<String>,
+    Json(body): Json<SubmitTaskRequest>,
+) -> Response {
+    let agent_id = match parse_uuid(&id) {
+        Ok(id) => id,
+        Err(status) => return status.into_response(),
+    };
+
+    match state.service.agent_service.get_agent(agent_id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return (StatusCode::NOT_FOUND, Json(serde_json::json!({
+                "error": "Agent not found"
+            }))).into_response();
+        }
+        Err(e) => {
+            tracing::error!("Failed to get agent: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+                "error": "Failed to get agent"
+            }))).into_response();
+        }
+    }
+
+    let input = SubmitTaskInput {
+        agent_id,
+        task: body.task,
+        timeout_seconds: body.timeout_seconds,
+        submitted_by: Some(user.user.username.clone()),
+    };
+    let entry = user.audit(AuditAction::SubmitTask, agent_id);
+    let result = state.service.task_service.submit_task(input).await;
+    state.service.audit_service.record(match &result {
+        Ok(task) => entry.with_after(&task.audit_state()),
+        Err(e) => entry.with_error(e),
+    }).await;
+
+    match result {
+        Ok(task) => (StatusCode::CREATED, Json(task)).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to submit task: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+                "error": "Failed to submit task"
+            }))).into_response()
+        }
+    }
+}
+
+/// Handler for GET /api/v1/agents/:id/tasks - list the tasks of an agent
+async fn list_tasks(
+    State(state): State<Arc<AppState>>,
+    _user: Require<perm::ViewTasks>,
+    Path(id): Path<String>,
+    Query(query): Query<ListTasksQuery>,
+) -> Response {
+    let agent_id = match parse_uuid(&id) {
+        Ok(id) => id,
+        Err(status) => return status.into_response(),
+    };
+
+    let limit = query.limit.unwrap_or(50).clamp(1, 500);
+    match state.service.task_service.list_tasks(agent_id, limit).await {
+        Ok(tasks) => {
+            let total = tasks.len();
+            (StatusCode::OK, Json(ListTasksResponse { tasks, total })).into_response()
+        }
+        Err(e) => {
+            tracing::error!("Failed to list tasks: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+                "error": "Failed to list tasks"
+            }))).into_response()
+        }
+    }
+}
+
+/// Handler for GET /api/v1/tasks/:id - get a task with its status and output
+async fn get_task(
+    State(state): State<Arc<AppState>>,
+    _user: Require<perm::ViewTasks>,
+    Path(id): Path<String>,
+) -> Response {
+    let task_id = match parse_uuid(&id) {
+        Ok(id) => id,
+        Err(status) => return status.into_response(),
+    };
+
+    match state.service.task_service.get_task(task_id).await {
+        Ok(Some(task)) => (StatusCode::OK, Json(task)).into_response(),
+        Ok(None) => {
+            (StatusCode::NOT_FOUND, Json(serde_json::json!({
+                "error": "Task not found"
+            }))).into_response()
+        }
+        Err(e) => {
+            tracing::error!("Failed to get task: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+                "error": "Failed to get task"
+            }))).into_response()
+        }
+    }
+}
+
+/// Handler for POST /api/v1/tasks/:id/cancel - cancel a task
+async fn cancel_task(
+    State(state): State<Arc<AppState>>,
+    user: Require<perm::CancelTask>,
+    Path(id): Path<String>,
+    body: Result<Json<CancelTaskRequest>, JsonRejection>,
+) -> Response {
+    let task_id = match parse_uuid(&id) {
+        Ok(id) => id,
+        Err(status) => return status.into_response(),
+    };
+
+    // The body is optional, but one that is sent must be valid
+    let reason = match body {
+        Ok(Json(body)) => body.reason,
+        Err(JsonRejection::MissingJsonContentType(_)) => None,
+        Err(rejection) => return rejection.into_response(),
+    };
+    let reason = reason.unwrap_or_else(|| "Cancelled via REST API".to_string());
+    let before = state.service.task_service.get_task(task_id).await.ok().flatten().map(|task| task.audit_state());
+    let result = state.service.task_service.cancel_task(task_id, reason, Some(user.user.username.clone())).await;
+    let entry = user.audit(AuditAction::CancelTask, task_id).with_before(&before);
+    state.service.audit_service.record(match &result {
+        Ok(Some(task)) => entry.with_after(&task.audit_state()),
+        Ok(None) => entry.with_error("Task not found"),
+        Err(e) => entry.with_error(e),
+    }).await;
+
+    match result {
+        Ok(Some(task)) => (StatusCode::OK, Json(task)).into_response(),
+        Ok(None) => {
+            (StatusCode::NOT_FOUND, Json(serde_json::json!({
+                "error": "Task not found"
+            }))).into_response()
+        }
+        Err(e) => {
+            tracing::error!("Failed to cancel task: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+                "error": "Failed to cancel task"
+            }))).into_response()
+        }
+    }
+}
+
+/// Handler for GET /api/v1/tasks/:id/events - get the audit events of a task
+async fn get_task_events(
+    State(state): State<Arc<AppState>>,
+    _user: Require<perm::ViewTasks>,
+    Path(id): Path<String>,
+) -> Response {
+    let task_id = match parse_uuid(&id) {
+        Ok(id) => id,
+        Err(status) => return status.into_response(),
+    };
+
+    match state.service.task_service.get_events(task_id).await {
+        Ok(events) => {
+            let events = events.into_iter().map(|e| TaskEventResponse {
+                id: e.event_id.to_string(),
+                event_type: e.event_type.to_string(),
+                timestamp: e.timestamp.to_rfc3339(),
+                reason: e.reason,
+                metadata: e.metadata,
+                triggered_by: e.triggered_by,
+            }).collect();
+            (StatusCode::OK, Json(TaskEventsResponse { events })).into_response()
+        }
+        Err(e) => {
+            tracing::error!("Failed to get task events: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+                "error": "Failed to get task events"
+            }))).into_response()
+        }
+    }
+}
+
+/// Handler for POST /api/v1/auth/login - log in with a username and password
+async fn login(
+    State(state): State<Arc<AppState>>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    Json(body): Json<LoginRequest>,
+) -> Response {
+    let result = state.service.auth_service.login(&body.username, &body.password).await;
+
+    // Failed attempts are recorded under the username that was tried
+    let context = AuditContext {
+        actor: body.username.clone(),
+        actor_role: result.as_ref().ok().map(|login| login.user.role),
+        source: AuditSource::Rest,
+        remote_addr: connect_info.map(|ConnectInfo(addr)| addr.to_string()),
+    };
+    let entry = match &result {
+        Ok(login) => NewAuditEntry::new(context, AuditAction::Login, login.user.id),
+        Err(e) => NewAuditEntry::new(context, AuditAction::Login, &body.username).with_error(e),
+    };
+    state.service.audit_service.record(entry).await;
+
+    match result {
+        Ok(result) => {
+            info!("User {} logged in", result.user.username);
+            (StatusCode::OK, Json(result)).into_response()
+        }
+        Err(e) => {
+            tracing::warn!("Failed login for user {}: {}", body.username, e);
+            auth_error_response(e)
+        }
+    }
+}
+
+/// Handler for POST /api/v1/auth/logout - revoke the token of the request
+async fn logout(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+) -> Response {
+    let result = state.service.auth_service.logout(&user.token).await;
+    let entry = user.audit(AuditAction::Logout, user.user.id);
+    state.service.audit_service.record(match &result {
+        Ok(_) => entry,
+        Err(e) => entry.with_error(e),
+    }).await;
+
+    match result {
+        Ok(_) => (StatusCode::NO_CONTENT).into_response(),
+        Err(e) => auth_error_response(e),
+    }
+}
+
+/// Handler for GET /api/v1/auth/me - get the authenticated user and its permissions
+async fn me(user: AuthUser) -> Response {
+    let permissions: Vec<&str> = user.user.role.permissions().iter().map(|p| p.as_str()).collect();
+    (StatusCode::OK, Json(serde_json::json!({
+        "user": user.user,
+        "permissions": permissions,
+    }))).into_response()
+}
+
+/// Handler for GET /api/v1/users - list all users
+async fn list_users(
+    State(state): State<Arc<AppState>>,
+    _user: Require<perm::ManageUsers>,
+) -> Response {
+    match state.service.auth_service.list_users().await {
+        Ok(users) => {
+            let total = users.len();
+            (StatusCode::OK, Json(ListUsersResponse { users, total })).into_response()
+        }
+        Err(e) => auth_error_response(e),
+    }
+}
+
+/// Handler for POST /api/v1/users - create a user
+async fn create_user(
+    State(state): State<Arc<AppState>>,
+    user: Require<perm::ManageUsers>,
+    Json(body): Json<CreateUserRequest>,
+) -> Response {
+    let username = body.username.clone();
+    let input = CreateUserInput {
+        username: body.username,
+        password: body.password,
+        role: body.role,
+    };
+    let result = state.service.auth_service.create_user(input).await;
+    state.service.audit_service.record(match &result {
+        Ok(created) => user.audit(AuditAction::CreateUser, created.id).with_after(created),
+        Err(e) => user.audit(AuditAction::CreateUser, &username).with_error(e),
+    }).await;
+
+    match result {
+        Ok(created) => {
+            info!("User {} created user {} with role {}", user.user.username, created.username, created.role);
+            (StatusCode::CREATED, Json(created)).into_response()
+        }
+        Err(e) => auth_error_response(e),
+    }
+}
+
+/// Handler for GET /api/v1/users/:id - get a user
+async fn get_user(
+    State(state): State<Arc<AppState>>,
+    _user: Require<perm::ManageUsers>,
+    Path(id): Path<String>,
+) -> Response {
+    let user_id = match parse_uuid(&id) {
+        Ok(id) => id,
+        Err(status) => return status.into_response(),
+    };
+
+    match state.service.auth_service.get_user(user_id).await {
+        Ok(Some(found)) => (StatusCode::OK, Json(found)).into_response(),
+        Ok(None) => auth_error_response(AuthError::UserNotFound),
+        Err(e) => auth_error_response(e),
+    }
+}
+
+/// Handler for PATCH /api/v1/users/:id - change the password, role or disabled flag of a user
+async fn update_user(
+    State(state): State<Arc<AppState>>,
+    user: Require<perm::ManageUsers>,
+    Path(id): Path<String>,
+    Json(body): Json<UpdateUserRequest>,
+) -> Response {
+    let user_id = match parse_uuid(&id) {
+        Ok(id) => id,
+        Err(status) => return status.into_response(),
+    };
+
+    let input = UpdateUserInput {
+        password: body.password,
+        role: body.role,
+        disabled: body.disabled,
+    };
+    let before = state.service.auth_service.get_user(user_id).await.ok().flatten();
+    let result = state.service.auth_service.update_user(user_id, input).await;
+    let entry = user.audit(AuditAction::UpdateUser, user_id).with_before(&before);
+    state.service.audit_service.record(match &result {
+        Ok(updated) => entry.with_after(updated),
+        Err(e) => entry.with_error(e),
+    }).await;
+
+    match result {
+        Ok(updated) => {
+            info!("User {} updated user {}", user.user.username, updated.username);
+            (StatusCode::OK, Json(updated)).into_response()
+        }
+        Err(e) => auth_error_response(e),
+    }
+}
+
+/// Handler for DELETE /api/v1/users/:id - delete a user
+async fn delete_user(
+    State(state): State<Arc<AppState>>,
+    user: Require<perm::ManageUsers>,
+    Path(id): Path<String>,
+) -> Response {
+    let user_id = match parse_uuid(&id) {
+        Ok(id) => id,
+        Err(status) => return status.into_response(),
+    };
+
+    let before = state.service.auth_service.get_user(user_id).await.ok().flatten();
+    let result = state.service.auth_service.delete_user(user_id).await;
+    let entry = user.audit(AuditAction::DeleteUser, user_id).with_before(&before);
+    state.service.audit_service.record(match &result {
+        Ok(true) => entry,
+        Ok(false) => entry.with_error(AuthError::UserNotFound),
+        Err(e) => entry.with_error(e),
+    }).await;
+
+    match result {
+        Ok(true) => {
+            info!("User {} deleted user {}", user.user.username, user_id);
+            (StatusCode::NO_CONTENT).into_response()
+        }
+        Ok(false) => auth_error_response(AuthError::UserNotFound),
+        Err(e) => auth_error_response(e),
+    }
+}
+
+/// Handler for GET /api/v1/events - stream lifecycle events, health scores and
+/// heartbeats as Server-Sent Events
+async fn stream_events(
+    State(state): State<Arc<AppState>>,
+    _user: Require<perm::ViewAgents>,
+    Query(query): Query<EventsQuery>,
+) -> Response {
+    let agent_id = match query.agent_id.as_deref().filter(|id| !id.is_empty()).map(parse_uuid).transpose() {
+        Ok(id) => id,
+        Err(status) => return status.into_response(),
+    };
+    let event_types = query.event_types.unwrap_or_default();
+    let filter = EventFilter::new(agent_id)
+        .with_event_types(event_types.split(',').map(str::to_string));
+
+    // Events are sent without an SSE event name so that `EventSource.onmessage` receives all of them
+    let stream = state.service.events.subscribe(filter).map(|event| {
+        Event::default()
+            .id(event.event_id().to_string())
+            .json_data(&event)
+    });
+    Sse::new(stream).keep_alive(KeepAlive::default()).into_response()
+}
+
+/// Handler for GET /api/v1/tunnels - list the open reverse tunnels
+async fn list_tunnels(
+    State(state): State<Arc<AppState>>,
+    _user: Require<perm::ViewAgents>,
+    Query(query): Query<TunnelsQuery>,
+) -> Response {
+    let agent_id = match query.agent_id.as_deref().filter(|id| !id.is_empty()).map(parse_uuid).transpose() {
+        Ok(id) => id,
+        Err(status) => return status.into_response(),
+    };
+    let tunnels = state.service.tunnel_broker.list(agent_id).await;
+    (StatusCode::OK, Json(tunnels)).into_response()
+}
+
+/// Handler for GET /api/v1/agents/:id/forwards - list the port forwards of a connected agent
+async fn list_forwards(
+    State(state): State<Arc<AppState>>,
+    _user: Require<perm::ViewAgents>,
+    Path(id): Path<String>,
+) -> Response {
+    let agent_id = match parse_uuid(&id) {
+        Ok(id) => id,
+        Err(status) => return status.into_response(),
+    };
+    match state.service.tunnel_broker.forwards(agent_id).await {
+        Some(forwards) => (StatusCode::OK, Json(forwards)).into_response(),
+        None => (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Agent is not connected"
+        }))).into_response(),
+    }
+}
+
+/// Handler for POST /api/v1/agents/:id/forwards/:name/start - start a port forward
+async fn start_forward(
+    State(state): State<Arc<AppState>>,
+    user: Require<perm::UpdateAgent>,
+    Path((id, name)): Path<(String, String)>,
+) -> Response {
+    toggle_forward(state, user, id, name, true).await
+}
+
+/// Handler for POST /api/v1/agents/:id/forwards/:name/stop - stop a port forward
+async fn stop_forward(
+    State(state): State<Arc<AppState>>,
+    user: Require<perm::UpdateAgent>,
+    Path((id, name)): Path<(String, String)>,
+) -> Response {
+    toggle_forward(state, user, id, name, false).await
+}
+
+/// Asks the agent to start or stop a forward, the agent reports the result
+async fn toggle_forward(state: Arc<AppState>, user: Require<perm::UpdateAgent>, id: String, name: String, start: bool) -> Response {
+    let agent_id = match parse_uuid(&id) {
+        Ok(id) => id,
+        Err(status) => return status.into_response(),
+    };
+    let (action, msg) = match start {
+        true => (AuditAction::StartForward, TunnelMessage::ForwardStart { name: name.clone() }),
+        false => (AuditAction::StopForward, TunnelMessage::ForwardStop { name: name.clone() }),
+    };
+    let forward = state.service.tunnel_broker.forwards(agent_id).await
+        .and_then(|forwards| forwards.into_iter().find(|forward| forward.name == name));
+    let entry = user.audit(action, agent_id);
+    let Some(forward) = forward else {
+        state.service.audit_service.record(entry.with_error(format!("Forward {} not found", name))).await;
+        return (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Forward not found or agent not connected"
+        }))).into_response();
+    };
+
+    let sent = match serde_json::to_string(&msg) {
+        Ok(json) => state.service.task_service.channels().send(agent_id, json).await,
+        Err(_) => false,
+    };
+    let entry = entry.with_before(&forward);
+    state.service.audit_service.record(match sent {
+        true => entry,
+        false => entry.with_error("Agent is not connected"),
+    }).await;
+
+    match sent {
+        true => (StatusCode::ACCEPTED, Json(forward)).into_response(),
+        false => (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Agent is not connected"
+        }))).into_response(),
+    }
+}
+
+/// Handler for GET /api/v1/ingress/routes - list the ingress routes
+async fn list_ingress_routes(
+    State(state): State<Arc<AppState>>,
+    _user: Require<perm::ViewAgents>,
+) -> Response {
+    match state.service.ingress_service.list_routes().await {
+        Ok(routes) => {
+            let total = routes.len();
+            (StatusCode::OK, Json(ListIngressRoutesResponse { routes, total })).into_response()
+        }
+        Err(e) => {
+            tracing::error!("Failed to list ingress routes: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+                "error": "Failed to list ingress routes"
+            }))).into_response()
+        }
+    }
+}
+
+/// Handler for POST /api/v1/ingress/routes - route a hostname to a target behind an agent
+async fn create_ingress_route(
+    State(state): State<Arc<AppState>>,
+    user: Require<perm::ManageIngress>,
+    Json(body): Json<CreateIngressRouteRequest>,
+) -> Response {
+    let agent_id = match parse_uuid(&body.agent_id) {
+        Ok(id) => id,
+        Err(status) => return status.into_response(),
+    };
+    let hostname = body.hostname.clone();
+    let input = CreateIngressRouteInput {
+        hostname: body.hostname,
+        agent_id,
+        target: body.target,
+        tls: body.tls,
+        created_by: Some(user.user.username.clone()),
+    };
+    let result = state.service.ingress_service.create_route(input).await;
+    state.service.audit_service.record(match &result {
+        Ok(route) => user.audit(AuditAction::CreateIngressRoute, route.id).with_after(route),
+        Err(e) => user.audit(AuditAction::CreateIngressRoute, &hostname).with_error(e),
+    }).await;
+
+    match result {
+        Ok(route) => (StatusCode::CREATED, Json(route)).into_response(),
+        Err(e) => ingress_error_response(e),
+    }
+}
+
+/// Handler for GET /api/v1/ingress/routes/:id - get an ingress route
+async fn get_ingress_route(
+    State(state): State<Arc<AppState>>,
+    _user: Require<perm::ViewAgents>,
+    Path(id): Path<String>,
+) -> Response {
+    let route_id = match parse_uuid(&id) {
+        Ok(id) => id,
+        Err(status) => return status.into_response(),
+    };
+
+    match state.service.ingress_service.get_route(route_id).await {
+        Ok(Some(route)) => (StatusCode::OK, Json(route)).into_response(),
+        Ok(None) => {
+            (StatusCode::NOT_FOUND, Json(serde_json::json!({
+                "error": "Ingress route not found"
+            }))).into_response()
+        }
+        Err(e) => {
+            tracing::error!("Failed to get ingress route: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+                "error": "Failed to get ingress route"
+            }))).into_response()
+        }
+    }
+}
+
+/// Handler for DELETE /api/v1/ingress/routes/:id - delete an ingress route
+async fn delete_ingress_route(
+    State(state): State<Arc<AppState>>,
+    user: Require<perm::ManageIngress>,
+    Path(id): Path<String>,
+) -> Response {
+    let route_id = match parse_uuid(&id) {
+        Ok(id) => id,
+        Err(status) => return status.into_response(),
+    };
+
+    let result = state.service.ingress_service.delete_route(route_id).await;
+    let entry = user.audit(AuditAction::DeleteIngressRoute, route_id);
+    state.service.audit_service.record(match &result {
+        Ok(Some(route)) => entry.with_before(route),
+        Ok(None) => entry.with_error("Ingress route not found"),
+        Err(e) => entry.with_error(e),
+    }).await;
+
+    match result {
+        Ok(Some(_)) => StatusCode::NO_CONTENT.into_response(),
+        Ok(None) => {
+            (StatusCode::NOT_FOUND, Json(serde_json::json!({
+                "error": "Ingress route not found"
+            }))).into_response()
+        }
+        Err(e) => {
+            tracing::error!("Failed to delete ingress route: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+                "error": "Failed to delete ingress route"
+            }))).into_response()
+        }
+    }
+}
+
+/// Handler for GET /api/v1/audit - query the audit log, newest first
+async fn list_audit_entries(
+    State(state): State<Arc<AppState>>,
+    _user: Require<perm::ViewAudit>,
+    Query(query): Query<AuditQuery>,
+) -> Response {
+    let limit = query.limit.unwrap_or(50);
+    let offset = query.offset.unwrap_or(0);
+    match state.service.audit_service.query(&query.filter(), limit, offset).await {
+        Ok(page) => (StatusCode::OK, Json(page)).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to query audit log: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+                "error": "Failed to query audit log"
+            }))).into_response()
+        }
+    }
+}
+
+/// Handler for GET /api/v1/audit/export - download the audit log as JSON Lines
+async fn export_audit_entries(
+    State(state): State<Arc<AppState>>,
+    _user: Require<perm::ViewAudit>,
+    Query(query): Query<AuditQuery>,
+) -> Response {
+    match state.service.audit_service.export_jsonl(&query.filter()).await {
+        Ok(jsonl) => (
+            StatusCode::OK,
+            [
+                (header::CONTENT_TYPE, "application/x-ndjson"),
+                (header::CONTENT_DISPOSITION, "attachment; filename=\"audit-log.jsonl\""),
+            ],
+            jsonl,
+        ).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to export audit log: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+                "error": "Failed to export audit log"
+            }))).into_response()
+        }
+    }
+}
+
 /// Create and configure the REST server
 pub async fn create_rest_server(
     config: RestConfig,
@@ -530,15 +1541,34 @@ pub async fn create_rest_server(
         .route("/", get(index))
         .route("/index.html", get(index))
         .route("/assets/*path", get(serve_asset))
+        // Authentication routes
+        .route("/api/v1/auth/login", post(login))
+        .route("/api/v1/auth/logout", post(logout))
+        .route("/api/v1/auth/me", get(me))
+        .route("/api/v1/users", get(list_users).post(create_user))
+        .route("/api/v1/users/:id", get(get_user).patch(update_user).delete(delete_user))
+        .route("/api/v1/audit", get(list_audit_entries))
+        .route("/api/v1/audit/export", get(export_audit_entries))
         // API routes
         .route("/api/v1/agents", get(list_agents))
-        .route("/api/v1/agents/:id", get(get_agent))
+        .route("/api/v1/agents/:id", get(get_agent).patch(update_agent).delete(delete_agent))
         .route("/api/v1/agents/:id/approve", post(approve_agent))
         .route("/api/v1/agents/:id/deny", post(deny_agent))
         .route("/api/v1/agents/:id/system-info", get(get_system_info))
         .route("/api/v1/agents/:id/system-info/query", post(query_system_info))
         .route("/api/v1/agents/:id/health", get(get_health_score))
         .route("/api/v1/agents/:id/lifecycle", get(get_lifecycle_events))
+        .route("/api/v1/events", get(stream_events))
+        .route("/api/v1/agents/:id/tasks", get(list_tasks).post(submit_task))
+        .route("/api/v1/tasks/:id", get(get_task))
+        .route("/api/v1/tasks/:id/cancel", post(cancel_task))
+        .route("/api/v1/tasks/:id/events", get(get_task_events))
+        .route("/api/v1/tunnels", get(list_tunnels))
+        .route("/api/v1/agents/:id/forwards", get(list_forwards))
+        .route("/api/v1/agents/:id/forwards/:name/start", post(start_forward))
+        .route("/api/v1/agents/:id/forwards/:name/stop", post(stop_forward))
+        .route("/api/v1/ingress/routes", get(list_ingress_routes).post(create_ingress_route))
+        .route("/api/v1/ingress/routes/:id", get(get_ingress_route).delete(delete_ingress_route))
         .with_state(Arc::new(state));
 
     tracing::info!("REST server configured on {}", config.addr);
@@ -570,6 +1600,41 @@ mod tests {
         assert!(json.contains("\"name\":\"Test Agent\""));
     }
 
+    #[test]
+    fn test_submit_task_request_deserialization() {
+        let body: SubmitTaskRequest = serde_json::from_str(
+            r#"{"task": {"type": "Shell", "data": {"command": "uptime", "working_dir": null, "env_vars": null}}}"#,
+        )
+        .unwrap();
+        assert_eq!(body.task.kind(), "shell");
+        assert_eq!(body.timeout_seconds, 0);
+
+        // The submitter is the authenticated user, not a body field
+        assert!(serde_json::from_str::<SubmitTaskRequest>(
+            r#"{"task": {"type": "Shell", "data": {"command": "uptime", "working_dir": null, "env_vars": null}}, "submitted_by": "mallory"}"#,
+        )
+        .is_err());
+        assert!(serde_json::from_str::<CancelTaskRequest>(r#"{"triggered_by": "mallory"}"#).is_err());
+    }
+
+    #[test]
+    fn test_permission_denied_response() {
+        let response = PermissionDenied(Permission::DeleteAgent).into_response();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = auth_error_response(AuthError::InvalidToken);
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = auth_error_response(AuthError::LastAdmin);
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+    }
+
+    #[test]
+    fn test_permission_markers() {
+        assert_eq!(perm::ViewAgents::PERMISSION, Permission::ViewAgents);
+        assert_eq!(perm::DeleteAgent::PERMISSION, Permission::DeleteAgent);
+        assert_eq!(perm::ManageIngress::PERMISSION, Permission::ManageIngress);
+    }
+
     #[test]
     fn test_parse_uuid_valid() {
         let valid_uuid = "550e8400-e29b-41d4-a716-446655440000";
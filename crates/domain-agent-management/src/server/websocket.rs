@@ -3,7 +3,15 @@
 //! Accepts WebSocket connections from agents and handles:
 //! - RegisterWithSecret: Agent registration with secret key
 //! - SystemInfoReport: System diagnostic information
-//! - Heartbeat: Agent heartbeat with health metrics
+//! - Heartbeat: Agent heartbeat with health metrics, recorded as a health
+//!   score and published to the event bus
+//! - TaskOutput / TaskResult: Output and results of remote tasks
+//! - TunnelRequest / TunnelClose: Reverse tunnels, handled by the TunnelBroker
+//! - ForwardReport: The agent's port forwards, recorded by the TunnelBroker
+//!
+//! Once an agent has registered, its connection is registered with the
+//! TaskService so tasks can be dispatched to it. Binary messages carry the
+//! multiplexed streams of the agent's tunnels.
 
 use anyhow::Result;
 use chrono::{DateTime, Utc};
@@ -11,6 +19,7 @@ use futures_util::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tokio::net::TcpStream;
+use tokio::sync::mpsc;
 use tokio_tungstenite::tungstenite::Message;
 use tokio_tungstenite::WebSocketStream;
 use tracing::{error, info, warn};
@@ -18,7 +27,11 @@ use uuid::Uuid;
 
 use domain_agent_protocol::diagnostic::SystemInfoReport;
 use domain_agent_protocol::lifecycle::{EventSource, LifecycleEvent, LifecycleEventType};
+use domain_agent_protocol::mux::{Multiplexer, Side};
+use domain_agent_protocol::task::{TaskOutputChunk, TaskResultReport};
+use domain_agent_protocol::tunnel::{FrameError, TunnelFrame, TunnelMessage};
 
+use crate::service::events::{BusEvent, HeartbeatEvent};
 use crate::service::Service;
 
 /// System information data nested in SystemInfoReport
@@ -114,16 +127,59 @@ impl WebSocketServer {
         let ws_stream = tokio_tungstenite::accept_async(stream).await?;
         let (mut write, mut read) = ws_stream.split();
 
-        while let Some(msg_result) = read.next().await {
+        // Messages queued for this agent by the TaskService
+        let (tx, mut rx) = mpsc::unbounded_channel::<String>();
+        // Tunnel frames queued by the multiplexer; streams opened by the agent
+        // for its port forwards are relayed to other agents
+        let (frame_tx, mut frame_rx) = mpsc::unbounded_channel::<Vec<u8>>();
+        let (mux, incoming) = Multiplexer::new(Side::Hub, frame_tx);
+        let relay = tokio::spawn(self.service.tunnel_broker.clone().relay_streams(mux.clone(), incoming));
+        let mut session = Session { agent_id: None, tx, mux };
+
+        loop {
+            let msg_result = tokio::select! {
+                msg = read.next() => match msg {
+                    Some(msg) => msg,
+                    None => break,
+                },
+                Some(outgoing) = rx.recv() => {
+                    if write.send(Message::Text(outgoing)).await.is_err() {
+                        break;
+                    }
+                    continue;
+                }
+                Some(frame) = frame_rx.recv() => {
+                    if write.send(Message::Binary(frame)).await.is_err() {
+                        break;
+                    }
+                    continue;
+                }
+            };
             match msg_result {
                 Ok(Message::Text(text)) => {
-                    if let Err(e) = self.process_message(&mut write, &text).await {
+                    if let Err(e) = self.process_message(&mut write, &mut session, &text).await {
                         error!("Error processing message: {}", e);
                     }
                 }
                 Ok(Message::Binary(data)) => {
+                    match TunnelFrame::decode(&data) {
+                        Ok(frame) if session.agent_id.is_some() => {
+                            session.mux.handle_frame(frame);
+                            continue;
+                        }
+                        Ok(_) => {
+                            warn!("Tunnel frame from an unregistered connection");
+                            continue;
+                        }
+                        Err(FrameError::Truncated) => {
+                            warn!("Truncated tunnel frame");
+                            continue;
+                        }
+                        // Not a frame, a JSON message sent as binary
+                        Err(FrameError::UnknownType(_)) => {}
+                    }
                     if let Ok(text) = String::from_utf8(data) {
-                        if let Err(e) = self.process_message(&mut write, &text).await {
+                        if let Err(e) = self.process_message(&mut write, &mut session, &text).await {
                             error!("Error processing binary message: {}", e);
                         }
                     }
@@ -145,6 +201,13 @@ impl WebSocketServer {
             }
         }
 
+        if let Some(agent_id) = session.agent_id {
+            self.service.task_service.channels().unregister(agent_id, &session.tx).await;
+        }
+        relay.abort();
+        self.service.tunnel_broker.close_connection(&session.mux, "Agent disconnected").await;
+        session.mux.close_all("Agent disconnected");
+
         Ok(())
     }
 
@@ -152,6 +215,7 @@ impl WebSocketServer {
     async fn process_message(
         &self,
         write: &mut futures_util::stream::SplitSink<WebSocketStream<TcpStream>, Message>,
+        session: &mut Session,
         text: &str,
     ) -> Result<()> {
         info!("Received text message: {:?}", &text);
@@ -170,7 +234,9 @@ impl WebSocketServer {
                             let msg: RegisterWithSecretPayload = serde_json::from_value(payload.clone())?;
                             info!("RegisterWithSecret payload: agent_name={}, hostname={}, capabilities={:?}",
                                   msg.agent_name, msg.hostname, msg.capabilities);
-                            self.handle_register(write, msg).await?;
+                            if let Some(agent_id) = self.handle_register(write, msg).await? {
+                                self.attach_agent(session, agent_id).await;
+                            }
                         } else {
                             info!("RegisterWithSecret missing payload field");
                             let response = ServerMessage::Error(ErrorPayload {
@@ -201,7 +267,7 @@ impl WebSocketServer {
                             let msg: HeartbeatPayload = serde_json::from_value(payload.clone())?;
                             info!("Heartbeat payload: status={}, timestamp={}, latency_ms={:?}",
                                   msg.status, msg.timestamp, msg.metrics.latency_ms);
-                            self.handle_heartbeat(write, msg).await?;
+                            self.handle_heartbeat(write, session, msg).await?;
                         } else {
                             info!("Heartbeat missing payload field");
                             let response = ServerMessage::Error(ErrorPayload {
@@ -226,6 +292,52 @@ impl WebSocketServer {
                             self.send_response(write, &response).await?;
                         }
                     }
+                    Some(msg_type @ ("TaskOutput" | "TaskResult")) => {
+                        let (Some(agent_id), Some(payload)) = (session.agent_id, json.get("payload")) else {
+                            info!("{} from an unregistered connection or without payload", msg_type);
+                            let response = ServerMessage::Error(ErrorPayload {
+                                code: "INVALID_MESSAGE".to_string(),
+                                message: format!("{} requires a registered agent and a 'payload' field", msg_type),
+                            });
+                            self.send_response(write, &response).await?;
+                            return Ok(());
+                        };
+                        if msg_type == "TaskOutput" {
+                            let chunk: TaskOutputChunk = serde_json::from_value(payload.clone())?;
+                            self.service.task_service.append_output(agent_id, &chunk).await?;
+                        } else {
+                            let report: TaskResultReport = serde_json::from_value(payload.clone())?;
+                            info!("TaskResult payload: task_id={}, success={}, status={:?}",
+                                  report.task_id, report.success, report.status);
+                            self.service.task_service.complete_task(agent_id, &report).await?;
+                        }
+                    }
+                    Some(msg_type @ ("TunnelRequest" | "TunnelClose" | "ForwardReport")) => {
+                        let Some(agent_id) = session.agent_id else {
+                            info!("{} from an unregistered connection", msg_type);
+                            let response = ServerMessage::Error(ErrorPayload {
+                                code: "INVALID_MESSAGE".to_string(),
+                                message: format!("{} requires a registered agent", msg_type),
+                            });
+                            self.send_response(write, &response).await?;
+                            return Ok(());
+                        };
+                        let msg: TunnelMessage = serde_json::from_value(json)?;
+                        if let TunnelMessage::ForwardReport { forwards } = msg {
+                            let agent_name = match self.service.agent_service.get_agent(agent_id).await {
+                                Ok(Some(agent)) => agent.name,
+                                _ => agent_id.to_string(),
+                            };
+                            self.service.tunnel_broker.report_forwards(agent_id, agent_name, &session.mux, forwards).await;
+                            return Ok(());
+                        }
+                        let response = self.service.tunnel_broker.handle_message(agent_id, &session.mux, msg).await;
+                        if let Some(response) = response {
+                            let json = serde_json::to_string(&response)?;
+                            info!("Sending response: {:?}", &json);
+                            write.send(Message::Text(json)).await?;
+                        }
+                    }
                     Some(t) => {
                         info!("Unknown message type received: {}", t);
                         let response = ServerMessage::Error(ErrorPayload {
@@ -269,16 +381,37 @@ impl WebSocketServer {
         Ok(())
     }
 
-    /// Handle RegisterWithSecret message
+    /// Registers the connection of an agent, assigns its ingress tunnels and
+    /// dispatches its pending tasks
+    async fn attach_agent(&self, session: &mut Session, agent_id: Uuid) {
+        let channels = self.service.task_service.channels();
+        if let Some(previous) = session.agent_id.replace(agent_id) {
+            channels.unregister(previous, &session.tx).await;
+        }
+        channels.register(agent_id, session.tx.clone()).await;
+        self.service.tunnel_broker.attach(agent_id, &session.mux).await;
+        for assignment in self.service.ingress_service.assignments(agent_id).await {
+            if let Ok(json) = serde_json::to_string(&assignment) {
+                let _ = session.tx.send(json);
+            }
+        }
+        match self.service.task_service.dispatch_pending(agent_id).await {
+            Ok(0) => {}
+            Ok(count) => info!("Dispatched {} pending tasks to agent {}", count, agent_id),
+            Err(e) => error!("Failed to dispatch pending tasks to agent {}: {}", agent_id, e),
+        }
+    }
+
+    /// Handle RegisterWithSecret message, returns the agent ID when accepted
     async fn handle_register(
         &self,
         write: &mut futures_util::stream::SplitSink<WebSocketStream<TcpStream>, Message>,
         msg: RegisterWithSecretPayload,
-    ) -> Result<()> {
+    ) -> Result<Option<Uuid>> {
         info!("Processing RegisterWithSecret: agent_name={}, hostname={}", msg.agent_name, msg.hostname);
 
-        // Determine the agent_id to use
-        let agent_id = match &msg.agent_id {
+        // Determine the agent_id to use and whether the agent is already known
+        let (agent_id, existing) = match &msg.agent_id {
             Some(agent_id_str) => {
                 if let Ok(agent_id) = Uuid::parse_str(agent_id_str) {
                     match self.service.agent_service.get_agent(agent_id).await {
@@ -290,59 +423,75 @@ impl WebSocketServer {
                                     reason: "Agent was denied by administrator".to_string(),
                                 });
                                 self.send_response(write, &response).await?;
-                                return Ok(());
+                                return Ok(None);
                             }
                             // Agent exists with non-denied state, use existing ID
                             info!("Agent {} exists with approval_state={}, proceeding", agent_id, agent.approval_state);
-                            agent_id
+                            (agent_id, true)
                         }
                         Ok(None) => {
                             // Agent doesn't exist, use the provided ID for new registration
                             info!("Agent {} not found, will create new record with provided ID", agent_id);
-                            agent_id
+                            (agent_id, false)
                         }
                         Err(e) => {
                             // Query failed, generate new ID
                             warn!("Failed to query agent {}: {}, generating new ID", agent_id, e);
-                            Uuid::new_v4()
+                            (Uuid::new_v4(), false)
                         }
                     }
                 } else {
                     // Invalid UUID format, generate new
                     warn!("Invalid agent_id format: {}, generating new ID", agent_id_str);
-                    Uuid::new_v4()
+                    (Uuid::new_v4(), false)
                 }
             }
             None => {
                 // No agent_id provided, generate new
                 info!("No agent_id provided, generating new ID");
-                Uuid::new_v4()
+                (Uuid::new_v4(), false)
             }
         };
 
         let now = Utc::now();
 
-        // Create agent input
-        let input = crate::service::agent::CreateAgentInput {
-            id: agent_id,
-            name: msg.agent_name.clone(),
-            endpoint: format!("ws://{}", msg.hostname),
-            status: "pending".to_string(),
-            approval_state: "pending".to_string(),
-            capabilities: serde_json::json!(msg.capabilities).into(),
-            cert_fingerprint: None,
-            auth_method: "secret".to_string(),
-            version: msg.version,
-            registered_at: Some(now),
-            last_seen_at: Some(now),
+        // A known agent reconnecting keeps its record
+        let result = if existing {
+            info!("Updating agent with id={}, name={}", agent_id, msg.agent_name);
+            let input = crate::service::agent::UpdateAgentInput {
+                name: Some(msg.agent_name.clone()),
+                endpoint: Some(format!("ws://{}", msg.hostname)),
+                capabilities: Some(serde_json::json!(msg.capabilities)),
+                version: msg.version,
+                last_seen_at: Some(now),
+                ..Default::default()
+            };
+            self.service
+                .agent_service
+                .update_agent(agent_id, input)
+                .await
+                .and_then(|agent| agent.ok_or_else(|| sea_orm::DbErr::RecordNotFound(agent_id.to_string())))
+        } else {
+            info!("Creating agent with id={}, name={}", agent_id, msg.agent_name);
+            let input = crate::service::agent::CreateAgentInput {
+                id: agent_id,
+                name: msg.agent_name.clone(),
+                endpoint: format!("ws://{}", msg.hostname),
+                status: "pending".to_string(),
+                approval_state: "pending".to_string(),
+                capabilities: serde_json::json!(msg.capabilities).into(),
+                cert_fingerprint: None,
+                auth_method: "secret".to_string(),
+                version: msg.version,
+                registered_at: Some(now),
+                last_seen_at: Some(now),
+            };
+            self.service.agent_service.create_agent(input).await
         };
 
-        info!("Creating agent with id={}, name={}", agent_id, msg.agent_name);
-
-        // Create agent via AgentService
-        match self.service.agent_service.create_agent(input).await {
+        match result {
             Ok(_agent_info) => {
-                info!("Agent created successfully: id={}, name={}", agent_id, msg.agent_name);
+                info!("Agent registered successfully: id={}, name={}", agent_id, msg.agent_name);
 
                 // Record lifecycle event
                 let event = LifecycleEvent::new(
@@ -373,6 +522,7 @@ impl WebSocketServer {
                     message: Some("Registration successful".to_string()),
                 });
                 self.send_response(write, &response).await?;
+                return Ok(Some(agent_id));
             }
             Err(e) => {
                 error!("Failed to create agent {}: {}", msg.agent_name, e);
@@ -385,7 +535,7 @@ impl WebSocketServer {
             }
         }
 
-        Ok(())
+        Ok(None)
     }
 
     /// Handle SystemInfoReport message
@@ -491,12 +641,12 @@ impl WebSocketServer {
     async fn handle_heartbeat(
         &self,
         write: &mut futures_util::stream::SplitSink<WebSocketStream<TcpStream>, Message>,
+        session: &Session,
         msg: HeartbeatPayload,
     ) -> Result<()> {
         info!("Processing Heartbeat: status={}, timestamp={}", msg.status, msg.timestamp);
 
         // Extract metrics for health score calculation
-        // Note: Without agent_id, we can't record health score
         let network_metrics = crate::service::health::NetworkHealthMetrics {
             latency_ms: msg.metrics.latency_ms,
             jitter_ms: msg.metrics.jitter_ms,
@@ -508,8 +658,37 @@ impl WebSocketServer {
               network_metrics.latency_ms, network_metrics.jitter_ms,
               network_metrics.packet_loss_percent, network_metrics.bandwidth_kbps);
 
-        // Note: Without agent_id, we can't update last_seen_at or record health score
-        // The agent would need to send its ID in the heartbeat or we need another way to identify it
+        // Heartbeats sent before registration can't be attributed to an agent
+        if let Some(agent_id) = session.agent_id {
+            let now = Utc::now();
+            let input = crate::service::agent::UpdateAgentInput {
+                name: None,
+                endpoint: None,
+                status: None,
+                approval_state: None,
+                capabilities: None,
+                cert_fingerprint: None,
+                auth_method: None,
+                version: None,
+                registered_at: None,
+                last_seen_at: Some(now),
+            };
+            if let Err(e) = self.service.agent_service.update_agent(agent_id, input).await {
+                error!("Failed to update last_seen_at for agent {}: {}", agent_id, e);
+            }
+            if let Err(e) = self.service.health_service.record_health_score(agent_id, &network_metrics).await {
+                error!("Failed to record health score for agent {}: {}", agent_id, e);
+            }
+            self.service.events.publish(BusEvent::Heartbeat(HeartbeatEvent {
+                event_id: Uuid::new_v4(),
+                agent_id,
+                status: msg.status,
+                metrics: network_metrics,
+                timestamp: now,
+            }));
+        } else {
+            warn!("Heartbeat received before registration, ignoring metrics");
+        }
 
         // Send heartbeat acknowledgment
         let response = ServerMessage::RegisterAccepted(RegisterAcceptedPayload {
@@ -550,6 +729,16 @@ impl WebSocketServer {
     }
 }
 
+/// State of a single agent connection
+struct Session {
+    /// Set once the agent has registered
+    agent_id: Option<Uuid>,
+    /// Sender for messages queued by the TaskService
+    tx: mpsc::UnboundedSender<String>,
+    /// Streams of the agent's tunnels
+    mux: Multiplexer,
+}
+
 // Message payload types for deserialization (extracted from 'payload' field)
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -0,0 +1,408 @@
+//! Operator audit log service
+//!
+//! This module provides the AuditService for recording which user performed
+//! which management action, through which API and from where, together with
+//! the state of the target before and after the action. Entries can be
+//! queried with filters and pagination or exported as JSON Lines.
+
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+use sea_orm::{
+    ActiveModelTrait, EntityTrait, PaginatorTrait, QueryOrder, QuerySelect, Select, Set,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tracing::error;
+use uuid::Uuid;
+
+use crate::domain::rbac::Role;
+use crate::storage::entities::audit_log::{ActiveModel, Column, Entity as AuditLogEntity, Model};
+use crate::storage::Database;
+
+/// Maximum number of entries returned by one query.
+pub const MAX_PAGE_SIZE: u64 = 500;
+
+/// Number of entries loaded at a time while exporting.
+const EXPORT_BATCH_SIZE: u64 = 1000;
+
+/// API a request came through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditSource {
+    Rest,
+    Grpc,
+}
+
+impl AuditSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditSource::Rest => "rest",
+            AuditSource::Grpc => "grpc",
+        }
+    }
+}
+
+/// Audited management actions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditAction {
+    Login,
+    Logout,
+    UpdateAgent,
+    DeleteAgent,
+    ApproveAgent,
+    DenyAgent,
+    GetSystemInfo,
+    QuerySystemInfo,
+    SubmitTask,
+    CancelTask,
+    CreateUser,
+    UpdateUser,
+    DeleteUser,
+    CreateIngressRoute,
+    DeleteIngressRoute,
+    StartForward,
+    StopForward,
+}
+
+impl AuditAction {
+    /// Name of the action as stored in the database.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::Login => "auth.login",
+            AuditAction::Logout => "auth.logout",
+            AuditAction::UpdateAgent => "agent.update",
+            AuditAction::DeleteAgent => "agent.delete",
+            AuditAction::ApproveAgent => "agent.approve",
+            AuditAction::DenyAgent => "agent.deny",
+            AuditAction::GetSystemInfo => "agent.system_info.get",
+            AuditAction::QuerySystemInfo => "agent.system_info.query",
+            AuditAction::SubmitTask => "task.submit",
+            AuditAction::CancelTask => "task.cancel",
+            AuditAction::CreateUser => "user.create",
+            AuditAction::UpdateUser => "user.update",
+            AuditAction::DeleteUser => "user.delete",
+            AuditAction::CreateIngressRoute => "ingress.create",
+            AuditAction::DeleteIngressRoute => "ingress.delete",
+            AuditAction::StartForward => "agent.forward.start",
+            AuditAction::StopForward => "agent.forward.stop",
+        }
+    }
+
+    /// Kind of object the action is performed on.
+    pub fn target_type(&self) -> &'static str {
+        match self {
+            AuditAction::Login
+            | AuditAction::Logout
+            | AuditAction::CreateUser
+            | AuditAction::UpdateUser
+            | AuditAction::DeleteUser => "user",
+            // Submitting targets the agent, the new task is in the `after` state
+            AuditAction::UpdateAgent
+            | AuditAction::DeleteAgent
+            | AuditAction::ApproveAgent
+            | AuditAction::DenyAgent
+            | AuditAction::GetSystemInfo
+            | AuditAction::QuerySystemInfo
+            | AuditAction::SubmitTask
+            | AuditAction::StartForward
+            | AuditAction::StopForward => "agent",
+            AuditAction::CancelTask => "task",
+            AuditAction::CreateIngressRoute | AuditAction::DeleteIngressRoute => "ingress_route",
+        }
+    }
+}
+
+impl std::fmt::Display for AuditAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Who sent a request and from where.
+#[derive(Debug, Clone)]
+pub struct AuditContext {
+    pub actor: String,
+    pub actor_role: Option<Role>,
+    pub source: AuditSource,
+    pub remote_addr: Option<String>,
+}
+
+/// An audit entry to record.
+#[derive(Debug, Clone)]
+pub struct NewAuditEntry {
+    pub context: AuditContext,
+    pub action: AuditAction,
+    pub target_id: Option<String>,
+    pub error: Option<String>,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+impl NewAuditEntry {
+    /// Creates a successful entry without before/after state.
+    pub fn new(context: AuditContext, action: AuditAction, target_id: impl ToString) -> Self {
+        Self {
+            context,
+            action,
+            target_id: Some(target_id.to_string()),
+            error: None,
+            before: None,
+            after: None,
+        }
+    }
+
+    /// Sets the state of the target before the action.
+    pub fn with_before<T: Serialize>(mut self, before: &T) -> Self {
+        self.before = to_state(before);
+        self
+    }
+
+    /// Sets the state of the target after the action.
+    pub fn with_after<T: Serialize>(mut self, after: &T) -> Self {
+        self.after = to_state(after);
+        self
+    }
+
+    /// Marks the action as failed.
+    pub fn with_error(mut self, error: impl ToString) -> Self {
+        self.error = Some(error.to_string());
+        self
+    }
+
+    /// Sets the outcome of an action on a target that may not exist: the
+    /// new state on success, otherwise `not_found` or the error.
+    pub fn with_result<T: Serialize, E: std::fmt::Display>(
+        self,
+        result: &Result<Option<T>, E>,
+        not_found: &str,
+    ) -> Self {
+        match result {
+            Ok(Some(after)) => self.with_after(after),
+            Ok(None) => self.with_error(not_found),
+            Err(e) => self.with_error(e),
+        }
+    }
+}
+
+/// Serializes a target state, `None` and unserializable values become no state.
+fn to_state<T: Serialize>(value: &T) -> Option<Value> {
+    serde_json::to_value(value)
+        .ok()
+        .filter(|value| !value.is_null())
+}
+
+/// Fields that differ between two states, as `{"field": {"before": .., "after": ..}}`.
+///
+/// Returns `None` unless both states are JSON objects.
+pub fn diff(before: Option<&Value>, after: Option<&Value>) -> Option<Value> {
+    let (Some(Value::Object(before)), Some(Value::Object(after))) = (before, after) else {
+        return None;
+    };
+    let mut changes = Map::new();
+    let mut keys: Vec<&String> = before.keys().chain(after.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    for key in keys {
+        let old = before.get(key).unwrap_or(&Value::Null);
+        let new = after.get(key).unwrap_or(&Value::Null);
+        if old != new {
+            changes.insert(
+                key.clone(),
+                serde_json::json!({ "before": old, "after": new }),
+            );
+        }
+    }
+    Some(Value::Object(changes))
+}
+
+/// Filters for querying the audit log, unset filters match everything.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AuditFilter {
+    pub actor: Option<String>,
+    pub action: Option<String>,
+    pub target_type: Option<String>,
+    pub target_id: Option<String>,
+    pub source: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl AuditFilter {
+    fn apply(&self, mut query: Select<AuditLogEntity>) -> Select<AuditLogEntity> {
+        if let Some(actor) = &self.actor {
+            query = query.filter(Column::Actor.eq(actor));
+        }
+        if let Some(action) = &self.action {
+            // "agent" matches all agent actions, "agent.approve" only approvals
+            query = query.filter(
+                Column::Action
+                    .eq(action)
+                    .or(Column::Action.starts_with(format!("{}.", action))),
+            );
+        }
+        if let Some(target_type) = &self.target_type {
+            query = query.filter(Column::TargetType.eq(target_type));
+        }
+        if let Some(target_id) = &self.target_id {
+            query = query.filter(Column::TargetId.eq(target_id));
+        }
+        if let Some(source) = &self.source {
+            query = query.filter(Column::Source.eq(source));
+        }
+        if let Some(since) = self.since {
+            query = query.filter(Column::Timestamp.gte(since));
+        }
+        if let Some(until) = self.until {
+            query = query.filter(Column::Timestamp.lt(until));
+        }
+        query
+    }
+}
+
+/// Audit entry returned by queries and exports.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEntryInfo {
+    pub id: Uuid,
+    pub timestamp: DateTime<Utc>,
+    pub actor: String,
+    pub actor_role: Option<String>,
+    pub action: String,
+    pub target_type: String,
+    pub target_id: Option<String>,
+    pub source: String,
+    pub remote_addr: Option<String>,
+    pub success: bool,
+    pub error: Option<String>,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+    /// Fields changed by the action, computed from `before` and `after`
+    pub changes: Option<Value>,
+}
+
+impl From<Model> for AuditEntryInfo {
+    fn from(model: Model) -> Self {
+        let changes = diff(model.before.as_ref(), model.after.as_ref());
+        Self {
+            id: model.id,
+            timestamp: model.timestamp,
+            actor: model.actor,
+            actor_role: model.actor_role,
+            action: model.action,
+            target_type: model.target_type,
+            target_id: model.target_id,
+            source: model.source,
+            remote_addr: model.remote_addr,
+            success: model.success,
+            error: model.error,
+            before: model.before,
+            after: model.after,
+            changes,
+        }
+    }
+}
+
+/// One page of audit entries, newest first.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditPage {
+    pub entries: Vec<AuditEntryInfo>,
+    /// Number of entries matching the filter
+    pub total: u64,
+    pub limit: u64,
+    pub offset: u64,
+}
+
+/// Service for the operator audit log.
+#[derive(Clone, Debug)]
+pub struct AuditService {
+    db: Database,
+}
+
+impl AuditService {
+    /// Creates a new AuditService with the given database.
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// Records an audit entry.
+    ///
+    /// Failures are logged instead of returned so that a broken audit log
+    /// never fails the audited request.
+    pub async fn record(&self, entry: NewAuditEntry) {
+        let action = entry.action;
+        let active_model = ActiveModel {
+            id: Set(Uuid::new_v4()),
+            timestamp: Set(Utc::now()),
+            actor: Set(entry.context.actor),
+            actor_role: Set(entry
+                .context
+                .actor_role
+                .map(|role| role.as_str().to_string())),
+            action: Set(action.as_str().to_string()),
+            target_type: Set(action.target_type().to_string()),
+            target_id: Set(entry.target_id),
+            source: Set(entry.context.source.as_str().to_string()),
+            remote_addr: Set(entry.context.remote_addr),
+            success: Set(entry.error.is_none()),
+            error: Set(entry.error),
+            before: Set(entry.before),
+            after: Set(entry.after),
+        };
+        if let Err(e) = active_model.insert(self.db.get_conn()).await {
+            error!("Failed to record audit entry for {}: {}", action, e);
+        }
+    }
+
+    /// Query audit entries matching the filter, newest first.
+    pub async fn query(
+        &self,
+        filter: &AuditFilter,
+        limit: u64,
+        offset: u64,
+    ) -> Result<AuditPage, sea_orm::DbErr> {
+        let limit = limit.clamp(1, MAX_PAGE_SIZE);
+        let total = filter
+            .apply(AuditLogEntity::find())
+            .count(self.db.get_conn())
+            .await?;
+        let entries = filter
+            .apply(AuditLogEntity::find())
+            .order_by_desc(Column::Timestamp)
+            .offset(offset)
+            .limit(limit)
+            .all(self.db.get_conn())
+            .await?;
+
+        Ok(AuditPage {
+            entries: entries.into_iter().map(AuditEntryInfo::from).collect(),
+            total,
+            limit,
+            offset,
+        })
+    }
+
+    /// Export all audit entries matching the filter as JSON Lines, oldest first.
+    pub async fn export_jsonl(&self, filter: &AuditFilter) -> Result<String, sea_orm::DbErr> {
+        let mut output = String::new();
+        let mut pages = filter
+            .apply(AuditLogEntity::find())
+            .order_by_asc(Column::Timestamp)
+            .order_by_asc(Column::Id)
+            .paginate(self.db.get_conn(), EXPORT_BATCH_SIZE);
+        while let Some(entries) = pages.fetch_and_next().await? {
+            for entry in entries {
+                let line = serde_json::to_string(&AuditEntryInfo::from(entry)).map_err(|e| {
+                    DbErr::Custom(format!("Failed to serialize audit entry: {}", e))
+                })?;
+                output.push_str(&line);
+                output.push('\n');
+            }
+        }
+        Ok(output)
+    }
+}
+
+impl Default for AuditService {
+    fn default() -> Self {
+        panic!("AuditService::default() is not supported, use AuditService::new(db)")
+    }
+}
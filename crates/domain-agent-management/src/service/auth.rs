@@ -0,0 +1,412 @@
+//! Authentication and user management service
+//!
+//! This module provides the AuthService for password login, token
+//! authentication and user accounts. Login issues an opaque random bearer
+//! token; only its SHA-256 hash is stored so a leaked database cannot be used
+//! to impersonate users.
+
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use chrono::{DateTime, Duration, Utc};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use sea_orm::entity::prelude::*;
+use sea_orm::{ActiveModelTrait, EntityTrait, PaginatorTrait, QueryOrder, Set};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::config::AuthConfig;
+use crate::domain::rbac::Role;
+use crate::storage::entities::auth_token::{
+    ActiveModel as AuthTokenActiveModel, Column as AuthTokenColumn, Entity as AuthTokenEntity,
+};
+use crate::storage::entities::user::{ActiveModel, Column, Entity as UserEntity, Model};
+use crate::storage::Database;
+
+/// Minimum length of user passwords.
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+
+/// Number of random bytes in a token.
+const TOKEN_BYTES: usize = 32;
+
+/// Errors returned by the authentication service.
+#[derive(Debug, Error)]
+pub enum AuthError {
+    #[error("Invalid username or password")]
+    InvalidCredentials,
+
+    #[error("Missing, invalid or expired token")]
+    InvalidToken,
+
+    #[error("User not found")]
+    UserNotFound,
+
+    #[error("User already exists: {0}")]
+    UserExists(String),
+
+    #[error("Password must be at least {MIN_PASSWORD_LENGTH} characters")]
+    WeakPassword,
+
+    #[error("At least one enabled admin must remain")]
+    LastAdmin,
+
+    #[error("Failed to hash password: {0}")]
+    PasswordHash(String),
+
+    #[error(transparent)]
+    Database(#[from] DbErr),
+}
+
+/// User information returned by the service, without the password hash.
+#[derive(Debug, Clone, Serialize)]
+pub struct UserInfo {
+    pub id: Uuid,
+    pub username: String,
+    pub role: Role,
+    pub disabled: bool,
+    pub last_login_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl TryFrom<Model> for UserInfo {
+    type Error = DbErr;
+
+    fn try_from(model: Model) -> Result<Self, Self::Error> {
+        let role = model
+            .role
+            .parse()
+            .map_err(|e| DbErr::Custom(format!("{} for user {}", e, model.username)))?;
+        Ok(Self {
+            id: model.id,
+            username: model.username,
+            role,
+            disabled: model.disabled,
+            last_login_at: model.last_login_at,
+            created_at: model.created_at,
+            updated_at: model.updated_at,
+        })
+    }
+}
+
+/// Result of a successful login.
+#[derive(Debug, Clone, Serialize)]
+pub struct LoginResult {
+    /// Bearer token to send in the `Authorization` header
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+    pub user: UserInfo,
+}
+
+/// Input for creating a user.
+#[derive(Debug, Clone)]
+pub struct CreateUserInput {
+    pub username: String,
+    pub password: String,
+    pub role: Role,
+}
+
+/// Input for updating a user, unset fields are left unchanged.
+#[derive(Debug, Clone, Default)]
+pub struct UpdateUserInput {
+    pub password: Option<String>,
+    pub role: Option<Role>,
+    pub disabled: Option<bool>,
+}
+
+/// Hashes a password with Argon2id, returning a PHC string.
+pub fn hash_password(password: &str) -> Result<String, AuthError> {
+    if password.chars().count() < MIN_PASSWORD_LENGTH {
+        return Err(AuthError::WeakPassword);
+    }
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| AuthError::PasswordHash(e.to_string()))
+}
+
+/// Verifies a password against a PHC string created by `hash_password`.
+pub fn verify_password(password: &str, password_hash: &str) -> bool {
+    PasswordHash::new(password_hash)
+        .map(|hash| {
+            Argon2::default()
+                .verify_password(password.as_bytes(), &hash)
+                .is_ok()
+        })
+        .unwrap_or(false)
+}
+
+/// Generates a new random token, hex encoded.
+pub fn generate_token() -> String {
+    let mut bytes = [0u8; TOKEN_BYTES];
+    OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+/// Hash of a token as stored in the database.
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// Extracts the token from an `Authorization: Bearer <token>` header value.
+pub fn parse_bearer(header: &str) -> Option<&str> {
+    let (scheme, token) = header.trim().split_once(' ')?;
+    let token = token.trim();
+    (scheme.eq_ignore_ascii_case("bearer") && !token.is_empty()).then_some(token)
+}
+
+/// Service for users, login and token authentication.
+#[derive(Clone, Debug)]
+pub struct AuthService {
+    db: Database,
+    token_ttl: Duration,
+}
+
+impl AuthService {
+    /// Creates a new AuthService with the given database and configuration.
+    pub fn new(db: Database, config: &AuthConfig) -> Self {
+        Self {
+            db,
+            token_ttl: Duration::hours(config.token_ttl_hours.max(1) as i64),
+        }
+    }
+
+    /// Creates the initial admin user when there are no users yet.
+    ///
+    /// The password comes from the configuration; when it is not set a random
+    /// password is generated and logged once so the operator can log in.
+    pub async fn ensure_admin(&self, config: &AuthConfig) -> Result<(), AuthError> {
+        if UserEntity::find().count(self.db.get_conn()).await? > 0 {
+            return Ok(());
+        }
+
+        let password = match &config.admin_password {
+            Some(password) => password.clone(),
+            None => {
+                let password = generate_token()[..16].to_string();
+                warn!(
+                    "No users exist, created admin user '{}' with generated password '{}', change it after logging in",
+                    config.admin_username, password
+                );
+                password
+            }
+        };
+        self.create_user(CreateUserInput {
+            username: config.admin_username.clone(),
+            password,
+            role: Role::Admin,
+        })
+        .await?;
+        info!("Created initial admin user '{}'", config.admin_username);
+        Ok(())
+    }
+
+    /// Checks a username and password and issues a new token.
+    pub async fn login(&self, username: &str, password: &str) -> Result<LoginResult, AuthError> {
+        let user = UserEntity::find()
+            .filter(Column::Username.eq(username))
+            .one(self.db.get_conn())
+            .await?
+            .ok_or(AuthError::InvalidCredentials)?;
+        if user.disabled || !verify_password(password, &user.password_hash) {
+            return Err(AuthError::InvalidCredentials);
+        }
+
+        let now = Utc::now();
+        // Drop the user's expired tokens so the table does not grow forever
+        AuthTokenEntity::delete_many()
+            .filter(AuthTokenColumn::UserId.eq(user.id))
+            .filter(AuthTokenColumn::ExpiresAt.lte(now))
+            .exec(self.db.get_conn())
+            .await?;
+
+        let token = generate_token();
+        let expires_at = now + self.token_ttl;
+        AuthTokenActiveModel {
+            id: Set(Uuid::new_v4()),
+            user_id: Set(user.id),
+            token_hash: Set(hash_token(&token)),
+            expires_at: Set(expires_at),
+            created_at: Set(now),
+        }
+        .insert(self.db.get_conn())
+        .await?;
+
+        let mut active_model: ActiveModel = user.into();
+        active_model.last_login_at = Set(Some(now));
+        let user = active_model.update(self.db.get_conn()).await?;
+
+        Ok(LoginResult {
+            token,
+            expires_at,
+            user: user.try_into()?,
+        })
+    }
+
+    /// Returns the user a token was issued to.
+    pub async fn authenticate(&self, token: &str) -> Result<UserInfo, AuthError> {
+        let stored = AuthTokenEntity::find()
+            .filter(AuthTokenColumn::TokenHash.eq(hash_token(token)))
+            .filter(AuthTokenColumn::ExpiresAt.gt(Utc::now()))
+            .one(self.db.get_conn())
+            .await?
+            .ok_or(AuthError::InvalidToken)?;
+        let user = UserEntity::find_by_id(stored.user_id)
+            .one(self.db.get_conn())
+            .await?
+            .ok_or(AuthError::InvalidToken)?;
+        if user.disabled {
+            return Err(AuthError::InvalidToken);
+        }
+        Ok(user.try_into()?)
+    }
+
+    /// Revokes a token, returns false when it did not exist.
+    pub async fn logout(&self, token: &str) -> Result<bool, AuthError> {
+        let result = AuthTokenEntity::delete_many()
+            .filter(AuthTokenColumn::TokenHash.eq(hash_token(token)))
+            .exec(self.db.get_conn())
+            .await?;
+        Ok(result.rows_affected > 0)
+    }
+
+    /// List all users.
+    pub async fn list_users(&self) -> Result<Vec<UserInfo>, AuthError> {
+        let users = UserEntity::find()
+            .order_by_asc(Column::Username)
+            .all(self.db.get_conn())
+            .await?;
+        Ok(users
+            .into_iter()
+            .map(UserInfo::try_from)
+            .collect::<Result<_, _>>()?)
+    }
+
+    /// Get a user by ID.
+    pub async fn get_user(&self, user_id: Uuid) -> Result<Option<UserInfo>, AuthError> {
+        let user = UserEntity::find_by_id(user_id)
+            .one(self.db.get_conn())
+            .await?;
+        Ok(user.map(UserInfo::try_from).transpose()?)
+    }
+
+    /// Create a new user.
+    pub async fn create_user(&self, input: CreateUserInput) -> Result<UserInfo, AuthError> {
+        let username = input.username.trim().to_string();
+        let exists = UserEntity::find()
+            .filter(Column::Username.eq(username.as_str()))
+            .count(self.db.get_conn())
+            .await?
+            > 0;
+        if exists {
+            return Err(AuthError::UserExists(username));
+        }
+
+        let now = Utc::now();
+        let user = ActiveModel {
+            id: Set(Uuid::new_v4()),
+            username: Set(username),
+            password_hash: Set(hash_password(&input.password)?),
+            role: Set(input.role.as_str().to_string()),
+            disabled: Set(false),
+            last_login_at: Set(None),
+            created_at: Set(now),
+            updated_at: Set(now),
+        }
+        .insert(self.db.get_conn())
+        .await?;
+        Ok(user.try_into()?)
+    }
+
+    /// Update a user by ID.
+    ///
+    /// Changing the password or disabling the user revokes all of the user's tokens.
+    pub async fn update_user(
+        &self,
+        user_id: Uuid,
+        input: UpdateUserInput,
+    ) -> Result<UserInfo, AuthError> {
+        let user = UserEntity::find_by_id(user_id)
+            .one(self.db.get_conn())
+            .await?
+            .ok_or(AuthError::UserNotFound)?;
+
+        let loses_admin =
+            input.role.is_some_and(|role| role != Role::Admin) || input.disabled == Some(true);
+        if loses_admin && user.role == Role::Admin.as_str() && !user.disabled {
+            self.ensure_other_admin(user_id).await?;
+        }
+
+        let revoke = input.password.is_some() || input.disabled == Some(true);
+        let mut active_model: ActiveModel = user.into();
+        if let Some(password) = input.password {
+            active_model.password_hash = Set(hash_password(&password)?);
+        }
+        if let Some(role) = input.role {
+            active_model.role = Set(role.as_str().to_string());
+        }
+        if let Some(disabled) = input.disabled {
+            active_model.disabled = Set(disabled);
+        }
+        active_model.updated_at = Set(Utc::now());
+        let user = active_model.update(self.db.get_conn()).await?;
+
+        if revoke {
+            self.revoke_tokens(user_id).await?;
+        }
+        Ok(user.try_into()?)
+    }
+
+    /// Delete a user by ID along with the user's tokens.
+    pub async fn delete_user(&self, user_id: Uuid) -> Result<bool, AuthError> {
+        let user = match UserEntity::find_by_id(user_id)
+            .one(self.db.get_conn())
+            .await?
+        {
+            Some(user) => user,
+            None => return Ok(false),
+        };
+        if user.role == Role::Admin.as_str() && !user.disabled {
+            self.ensure_other_admin(user_id).await?;
+        }
+
+        self.revoke_tokens(user_id).await?;
+        let active_model: ActiveModel = user.into();
+        active_model.delete(self.db.get_conn()).await?;
+        Ok(true)
+    }
+
+    /// Revokes all tokens of a user.
+    async fn revoke_tokens(&self, user_id: Uuid) -> Result<(), AuthError> {
+        AuthTokenEntity::delete_many()
+            .filter(AuthTokenColumn::UserId.eq(user_id))
+            .exec(self.db.get_conn())
+            .await?;
+        Ok(())
+    }
+
+    /// Fails unless an enabled admin other than `user_id` exists, so nobody
+    /// can lock everyone out of user management.
+    async fn ensure_other_admin(&self, user_id: Uuid) -> Result<(), AuthError> {
+        let others = UserEntity::find()
+            .filter(Column::Role.eq(Role::Admin.as_str()))
+            .filter(Column::Disabled.eq(false))
+            .filter(Column::Id.ne(user_id))
+            .count(self.db.get_conn())
+            .await?;
+        if others == 0 {
+            return Err(AuthError::LastAdmin);
+        }
+        Ok(())
+    }
+}
+
+impl Default for AuthService {
+    fn default() -> Self {
+        panic!("AuthService::default() is not supported, use AuthService::new(db, config)")
+    }
+}
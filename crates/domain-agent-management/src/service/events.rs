@@ -0,0 +1,171 @@
+//! Internal event bus
+//!
+//! This module provides the EventBus that lifecycle events, health scores
+//! and agent heartbeats are published to as they are recorded. The gRPC
+//! streaming methods and the REST Server-Sent Events route subscribe to it
+//! to push updates to clients instead of having them poll.
+
+use chrono::{DateTime, Utc};
+use domain_agent_protocol::lifecycle::LifecycleEvent;
+use futures_util::Stream;
+use serde::Serialize;
+use tokio::sync::broadcast;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::service::health::NetworkHealthMetrics;
+use crate::storage::entities::health_score;
+
+/// Number of events buffered for each subscriber before it starts missing events.
+pub const DEFAULT_CAPACITY: usize = 1024;
+
+/// Event type of health score updates.
+pub const HEALTH_SCORE_EVENT: &str = "health_score";
+
+/// Event type of agent heartbeats.
+pub const HEARTBEAT_EVENT: &str = "heartbeat";
+
+/// Heartbeat received from a registered agent.
+#[derive(Debug, Clone, Serialize)]
+pub struct HeartbeatEvent {
+    pub event_id: Uuid,
+    pub agent_id: Uuid,
+    pub status: String,
+    pub metrics: NetworkHealthMetrics,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// An event published on the bus.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", content = "data", rename_all = "snake_case")]
+pub enum BusEvent {
+    Lifecycle(LifecycleEvent),
+    Health(health_score::Model),
+    Heartbeat(HeartbeatEvent),
+}
+
+impl BusEvent {
+    pub fn event_id(&self) -> Uuid {
+        match self {
+            BusEvent::Lifecycle(event) => event.event_id,
+            BusEvent::Health(score) => score.id,
+            BusEvent::Heartbeat(heartbeat) => heartbeat.event_id,
+        }
+    }
+
+    pub fn agent_id(&self) -> Uuid {
+        match self {
+            BusEvent::Lifecycle(event) => event.agent_id,
+            BusEvent::Health(score) => score.agent_id,
+            BusEvent::Heartbeat(heartbeat) => heartbeat.agent_id,
+        }
+    }
+
+    /// The lifecycle event type such as `agent_connected`, or `health_score` or `heartbeat`.
+    pub fn event_type(&self) -> String {
+        match self {
+            BusEvent::Lifecycle(event) => event.event_type.to_string(),
+            BusEvent::Health(_) => HEALTH_SCORE_EVENT.to_string(),
+            BusEvent::Heartbeat(_) => HEARTBEAT_EVENT.to_string(),
+        }
+    }
+
+    pub fn timestamp(&self) -> DateTime<Utc> {
+        match self {
+            BusEvent::Lifecycle(event) => event.timestamp,
+            BusEvent::Health(score) => score.scored_at,
+            BusEvent::Heartbeat(heartbeat) => heartbeat.timestamp,
+        }
+    }
+}
+
+/// Selects the events a subscriber receives, unset filters match everything.
+#[derive(Debug, Clone, Default)]
+pub struct EventFilter {
+    pub agent_id: Option<Uuid>,
+    /// Event types to receive, empty for all
+    pub event_types: Vec<String>,
+}
+
+impl EventFilter {
+    /// Filter for the events of one agent, or of all agents.
+    pub fn new(agent_id: Option<Uuid>) -> Self {
+        Self {
+            agent_id,
+            event_types: Vec::new(),
+        }
+    }
+
+    /// Only receive events of the given types.
+    pub fn with_event_types(mut self, event_types: impl IntoIterator<Item = String>) -> Self {
+        self.event_types = event_types
+            .into_iter()
+            .map(|event_type| event_type.trim().to_string())
+            .filter(|event_type| !event_type.is_empty())
+            .collect();
+        self
+    }
+
+    pub fn matches(&self, event: &BusEvent) -> bool {
+        if self
+            .agent_id
+            .is_some_and(|agent_id| agent_id != event.agent_id())
+        {
+            return false;
+        }
+        self.event_types.is_empty() || self.event_types.contains(&event.event_type())
+    }
+}
+
+/// Broadcasts agent events to all subscribers.
+///
+/// Publishing never blocks: a subscriber that falls more than the capacity
+/// behind skips the oldest events.
+#[derive(Clone, Debug)]
+pub struct EventBus {
+    sender: broadcast::Sender<BusEvent>,
+}
+
+impl EventBus {
+    /// Creates an event bus buffering `capacity` events per subscriber.
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        Self { sender }
+    }
+
+    /// Publishes an event to the current subscribers.
+    pub fn publish(&self, event: BusEvent) {
+        // An error only means nobody is subscribed
+        let _ = self.sender.send(event);
+    }
+
+    /// Number of current subscribers.
+    pub fn subscriber_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+
+    /// Subscribes to the events matching the filter that are published from now on.
+    pub fn subscribe(&self, filter: EventFilter) -> impl Stream<Item = BusEvent> + Send + 'static {
+        let receiver = self.sender.subscribe();
+        futures_util::stream::unfold((receiver, filter), |(mut receiver, filter)| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) if filter.matches(&event) => {
+                        return Some((event, (receiver, filter)))
+                    }
+                    Ok(_) => continue,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("Event subscriber lagged behind, skipped {} events", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        })
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
@@ -7,6 +7,7 @@ use sea_orm::{ActiveModelTrait, EntityTrait, ColumnTrait, QueryFilter, QueryOrde
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::service::events::{BusEvent, EventBus};
 use crate::storage::entities::health_score::{self, ActiveModel as HealthScoreActiveModel};
 
 /// Network health metrics used for health score calculation.
@@ -179,15 +180,17 @@ pub fn calculate_component_scores(metrics: &NetworkHealthMetrics) -> ComponentSc
 #[derive(Clone, Debug)]
 pub struct HealthService {
     db: sea_orm::DatabaseConnection,
+    events: EventBus,
 }
 
 impl HealthService {
-    /// Creates a new HealthService with the given database connection.
-    pub fn new(db: sea_orm::DatabaseConnection) -> Self {
-        Self { db }
+    /// Creates a new HealthService with the given database connection,
+    /// publishing recorded scores to `events`.
+    pub fn new(db: sea_orm::DatabaseConnection, events: EventBus) -> Self {
+        Self { db, events }
     }
 
-    /// Records a health score for an agent.
+    /// Records a health score for an agent and publishes it.
     pub async fn record_health_score(
         &self,
         agent_id: Uuid,
@@ -208,7 +211,9 @@ impl HealthService {
             component_scores: Set(Some(serde_json::to_value(component_scores).unwrap().into())),
         };
 
-        active_model.insert(&self.db).await
+        let score = active_model.insert(&self.db).await?;
+        self.events.publish(BusEvent::Health(score.clone()));
+        Ok(score)
     }
 
     /// Gets the latest health score for an agent.
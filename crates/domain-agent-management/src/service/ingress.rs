@@ -0,0 +1,281 @@
+//! Hostname-based ingress routes
+//!
+//! This module provides the IngressService, which maps public hostnames to a
+//! `host:port` target behind an agent. Routes are stored in the database and
+//! cached in memory for the ingress listeners. Every route is a tunnel
+//! defined by the Hub: its id is the tunnel id, and the agent is told where
+//! to forward the tunnel's streams with a `TunnelAssign` message whenever it
+//! connects or the route is created.
+
+use chrono::{DateTime, Utc};
+use domain_agent_protocol::mux::MuxStream;
+use domain_agent_protocol::tunnel::{TunnelMessage, TunnelType};
+use sea_orm::entity::prelude::*;
+use sea_orm::{ActiveModelTrait, EntityTrait, PaginatorTrait, QueryOrder, Set};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use thiserror::Error;
+use tokio::sync::RwLock;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::service::task::AgentChannels;
+use crate::service::tunnel::TunnelBroker;
+use crate::storage::entities::agent::Entity as AgentEntity;
+use crate::storage::entities::ingress_route::{
+    ActiveModel, Column, Entity as IngressRouteEntity, Model,
+};
+use crate::storage::Database;
+
+/// Errors returned when creating an ingress route.
+#[derive(Debug, Error)]
+pub enum IngressError {
+    #[error("Invalid hostname: {0}")]
+    InvalidHostname(String),
+
+    #[error("Invalid target, expected host:port: {0}")]
+    InvalidTarget(String),
+
+    #[error("A route for {0} already exists")]
+    HostnameTaken(String),
+
+    #[error("Agent not found: {0}")]
+    AgentNotFound(Uuid),
+
+    #[error(transparent)]
+    Database(#[from] DbErr),
+}
+
+/// Input for creating an ingress route.
+#[derive(Debug, Clone)]
+pub struct CreateIngressRouteInput {
+    pub hostname: String,
+    pub agent_id: Uuid,
+    /// Address the agent forwards the requests to, e.g. `127.0.0.1:8080`
+    pub target: String,
+    /// Pass TLS connections through to the target instead of plain HTTP
+    pub tls: bool,
+    pub created_by: Option<String>,
+}
+
+/// Ingress route returned by the service.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IngressRouteInfo {
+    pub id: Uuid,
+    pub hostname: String,
+    pub agent_id: Uuid,
+    pub target: String,
+    pub tls: bool,
+    pub created_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<Model> for IngressRouteInfo {
+    fn from(model: Model) -> Self {
+        Self {
+            id: model.id,
+            hostname: model.hostname,
+            agent_id: model.agent_id,
+            target: model.target,
+            tls: model.tls,
+            created_by: model.created_by,
+            created_at: model.created_at,
+        }
+    }
+}
+
+impl IngressRouteInfo {
+    /// Message telling the agent to forward the route's streams to its target.
+    pub fn assignment(&self) -> TunnelMessage {
+        TunnelMessage::TunnelAssign {
+            tunnel_id: self.id,
+            target: self.target.clone(),
+            tunnel_type: if self.tls {
+                TunnelType::Tcp
+            } else {
+                TunnelType::Http
+            },
+        }
+    }
+}
+
+/// Service for managing and resolving ingress routes.
+#[derive(Clone, Debug)]
+pub struct IngressService {
+    db: Database,
+    /// Routes by hostname
+    routes: Arc<RwLock<HashMap<String, IngressRouteInfo>>>,
+    broker: TunnelBroker,
+    channels: AgentChannels,
+}
+
+impl IngressService {
+    /// Creates a new IngressService, call [`IngressService::load`] before resolving routes.
+    pub fn new(db: Database, broker: TunnelBroker, channels: AgentChannels) -> Self {
+        Self {
+            db,
+            routes: Arc::new(RwLock::new(HashMap::new())),
+            broker,
+            channels,
+        }
+    }
+
+    /// Loads the stored routes into the cache, returns how many there are.
+    pub async fn load(&self) -> Result<usize, DbErr> {
+        let routes: HashMap<String, IngressRouteInfo> = IngressRouteEntity::find()
+            .all(self.db.get_conn())
+            .await?
+            .into_iter()
+            .map(|model| (model.hostname.clone(), model.into()))
+            .collect();
+        let count = routes.len();
+        *self.routes.write().await = routes;
+        Ok(count)
+    }
+
+    /// Creates a route and assigns it to the agent if it is connected.
+    pub async fn create_route(
+        &self,
+        input: CreateIngressRouteInput,
+    ) -> Result<IngressRouteInfo, IngressError> {
+        let hostname = normalize_hostname(&input.hostname)
+            .ok_or_else(|| IngressError::InvalidHostname(input.hostname.clone()))?;
+        let target = input.target.trim().to_string();
+        if !is_valid_target(&target) {
+            return Err(IngressError::InvalidTarget(input.target));
+        }
+        AgentEntity::find_by_id(input.agent_id)
+            .one(self.db.get_conn())
+            .await?
+            .ok_or(IngressError::AgentNotFound(input.agent_id))?;
+        let exists = IngressRouteEntity::find()
+            .filter(Column::Hostname.eq(hostname.as_str()))
+            .count(self.db.get_conn())
+            .await?
+            > 0;
+        if exists {
+            return Err(IngressError::HostnameTaken(hostname));
+        }
+
+        let route: IngressRouteInfo = ActiveModel {
+            id: Set(Uuid::new_v4()),
+            hostname: Set(hostname),
+            agent_id: Set(input.agent_id),
+            target: Set(target),
+            tls: Set(input.tls),
+            created_by: Set(input.created_by),
+            created_at: Set(Utc::now()),
+        }
+        .insert(self.db.get_conn())
+        .await?
+        .into();
+        info!(
+            "Ingress route {} created: {} -> agent {} {}",
+            route.id, route.hostname, route.agent_id, route.target
+        );
+
+        self.routes
+            .write()
+            .await
+            .insert(route.hostname.clone(), route.clone());
+        self.notify(route.agent_id, &route.assignment()).await;
+        Ok(route)
+    }
+
+    /// Lists all routes ordered by hostname.
+    pub async fn list_routes(&self) -> Result<Vec<IngressRouteInfo>, DbErr> {
+        let routes = IngressRouteEntity::find()
+            .order_by_asc(Column::Hostname)
+            .all(self.db.get_conn())
+            .await?;
+        Ok(routes.into_iter().map(Into::into).collect())
+    }
+
+    /// Gets a route by ID.
+    pub async fn get_route(&self, route_id: Uuid) -> Result<Option<IngressRouteInfo>, DbErr> {
+        let route = IngressRouteEntity::find_by_id(route_id)
+            .one(self.db.get_conn())
+            .await?;
+        Ok(route.map(Into::into))
+    }
+
+    /// Deletes a route and closes its tunnel, returns the deleted route.
+    pub async fn delete_route(&self, route_id: Uuid) -> Result<Option<IngressRouteInfo>, DbErr> {
+        let Some(model) = IngressRouteEntity::find_by_id(route_id)
+            .one(self.db.get_conn())
+            .await?
+        else {
+            return Ok(None);
+        };
+        let active_model: ActiveModel = model.clone().into();
+        active_model.delete(self.db.get_conn()).await?;
+
+        let route = IngressRouteInfo::from(model);
+        self.routes.write().await.remove(&route.hostname);
+        let close = TunnelMessage::TunnelClose {
+            tunnel_id: route.id,
+            reason: Some("Ingress route deleted".to_string()),
+        };
+        self.notify(route.agent_id, &close).await;
+        info!("Ingress route {} deleted: {}", route.id, route.hostname);
+        Ok(Some(route))
+    }
+
+    /// Tunnel assignments of all routes of an agent, sent when it connects.
+    pub async fn assignments(&self, agent_id: Uuid) -> Vec<TunnelMessage> {
+        self.routes
+            .read()
+            .await
+            .values()
+            .filter(|route| route.agent_id == agent_id)
+            .map(IngressRouteInfo::assignment)
+            .collect()
+    }
+
+    /// Finds the route of a hostname, as sent in a Host header or SNI.
+    pub async fn resolve(&self, hostname: &str) -> Option<IngressRouteInfo> {
+        let hostname = normalize_hostname(hostname)?;
+        self.routes.read().await.get(&hostname).cloned()
+    }
+
+    /// Opens a stream to the route's target, None when the agent is not connected.
+    pub async fn open_stream(&self, route: &IngressRouteInfo) -> Option<MuxStream> {
+        self.broker.open_stream(route.agent_id, route.id).await
+    }
+
+    async fn notify(&self, agent_id: Uuid, msg: &TunnelMessage) {
+        if let Ok(json) = serde_json::to_string(msg) {
+            self.channels.send(agent_id, json).await;
+        }
+    }
+}
+
+/// Lowercases a hostname and removes a trailing dot, None if it is not a valid DNS name.
+pub fn normalize_hostname(hostname: &str) -> Option<String> {
+    let hostname = hostname.trim().trim_end_matches('.').to_ascii_lowercase();
+    let valid_label = |label: &str| {
+        (1..=63).contains(&label.len())
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'-')
+    };
+    (hostname.len() <= 253 && hostname.split('.').all(valid_label)).then_some(hostname)
+}
+
+/// Whether a target is a `host:port` address, IPv6 hosts in brackets.
+fn is_valid_target(target: &str) -> bool {
+    let Some((host, port)) = target.rsplit_once(':') else {
+        return false;
+    };
+    let valid_host = match host
+        .strip_prefix('[')
+        .and_then(|host| host.strip_suffix(']'))
+    {
+        Some(ipv6) => ipv6.parse::<std::net::Ipv6Addr>().is_ok(),
+        None => normalize_hostname(host).is_some(),
+    };
+    valid_host && port.parse::<u16>().is_ok_and(|port| port > 0)
+}
@@ -1,7 +1,8 @@
 //! Lifecycle event recording service
 //!
 //! This module provides the LifecycleService for recording and querying
-//! lifecycle events in the database.
+//! lifecycle events in the database. Recorded events are published to the
+//! EventBus.
 
 use domain_agent_protocol::lifecycle::{EventSource, LifecycleEvent, LifecycleEventType};
 use sea_orm::ActiveModelTrait;
@@ -9,6 +10,7 @@ use sea_orm::entity::prelude::*;
 use sea_orm::{Set, QueryOrder, EntityTrait, JsonValue};
 use uuid::Uuid;
 
+use crate::service::events::{BusEvent, EventBus};
 use crate::storage::Database;
 use crate::storage::entities::lifecycle_event::{Entity as LifecycleEventEntity, ActiveModel, Model as LifecycleEventModel};
 
@@ -19,15 +21,17 @@ use crate::storage::entities::lifecycle_event::{Entity as LifecycleEventEntity,
 #[derive(Clone, Debug)]
 pub struct LifecycleService {
     db: Database,
+    events: EventBus,
 }
 
 impl LifecycleService {
-    /// Creates a new LifecycleService with the given database connection.
-    pub fn new(db: Database) -> Self {
-        Self { db }
+    /// Creates a new LifecycleService with the given database connection,
+    /// publishing recorded events to `events`.
+    pub fn new(db: Database, events: EventBus) -> Self {
+        Self { db, events }
     }
 
-    /// Records a lifecycle event to the database.
+    /// Records a lifecycle event to the database and publishes it.
     ///
     /// # Arguments
     ///
@@ -56,6 +60,7 @@ impl LifecycleService {
         };
 
         active_model.insert(self.db.get_conn()).await?;
+        self.events.publish(BusEvent::Lifecycle(event.clone()));
 
         Ok(event_id)
     }
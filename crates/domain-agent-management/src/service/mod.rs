@@ -1,23 +1,38 @@
 //! Service module for agent management
 //!
 //! Contains business logic services including lifecycle event management,
-//! health scoring, agent management, and diagnostics.
+//! health scoring, agent management, diagnostics, remote tasks, user
+//! authentication, the operator audit log, the event bus that feeds
+//! the streaming APIs, the reverse tunnel broker and the ingress routes.
 
 pub mod agent;
+pub mod audit;
+pub mod auth;
 pub mod diagnostic;
+pub mod events;
 pub mod health;
+pub mod ingress;
 pub mod lifecycle;
+pub mod task;
+pub mod tunnel;
 
 pub use agent::{AgentService, AgentInfo};
+pub use audit::AuditService;
+pub use auth::{AuthError, AuthService, UserInfo};
 pub use diagnostic::DiagnosticService;
+pub use events::{BusEvent, EventBus, EventFilter};
 pub use health::{HealthService, NetworkHealthMetrics};
+pub use ingress::{IngressRouteInfo, IngressService};
 pub use lifecycle::LifecycleService;
+pub use task::{AgentChannels, TaskService};
+pub use tunnel::{TunnelBroker, TunnelInfo};
 
 use crate::config::AppConfig;
 use crate::server::grpc::create_grpc_server;
 use crate::server::rest::{create_rest_server, AppState, RestConfig};
 use crate::storage::Database;
 use anyhow::Result;
+use std::net::SocketAddr;
 use tonic::transport::Server as TonicServer;
 use tracing::{info, error};
 
@@ -28,6 +43,12 @@ pub struct Service {
     pub lifecycle_service: LifecycleService,
     pub health_service: HealthService,
     pub diagnostic_service: DiagnosticService,
+    pub task_service: TaskService,
+    pub auth_service: AuthService,
+    pub audit_service: AuditService,
+    pub tunnel_broker: TunnelBroker,
+    pub ingress_service: IngressService,
+    pub events: EventBus,
     pub database: Database,
     pub config: AppConfig,
 }
@@ -35,7 +56,8 @@ pub struct Service {
 impl Service {
     /// Creates a new Service instance with all subsystems initialized
     pub async fn new(config: AppConfig) -> Result<Self> {
-        info!("Initializing Agent Management Service...:{:?}", &config.database.connection_url());
+        // Log the configured URL, the connection URL may contain the password
+        info!("Initializing Agent Management Service...:{:?}", &config.database.url);
 
         // Initialize database
         let database = Database::new(&config.database.connection_url()).await?;
@@ -43,10 +65,23 @@ impl Service {
         info!("Database initialized and migrations completed");
 
         // Initialize services
+        let events = EventBus::default();
         let agent_service = AgentService::new(database.clone());
-        let lifecycle_service = LifecycleService::new(database.clone());
-        let health_service = HealthService::new(database.get_conn().clone());
+        let lifecycle_service = LifecycleService::new(database.clone(), events.clone());
+        let health_service = HealthService::new(database.get_conn().clone(), events.clone());
         let diagnostic_service = DiagnosticService::new(database.clone());
+        let task_service = TaskService::new(database.clone(), AgentChannels::default());
+        let auth_service = AuthService::new(database.clone(), &config.auth);
+        auth_service.ensure_admin(&config.auth).await?;
+        let audit_service = AuditService::new(database.clone());
+        let tunnel_broker = TunnelBroker::new(config.tunnel.clone());
+        let ingress_service = IngressService::new(
+            database.clone(),
+            tunnel_broker.clone(),
+            task_service.channels().clone(),
+        );
+        let routes = ingress_service.load().await?;
+        info!("Loaded {} ingress routes", routes);
 
         info!("All services initialized successfully");
 
@@ -55,12 +90,18 @@ impl Service {
             lifecycle_service,
             health_service,
             diagnostic_service,
+            task_service,
+            auth_service,
+            audit_service,
+            tunnel_broker,
+            ingress_service,
+            events,
             database,
             config,
         })
     }
 
-    /// Runs all servers (gRPC, REST, WebSocket) concurrently
+    /// Runs all servers (gRPC, REST, WebSocket, ingress) concurrently
     pub async fn run(self) -> Result<()> {
         let config = self.config.clone();
 
@@ -83,6 +124,9 @@ impl Service {
         // Clone self for WebSocket server before moving into grpc_handle
         let ws_service = self.clone();
 
+        // Start the ingress listeners, if configured
+        crate::server::ingress::run_ingress(self.ingress_service.clone(), config.ingress.clone()).await;
+
         // Spawn gRPC server
         let grpc_handle = tokio::spawn(async move {
             let grpc_server = create_grpc_server(&grpc_addr, self.clone()).await
@@ -109,7 +153,7 @@ impl Service {
                 .await
                 .expect("Failed to bind REST server");
 
-            axum::serve(listener, app)
+            axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
                 .await
                 .expect("REST server failed");
         });
@@ -0,0 +1,512 @@
+//! Remote task service
+//!
+//! This module provides the TaskService for submitting tasks to agents,
+//! dispatching them over the agents' WebSocket connections and storing their
+//! output, results and audit events.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use domain_agent_protocol::task::{
+    TaskAssignment, TaskCancellation, TaskEvent, TaskEventType, TaskOutputChunk, TaskResultReport,
+    TaskStatus, TaskType,
+};
+use sea_orm::entity::prelude::*;
+use sea_orm::{ActiveModelTrait, EntityTrait, QueryOrder, QuerySelect, Set};
+use serde::Serialize;
+use tokio::sync::{mpsc, RwLock};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::storage::entities::task::{ActiveModel, Entity as TaskEntity, Model};
+use crate::storage::entities::task_event::{
+    ActiveModel as TaskEventActiveModel, Entity as TaskEventEntity,
+};
+use crate::storage::Database;
+
+/// Maximum output stored per task, older output is dropped.
+pub const MAX_STORED_OUTPUT: usize = 1024 * 1024;
+
+/// Outgoing message channels of the connected agents.
+///
+/// The WebSocket server registers a channel when an agent registers and
+/// removes it when the connection closes.
+#[derive(Clone, Debug, Default)]
+pub struct AgentChannels {
+    channels: Arc<RwLock<HashMap<Uuid, mpsc::UnboundedSender<String>>>>,
+}
+
+impl AgentChannels {
+    /// Registers the message channel of a connected agent.
+    pub async fn register(&self, agent_id: Uuid, tx: mpsc::UnboundedSender<String>) {
+        self.channels.write().await.insert(agent_id, tx);
+    }
+
+    /// Removes the message channel of a disconnected agent.
+    ///
+    /// The channel is only removed if it was not replaced by a newer connection.
+    pub async fn unregister(&self, agent_id: Uuid, tx: &mpsc::UnboundedSender<String>) {
+        let mut channels = self.channels.write().await;
+        if channels
+            .get(&agent_id)
+            .is_some_and(|current| current.same_channel(tx))
+        {
+            channels.remove(&agent_id);
+        }
+    }
+
+    /// Whether the agent is connected.
+    pub async fn is_connected(&self, agent_id: Uuid) -> bool {
+        self.channels.read().await.contains_key(&agent_id)
+    }
+
+    /// Sends a message to an agent, returns false when it is not connected.
+    pub async fn send(&self, agent_id: Uuid, msg: String) -> bool {
+        match self.channels.read().await.get(&agent_id) {
+            Some(tx) => tx.send(msg).is_ok(),
+            None => false,
+        }
+    }
+}
+
+/// Input for submitting a task.
+#[derive(Debug, Clone)]
+pub struct SubmitTaskInput {
+    pub agent_id: Uuid,
+    pub task: TaskType,
+    /// 0 uses the agent's default timeout
+    pub timeout_seconds: u32,
+    pub submitted_by: Option<String>,
+}
+
+/// Task information returned by get_task and list_tasks.
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskInfo {
+    pub id: Uuid,
+    pub agent_id: Uuid,
+    pub task: TaskType,
+    pub status: TaskStatus,
+    pub timeout_seconds: u32,
+    pub output: String,
+    pub error: Option<String>,
+    pub exit_code: Option<i32>,
+    pub duration_ms: Option<u64>,
+    pub submitted_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub dispatched_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+impl TryFrom<Model> for TaskInfo {
+    type Error = sea_orm::DbErr;
+
+    fn try_from(model: Model) -> Result<Self, Self::Error> {
+        let task = serde_json::from_value(model.payload)
+            .map_err(|e| DbErr::Custom(format!("Invalid task payload: {}", e)))?;
+        let status = TaskStatus::parse(&model.status)
+            .ok_or_else(|| DbErr::Custom(format!("Unknown task status: {}", model.status)))?;
+        Ok(Self {
+            id: model.id,
+            agent_id: model.agent_id,
+            task,
+            status,
+            timeout_seconds: model.timeout_seconds.max(0) as u32,
+            output: model.output,
+            error: model.error,
+            exit_code: model.exit_code,
+            duration_ms: model.duration_ms.map(|ms| ms.max(0) as u64),
+            submitted_by: model.submitted_by,
+            created_at: model.created_at,
+            dispatched_at: model.dispatched_at,
+            finished_at: model.finished_at,
+        })
+    }
+}
+
+impl TaskInfo {
+    /// State recorded in the audit log, without the output and the content
+    /// of uploaded files.
+    pub fn audit_state(&self) -> serde_json::Value {
+        let mut task = self.task.clone();
+        if let TaskType::FileUpload { content_base64, .. } = &mut task {
+            *content_base64 = format!("<{} base64 characters>", content_base64.len());
+        }
+        let mut state = serde_json::json!(self);
+        if let Some(fields) = state.as_object_mut() {
+            fields.remove("output");
+            fields.insert("task".to_string(), serde_json::json!(task));
+        }
+        state
+    }
+}
+
+/// Service for remote tasks.
+///
+/// Tasks for disconnected agents stay pending and are dispatched when the
+/// agent registers again. Every state change is recorded as a task event.
+#[derive(Clone, Debug)]
+pub struct TaskService {
+    db: Database,
+    channels: AgentChannels,
+}
+
+impl TaskService {
+    /// Creates a new TaskService with the given database and agent channels.
+    pub fn new(db: Database, channels: AgentChannels) -> Self {
+        Self { db, channels }
+    }
+
+    /// The channels of the connected agents.
+    pub fn channels(&self) -> &AgentChannels {
+        &self.channels
+    }
+
+    /// Stores a new task and dispatches it if the agent is connected.
+    pub async fn submit_task(&self, input: SubmitTaskInput) -> Result<TaskInfo, sea_orm::DbErr> {
+        let payload = serde_json::to_value(&input.task)
+            .map_err(|e| DbErr::Custom(format!("Failed to serialize task: {}", e)))?;
+        let active_model = ActiveModel {
+            id: Set(Uuid::new_v4()),
+            agent_id: Set(input.agent_id),
+            task_type: Set(input.task.kind().to_string()),
+            payload: Set(payload),
+            status: Set(TaskStatus::Pending.to_string()),
+            timeout_seconds: Set(input.timeout_seconds.min(i32::MAX as u32) as i32),
+            output: Set(String::new()),
+            error: Set(None),
+            exit_code: Set(None),
+            duration_ms: Set(None),
+            submitted_by: Set(input.submitted_by.clone()),
+            created_at: Set(Utc::now()),
+            dispatched_at: Set(None),
+            finished_at: Set(None),
+        };
+        let task = active_model.insert(self.db.get_conn()).await?;
+        info!(
+            "Task {} ({}) submitted for agent {}",
+            task.id, task.task_type, task.agent_id
+        );
+
+        let mut event = TaskEvent::new(task.id, task.agent_id, TaskEventType::TaskSubmitted)
+            .with_metadata(serde_json::json!({
+                "task_type": task.task_type,
+                "timeout_seconds": task.timeout_seconds,
+            }));
+        if let Some(submitted_by) = &input.submitted_by {
+            event = event.with_triggered_by(submitted_by.clone());
+        }
+        self.record_event(&event).await?;
+
+        let task = self.dispatch(task).await?;
+        task.try_into()
+    }
+
+    /// Dispatches the pending tasks of an agent, returns how many were sent.
+    ///
+    /// Cancellations requested while the agent was offline are sent again.
+    pub async fn dispatch_pending(&self, agent_id: Uuid) -> Result<usize, sea_orm::DbErr> {
+        use crate::storage::entities::task::Column;
+
+        let cancelling: Vec<Model> = TaskEntity::find()
+            .filter(Column::AgentId.eq(agent_id))
+            .filter(Column::Status.eq(TaskStatus::Cancelling.to_string()))
+            .all(self.db.get_conn())
+            .await?;
+        for task in cancelling {
+            self.send_cancellation(
+                agent_id,
+                task.id,
+                "Cancel requested while disconnected".to_string(),
+            )
+            .await;
+        }
+
+        let pending: Vec<Model> = TaskEntity::find()
+            .filter(Column::AgentId.eq(agent_id))
+            .filter(Column::Status.eq(TaskStatus::Pending.to_string()))
+            .order_by_asc(Column::CreatedAt)
+            .all(self.db.get_conn())
+            .await?;
+
+        let mut dispatched = 0;
+        for task in pending {
+            if self.dispatch(task).await?.status == TaskStatus::Dispatched.to_string() {
+                dispatched += 1;
+            }
+        }
+        Ok(dispatched)
+    }
+
+    /// Sends a pending task to its agent, the task stays pending if the agent is offline.
+    async fn dispatch(&self, task: Model) -> Result<Model, sea_orm::DbErr> {
+        let task_type: TaskType = serde_json::from_value(task.payload.clone())
+            .map_err(|e| DbErr::Custom(format!("Invalid task payload: {}", e)))?;
+        let assignment = TaskAssignment {
+            task_id: task.id,
+            task_type,
+            params: serde_json::Value::Null,
+            timeout_seconds: task.timeout_seconds.max(0) as u32,
+        };
+        let msg = serde_json::json!({ "type": "TaskAssigned", "payload": assignment }).to_string();
+        if !self.channels.send(task.agent_id, msg).await {
+            return Ok(task);
+        }
+        info!("Task {} dispatched to agent {}", task.id, task.agent_id);
+
+        let mut active_model: ActiveModel = task.into();
+        active_model.status = Set(TaskStatus::Dispatched.to_string());
+        active_model.dispatched_at = Set(Some(Utc::now()));
+        let task = active_model.update(self.db.get_conn()).await?;
+
+        let event = TaskEvent::new(task.id, task.agent_id, TaskEventType::TaskDispatched);
+        self.record_event(&event).await?;
+        Ok(task)
+    }
+
+    /// Get a task by ID.
+    pub async fn get_task(&self, task_id: Uuid) -> Result<Option<TaskInfo>, sea_orm::DbErr> {
+        TaskEntity::find_by_id(task_id)
+            .one(self.db.get_conn())
+            .await?
+            .map(TaskInfo::try_from)
+            .transpose()
+    }
+
+    /// Lists the latest tasks of an agent, newest first.
+    pub async fn list_tasks(
+        &self,
+        agent_id: Uuid,
+        limit: u64,
+    ) -> Result<Vec<TaskInfo>, sea_orm::DbErr> {
+        use crate::storage::entities::task::Column;
+
+        TaskEntity::find()
+            .filter(Column::AgentId.eq(agent_id))
+            .order_by_desc(Column::CreatedAt)
+            .limit(limit)
+            .all(self.db.get_conn())
+            .await?
+            .into_iter()
+            .map(TaskInfo::try_from)
+            .collect()
+    }
+
+    /// Cancels a task.
+    ///
+    /// A pending task is cancelled right away; a dispatched or running task is
+    /// marked as cancelling until the agent reports its result. Finished tasks
+    /// are returned unchanged.
+    pub async fn cancel_task(
+        &self,
+        task_id: Uuid,
+        reason: String,
+        triggered_by: Option<String>,
+    ) -> Result<Option<TaskInfo>, sea_orm::DbErr> {
+        let Some(task) = TaskEntity::find_by_id(task_id)
+            .one(self.db.get_conn())
+            .await?
+        else {
+            return Ok(None);
+        };
+        let status = TaskStatus::parse(&task.status);
+        if status.is_none_or(|status| status.is_finished() || status == TaskStatus::Cancelling) {
+            return task.try_into().map(Some);
+        }
+
+        let agent_id = task.agent_id;
+        let mut active_model: ActiveModel = task.into();
+        let event_type = if status == Some(TaskStatus::Pending) {
+            active_model.status = Set(TaskStatus::Cancelled.to_string());
+            active_model.error = Set(Some(format!("Cancelled: {}", reason)));
+            active_model.finished_at = Set(Some(Utc::now()));
+            TaskEventType::TaskCancelled
+        } else {
+            if !self
+                .send_cancellation(agent_id, task_id, reason.clone())
+                .await
+            {
+                warn!(
+                    "Agent {} is not connected, task {} is cancelled when it reconnects",
+                    agent_id, task_id
+                );
+            }
+            active_model.status = Set(TaskStatus::Cancelling.to_string());
+            TaskEventType::TaskCancelRequested
+        };
+        let task = active_model.update(self.db.get_conn()).await?;
+
+        let mut event = TaskEvent::new(task_id, agent_id, event_type).with_reason(reason);
+        if let Some(triggered_by) = triggered_by {
+            event = event.with_triggered_by(triggered_by);
+        }
+        self.record_event(&event).await?;
+        task.try_into().map(Some)
+    }
+
+    /// Sends a cancellation to an agent, returns false when it is not connected.
+    async fn send_cancellation(&self, agent_id: Uuid, task_id: Uuid, reason: String) -> bool {
+        let cancellation = TaskCancellation { task_id, reason };
+        let msg =
+            serde_json::json!({ "type": "TaskCancelled", "payload": cancellation }).to_string();
+        self.channels.send(agent_id, msg).await
+    }
+
+    /// Appends a chunk of streamed output to a task.
+    pub async fn append_output(
+        &self,
+        agent_id: Uuid,
+        chunk: &TaskOutputChunk,
+    ) -> Result<(), sea_orm::DbErr> {
+        let Some(task) = self.find_agent_task(agent_id, chunk.task_id).await? else {
+            return Ok(());
+        };
+        let status = TaskStatus::parse(&task.status);
+        if status.is_none_or(|status| status.is_finished()) {
+            return Ok(());
+        }
+
+        let output = append_truncated(&task.output, &chunk.data);
+        let mut active_model: ActiveModel = task.into();
+        active_model.output = Set(output);
+        if status == Some(TaskStatus::Dispatched) {
+            active_model.status = Set(TaskStatus::Running.to_string());
+        }
+        active_model.update(self.db.get_conn()).await?;
+        Ok(())
+    }
+
+    /// Stores the result reported by the agent and records the final event.
+    pub async fn complete_task(
+        &self,
+        agent_id: Uuid,
+        report: &TaskResultReport,
+    ) -> Result<Option<TaskInfo>, sea_orm::DbErr> {
+        let Some(task) = self.find_agent_task(agent_id, report.task_id).await? else {
+            return Ok(None);
+        };
+        if TaskStatus::parse(&task.status).is_none_or(|status| status.is_finished()) {
+            return task.try_into().map(Some);
+        }
+
+        let status = report.final_status();
+        // Streamed output is kept; tasks without a stream (file downloads) report it here
+        let output = if task.output.is_empty() {
+            append_truncated("", &report.output)
+        } else {
+            task.output.clone()
+        };
+        let mut active_model: ActiveModel = task.into();
+        active_model.status = Set(status.to_string());
+        active_model.output = Set(output);
+        active_model.error = Set(report.error.clone());
+        active_model.exit_code = Set(report.exit_code);
+        active_model.duration_ms = Set(Some(report.duration_ms.min(i64::MAX as u64) as i64));
+        active_model.finished_at = Set(Some(Utc::now()));
+        let task = active_model.update(self.db.get_conn()).await?;
+        info!(
+            "Task {} finished on agent {}: {}",
+            task.id, agent_id, status
+        );
+
+        if let Some(event_type) = TaskEventType::finished(status) {
+            let mut event = TaskEvent::new(task.id, agent_id, event_type)
+                .with_metadata(serde_json::json!({
+                    "exit_code": report.exit_code,
+                    "duration_ms": report.duration_ms,
+                }))
+                .with_triggered_by("agent");
+            if let Some(error) = &report.error {
+                event = event.with_reason(error.clone());
+            }
+            self.record_event(&event).await?;
+        }
+        task.try_into().map(Some)
+    }
+
+    /// Finds a task that belongs to the agent.
+    async fn find_agent_task(
+        &self,
+        agent_id: Uuid,
+        task_id: Uuid,
+    ) -> Result<Option<Model>, sea_orm::DbErr> {
+        let task = TaskEntity::find_by_id(task_id)
+            .one(self.db.get_conn())
+            .await?;
+        match task {
+            Some(task) if task.agent_id == agent_id => Ok(Some(task)),
+            Some(_) => {
+                warn!(
+                    "Agent {} reported task {} of another agent",
+                    agent_id, task_id
+                );
+                Ok(None)
+            }
+            None => {
+                warn!("Agent {} reported unknown task {}", agent_id, task_id);
+                Ok(None)
+            }
+        }
+    }
+
+    /// Records a task event to the database.
+    pub async fn record_event(&self, event: &TaskEvent) -> Result<Uuid, sea_orm::DbErr> {
+        let active_model = TaskEventActiveModel {
+            id: Set(event.event_id),
+            task_id: Set(event.task_id),
+            agent_id: Set(event.agent_id),
+            event_type: Set(event.event_type.to_string()),
+            timestamp: Set(event.timestamp),
+            reason: Set(event.reason.clone()),
+            metadata: Set(event.metadata.clone()),
+            triggered_by: Set(event.triggered_by.clone()),
+        };
+
+        active_model.insert(self.db.get_conn()).await?;
+
+        Ok(event.event_id)
+    }
+
+    /// Retrieves all events of a task ordered by timestamp.
+    pub async fn get_events(&self, task_id: Uuid) -> Result<Vec<TaskEvent>, sea_orm::DbErr> {
+        use crate::storage::entities::task_event::Column;
+
+        let events = TaskEventEntity::find()
+            .filter(Column::TaskId.eq(task_id))
+            .order_by_asc(Column::Timestamp)
+            .all(self.db.get_conn())
+            .await?;
+
+        events
+            .into_iter()
+            .map(|model| {
+                let event_type = TaskEventType::parse(&model.event_type).ok_or_else(|| {
+                    DbErr::Custom(format!("Unknown task event type: {}", model.event_type))
+                })?;
+                Ok(TaskEvent {
+                    event_id: model.id,
+                    task_id: model.task_id,
+                    agent_id: model.agent_id,
+                    event_type,
+                    timestamp: model.timestamp,
+                    reason: model.reason,
+                    metadata: model.metadata,
+                    triggered_by: model.triggered_by,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Appends `data` to `output`, keeping at most [`MAX_STORED_OUTPUT`] bytes of the end.
+pub fn append_truncated(output: &str, data: &str) -> String {
+    let mut output = format!("{}{}", output, data);
+    if output.len() > MAX_STORED_OUTPUT {
+        let mut cut = output.len() - MAX_STORED_OUTPUT;
+        while !output.is_char_boundary(cut) {
+            cut += 1;
+        }
+        output.drain(..cut);
+    }
+    output
+}
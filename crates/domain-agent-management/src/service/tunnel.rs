@@ -0,0 +1,434 @@
+//! Reverse tunnel broker
+//!
+//! This module provides the TunnelBroker, which listens on a public port for
+//! every tunnel an agent requests and bridges each accepted connection to a
+//! stream on the multiplexer of the agent's WebSocket. The agent forwards
+//! the stream to the tunnel's target, so services behind the agent's NAT
+//! become reachable through the Hub.
+//!
+//! The broker also tracks the current connection of every agent, so other
+//! services, e.g. the ingress, can open streams for tunnels the Hub defined.
+//!
+//! Agents report their named port forwards to the broker. Streams an agent
+//! opens for a `connect` forward are relayed to the `expose` forward of the
+//! agent named by its target, if that forward allows the connecting agent.
+
+use chrono::{DateTime, Utc};
+use domain_agent_protocol::mux::{Multiplexer, MuxStream, MAX_FRAME_DATA};
+use domain_agent_protocol::tunnel::{
+    parse_forward_target, ForwardDirection, ForwardStatus, TunnelMessage, TunnelType,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use thiserror::Error;
+use tokio::net::TcpListener;
+use tokio::sync::{mpsc, RwLock};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::config::TunnelConfig;
+use crate::server::accept_with_backoff;
+
+/// Errors returned when opening a tunnel.
+#[derive(Debug, Error)]
+pub enum TunnelError {
+    #[error("Tunnel {0} belongs to another agent")]
+    NotOwner(Uuid),
+
+    #[error("Public port {0} is outside the allowed range")]
+    PortNotAllowed(u16),
+
+    #[error("No free public port in the allowed range")]
+    NoFreePort,
+
+    #[error("Failed to listen on {0}: {1}")]
+    Bind(String, std::io::Error),
+}
+
+/// An open tunnel.
+#[derive(Debug, Clone, Serialize)]
+pub struct TunnelInfo {
+    pub tunnel_id: Uuid,
+    pub agent_id: Uuid,
+    /// Address the agent forwards connections to, as seen from the agent
+    pub target: String,
+    pub tunnel_type: TunnelType,
+    /// Address the Hub accepts connections on
+    pub public_addr: SocketAddr,
+    pub opened_at: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+struct Tunnel {
+    info: TunnelInfo,
+    mux: Multiplexer,
+    listener: JoinHandle<()>,
+}
+
+/// Port forwards last reported by a connected agent.
+#[derive(Debug)]
+struct AgentForwards {
+    agent_name: String,
+    mux: Multiplexer,
+    forwards: Vec<ForwardStatus>,
+}
+
+/// Service exposing agent tunnels on public ports.
+#[derive(Clone, Debug)]
+pub struct TunnelBroker {
+    config: TunnelConfig,
+    tunnels: Arc<RwLock<HashMap<Uuid, Tunnel>>>,
+    /// Multiplexer of the current connection of each registered agent
+    connections: Arc<RwLock<HashMap<Uuid, Multiplexer>>>,
+    forwards: Arc<RwLock<HashMap<Uuid, AgentForwards>>>,
+}
+
+impl TunnelBroker {
+    /// Creates a new TunnelBroker with the given configuration.
+    pub fn new(config: TunnelConfig) -> Self {
+        Self {
+            config,
+            tunnels: Arc::new(RwLock::new(HashMap::new())),
+            connections: Arc::new(RwLock::new(HashMap::new())),
+            forwards: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Records the connection of a registered agent, replacing an older one.
+    pub async fn attach(&self, agent_id: Uuid, mux: &Multiplexer) {
+        self.connections.write().await.insert(agent_id, mux.clone());
+    }
+
+    /// Opens a stream for a tunnel on the agent's current connection.
+    ///
+    /// Returns None when the agent is not connected.
+    pub async fn open_stream(&self, agent_id: Uuid, tunnel_id: Uuid) -> Option<MuxStream> {
+        let connections = self.connections.read().await;
+        Some(connections.get(&agent_id)?.open(tunnel_id))
+    }
+
+    /// Opens a tunnel for an agent and starts accepting connections on its public port.
+    ///
+    /// Requesting an open tunnel again, e.g. after the agent reconnected,
+    /// replaces it.
+    pub async fn open(
+        &self,
+        agent_id: Uuid,
+        mux: &Multiplexer,
+        tunnel_id: Uuid,
+        target: String,
+        tunnel_type: TunnelType,
+        public_port: Option<u16>,
+    ) -> Result<TunnelInfo, TunnelError> {
+        if let Some(existing) = self.tunnels.read().await.get(&tunnel_id) {
+            if existing.info.agent_id != agent_id {
+                return Err(TunnelError::NotOwner(tunnel_id));
+            }
+        }
+        self.close(tunnel_id, "Tunnel replaced").await;
+
+        let listener = self.bind(public_port).await?;
+        let public_addr = listener.local_addr().map_err(|e| {
+            TunnelError::Bind(format!("{}:{:?}", self.config.bind_host, public_port), e)
+        })?;
+        let info = TunnelInfo {
+            tunnel_id,
+            agent_id,
+            target,
+            tunnel_type,
+            public_addr,
+            opened_at: Utc::now(),
+        };
+        let listener = tokio::spawn(accept_connections(listener, tunnel_id, mux.clone()));
+        info!(
+            "Tunnel {} of agent {} listening on {} for {}",
+            tunnel_id, agent_id, public_addr, info.target
+        );
+        self.tunnels.write().await.insert(
+            tunnel_id,
+            Tunnel {
+                info: info.clone(),
+                mux: mux.clone(),
+                listener,
+            },
+        );
+        Ok(info)
+    }
+
+    /// Listens on the requested port, or on a free one of the allowed range.
+    async fn bind(&self, public_port: Option<u16>) -> Result<TcpListener, TunnelError> {
+        let (min_port, max_port) = (self.config.min_port, self.config.max_port);
+        let ranged = max_port > 0;
+        let ports: Vec<u16> = match public_port {
+            Some(port) if ranged && !(min_port..=max_port).contains(&port) => {
+                return Err(TunnelError::PortNotAllowed(port))
+            }
+            Some(port) => vec![port],
+            None if ranged => (min_port.max(1)..=max_port).collect(),
+            None => vec![0],
+        };
+
+        let mut last_error = None;
+        for port in &ports {
+            let addr = format!("{}:{}", self.config.bind_host, port);
+            match TcpListener::bind(&addr).await {
+                Ok(listener) => return Ok(listener),
+                Err(e) => last_error = Some(TunnelError::Bind(addr, e)),
+            }
+        }
+        match last_error {
+            Some(error) if ports.len() == 1 => Err(error),
+            _ => Err(TunnelError::NoFreePort),
+        }
+    }
+
+    /// Closes a tunnel and aborts its connections.
+    pub async fn close(&self, tunnel_id: Uuid, reason: &str) -> Option<TunnelInfo> {
+        let tunnel = self.tunnels.write().await.remove(&tunnel_id)?;
+        tunnel.listener.abort();
+        let streams = tunnel.mux.close_tunnel(tunnel_id, reason);
+        info!(
+            "Tunnel {} closed ({}), aborted {} connections",
+            tunnel_id, reason, streams
+        );
+        Some(tunnel.info)
+    }
+
+    /// Closes all tunnels carried by an agent connection, returns how many were open.
+    ///
+    /// Tunnels already taken over by a newer connection of the agent are kept.
+    pub async fn close_connection(&self, mux: &Multiplexer, reason: &str) -> usize {
+        self.connections
+            .write()
+            .await
+            .retain(|_, connection| !connection.same_connection(mux));
+        self.forwards
+            .write()
+            .await
+            .retain(|_, agent| !agent.mux.same_connection(mux));
+        let tunnel_ids: Vec<Uuid> = self
+            .tunnels
+            .read()
+            .await
+            .values()
+            .filter(|tunnel| tunnel.mux.same_connection(mux))
+            .map(|tunnel| tunnel.info.tunnel_id)
+            .collect();
+        for tunnel_id in &tunnel_ids {
+            self.close(*tunnel_id, reason).await;
+        }
+        tunnel_ids.len()
+    }
+
+    /// Lists the open tunnels, of one agent or of all agents, oldest first.
+    pub async fn list(&self, agent_id: Option<Uuid>) -> Vec<TunnelInfo> {
+        let mut tunnels: Vec<TunnelInfo> = self
+            .tunnels
+            .read()
+            .await
+            .values()
+            .filter(|tunnel| agent_id.is_none_or(|agent_id| tunnel.info.agent_id == agent_id))
+            .map(|tunnel| tunnel.info.clone())
+            .collect();
+        tunnels.sort_by_key(|tunnel| tunnel.opened_at);
+        tunnels
+    }
+
+    /// Records the port forwards reported by an agent connection.
+    pub async fn report_forwards(
+        &self,
+        agent_id: Uuid,
+        agent_name: String,
+        mux: &Multiplexer,
+        forwards: Vec<ForwardStatus>,
+    ) {
+        debug!("Agent {} reported {} forwards", agent_id, forwards.len());
+        self.forwards.write().await.insert(
+            agent_id,
+            AgentForwards {
+                agent_name,
+                mux: mux.clone(),
+                forwards,
+            },
+        );
+    }
+
+    /// Port forwards last reported by an agent, None when it is not connected.
+    pub async fn forwards(&self, agent_id: Uuid) -> Option<Vec<ForwardStatus>> {
+        let forwards = self.forwards.read().await;
+        Some(forwards.get(&agent_id)?.forwards.clone())
+    }
+
+    /// Relays the streams an agent connection opens until the connection closes.
+    pub async fn relay_streams(
+        self,
+        mux: Multiplexer,
+        mut incoming: mpsc::UnboundedReceiver<MuxStream>,
+    ) {
+        while let Some(stream) = incoming.recv().await {
+            match self.open_peer_stream(&mux, stream.tunnel_id()).await {
+                Ok(peer) => {
+                    debug!(
+                        "Forward {}: relaying stream {} to stream {} of tunnel {}",
+                        stream.tunnel_id(),
+                        stream.id(),
+                        peer.id(),
+                        peer.tunnel_id()
+                    );
+                    tokio::spawn(relay(stream, peer));
+                }
+                Err(e) => {
+                    warn!("Forward {}: stream refused: {}", stream.tunnel_id(), e);
+                    stream.reset(e);
+                }
+            }
+        }
+    }
+
+    /// Opens a stream to the `expose` forward targeted by a `connect` forward.
+    async fn open_peer_stream(
+        &self,
+        mux: &Multiplexer,
+        tunnel_id: Uuid,
+    ) -> Result<MuxStream, String> {
+        let agents = self.forwards.read().await;
+        let (source_id, source) = agents
+            .iter()
+            .find(|(_, agent)| agent.mux.same_connection(mux))
+            .ok_or("The agent has not reported its forwards")?;
+        let forward = source
+            .forwards
+            .iter()
+            .find(|forward| {
+                forward.tunnel_id == tunnel_id
+                    && forward.direction == ForwardDirection::Connect
+                    && forward.active
+            })
+            .ok_or("Unknown forward")?;
+        let (peer, name) = parse_forward_target(&forward.target)
+            .ok_or_else(|| format!("Invalid forward target {}", forward.target))?;
+
+        let mut peers = agents
+            .iter()
+            .filter(|(id, agent)| agent.agent_name == peer || id.to_string() == peer);
+        let (_, peer_agent) = peers
+            .next()
+            .ok_or_else(|| format!("Agent {} is not connected", peer))?;
+        if peers.next().is_some() {
+            return Err(format!("Several connected agents are named {}", peer));
+        }
+        let exposed = peer_agent
+            .forwards
+            .iter()
+            .find(|exposed| {
+                exposed.name == name
+                    && exposed.direction == ForwardDirection::Expose
+                    && exposed.active
+            })
+            .ok_or_else(|| format!("Agent {} has no running forward {}", peer, name))?;
+        let allowed = exposed
+            .allowed_agents
+            .iter()
+            .any(|allowed| *allowed == source.agent_name || *allowed == source_id.to_string());
+        if !allowed {
+            return Err(format!(
+                "Agent {} may not connect to {}",
+                source.agent_name, forward.target
+            ));
+        }
+        Ok(peer_agent.mux.open(exposed.tunnel_id))
+    }
+
+    /// Handles a tunnel message from an agent, returns the response to send back.
+    pub async fn handle_message(
+        &self,
+        agent_id: Uuid,
+        mux: &Multiplexer,
+        msg: TunnelMessage,
+    ) -> Option<TunnelMessage> {
+        match msg {
+            TunnelMessage::TunnelRequest {
+                tunnel_id,
+                target,
+                tunnel_type,
+                public_port,
+            } => {
+                let result = self
+                    .open(agent_id, mux, tunnel_id, target, tunnel_type, public_port)
+                    .await;
+                if let Err(e) = &result {
+                    warn!("Tunnel {} of agent {} refused: {}", tunnel_id, agent_id, e);
+                }
+                Some(TunnelMessage::TunnelResponse {
+                    tunnel_id,
+                    success: result.is_ok(),
+                    public_port: result.as_ref().ok().map(|info| info.public_addr.port()),
+                    error: result.err().map(|e| e.to_string()),
+                })
+            }
+            TunnelMessage::TunnelClose { tunnel_id, reason } => {
+                let owned = self
+                    .tunnels
+                    .read()
+                    .await
+                    .get(&tunnel_id)
+                    .is_some_and(|tunnel| tunnel.info.agent_id == agent_id);
+                if owned {
+                    let reason = reason.unwrap_or_else(|| "Closed by agent".to_string());
+                    self.close(tunnel_id, &reason).await;
+                }
+                None
+            }
+            TunnelMessage::TunnelResponse { tunnel_id, .. }
+            | TunnelMessage::TunnelAssign { tunnel_id, .. } => {
+                debug!(
+                    "Ignoring Hub message for tunnel {} from agent {}",
+                    tunnel_id, agent_id
+                );
+                None
+            }
+            // Reports are recorded with the agent's name by `report_forwards`
+            TunnelMessage::ForwardReport { .. }
+            | TunnelMessage::ForwardStart { .. }
+            | TunnelMessage::ForwardStop { .. } => {
+                debug!("Ignoring forward message from agent {}", agent_id);
+                None
+            }
+        }
+    }
+}
+
+/// Copies data between two streams, e.g. of two agents.
+async fn relay(stream: MuxStream, peer: MuxStream) {
+    let (local, remote) = tokio::io::duplex(4 * MAX_FRAME_DATA);
+    let (stream_result, peer_result) = tokio::join!(stream.bridge(local), peer.bridge(remote));
+    if let Err(e) = stream_result.and(peer_result) {
+        debug!("Relayed stream aborted: {}", e);
+    }
+}
+
+/// Opens a stream on the agent's multiplexer for every accepted connection.
+async fn accept_connections(listener: TcpListener, tunnel_id: Uuid, mux: Multiplexer) {
+    let name = format!("Tunnel {}", tunnel_id);
+    loop {
+        let (connection, peer_addr) = accept_with_backoff(&listener, &name).await;
+        let stream = mux.open(tunnel_id);
+        debug!(
+            "Tunnel {}: connection from {} on stream {}",
+            tunnel_id,
+            peer_addr,
+            stream.id()
+        );
+        tokio::spawn(async move {
+            if let Err(e) = stream.bridge(connection).await {
+                debug!(
+                    "Tunnel {}: connection from {} aborted: {}",
+                    tunnel_id, peer_addr, e
+                );
+            }
+        });
+    }
+}
@@ -12,7 +12,7 @@ use uuid::Uuid;
 #[sea_orm(table_name = "agents")]
 pub struct Model {
     /// Unique identifier for the agent.
-    #[sea_orm(primary_key, column_type = "Uuid")]
+    #[sea_orm(primary_key, auto_increment = false, column_type = "Uuid")]
     pub id: Uuid,
 
     /// Human-readable name of the agent.
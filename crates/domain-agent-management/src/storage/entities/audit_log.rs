@@ -0,0 +1,75 @@
+//! Audit log entity
+
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Audit log entity representing a management action performed by a user.
+#[derive(
+    Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize,
+)]
+#[sea_orm(table_name = "audit_logs")]
+pub struct Model {
+    /// Unique identifier for the entry.
+    #[sea_orm(primary_key, auto_increment = false, column_type = "Uuid")]
+    pub id: Uuid,
+
+    /// Timestamp when the action was performed.
+    #[sea_orm(column_type = "TimestampWithTimeZone")]
+    pub timestamp: DateTime<Utc>,
+
+    /// Username of the user who performed the action.
+    #[sea_orm(column_type = "Text")]
+    pub actor: String,
+
+    /// Role of the user at the time of the action, unknown for failed logins.
+    #[sea_orm(column_type = "Text", nullable)]
+    pub actor_role: Option<String>,
+
+    /// Action performed (e.g., "agent.approve", "task.submit").
+    #[sea_orm(column_type = "Text")]
+    pub action: String,
+
+    /// Kind of object the action was performed on (e.g., "agent", "task", "user").
+    #[sea_orm(column_type = "Text")]
+    pub target_type: String,
+
+    /// Identifier of the object the action was performed on.
+    #[sea_orm(column_type = "Text", nullable)]
+    pub target_id: Option<String>,
+
+    /// API the request came through ("rest" or "grpc").
+    #[sea_orm(column_type = "Text")]
+    pub source: String,
+
+    /// Address of the client that sent the request.
+    #[sea_orm(column_type = "Text", nullable)]
+    pub remote_addr: Option<String>,
+
+    /// Whether the action succeeded.
+    pub success: bool,
+
+    /// Error message when the action failed.
+    #[sea_orm(column_type = "Text", nullable)]
+    pub error: Option<String>,
+
+    /// State of the target before the action in JSON format.
+    #[sea_orm(column_type = "Json", nullable)]
+    pub before: Option<Json>,
+
+    /// State of the target after the action in JSON format.
+    #[sea_orm(column_type = "Json", nullable)]
+    pub after: Option<Json>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation {}
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef {
+        panic!("No relation defined for AuditLog")
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
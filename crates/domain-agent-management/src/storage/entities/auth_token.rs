@@ -0,0 +1,44 @@
+//! Auth token entity
+
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Auth token entity representing a session token issued at login.
+#[derive(
+    Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize,
+)]
+#[sea_orm(table_name = "auth_tokens")]
+pub struct Model {
+    /// Unique identifier for the token.
+    #[sea_orm(primary_key, auto_increment = false, column_type = "Uuid")]
+    pub id: Uuid,
+
+    /// Reference to the user the token was issued to.
+    #[sea_orm(column_type = "Uuid")]
+    pub user_id: Uuid,
+
+    /// Hex-encoded SHA-256 hash of the token, the token itself is never stored.
+    #[sea_orm(column_type = "Text", unique)]
+    pub token_hash: String,
+
+    /// Timestamp after which the token is no longer accepted.
+    #[sea_orm(column_type = "TimestampWithTimeZone")]
+    pub expires_at: DateTime<Utc>,
+
+    /// Timestamp when the token was issued.
+    #[sea_orm(column_type = "TimestampWithTimeZone")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation {}
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef {
+        panic!("No relation defined for AuthToken")
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
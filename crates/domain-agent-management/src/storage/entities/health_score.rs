@@ -12,7 +12,7 @@ use uuid::Uuid;
 #[sea_orm(table_name = "agent_health_scores")]
 pub struct Model {
     /// Unique identifier for the health score record.
-    #[sea_orm(primary_key, column_type = "Uuid")]
+    #[sea_orm(primary_key, auto_increment = false, column_type = "Uuid")]
     pub id: Uuid,
 
     /// Reference to the agent this health score belongs to.
@@ -0,0 +1,52 @@
+//! Ingress route entity
+
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Ingress route entity mapping a hostname to a target behind an agent.
+#[derive(
+    Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize,
+)]
+#[sea_orm(table_name = "ingress_routes")]
+pub struct Model {
+    /// Unique identifier for the route, also the id of its tunnel.
+    #[sea_orm(primary_key, auto_increment = false, column_type = "Uuid")]
+    pub id: Uuid,
+
+    /// Hostname matched against the Host header or the TLS SNI, lowercase.
+    #[sea_orm(column_type = "Text", unique)]
+    pub hostname: String,
+
+    /// Agent that forwards the requests.
+    #[sea_orm(column_type = "Uuid")]
+    pub agent_id: Uuid,
+
+    /// Address the agent forwards the requests to (e.g., "127.0.0.1:8080").
+    #[sea_orm(column_type = "Text")]
+    pub target: String,
+
+    /// Whether TLS connections are passed through to the target, instead of
+    /// plain HTTP requests.
+    pub tls: bool,
+
+    /// Username of the user who created the route.
+    #[sea_orm(column_type = "Text", nullable)]
+    pub created_by: Option<String>,
+
+    /// Timestamp when the route was created.
+    #[sea_orm(column_type = "TimestampWithTimeZone")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation {}
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef {
+        panic!("No relation defined for IngressRoute")
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
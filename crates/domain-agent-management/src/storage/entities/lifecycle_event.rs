@@ -12,7 +12,7 @@ use uuid::Uuid;
 #[sea_orm(table_name = "agent_lifecycle_events")]
 pub struct Model {
     /// Unique identifier for the lifecycle event.
-    #[sea_orm(primary_key, column_type = "Uuid")]
+    #[sea_orm(primary_key, auto_increment = false, column_type = "Uuid")]
     pub id: Uuid,
 
     /// Reference to the agent this event belongs to.
@@ -3,12 +3,26 @@
 //! Sea-orm entity models for agent management.
 
 pub mod agent;
+pub mod audit_log;
+pub mod auth_token;
 pub mod health_score;
+pub mod ingress_route;
 pub mod lifecycle_event;
+pub mod role;
 pub mod system_info;
+pub mod task;
+pub mod task_event;
+pub mod user;
 
 // Re-export the Entity types from each module
 pub use agent::Entity as AgentEntity;
+pub use audit_log::Entity as AuditLogEntity;
+pub use auth_token::Entity as AuthTokenEntity;
 pub use health_score::Entity as HealthScoreEntity;
+pub use ingress_route::Entity as IngressRouteEntity;
 pub use lifecycle_event::{Entity as LifecycleEventEntity, ActiveModel as LifecycleEventActiveModel};
+pub use role::Entity as RoleEntity;
 pub use system_info::Entity as SystemInfoEntity;
+pub use task::Entity as TaskEntity;
+pub use task_event::Entity as TaskEventEntity;
+pub use user::Entity as UserEntity;
@@ -0,0 +1,37 @@
+//! Role entity
+
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Role entity representing a role that can be assigned to users.
+///
+/// The permissions of each role are defined in `domain::rbac`.
+#[derive(
+    Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize,
+)]
+#[sea_orm(table_name = "roles")]
+pub struct Model {
+    /// Name of the role (e.g., "viewer", "operator", "admin").
+    #[sea_orm(primary_key, auto_increment = false, column_type = "Text")]
+    pub name: String,
+
+    /// Human-readable description of the role.
+    #[sea_orm(column_type = "Text")]
+    pub description: String,
+
+    /// Timestamp when the role was created.
+    #[sea_orm(column_type = "TimestampWithTimeZone")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation {}
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef {
+        panic!("No relation defined for Role")
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
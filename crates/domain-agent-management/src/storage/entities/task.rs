@@ -0,0 +1,79 @@
+//! Task entity
+
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Task entity representing a remote task submitted to an agent.
+#[derive(
+    Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize,
+)]
+#[sea_orm(table_name = "agent_tasks")]
+pub struct Model {
+    /// Unique identifier for the task.
+    #[sea_orm(primary_key, auto_increment = false, column_type = "Uuid")]
+    pub id: Uuid,
+
+    /// Reference to the agent running the task.
+    #[sea_orm(column_type = "Uuid")]
+    pub agent_id: Uuid,
+
+    /// Kind of task (e.g., "shell", "script", "file_upload").
+    #[sea_orm(column_type = "Text")]
+    pub task_type: String,
+
+    /// The full task definition (`TaskType`) in JSON format.
+    #[sea_orm(column_type = "Json")]
+    pub payload: Json,
+
+    /// Current status (e.g., "pending", "dispatched", "running", "succeeded").
+    #[sea_orm(column_type = "Text")]
+    pub status: String,
+
+    /// Timeout requested for the task, 0 uses the agent's default.
+    pub timeout_seconds: i32,
+
+    /// Output streamed by the agent, truncated to the latest output.
+    #[sea_orm(column_type = "Text")]
+    pub output: String,
+
+    /// Error reported by the agent.
+    #[sea_orm(column_type = "Text", nullable)]
+    pub error: Option<String>,
+
+    /// Exit code of the process, if any.
+    #[sea_orm(nullable)]
+    pub exit_code: Option<i32>,
+
+    /// Run time reported by the agent in milliseconds.
+    #[sea_orm(nullable)]
+    pub duration_ms: Option<i64>,
+
+    /// Identity of the user or system that submitted the task.
+    #[sea_orm(column_type = "Text", nullable)]
+    pub submitted_by: Option<String>,
+
+    /// Timestamp when the task was submitted.
+    #[sea_orm(column_type = "TimestampWithTimeZone")]
+    pub created_at: DateTime<Utc>,
+
+    /// Timestamp when the task was sent to the agent.
+    #[sea_orm(column_type = "TimestampWithTimeZone", nullable)]
+    pub dispatched_at: Option<DateTime<Utc>>,
+
+    /// Timestamp when the task finished.
+    #[sea_orm(column_type = "TimestampWithTimeZone", nullable)]
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation {}
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef {
+        panic!("No relation defined for Task")
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
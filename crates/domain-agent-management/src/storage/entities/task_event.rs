@@ -0,0 +1,56 @@
+//! Task event entity
+
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// TaskEvent entity representing an audit log entry for a remote task.
+#[derive(
+    Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize,
+)]
+#[sea_orm(table_name = "agent_task_events")]
+pub struct Model {
+    /// Unique identifier for the task event.
+    #[sea_orm(primary_key, auto_increment = false, column_type = "Uuid")]
+    pub id: Uuid,
+
+    /// Reference to the task this event belongs to.
+    #[sea_orm(column_type = "Uuid")]
+    pub task_id: Uuid,
+
+    /// Reference to the agent running the task.
+    #[sea_orm(column_type = "Uuid")]
+    pub agent_id: Uuid,
+
+    /// Type of task event (e.g., "task_submitted", "task_succeeded").
+    #[sea_orm(column_type = "Text")]
+    pub event_type: String,
+
+    /// Timestamp when the event occurred.
+    #[sea_orm(column_type = "TimestampWithTimeZone")]
+    pub timestamp: DateTime<Utc>,
+
+    /// Reason or description for the event.
+    #[sea_orm(column_type = "Text", nullable)]
+    pub reason: Option<String>,
+
+    /// Additional metadata associated with the event in JSON format.
+    #[sea_orm(column_type = "Json", nullable)]
+    pub metadata: Option<Json>,
+
+    /// Identity of the entity that triggered this event.
+    #[sea_orm(column_type = "Text", nullable)]
+    pub triggered_by: Option<String>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation {}
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef {
+        panic!("No relation defined for TaskEvent")
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
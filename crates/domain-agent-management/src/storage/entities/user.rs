@@ -0,0 +1,56 @@
+//! User entity
+
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// User entity representing an account of the management API.
+#[derive(
+    Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize,
+)]
+#[sea_orm(table_name = "users")]
+pub struct Model {
+    /// Unique identifier for the user.
+    #[sea_orm(primary_key, auto_increment = false, column_type = "Uuid")]
+    pub id: Uuid,
+
+    /// Login name of the user.
+    #[sea_orm(column_type = "Text", unique)]
+    pub username: String,
+
+    /// Argon2 hash of the user's password in PHC string format.
+    #[serde(skip_serializing)]
+    #[sea_orm(column_type = "Text")]
+    pub password_hash: String,
+
+    /// Name of the user's role (e.g., "viewer", "operator", "admin").
+    #[sea_orm(column_type = "Text")]
+    pub role: String,
+
+    /// Whether the user is prevented from logging in.
+    pub disabled: bool,
+
+    /// Timestamp of the user's last successful login.
+    #[sea_orm(column_type = "TimestampWithTimeZone", nullable)]
+    pub last_login_at: Option<DateTime<Utc>>,
+
+    /// Timestamp when the user was created.
+    #[sea_orm(column_type = "TimestampWithTimeZone")]
+    pub created_at: DateTime<Utc>,
+
+    /// Timestamp when the user was last updated.
+    #[sea_orm(column_type = "TimestampWithTimeZone")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation {}
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef {
+        panic!("No relation defined for User")
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
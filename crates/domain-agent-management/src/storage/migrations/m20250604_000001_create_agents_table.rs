@@ -2,6 +2,8 @@
 
 use sea_orm_migration::prelude::*;
 
+use super::uuid_pk;
+
 /// Create the agents table.
 /// This table stores agent registration records with their status and capabilities.
 #[derive(DeriveMigrationName)]
@@ -14,13 +16,7 @@ impl MigrationTrait for Migration {
             .create_table(
                 Table::create()
                     .table(Agents::Table)
-                    .col(
-                        ColumnDef::new(Agents::Id)
-                            .uuid()
-                            .not_null()
-                            .primary_key()
-                            .default(Expr::cust("gen_random_uuid()")),
-                    )
+                    .col(uuid_pk(manager, Agents::Id))
                     .col(ColumnDef::new(Agents::Name).text().not_null())
                     .col(ColumnDef::new(Agents::Endpoint).text().not_null())
                     .col(ColumnDef::new(Agents::Status).text().not_null())
@@ -43,13 +39,13 @@ impl MigrationTrait for Migration {
                         ColumnDef::new(Agents::CreatedAt)
                             .timestamp_with_time_zone()
                             .not_null()
-                            .default(Expr::cust("now()")),
+                            .default(Expr::current_timestamp()),
                     )
                     .col(
                         ColumnDef::new(Agents::UpdatedAt)
                             .timestamp_with_time_zone()
                             .not_null()
-                            .default(Expr::cust("now()")),
+                            .default(Expr::current_timestamp()),
                     )
                     .to_owned(),
             )
@@ -2,6 +2,8 @@
 
 use sea_orm_migration::prelude::*;
 
+use super::uuid_pk;
+
 /// Create the agent_lifecycle_events table.
 /// This table stores audit log entries for agent lifecycle events.
 #[derive(DeriveMigrationName)]
@@ -14,13 +16,7 @@ impl MigrationTrait for Migration {
             .create_table(
                 Table::create()
                     .table(AgentLifecycleEvents::Table)
-                    .col(
-                        ColumnDef::new(AgentLifecycleEvents::Id)
-                            .uuid()
-                            .not_null()
-                            .primary_key()
-                            .default(Expr::cust("gen_random_uuid()")),
-                    )
+                    .col(uuid_pk(manager, AgentLifecycleEvents::Id))
                     .col(
                         ColumnDef::new(AgentLifecycleEvents::AgentId)
                             .uuid()
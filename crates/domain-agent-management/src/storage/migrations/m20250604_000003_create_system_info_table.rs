@@ -2,6 +2,8 @@
 
 use sea_orm_migration::prelude::*;
 
+use super::uuid_pk;
+
 /// Create the agent_system_info table.
 /// This table stores diagnostic reports from agents.
 #[derive(DeriveMigrationName)]
@@ -14,13 +16,7 @@ impl MigrationTrait for Migration {
             .create_table(
                 Table::create()
                     .table(AgentSystemInfo::Table)
-                    .col(
-                        ColumnDef::new(AgentSystemInfo::Id)
-                            .uuid()
-                            .not_null()
-                            .primary_key()
-                            .default(Expr::cust("gen_random_uuid()")),
-                    )
+                    .col(uuid_pk(manager, AgentSystemInfo::Id))
                     .col(
                         ColumnDef::new(AgentSystemInfo::AgentId)
                             .uuid()
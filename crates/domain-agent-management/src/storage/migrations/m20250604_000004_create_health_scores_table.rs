@@ -2,6 +2,8 @@
 
 use sea_orm_migration::prelude::*;
 
+use super::uuid_pk;
+
 /// Create the agent_health_scores table.
 /// This table stores health metrics over time for agents.
 #[derive(DeriveMigrationName)]
@@ -14,13 +16,7 @@ impl MigrationTrait for Migration {
             .create_table(
                 Table::create()
                     .table(AgentHealthScores::Table)
-                    .col(
-                        ColumnDef::new(AgentHealthScores::Id)
-                            .uuid()
-                            .not_null()
-                            .primary_key()
-                            .default(Expr::cust("gen_random_uuid()")),
-                    )
+                    .col(uuid_pk(manager, AgentHealthScores::Id))
                     .col(
                         ColumnDef::new(AgentHealthScores::AgentId)
                             .uuid()
@@ -0,0 +1,141 @@
+//! Migration: Create tasks table
+
+use sea_orm_migration::prelude::*;
+
+use super::uuid_pk;
+
+/// Create the agent_tasks table.
+/// This table stores remote tasks submitted to agents and their results.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AgentTasks::Table)
+                    .col(uuid_pk(manager, AgentTasks::Id))
+                    .col(
+                        ColumnDef::new(AgentTasks::AgentId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AgentTasks::TaskType)
+                            .text()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AgentTasks::Payload)
+                            .json()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AgentTasks::Status)
+                            .text()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AgentTasks::TimeoutSeconds)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AgentTasks::Output)
+                            .text()
+                            .not_null()
+                            .default(""),
+                    )
+                    .col(
+                        ColumnDef::new(AgentTasks::Error)
+                            .text()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(AgentTasks::ExitCode)
+                            .integer()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(AgentTasks::DurationMs)
+                            .big_integer()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(AgentTasks::SubmittedBy)
+                            .text()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(AgentTasks::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(AgentTasks::DispatchedAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(AgentTasks::FinishedAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_tasks_agent_id_status")
+                    .table(AgentTasks::Table)
+                    .col(AgentTasks::AgentId)
+                    .col(AgentTasks::Status)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_tasks_created_at")
+                    .table(AgentTasks::Table)
+                    .col(AgentTasks::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AgentTasks::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+/// AgentTasks table column names
+#[derive(Iden)]
+pub enum AgentTasks {
+    Table,
+    Id,
+    AgentId,
+    TaskType,
+    Payload,
+    Status,
+    TimeoutSeconds,
+    Output,
+    Error,
+    ExitCode,
+    DurationMs,
+    SubmittedBy,
+    CreatedAt,
+    DispatchedAt,
+    FinishedAt,
+}
@@ -0,0 +1,103 @@
+//! Migration: Create task events table
+
+use sea_orm_migration::prelude::*;
+
+use super::uuid_pk;
+
+/// Create the agent_task_events table.
+/// This table stores audit log entries for remote tasks.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AgentTaskEvents::Table)
+                    .col(uuid_pk(manager, AgentTaskEvents::Id))
+                    .col(
+                        ColumnDef::new(AgentTaskEvents::TaskId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AgentTaskEvents::AgentId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AgentTaskEvents::EventType)
+                            .text()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AgentTaskEvents::Timestamp)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AgentTaskEvents::Reason)
+                            .text()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(AgentTaskEvents::Metadata)
+                            .json()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(AgentTaskEvents::TriggeredBy)
+                            .text()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_task_events_task_id_timestamp")
+                    .table(AgentTaskEvents::Table)
+                    .col(AgentTaskEvents::TaskId)
+                    .col(AgentTaskEvents::Timestamp)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_task_events_agent_id")
+                    .table(AgentTaskEvents::Table)
+                    .col(AgentTaskEvents::AgentId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AgentTaskEvents::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+/// AgentTaskEvents table column names
+#[derive(Iden)]
+pub enum AgentTaskEvents {
+    Table,
+    Id,
+    TaskId,
+    AgentId,
+    EventType,
+    Timestamp,
+    Reason,
+    Metadata,
+    TriggeredBy,
+}
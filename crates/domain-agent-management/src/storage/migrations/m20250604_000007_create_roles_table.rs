@@ -0,0 +1,69 @@
+//! Migration: Create roles table
+
+use sea_orm_migration::prelude::*;
+
+/// Create the roles table.
+/// This table stores the roles that can be assigned to users and seeds the built-in roles.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Roles::Table)
+                    .col(
+                        ColumnDef::new(Roles::Name)
+                            .text()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(Roles::Description)
+                            .text()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Roles::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Seed the built-in roles
+        manager
+            .exec_stmt(
+                Query::insert()
+                    .into_table(Roles::Table)
+                    .columns([Roles::Name, Roles::Description])
+                    .values_panic(["viewer".into(), "Read-only access to agents and tasks".into()])
+                    .values_panic(["operator".into(), "Viewer access plus running tasks and updating agents".into()])
+                    .values_panic(["admin".into(), "Full access including agent approval and user management".into()])
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Roles::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+/// Roles table column names
+#[derive(Iden)]
+pub enum Roles {
+    Table,
+    Name,
+    Description,
+    CreatedAt,
+}
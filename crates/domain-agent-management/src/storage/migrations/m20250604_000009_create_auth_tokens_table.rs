@@ -0,0 +1,85 @@
+//! Migration: Create auth tokens table
+
+use sea_orm_migration::prelude::*;
+
+use super::m20250604_000008_create_users_table::Users;
+use super::uuid_pk;
+
+/// Create the auth_tokens table.
+/// This table stores the session tokens issued at login. Only a SHA-256 hash
+/// of each token is stored.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AuthTokens::Table)
+                    .col(uuid_pk(manager, AuthTokens::Id))
+                    .col(
+                        ColumnDef::new(AuthTokens::UserId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AuthTokens::TokenHash)
+                            .text()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(
+                        ColumnDef::new(AuthTokens::ExpiresAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AuthTokens::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_auth_tokens_user_id")
+                            .from(AuthTokens::Table, AuthTokens::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_auth_tokens_user_id")
+                    .table(AuthTokens::Table)
+                    .col(AuthTokens::UserId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AuthTokens::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+/// AuthTokens table column names
+#[derive(Iden)]
+pub enum AuthTokens {
+    Table,
+    Id,
+    UserId,
+    TokenHash,
+    ExpiresAt,
+    CreatedAt,
+}
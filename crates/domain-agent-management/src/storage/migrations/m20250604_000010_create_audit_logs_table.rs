@@ -0,0 +1,145 @@
+//! Migration: Create audit logs table
+
+use sea_orm_migration::prelude::*;
+
+use super::uuid_pk;
+
+/// Create the audit_logs table.
+/// This table stores who performed which management action on which target,
+/// with the state of the target before and after the action.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AuditLogs::Table)
+                    .col(uuid_pk(manager, AuditLogs::Id))
+                    .col(
+                        ColumnDef::new(AuditLogs::Timestamp)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(AuditLogs::Actor)
+                            .text()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AuditLogs::ActorRole)
+                            .text()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(AuditLogs::Action)
+                            .text()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AuditLogs::TargetType)
+                            .text()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AuditLogs::TargetId)
+                            .text()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(AuditLogs::Source)
+                            .text()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AuditLogs::RemoteAddr)
+                            .text()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(AuditLogs::Success)
+                            .boolean()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AuditLogs::Error)
+                            .text()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(AuditLogs::Before)
+                            .json()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(AuditLogs::After)
+                            .json()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_audit_logs_timestamp")
+                    .table(AuditLogs::Table)
+                    .col(AuditLogs::Timestamp)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_audit_logs_actor")
+                    .table(AuditLogs::Table)
+                    .col(AuditLogs::Actor)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_audit_logs_target")
+                    .table(AuditLogs::Table)
+                    .col(AuditLogs::TargetType)
+                    .col(AuditLogs::TargetId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AuditLogs::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+/// AuditLogs table column names
+#[derive(Iden)]
+pub enum AuditLogs {
+    Table,
+    Id,
+    Timestamp,
+    Actor,
+    ActorRole,
+    Action,
+    TargetType,
+    TargetId,
+    Source,
+    RemoteAddr,
+    Success,
+    Error,
+    Before,
+    After,
+}
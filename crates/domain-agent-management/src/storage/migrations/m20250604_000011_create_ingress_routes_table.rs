@@ -0,0 +1,90 @@
+//! Migration: Create ingress routes table
+
+use sea_orm_migration::prelude::*;
+
+use super::uuid_pk;
+
+/// Create the ingress_routes table.
+/// This table maps the hostnames served by the ingress to an agent and the
+/// `host:port` target the agent forwards the requests to.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(IngressRoutes::Table)
+                    .col(uuid_pk(manager, IngressRoutes::Id))
+                    .col(
+                        ColumnDef::new(IngressRoutes::Hostname)
+                            .text()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(
+                        ColumnDef::new(IngressRoutes::AgentId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(IngressRoutes::Target)
+                            .text()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(IngressRoutes::Tls)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(
+                        ColumnDef::new(IngressRoutes::CreatedBy)
+                            .text()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(IngressRoutes::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_ingress_routes_agent_id")
+                    .table(IngressRoutes::Table)
+                    .col(IngressRoutes::AgentId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(IngressRoutes::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+/// IngressRoutes table column names
+#[derive(Iden)]
+pub enum IngressRoutes {
+    Table,
+    Id,
+    Hostname,
+    AgentId,
+    Target,
+    Tls,
+    CreatedBy,
+    CreatedAt,
+}
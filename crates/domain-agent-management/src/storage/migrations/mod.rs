@@ -1,6 +1,10 @@
 //! Storage migrations
 //!
-//! Database migrations for the agent management storage layer.
+//! Database migrations for the agent management storage layer. The
+//! migrations run on both PostgreSQL and SQLite, so column types and
+//! defaults must be expressible on both backends. Timestamps default to
+//! `CURRENT_TIMESTAMP`, the same as `now()` on PostgreSQL, so the
+//! PostgreSQL schema is unchanged by SQLite support.
 
 pub use sea_orm_migration::prelude::*;
 
@@ -8,11 +12,38 @@ pub mod m20250604_000001_create_agents_table;
 pub mod m20250604_000002_create_lifecycle_events_table;
 pub mod m20250604_000003_create_system_info_table;
 pub mod m20250604_000004_create_health_scores_table;
+pub mod m20250604_000005_create_tasks_table;
+pub mod m20250604_000006_create_task_events_table;
+pub mod m20250604_000007_create_roles_table;
+pub mod m20250604_000008_create_users_table;
+pub mod m20250604_000009_create_auth_tokens_table;
+pub mod m20250604_000010_create_audit_logs_table;
+pub mod m20250604_000011_create_ingress_routes_table;
 
 use m20250604_000001_create_agents_table::Migration as CreateAgentsTable;
 use m20250604_000002_create_lifecycle_events_table::Migration as CreateLifecycleEventsTable;
 use m20250604_000003_create_system_info_table::Migration as CreateSystemInfoTable;
 use m20250604_000004_create_health_scores_table::Migration as CreateHealthScoresTable;
+use m20250604_000005_create_tasks_table::Migration as CreateTasksTable;
+use m20250604_000006_create_task_events_table::Migration as CreateTaskEventsTable;
+use m20250604_000007_create_roles_table::Migration as CreateRolesTable;
+use m20250604_000008_create_users_table::Migration as CreateUsersTable;
+use m20250604_000009_create_auth_tokens_table::Migration as CreateAuthTokensTable;
+use m20250604_000010_create_audit_logs_table::Migration as CreateAuditLogsTable;
+use m20250604_000011_create_ingress_routes_table::Migration as CreateIngressRoutesTable;
+
+/// UUID primary key column.
+///
+/// PostgreSQL generates ids for rows inserted without one. SQLite has no
+/// UUID function, there the services always set the id themselves.
+pub fn uuid_pk<T: IntoIden>(manager: &SchemaManager, name: T) -> ColumnDef {
+    let mut column = ColumnDef::new(name);
+    column.uuid().not_null().primary_key();
+    if manager.get_database_backend() == sea_orm::DatabaseBackend::Postgres {
+        column.default(Expr::cust("gen_random_uuid()"));
+    }
+    column
+}
 
 /// All migrations to be run
 pub struct Migrator;
@@ -25,6 +56,13 @@ impl MigratorTrait for Migrator {
             Box::new(CreateLifecycleEventsTable),
             Box::new(CreateSystemInfoTable),
             Box::new(CreateHealthScoresTable),
+            Box::new(CreateTasksTable),
+            Box::new(CreateTaskEventsTable),
+            Box::new(CreateRolesTable),
+            Box::new(CreateUsersTable),
+            Box::new(CreateAuthTokensTable),
+            Box::new(CreateAuditLogsTable),
+            Box::new(CreateIngressRoutesTable),
         ]
     }
 }
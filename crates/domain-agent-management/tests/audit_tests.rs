@@ -0,0 +1,153 @@
+//! Unit tests for the operator audit log
+//!
+//! These tests cover building audit entries, state diffs and the task
+//! snapshot stored in the log, none of which need a database.
+
+use chrono::Utc;
+use domain_agent_management::domain::rbac::Role;
+use domain_agent_management::service::audit::{
+    diff, AuditAction, AuditContext, AuditEntryInfo, AuditSource, NewAuditEntry,
+};
+use domain_agent_management::service::task::TaskInfo;
+use domain_agent_management::storage::entities::audit_log::Model;
+use domain_agent_protocol::task::{TaskStatus, TaskType};
+use serde_json::json;
+use uuid::Uuid;
+
+fn context() -> AuditContext {
+    AuditContext {
+        actor: "alice".to_string(),
+        actor_role: Some(Role::Admin),
+        source: AuditSource::Rest,
+        remote_addr: Some("192.0.2.1:50000".to_string()),
+    }
+}
+
+// Test action names and target types
+#[test]
+fn test_action_names() {
+    assert_eq!(AuditAction::ApproveAgent.as_str(), "agent.approve");
+    assert_eq!(AuditAction::ApproveAgent.target_type(), "agent");
+    assert_eq!(AuditAction::SubmitTask.to_string(), "task.submit");
+    assert_eq!(AuditAction::SubmitTask.target_type(), "agent");
+    assert_eq!(AuditAction::CancelTask.target_type(), "task");
+    assert_eq!(AuditAction::Login.target_type(), "user");
+    assert_eq!(AuditSource::Grpc.as_str(), "grpc");
+}
+
+// Test the diff of two states
+#[test]
+fn test_diff() {
+    let before = json!({ "name": "web-1", "status": "online", "version": "1.0" });
+    let after = json!({ "name": "web-2", "status": "online", "tags": ["prod"] });
+
+    let changes = diff(Some(&before), Some(&after)).unwrap();
+    assert_eq!(
+        changes,
+        json!({
+            "name": { "before": "web-1", "after": "web-2" },
+            "tags": { "before": null, "after": ["prod"] },
+            "version": { "before": "1.0", "after": null },
+        })
+    );
+
+    assert_eq!(diff(Some(&before), Some(&before)), Some(json!({})));
+    assert_eq!(diff(None, Some(&after)), None);
+    assert_eq!(diff(Some(&json!("a")), Some(&json!("b"))), None);
+}
+
+// Test the outcome recorded by the entry builders
+#[test]
+fn test_entry_outcome() {
+    let agent = json!({ "name": "web-1" });
+
+    let entry = NewAuditEntry::new(context(), AuditAction::UpdateAgent, "agent-1")
+        .with_before(&Some(agent.clone()))
+        .with_result(
+            &Ok::<_, String>(Some(json!({ "name": "web-2" }))),
+            "Agent not found",
+        );
+    assert_eq!(entry.target_id.as_deref(), Some("agent-1"));
+    assert_eq!(entry.before, Some(agent));
+    assert_eq!(entry.after, Some(json!({ "name": "web-2" })));
+    assert!(entry.error.is_none());
+
+    let entry = NewAuditEntry::new(context(), AuditAction::UpdateAgent, "agent-2")
+        .with_before(&None::<serde_json::Value>)
+        .with_result(
+            &Ok::<Option<serde_json::Value>, String>(None),
+            "Agent not found",
+        );
+    assert!(entry.before.is_none());
+    assert!(entry.after.is_none());
+    assert_eq!(entry.error.as_deref(), Some("Agent not found"));
+
+    let entry = NewAuditEntry::new(context(), AuditAction::DeleteAgent, "agent-3").with_result(
+        &Err::<Option<serde_json::Value>, _>("connection reset"),
+        "Agent not found",
+    );
+    assert_eq!(entry.error.as_deref(), Some("connection reset"));
+}
+
+// Test that entries read back from the database include the changed fields
+#[test]
+fn test_entry_info_changes() {
+    let model = Model {
+        id: Uuid::new_v4(),
+        timestamp: Utc::now(),
+        actor: "alice".to_string(),
+        actor_role: Some("admin".to_string()),
+        action: AuditAction::ApproveAgent.as_str().to_string(),
+        target_type: "agent".to_string(),
+        target_id: Some(Uuid::new_v4().to_string()),
+        source: "grpc".to_string(),
+        remote_addr: None,
+        success: true,
+        error: None,
+        before: Some(json!({ "approval_state": "pending", "name": "web-1" })),
+        after: Some(json!({ "approval_state": "approved", "name": "web-1" })),
+    };
+
+    let info = AuditEntryInfo::from(model);
+    assert_eq!(
+        info.changes,
+        Some(json!({ "approval_state": { "before": "pending", "after": "approved" } }))
+    );
+    let line = serde_json::to_value(&info).unwrap();
+    assert_eq!(line["action"], "agent.approve");
+    assert_eq!(line["source"], "grpc");
+}
+
+// Test that task snapshots leave out the output and uploaded content
+#[test]
+fn test_task_audit_state() {
+    let task = TaskInfo {
+        id: Uuid::new_v4(),
+        agent_id: Uuid::new_v4(),
+        task: TaskType::FileUpload {
+            remote_path: "/etc/app.conf".to_string(),
+            content_base64: "aGVsbG8gd29ybGQ=".to_string(),
+            mode: Some(0o644),
+        },
+        status: TaskStatus::Succeeded,
+        timeout_seconds: 60,
+        output: "a lot of output".to_string(),
+        error: None,
+        exit_code: Some(0),
+        duration_ms: Some(12),
+        submitted_by: Some("alice".to_string()),
+        created_at: Utc::now(),
+        dispatched_at: None,
+        finished_at: None,
+    };
+
+    let state = task.audit_state();
+    assert!(state.get("output").is_none());
+    assert_eq!(state["task"]["data"]["remote_path"], "/etc/app.conf");
+    assert_eq!(
+        state["task"]["data"]["content_base64"],
+        "<16 base64 characters>"
+    );
+    assert_eq!(state["status"], "succeeded");
+    assert_eq!(state["submitted_by"], "alice");
+}
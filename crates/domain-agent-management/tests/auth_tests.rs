@@ -0,0 +1,122 @@
+//! Unit tests for role-based access control and AuthService helpers
+//!
+//! These tests cover the parts of authentication that do not need a database.
+
+use chrono::Utc;
+use domain_agent_management::domain::rbac::{Permission, Role};
+use domain_agent_management::service::auth::{
+    generate_token, hash_password, hash_token, parse_bearer, verify_password, AuthError, UserInfo,
+};
+use domain_agent_management::storage::entities::user::Model;
+use uuid::Uuid;
+
+fn user_model(role: &str) -> Model {
+    Model {
+        id: Uuid::new_v4(),
+        username: "alice".to_string(),
+        password_hash: "$argon2id$unused".to_string(),
+        role: role.to_string(),
+        disabled: false,
+        last_login_at: None,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }
+}
+
+// Test the permissions granted by each role
+#[test]
+fn test_role_permissions() {
+    assert!(Role::Viewer.allows(Permission::ViewAgents));
+    assert!(Role::Viewer.allows(Permission::ViewTasks));
+    assert!(!Role::Viewer.allows(Permission::SubmitTask));
+    assert!(!Role::Viewer.allows(Permission::UpdateAgent));
+
+    assert!(Role::Operator.allows(Permission::SubmitTask));
+    assert!(Role::Operator.allows(Permission::CancelTask));
+    assert!(Role::Operator.allows(Permission::UpdateAgent));
+    assert!(!Role::Operator.allows(Permission::ApproveAgent));
+    assert!(!Role::Operator.allows(Permission::DeleteAgent));
+    assert!(!Role::Operator.allows(Permission::ManageUsers));
+
+    // Admin has every permission
+    assert_eq!(Role::Admin.permissions().len(), Permission::ALL.len());
+
+    // Every role has at least the permissions of the roles below it
+    for pair in Role::ALL.windows(2) {
+        for permission in pair[0].permissions() {
+            assert!(
+                pair[1].allows(permission),
+                "{} lacks {}",
+                pair[1],
+                permission
+            );
+        }
+    }
+}
+
+// Test role names round-trip and unknown roles are rejected
+#[test]
+fn test_role_parse() {
+    for role in Role::ALL {
+        assert_eq!(role.as_str().parse::<Role>().unwrap(), role);
+    }
+    assert_eq!(" Admin ".parse::<Role>().unwrap(), Role::Admin);
+    assert!("root".parse::<Role>().is_err());
+
+    let role: Role = serde_json::from_str("\"operator\"").unwrap();
+    assert_eq!(role, Role::Operator);
+    assert_eq!(serde_json::to_string(&Role::Viewer).unwrap(), "\"viewer\"");
+}
+
+// Test that a stored user converts to UserInfo
+#[test]
+fn test_user_info_from_model() {
+    let info = UserInfo::try_from(user_model("operator")).unwrap();
+    assert_eq!(info.role, Role::Operator);
+    assert!(UserInfo::try_from(user_model("superuser")).is_err());
+
+    // The password hash is never serialized
+    let json = serde_json::to_string(&user_model("admin")).unwrap();
+    assert!(!json.contains("password_hash"));
+}
+
+// Test password hashing and verification
+#[test]
+fn test_password_hash() {
+    let hash = hash_password("correct horse").unwrap();
+    assert!(hash.starts_with("$argon2id$"));
+    assert!(verify_password("correct horse", &hash));
+    assert!(!verify_password("wrong horse", &hash));
+    assert!(!verify_password("correct horse", "not a hash"));
+
+    // Each hash uses a new salt
+    assert_ne!(hash, hash_password("correct horse").unwrap());
+
+    assert!(matches!(
+        hash_password("short"),
+        Err(AuthError::WeakPassword)
+    ));
+}
+
+// Test token generation and hashing
+#[test]
+fn test_tokens() {
+    let token = generate_token();
+    assert_eq!(token.len(), 64);
+    assert_ne!(token, generate_token());
+
+    let hash = hash_token(&token);
+    assert_eq!(hash.len(), 64);
+    assert_eq!(hash, hash_token(&token));
+    assert_ne!(hash, token);
+}
+
+// Test parsing of the Authorization header
+#[test]
+fn test_parse_bearer() {
+    assert_eq!(parse_bearer("Bearer abc123"), Some("abc123"));
+    assert_eq!(parse_bearer("bearer  abc123 "), Some("abc123"));
+    assert_eq!(parse_bearer("Basic YWxpY2U6cGFzcw=="), None);
+    assert_eq!(parse_bearer("Bearer "), None);
+    assert_eq!(parse_bearer("abc123"), None);
+}
@@ -0,0 +1,66 @@
+//! Helpers shared by the tunnel and ingress tests
+//!
+//! The Hub and agent multiplexers are wired together with channels in place
+//! of the agent WebSocket, and the agent side forwards its streams to a
+//! local target such as the echo server.
+
+// Each test binary uses only some of the helpers
+#![allow(dead_code)]
+
+use domain_agent_protocol::mux::{Multiplexer, MuxStream, Side};
+use domain_agent_protocol::tunnel::TunnelFrame;
+use std::net::SocketAddr;
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+
+/// Delivers the frames of one multiplexer to the other
+pub fn pump(mut frames: mpsc::UnboundedReceiver<Vec<u8>>, peer: Multiplexer) {
+    tokio::spawn(async move {
+        while let Some(frame) = frames.recv().await {
+            peer.handle_frame(TunnelFrame::decode(&frame).unwrap());
+        }
+    });
+}
+
+/// Connects a Hub and an agent multiplexer, the agent forwards all streams to `target`
+pub fn connect(target: SocketAddr) -> Multiplexer {
+    let (hub_tx, hub_rx) = mpsc::unbounded_channel();
+    let (agent_tx, agent_rx) = mpsc::unbounded_channel();
+    let (hub, _) = Multiplexer::new(Side::Hub, hub_tx);
+    let (agent, mut incoming) = Multiplexer::new(Side::Agent, agent_tx);
+    pump(hub_rx, agent.clone());
+    pump(agent_rx, hub.clone());
+
+    tokio::spawn(async move {
+        while let Some(stream) = incoming.recv().await {
+            tokio::spawn(forward(stream, target));
+        }
+    });
+    hub
+}
+
+async fn forward(stream: MuxStream, target: SocketAddr) {
+    match TcpStream::connect(target).await {
+        Ok(connection) => {
+            let _ = stream.bridge(connection).await;
+        }
+        Err(e) => stream.reset(e.to_string()),
+    }
+}
+
+/// Echoes every connection until the peer closes its writing half
+pub async fn echo_server() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        while let Ok((mut connection, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let (mut reader, mut writer) = connection.split();
+                let _ = tokio::io::copy(&mut reader, &mut writer).await;
+                let _ = writer.shutdown().await;
+            });
+        }
+    });
+    addr
+}
@@ -0,0 +1,157 @@
+//! Unit tests for the internal event bus
+//!
+//! These tests publish events directly to the EventBus and check what
+//! filtered subscribers receive.
+
+use chrono::Utc;
+use domain_agent_management::service::events::{
+    BusEvent, EventBus, EventFilter, HeartbeatEvent, HEALTH_SCORE_EVENT, HEARTBEAT_EVENT,
+};
+use domain_agent_management::service::health::NetworkHealthMetrics;
+use domain_agent_management::storage::entities::health_score;
+use domain_agent_protocol::lifecycle::{EventSource, LifecycleEvent, LifecycleEventType};
+use futures_util::StreamExt;
+use std::time::Duration;
+use uuid::Uuid;
+
+fn lifecycle(agent_id: Uuid, event_type: LifecycleEventType) -> BusEvent {
+    BusEvent::Lifecycle(LifecycleEvent::new(
+        agent_id,
+        event_type,
+        EventSource::Agent,
+    ))
+}
+
+fn health(agent_id: Uuid, score: f64) -> BusEvent {
+    BusEvent::Health(health_score::Model {
+        id: Uuid::new_v4(),
+        agent_id,
+        scored_at: Utc::now(),
+        overall_score: score,
+        latency_ms: Some(20.0),
+        jitter_ms: None,
+        packet_loss_percent: None,
+        bandwidth_kbps: None,
+        component_scores: None,
+    })
+}
+
+fn heartbeat(agent_id: Uuid) -> BusEvent {
+    BusEvent::Heartbeat(HeartbeatEvent {
+        event_id: Uuid::new_v4(),
+        agent_id,
+        status: "online".to_string(),
+        metrics: NetworkHealthMetrics {
+            latency_ms: Some(20.0),
+            jitter_ms: None,
+            packet_loss_percent: None,
+            bandwidth_kbps: None,
+        },
+        timestamp: Utc::now(),
+    })
+}
+
+// Test event types and filter matching
+#[test]
+fn test_filter_matches() {
+    let agent = Uuid::new_v4();
+    let other = Uuid::new_v4();
+
+    assert_eq!(
+        lifecycle(agent, LifecycleEventType::AgentConnected).event_type(),
+        "agent_connected"
+    );
+    assert_eq!(health(agent, 90.0).event_type(), HEALTH_SCORE_EVENT);
+    assert_eq!(heartbeat(agent).event_type(), HEARTBEAT_EVENT);
+
+    let all = EventFilter::default();
+    assert!(all.matches(&heartbeat(agent)));
+    assert!(all.matches(&health(other, 50.0)));
+
+    let filter = EventFilter::new(Some(agent)).with_event_types(vec![
+        "agent_connected".to_string(),
+        " health_score ".to_string(),
+        String::new(),
+    ]);
+    assert_eq!(filter.event_types, vec!["agent_connected", "health_score"]);
+    assert!(filter.matches(&lifecycle(agent, LifecycleEventType::AgentConnected)));
+    assert!(filter.matches(&health(agent, 90.0)));
+    assert!(!filter.matches(&heartbeat(agent)));
+    assert!(!filter.matches(&lifecycle(agent, LifecycleEventType::AgentClosed)));
+    assert!(!filter.matches(&health(other, 90.0)));
+}
+
+// Test that subscribers only receive matching events published after subscribing
+#[tokio::test]
+async fn test_subscribe() {
+    let bus = EventBus::new(16);
+    let agent = Uuid::new_v4();
+    let other = Uuid::new_v4();
+
+    // Publishing without subscribers is not an error
+    bus.publish(heartbeat(agent));
+    assert_eq!(bus.subscriber_count(), 0);
+
+    let mut everything = Box::pin(bus.subscribe(EventFilter::default()));
+    let mut agent_health = Box::pin(bus.subscribe(
+        EventFilter::new(Some(agent)).with_event_types([HEALTH_SCORE_EVENT.to_string()]),
+    ));
+    assert_eq!(bus.subscriber_count(), 2);
+
+    bus.publish(lifecycle(agent, LifecycleEventType::AgentConnected));
+    bus.publish(health(other, 40.0));
+    bus.publish(health(agent, 95.0));
+
+    let received = tokio::time::timeout(Duration::from_secs(1), everything.next())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(received.event_type(), "agent_connected");
+    assert_eq!(everything.next().await.unwrap().agent_id(), other);
+    assert_eq!(everything.next().await.unwrap().agent_id(), agent);
+
+    match agent_health.next().await.unwrap() {
+        BusEvent::Health(score) => {
+            assert_eq!(score.agent_id, agent);
+            assert_eq!(score.overall_score, 95.0);
+        }
+        event => panic!("Unexpected event: {:?}", event),
+    }
+
+    drop(everything);
+    drop(agent_health);
+    assert_eq!(bus.subscriber_count(), 0);
+}
+
+// Test that a lagging subscriber skips the oldest events instead of blocking publishers
+#[tokio::test]
+async fn test_lagging_subscriber() {
+    let bus = EventBus::new(2);
+    let agent = Uuid::new_v4();
+    let mut subscriber = Box::pin(bus.subscribe(EventFilter::default()));
+
+    for score in [10.0, 20.0, 30.0, 40.0] {
+        bus.publish(health(agent, score));
+    }
+
+    let scores: Vec<f64> = subscriber
+        .by_ref()
+        .take(2)
+        .map(|event| match event {
+            BusEvent::Health(score) => score.overall_score,
+            event => panic!("Unexpected event: {:?}", event),
+        })
+        .collect()
+        .await;
+    assert_eq!(scores, vec![30.0, 40.0]);
+}
+
+// Test the JSON sent to Server-Sent Events clients
+#[test]
+fn test_event_json() {
+    let agent = Uuid::new_v4();
+    let json = serde_json::to_value(heartbeat(agent)).unwrap();
+    assert_eq!(json["kind"], "heartbeat");
+    assert_eq!(json["data"]["agent_id"], agent.to_string());
+    assert_eq!(json["data"]["status"], "online");
+}
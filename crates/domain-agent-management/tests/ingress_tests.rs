@@ -0,0 +1,343 @@
+//! Ingress tests
+//!
+//! Routes are stored in an in-memory SQLite database. The end-to-end tests
+//! wire the Hub and agent multiplexers together with the helpers shared with
+//! the tunnel tests, and the agent forwards its streams to an echo server.
+
+mod common;
+
+use chrono::Utc;
+use common::echo_server;
+use domain_agent_management::config::TunnelConfig;
+use domain_agent_management::server::ingress::{
+    http_host, serve_http, serve_https, tls_server_name, PeekError,
+};
+use domain_agent_management::service::agent::{AgentService, CreateAgentInput};
+use domain_agent_management::service::ingress::{
+    CreateIngressRouteInput, IngressError, IngressService,
+};
+use domain_agent_management::service::task::AgentChannels;
+use domain_agent_management::service::tunnel::TunnelBroker;
+use domain_agent_management::storage::Database;
+use domain_agent_protocol::tunnel::TunnelMessage;
+use serde_json::json;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio::time::timeout;
+use uuid::Uuid;
+
+struct Fixture {
+    ingress: IngressService,
+    broker: TunnelBroker,
+    channels: AgentChannels,
+    agent_id: Uuid,
+}
+
+async fn fixture() -> Fixture {
+    let database = Database::new("sqlite::memory:").await.unwrap();
+    database.run_migrations().await.unwrap();
+    let agent_id = Uuid::new_v4();
+    AgentService::new(database.clone())
+        .create_agent(CreateAgentInput {
+            id: agent_id,
+            name: "lan-1".to_string(),
+            endpoint: "192.0.2.10:9000".to_string(),
+            status: "connected".to_string(),
+            approval_state: "approved".to_string(),
+            capabilities: json!([]),
+            cert_fingerprint: None,
+            auth_method: "secret".to_string(),
+            version: None,
+            registered_at: Some(Utc::now()),
+            last_seen_at: None,
+        })
+        .await
+        .unwrap();
+
+    let broker = TunnelBroker::new(TunnelConfig::default());
+    let channels = AgentChannels::default();
+    let ingress = IngressService::new(database, broker.clone(), channels.clone());
+    Fixture {
+        ingress,
+        broker,
+        channels,
+        agent_id,
+    }
+}
+
+fn route(agent_id: Uuid, hostname: &str, target: &str, tls: bool) -> CreateIngressRouteInput {
+    CreateIngressRouteInput {
+        hostname: hostname.to_string(),
+        agent_id,
+        target: target.to_string(),
+        tls,
+        created_by: Some("admin".to_string()),
+    }
+}
+
+/// Connects an agent multiplexer that forwards all streams to `target`
+async fn connect_agent(broker: &TunnelBroker, agent_id: Uuid, target: SocketAddr) {
+    broker.attach(agent_id, &common::connect(target)).await;
+}
+
+/// Sends `data`, closes the writing half and returns everything received
+async fn exchange(addr: SocketAddr, data: &[u8]) -> Vec<u8> {
+    let mut connection = TcpStream::connect(addr).await.unwrap();
+    connection.write_all(data).await.unwrap();
+    connection.shutdown().await.unwrap();
+    let mut received = Vec::new();
+    timeout(
+        Duration::from_secs(5),
+        connection.read_to_end(&mut received),
+    )
+    .await
+    .unwrap()
+    .unwrap();
+    received
+}
+
+/// A TLS 1.2 style ClientHello with a server_name extension, split into
+/// records of at most `record_size` bytes
+fn client_hello(server_name: &str, record_size: usize) -> Vec<u8> {
+    let name = server_name.as_bytes();
+    let mut sni = Vec::new();
+    sni.extend_from_slice(&((name.len() + 3) as u16).to_be_bytes());
+    sni.push(0);
+    sni.extend_from_slice(&(name.len() as u16).to_be_bytes());
+    sni.extend_from_slice(name);
+
+    let mut extensions = Vec::new();
+    // supported_groups, skipped by the parser
+    extensions.extend_from_slice(&[0x00, 0x0a, 0x00, 0x04, 0x00, 0x02, 0x00, 0x1d]);
+    extensions.extend_from_slice(&[0x00, 0x00]);
+    extensions.extend_from_slice(&(sni.len() as u16).to_be_bytes());
+    extensions.extend_from_slice(&sni);
+
+    let mut body = vec![0x03, 0x03];
+    body.extend_from_slice(&[7u8; 32]);
+    body.push(0);
+    body.extend_from_slice(&[0x00, 0x02, 0x13, 0x01]);
+    body.extend_from_slice(&[0x01, 0x00]);
+    body.extend_from_slice(&(extensions.len() as u16).to_be_bytes());
+    body.extend_from_slice(&extensions);
+
+    let mut handshake = vec![0x01];
+    handshake.extend_from_slice(&(body.len() as u32).to_be_bytes()[1..]);
+    handshake.extend_from_slice(&body);
+
+    let mut records = Vec::new();
+    for fragment in handshake.chunks(record_size) {
+        records.extend_from_slice(&[0x16, 0x03, 0x01]);
+        records.extend_from_slice(&(fragment.len() as u16).to_be_bytes());
+        records.extend_from_slice(fragment);
+    }
+    records
+}
+
+// Test creating, resolving and deleting routes
+#[tokio::test]
+async fn test_route_crud() {
+    let Fixture {
+        ingress,
+        channels,
+        agent_id,
+        ..
+    } = fixture().await;
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    channels.register(agent_id, tx).await;
+
+    let created = ingress
+        .create_route(route(
+            agent_id,
+            "App1.Example.com.",
+            "127.0.0.1:8080",
+            false,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(created.hostname, "app1.example.com");
+
+    // The connected agent is told where to forward the route's streams
+    let assign: TunnelMessage = serde_json::from_str(&rx.recv().await.unwrap()).unwrap();
+    assert_eq!(assign, created.assignment());
+    assert_eq!(
+        ingress.assignments(agent_id).await,
+        vec![created.assignment()]
+    );
+    assert!(ingress.assignments(Uuid::new_v4()).await.is_empty());
+
+    let resolved = ingress.resolve("APP1.example.com").await.unwrap();
+    assert_eq!(resolved.id, created.id);
+    assert!(ingress.resolve("app2.example.com").await.is_none());
+
+    let duplicate = ingress
+        .create_route(route(agent_id, "app1.example.com", "127.0.0.1:9090", true))
+        .await;
+    assert!(matches!(duplicate, Err(IngressError::HostnameTaken(_))));
+    let invalid = ingress
+        .create_route(route(
+            agent_id,
+            "bad_host.example.com",
+            "127.0.0.1:8080",
+            false,
+        ))
+        .await;
+    assert!(matches!(invalid, Err(IngressError::InvalidHostname(_))));
+    for target in ["127.0.0.1", "127.0.0.1:0", "[::1", "host:port"] {
+        let invalid = ingress
+            .create_route(route(agent_id, "app3.example.com", target, false))
+            .await;
+        assert!(
+            matches!(invalid, Err(IngressError::InvalidTarget(_))),
+            "{}",
+            target
+        );
+    }
+    let unknown = ingress
+        .create_route(route(Uuid::new_v4(), "app3.example.com", "[::1]:80", false))
+        .await;
+    assert!(matches!(unknown, Err(IngressError::AgentNotFound(_))));
+
+    ingress
+        .create_route(route(agent_id, "secure.example.com", "[::1]:8443", true))
+        .await
+        .unwrap();
+    let routes = ingress.list_routes().await.unwrap();
+    let hostnames: Vec<&str> = routes.iter().map(|route| route.hostname.as_str()).collect();
+    assert_eq!(hostnames, ["app1.example.com", "secure.example.com"]);
+
+    // Routes survive a restart
+    assert_eq!(ingress.load().await.unwrap(), 2);
+
+    let deleted = ingress.delete_route(created.id).await.unwrap().unwrap();
+    assert_eq!(deleted.id, created.id);
+    assert!(ingress.delete_route(created.id).await.unwrap().is_none());
+    assert!(ingress.get_route(created.id).await.unwrap().is_none());
+    assert!(ingress.resolve("app1.example.com").await.is_none());
+    let _assign_secure = rx.recv().await.unwrap();
+    let close: TunnelMessage = serde_json::from_str(&rx.recv().await.unwrap()).unwrap();
+    assert!(
+        matches!(close, TunnelMessage::TunnelClose { tunnel_id, .. } if tunnel_id == created.id)
+    );
+}
+
+// Test that HTTP requests are routed by Host header through the agent
+#[tokio::test]
+async fn test_http_routing() {
+    let Fixture {
+        ingress,
+        broker,
+        agent_id,
+        ..
+    } = fixture().await;
+    let target = echo_server().await;
+    ingress
+        .create_route(route(
+            agent_id,
+            "app1.example.com",
+            &target.to_string(),
+            false,
+        ))
+        .await
+        .unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(serve_http(listener, ingress.clone()));
+
+    // The agent is offline
+    let request = b"GET / HTTP/1.1\r\nHost: app1.example.com:8000\r\n\r\n";
+    let response = exchange(addr, request).await;
+    assert!(response.starts_with(b"HTTP/1.1 502 "));
+
+    connect_agent(&broker, agent_id, target).await;
+    let mut request =
+        b"POST /upload HTTP/1.1\r\nhost: APP1.example.com\r\nContent-Length: 5\r\n\r\n".to_vec();
+    request.extend_from_slice(b"hello");
+    // The echo server returns the request as received by the target
+    assert_eq!(exchange(addr, &request).await, request);
+
+    let response = exchange(addr, b"GET / HTTP/1.1\r\nHost: unknown.example.com\r\n\r\n").await;
+    assert!(response.starts_with(b"HTTP/1.1 404 "));
+    let response = exchange(addr, b"GET / HTTP/1.1\r\n\r\n").await;
+    assert!(response.starts_with(b"HTTP/1.1 400 "));
+}
+
+// Test that TLS connections are passed through by SNI
+#[tokio::test]
+async fn test_tls_passthrough() {
+    let Fixture {
+        ingress,
+        broker,
+        agent_id,
+        ..
+    } = fixture().await;
+    let target = echo_server().await;
+    ingress
+        .create_route(route(
+            agent_id,
+            "secure.example.com",
+            &target.to_string(),
+            true,
+        ))
+        .await
+        .unwrap();
+    ingress
+        .create_route(route(
+            agent_id,
+            "plain.example.com",
+            &target.to_string(),
+            false,
+        ))
+        .await
+        .unwrap();
+    connect_agent(&broker, agent_id, target).await;
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(serve_https(listener, ingress));
+
+    let hello = client_hello("secure.example.com", 64);
+    assert_eq!(exchange(addr, &hello).await, hello);
+
+    // Routes for plain HTTP are not served over TLS
+    let hello = client_hello("plain.example.com", 1024);
+    assert!(exchange(addr, &hello).await.is_empty());
+}
+
+// Test reading hostnames from request heads and ClientHellos
+#[test]
+fn test_peek_hostname() {
+    assert_eq!(
+        http_host(b"GET / HTTP/1.1\r\nUser-Agent: curl\r\nHost: a.example.com:8080\r\n\r\n"),
+        Ok("a.example.com".to_string())
+    );
+    assert_eq!(
+        http_host(b"GET / HTTP/1.1\r\nHost: [::1]:8080\r\n\r\n"),
+        Ok("[::1]".to_string())
+    );
+    assert_eq!(
+        http_host(b"GET / HTTP/1.1\r\nHost: a"),
+        Err(PeekError::Incomplete)
+    );
+    assert_eq!(http_host(b"\x16\x03\x01\r\n\r\n"), Err(PeekError::Invalid));
+    assert_eq!(
+        http_host(b"GET / HTTP/1.0\r\n\r\n"),
+        Err(PeekError::MissingHost)
+    );
+
+    let hello = client_hello("app1.example.com", 16 * 1024);
+    assert_eq!(tls_server_name(&hello), Ok("app1.example.com".to_string()));
+    // Split into several records, and truncated
+    let hello = client_hello("app1.example.com", 20);
+    assert_eq!(tls_server_name(&hello), Ok("app1.example.com".to_string()));
+    assert_eq!(
+        tls_server_name(&hello[..hello.len() - 1]),
+        Err(PeekError::Incomplete)
+    );
+    assert_eq!(
+        tls_server_name(b"GET / HTTP/1.1\r\n"),
+        Err(PeekError::Invalid)
+    );
+}
@@ -0,0 +1,87 @@
+//! Unit tests for TaskService types
+//!
+//! These tests cover the parts of the task service that do not need a database.
+
+use chrono::Utc;
+use domain_agent_management::service::task::{
+    append_truncated, AgentChannels, TaskInfo, MAX_STORED_OUTPUT,
+};
+use domain_agent_management::storage::entities::task::Model;
+use domain_agent_protocol::task::{TaskStatus, TaskType};
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+fn task_model(status: &str) -> Model {
+    Model {
+        id: Uuid::new_v4(),
+        agent_id: Uuid::new_v4(),
+        task_type: "shell".to_string(),
+        payload: serde_json::json!({
+            "type": "Shell",
+            "data": {"command": "uptime", "working_dir": null, "env_vars": null},
+        }),
+        status: status.to_string(),
+        timeout_seconds: 30,
+        output: String::new(),
+        error: None,
+        exit_code: None,
+        duration_ms: Some(12),
+        submitted_by: Some("admin".to_string()),
+        created_at: Utc::now(),
+        dispatched_at: None,
+        finished_at: None,
+    }
+}
+
+// Test that a stored task converts to TaskInfo
+#[test]
+fn test_task_info_from_model() {
+    let info = TaskInfo::try_from(task_model("running")).unwrap();
+    assert_eq!(info.status, TaskStatus::Running);
+    assert_eq!(info.timeout_seconds, 30);
+    assert_eq!(info.duration_ms, Some(12));
+    assert!(matches!(info.task, TaskType::Shell { ref command, .. } if command == "uptime"));
+}
+
+// Test that an unknown status is reported instead of silently mapped
+#[test]
+fn test_task_info_unknown_status() {
+    assert!(TaskInfo::try_from(task_model("exploded")).is_err());
+}
+
+// Test that stored output keeps the latest bytes
+#[test]
+fn test_append_truncated() {
+    assert_eq!(append_truncated("ab", "cd"), "abcd");
+
+    let big = "x".repeat(MAX_STORED_OUTPUT);
+    let output = append_truncated(&big, "tail");
+    assert_eq!(output.len(), MAX_STORED_OUTPUT);
+    assert!(output.ends_with("tail"));
+
+    // Never cuts a multi-byte character in half
+    let output = append_truncated(&"é".repeat(MAX_STORED_OUTPUT / 2), "!");
+    assert!(output.len() <= MAX_STORED_OUTPUT);
+    assert!(output.ends_with('!'));
+}
+
+// Test that a replaced connection is not unregistered by the old one
+#[tokio::test]
+async fn test_agent_channels() {
+    let channels = AgentChannels::default();
+    let agent_id = Uuid::new_v4();
+    assert!(!channels.send(agent_id, "lost".to_string()).await);
+
+    let (old_tx, _old_rx) = mpsc::unbounded_channel();
+    let (new_tx, mut new_rx) = mpsc::unbounded_channel();
+    channels.register(agent_id, old_tx.clone()).await;
+    channels.register(agent_id, new_tx.clone()).await;
+
+    channels.unregister(agent_id, &old_tx).await;
+    assert!(channels.is_connected(agent_id).await);
+    assert!(channels.send(agent_id, "hello".to_string()).await);
+    assert_eq!(new_rx.recv().await.as_deref(), Some("hello"));
+
+    channels.unregister(agent_id, &new_tx).await;
+    assert!(!channels.is_connected(agent_id).await);
+}
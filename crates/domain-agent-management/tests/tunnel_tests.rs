@@ -0,0 +1,308 @@
+//! Reverse tunnel tests
+//!
+//! The Hub and agent multiplexers are wired together by the shared helpers,
+//! and the agent side forwards its streams to an echo server on localhost, so
+//! data flows through both ends of a tunnel.
+
+mod common;
+
+use common::{connect, echo_server, pump};
+use domain_agent_management::config::TunnelConfig;
+use domain_agent_management::service::tunnel::TunnelBroker;
+use domain_agent_protocol::mux::{Multiplexer, Side};
+use domain_agent_protocol::tunnel::{ForwardDirection, ForwardStatus, TunnelMessage, TunnelType};
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio::time::timeout;
+use uuid::Uuid;
+
+fn local_config() -> TunnelConfig {
+    TunnelConfig {
+        bind_host: "127.0.0.1".to_string(),
+        ..Default::default()
+    }
+}
+
+/// Connects a Hub and an agent multiplexer, the broker relays the streams the agent opens
+fn connect_relayed(broker: &TunnelBroker) -> (Multiplexer, Multiplexer) {
+    let (hub_tx, hub_rx) = mpsc::unbounded_channel();
+    let (agent_tx, agent_rx) = mpsc::unbounded_channel();
+    let (hub, incoming) = Multiplexer::new(Side::Hub, hub_tx);
+    let (agent, _) = Multiplexer::new(Side::Agent, agent_tx);
+    pump(hub_rx, agent.clone());
+    pump(agent_rx, hub.clone());
+    tokio::spawn(broker.clone().relay_streams(hub.clone(), incoming));
+    (hub, agent)
+}
+
+fn forward_status(
+    name: &str,
+    direction: ForwardDirection,
+    target: &str,
+    allowed_agents: &[&str],
+) -> ForwardStatus {
+    ForwardStatus {
+        name: name.to_string(),
+        tunnel_id: Uuid::new_v4(),
+        direction,
+        bind: None,
+        target: target.to_string(),
+        allowed_agents: allowed_agents
+            .iter()
+            .map(|agent| agent.to_string())
+            .collect(),
+        allowed_sources: Vec::new(),
+        active: true,
+        public_port: None,
+        error: None,
+    }
+}
+
+async fn open_tunnel(broker: &TunnelBroker, hub: &Multiplexer, target: SocketAddr) -> SocketAddr {
+    broker
+        .open(
+            Uuid::new_v4(),
+            hub,
+            Uuid::new_v4(),
+            target.to_string(),
+            TunnelType::Tcp,
+            None,
+        )
+        .await
+        .unwrap()
+        .public_addr
+}
+
+// Test that data sent to the public port is echoed back through the tunnel
+#[tokio::test]
+async fn test_tunnel_round_trip() {
+    let target = echo_server().await;
+    let hub = connect(target);
+    let broker = TunnelBroker::new(local_config());
+    let public_addr = open_tunnel(&broker, &hub, target).await;
+    assert_eq!(broker.list(None).await.len(), 1);
+
+    // More than a flow control window, so window updates are needed
+    let data: Vec<u8> = (0..1024 * 1024).map(|i| (i % 251) as u8).collect();
+    let connection = TcpStream::connect(public_addr).await.unwrap();
+    let (mut reader, mut writer) = connection.into_split();
+    let sent = data.clone();
+    tokio::spawn(async move {
+        writer.write_all(&sent).await.unwrap();
+        writer.shutdown().await.unwrap();
+    });
+
+    let mut received = Vec::new();
+    timeout(Duration::from_secs(10), reader.read_to_end(&mut received))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(received.len(), data.len());
+    assert!(received == data);
+}
+
+// Test that a client that does not read only stalls its own stream
+#[tokio::test]
+async fn test_slow_client_does_not_block_other_streams() {
+    let target = echo_server().await;
+    let hub = connect(target);
+    let broker = TunnelBroker::new(local_config());
+    let public_addr = open_tunnel(&broker, &hub, target).await;
+
+    // Sends far more than the socket buffers and windows hold, never reads
+    let mut stalled = TcpStream::connect(public_addr).await.unwrap();
+    let stalled_writer = tokio::spawn(async move {
+        let chunk = vec![0u8; 64 * 1024];
+        loop {
+            if stalled.write_all(&chunk).await.is_err() {
+                break;
+            }
+        }
+    });
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let mut client = TcpStream::connect(public_addr).await.unwrap();
+    let mut reply = [0u8; 5];
+    timeout(Duration::from_secs(5), async {
+        client.write_all(b"hello").await.unwrap();
+        client.read_exact(&mut reply).await.unwrap();
+    })
+    .await
+    .expect("stream stalled by another client");
+    assert_eq!(&reply, b"hello");
+    assert!(!stalled_writer.is_finished());
+    assert_eq!(hub.stream_count(), 2);
+    stalled_writer.abort();
+}
+
+// Test that a connection is closed when the agent cannot reach the target
+#[tokio::test]
+async fn test_unreachable_target_closes_connection() {
+    let unused = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let target = unused.local_addr().unwrap();
+    drop(unused);
+
+    let hub = connect(target);
+    let broker = TunnelBroker::new(local_config());
+    let public_addr = open_tunnel(&broker, &hub, target).await;
+
+    let mut connection = TcpStream::connect(public_addr).await.unwrap();
+    let mut received = Vec::new();
+    let result = timeout(
+        Duration::from_secs(5),
+        connection.read_to_end(&mut received),
+    )
+    .await
+    .unwrap();
+    assert!(matches!(result, Ok(0) | Err(_)));
+    assert!(received.is_empty());
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(hub.stream_count(), 0);
+}
+
+// Test tunnel requests, port ranges and closing the tunnels of a connection
+#[tokio::test]
+async fn test_broker_messages() {
+    let (frames, _frames_rx) = mpsc::unbounded_channel();
+    let (mux, _) = Multiplexer::new(Side::Hub, frames);
+    let agent_id = Uuid::new_v4();
+    let tunnel_id = Uuid::new_v4();
+    let request = |public_port| TunnelMessage::TunnelRequest {
+        tunnel_id,
+        target: "127.0.0.1:8081".to_string(),
+        tunnel_type: TunnelType::Tcp,
+        public_port,
+    };
+
+    let broker = TunnelBroker::new(TunnelConfig {
+        bind_host: "127.0.0.1".to_string(),
+        min_port: 1,
+        max_port: 1023,
+    });
+    let response = broker
+        .handle_message(agent_id, &mux, request(Some(2000)))
+        .await;
+    assert!(matches!(
+        response,
+        Some(TunnelMessage::TunnelResponse {
+            success: false,
+            error: Some(_),
+            ..
+        })
+    ));
+
+    let broker = TunnelBroker::new(local_config());
+    let response = broker.handle_message(agent_id, &mux, request(None)).await;
+    let Some(TunnelMessage::TunnelResponse {
+        success: true,
+        public_port: Some(port),
+        ..
+    }) = response
+    else {
+        panic!("unexpected response {:?}", response);
+    };
+    let tunnels = broker.list(Some(agent_id)).await;
+    assert_eq!(tunnels.len(), 1);
+    assert_eq!(tunnels[0].public_addr.port(), port);
+    assert!(broker.list(Some(Uuid::new_v4())).await.is_empty());
+
+    // Another agent can neither take over nor close the tunnel
+    let other = Uuid::new_v4();
+    let response = broker.handle_message(other, &mux, request(None)).await;
+    assert!(matches!(
+        response,
+        Some(TunnelMessage::TunnelResponse { success: false, .. })
+    ));
+    let close = TunnelMessage::TunnelClose {
+        tunnel_id,
+        reason: None,
+    };
+    assert!(broker.handle_message(other, &mux, close).await.is_none());
+    assert_eq!(broker.list(None).await.len(), 1);
+
+    // A newer connection of the agent keeps the tunnel
+    let (frames, _frames_rx) = mpsc::unbounded_channel();
+    let (old_mux, _) = Multiplexer::new(Side::Hub, frames);
+    assert_eq!(broker.close_connection(&old_mux, "gone").await, 0);
+    assert_eq!(broker.close_connection(&mux, "gone").await, 1);
+    assert!(broker.list(None).await.is_empty());
+}
+
+// Test that streams of a connect forward reach the expose forward of another agent
+#[tokio::test]
+async fn test_forward_relay() {
+    let broker = TunnelBroker::new(local_config());
+    let (client_hub, client) = connect_relayed(&broker);
+    let server_hub = connect(echo_server().await);
+    let (client_id, server_id) = (Uuid::new_v4(), Uuid::new_v4());
+    let connect_forward = forward_status("db", ForwardDirection::Connect, "lan-2/db", &[]);
+    let tunnel_id = connect_forward.tunnel_id;
+    broker
+        .report_forwards(
+            client_id,
+            "lan-1".to_string(),
+            &client_hub,
+            vec![connect_forward],
+        )
+        .await;
+    let expose_forward =
+        forward_status("db", ForwardDirection::Expose, "127.0.0.1:5432", &["lan-1"]);
+    broker
+        .report_forwards(
+            server_id,
+            "lan-2".to_string(),
+            &server_hub,
+            vec![expose_forward],
+        )
+        .await;
+    assert_eq!(
+        broker.forwards(client_id).await.unwrap()[0].target,
+        "lan-2/db"
+    );
+
+    let (mut local, remote) = tokio::io::duplex(1024);
+    tokio::spawn(client.open(tunnel_id).bridge(remote));
+    local.write_all(b"hello").await.unwrap();
+    local.shutdown().await.unwrap();
+    let mut received = Vec::new();
+    timeout(Duration::from_secs(5), local.read_to_end(&mut received))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(received, b"hello");
+
+    // Only agents in the allowed peers may connect
+    let expose_forward =
+        forward_status("db", ForwardDirection::Expose, "127.0.0.1:5432", &["lan-3"]);
+    broker
+        .report_forwards(
+            server_id,
+            "lan-2".to_string(),
+            &server_hub,
+            vec![expose_forward],
+        )
+        .await;
+    let (_local, remote) = tokio::io::duplex(1024);
+    let result = timeout(
+        Duration::from_secs(5),
+        client.open(tunnel_id).bridge(remote),
+    )
+    .await
+    .unwrap();
+    assert!(result.unwrap_err().to_string().contains("may not connect"));
+
+    // The forwards of a closed connection are forgotten
+    broker.close_connection(&server_hub, "gone").await;
+    assert!(broker.forwards(server_id).await.is_none());
+    let (_local, remote) = tokio::io::duplex(1024);
+    let result = timeout(
+        Duration::from_secs(5),
+        client.open(tunnel_id).bridge(remote),
+    )
+    .await
+    .unwrap();
+    assert!(result.unwrap_err().to_string().contains("not connected"));
+}
@@ -0,0 +1,61 @@
+//! TLS certificate probes run by agents inside private networks.
+//!
+//! The agent only performs the handshake and returns the served chain; the Hub
+//! parses the certificates, flags problems and stores the results the same way
+//! as for its own probes.
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Payload of `CertProbeRequest` (Hub -> Agent).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CertProbeRequest {
+    pub request_id: Uuid,
+    /// `host`, `host:port` or `[v6]:port`, the port defaults to 443
+    pub targets: Vec<String>,
+}
+
+/// Payload of `CertProbeResult` (Agent -> Hub).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CertProbeResult {
+    /// The request being answered, empty for scheduled probes
+    #[serde(default)]
+    pub request_id: Option<Uuid>,
+    pub reports: Vec<CertProbeReport>,
+}
+
+/// Outcome of probing one target.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CertProbeReport {
+    /// Target as requested
+    pub target: String,
+    /// Name sent in the TLS SNI extension
+    pub server_name: String,
+    /// Base64 DER certificates as served, leaf first
+    #[serde(default)]
+    pub chain: Vec<String>,
+    /// Connection or handshake failure, `chain` is empty
+    #[serde(default)]
+    pub error: Option<String>,
+    /// Unix timestamp of the probe
+    pub probed_at: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_result_wire_format() {
+        let result: CertProbeResult = serde_json::from_str(
+            r#"{"reports":[{"target":"db.internal:5443","server_name":"db.internal","error":"connection refused","probed_at":1700000000}]}"#,
+        )
+        .unwrap();
+        assert_eq!(result.request_id, None);
+        assert!(result.reports[0].chain.is_empty());
+        assert_eq!(
+            result.reports[0].error.as_deref(),
+            Some("connection refused")
+        );
+    }
+}
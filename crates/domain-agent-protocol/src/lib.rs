@@ -1,9 +1,17 @@
-//! Agent Protocol - Shared protocol definitions for lifecycle events and diagnostic data.
+//! Agent Protocol - Shared protocol definitions for lifecycle events, diagnostic data, remote tasks,
+//! certificate probes and reverse tunnels.
 //!
 //! This crate provides shared types used by both agent-management service and domain-agent.
 
+pub mod certprobe;
 pub mod diagnostic;
 pub mod lifecycle;
+pub mod mux;
+pub mod task;
+pub mod tunnel;
 
+pub use certprobe::*;
 pub use diagnostic::*;
 pub use lifecycle::*;
+pub use task::*;
+pub use tunnel::*;
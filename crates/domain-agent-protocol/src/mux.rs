@@ -0,0 +1,490 @@
+//! Stream multiplexer for reverse tunnels.
+//!
+//! Carries many TCP connections over one agent WebSocket. Each connection is
+//! a stream with its own id; the Hub opens streams with even ids and the
+//! agent with odd ids, so both sides can open streams without coordinating.
+//!
+//! Flow control is credit based: a side may have at most [`INITIAL_WINDOW`]
+//! bytes of a stream in flight, and the receiver hands the credit back with
+//! a `WindowUpdate` once it has written the data to its local connection.
+//! A slow client therefore only stalls its own stream while the WebSocket
+//! keeps carrying the others.
+
+use std::collections::HashMap;
+use std::io;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::sync::{mpsc, Semaphore};
+use uuid::Uuid;
+
+use crate::tunnel::TunnelFrame;
+
+/// Bytes of a stream a side may send before it needs a window update.
+pub const INITIAL_WINDOW: u32 = 256 * 1024;
+
+/// Largest payload of a data frame.
+pub const MAX_FRAME_DATA: usize = 16 * 1024;
+
+/// Which end of the agent WebSocket a multiplexer runs on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Hub,
+    Agent,
+}
+
+impl Side {
+    fn first_stream_id(self) -> u32 {
+        match self {
+            Side::Hub => 2,
+            Side::Agent => 1,
+        }
+    }
+
+    /// Whether streams with this id are opened by this side.
+    fn opens(self, stream_id: u32) -> bool {
+        stream_id % 2 == self.first_stream_id() % 2
+    }
+}
+
+/// Data or close received from the peer for one stream.
+enum Inbound {
+    Data(Vec<u8>),
+    Close(Option<String>),
+}
+
+/// Multiplexer side of a stream.
+struct StreamEntry {
+    tunnel_id: Uuid,
+    inbound: mpsc::UnboundedSender<Inbound>,
+    /// Credit for sending data to the peer
+    send_window: Arc<Semaphore>,
+    /// Bytes received but not yet written to the local connection
+    recv_pending: Arc<AtomicU32>,
+}
+
+impl StreamEntry {
+    /// Aborts the local end of the stream.
+    fn abort(&self, error: String) {
+        let _ = self.inbound.send(Inbound::Close(Some(error)));
+        self.send_window.close();
+    }
+}
+
+struct Shared {
+    side: Side,
+    frames: mpsc::UnboundedSender<Vec<u8>>,
+    streams: Mutex<HashMap<u32, StreamEntry>>,
+    next_stream_id: AtomicU32,
+    incoming: mpsc::UnboundedSender<MuxStream>,
+}
+
+impl Shared {
+    fn streams(&self) -> MutexGuard<'_, HashMap<u32, StreamEntry>> {
+        self.streams.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Queues a frame for the WebSocket, returns false when it is gone.
+    fn send(&self, frame: TunnelFrame) -> bool {
+        self.frames.send(frame.encode()).is_ok()
+    }
+
+    fn register(self: &Arc<Self>, stream_id: u32, tunnel_id: Uuid) -> MuxStream {
+        let (inbound_tx, inbound) = mpsc::unbounded_channel();
+        let send_window = Arc::new(Semaphore::new(INITIAL_WINDOW as usize));
+        let recv_pending = Arc::new(AtomicU32::new(0));
+        self.streams().insert(
+            stream_id,
+            StreamEntry {
+                tunnel_id,
+                inbound: inbound_tx,
+                send_window: send_window.clone(),
+                recv_pending: recv_pending.clone(),
+            },
+        );
+        MuxStream {
+            id: stream_id,
+            tunnel_id,
+            shared: self.clone(),
+            inbound,
+            send_window,
+            recv_pending,
+            finished: false,
+        }
+    }
+
+    /// Aborts a stream on both sides.
+    fn abort(&self, stream_id: u32, error: String) {
+        if let Some(entry) = self.streams().remove(&stream_id) {
+            entry.abort(error.clone());
+            self.send(TunnelFrame::Close {
+                stream_id,
+                error: Some(error),
+            });
+        }
+    }
+}
+
+/// Multiplexes the streams of all tunnels of one agent connection.
+#[derive(Clone)]
+pub struct Multiplexer {
+    shared: Arc<Shared>,
+}
+
+impl Multiplexer {
+    /// Creates a multiplexer that queues its encoded frames on `frames`.
+    ///
+    /// Streams opened by the peer are delivered to the returned receiver,
+    /// dropping the receiver refuses them.
+    pub fn new(
+        side: Side,
+        frames: mpsc::UnboundedSender<Vec<u8>>,
+    ) -> (Self, mpsc::UnboundedReceiver<MuxStream>) {
+        let (incoming, incoming_rx) = mpsc::unbounded_channel();
+        let shared = Shared {
+            side,
+            frames,
+            streams: Mutex::new(HashMap::new()),
+            next_stream_id: AtomicU32::new(side.first_stream_id()),
+            incoming,
+        };
+        (
+            Self {
+                shared: Arc::new(shared),
+            },
+            incoming_rx,
+        )
+    }
+
+    /// Opens a stream to the peer for a connection to a tunnel.
+    pub fn open(&self, tunnel_id: Uuid) -> MuxStream {
+        let stream_id = self.shared.next_stream_id.fetch_add(2, Ordering::SeqCst);
+        let stream = self.shared.register(stream_id, tunnel_id);
+        self.shared.send(TunnelFrame::Open {
+            stream_id,
+            tunnel_id,
+        });
+        stream
+    }
+
+    /// Handles a frame received from the peer.
+    pub fn handle_frame(&self, frame: TunnelFrame) {
+        match frame {
+            TunnelFrame::Open {
+                stream_id,
+                tunnel_id,
+            } => {
+                if self.shared.side.opens(stream_id)
+                    || self.shared.streams().contains_key(&stream_id)
+                {
+                    self.shared.send(TunnelFrame::Close {
+                        stream_id,
+                        error: Some("Invalid stream id".to_string()),
+                    });
+                    return;
+                }
+                let stream = self.shared.register(stream_id, tunnel_id);
+                if let Err(rejected) = self.shared.incoming.send(stream) {
+                    rejected.0.reset("Streams are not accepted");
+                }
+            }
+            TunnelFrame::Data { stream_id, data } => {
+                let streams = self.shared.streams();
+                let Some(entry) = streams.get(&stream_id) else {
+                    // Already closed here, the peer learns from our close
+                    return;
+                };
+                let len = data.len() as u32;
+                let pending = entry.recv_pending.fetch_add(len, Ordering::SeqCst) + len;
+                if pending <= INITIAL_WINDOW {
+                    let _ = entry.inbound.send(Inbound::Data(data));
+                    return;
+                }
+                drop(streams);
+                self.shared
+                    .abort(stream_id, "Flow control window exceeded".to_string());
+            }
+            TunnelFrame::WindowUpdate {
+                stream_id,
+                increment,
+            } => {
+                if let Some(entry) = self.shared.streams().get(&stream_id) {
+                    // Never hold more credit than a whole window
+                    let available = entry.send_window.available_permits() as u32;
+                    let increment = increment.min(INITIAL_WINDOW.saturating_sub(available));
+                    entry.send_window.add_permits(increment as usize);
+                }
+            }
+            TunnelFrame::Close {
+                stream_id,
+                error: None,
+            } => {
+                if let Some(entry) = self.shared.streams().get(&stream_id) {
+                    let _ = entry.inbound.send(Inbound::Close(None));
+                }
+            }
+            TunnelFrame::Close {
+                stream_id,
+                error: Some(error),
+            } => {
+                if let Some(entry) = self.shared.streams().remove(&stream_id) {
+                    entry.abort(error);
+                }
+            }
+        }
+    }
+
+    /// Aborts all streams of a tunnel, returns how many were open.
+    pub fn close_tunnel(&self, tunnel_id: Uuid, reason: &str) -> usize {
+        let stream_ids: Vec<u32> = self
+            .shared
+            .streams()
+            .iter()
+            .filter(|(_, entry)| entry.tunnel_id == tunnel_id)
+            .map(|(stream_id, _)| *stream_id)
+            .collect();
+        for stream_id in &stream_ids {
+            self.shared.abort(*stream_id, reason.to_string());
+        }
+        stream_ids.len()
+    }
+
+    /// Aborts all streams, e.g. when the WebSocket is gone.
+    pub fn close_all(&self, reason: &str) {
+        let streams: Vec<StreamEntry> = self
+            .shared
+            .streams()
+            .drain()
+            .map(|(_, entry)| entry)
+            .collect();
+        for entry in streams {
+            entry.abort(reason.to_string());
+        }
+    }
+
+    /// Whether both multiplexers belong to the same connection.
+    pub fn same_connection(&self, other: &Multiplexer) -> bool {
+        Arc::ptr_eq(&self.shared, &other.shared)
+    }
+
+    /// Number of open streams.
+    pub fn stream_count(&self) -> usize {
+        self.shared.streams().len()
+    }
+}
+
+impl std::fmt::Debug for Multiplexer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Multiplexer")
+            .field("side", &self.shared.side)
+            .field("streams", &self.stream_count())
+            .finish_non_exhaustive()
+    }
+}
+
+/// One multiplexed connection. Dropping it without bridging aborts it.
+pub struct MuxStream {
+    id: u32,
+    tunnel_id: Uuid,
+    shared: Arc<Shared>,
+    inbound: mpsc::UnboundedReceiver<Inbound>,
+    send_window: Arc<Semaphore>,
+    recv_pending: Arc<AtomicU32>,
+    finished: bool,
+}
+
+impl MuxStream {
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Tunnel the stream belongs to.
+    pub fn tunnel_id(&self) -> Uuid {
+        self.tunnel_id
+    }
+
+    /// Aborts the stream, e.g. when its target cannot be reached.
+    pub fn reset(mut self, error: impl Into<String>) {
+        self.finished = true;
+        self.shared.abort(self.id, error.into());
+    }
+
+    /// Copies data between the stream and a local connection until both
+    /// directions are closed.
+    ///
+    /// End of file on the local connection half closes the stream, and a
+    /// half close from the peer shuts down the writing half of `io`. Errors
+    /// on either side abort the stream in both directions.
+    pub async fn bridge<S>(mut self, io: S) -> io::Result<()>
+    where
+        S: AsyncRead + AsyncWrite,
+    {
+        let (mut reader, mut writer) = tokio::io::split(io);
+        let stream_id = self.id;
+        let shared = self.shared.clone();
+        let send_window = self.send_window.clone();
+        let recv_pending = self.recv_pending.clone();
+        let inbound = &mut self.inbound;
+
+        let upstream = async {
+            let mut buf = vec![0u8; MAX_FRAME_DATA];
+            loop {
+                let n = reader.read(&mut buf).await?;
+                if n == 0 {
+                    shared.send(TunnelFrame::Close {
+                        stream_id,
+                        error: None,
+                    });
+                    return Ok(());
+                }
+                match send_window.acquire_many(n as u32).await {
+                    Ok(permit) => permit.forget(),
+                    Err(_) => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::ConnectionReset,
+                            "Stream closed by peer",
+                        ))
+                    }
+                }
+                let frame = TunnelFrame::Data {
+                    stream_id,
+                    data: buf[..n].to_vec(),
+                };
+                if !shared.send(frame) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::BrokenPipe,
+                        "Tunnel connection closed",
+                    ));
+                }
+            }
+        };
+
+        let downstream = async {
+            loop {
+                match inbound.recv().await {
+                    Some(Inbound::Data(data)) => {
+                        writer.write_all(&data).await?;
+                        let increment = data.len() as u32;
+                        recv_pending.fetch_sub(increment, Ordering::SeqCst);
+                        shared.send(TunnelFrame::WindowUpdate {
+                            stream_id,
+                            increment,
+                        });
+                    }
+                    Some(Inbound::Close(None)) => {
+                        writer.shutdown().await?;
+                        return Ok(());
+                    }
+                    Some(Inbound::Close(Some(error))) => {
+                        return Err(io::Error::new(io::ErrorKind::ConnectionReset, error))
+                    }
+                    None => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::ConnectionAborted,
+                            "Tunnel closed",
+                        ))
+                    }
+                }
+            }
+        };
+
+        let result = tokio::try_join!(upstream, downstream).map(|_| ());
+        self.finished = true;
+        match &result {
+            Ok(()) => {
+                self.shared.streams().remove(&stream_id);
+            }
+            Err(e) => self.shared.abort(stream_id, e.to_string()),
+        }
+        result
+    }
+}
+
+impl Drop for MuxStream {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.shared.abort(self.id, "Stream dropped".to_string());
+        }
+    }
+}
+
+impl std::fmt::Debug for MuxStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MuxStream")
+            .field("id", &self.id)
+            .field("tunnel_id", &self.tunnel_id)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(frame: Result<Vec<u8>, mpsc::error::TryRecvError>) -> TunnelFrame {
+        TunnelFrame::decode(&frame.unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_stream_ids() {
+        let (frames, mut frames_rx) = mpsc::unbounded_channel();
+        let (hub, _) = Multiplexer::new(Side::Hub, frames);
+        let tunnel_id = Uuid::new_v4();
+        assert_eq!(hub.open(tunnel_id).id(), 2);
+        assert_eq!(hub.open(tunnel_id).id(), 4);
+        assert!(matches!(
+            decode(frames_rx.try_recv()),
+            TunnelFrame::Open { stream_id: 2, .. }
+        ));
+
+        // The agent may not open streams with the Hub's ids
+        let (frames, mut frames_rx) = mpsc::unbounded_channel();
+        let (hub, mut incoming) = Multiplexer::new(Side::Hub, frames);
+        hub.handle_frame(TunnelFrame::Open {
+            stream_id: 6,
+            tunnel_id,
+        });
+        assert!(matches!(
+            decode(frames_rx.try_recv()),
+            TunnelFrame::Close {
+                stream_id: 6,
+                error: Some(_)
+            }
+        ));
+        hub.handle_frame(TunnelFrame::Open {
+            stream_id: 7,
+            tunnel_id,
+        });
+        assert_eq!(incoming.try_recv().unwrap().id(), 7);
+    }
+
+    #[test]
+    fn test_window_exceeded_aborts_stream() {
+        let (frames, mut frames_rx) = mpsc::unbounded_channel();
+        let (agent, mut incoming) = Multiplexer::new(Side::Agent, frames);
+        agent.handle_frame(TunnelFrame::Open {
+            stream_id: 2,
+            tunnel_id: Uuid::new_v4(),
+        });
+        let _stream = incoming.try_recv().unwrap();
+
+        // Nothing is written to a connection, so no credit comes back
+        let mut sent = 0;
+        while sent <= INITIAL_WINDOW as usize {
+            agent.handle_frame(TunnelFrame::Data {
+                stream_id: 2,
+                data: vec![0; MAX_FRAME_DATA],
+            });
+            sent += MAX_FRAME_DATA;
+        }
+        assert!(matches!(
+            decode(frames_rx.try_recv()),
+            TunnelFrame::Close {
+                stream_id: 2,
+                error: Some(_)
+            }
+        ));
+        assert_eq!(agent.stream_count(), 0);
+    }
+}
@@ -0,0 +1,353 @@
+//! Remote task types shared by the agent and the management service.
+//!
+//! The wire format matches the Hub's `TaskAssigned` / `TaskResult` /
+//! `TaskCancelled` messages, plus `TaskOutput` for streamed stdout/stderr.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Work an agent can be asked to perform.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
+pub enum TaskType {
+    /// Run a command (executed without a shell)
+    Shell {
+        command: String,
+        working_dir: Option<String>,
+        env_vars: Option<std::collections::HashMap<String, String>>,
+    },
+    /// Run a script registered in the agent configuration
+    Script { script_id: Uuid, args: Vec<String> },
+    /// Write a file on the agent
+    FileUpload {
+        remote_path: String,
+        content_base64: String,
+        mode: Option<u32>,
+    },
+    /// Read a file from the agent, the content is returned base64 encoded
+    FileDownload { remote_path: String },
+    /// Delete a file on the agent
+    FileDelete { remote_path: String },
+    /// Report the public IP of the agent
+    GetPublicIp,
+    /// Serve an ACME HTTP-01 challenge at `<web_root>/.well-known/acme-challenge/<token>`.
+    /// An empty `web_root` uses the agent's `[http01]` configuration, an empty
+    /// `key_authorization` removes the challenge.
+    SslHttpChallenge {
+        token: String,
+        key_authorization: String,
+        web_root: String,
+    },
+}
+
+impl TaskType {
+    /// Short name of the task type, as stored by the management service.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            TaskType::Shell { .. } => "shell",
+            TaskType::Script { .. } => "script",
+            TaskType::FileUpload { .. } => "file_upload",
+            TaskType::FileDownload { .. } => "file_download",
+            TaskType::FileDelete { .. } => "file_delete",
+            TaskType::GetPublicIp => "get_public_ip",
+            TaskType::SslHttpChallenge { .. } => "ssl_http_challenge",
+        }
+    }
+}
+
+/// Status of a task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskStatus {
+    /// Stored, waiting for the agent to connect
+    Pending,
+    /// Sent to the agent
+    Dispatched,
+    /// The agent reported output
+    Running,
+    /// Cancellation sent to the agent
+    Cancelling,
+    Succeeded,
+    Failed,
+    Cancelled,
+    TimedOut,
+}
+
+impl TaskStatus {
+    /// Whether the task has finished.
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            TaskStatus::Succeeded
+                | TaskStatus::Failed
+                | TaskStatus::Cancelled
+                | TaskStatus::TimedOut
+        )
+    }
+
+    /// Parses the value produced by `Display`.
+    pub fn parse(value: &str) -> Option<Self> {
+        Some(match value {
+            "pending" => TaskStatus::Pending,
+            "dispatched" => TaskStatus::Dispatched,
+            "running" => TaskStatus::Running,
+            "cancelling" => TaskStatus::Cancelling,
+            "succeeded" => TaskStatus::Succeeded,
+            "failed" => TaskStatus::Failed,
+            "cancelled" => TaskStatus::Cancelled,
+            "timed_out" => TaskStatus::TimedOut,
+            _ => return None,
+        })
+    }
+}
+
+impl std::fmt::Display for TaskStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TaskStatus::Pending => write!(f, "pending"),
+            TaskStatus::Dispatched => write!(f, "dispatched"),
+            TaskStatus::Running => write!(f, "running"),
+            TaskStatus::Cancelling => write!(f, "cancelling"),
+            TaskStatus::Succeeded => write!(f, "succeeded"),
+            TaskStatus::Failed => write!(f, "failed"),
+            TaskStatus::Cancelled => write!(f, "cancelled"),
+            TaskStatus::TimedOut => write!(f, "timed_out"),
+        }
+    }
+}
+
+/// Output stream of a process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// Payload of `TaskAssigned` (Hub -> Agent).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskAssignment {
+    pub task_id: Uuid,
+    pub task_type: TaskType,
+    #[serde(default)]
+    pub params: serde_json::Value,
+    /// 0 uses the agent's default timeout
+    #[serde(default)]
+    pub timeout_seconds: u32,
+}
+
+/// Payload of `TaskOutput` (Agent -> Hub), one chunk of process output.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskOutputChunk {
+    pub task_id: Uuid,
+    pub stream: OutputStream,
+    /// Sequence number of the chunk within the task, starting at 0
+    pub seq: u64,
+    pub data: String,
+}
+
+/// Payload of `TaskResult` (Agent -> Hub).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskResultReport {
+    pub task_id: Uuid,
+    pub success: bool,
+    /// Final status; older agents only report `success`
+    #[serde(default)]
+    pub status: Option<TaskStatus>,
+    /// Tail of stdout, or the base64 content for `FileDownload`
+    pub output: String,
+    pub error: Option<String>,
+    pub exit_code: Option<i32>,
+    pub duration_ms: u64,
+}
+
+impl TaskResultReport {
+    /// The reported status, derived from `success` when missing.
+    pub fn final_status(&self) -> TaskStatus {
+        match self.status {
+            Some(status) if status.is_finished() => status,
+            _ if self.success => TaskStatus::Succeeded,
+            _ => TaskStatus::Failed,
+        }
+    }
+}
+
+/// Payload of `TaskCancelled` (Hub -> Agent).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskCancellation {
+    pub task_id: Uuid,
+    pub reason: String,
+}
+
+/// Represents the type of task audit event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskEventType {
+    TaskSubmitted,
+    TaskDispatched,
+    TaskCancelRequested,
+    TaskSucceeded,
+    TaskFailed,
+    TaskCancelled,
+    TaskTimedOut,
+}
+
+impl TaskEventType {
+    /// The event recorded when a task finishes with `status`.
+    pub fn finished(status: TaskStatus) -> Option<Self> {
+        Some(match status {
+            TaskStatus::Succeeded => TaskEventType::TaskSucceeded,
+            TaskStatus::Failed => TaskEventType::TaskFailed,
+            TaskStatus::Cancelled => TaskEventType::TaskCancelled,
+            TaskStatus::TimedOut => TaskEventType::TaskTimedOut,
+            _ => return None,
+        })
+    }
+
+    /// Parses the value produced by `Display`.
+    pub fn parse(value: &str) -> Option<Self> {
+        Some(match value {
+            "task_submitted" => TaskEventType::TaskSubmitted,
+            "task_dispatched" => TaskEventType::TaskDispatched,
+            "task_cancel_requested" => TaskEventType::TaskCancelRequested,
+            "task_succeeded" => TaskEventType::TaskSucceeded,
+            "task_failed" => TaskEventType::TaskFailed,
+            "task_cancelled" => TaskEventType::TaskCancelled,
+            "task_timed_out" => TaskEventType::TaskTimedOut,
+            _ => return None,
+        })
+    }
+}
+
+impl std::fmt::Display for TaskEventType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TaskEventType::TaskSubmitted => write!(f, "task_submitted"),
+            TaskEventType::TaskDispatched => write!(f, "task_dispatched"),
+            TaskEventType::TaskCancelRequested => write!(f, "task_cancel_requested"),
+            TaskEventType::TaskSucceeded => write!(f, "task_succeeded"),
+            TaskEventType::TaskFailed => write!(f, "task_failed"),
+            TaskEventType::TaskCancelled => write!(f, "task_cancelled"),
+            TaskEventType::TaskTimedOut => write!(f, "task_timed_out"),
+        }
+    }
+}
+
+/// Audit record for a task, modelled after `LifecycleEvent`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskEvent {
+    /// Unique identifier for this event.
+    pub event_id: Uuid,
+    /// The task this event belongs to.
+    pub task_id: Uuid,
+    /// The agent running the task.
+    pub agent_id: Uuid,
+    /// The type of task event.
+    pub event_type: TaskEventType,
+    /// When the event occurred.
+    pub timestamp: DateTime<Utc>,
+    /// Optional reason for the event.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Optional additional metadata.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Value>,
+    /// Optional identifier of what triggered this event.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub triggered_by: Option<String>,
+}
+
+impl TaskEvent {
+    /// Creates a new TaskEvent with the given parameters.
+    pub fn new(task_id: Uuid, agent_id: Uuid, event_type: TaskEventType) -> Self {
+        Self {
+            event_id: Uuid::new_v4(),
+            task_id,
+            agent_id,
+            event_type,
+            timestamp: Utc::now(),
+            reason: None,
+            metadata: None,
+            triggered_by: None,
+        }
+    }
+
+    /// Sets the reason for this event.
+    pub fn with_reason(mut self, reason: impl Into<String>) -> Self {
+        self.reason = Some(reason.into());
+        self
+    }
+
+    /// Sets the metadata for this event.
+    pub fn with_metadata(mut self, metadata: serde_json::Value) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+
+    /// Sets what triggered this event.
+    pub fn with_triggered_by(mut self, triggered_by: impl Into<String>) -> Self {
+        self.triggered_by = Some(triggered_by.into());
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_task_type_wire_format() {
+        let task = TaskType::Shell {
+            command: "uptime".to_string(),
+            working_dir: None,
+            env_vars: None,
+        };
+        let json = serde_json::to_value(&task).unwrap();
+        assert_eq!(json["type"], "Shell");
+        assert_eq!(json["data"]["command"], "uptime");
+        assert_eq!(serde_json::from_value::<TaskType>(json).unwrap(), task);
+
+        let json = serde_json::to_value(TaskType::GetPublicIp).unwrap();
+        assert_eq!(json, serde_json::json!({"type": "GetPublicIp"}));
+    }
+
+    #[test]
+    fn test_task_status_round_trip() {
+        for status in [
+            TaskStatus::Pending,
+            TaskStatus::Dispatched,
+            TaskStatus::Running,
+            TaskStatus::Cancelling,
+            TaskStatus::Succeeded,
+            TaskStatus::Failed,
+            TaskStatus::Cancelled,
+            TaskStatus::TimedOut,
+        ] {
+            assert_eq!(TaskStatus::parse(&status.to_string()), Some(status));
+        }
+        assert!(TaskStatus::TimedOut.is_finished());
+        assert!(!TaskStatus::Cancelling.is_finished());
+    }
+
+    #[test]
+    fn test_result_final_status() {
+        let mut report: TaskResultReport = serde_json::from_value(serde_json::json!({
+            "task_id": Uuid::new_v4(),
+            "success": false,
+            "output": "",
+            "error": "exit status 1",
+            "exit_code": 1,
+            "duration_ms": 5,
+        }))
+        .unwrap();
+        assert_eq!(report.final_status(), TaskStatus::Failed);
+
+        report.status = Some(TaskStatus::TimedOut);
+        assert_eq!(report.final_status(), TaskStatus::TimedOut);
+        assert_eq!(
+            TaskEventType::finished(report.final_status()),
+            Some(TaskEventType::TaskTimedOut)
+        );
+    }
+}
@@ -0,0 +1,347 @@
+//! Reverse tunnel types shared by the agent and the management service.
+//!
+//! An agent asks the Hub for a tunnel with a `TunnelRequest` text message;
+//! the Hub then listens on a public port and opens one stream per accepted
+//! connection. Streams are multiplexed over the agent WebSocket as binary
+//! messages, each holding one [`TunnelFrame`]:
+//!
+//! ```text
+//! +------+-----------+----------------------------------------------+
+//! | type | stream id | body                                         |
+//! | u8   | u32 (BE)  | Open: tunnel id (16 bytes)                   |
+//! |      |           | Data: payload                                |
+//! |      |           | WindowUpdate: increment, u32 (BE)            |
+//! |      |           | Close: UTF-8 error, empty for a clean close  |
+//! +------+-----------+----------------------------------------------+
+//! ```
+//!
+//! The Hub can also define tunnels itself, e.g. for hostname-based ingress
+//! routes, and hands them to the agent with a `TunnelAssign` message.
+//!
+//! Named port forwards configured on an agent are reported with
+//! `ForwardReport` and started or stopped by the Hub with `ForwardStart` and
+//! `ForwardStop`. A `connect` forward listens on the agent and opens a stream
+//! for each connection; the Hub relays it to the `expose` forward of another
+//! agent named by the forward's target.
+//!
+//! See [`crate::mux`] for the flow control rules.
+
+use std::net::IpAddr;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Tunnel type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TunnelType {
+    /// TCP direct connection
+    Tcp,
+    /// HTTP proxy
+    Http,
+    /// WebSocket
+    WebSocket,
+}
+
+impl TunnelType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TunnelType::Tcp => "tcp",
+            TunnelType::Http => "http",
+            TunnelType::WebSocket => "web_socket",
+        }
+    }
+}
+
+/// Direction of a named port forward.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ForwardDirection {
+    /// Expose a service reachable from the agent through the Hub, like `ssh -R`
+    Expose,
+    /// Listen on the agent and reach the `expose` forward of another agent,
+    /// like `ssh -L`
+    Connect,
+}
+
+/// A named port forward as reported by the agent.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForwardStatus {
+    pub name: String,
+    /// Tunnel carrying the streams of the forward
+    pub tunnel_id: Uuid,
+    pub direction: ForwardDirection,
+    /// Listen address on the agent for `connect`, public port on the Hub for
+    /// `expose` (none: only reachable by peers)
+    #[serde(default)]
+    pub bind: Option<String>,
+    /// `host:port` for `expose`, `<agent>/<forward>` for `connect`
+    pub target: String,
+    /// Agents (name or ID) that may connect to an `expose` forward, the Hub
+    /// refuses all others (empty: none)
+    #[serde(default)]
+    pub allowed_agents: Vec<String>,
+    /// Client IPs that may use a `connect` forward, the agent refuses all
+    /// others (empty: none)
+    #[serde(default)]
+    pub allowed_sources: Vec<IpAddr>,
+    /// Whether the forward is running
+    pub active: bool,
+    /// Public port the Hub listens on for an `expose` forward
+    #[serde(default)]
+    pub public_port: Option<u16>,
+    /// Why the forward failed to start
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// Splits the `<agent>/<forward>` target of a `connect` forward.
+pub fn parse_forward_target(target: &str) -> Option<(&str, &str)> {
+    let (agent, forward) = target.split_once('/')?;
+    let valid = |part: &str| !part.is_empty() && !part.contains('/');
+    (valid(agent) && valid(forward)).then_some((agent, forward))
+}
+
+/// Tunnel control messages, sent as JSON text messages.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "payload")]
+pub enum TunnelMessage {
+    /// Agent asks the Hub to expose `target` (a `host:port` reachable from
+    /// the agent) on a public port
+    TunnelRequest {
+        tunnel_id: Uuid,
+        target: String,
+        tunnel_type: TunnelType,
+        /// Public port to listen on, the Hub picks one when unset
+        #[serde(default)]
+        public_port: Option<u16>,
+    },
+
+    /// Hub responds to a tunnel request
+    TunnelResponse {
+        tunnel_id: Uuid,
+        success: bool,
+        public_port: Option<u16>,
+        error: Option<String>,
+    },
+
+    /// Hub asks the agent to forward the streams of a Hub-defined tunnel,
+    /// e.g. an ingress route, to `target`
+    TunnelAssign {
+        tunnel_id: Uuid,
+        target: String,
+        tunnel_type: TunnelType,
+    },
+
+    /// Close a tunnel and all of its streams, sent by either side
+    TunnelClose {
+        tunnel_id: Uuid,
+        reason: Option<String>,
+    },
+
+    /// Agent reports all of its port forwards, after connecting and after
+    /// every change
+    ForwardReport { forwards: Vec<ForwardStatus> },
+
+    /// Hub asks the agent to start a port forward
+    ForwardStart { name: String },
+
+    /// Hub asks the agent to stop a port forward
+    ForwardStop { name: String },
+}
+
+/// One frame of the stream multiplexer, sent as a binary WebSocket message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TunnelFrame {
+    /// Opens a stream for a new connection to a tunnel
+    Open { stream_id: u32, tunnel_id: Uuid },
+    /// Stream data, at most [`crate::mux::MAX_FRAME_DATA`] bytes
+    Data { stream_id: u32, data: Vec<u8> },
+    /// The receiver consumed `increment` bytes, the sender may send that much more
+    WindowUpdate { stream_id: u32, increment: u32 },
+    /// Without an error the sender has no more data (half close), with an
+    /// error the stream is aborted in both directions
+    Close {
+        stream_id: u32,
+        error: Option<String>,
+    },
+}
+
+const FRAME_OPEN: u8 = 1;
+const FRAME_DATA: u8 = 2;
+const FRAME_WINDOW_UPDATE: u8 = 3;
+const FRAME_CLOSE: u8 = 4;
+
+/// Length of the type and stream id header.
+const HEADER_LEN: usize = 5;
+
+/// Error returned when decoding an invalid frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FrameError {
+    /// The message is shorter than its frame type requires
+    Truncated,
+    /// The first byte is not a known frame type
+    UnknownType(u8),
+}
+
+impl std::fmt::Display for FrameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FrameError::Truncated => f.write_str("Truncated tunnel frame"),
+            FrameError::UnknownType(frame_type) => {
+                write!(f, "Unknown tunnel frame type {}", frame_type)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FrameError {}
+
+impl TunnelFrame {
+    pub fn stream_id(&self) -> u32 {
+        match self {
+            TunnelFrame::Open { stream_id, .. }
+            | TunnelFrame::Data { stream_id, .. }
+            | TunnelFrame::WindowUpdate { stream_id, .. }
+            | TunnelFrame::Close { stream_id, .. } => *stream_id,
+        }
+    }
+
+    /// Encodes the frame as the content of a binary WebSocket message.
+    pub fn encode(&self) -> Vec<u8> {
+        let increment_bytes;
+        let (frame_type, body): (u8, &[u8]) = match self {
+            TunnelFrame::Open { tunnel_id, .. } => (FRAME_OPEN, tunnel_id.as_bytes()),
+            TunnelFrame::Data { data, .. } => (FRAME_DATA, data),
+            TunnelFrame::WindowUpdate { increment, .. } => {
+                increment_bytes = increment.to_be_bytes();
+                (FRAME_WINDOW_UPDATE, &increment_bytes)
+            }
+            TunnelFrame::Close { error, .. } => (
+                FRAME_CLOSE,
+                error.as_deref().map(str::as_bytes).unwrap_or_default(),
+            ),
+        };
+        let mut bytes = Vec::with_capacity(HEADER_LEN + body.len());
+        bytes.push(frame_type);
+        bytes.extend_from_slice(&self.stream_id().to_be_bytes());
+        bytes.extend_from_slice(body);
+        bytes
+    }
+
+    /// Decodes a binary WebSocket message.
+    ///
+    /// JSON messages start with `{`, which is not a frame type, so callers
+    /// can fall back to parsing the message as JSON on `UnknownType`.
+    pub fn decode(bytes: &[u8]) -> Result<Self, FrameError> {
+        let frame_type = *bytes.first().ok_or(FrameError::Truncated)?;
+        if !(FRAME_OPEN..=FRAME_CLOSE).contains(&frame_type) {
+            return Err(FrameError::UnknownType(frame_type));
+        }
+        let header: [u8; 4] = bytes
+            .get(1..HEADER_LEN)
+            .and_then(|id| id.try_into().ok())
+            .ok_or(FrameError::Truncated)?;
+        let stream_id = u32::from_be_bytes(header);
+        let body = &bytes[HEADER_LEN..];
+
+        Ok(match frame_type {
+            FRAME_OPEN => TunnelFrame::Open {
+                stream_id,
+                tunnel_id: Uuid::from_slice(body).map_err(|_| FrameError::Truncated)?,
+            },
+            FRAME_DATA => TunnelFrame::Data {
+                stream_id,
+                data: body.to_vec(),
+            },
+            FRAME_WINDOW_UPDATE => TunnelFrame::WindowUpdate {
+                stream_id,
+                increment: u32::from_be_bytes(body.try_into().map_err(|_| FrameError::Truncated)?),
+            },
+            _ => TunnelFrame::Close {
+                stream_id,
+                error: (!body.is_empty()).then(|| String::from_utf8_lossy(body).into_owned()),
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_round_trip() {
+        let frames = [
+            TunnelFrame::Open {
+                stream_id: 2,
+                tunnel_id: Uuid::new_v4(),
+            },
+            TunnelFrame::Data {
+                stream_id: 4,
+                data: b"GET / HTTP/1.1\r\n\r\n".to_vec(),
+            },
+            TunnelFrame::WindowUpdate {
+                stream_id: u32::MAX,
+                increment: 65536,
+            },
+            TunnelFrame::Close {
+                stream_id: 1,
+                error: None,
+            },
+            TunnelFrame::Close {
+                stream_id: 3,
+                error: Some("Connection refused".to_string()),
+            },
+        ];
+        for frame in frames {
+            assert_eq!(TunnelFrame::decode(&frame.encode()).unwrap(), frame);
+        }
+    }
+
+    #[test]
+    fn test_invalid_frames() {
+        assert_eq!(TunnelFrame::decode(&[]), Err(FrameError::Truncated));
+        assert_eq!(
+            TunnelFrame::decode(&[FRAME_DATA, 0, 0]),
+            Err(FrameError::Truncated)
+        );
+        assert_eq!(
+            TunnelFrame::decode(&[FRAME_WINDOW_UPDATE, 0, 0, 0, 1, 0]),
+            Err(FrameError::Truncated)
+        );
+        assert_eq!(
+            TunnelFrame::decode(br#"{"type":"Heartbeat"}"#),
+            Err(FrameError::UnknownType(b'{'))
+        );
+    }
+
+    #[test]
+    fn test_message_wire_format() {
+        let msg = TunnelMessage::TunnelRequest {
+            tunnel_id: Uuid::new_v4(),
+            target: "127.0.0.1:8081".to_string(),
+            tunnel_type: TunnelType::Tcp,
+            public_port: None,
+        };
+        let json = serde_json::to_value(&msg).unwrap();
+        assert_eq!(json["type"], "TunnelRequest");
+        assert_eq!(json["payload"]["target"], "127.0.0.1:8081");
+        assert_eq!(json["payload"]["tunnel_type"], "tcp");
+
+        let msg = TunnelMessage::ForwardStart {
+            name: "db".to_string(),
+        };
+        let json = serde_json::to_value(&msg).unwrap();
+        assert_eq!(json["type"], "ForwardStart");
+        assert_eq!(json["payload"]["name"], "db");
+    }
+
+    #[test]
+    fn test_parse_forward_target() {
+        assert_eq!(parse_forward_target("lan-2/db"), Some(("lan-2", "db")));
+        for target in ["lan-2", "/db", "lan-2/", "lan-2/db/x", "127.0.0.1:5432"] {
+            assert_eq!(parse_forward_target(target), None, "{}", target);
+        }
+    }
+}
@@ -0,0 +1,206 @@
+//! TLS certificate probes
+//!
+//! Connects to a target, completes the TLS handshake without verifying the
+//! certificate and returns the chain the server presented. Parsing and the
+//! expiry, hostname and self-signed checks happen on the Hub, so targets that
+//! are only reachable from this network are reported like the Hub's own.
+
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use domain_agent_protocol::CertProbeReport;
+use futures_util::future::join_all;
+use tokio::net::TcpStream;
+use tokio_rustls::rustls::client::danger::{
+    HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier,
+};
+use tokio_rustls::rustls::crypto::{ring, CryptoProvider};
+use tokio_rustls::rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use tokio_rustls::rustls::{ClientConfig, DigitallySignedStruct, Error, SignatureScheme};
+use tokio_rustls::TlsConnector;
+use tracing::debug;
+
+/// Port used when a target has none
+pub const DEFAULT_PORT: u16 = 443;
+
+/// Split `host`, `host:port` or `[v6]:port` into host and port
+pub fn parse_target(target: &str) -> Result<(String, u16), String> {
+    let target = target.trim();
+    let (host, port) = if let Some(rest) = target.strip_prefix('[') {
+        let (host, rest) = rest
+            .split_once(']')
+            .ok_or_else(|| format!("Invalid target {}", target))?;
+        match rest {
+            "" => (host, None),
+            _ => match rest.strip_prefix(':') {
+                Some(port) => (host, Some(port)),
+                None => return Err(format!("Invalid target {}", target)),
+            },
+        }
+    } else if target.parse::<IpAddr>().is_ok() {
+        (target, None)
+    } else {
+        match target.split_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (target, None),
+        }
+    };
+    if host.is_empty() {
+        return Err(format!("Invalid target {:?}", target));
+    }
+    let port = match port {
+        Some(port) => port
+            .parse()
+            .map_err(|_| format!("Invalid port in target {}", target))?,
+        None => DEFAULT_PORT,
+    };
+    Ok((host.to_string(), port))
+}
+
+/// Probe one target, failures are reported in the result
+pub async fn probe(target: &str, timeout: Duration) -> CertProbeReport {
+    let (server_name, result) = match parse_target(target) {
+        Ok((host, port)) => {
+            let result = tokio::time::timeout(timeout, fetch_chain(&host, port))
+                .await
+                .unwrap_or_else(|_| Err(format!("Timed out after {}s", timeout.as_secs())));
+            (host, result)
+        }
+        Err(e) => (String::new(), Err(e)),
+    };
+    if let Err(e) = &result {
+        debug!("Certificate probe of {} failed: {}", target, e);
+    }
+    CertProbeReport {
+        target: target.to_string(),
+        server_name,
+        chain: result
+            .as_deref()
+            .unwrap_or_default()
+            .iter()
+            .map(|der| BASE64.encode(der))
+            .collect(),
+        error: result.err(),
+        probed_at: chrono::Utc::now().timestamp(),
+    }
+}
+
+/// Probe all targets concurrently
+pub async fn probe_all(targets: &[String], timeout: Duration) -> Vec<CertProbeReport> {
+    join_all(targets.iter().map(|target| probe(target, timeout))).await
+}
+
+/// Handshake with the target and return the DER certificates it presented
+async fn fetch_chain(host: &str, port: u16) -> Result<Vec<Vec<u8>>, String> {
+    let server_name = ServerName::try_from(host.to_string())
+        .map_err(|e| format!("Invalid server name {}: {}", host, e))?;
+    let provider = Arc::new(ring::default_provider());
+    let config = ClientConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .map_err(|e| format!("TLS configuration failed: {}", e))?
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(AcceptAnyCertificate(provider)))
+        .with_no_client_auth();
+
+    let stream = TcpStream::connect((host, port))
+        .await
+        .map_err(|e| format!("Failed to connect to {}:{}: {}", host, port, e))?;
+    let tls = TlsConnector::from(Arc::new(config))
+        .connect(server_name, stream)
+        .await
+        .map_err(|e| format!("TLS handshake with {}:{} failed: {}", host, port, e))?;
+    let chain: Vec<Vec<u8>> = tls
+        .get_ref()
+        .1
+        .peer_certificates()
+        .unwrap_or_default()
+        .iter()
+        .map(|certificate| certificate.to_vec())
+        .collect();
+    match chain.is_empty() {
+        true => Err(format!("{}:{} presented no certificate", host, port)),
+        false => Ok(chain),
+    }
+}
+
+/// Accepts every certificate: the probe inspects the chain instead of trusting it
+#[derive(Debug)]
+struct AcceptAnyCertificate(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for AcceptAnyCertificate {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        _message: &[u8],
+        _cert: &CertificateDer<'_>,
+        _dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, Error> {
+        Ok(HandshakeSignatureValid::assertion())
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        _message: &[u8],
+        _cert: &CertificateDer<'_>,
+        _dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, Error> {
+        Ok(HandshakeSignatureValid::assertion())
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_target() {
+        let parse = |target: &str| parse_target(target).unwrap();
+        assert_eq!(parse("example.com"), ("example.com".to_string(), 443));
+        assert_eq!(parse("example.com:8443"), ("example.com".to_string(), 8443));
+        assert_eq!(parse("10.0.0.5"), ("10.0.0.5".to_string(), 443));
+        assert_eq!(parse("[::1]:8443"), ("::1".to_string(), 8443));
+        assert_eq!(parse("[::1]"), ("::1".to_string(), 443));
+        assert_eq!(parse("fd00::5"), ("fd00::5".to_string(), 443));
+        assert!(parse_target("").is_err());
+        assert!(parse_target(":443").is_err());
+        assert!(parse_target("example.com:https").is_err());
+        assert!(parse_target("[::1").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_probe_failures() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+
+        let reports = probe_all(
+            &[addr.to_string(), "bad:port".to_string()],
+            Duration::from_secs(5),
+        )
+        .await;
+        assert_eq!(reports[0].server_name, "127.0.0.1");
+        assert!(reports[0].chain.is_empty());
+        assert!(reports[0].error.is_some());
+        assert!(reports[1]
+            .error
+            .as_deref()
+            .unwrap()
+            .contains("Invalid port"));
+    }
+}
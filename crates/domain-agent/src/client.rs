@@ -5,15 +5,25 @@ use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::net::TcpStream;
-use tokio::sync::{broadcast, Mutex, RwLock};
+use tokio::sync::{broadcast, mpsc, Mutex, RwLock};
 use tokio::time::interval;
 use tokio_tungstenite::{client_async, connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
-use domain_agent_protocol::{SystemInfoQuery, SystemInfoReport, SystemInfoResponse};
-use crate::config::{AgentConfig, ProxyConfig};
+use domain_agent_protocol::{
+    CertProbeRequest, CertProbeResult, SystemInfoQuery, SystemInfoReport, SystemInfoResponse,
+    TaskAssignment, TaskCancellation, TaskOutputChunk, TaskResultReport, TunnelFrame,
+    TunnelMessage, TunnelType,
+};
+use crate::certprobe;
+use crate::config::{AgentConfig, DdnsMode, ProxyConfig};
+use crate::ddns::DdnsUpdater;
 use crate::diagnostic::collect_system_info;
+use crate::http01::Http01Responder;
 use crate::identity::AgentIdentity;
+use crate::p2p::{P2pManager, P2pMessage};
+use crate::task::TaskExecutor;
+use crate::tunnel::TunnelManager;
 
 /// Agent connection state
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -50,11 +60,54 @@ pub struct AgentClient {
 
     // Shutdown signal
     shutdown_tx: Arc<RwLock<Option<broadcast::Sender<()>>>>,
+
+    // DDNS updater, None when no record is configured
+    ddns: Option<Arc<Mutex<DdnsUpdater>>>,
+
+    // Remote task execution, results are queued until the main loop sends them
+    tasks: TaskExecutor,
+    task_rx: Arc<Mutex<mpsc::UnboundedReceiver<AgentMessage>>>,
+
+    // Certificate probe results, sent through the task message queue
+    probe_tx: mpsc::UnboundedSender<AgentMessage>,
+
+    // ACME HTTP-01 challenges, None when this agent is not a validator
+    http01: Option<Http01Responder>,
+
+    // Reverse tunnels, their frames are queued until the main loop sends them
+    tunnels: TunnelManager,
+    frame_rx: Arc<Mutex<mpsc::UnboundedReceiver<Vec<u8>>>>,
+
+    // P2P connections, their signaling is queued until the main loop sends it
+    p2p: Arc<P2pManager>,
+    p2p_rx: Arc<Mutex<mpsc::UnboundedReceiver<P2pMessage>>>,
 }
 
 impl AgentClient {
     /// Create a new agent client
     pub fn new(config: AgentConfig, identity: AgentIdentity) -> Self {
+        let ddns = config
+            .ddns
+            .clone()
+            .filter(|ddns| !ddns.records.is_empty())
+            .map(|ddns| Arc::new(Mutex::new(DdnsUpdater::new(ddns))));
+        let http01 = config.http01.clone().map(Http01Responder::new);
+        let (task_tx, task_rx) = mpsc::unbounded_channel();
+        let probe_tx = task_tx.clone();
+        let mut tasks = TaskExecutor::new(config.tasks.clone(), task_tx);
+        if let Some(http01) = &http01 {
+            tasks = tasks.with_http01(http01.clone());
+        }
+        let (frame_tx, frame_rx) = mpsc::unbounded_channel();
+        let tunnels = TunnelManager::new(frame_tx);
+        if config.tunnel_port > 0 {
+            tunnels.add(format!("127.0.0.1:{}", config.tunnel_port), TunnelType::Tcp);
+        }
+        for forward in &config.forwards {
+            tunnels.add_forward(forward.clone());
+        }
+        let (p2p_tx, p2p_rx) = mpsc::unbounded_channel();
+        let p2p = Arc::new(P2pManager::new(&config, p2p_tx));
         Self {
             config,
             identity,
@@ -65,9 +118,23 @@ impl AgentClient {
             ws_read: Arc::new(RwLock::new(None)),
             reconnect: Arc::new(ReconnectionManager::new()),
             shutdown_tx: Arc::new(RwLock::new(None)),
+            ddns,
+            tasks,
+            task_rx: Arc::new(Mutex::new(task_rx)),
+            probe_tx,
+            http01,
+            tunnels,
+            frame_rx: Arc::new(Mutex::new(frame_rx)),
+            p2p,
+            p2p_rx: Arc::new(Mutex::new(p2p_rx)),
         }
     }
 
+    /// P2P connection manager
+    pub fn p2p(&self) -> Arc<P2pManager> {
+        self.p2p.clone()
+    }
+
     /// Get current state
     pub fn get_state(&self) -> AgentState {
         AgentState::from(self.state.load(Ordering::SeqCst))
@@ -111,6 +178,14 @@ impl AgentClient {
         // Wait for registration response
         self.wait_for_registration_response().await?;
 
+        // Expose the configured tunnels through the Hub and report the forwards
+        for request in self.tunnels.attach() {
+            let json = serde_json::to_string(&request)
+                .map_err(|e| format!("Failed to serialize tunnel request: {}", e))?;
+            info!("Sending tunnel message: {}", &json);
+            self.send_message(&json).await?;
+        }
+
         Ok(())
     }
 
@@ -181,17 +256,33 @@ impl AgentClient {
         Ok((host, port))
     }
 
+    /// Capabilities advertised to the Hub
+    fn capabilities(&self) -> Vec<String> {
+        let mut capabilities = vec![
+            "ddns_client".to_string(),
+            "shell_executor".to_string(),
+            "cert_monitor".to_string(),
+        ];
+        if self.http01.is_some() {
+            capabilities.push("ssl_validator".to_string());
+        }
+        let tasks = &self.config.tasks;
+        if tasks.enabled && !tasks.scripts.is_empty() {
+            capabilities.push("script_runner".to_string());
+        }
+        if tasks.enabled && !tasks.allowed_paths.is_empty() {
+            capabilities.push("file_transfer".to_string());
+        }
+        capabilities
+    }
+
     /// Send registration message
     async fn send_registration(&self) -> Result<(), String> {
         let register_msg = AgentMessage::RegisterWithSecret {
             agent_id: Some(self.identity.id().to_string()),
             agent_name: self.config.name.clone(),
             agent_key: self.config.key.clone(),
-            capabilities: vec![
-                "ddns_client".to_string(),
-                "shell_executor".to_string(),
-                "ssl_validator".to_string(),
-            ],
+            capabilities: self.capabilities(),
             version: Some(self.config.version.clone()),
             hostname: self.config.hostname.clone(),
         };
@@ -312,26 +403,32 @@ impl AgentClient {
         Ok(())
     }
 
-    /// Receive a text message
+    /// Receive a text message, tunnel frames are handed to the tunnels
     async fn receive_message(&self) -> Result<String, String> {
         let mut read_guard = self.ws_read.write().await;
         let read = read_guard.as_mut()
             .ok_or_else(|| "WebSocket not connected".to_string())?;
 
-        match read.next().await {
-            Some(Ok(msg)) => {
-                let text = msg.into_text()
-                    .map_err(|e| format!("Failed to get text: {}", e))?;
-                info!("Received message: {}", &text);
-                Ok(text)
-            }
-            Some(Err(e)) => {
-                error!("WebSocket error: {}", e);
-                Err(format!("WebSocket error: {}", e))
-            }
-            None => {
-                error!("WebSocket stream ended");
-                Err("WebSocket stream ended".to_string())
+        loop {
+            match read.next().await {
+                Some(Ok(Message::Binary(data))) => match TunnelFrame::decode(&data) {
+                    Ok(frame) => self.tunnels.handle_frame(frame),
+                    Err(e) => warn!("Invalid tunnel frame: {}", e),
+                },
+                Some(Ok(msg)) => {
+                    let text = msg.into_text()
+                        .map_err(|e| format!("Failed to get text: {}", e))?;
+                    info!("Received message: {}", &text);
+                    return Ok(text);
+                }
+                Some(Err(e)) => {
+                    error!("WebSocket error: {}", e);
+                    return Err(format!("WebSocket error: {}", e));
+                }
+                None => {
+                    error!("WebSocket stream ended");
+                    return Err("WebSocket stream ended".to_string());
+                }
             }
         }
     }
@@ -342,6 +439,14 @@ impl AgentClient {
 
         let mut heartbeat_ticker = interval(Duration::from_secs(30));
         let mut consecutive_failures = 0u32;
+        let ddns_interval = match &self.ddns {
+            Some(ddns) => ddns.lock().await.interval(),
+            None => Duration::from_secs(3600),
+        };
+        let mut ddns_ticker = interval(ddns_interval);
+        let cert_probe_interval = self.config.cert_probe.interval();
+        let mut cert_probe_ticker =
+            interval(cert_probe_interval.unwrap_or(Duration::from_secs(3600)));
 
         loop {
             tokio::select! {
@@ -366,6 +471,35 @@ impl AgentClient {
                     }
                 }
 
+                // DDNS public IP check
+                _ = ddns_ticker.tick() => {
+                    if self.ddns.is_some() && self.get_state() == AgentState::Registered {
+                        self.run_ddns_check().await;
+                    }
+                }
+
+                // Scheduled certificate probes
+                _ = cert_probe_ticker.tick() => {
+                    if cert_probe_interval.is_some() && self.get_state() == AgentState::Registered {
+                        self.spawn_cert_probe(None, self.config.cert_probe.targets.clone());
+                    }
+                }
+
+                // Task output and results
+                Some(msg) = self.next_task_message() => {
+                    self.send_task_message(&msg).await;
+                }
+
+                // Reverse tunnel streams
+                Some(frame) = self.next_tunnel_frame() => {
+                    self.send_tunnel_frame(frame).await;
+                }
+
+                // P2P signaling
+                Some(msg) = self.next_p2p_message() => {
+                    self.send_p2p_message(&msg).await;
+                }
+
                 // Incoming messages
                 msg = self.receive_message() => {
                     match msg {
@@ -395,6 +529,23 @@ impl AgentClient {
 
     /// Handle incoming message
     async fn handle_message(&self, msg: &str) -> Result<(), String> {
+        // Tunnel control messages are not part of AgentMessage
+        if let Ok(msg) = serde_json::from_str::<TunnelMessage>(msg) {
+            for reply in self.tunnels.handle_message(msg) {
+                let json = serde_json::to_string(&reply)
+                    .map_err(|e| format!("Failed to serialize tunnel message: {}", e))?;
+                info!("Sending tunnel message: {}", &json);
+                self.send_message(&json).await?;
+            }
+            return Ok(());
+        }
+
+        // P2P signaling relayed by the Hub
+        if let Ok(msg) = serde_json::from_str::<P2pMessage>(msg) {
+            self.p2p.handle_message(msg).await;
+            return Ok(());
+        }
+
         let response: AgentMessage = serde_json::from_str(msg)
             .map_err(|e| format!("Failed to parse message: {}", e))?;
 
@@ -422,6 +573,93 @@ impl AgentClient {
                     warn!("Failed to send system info report: {}", e);
                 }
             }
+            AgentMessage::DdnsUpdateRequest { domain, record_type } => {
+                info!("DdnsUpdateRequest received: {} {}", domain, record_type);
+                match &self.ddns {
+                    Some(ddns) => {
+                        ddns.lock().await.forget(&domain, &record_type);
+                        self.run_ddns_check().await;
+                    }
+                    None => warn!("DDNS is not configured, ignoring update request"),
+                }
+            }
+            AgentMessage::DdnsUpdateResult { domain, record_type, success, new_ip, error, .. } => {
+                let record_type = record_type.unwrap_or_else(|| "A".to_string());
+                if success {
+                    info!("DDNS {} {} updated by Hub: {:?}", domain, record_type, new_ip);
+                } else {
+                    warn!("DDNS {} {} update failed on Hub: {:?}", domain, record_type, error);
+                    // Report the IP again on the next check
+                    if let Some(ddns) = &self.ddns {
+                        ddns.lock().await.forget(&domain, &record_type);
+                    }
+                }
+            }
+            AgentMessage::TaskAssigned { assignment } => {
+                info!(
+                    "TaskAssigned received: task_id={}, type={}",
+                    assignment.task_id,
+                    assignment.task_type.kind()
+                );
+                self.tasks.spawn(assignment).await;
+            }
+            AgentMessage::SslChallengeRequest {
+                domain,
+                challenge_type,
+                token,
+                key_authorization,
+                cleanup,
+            } => {
+                info!(
+                    "SslChallengeRequest received: {} {} token={} cleanup={}",
+                    domain, challenge_type, token, cleanup
+                );
+                let result = match (&self.http01, challenge_type.as_str()) {
+                    (None, _) => Err("HTTP-01 challenges are not configured on this agent".to_string()),
+                    (Some(_), kind) if kind != "http01" => {
+                        Err(format!("Unsupported challenge type {}", kind))
+                    }
+                    (Some(http01), _) if cleanup => http01.cleanup(&token).await,
+                    (Some(http01), _) => match &key_authorization {
+                        Some(key_authorization) => http01.present(&token, key_authorization).await,
+                        None => Err("Missing key authorization".to_string()),
+                    },
+                };
+                if let Err(e) = &result {
+                    warn!("SSL challenge for {} failed: {}", domain, e);
+                }
+                let response = AgentMessage::SslChallengeResponse {
+                    domain,
+                    token: Some(token),
+                    success: result.is_ok(),
+                    key_authorization,
+                    error: result.err(),
+                };
+                let json = serde_json::to_string(&response)
+                    .map_err(|e| format!("Failed to serialize message: {}", e))?;
+                self.send_message(&json).await?;
+            }
+            AgentMessage::CertProbeRequest { request } => {
+                info!(
+                    "CertProbeRequest received: request_id={}, {} targets",
+                    request.request_id,
+                    request.targets.len()
+                );
+                self.spawn_cert_probe(Some(request.request_id), request.targets);
+            }
+            AgentMessage::TaskCancelled { cancellation } => {
+                info!(
+                    "TaskCancelled received: task_id={}, reason={}",
+                    cancellation.task_id, cancellation.reason
+                );
+                if !self
+                    .tasks
+                    .cancel(cancellation.task_id, cancellation.reason)
+                    .await
+                {
+                    debug!("Task {} is not running", cancellation.task_id);
+                }
+            }
             _ => {
                 debug!("Unhandled message type: {:?}", response);
             }
@@ -430,6 +668,123 @@ impl AgentClient {
         Ok(())
     }
 
+    /// Probe the targets in the background, the result is queued for the main loop
+    fn spawn_cert_probe(&self, request_id: Option<Uuid>, targets: Vec<String>) {
+        let timeout = Duration::from_secs(self.config.cert_probe.timeout_secs);
+        let probe_tx = self.probe_tx.clone();
+        tokio::spawn(async move {
+            let reports = certprobe::probe_all(&targets, timeout).await;
+            let result = CertProbeResult { request_id, reports };
+            let _ = probe_tx.send(AgentMessage::CertProbeResult { result });
+        });
+    }
+
+    /// Next output chunk or result produced by a task
+    async fn next_task_message(&self) -> Option<AgentMessage> {
+        self.task_rx.lock().await.recv().await
+    }
+
+    /// Send a task output chunk or result to the Hub
+    async fn send_task_message(&self, msg: &AgentMessage) {
+        let json = match serde_json::to_string(msg) {
+            Ok(json) => json,
+            Err(e) => {
+                warn!("Failed to serialize task message: {}", e);
+                return;
+            }
+        };
+        if let Err(e) = self.send_message(&json).await {
+            warn!("Failed to send task message: {}", e);
+        }
+    }
+
+    /// Next frame produced by the reverse tunnels
+    async fn next_tunnel_frame(&self) -> Option<Vec<u8>> {
+        self.frame_rx.lock().await.recv().await
+    }
+
+    /// Send a tunnel frame to the Hub as a binary message
+    async fn send_tunnel_frame(&self, frame: Vec<u8>) {
+        let mut write_guard = self.ws_write.lock().await;
+        // Frames of a connection that is gone are dropped, its streams are aborted
+        let Some(write) = write_guard.as_mut() else {
+            return;
+        };
+        if let Err(e) = write.send(Message::Binary(frame)).await {
+            warn!("Failed to send tunnel frame: {}", e);
+        }
+    }
+
+    /// Next signaling message produced by the P2P connections
+    async fn next_p2p_message(&self) -> Option<P2pMessage> {
+        self.p2p_rx.lock().await.recv().await
+    }
+
+    /// Send a P2P signaling message to the Hub
+    async fn send_p2p_message(&self, msg: &P2pMessage) {
+        let json = match serde_json::to_string(msg) {
+            Ok(json) => json,
+            Err(e) => {
+                warn!("Failed to serialize P2P message: {}", e);
+                return;
+            }
+        };
+        if let Err(e) = self.send_message(&json).await {
+            warn!("Failed to send P2P message: {}", e);
+        }
+    }
+
+    /// Check the public IP and report (or apply) the changes
+    async fn run_ddns_check(&self) {
+        let Some(ddns) = &self.ddns else {
+            return;
+        };
+        let mut updater = ddns.lock().await;
+        for change in updater.check().await {
+            let msg = match updater.mode() {
+                DdnsMode::Hub => AgentMessage::DdnsIpChanged {
+                    domain: change.name.clone(),
+                    record_type: change.record_type.clone(),
+                    old_ip: change.old_ip.map(|ip| ip.to_string()),
+                    new_ip: change.new_ip.to_string(),
+                },
+                DdnsMode::Direct => {
+                    let result = updater.update_direct(&change).await;
+                    if let Err(e) = &result {
+                        warn!("DDNS direct update of {} failed: {}", change.name, e);
+                        updater.forget(&change.name, &change.record_type);
+                    }
+                    AgentMessage::DdnsUpdateResult {
+                        domain: change.name.clone(),
+                        record_type: Some(change.record_type.clone()),
+                        success: result.is_ok(),
+                        old_ip: result
+                            .as_ref()
+                            .ok()
+                            .cloned()
+                            .flatten()
+                            .or_else(|| change.old_ip.map(|ip| ip.to_string())),
+                        new_ip: Some(change.new_ip.to_string()),
+                        error: result.err(),
+                    }
+                }
+            };
+
+            let json = match serde_json::to_string(&msg) {
+                Ok(json) => json,
+                Err(e) => {
+                    warn!("Failed to serialize DDNS message: {}", e);
+                    continue;
+                }
+            };
+            info!("Sending DDNS message: {}", &json);
+            if let Err(e) = self.send_message(&json).await {
+                warn!("Failed to send DDNS message: {}", e);
+                updater.forget(&change.name, &change.record_type);
+            }
+        }
+    }
+
     /// Send heartbeat using saved write half
     async fn send_heartbeat(&self) -> Result<(), String> {
         let msg = AgentMessage::Heartbeat {
@@ -504,6 +859,7 @@ impl AgentClient {
 
     /// Clear connection resources
     async fn clear_connection(&self) {
+        self.tunnels.detach();
         {
             let mut write_guard = self.ws_write.lock().await;
             *write_guard = None;
@@ -531,6 +887,7 @@ impl AgentClient {
             warn!("Failed to send unregister: {}", e);
         }
 
+        self.tunnels.stop_all();
         self.clear_connection().await;
         self.set_state(AgentState::Closed);
         info!("Disconnected gracefully");
@@ -681,6 +1038,99 @@ pub enum AgentMessage {
         #[serde(flatten)]
         response: SystemInfoResponse,
     },
+
+    /// Hub asks the agent to check and report a DDNS record now
+    #[serde(rename = "DdnsUpdateRequest")]
+    DdnsUpdateRequest {
+        domain: String,
+        record_type: String,
+    },
+
+    /// Public IP changed, the Hub should update the record
+    #[serde(rename = "DdnsIpChanged")]
+    DdnsIpChanged {
+        domain: String,
+        record_type: String,
+        old_ip: Option<String>,
+        new_ip: String,
+    },
+
+    /// DDNS update result (from the Hub, or reported after a direct update)
+    #[serde(rename = "DdnsUpdateResult")]
+    DdnsUpdateResult {
+        domain: String,
+        #[serde(default)]
+        record_type: Option<String>,
+        success: bool,
+        old_ip: Option<String>,
+        new_ip: Option<String>,
+        error: Option<String>,
+    },
+
+    /// Task assigned by the Hub
+    #[serde(rename = "TaskAssigned")]
+    TaskAssigned {
+        #[serde(flatten)]
+        assignment: TaskAssignment,
+    },
+
+    /// Chunk of stdout/stderr of a running task
+    #[serde(rename = "TaskOutput")]
+    TaskOutput {
+        #[serde(flatten)]
+        chunk: TaskOutputChunk,
+    },
+
+    /// Result of a finished task
+    #[serde(rename = "TaskResult")]
+    TaskResult {
+        #[serde(flatten)]
+        report: TaskResultReport,
+    },
+
+    /// Hub cancels a running task
+    #[serde(rename = "TaskCancelled")]
+    TaskCancelled {
+        #[serde(flatten)]
+        cancellation: TaskCancellation,
+    },
+
+    /// Hub asks to serve (or remove) an ACME challenge
+    #[serde(rename = "SslChallengeRequest")]
+    SslChallengeRequest {
+        domain: String,
+        /// `http01` or `dns01`
+        challenge_type: String,
+        token: String,
+        #[serde(default)]
+        key_authorization: Option<String>,
+        #[serde(default)]
+        cleanup: bool,
+    },
+
+    /// Result of an `SslChallengeRequest`
+    #[serde(rename = "SslChallengeResponse")]
+    SslChallengeResponse {
+        domain: String,
+        token: Option<String>,
+        success: bool,
+        key_authorization: Option<String>,
+        error: Option<String>,
+    },
+
+    /// Hub asks to probe the TLS certificates of some targets
+    #[serde(rename = "CertProbeRequest")]
+    CertProbeRequest {
+        #[serde(flatten)]
+        request: CertProbeRequest,
+    },
+
+    /// Certificate chains presented by the probed targets
+    #[serde(rename = "CertProbeResult")]
+    CertProbeResult {
+        #[serde(flatten)]
+        result: CertProbeResult,
+    },
 }
 
 /// Agent metrics
@@ -806,4 +1256,98 @@ mod tests {
         assert_eq!(AgentState::from(3), AgentState::Registered);
         assert_eq!(AgentState::from(99), AgentState::Disconnected);
     }
+
+    #[test]
+    fn test_task_message_wire_format() {
+        let task_id = Uuid::new_v4();
+        let msg: AgentMessage = serde_json::from_value(serde_json::json!({
+            "type": "TaskAssigned",
+            "payload": {
+                "task_id": task_id,
+                "task_type": {"type": "Shell", "data": {"command": "uptime", "working_dir": null, "env_vars": null}},
+                "params": null,
+                "timeout_seconds": 30,
+            },
+        }))
+        .unwrap();
+        match msg {
+            AgentMessage::TaskAssigned { assignment } => {
+                assert_eq!(assignment.task_id, task_id);
+                assert_eq!(assignment.task_type.kind(), "shell");
+                assert_eq!(assignment.timeout_seconds, 30);
+            }
+            other => panic!("unexpected message {:?}", other),
+        }
+
+        let json = serde_json::to_value(AgentMessage::TaskOutput {
+            chunk: TaskOutputChunk {
+                task_id,
+                stream: domain_agent_protocol::OutputStream::Stderr,
+                seq: 2,
+                data: "oops".to_string(),
+            },
+        })
+        .unwrap();
+        assert_eq!(json["type"], "TaskOutput");
+        assert_eq!(json["payload"]["stream"], "stderr");
+        assert_eq!(json["payload"]["seq"], 2);
+    }
+
+    #[test]
+    fn test_ssl_challenge_wire_format() {
+        let msg: AgentMessage = serde_json::from_value(serde_json::json!({
+            "type": "SslChallengeRequest",
+            "payload": {
+                "domain": "www.example.com",
+                "challenge_type": "http01",
+                "token": "token-1",
+                "key_authorization": "token-1.thumbprint",
+                "cleanup": false,
+            },
+        }))
+        .unwrap();
+        assert!(matches!(
+            msg,
+            AgentMessage::SslChallengeRequest { ref challenge_type, cleanup: false, .. }
+                if challenge_type == "http01"
+        ));
+
+        let json = serde_json::to_value(AgentMessage::SslChallengeResponse {
+            domain: "www.example.com".to_string(),
+            token: Some("token-1".to_string()),
+            success: true,
+            key_authorization: None,
+            error: None,
+        })
+        .unwrap();
+        assert_eq!(json["type"], "SslChallengeResponse");
+        assert_eq!(json["payload"]["token"], "token-1");
+    }
+
+    #[test]
+    fn test_cert_probe_wire_format() {
+        let msg: AgentMessage = serde_json::from_value(serde_json::json!({
+            "type": "CertProbeRequest",
+            "payload": {
+                "request_id": "9b2f4c1e-8f3a-4d6b-9a4e-2c1d5e7f8a90",
+                "targets": ["intranet.local:8443"],
+            },
+        }))
+        .unwrap();
+        assert!(matches!(
+            msg,
+            AgentMessage::CertProbeRequest { ref request } if request.targets.len() == 1
+        ));
+
+        let json = serde_json::to_value(AgentMessage::CertProbeResult {
+            result: CertProbeResult {
+                request_id: None,
+                reports: vec![],
+            },
+        })
+        .unwrap();
+        assert_eq!(json["type"], "CertProbeResult");
+        assert!(json["payload"]["request_id"].is_null());
+        assert!(json["payload"]["reports"].as_array().unwrap().is_empty());
+    }
 }
@@ -1,11 +1,14 @@
 //! Agent configuration
 
 use clap::{CommandFactory, Parser};
+use domain_agent_protocol::tunnel::{parse_forward_target, ForwardDirection};
 use serde::{Deserialize, Serialize};
 use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::OnceLock;
+use std::time::Duration;
+use uuid::Uuid;
 
 static CONFIG_FILE_PATH: OnceLock<Option<String>> = OnceLock::new();
 
@@ -78,6 +81,510 @@ impl Default for ReconnectionConfig {
     }
 }
 
+/// Where the DDNS updater reads the public IP from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IpSource {
+    /// Global addresses of local network interfaces
+    #[default]
+    Interface,
+    /// Mapped address returned by a STUN binding request
+    Stun,
+}
+
+/// Who updates the DNS records when the public IP changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DdnsMode {
+    /// Report to the Hub, which updates the records with the account's credential
+    #[default]
+    Hub,
+    /// Update the records directly with a scoped credential, then report to the Hub
+    Direct,
+}
+
+/// Cloudflare API token limited to DNS edit on a single zone
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CloudflareCredential {
+    pub api_token: String,
+    pub zone_id: String,
+}
+
+/// A record kept in sync with the public IP
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DdnsRecord {
+    /// Fully qualified name, e.g. `home.example.com`
+    pub name: String,
+    /// `A` or `AAAA`
+    #[serde(rename = "type", default = "default_ddns_record_type")]
+    pub record_type: String,
+}
+
+fn default_ddns_record_type() -> String {
+    "A".to_string()
+}
+
+fn default_ddns_interval() -> u64 {
+    300
+}
+
+/// DDNS configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DdnsConfig {
+    /// Check interval in seconds
+    #[serde(default = "default_ddns_interval")]
+    pub interval_secs: u64,
+    #[serde(default)]
+    pub source: IpSource,
+    /// Only use this interface when `source = "interface"`
+    #[serde(default)]
+    pub interface: Option<String>,
+    /// STUN server (host:port) when `source = "stun"`
+    #[serde(default)]
+    pub stun_server: Option<String>,
+    #[serde(default)]
+    pub mode: DdnsMode,
+    /// Credential for `mode = "direct"`
+    #[serde(default)]
+    pub cloudflare: Option<CloudflareCredential>,
+    #[serde(default)]
+    pub records: Vec<DdnsRecord>,
+}
+
+impl DdnsConfig {
+    /// Check that the interval is positive, that the IP source and mode have
+    /// the settings they need and that every record is a named A or AAAA record
+    pub fn validate(&self) -> Result<(), String> {
+        if self.interval_secs == 0 {
+            return Err("ddns.interval_secs must be greater than 0".to_string());
+        }
+        if self.source == IpSource::Stun && self.stun_server.is_none() {
+            return Err("ddns.stun_server is required when source = \"stun\"".to_string());
+        }
+        if self.mode == DdnsMode::Direct && self.cloudflare.is_none() {
+            return Err("ddns.cloudflare is required when mode = \"direct\"".to_string());
+        }
+        for record in &self.records {
+            if record.name.trim().is_empty() {
+                return Err("ddns record name must not be empty".to_string());
+            }
+            if !matches!(record.record_type.as_str(), "A" | "AAAA") {
+                return Err(format!(
+                    "ddns record {} has unsupported type {}, expected A or AAAA",
+                    record.name, record.record_type
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A script the Hub may run by ID
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScriptConfig {
+    pub id: Uuid,
+    /// Executable run with the arguments sent by the Hub
+    pub path: String,
+}
+
+fn default_task_timeout() -> u64 {
+    300
+}
+
+fn default_max_task_timeout() -> u64 {
+    3600
+}
+
+/// Remote tasks this agent accepts from the Hub
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskPolicy {
+    /// Reject every task when false
+    #[serde(default)]
+    pub enabled: bool,
+    /// Allowed commands, matched on the leading arguments:
+    /// `"systemctl status"` allows `systemctl status nginx`, `"*"` allows any command
+    #[serde(default)]
+    pub allowed_commands: Vec<String>,
+    /// Directories file tasks may read, write and delete in, and commands may run in
+    #[serde(default)]
+    pub allowed_paths: Vec<String>,
+    /// Environment variables the Hub may set for commands; `PATH`, `LD_*` and
+    /// `DYLD_*` are never accepted
+    #[serde(default)]
+    pub allowed_env: Vec<String>,
+    #[serde(default)]
+    pub scripts: Vec<ScriptConfig>,
+    /// Timeout used when the Hub does not set one
+    #[serde(default = "default_task_timeout")]
+    pub default_timeout_secs: u64,
+    /// Upper bound for timeouts requested by the Hub
+    #[serde(default = "default_max_task_timeout")]
+    pub max_timeout_secs: u64,
+}
+
+impl Default for TaskPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            allowed_commands: Vec::new(),
+            allowed_paths: Vec::new(),
+            allowed_env: Vec::new(),
+            scripts: Vec::new(),
+            default_timeout_secs: default_task_timeout(),
+            max_timeout_secs: default_max_task_timeout(),
+        }
+    }
+}
+
+impl TaskPolicy {
+    /// Check that the timeouts are positive, that the allowed paths are
+    /// absolute and that the allowed environment does not include `PATH` or
+    /// loader variables
+    pub fn validate(&self) -> Result<(), String> {
+        if self.default_timeout_secs == 0 || self.max_timeout_secs == 0 {
+            return Err("tasks timeouts must be greater than 0".to_string());
+        }
+        for path in &self.allowed_paths {
+            if !Path::new(path).is_absolute() {
+                return Err(format!("tasks.allowed_paths entry {} must be absolute", path));
+            }
+        }
+        if let Some(name) = self.allowed_env.iter().find(|name| env_forbidden(name)) {
+            return Err(format!("tasks.allowed_env must not contain {}", name));
+        }
+        Ok(())
+    }
+
+    /// Whether the command is allowed; its program (`argv[0]`) is already
+    /// resolved with [`resolve_program`], and so is the program of each entry
+    pub fn command_allowed(&self, argv: &[String]) -> bool {
+        self.allowed_commands.iter().any(|allowed| {
+            let prefix: Vec<&str> = allowed.split_whitespace().collect();
+            let Some((program, args)) = prefix.split_first() else {
+                return false;
+            };
+            if prefix == ["*"] {
+                return true;
+            }
+            let program_matches = resolve_program(program).is_ok_and(|program| {
+                Some(program.to_string_lossy().as_ref()) == argv.first().map(String::as_str)
+            });
+            program_matches
+                && args.len() < argv.len()
+                && args.iter().zip(&argv[1..]).all(|(a, b)| *a == b)
+        })
+    }
+
+    /// Whether the Hub may set the environment variable
+    pub fn env_allowed(&self, name: &str) -> bool {
+        !env_forbidden(name) && self.allowed_env.iter().any(|allowed| allowed == name)
+    }
+
+    /// Whether the (normalized, absolute) path is inside an allowed directory
+    pub fn path_allowed(&self, path: &Path) -> bool {
+        self.allowed_paths
+            .iter()
+            .any(|allowed| path.starts_with(allowed))
+    }
+
+    /// Find a configured script
+    pub fn script(&self, id: &Uuid) -> Option<&ScriptConfig> {
+        self.scripts.iter().find(|script| &script.id == id)
+    }
+
+    /// Effective timeout for a task, `requested` = 0 uses the default
+    pub fn timeout(&self, requested: u32) -> Duration {
+        let secs = if requested == 0 {
+            self.default_timeout_secs
+        } else {
+            requested as u64
+        };
+        Duration::from_secs(secs.min(self.max_timeout_secs))
+    }
+}
+
+/// Variables that change which program runs or what it loads
+fn env_forbidden(name: &str) -> bool {
+    let name = name.to_ascii_uppercase();
+    name == "PATH" || name.starts_with("LD_") || name.starts_with("DYLD_")
+}
+
+/// Absolute path of a program: a bare name is looked up in the agent's own
+/// `PATH`, any other name must be absolute, so neither depends on a working
+/// directory or environment chosen by the Hub
+///
+/// The directory is canonicalized but the file name is kept, so multi-call
+/// binaries such as busybox still see the name they were started as.
+pub fn resolve_program(program: &str) -> Result<PathBuf, String> {
+    let path = Path::new(program);
+    let candidate = if path.is_absolute() {
+        path.to_path_buf()
+    } else if path.components().count() == 1 {
+        let search = std::env::var_os("PATH").unwrap_or_default();
+        std::env::split_paths(&search)
+            .filter(|dir| dir.is_absolute())
+            .map(|dir| dir.join(path))
+            .find(|candidate| is_executable(candidate))
+            .ok_or_else(|| format!("Program {} not found in PATH", program))?
+    } else {
+        return Err(format!("Program path {} must be absolute", program));
+    };
+    let (Some(dir), Some(name)) = (candidate.parent(), candidate.file_name()) else {
+        return Err(format!("Invalid program {}", program));
+    };
+    let dir =
+        std::fs::canonicalize(dir).map_err(|e| format!("Program {} not found: {}", program, e))?;
+    let resolved = dir.join(name);
+    if !is_executable(&resolved) {
+        return Err(format!("Program {} is not an executable file", program));
+    }
+    Ok(resolved)
+}
+
+fn is_executable(path: &Path) -> bool {
+    let Ok(metadata) = std::fs::metadata(path) else {
+        return false;
+    };
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        metadata.is_file() && metadata.permissions().mode() & 0o111 != 0
+    }
+    #[cfg(not(unix))]
+    metadata.is_file()
+}
+
+fn default_http01_listen_timeout() -> u64 {
+    300
+}
+
+/// How ACME HTTP-01 challenges for this host are answered
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Http01Config {
+    /// Write challenge files to `<web_root>/.well-known/acme-challenge/` for the
+    /// web server already running on this host
+    #[serde(default)]
+    pub web_root: Option<String>,
+    /// Serve challenges from a built-in listener on this address (e.g. `0.0.0.0:80`)
+    #[serde(default)]
+    pub listen: Option<String>,
+    /// Stop the built-in listener after this many seconds, even if challenges are left
+    #[serde(default = "default_http01_listen_timeout")]
+    pub listen_timeout_secs: u64,
+}
+
+impl Http01Config {
+    /// Check that exactly one of an absolute web root and a listen address is
+    /// set, the latter with a positive timeout
+    pub fn validate(&self) -> Result<(), String> {
+        match (&self.web_root, &self.listen) {
+            (Some(web_root), None) => {
+                if !Path::new(web_root).is_absolute() {
+                    return Err(format!("http01.web_root {} must be absolute", web_root));
+                }
+            }
+            (None, Some(listen)) => {
+                if listen.parse::<std::net::SocketAddr>().is_err() {
+                    return Err(format!("http01.listen {} is not an IP:port address", listen));
+                }
+                if self.listen_timeout_secs == 0 {
+                    return Err("http01.listen_timeout_secs must be greater than 0".to_string());
+                }
+            }
+            _ => return Err("http01 requires exactly one of web_root and listen".to_string()),
+        }
+        Ok(())
+    }
+}
+
+fn default_cert_probe_interval() -> u64 {
+    86400
+}
+
+fn default_cert_probe_timeout() -> u64 {
+    10
+}
+
+/// TLS certificate probes reported to the Hub
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CertProbeConfig {
+    /// Probed on a schedule: `host`, `host:port` or `[v6]:port`
+    #[serde(default)]
+    pub targets: Vec<String>,
+    /// Seconds between scheduled probes (0 = only when the Hub asks)
+    #[serde(default = "default_cert_probe_interval")]
+    pub interval_secs: u64,
+    /// Connect and handshake timeout of one probe
+    #[serde(default = "default_cert_probe_timeout")]
+    pub timeout_secs: u64,
+}
+
+impl Default for CertProbeConfig {
+    fn default() -> Self {
+        Self {
+            targets: Vec::new(),
+            interval_secs: default_cert_probe_interval(),
+            timeout_secs: default_cert_probe_timeout(),
+        }
+    }
+}
+
+impl CertProbeConfig {
+    /// Check that every target is a valid `host[:port]` and the timeout is positive
+    pub fn validate(&self) -> Result<(), String> {
+        for target in &self.targets {
+            crate::certprobe::parse_target(target)
+                .map_err(|e| format!("cert_probe.targets: {}", e))?;
+        }
+        if self.timeout_secs == 0 {
+            return Err("cert_probe.timeout_secs must be greater than 0".to_string());
+        }
+        Ok(())
+    }
+
+    /// Interval of scheduled probes, None when there is nothing to probe
+    pub fn interval(&self) -> Option<Duration> {
+        match self.targets.is_empty() || self.interval_secs == 0 {
+            true => None,
+            false => Some(Duration::from_secs(self.interval_secs)),
+        }
+    }
+}
+
+/// TURN server for relayed P2P candidates, with long-term credentials
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TurnConfig {
+    /// host:port
+    pub server: String,
+    pub username: String,
+    pub password: String,
+}
+
+/// A local listener whose connections are carried to another agent over
+/// P2P, `[[p2p.connect]]` in the config file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct P2pConnectConfig {
+    /// Local `IP:port` to listen on
+    pub bind: String,
+    /// ID of the agent whose `expose` target the connections reach
+    pub peer: Uuid,
+}
+
+/// Whether `addr` looks like `host:port` with a non-zero port
+fn is_host_port(addr: &str) -> bool {
+    let port = addr.rsplit_once(':').map(|(_, port)| port);
+    port.is_some_and(|port| port.parse::<u16>().is_ok_and(|port| port > 0))
+}
+
+fn default_forward_enabled() -> bool {
+    true
+}
+
+/// A named port forward, `[[forward]]` in the config file
+///
+/// Unknown keys are rejected, so a misspelt allow-list is not dropped silently.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ForwardConfig {
+    pub name: String,
+    pub direction: ForwardDirection,
+    /// `expose`: public port on the Hub as `[host:]port`, the Hub picks the
+    /// host; unset keeps the forward reachable by peers only.
+    /// `connect`: local `IP:port` to listen on
+    #[serde(default)]
+    pub bind: Option<String>,
+    /// `expose`: `host:port` reachable from this agent.
+    /// `connect`: `<agent>/<forward>`, an `expose` forward of another agent
+    pub target: String,
+    /// `expose` only: agents (name or ID) whose `connect` forwards may reach
+    /// it, checked by the Hub. Empty allows no agent
+    #[serde(default)]
+    pub allowed_agents: Vec<String>,
+    /// `connect` only: client IPs allowed to connect to `bind`, checked by
+    /// the agent. Empty allows no client
+    #[serde(default)]
+    pub allowed_sources: Vec<std::net::IpAddr>,
+    /// Start with the agent, otherwise only when the Hub starts it
+    #[serde(default = "default_forward_enabled")]
+    pub enabled: bool,
+}
+
+impl ForwardConfig {
+    /// Check the name and, by direction, that `expose` has a `host:port` target
+    /// and `connect` an `IP:port` bind and an `<agent>/<forward>` target, and
+    /// that each allow-list is only set on its own direction
+    pub fn validate(&self) -> Result<(), String> {
+        let name_valid = !self.name.is_empty()
+            && self
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+        if !name_valid {
+            return Err(format!(
+                "forward name {:?} may only contain letters, digits, '-', '_' and '.'",
+                self.name
+            ));
+        }
+        match self.direction {
+            ForwardDirection::Expose => {
+                if let Some(bind) = &self.bind {
+                    if self.public_port().is_none() {
+                        return Err(format!(
+                            "forward {}: bind {} is not a [host:]port",
+                            self.name, bind
+                        ));
+                    }
+                }
+                let port = self.target.rsplit_once(':').map(|(_, port)| port);
+                if !port.is_some_and(|port| port.parse::<u16>().is_ok_and(|port| port > 0)) {
+                    return Err(format!(
+                        "forward {}: target {} is not a host:port address",
+                        self.name, self.target
+                    ));
+                }
+                if self.allowed_agents.iter().any(|agent| agent.trim().is_empty()) {
+                    return Err(format!("forward {}: empty allowed agent", self.name));
+                }
+                if !self.allowed_sources.is_empty() {
+                    return Err(format!(
+                        "forward {}: allowed_sources only applies to connect forwards",
+                        self.name
+                    ));
+                }
+            }
+            ForwardDirection::Connect => {
+                let bind = self.bind.as_deref().unwrap_or_default();
+                if bind.parse::<std::net::SocketAddr>().is_err() {
+                    return Err(format!(
+                        "forward {}: bind {:?} is not an IP:port address",
+                        self.name, bind
+                    ));
+                }
+                if parse_forward_target(&self.target).is_none() {
+                    return Err(format!(
+                        "forward {}: target {} is not <agent>/<forward>",
+                        self.name, self.target
+                    ));
+                }
+                if !self.allowed_agents.is_empty() {
+                    return Err(format!(
+                        "forward {}: allowed_agents only applies to expose forwards",
+                        self.name
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Port of the Hub an `expose` forward asks for, 0 lets the Hub pick
+    pub fn public_port(&self) -> Option<u16> {
+        let bind = self.bind.as_deref()?;
+        let port = bind.rsplit_once(':').map_or(bind, |(_, port)| port);
+        port.parse().ok()
+    }
+}
+
 /// Agent configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentConfig {
@@ -103,6 +610,37 @@ pub struct AgentConfig {
     /// P2P listen port (0 = disabled)
     #[serde(default)]
     pub p2p_port: u16,
+    /// STUN server (host:port) for P2P NAT discovery, needs RFC 5780 support
+    #[serde(default)]
+    pub p2p_stun_server: Option<String>,
+    /// TURN server for relayed P2P candidates
+    #[serde(default)]
+    pub p2p_turn: Option<TurnConfig>,
+    /// Local `host:port` that P2P connections from other agents reach
+    /// (None = incoming P2P connections are refused)
+    #[serde(default)]
+    pub p2p_expose: Option<String>,
+    /// Agents allowed to connect to `p2p_expose`
+    #[serde(default)]
+    pub p2p_allowed_peers: Vec<Uuid>,
+    /// Local listeners carried to other agents over P2P
+    #[serde(default)]
+    pub p2p_connect: Vec<P2pConnectConfig>,
+    /// DDNS updater (None = disabled)
+    #[serde(default)]
+    pub ddns: Option<DdnsConfig>,
+    /// Remote task policy
+    #[serde(default)]
+    pub tasks: TaskPolicy,
+    /// ACME HTTP-01 challenges (None = not a validator)
+    #[serde(default)]
+    pub http01: Option<Http01Config>,
+    /// TLS certificate probes
+    #[serde(default)]
+    pub cert_probe: CertProbeConfig,
+    /// Named port forwards
+    #[serde(default)]
+    pub forwards: Vec<ForwardConfig>,
     /// Configuration directory for identity and other files
     #[serde(skip)]
     pub config_dir: PathBuf,
@@ -121,6 +659,16 @@ struct FileConfig {
     tunnel: Option<FileTunnelConfig>,
     #[serde(default)]
     p2p: Option<FileP2PConfig>,
+    #[serde(default)]
+    ddns: Option<DdnsConfig>,
+    #[serde(default)]
+    tasks: Option<TaskPolicy>,
+    #[serde(default)]
+    http01: Option<Http01Config>,
+    #[serde(default)]
+    cert_probe: Option<CertProbeConfig>,
+    #[serde(default)]
+    forward: Vec<ForwardConfig>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -143,6 +691,56 @@ struct FileTunnelConfig {
 struct FileP2PConfig {
     #[serde(default)]
     port: Option<u16>,
+    #[serde(default)]
+    stun_server: Option<String>,
+    #[serde(default)]
+    turn_server: Option<String>,
+    #[serde(default)]
+    turn_username: Option<String>,
+    #[serde(default)]
+    turn_password: Option<String>,
+    #[serde(default)]
+    expose: Option<String>,
+    #[serde(default)]
+    allowed_peers: Vec<Uuid>,
+    #[serde(default)]
+    connect: Vec<P2pConnectConfig>,
+}
+
+impl FileP2PConfig {
+    /// Check that the servers and the expose target are `host:port`, that TURN
+    /// credentials and allowed peers come with what they need and that every
+    /// connect binds an `IP:port`
+    fn validate(&self) -> Result<(), String> {
+        for (name, addr) in [
+            ("stun_server", &self.stun_server),
+            ("turn_server", &self.turn_server),
+            ("expose", &self.expose),
+        ] {
+            if let Some(addr) = addr {
+                if !is_host_port(addr) {
+                    return Err(format!("p2p {} {} is not a host:port address", name, addr));
+                }
+            }
+        }
+        if self.turn_server.is_none()
+            && (self.turn_username.is_some() || self.turn_password.is_some())
+        {
+            return Err("p2p turn_username and turn_password need a turn_server".to_string());
+        }
+        if !self.allowed_peers.is_empty() && self.expose.is_none() {
+            return Err("p2p allowed_peers needs an expose target".to_string());
+        }
+        for connect in &self.connect {
+            if connect.bind.parse::<std::net::SocketAddr>().is_err() {
+                return Err(format!(
+                    "p2p connect bind {:?} is not an IP:port address",
+                    connect.bind
+                ));
+            }
+        }
+        Ok(())
+    }
 }
 
 impl AgentConfig {
@@ -158,6 +756,16 @@ impl AgentConfig {
             reconnection: ReconnectionConfig::default(),
             tunnel_port: 0,
             p2p_port: 0,
+            p2p_stun_server: None,
+            p2p_turn: None,
+            p2p_expose: None,
+            p2p_allowed_peers: Vec::new(),
+            p2p_connect: Vec::new(),
+            ddns: None,
+            tasks: TaskPolicy::default(),
+            http01: None,
+            cert_probe: CertProbeConfig::default(),
+            forwards: Vec::new(),
             config_dir: PathBuf::from("."),
         }
     }
@@ -196,9 +804,47 @@ impl AgentConfig {
         }
 
         if let Some(p2p) = file_config.p2p {
+            p2p.validate()?;
             config.p2p_port = p2p.port.unwrap_or(0);
+            config.p2p_stun_server = p2p.stun_server;
+            config.p2p_turn = p2p.turn_server.map(|server| TurnConfig {
+                server,
+                username: p2p.turn_username.unwrap_or_default(),
+                password: p2p.turn_password.unwrap_or_default(),
+            });
+            config.p2p_expose = p2p.expose;
+            config.p2p_allowed_peers = p2p.allowed_peers;
+            config.p2p_connect = p2p.connect;
+        }
+
+        if let Some(ddns) = file_config.ddns {
+            ddns.validate()?;
+            config.ddns = Some(ddns);
+        }
+
+        if let Some(tasks) = file_config.tasks {
+            tasks.validate()?;
+            config.tasks = tasks;
+        }
+
+        if let Some(http01) = file_config.http01 {
+            http01.validate()?;
+            config.http01 = Some(http01);
         }
 
+        if let Some(cert_probe) = file_config.cert_probe {
+            cert_probe.validate()?;
+            config.cert_probe = cert_probe;
+        }
+
+        for (i, forward) in file_config.forward.iter().enumerate() {
+            forward.validate()?;
+            if file_config.forward[..i].iter().any(|other| other.name == forward.name) {
+                return Err(format!("forward {} is configured twice", forward.name));
+            }
+        }
+        config.forwards = file_config.forward;
+
         Ok(config)
     }
 
@@ -309,3 +955,280 @@ struct Args {
     #[arg(short = 'p', long)]
     p2p_port: Option<u16>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_config(content: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!(
+            "domain-agent-config-{}-{}.toml",
+            std::process::id(),
+            rand::random::<u32>()
+        ));
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_ddns_config() {
+        let path = write_config(
+            r#"
+[agent]
+hub = "hub.example.com:8080"
+
+[ddns]
+source = "stun"
+stun_server = "stun.example.com:3478"
+mode = "direct"
+cloudflare = { api_token = "token", zone_id = "zone" }
+
+[[ddns.records]]
+name = "home.example.com"
+
+[[ddns.records]]
+name = "home.example.com"
+type = "AAAA"
+"#,
+        );
+        let config = AgentConfig::from_file(path.to_str().unwrap()).unwrap();
+        fs::remove_file(&path).ok();
+
+        let ddns = config.ddns.unwrap();
+        assert_eq!(ddns.interval_secs, 300);
+        assert_eq!(ddns.source, IpSource::Stun);
+        assert_eq!(ddns.mode, DdnsMode::Direct);
+        assert_eq!(ddns.records[0].record_type, "A");
+        assert_eq!(ddns.records[1].record_type, "AAAA");
+    }
+
+    #[test]
+    fn test_invalid_ddns_config() {
+        for ddns in [
+            "[ddns]\nsource = \"stun\"\n",
+            "[ddns]\nmode = \"direct\"\n",
+            "[ddns]\n[[ddns.records]]\nname = \"home.example.com\"\ntype = \"CNAME\"\n",
+        ] {
+            let path = write_config(ddns);
+            let result = AgentConfig::from_file(path.to_str().unwrap());
+            fs::remove_file(&path).ok();
+            assert!(result.is_err(), "{}", ddns);
+        }
+    }
+
+    #[test]
+    fn test_task_policy() {
+        let path = write_config(
+            r#"
+[tasks]
+enabled = true
+allowed_commands = ["sh -c"]
+allowed_paths = ["/var/www"]
+allowed_env = ["GREETING"]
+max_timeout_secs = 60
+
+[[tasks.scripts]]
+id = "9b2f4c1e-8f3a-4d6b-9a4e-2c1d5e7f8a90"
+path = "/opt/scripts/backup.sh"
+"#,
+        );
+        let config = AgentConfig::from_file(path.to_str().unwrap()).unwrap();
+        fs::remove_file(&path).ok();
+
+        let tasks = config.tasks;
+        assert!(tasks.enabled);
+        let argv = |command: &str| -> Vec<String> {
+            let mut argv: Vec<String> = command.split_whitespace().map(String::from).collect();
+            argv[0] = resolve_program(&argv[0])
+                .unwrap()
+                .to_string_lossy()
+                .into_owned();
+            argv
+        };
+        assert!(tasks.command_allowed(&argv("sh -c true")));
+        assert!(!tasks.command_allowed(&argv("sh -e true")));
+        assert!(!tasks.command_allowed(&argv("sh")));
+        assert!(tasks.env_allowed("GREETING"));
+        assert!(!tasks.env_allowed("HOME"));
+        assert!(!tasks.env_allowed("PATH"));
+        assert!(tasks.path_allowed(Path::new("/var/www/index.html")));
+        assert!(!tasks.path_allowed(Path::new("/var/www2/index.html")));
+        assert!(tasks
+            .script(&"9b2f4c1e-8f3a-4d6b-9a4e-2c1d5e7f8a90".parse().unwrap())
+            .is_some());
+        assert_eq!(tasks.timeout(0), Duration::from_secs(60));
+        assert_eq!(tasks.timeout(10), Duration::from_secs(10));
+
+        // Tasks are disabled unless configured
+        assert!(
+            !AgentConfig::new(String::new(), String::new(), String::new())
+                .tasks
+                .enabled
+        );
+    }
+
+    #[test]
+    fn test_invalid_task_policy() {
+        for tasks in [
+            "[tasks]\nallowed_paths = [\"www\"]\n",
+            "[tasks]\nallowed_env = [\"PATH\"]\n",
+            "[tasks]\nallowed_env = [\"LD_PRELOAD\"]\n",
+            "[tasks]\nallowed_env = [\"DYLD_INSERT_LIBRARIES\"]\n",
+        ] {
+            let path = write_config(tasks);
+            let result = AgentConfig::from_file(path.to_str().unwrap());
+            fs::remove_file(&path).ok();
+            assert!(result.is_err(), "{}", tasks);
+        }
+    }
+
+    #[test]
+    fn test_http01_config() {
+        let path = write_config("[http01]\nweb_root = \"/var/www/html\"\n");
+        let config = AgentConfig::from_file(path.to_str().unwrap()).unwrap();
+        fs::remove_file(&path).ok();
+        let http01 = config.http01.unwrap();
+        assert_eq!(http01.web_root.as_deref(), Some("/var/www/html"));
+        assert_eq!(http01.listen_timeout_secs, 300);
+
+        for http01 in [
+            "[http01]\n",
+            "[http01]\nweb_root = \"www\"\n",
+            "[http01]\nlisten = \"localhost\"\n",
+            "[http01]\nweb_root = \"/var/www\"\nlisten = \"0.0.0.0:80\"\n",
+        ] {
+            let path = write_config(http01);
+            let result = AgentConfig::from_file(path.to_str().unwrap());
+            fs::remove_file(&path).ok();
+            assert!(result.is_err(), "{}", http01);
+        }
+    }
+
+    #[test]
+    fn test_cert_probe_config() {
+        let path = write_config(
+            "[cert_probe]\ntargets = [\"intranet.local\", \"10.0.0.5:8443\"]\n",
+        );
+        let config = AgentConfig::from_file(path.to_str().unwrap()).unwrap();
+        fs::remove_file(&path).ok();
+        assert_eq!(config.cert_probe.targets.len(), 2);
+        assert_eq!(config.cert_probe.interval(), Some(Duration::from_secs(86400)));
+
+        // Without targets the agent only probes when the Hub asks
+        let config = AgentConfig::new(String::new(), String::new(), String::new());
+        assert_eq!(config.cert_probe.interval(), None);
+
+        for cert_probe in [
+            "[cert_probe]\ntargets = [\"host:https\"]\n",
+            "[cert_probe]\ntimeout_secs = 0\n",
+        ] {
+            let path = write_config(cert_probe);
+            let result = AgentConfig::from_file(path.to_str().unwrap());
+            fs::remove_file(&path).ok();
+            assert!(result.is_err(), "{}", cert_probe);
+        }
+    }
+
+    #[test]
+    fn test_forward_config() {
+        let path = write_config(
+            r#"
+[[forward]]
+name = "web"
+direction = "expose"
+bind = "0.0.0.0:20080"
+target = "127.0.0.1:8080"
+
+[[forward]]
+name = "db"
+direction = "expose"
+target = "127.0.0.1:5432"
+allowed_agents = ["lan-2"]
+
+[[forward]]
+name = "office-db"
+direction = "connect"
+bind = "127.0.0.1:15432"
+target = "office/db"
+allowed_sources = ["127.0.0.1"]
+enabled = false
+"#,
+        );
+        let config = AgentConfig::from_file(path.to_str().unwrap()).unwrap();
+        fs::remove_file(&path).ok();
+
+        let forwards = config.forwards;
+        assert_eq!(forwards.len(), 3);
+        assert_eq!(forwards[0].public_port(), Some(20080));
+        assert!(forwards[0].enabled);
+        assert_eq!(forwards[1].public_port(), None);
+        assert_eq!(forwards[1].allowed_agents, vec!["lan-2"]);
+        assert_eq!(forwards[2].direction, ForwardDirection::Connect);
+        assert_eq!(
+            forwards[2].allowed_sources,
+            vec![std::net::IpAddr::from([127, 0, 0, 1])]
+        );
+        assert!(!forwards[2].enabled);
+
+        for forward in [
+            "[[forward]]\nname = \"web\"\ndirection = \"expose\"\ntarget = \"localhost\"\n",
+            "[[forward]]\nname = \"web\"\ndirection = \"expose\"\nbind = \"http\"\ntarget = \"localhost:80\"\n",
+            "[[forward]]\nname = \"a b\"\ndirection = \"expose\"\ntarget = \"localhost:80\"\n",
+            "[[forward]]\nname = \"db\"\ndirection = \"connect\"\ntarget = \"office/db\"\n",
+            "[[forward]]\nname = \"db\"\ndirection = \"connect\"\nbind = \"127.0.0.1:15432\"\ntarget = \"127.0.0.1:5432\"\n",
+            "[[forward]]\nname = \"db\"\ndirection = \"connect\"\nbind = \"127.0.0.1:15432\"\ntarget = \"office/db\"\nallowed_sources = [\"lan-2\"]\n",
+            "[[forward]]\nname = \"db\"\ndirection = \"connect\"\nbind = \"127.0.0.1:15432\"\ntarget = \"office/db\"\nallowed_agents = [\"lan-2\"]\n",
+            "[[forward]]\nname = \"db\"\ndirection = \"expose\"\ntarget = \"127.0.0.1:5432\"\nallowed_sources = [\"127.0.0.1\"]\n",
+            "[[forward]]\nname = \"db\"\ndirection = \"expose\"\ntarget = \"127.0.0.1:5432\"\nallowed_peers = [\"lan-2\"]\n",
+            "[[forward]]\nname = \"web\"\ndirection = \"expose\"\ntarget = \"localhost:80\"\n[[forward]]\nname = \"web\"\ndirection = \"expose\"\ntarget = \"localhost:81\"\n",
+        ] {
+            let path = write_config(forward);
+            let result = AgentConfig::from_file(path.to_str().unwrap());
+            fs::remove_file(&path).ok();
+            assert!(result.is_err(), "{}", forward);
+        }
+    }
+
+    #[test]
+    fn test_p2p_config() {
+        let path = write_config(
+            r#"
+[p2p]
+stun_server = "stun.example.com:3478"
+turn_server = "turn.example.com:3478"
+turn_username = "agent"
+turn_password = "secret"
+expose = "127.0.0.1:22"
+allowed_peers = ["6f1c2a4e-3b5d-4e7f-8a9b-0c1d2e3f4a5b"]
+
+[[p2p.connect]]
+bind = "127.0.0.1:2222"
+peer = "6f1c2a4e-3b5d-4e7f-8a9b-0c1d2e3f4a5b"
+"#,
+        );
+        let config = AgentConfig::from_file(path.to_str().unwrap()).unwrap();
+        fs::remove_file(&path).ok();
+
+        let peer: Uuid = "6f1c2a4e-3b5d-4e7f-8a9b-0c1d2e3f4a5b".parse().unwrap();
+        let turn = config.p2p_turn.unwrap();
+        assert_eq!(turn.server, "turn.example.com:3478");
+        assert_eq!(turn.username, "agent");
+        assert_eq!(config.p2p_expose.as_deref(), Some("127.0.0.1:22"));
+        assert_eq!(config.p2p_allowed_peers, vec![peer]);
+        assert_eq!(config.p2p_connect[0].peer, peer);
+
+        for p2p in [
+            "[p2p]\nturn_server = \"turn.example.com\"\n",
+            "[p2p]\nturn_username = \"agent\"\n",
+            "[p2p]\nexpose = \"ssh\"\n",
+            "[p2p]\nallowed_peers = [\"6f1c2a4e-3b5d-4e7f-8a9b-0c1d2e3f4a5b\"]\n",
+            "[p2p]\nallowed_peers = [\"office\"]\nexpose = \"127.0.0.1:22\"\n",
+            "[[p2p.connect]]\nbind = \"2222\"\npeer = \"6f1c2a4e-3b5d-4e7f-8a9b-0c1d2e3f4a5b\"\n",
+        ] {
+            let path = write_config(p2p);
+            let result = AgentConfig::from_file(path.to_str().unwrap());
+            fs::remove_file(&path).ok();
+            assert!(result.is_err(), "{}", p2p);
+        }
+    }
+}
@@ -0,0 +1,335 @@
+//! DDNS updater
+//!
+//! Watches the public IPv4/IPv6 address of this host, read from the network
+//! interfaces or from a STUN binding against `domain-stun`. When it changes the
+//! agent either reports to the Hub (which updates the records through the
+//! account's DNS provider) or updates the records itself with a credential
+//! scoped to the zone, and reports the result so the Hub can audit it.
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::Duration;
+
+use serde::Deserialize;
+use sysinfo::Networks;
+use tracing::{debug, info, warn};
+
+use crate::config::{CloudflareCredential, DdnsConfig, DdnsMode, DdnsRecord, IpSource};
+use crate::stun;
+
+const CLOUDFLARE_API: &str = "https://api.cloudflare.com/client/v4";
+
+/// Public IP change of one configured record
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IpChange {
+    pub name: String,
+    pub record_type: String,
+    /// Last IP seen by this agent, None after start-up
+    pub old_ip: Option<IpAddr>,
+    pub new_ip: IpAddr,
+}
+
+/// Detects public IP changes for the configured records
+pub struct DdnsUpdater {
+    config: DdnsConfig,
+    /// (name, record type) -> last reported IP
+    last_ips: HashMap<(String, String), IpAddr>,
+    http: reqwest::Client,
+}
+
+impl DdnsUpdater {
+    pub fn new(config: DdnsConfig) -> Self {
+        Self {
+            config,
+            last_ips: HashMap::new(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.config.interval_secs)
+    }
+
+    pub fn mode(&self) -> DdnsMode {
+        self.config.mode
+    }
+
+    /// Forget the last IP of a record so the next check reports it again
+    pub fn forget(&mut self, name: &str, record_type: &str) {
+        self.last_ips
+            .retain(|(n, t), _| !(n.eq_ignore_ascii_case(name) && t == record_type));
+    }
+
+    /// Detect the public IPs and return the records whose IP changed
+    pub async fn check(&mut self) -> Vec<IpChange> {
+        let mut detected: HashMap<bool, Option<IpAddr>> = HashMap::new();
+        let mut changes = Vec::new();
+
+        for DdnsRecord { name, record_type } in &self.config.records {
+            let ipv6 = record_type == "AAAA";
+            let ip = match detected.get(&ipv6) {
+                Some(ip) => *ip,
+                None => {
+                    let ip = match self.detect(ipv6).await {
+                        Ok(ip) => Some(ip),
+                        Err(e) => {
+                            warn!("Failed to detect public {}: {}", family(ipv6), e);
+                            None
+                        }
+                    };
+                    detected.insert(ipv6, ip);
+                    ip
+                }
+            };
+            let Some(new_ip) = ip else {
+                continue;
+            };
+
+            changes.extend(Self::observe(&mut self.last_ips, name, record_type, new_ip));
+        }
+        changes
+    }
+
+    /// Remember the IP of a record, returns the change if it differs from the last one
+    fn observe(
+        last_ips: &mut HashMap<(String, String), IpAddr>,
+        name: &str,
+        record_type: &str,
+        new_ip: IpAddr,
+    ) -> Option<IpChange> {
+        let key = (name.to_string(), record_type.to_string());
+        let old_ip = last_ips.insert(key, new_ip);
+        if old_ip == Some(new_ip) {
+            debug!("DDNS {} {} unchanged: {}", name, record_type, new_ip);
+            return None;
+        }
+        info!(
+            "DDNS {} {} changed: {:?} -> {}",
+            name, record_type, old_ip, new_ip
+        );
+        Some(IpChange {
+            name: name.to_string(),
+            record_type: record_type.to_string(),
+            old_ip,
+            new_ip,
+        })
+    }
+
+    /// Detect the public IPv4 or IPv6 address
+    async fn detect(&self, ipv6: bool) -> Result<IpAddr, String> {
+        match self.config.source {
+            IpSource::Interface => {
+                let networks = Networks::new_with_refreshed_list();
+                let addrs = networks
+                    .iter()
+                    .filter(|(name, _)| {
+                        self.config
+                            .interface
+                            .as_ref()
+                            .is_none_or(|interface| interface == *name)
+                    })
+                    .flat_map(|(_, data)| data.ip_networks().iter().map(|network| network.addr));
+                select_public_ip(addrs, ipv6)
+                    .ok_or_else(|| format!("No global {} on the interfaces", family(ipv6)))
+            }
+            IpSource::Stun => {
+                let server = self
+                    .config
+                    .stun_server
+                    .as_deref()
+                    .ok_or_else(|| "STUN server not configured".to_string())?;
+                stun::query_mapped_address(server, ipv6)
+                    .await
+                    .map(|addr| addr.ip())
+            }
+        }
+    }
+
+    /// Update the record with the scoped credential, returns the previous value
+    pub async fn update_direct(&self, change: &IpChange) -> Result<Option<String>, String> {
+        let credential = self
+            .config
+            .cloudflare
+            .as_ref()
+            .ok_or_else(|| "No credential for direct DDNS updates".to_string())?;
+        update_cloudflare(&self.http, CLOUDFLARE_API, credential, change).await
+    }
+}
+
+fn family(ipv6: bool) -> &'static str {
+    if ipv6 {
+        "IPv6"
+    } else {
+        "IPv4"
+    }
+}
+
+/// Pick the first globally routable address of the requested family
+fn select_public_ip(addrs: impl IntoIterator<Item = IpAddr>, ipv6: bool) -> Option<IpAddr> {
+    addrs
+        .into_iter()
+        .find(|addr| addr.is_ipv6() == ipv6 && is_public(addr))
+}
+
+/// Whether the address can be published in DNS
+fn is_public(addr: &IpAddr) -> bool {
+    match addr {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            // 100.64.0.0/10 is carrier-grade NAT
+            let shared = a == 100 && (64..128).contains(&b);
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || shared)
+        }
+        IpAddr::V6(ip) => {
+            let first = ip.segments()[0];
+            // fc00::/7 unique local, fe80::/10 link local
+            let unique_local = first & 0xfe00 == 0xfc00;
+            let link_local = first & 0xffc0 == 0xfe80;
+            !(ip.is_loopback() || ip.is_unspecified() || unique_local || link_local)
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CloudflareResponse<T> {
+    success: bool,
+    #[serde(default)]
+    errors: Vec<CloudflareError>,
+    result: Option<T>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CloudflareError {
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CloudflareRecord {
+    id: String,
+    content: String,
+}
+
+impl<T> CloudflareResponse<T> {
+    fn into_result(self) -> Result<Option<T>, String> {
+        if self.success {
+            Ok(self.result)
+        } else {
+            let messages: Vec<String> = self.errors.into_iter().map(|e| e.message).collect();
+            Err(format!("Cloudflare API error: {}", messages.join("; ")))
+        }
+    }
+}
+
+/// Create or update the record through the Cloudflare API
+async fn update_cloudflare(
+    http: &reqwest::Client,
+    api: &str,
+    credential: &CloudflareCredential,
+    change: &IpChange,
+) -> Result<Option<String>, String> {
+    let records_url = format!("{}/zones/{}/dns_records", api, credential.zone_id);
+    let existing = http
+        .get(&records_url)
+        .bearer_auth(&credential.api_token)
+        .query(&[
+            ("type", change.record_type.as_str()),
+            ("name", change.name.as_str()),
+        ])
+        .send()
+        .await
+        .map_err(|e| format!("Failed to query Cloudflare: {}", e))?
+        .json::<CloudflareResponse<Vec<CloudflareRecord>>>()
+        .await
+        .map_err(|e| format!("Invalid Cloudflare response: {}", e))?
+        .into_result()?
+        .unwrap_or_default()
+        .into_iter()
+        .next();
+
+    let content = change.new_ip.to_string();
+    let request = match &existing {
+        Some(record) if record.content == content => return Ok(Some(record.content.clone())),
+        Some(record) => http
+            .patch(format!("{}/{}", records_url, record.id))
+            .json(&serde_json::json!({ "content": content })),
+        None => http.post(&records_url).json(&serde_json::json!({
+            "type": change.record_type,
+            "name": change.name,
+            "content": content,
+            "ttl": 1,
+            "proxied": false,
+        })),
+    };
+    request
+        .bearer_auth(&credential.api_token)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to update Cloudflare: {}", e))?
+        .json::<CloudflareResponse<CloudflareRecord>>()
+        .await
+        .map_err(|e| format!("Invalid Cloudflare response: {}", e))?
+        .into_result()?;
+    Ok(existing.map(|record| record.content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_public_ip() {
+        let addrs: Vec<IpAddr> = [
+            "127.0.0.1",
+            "192.168.1.10",
+            "100.64.0.1",
+            "fe80::1",
+            "fd00::1",
+            "203.0.113.7",
+            "2001:db8::7",
+        ]
+        .iter()
+        .map(|addr| addr.parse().unwrap())
+        .collect();
+
+        assert_eq!(
+            select_public_ip(addrs.clone(), false),
+            Some("203.0.113.7".parse().unwrap())
+        );
+        assert_eq!(
+            select_public_ip(addrs.clone(), true),
+            Some("2001:db8::7".parse().unwrap())
+        );
+        assert_eq!(select_public_ip(addrs[..5].to_vec(), false), None);
+    }
+
+    #[test]
+    fn test_observe_and_forget() {
+        let mut updater = DdnsUpdater::new(DdnsConfig {
+            interval_secs: 60,
+            source: IpSource::Interface,
+            interface: None,
+            stun_server: None,
+            mode: DdnsMode::Hub,
+            cloudflare: None,
+            records: vec![],
+        });
+        let first: IpAddr = "203.0.113.7".parse().unwrap();
+        let second: IpAddr = "203.0.113.8".parse().unwrap();
+        let observe = |updater: &mut DdnsUpdater, ip| {
+            DdnsUpdater::observe(&mut updater.last_ips, "home.example.com", "A", ip)
+        };
+
+        assert_eq!(observe(&mut updater, first).unwrap().old_ip, None);
+        assert!(observe(&mut updater, first).is_none());
+        assert_eq!(observe(&mut updater, second).unwrap().old_ip, Some(first));
+
+        // The Hub failed to update the record, report the same IP again
+        updater.forget("HOME.example.com", "A");
+        assert_eq!(observe(&mut updater, second).unwrap().old_ip, None);
+    }
+}
@@ -0,0 +1,354 @@
+//! ACME HTTP-01 challenges
+//!
+//! The Hub sends the token and key authorization of a challenge for this host.
+//! They are either written to `<web_root>/.well-known/acme-challenge/<token>`
+//! for the web server already running here, or served by a short-lived
+//! built-in listener that starts with the first challenge and stops once every
+//! challenge is cleaned up or `listen_timeout_secs` expires.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{oneshot, Mutex};
+use tracing::{debug, info, warn};
+
+use crate::config::Http01Config;
+use crate::net::accept_with_backoff;
+
+/// URL path prefix of HTTP-01 challenges
+pub const CHALLENGE_PATH: &str = "/.well-known/acme-challenge/";
+/// Largest request head read by the built-in listener
+const MAX_REQUEST_SIZE: usize = 8192;
+/// Time a client has to send its request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Tokens are base64url, which also keeps them from escaping the challenge directory
+pub fn validate_token(token: &str) -> Result<(), String> {
+    let valid = !token.is_empty()
+        && token
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    match valid {
+        true => Ok(()),
+        false => Err(format!("Invalid challenge token {:?}", token)),
+    }
+}
+
+/// Path of the challenge file below a web root
+pub fn challenge_file(web_root: &Path, token: &str) -> Result<PathBuf, String> {
+    validate_token(token)?;
+    Ok(web_root.join(CHALLENGE_PATH.trim_matches('/')).join(token))
+}
+
+/// Write a challenge file below a web root
+pub async fn write_challenge(
+    web_root: &Path,
+    token: &str,
+    key_authorization: &str,
+) -> Result<PathBuf, String> {
+    let path = challenge_file(web_root, token)?;
+    if let Some(dir) = path.parent() {
+        tokio::fs::create_dir_all(dir)
+            .await
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    tokio::fs::write(&path, key_authorization)
+        .await
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+/// Remove a challenge file, a missing file is not an error
+pub async fn remove_challenge(web_root: &Path, token: &str) -> Result<(), String> {
+    let path = challenge_file(web_root, token)?;
+    match tokio::fs::remove_file(&path).await {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("Failed to delete {}: {}", path.display(), e)),
+    }
+}
+
+/// Answers the HTTP-01 challenges requested by the Hub
+#[derive(Clone)]
+pub struct Http01Responder {
+    config: Http01Config,
+    /// token -> key authorization, served by the built-in listener
+    tokens: Arc<Mutex<HashMap<String, String>>>,
+    /// Stops the built-in listener
+    stop_tx: Arc<Mutex<Option<oneshot::Sender<()>>>>,
+}
+
+impl Http01Responder {
+    pub fn new(config: Http01Config) -> Self {
+        Self {
+            config,
+            tokens: Arc::new(Mutex::new(HashMap::new())),
+            stop_tx: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Make the challenge available over HTTP
+    pub async fn present(&self, token: &str, key_authorization: &str) -> Result<(), String> {
+        validate_token(token)?;
+        if let Some(web_root) = &self.config.web_root {
+            let path = write_challenge(Path::new(web_root), token, key_authorization).await?;
+            info!("HTTP-01 challenge written to {}", path.display());
+            return Ok(());
+        }
+
+        self.tokens
+            .lock()
+            .await
+            .insert(token.to_string(), key_authorization.to_string());
+        if let Err(e) = self.start_listener().await {
+            self.tokens.lock().await.remove(token);
+            return Err(e);
+        }
+        info!(
+            "HTTP-01 challenge {} served by the built-in listener",
+            token
+        );
+        Ok(())
+    }
+
+    /// Remove the challenge, the built-in listener stops with the last one
+    pub async fn cleanup(&self, token: &str) -> Result<(), String> {
+        validate_token(token)?;
+        if let Some(web_root) = &self.config.web_root {
+            return remove_challenge(Path::new(web_root), token).await;
+        }
+
+        let mut tokens = self.tokens.lock().await;
+        tokens.remove(token);
+        if tokens.is_empty() {
+            if let Some(stop_tx) = self.stop_tx.lock().await.take() {
+                let _ = stop_tx.send(());
+            }
+        }
+        Ok(())
+    }
+
+    /// Start the built-in listener unless it is already running
+    async fn start_listener(&self) -> Result<(), String> {
+        let mut stop_tx = self.stop_tx.lock().await;
+        if stop_tx.as_ref().is_some_and(|tx| !tx.is_closed()) {
+            return Ok(());
+        }
+        let addr = self.config.listen.as_deref().unwrap_or("0.0.0.0:80");
+        let listener = TcpListener::bind(addr)
+            .await
+            .map_err(|e| format!("Failed to listen on {}: {}", addr, e))?;
+        info!("HTTP-01 listener started on {}", addr);
+
+        let (tx, rx) = oneshot::channel();
+        *stop_tx = Some(tx);
+        let timeout = Duration::from_secs(self.config.listen_timeout_secs);
+        tokio::spawn(run_listener(listener, self.tokens.clone(), rx, timeout));
+        Ok(())
+    }
+}
+
+/// Accept connections until stopped or the timeout expires
+async fn run_listener(
+    listener: TcpListener,
+    tokens: Arc<Mutex<HashMap<String, String>>>,
+    mut stop_rx: oneshot::Receiver<()>,
+    timeout: Duration,
+) {
+    let deadline = tokio::time::sleep(timeout);
+    tokio::pin!(deadline);
+    loop {
+        tokio::select! {
+            (stream, peer) = accept_with_backoff(&listener, "HTTP-01 listener") => {
+                debug!("HTTP-01 request from {}", peer);
+                tokio::spawn(serve(stream, tokens.clone()));
+            }
+            _ = &mut stop_rx => {
+                info!("HTTP-01 listener stopped");
+                break;
+            }
+            _ = &mut deadline => {
+                warn!("HTTP-01 listener timed out after {}s", timeout.as_secs());
+                tokens.lock().await.clear();
+                break;
+            }
+        }
+    }
+}
+
+/// Answer one request with the key authorization of the requested token
+async fn serve(mut stream: TcpStream, tokens: Arc<Mutex<HashMap<String, String>>>) {
+    let mut request = Vec::new();
+    let mut buf = [0u8; 1024];
+    let read = tokio::time::timeout(REQUEST_TIMEOUT, async {
+        while !request.windows(4).any(|w| w == b"\r\n\r\n") && request.len() < MAX_REQUEST_SIZE {
+            match stream.read(&mut buf).await {
+                Ok(0) | Err(_) => break,
+                Ok(n) => request.extend_from_slice(&buf[..n]),
+            }
+        }
+    })
+    .await;
+    if read.is_err() {
+        return;
+    }
+
+    let request = String::from_utf8_lossy(&request);
+    let mut parts = request
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .split_whitespace();
+    let (method, target) = (parts.next(), parts.next().unwrap_or_default());
+    let path = target.split('?').next().unwrap_or_default();
+    let key_authorization = match (method, path.strip_prefix(CHALLENGE_PATH)) {
+        (Some("GET") | Some("HEAD"), Some(token)) => tokens.lock().await.get(token).cloned(),
+        _ => None,
+    };
+
+    let response = match key_authorization {
+        Some(body) => response("200 OK", &body, method == Some("HEAD")),
+        None => response("404 Not Found", "", false),
+    };
+    if let Err(e) = stream.write_all(response.as_bytes()).await {
+        debug!("Failed to answer HTTP-01 request: {}", e);
+    }
+    let _ = stream.shutdown().await;
+}
+
+fn response(status: &str, body: &str, head: bool) -> String {
+    format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        if head { "" } else { body }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn get(addr: &str, path: &str) -> String {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        let request = format!("GET {} HTTP/1.1\r\nHost: www.example.com\r\n\r\n", path);
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    fn free_addr() -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_validate_token() {
+        assert!(validate_token("evaGxfADs6pSRb2LAv9IZf17Dt3juxGJ-PCt92wr-oA").is_ok());
+        assert!(validate_token("").is_err());
+        assert!(validate_token("../etc/passwd").is_err());
+        assert!(validate_token("a/b").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_web_root() {
+        let dir =
+            std::env::temp_dir().join(format!("domain-agent-http01-{}", rand::random::<u32>()));
+        let responder = Http01Responder::new(Http01Config {
+            web_root: Some(dir.to_string_lossy().into_owned()),
+            listen: None,
+            listen_timeout_secs: 300,
+        });
+
+        responder
+            .present("token-1", "token-1.thumbprint")
+            .await
+            .unwrap();
+        let path = dir.join(".well-known/acme-challenge/token-1");
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "token-1.thumbprint"
+        );
+
+        responder.cleanup("token-1").await.unwrap();
+        assert!(!path.exists());
+        // Cleaning up twice is fine
+        responder.cleanup("token-1").await.unwrap();
+        assert!(responder.present("../token", "value").await.is_err());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_built_in_listener() {
+        let addr = free_addr();
+        let responder = Http01Responder::new(Http01Config {
+            web_root: None,
+            listen: Some(addr.clone()),
+            listen_timeout_secs: 300,
+        });
+
+        responder
+            .present("token-1", "token-1.thumbprint")
+            .await
+            .unwrap();
+        responder
+            .present("token-2", "token-2.thumbprint")
+            .await
+            .unwrap();
+        let response = get(&addr, "/.well-known/acme-challenge/token-1").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"), "{}", response);
+        assert!(response.ends_with("\r\n\r\ntoken-1.thumbprint"));
+        let response = get(&addr, "/.well-known/acme-challenge/unknown").await;
+        assert!(response.starts_with("HTTP/1.1 404"));
+        let response = get(&addr, "/index.html").await;
+        assert!(response.starts_with("HTTP/1.1 404"));
+
+        // The listener keeps running until the last challenge is removed
+        responder.cleanup("token-1").await.unwrap();
+        let response = get(&addr, "/.well-known/acme-challenge/token-2").await;
+        assert!(response.ends_with("token-2.thumbprint"));
+        responder.cleanup("token-2").await.unwrap();
+
+        let mut stopped = false;
+        for _ in 0..50 {
+            if TcpStream::connect(&addr).await.is_err() {
+                stopped = true;
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert!(stopped, "listener still running");
+
+        // A new challenge starts it again
+        responder
+            .present("token-3", "token-3.thumbprint")
+            .await
+            .unwrap();
+        let response = get(&addr, "/.well-known/acme-challenge/token-3").await;
+        assert!(response.ends_with("token-3.thumbprint"));
+        responder.cleanup("token-3").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_listener_timeout() {
+        let addr = free_addr();
+        let responder = Http01Responder::new(Http01Config {
+            web_root: None,
+            listen: Some(addr.clone()),
+            listen_timeout_secs: 1,
+        });
+        responder
+            .present("token-1", "token-1.thumbprint")
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        assert!(TcpStream::connect(&addr).await.is_err());
+        assert!(responder.tokens.lock().await.is_empty());
+    }
+}
@@ -0,0 +1,1229 @@
+//! ICE-style connectivity establishment (RFC 8445) for agent P2P
+//!
+//! Each side gathers host, server reflexive (STUN) and relayed (TURN)
+//! candidates on one UDP socket and learns the other side's candidates
+//! through Hub signalling. Candidate pairs are checked in priority order with
+//! STUN Binding requests signed with the ICE credentials. Both sides check at
+//! the same time, so the outgoing checks open the NAT mappings that the
+//! peer's checks come in through (UDP hole punching). A check from an unknown
+//! address adds a peer reflexive candidate and triggers a check back, which
+//! gets through NATs that map every destination to a new port. The
+//! controlling side nominates the best working pair.
+
+use rand::distributions::{Alphanumeric, DistString};
+use std::collections::VecDeque;
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
+use std::time::Duration;
+use sysinfo::Networks;
+use tokio::net::UdpSocket;
+use tokio::sync::mpsc;
+use tokio::time::{interval, sleep_until, timeout_at, Instant, MissedTickBehavior};
+use tracing::{debug, warn};
+
+use crate::config::TurnConfig;
+use crate::stun::{
+    self, Message, ATTR_USERNAME, ATTR_XOR_MAPPED_ADDRESS, BINDING_ERROR, BINDING_REQUEST,
+    BINDING_SUCCESS,
+};
+use crate::turn::{self, TurnClient};
+
+const ATTR_PRIORITY: u16 = 0x0024;
+const ATTR_USE_CANDIDATE: u16 = 0x0025;
+const ATTR_ICE_CONTROLLED: u16 = 0x8029;
+const ATTR_ICE_CONTROLLING: u16 = 0x802A;
+
+/// Only one component is used
+const COMPONENT: u32 = 1;
+/// Pacing of new checks (Ta)
+const CHECK_PACING: Duration = Duration::from_millis(20);
+/// Time between retransmissions of a check
+const CHECK_RTO: Duration = Duration::from_millis(250);
+/// Checks sent on a pair before it fails
+const CHECK_ATTEMPTS: u32 = 8;
+/// Time the controlling side waits for better pairs after the first success
+const NOMINATION_DELAY: Duration = Duration::from_millis(300);
+/// Time allowed for the connectivity checks
+const CHECK_TIMEOUT: Duration = Duration::from_secs(15);
+/// Requests sent to STUN and TURN servers before giving up
+const REQUEST_ATTEMPTS: u32 = 3;
+/// Time to wait for each response of a server
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// A UDP socket, abstracted so that tests can put it behind an emulated NAT
+pub trait Transport: Send + Sync + 'static {
+    fn local_addr(&self) -> io::Result<SocketAddr>;
+
+    fn send_to(
+        &self,
+        buf: &[u8],
+        target: SocketAddr,
+    ) -> impl Future<Output = io::Result<usize>> + Send;
+
+    fn recv_from(
+        &self,
+        buf: &mut [u8],
+    ) -> impl Future<Output = io::Result<(usize, SocketAddr)>> + Send;
+}
+
+impl Transport for UdpSocket {
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        UdpSocket::local_addr(self)
+    }
+
+    fn send_to(
+        &self,
+        buf: &[u8],
+        target: SocketAddr,
+    ) -> impl Future<Output = io::Result<usize>> + Send {
+        UdpSocket::send_to(self, buf, target)
+    }
+
+    fn recv_from(
+        &self,
+        buf: &mut [u8],
+    ) -> impl Future<Output = io::Result<(usize, SocketAddr)>> + Send {
+        UdpSocket::recv_from(self, buf)
+    }
+}
+
+/// Send a STUN request to `server` until a response with its transaction ID
+/// arrives, ignoring other datagrams
+pub async fn request<T: Transport>(
+    transport: &T,
+    server: SocketAddr,
+    msg: &[u8],
+) -> Result<Message, String> {
+    let transaction_id = &msg[8..20];
+    let mut buf = vec![0u8; 2048];
+    for attempt in 1..=REQUEST_ATTEMPTS {
+        transport
+            .send_to(msg, server)
+            .await
+            .map_err(|e| format!("Failed to send to {}: {}", server, e))?;
+        let deadline = Instant::now() + REQUEST_TIMEOUT;
+        loop {
+            match timeout_at(deadline, transport.recv_from(&mut buf)).await {
+                Ok(Ok((len, from)))
+                    if from == server
+                        && stun::is_stun(&buf[..len])
+                        && &buf[8..20] == transaction_id =>
+                {
+                    return Message::decode(&buf[..len]);
+                }
+                Ok(Ok((_, from))) => debug!("Ignoring datagram from {}", from),
+                Ok(Err(e)) => return Err(format!("Failed to receive from {}: {}", server, e)),
+                Err(_) => {
+                    debug!("Request to {} timed out (attempt {})", server, attempt);
+                    break;
+                }
+            }
+        }
+    }
+    Err(format!("No response from {}", server))
+}
+
+/// Candidate types, in the order of preference
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CandidateKind {
+    /// An address of a local interface
+    Host,
+    /// Our public address as seen by the STUN (or TURN) server
+    ServerReflexive,
+    /// Our address as seen by the peer, learned from its checks
+    PeerReflexive,
+    /// The address allocated on the TURN server
+    Relayed,
+}
+
+impl CandidateKind {
+    fn type_preference(self) -> u32 {
+        match self {
+            CandidateKind::Host => 126,
+            CandidateKind::PeerReflexive => 110,
+            CandidateKind::ServerReflexive => 100,
+            CandidateKind::Relayed => 0,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            CandidateKind::Host => "host",
+            CandidateKind::ServerReflexive => "srflx",
+            CandidateKind::PeerReflexive => "prflx",
+            CandidateKind::Relayed => "relay",
+        }
+    }
+}
+
+/// RFC 8445 candidate priority
+pub fn priority(kind: CandidateKind, local_preference: u32) -> u32 {
+    (kind.type_preference() << 24) | ((local_preference & 0xFFFF) << 8) | (256 - COMPONENT)
+}
+
+/// RFC 8445 pair priority from the controlling and controlled candidate priorities
+fn pair_priority(controlling: u32, controlled: u32) -> u64 {
+    let (g, d) = (u64::from(controlling), u64::from(controlled));
+    (g.min(d) << 32) + 2 * g.max(d) + u64::from(g > d)
+}
+
+/// A transport address one side can be reached at
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    pub foundation: String,
+    pub priority: u32,
+    pub addr: SocketAddr,
+    pub kind: CandidateKind,
+    /// Base of a reflexive candidate, mapped address of a relayed one
+    pub related: Option<SocketAddr>,
+}
+
+impl Candidate {
+    pub fn new(
+        kind: CandidateKind,
+        addr: SocketAddr,
+        related: Option<SocketAddr>,
+        local_preference: u32,
+    ) -> Self {
+        // Candidates of the same type from the same base share a foundation
+        let base = related.unwrap_or(addr).ip();
+        let foundation = crc32fast::hash(format!("{} {}", kind.as_str(), base).as_bytes());
+        Self {
+            foundation: format!("{:08x}", foundation),
+            priority: priority(kind, local_preference),
+            addr,
+            kind,
+            related,
+        }
+    }
+}
+
+/// The `candidate:` attribute of SDP (RFC 8839)
+impl fmt::Display for Candidate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "candidate:{} {} udp {} {} {} typ {}",
+            self.foundation,
+            COMPONENT,
+            self.priority,
+            self.addr.ip(),
+            self.addr.port(),
+            self.kind.as_str()
+        )?;
+        if let Some(related) = self.related {
+            write!(f, " raddr {} rport {}", related.ip(), related.port())?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Candidate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid ICE candidate: {}", s);
+        let line = s.trim();
+        let line = line.strip_prefix("a=").unwrap_or(line);
+        let fields: Vec<&str> = line
+            .strip_prefix("candidate:")
+            .ok_or_else(invalid)?
+            .split_whitespace()
+            .collect();
+        if fields.len() < 8 || !fields[2].eq_ignore_ascii_case("udp") || fields[6] != "typ" {
+            return Err(invalid());
+        }
+        let ip: IpAddr = fields[4].parse().map_err(|_| invalid())?;
+        let port: u16 = fields[5].parse().map_err(|_| invalid())?;
+        let kind = match fields[7] {
+            "host" => CandidateKind::Host,
+            "srflx" => CandidateKind::ServerReflexive,
+            "prflx" => CandidateKind::PeerReflexive,
+            "relay" => CandidateKind::Relayed,
+            _ => return Err(invalid()),
+        };
+        let mut related_ip = None;
+        let mut related_port = None;
+        for pair in fields[8..].chunks(2) {
+            match pair {
+                ["raddr", ip] => related_ip = ip.parse::<IpAddr>().ok(),
+                ["rport", port] => related_port = port.parse::<u16>().ok(),
+                _ => {}
+            }
+        }
+        Ok(Self {
+            foundation: fields[0].to_string(),
+            priority: fields[3].parse().map_err(|_| invalid())?,
+            addr: SocketAddr::new(ip, port),
+            kind,
+            related: related_ip
+                .zip(related_port)
+                .map(|(ip, port)| SocketAddr::new(ip, port)),
+        })
+    }
+}
+
+/// ICE username fragment and password of one side
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Credentials {
+    pub ufrag: String,
+    pub pwd: String,
+}
+
+impl Credentials {
+    pub fn generate() -> Self {
+        let mut rng = rand::thread_rng();
+        Self {
+            ufrag: Alphanumeric.sample_string(&mut rng, 8),
+            pwd: Alphanumeric.sample_string(&mut rng, 24),
+        }
+    }
+}
+
+/// Host candidate addresses of a socket: its own address, or every
+/// interface address of its family when bound to the wildcard address
+pub fn host_addresses(local: SocketAddr) -> Vec<SocketAddr> {
+    if !local.ip().is_unspecified() {
+        return vec![local];
+    }
+    let networks = Networks::new_with_refreshed_list();
+    let mut addrs: Vec<SocketAddr> = networks
+        .values()
+        .flat_map(|data| data.ip_networks().iter().map(|network| network.addr))
+        .filter(|ip| ip.is_ipv6() == local.is_ipv6() && !ip.is_loopback())
+        .filter(|ip| match ip {
+            IpAddr::V4(ip) => !ip.is_link_local(),
+            IpAddr::V6(ip) => !ip.is_unicast_link_local(),
+        })
+        .map(|ip| SocketAddr::new(ip, local.port()))
+        .collect();
+    addrs.sort();
+    addrs.dedup();
+    addrs
+}
+
+/// Local candidates of a session and the TURN allocation of its relayed candidate
+pub struct Gathered {
+    pub candidates: Vec<Candidate>,
+    pub relay: Option<TurnClient>,
+}
+
+/// Gather host, server reflexive and relayed candidates on `transport`
+///
+/// A STUN or TURN server that does not answer only leaves out its
+/// candidates.
+pub async fn gather<T: Transport>(
+    transport: &T,
+    stun_server: Option<&str>,
+    turn: Option<&TurnConfig>,
+) -> Result<Gathered, String> {
+    let local = transport
+        .local_addr()
+        .map_err(|e| format!("Failed to get local addr: {}", e))?;
+    let hosts = host_addresses(local);
+    let mut candidates: Vec<Candidate> = hosts
+        .iter()
+        .enumerate()
+        .map(|(i, addr)| Candidate::new(CandidateKind::Host, *addr, None, 65535 - i as u32))
+        .collect();
+    let base = hosts.first().copied().unwrap_or(local);
+
+    if let Some(server) = stun_server {
+        match server_reflexive(transport, server, local.is_ipv6()).await {
+            Ok(mapped) => add_reflexive(&mut candidates, mapped, base),
+            Err(e) => warn!("No server reflexive candidate from {}: {}", server, e),
+        }
+    }
+
+    let relay = match turn {
+        Some(config) => match TurnClient::allocate(transport, config).await {
+            Ok(client) => {
+                if let Some(mapped) = client.mapped_address() {
+                    add_reflexive(&mut candidates, mapped, base);
+                }
+                candidates.push(Candidate::new(
+                    CandidateKind::Relayed,
+                    client.relayed_address(),
+                    client.mapped_address(),
+                    65535,
+                ));
+                Some(client)
+            }
+            Err(e) => {
+                warn!("No relayed candidate from {}: {}", config.server, e);
+                None
+            }
+        },
+        None => None,
+    };
+
+    Ok(Gathered { candidates, relay })
+}
+
+/// Add a server reflexive candidate unless the address is already known
+fn add_reflexive(candidates: &mut Vec<Candidate>, mapped: SocketAddr, base: SocketAddr) {
+    if !candidates.iter().any(|candidate| candidate.addr == mapped) {
+        candidates.push(Candidate::new(
+            CandidateKind::ServerReflexive,
+            mapped,
+            Some(base),
+            65535,
+        ));
+    }
+}
+
+/// Our address as seen by the STUN server
+async fn server_reflexive<T: Transport>(
+    transport: &T,
+    server: &str,
+    ipv6: bool,
+) -> Result<SocketAddr, String> {
+    let server = stun::resolve(server, ipv6).await?;
+    let response = request(
+        transport,
+        server,
+        &Message::request(BINDING_REQUEST).encode(None),
+    )
+    .await?;
+    response
+        .address(ATTR_XOR_MAPPED_ADDRESS)
+        .ok_or_else(|| "STUN response has no mapped address".to_string())
+}
+
+/// How datagrams of a candidate pair travel: straight through the socket or
+/// through the TURN allocation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Base {
+    Direct,
+    Relayed,
+}
+
+/// The selected candidate pair
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Path {
+    pub base: Base,
+    pub remote: SocketAddr,
+}
+
+/// A datagram received on a link
+pub enum Received {
+    /// From the peer, directly or in a TURN Data indication
+    Peer {
+        base: Base,
+        from: SocketAddr,
+        data: Vec<u8>,
+    },
+    /// A response of the TURN server
+    Turn(Message),
+}
+
+/// The UDP socket of a session with its TURN allocation
+pub struct Link<T> {
+    transport: T,
+    relay: Option<TurnClient>,
+}
+
+impl<T: Transport> Link<T> {
+    pub fn new(transport: T, relay: Option<TurnClient>) -> Self {
+        Self { transport, relay }
+    }
+
+    /// Send a datagram to the peer at `to`
+    pub async fn send(&self, base: Base, to: SocketAddr, data: &[u8]) -> io::Result<()> {
+        match (base, &self.relay) {
+            (Base::Direct, _) => self.transport.send_to(data, to).await.map(drop),
+            (Base::Relayed, Some(relay)) => self
+                .transport
+                .send_to(&relay.send_indication(to, data), relay.server())
+                .await
+                .map(drop),
+            (Base::Relayed, None) => Err(io::Error::new(
+                io::ErrorKind::NotConnected,
+                "no TURN allocation",
+            )),
+        }
+    }
+
+    /// Receive the next datagram, unwrapping Data indications
+    pub async fn recv(&self, buf: &mut [u8]) -> io::Result<Received> {
+        let (len, from) = self.transport.recv_from(buf).await?;
+        let data = &buf[..len];
+        if let Some(relay) = &self.relay {
+            if from == relay.server() && stun::is_stun(data) {
+                if let Ok(msg) = Message::decode(data) {
+                    return Ok(match turn::data_indication(&msg) {
+                        Some((from, data)) => Received::Peer {
+                            base: Base::Relayed,
+                            from,
+                            data,
+                        },
+                        None => Received::Turn(msg),
+                    });
+                }
+            }
+        }
+        Ok(Received::Peer {
+            base: Base::Direct,
+            from,
+            data: data.to_vec(),
+        })
+    }
+
+    /// Let `peers` send to the relayed address
+    pub async fn permit(&self, peers: &[SocketAddr]) {
+        if let Some(relay) = &self.relay {
+            let request = relay.create_permission(peers);
+            if let Err(e) = self.transport.send_to(&request, relay.server()).await {
+                debug!("Failed to send TURN permission: {}", e);
+            }
+        }
+    }
+
+    /// Refresh the TURN allocation and the permission for `peer`
+    pub async fn refresh(&self, peer: SocketAddr) {
+        if let Some(relay) = &self.relay {
+            if let Err(e) = self
+                .transport
+                .send_to(&relay.refresh(), relay.server())
+                .await
+            {
+                debug!("Failed to send TURN refresh: {}", e);
+            }
+            self.permit(&[peer]).await;
+        }
+    }
+
+    /// Handle a response of the TURN server, true if the requests have to be
+    /// sent again with a new nonce
+    pub fn handle_turn(&mut self, response: &Message) -> bool {
+        self.relay
+            .as_mut()
+            .is_some_and(|relay| relay.handle_response(response))
+    }
+
+    fn family_of(&self, base: Base) -> Option<bool> {
+        match base {
+            Base::Direct => self.transport.local_addr().ok().map(|addr| addr.is_ipv6()),
+            Base::Relayed => self
+                .relay
+                .as_ref()
+                .map(|relay| relay.relayed_address().is_ipv6()),
+        }
+    }
+}
+
+/// Signalling events during the checks
+#[derive(Debug)]
+pub enum Remote {
+    /// A candidate of the peer
+    Candidate(Candidate),
+    /// The peer has no more candidates
+    End,
+    /// The peer gave up
+    Failed(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PairState {
+    Waiting,
+    InProgress,
+    Succeeded,
+    Failed,
+}
+
+struct Pair {
+    base: Base,
+    remote: SocketAddr,
+    priority: u64,
+    state: PairState,
+    transaction_id: [u8; 12],
+    attempts: u32,
+    next_send: Instant,
+    /// Controlling: nominating this pair; controlled: nominated by the peer
+    nominated: bool,
+}
+
+/// The ICE agent of one side of a session
+#[derive(Debug, Clone)]
+pub struct Agent {
+    local: Credentials,
+    remote: Credentials,
+    controlling: bool,
+    tie_breaker: u64,
+}
+
+impl Agent {
+    pub fn new(local: Credentials, remote: Credentials, controlling: bool) -> Self {
+        Self {
+            local,
+            remote,
+            controlling,
+            tie_breaker: rand::random(),
+        }
+    }
+
+    /// Answer a connectivity check from the peer, None if it is not one
+    pub fn respond(&self, request: &Message, raw: &[u8], from: SocketAddr) -> Option<Vec<u8>> {
+        if request.msg_type != BINDING_REQUEST {
+            return None;
+        }
+        let username = format!("{}:{}", self.local.ufrag, self.remote.ufrag);
+        if request.attribute(ATTR_USERNAME) != Some(username.as_bytes())
+            || !stun::check_integrity(raw, self.local.pwd.as_bytes())
+        {
+            debug!("Ignoring unauthenticated check from {}", from);
+            return None;
+        }
+        let response = Message::new(BINDING_SUCCESS, request.transaction_id)
+            .with_address(ATTR_XOR_MAPPED_ADDRESS, from)
+            .encode(Some(self.local.pwd.as_bytes()));
+        Some(response)
+    }
+
+    /// Run the connectivity checks until a pair is selected
+    pub async fn establish<T: Transport>(
+        &self,
+        link: &mut Link<T>,
+        local_candidates: &[Candidate],
+        remote: &mut mpsc::UnboundedReceiver<Remote>,
+    ) -> Result<Path, String> {
+        let mut checks = Checklist {
+            agent: self,
+            direct_priority: local_candidates
+                .iter()
+                .filter(|candidate| candidate.kind == CandidateKind::Host)
+                .map(|candidate| candidate.priority)
+                .max()
+                .unwrap_or_else(|| priority(CandidateKind::Host, 65535)),
+            relayed_priority: priority(CandidateKind::Relayed, 65535),
+            pairs: Vec::new(),
+            triggered: VecDeque::new(),
+            first_success: None,
+            nominating: false,
+        };
+        let deadline = Instant::now() + CHECK_TIMEOUT;
+        let mut ticker = interval(CHECK_PACING);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut remote_open = true;
+        let mut buf = vec![0u8; 2048];
+
+        loop {
+            tokio::select! {
+                _ = sleep_until(deadline) => {
+                    return Err("ICE connectivity checks timed out".to_string());
+                }
+                event = remote.recv(), if remote_open => match event {
+                    Some(Remote::Candidate(candidate)) => {
+                        checks.add_remote(link, candidate.addr, candidate.priority).await;
+                    }
+                    Some(Remote::End) | None => remote_open = false,
+                    Some(Remote::Failed(reason)) => return Err(reason),
+                },
+                received = link.recv(&mut buf) => match received {
+                    Ok(Received::Peer { base, from, data }) => {
+                        if let Some(path) = checks.handle(link, base, from, &data).await {
+                            return Ok(path);
+                        }
+                    }
+                    Ok(Received::Turn(msg)) => {
+                        if link.handle_turn(&msg) {
+                            let peers: Vec<SocketAddr> = checks.pairs.iter().map(|pair| pair.remote).collect();
+                            link.permit(&peers).await;
+                        }
+                    }
+                    Err(e) => debug!("Failed to receive: {}", e),
+                },
+                _ = ticker.tick() => {
+                    checks.tick(link).await;
+                    let failed = checks.pairs.iter().all(|pair| pair.state == PairState::Failed);
+                    if !remote_open && failed {
+                        return Err("No candidate pair works".to_string());
+                    }
+                }
+            }
+        }
+    }
+
+    /// A check on `pair`, nominating it if the controlling side asks for it
+    fn check_request(&self, pair: &Pair, priority: u32) -> Vec<u8> {
+        let username = format!("{}:{}", self.remote.ufrag, self.local.ufrag);
+        let mut msg = Message::new(BINDING_REQUEST, pair.transaction_id)
+            .with_attribute(ATTR_USERNAME, username.into_bytes())
+            .with_attribute(ATTR_PRIORITY, priority.to_be_bytes());
+        msg = if self.controlling {
+            msg.with_attribute(ATTR_ICE_CONTROLLING, self.tie_breaker.to_be_bytes())
+        } else {
+            msg.with_attribute(ATTR_ICE_CONTROLLED, self.tie_breaker.to_be_bytes())
+        };
+        if self.controlling && pair.nominated {
+            msg = msg.with_attribute(ATTR_USE_CANDIDATE, Vec::new());
+        }
+        msg.encode(Some(self.remote.pwd.as_bytes()))
+    }
+}
+
+/// State of the connectivity checks
+struct Checklist<'a> {
+    agent: &'a Agent,
+    /// Priorities of the local candidates of each base
+    direct_priority: u32,
+    relayed_priority: u32,
+    pairs: Vec<Pair>,
+    triggered: VecDeque<usize>,
+    first_success: Option<Instant>,
+    nominating: bool,
+}
+
+impl Checklist<'_> {
+    /// Pair a remote address with each base of its family
+    async fn add_remote<T: Transport>(
+        &mut self,
+        link: &Link<T>,
+        addr: SocketAddr,
+        remote_priority: u32,
+    ) {
+        for base in [Base::Direct, Base::Relayed] {
+            if link.family_of(base) == Some(addr.is_ipv6()) && self.find(base, addr).is_none() {
+                self.add_pair(base, addr, remote_priority);
+            }
+        }
+        link.permit(&[addr]).await;
+    }
+
+    fn add_pair(&mut self, base: Base, remote: SocketAddr, remote_priority: u32) -> usize {
+        let local_priority = match base {
+            Base::Direct => self.direct_priority,
+            Base::Relayed => self.relayed_priority,
+        };
+        let priority = if self.agent.controlling {
+            pair_priority(local_priority, remote_priority)
+        } else {
+            pair_priority(remote_priority, local_priority)
+        };
+        debug!(
+            "Candidate pair {:?} -> {} (priority {})",
+            base, remote, priority
+        );
+        self.pairs.push(Pair {
+            base,
+            remote,
+            priority,
+            state: PairState::Waiting,
+            transaction_id: [0; 12],
+            attempts: 0,
+            next_send: Instant::now(),
+            nominated: false,
+        });
+        self.pairs.len() - 1
+    }
+
+    fn find(&self, base: Base, remote: SocketAddr) -> Option<usize> {
+        self.pairs
+            .iter()
+            .position(|pair| pair.base == base && pair.remote == remote)
+    }
+
+    /// Start a new transaction on pair `i`
+    async fn start_check<T: Transport>(&mut self, link: &Link<T>, i: usize) {
+        let pair = &mut self.pairs[i];
+        pair.state = PairState::InProgress;
+        pair.transaction_id = rand::random();
+        pair.attempts = 0;
+        self.send_check(link, i).await;
+    }
+
+    async fn send_check<T: Transport>(&mut self, link: &Link<T>, i: usize) {
+        let priority = match self.pairs[i].base {
+            Base::Direct => priority(CandidateKind::PeerReflexive, 65535),
+            Base::Relayed => self.relayed_priority,
+        };
+        let pair = &mut self.pairs[i];
+        let request = self.agent.check_request(pair, priority);
+        pair.attempts += 1;
+        pair.next_send = Instant::now() + CHECK_RTO;
+        if let Err(e) = link.send(pair.base, pair.remote, &request).await {
+            debug!("Failed to send check to {}: {}", pair.remote, e);
+        }
+    }
+
+    /// Retransmit, start the next check and nominate
+    async fn tick<T: Transport>(&mut self, link: &Link<T>) {
+        let now = Instant::now();
+        for i in 0..self.pairs.len() {
+            let pair = &mut self.pairs[i];
+            if pair.state != PairState::InProgress || pair.next_send > now {
+                continue;
+            }
+            if pair.attempts >= CHECK_ATTEMPTS {
+                debug!("Candidate pair {:?} -> {} failed", pair.base, pair.remote);
+                pair.state = PairState::Failed;
+                if pair.nominated {
+                    self.nominating = false;
+                }
+            } else {
+                self.send_check(link, i).await;
+            }
+        }
+
+        let next = std::iter::from_fn(|| self.triggered.pop_front())
+            .find(|i| self.pairs[*i].state == PairState::Waiting)
+            .or_else(|| {
+                (0..self.pairs.len())
+                    .filter(|i| self.pairs[*i].state == PairState::Waiting)
+                    .max_by_key(|i| self.pairs[*i].priority)
+            });
+        if let Some(i) = next {
+            self.start_check(link, i).await;
+        }
+
+        if self.agent.controlling && !self.nominating {
+            if let Some(i) = self.nomination(now) {
+                debug!(
+                    "Nominating {:?} -> {}",
+                    self.pairs[i].base, self.pairs[i].remote
+                );
+                self.nominating = true;
+                self.pairs[i].nominated = true;
+                self.start_check(link, i).await;
+            }
+        }
+    }
+
+    /// The best working pair once no better pair can still succeed, or
+    /// after the nomination delay
+    fn nomination(&self, now: Instant) -> Option<usize> {
+        let best = (0..self.pairs.len())
+            .filter(|i| self.pairs[*i].state == PairState::Succeeded)
+            .max_by_key(|i| self.pairs[*i].priority)?;
+        let better_pending = self.pairs.iter().any(|pair| {
+            matches!(pair.state, PairState::Waiting | PairState::InProgress)
+                && pair.priority > self.pairs[best].priority
+        });
+        let waited = self
+            .first_success
+            .is_some_and(|first| now >= first + NOMINATION_DELAY);
+        (!better_pending || waited).then_some(best)
+    }
+
+    /// Handle a datagram from the peer, returning the selected path
+    async fn handle<T: Transport>(
+        &mut self,
+        link: &Link<T>,
+        base: Base,
+        from: SocketAddr,
+        data: &[u8],
+    ) -> Option<Path> {
+        // Early data of a peer that already selected is retransmitted later
+        let msg = Message::decode(data).ok()?;
+        match msg.msg_type {
+            BINDING_REQUEST => {
+                let response = self.agent.respond(&msg, data, from)?;
+                if let Err(e) = link.send(base, from, &response).await {
+                    debug!("Failed to answer check from {}: {}", from, e);
+                }
+                let i = match self.find(base, from) {
+                    Some(i) => i,
+                    None => {
+                        // Peer reflexive: the peer's NAT gave it a new mapping
+                        let priority = msg
+                            .attribute(ATTR_PRIORITY)
+                            .and_then(|value| value.try_into().ok())
+                            .map(u32::from_be_bytes)
+                            .unwrap_or_else(|| priority(CandidateKind::PeerReflexive, 0));
+                        debug!("Peer reflexive candidate {}", from);
+                        link.permit(&[from]).await;
+                        self.add_pair(base, from, priority)
+                    }
+                };
+                let pair = &mut self.pairs[i];
+                if !self.agent.controlling && msg.attribute(ATTR_USE_CANDIDATE).is_some() {
+                    pair.nominated = true;
+                    if pair.state == PairState::Succeeded {
+                        return Some(Path { base, remote: from });
+                    }
+                }
+                if matches!(pair.state, PairState::Waiting | PairState::Failed) {
+                    pair.state = PairState::Waiting;
+                    self.triggered.push_back(i);
+                }
+                None
+            }
+            BINDING_SUCCESS | BINDING_ERROR => {
+                let i = self.pairs.iter().position(|pair| {
+                    pair.state == PairState::InProgress && pair.transaction_id == msg.transaction_id
+                })?;
+                if !stun::check_integrity(data, self.agent.remote.pwd.as_bytes()) {
+                    debug!("Ignoring unauthenticated response from {}", from);
+                    return None;
+                }
+                let pair = &mut self.pairs[i];
+                if msg.msg_type == BINDING_ERROR || from != pair.remote {
+                    pair.state = PairState::Failed;
+                    if pair.nominated {
+                        self.nominating = false;
+                    }
+                    return None;
+                }
+                debug!("Candidate pair {:?} -> {} works", pair.base, pair.remote);
+                pair.state = PairState::Succeeded;
+                self.first_success.get_or_insert_with(Instant::now);
+                pair.nominated.then_some(Path {
+                    base: pair.base,
+                    remote: pair.remote,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// In-process NAT emulator: datagrams of the inner socket leave through
+/// sockets on a public IP, one per mapping, with RFC 4787 mapping and
+/// filtering behavior
+#[cfg(test)]
+pub(crate) mod nat {
+    use super::Transport;
+    use crate::stun::NatBehavior;
+    use std::collections::{HashMap, HashSet};
+    use std::future::Future;
+    use std::io;
+    use std::net::{IpAddr, SocketAddr};
+    use std::sync::{Arc, Mutex};
+    use tokio::net::UdpSocket;
+    use tokio::sync::mpsc;
+
+    type Datagram = (Vec<u8>, SocketAddr);
+
+    #[derive(Clone)]
+    struct Mapping {
+        socket: Arc<UdpSocket>,
+        sent_to: Arc<Mutex<HashSet<SocketAddr>>>,
+    }
+
+    pub struct NatSocket {
+        private: SocketAddr,
+        public_ip: IpAddr,
+        mapping: NatBehavior,
+        filtering: NatBehavior,
+        mappings: Mutex<HashMap<Option<SocketAddr>, Mapping>>,
+        inbox_tx: mpsc::UnboundedSender<Datagram>,
+        inbox: tokio::sync::Mutex<mpsc::UnboundedReceiver<Datagram>>,
+    }
+
+    impl NatSocket {
+        /// A socket at the unreachable `private` address behind a NAT on `public_ip`
+        pub fn new(
+            private: SocketAddr,
+            public_ip: IpAddr,
+            mapping: NatBehavior,
+            filtering: NatBehavior,
+        ) -> Self {
+            let (inbox_tx, inbox) = mpsc::unbounded_channel();
+            Self {
+                private,
+                public_ip,
+                mapping,
+                filtering,
+                mappings: Mutex::new(HashMap::new()),
+                inbox_tx,
+                inbox: tokio::sync::Mutex::new(inbox),
+            }
+        }
+
+        /// The public socket for datagrams to `target`, created on first use
+        fn mapping(&self, target: SocketAddr) -> io::Result<Mapping> {
+            let key = match self.mapping {
+                NatBehavior::EndpointIndependent => None,
+                NatBehavior::AddressDependent => Some(SocketAddr::new(target.ip(), 0)),
+                NatBehavior::AddressAndPortDependent => Some(target),
+            };
+            let mut mappings = self.mappings.lock().unwrap();
+            if let Some(mapping) = mappings.get(&key) {
+                return Ok(mapping.clone());
+            }
+            let socket = std::net::UdpSocket::bind((self.public_ip, 0))?;
+            socket.set_nonblocking(true)?;
+            let socket = Arc::new(UdpSocket::from_std(socket)?);
+            let sent_to = Arc::new(Mutex::new(HashSet::<SocketAddr>::new()));
+            let (rx_socket, rx_sent_to) = (socket.clone(), sent_to.clone());
+            let (filtering, inbox) = (self.filtering, self.inbox_tx.clone());
+            tokio::spawn(async move {
+                let mut buf = vec![0u8; 2048];
+                while let Ok((len, from)) = rx_socket.recv_from(&mut buf).await {
+                    let allowed = {
+                        let sent_to = rx_sent_to.lock().unwrap();
+                        match filtering {
+                            NatBehavior::EndpointIndependent => true,
+                            NatBehavior::AddressDependent => {
+                                sent_to.iter().any(|addr| addr.ip() == from.ip())
+                            }
+                            NatBehavior::AddressAndPortDependent => sent_to.contains(&from),
+                        }
+                    };
+                    if allowed && inbox.send((buf[..len].to_vec(), from)).is_err() {
+                        break;
+                    }
+                }
+            });
+            let mapping = Mapping { socket, sent_to };
+            mappings.insert(key, mapping.clone());
+            Ok(mapping)
+        }
+    }
+
+    impl Transport for NatSocket {
+        fn local_addr(&self) -> io::Result<SocketAddr> {
+            Ok(self.private)
+        }
+
+        fn send_to(
+            &self,
+            buf: &[u8],
+            target: SocketAddr,
+        ) -> impl Future<Output = io::Result<usize>> + Send {
+            let mapping = self.mapping(target);
+            async move {
+                let mapping = mapping?;
+                mapping.sent_to.lock().unwrap().insert(target);
+                mapping.socket.send_to(buf, target).await
+            }
+        }
+
+        async fn recv_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+            let (data, from) = self
+                .inbox
+                .lock()
+                .await
+                .recv()
+                .await
+                .ok_or_else(|| io::Error::from(io::ErrorKind::BrokenPipe))?;
+            let len = data.len().min(buf.len());
+            buf[..len].copy_from_slice(&data[..len]);
+            Ok((len, from))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::nat::NatSocket;
+    use super::*;
+    use crate::stun::NatBehavior::{self, *};
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn test_candidate_format() {
+        let host = Candidate::new(
+            CandidateKind::Host,
+            "192.168.1.10:40000".parse().unwrap(),
+            None,
+            65535,
+        );
+        assert_eq!(host.priority, 2130706431);
+        let line = host.to_string();
+        assert!(line.starts_with("candidate:"), "{}", line);
+        assert!(
+            line.ends_with(" 1 udp 2130706431 192.168.1.10 40000 typ host"),
+            "{}",
+            line
+        );
+        assert_eq!(line.parse::<Candidate>(), Ok(host));
+
+        let relay = Candidate::new(
+            CandidateKind::Relayed,
+            "[2001:db8::5]:49152".parse().unwrap(),
+            Some("[2001:db8::7]:40000".parse().unwrap()),
+            65535,
+        );
+        let line = relay.to_string();
+        assert!(
+            line.ends_with("typ relay raddr 2001:db8::7 rport 40000"),
+            "{}",
+            line
+        );
+        assert_eq!(format!("a={}", line).parse::<Candidate>(), Ok(relay));
+
+        for invalid in [
+            "",
+            "candidate:1 1 tcp 1 10.0.0.1 9 typ host",
+            "candidate:1 1 udp 1 10.0.0.1 9 typ unknown",
+            "candidate:1 1 udp x 10.0.0.1 9 typ host",
+        ] {
+            assert!(invalid.parse::<Candidate>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_priorities() {
+        let host = priority(CandidateKind::Host, 65535);
+        let srflx = priority(CandidateKind::ServerReflexive, 65535);
+        let relay = priority(CandidateKind::Relayed, 65535);
+        assert!(host > srflx && srflx > relay);
+        // Both sides order the pairs the same way
+        assert_eq!(pair_priority(host, srflx), pair_priority(host, srflx));
+        assert!(pair_priority(host, srflx) > pair_priority(srflx, host));
+        assert!(pair_priority(srflx, srflx) > pair_priority(relay, host));
+    }
+
+    #[test]
+    fn test_respond_checks_credentials() {
+        let (a, b) = (Credentials::generate(), Credentials::generate());
+        let controlling = Agent::new(a.clone(), b.clone(), true);
+        let controlled = Agent::new(b, a, false);
+        let pair = Pair {
+            base: Base::Direct,
+            remote: "127.0.0.1:1".parse().unwrap(),
+            priority: 0,
+            state: PairState::InProgress,
+            transaction_id: [9; 12],
+            attempts: 0,
+            next_send: Instant::now(),
+            nominated: true,
+        };
+        let from: SocketAddr = "203.0.113.1:5000".parse().unwrap();
+        let request = controlling.check_request(&pair, 1);
+        let msg = Message::decode(&request).unwrap();
+        assert!(msg.attribute(ATTR_USE_CANDIDATE).is_some());
+        let response = controlled.respond(&msg, &request, from).unwrap();
+        assert!(stun::check_integrity(
+            &response,
+            controlled.local.pwd.as_bytes()
+        ));
+        let response = Message::decode(&response).unwrap();
+        assert_eq!(response.address(ATTR_XOR_MAPPED_ADDRESS), Some(from));
+
+        // The controlling side does not accept its own checks
+        assert!(controlling.respond(&msg, &request, from).is_none());
+    }
+
+    struct Side {
+        link: Link<NatSocket>,
+        candidates: Vec<Candidate>,
+    }
+
+    async fn side(
+        private: &str,
+        public: Ipv4Addr,
+        behavior: (NatBehavior, NatBehavior),
+        stun_server: &str,
+        turn: Option<&TurnConfig>,
+    ) -> Side {
+        let socket = NatSocket::new(
+            private.parse().unwrap(),
+            IpAddr::V4(public),
+            behavior.0,
+            behavior.1,
+        );
+        let gathered = gather(&socket, Some(stun_server), turn).await.unwrap();
+        Side {
+            link: Link::new(socket, gathered.relay),
+            candidates: gathered.candidates,
+        }
+    }
+
+    /// Run the checks between two agents behind emulated NATs
+    async fn connect(
+        a: (NatBehavior, NatBehavior),
+        b: (NatBehavior, NatBehavior),
+        turn: bool,
+    ) -> (Result<Path, String>, Result<Path, String>, Vec<Candidate>) {
+        let stun_server = stun::start_domain_stun(false).await.to_string();
+        let turn = match turn {
+            true => Some(TurnConfig {
+                server: turn::start_turn_server("agent", "secret").await.to_string(),
+                username: "agent".to_string(),
+                password: "secret".to_string(),
+            }),
+            false => None,
+        };
+        let mut a_side = side(
+            "192.0.2.1:40000",
+            Ipv4Addr::new(127, 0, 0, 3),
+            a,
+            &stun_server,
+            turn.as_ref(),
+        )
+        .await;
+        let mut b_side = side(
+            "192.0.2.2:40000",
+            Ipv4Addr::new(127, 0, 0, 4),
+            b,
+            &stun_server,
+            turn.as_ref(),
+        )
+        .await;
+        assert!(a_side
+            .candidates
+            .iter()
+            .any(|candidate| candidate.kind == CandidateKind::ServerReflexive));
+
+        let (a_creds, b_creds) = (Credentials::generate(), Credentials::generate());
+        let a_agent = Agent::new(a_creds.clone(), b_creds.clone(), true);
+        let b_agent = Agent::new(b_creds, a_creds, false);
+        let (a_tx, mut a_rx) = mpsc::unbounded_channel();
+        let (b_tx, mut b_rx) = mpsc::unbounded_channel();
+        for candidate in &b_side.candidates {
+            a_tx.send(Remote::Candidate(candidate.clone())).unwrap();
+        }
+        a_tx.send(Remote::End).unwrap();
+        for candidate in &a_side.candidates {
+            b_tx.send(Remote::Candidate(candidate.clone())).unwrap();
+        }
+        b_tx.send(Remote::End).unwrap();
+
+        let (a_path, b_path) = tokio::join!(
+            a_agent.establish(&mut a_side.link, &a_side.candidates, &mut a_rx),
+            b_agent.establish(&mut b_side.link, &b_side.candidates, &mut b_rx),
+        );
+        (a_path, b_path, b_side.candidates)
+    }
+
+    fn public(last: u8) -> IpAddr {
+        IpAddr::V4(Ipv4Addr::new(127, 0, 0, last))
+    }
+
+    #[tokio::test]
+    async fn test_hole_punching_port_restricted() {
+        let port_restricted = (EndpointIndependent, AddressAndPortDependent);
+        let (a, b, _) = connect(port_restricted, port_restricted, false).await;
+        let (a, b) = (a.unwrap(), b.unwrap());
+        assert_eq!(a.base, Base::Direct);
+        assert_eq!(a.remote.ip(), public(4));
+        assert_eq!(b.base, Base::Direct);
+        assert_eq!(b.remote.ip(), public(3));
+    }
+
+    #[tokio::test]
+    async fn test_symmetric_to_full_cone_uses_peer_reflexive() {
+        let symmetric = (AddressAndPortDependent, AddressAndPortDependent);
+        let full_cone = (EndpointIndependent, EndpointIndependent);
+        let (a, b, _) = connect(symmetric, full_cone, false).await;
+        let (a, b) = (a.unwrap(), b.unwrap());
+        assert_eq!((a.base, a.remote.ip()), (Base::Direct, public(4)));
+        assert_eq!((b.base, b.remote.ip()), (Base::Direct, public(3)));
+    }
+
+    #[tokio::test]
+    async fn test_symmetric_nats_need_turn() {
+        let symmetric = (AddressAndPortDependent, AddressAndPortDependent);
+        let (a, b, _) = connect(symmetric, symmetric, false).await;
+        assert!(a.is_err() && b.is_err(), "{:?} {:?}", a, b);
+
+        let (a, b, b_candidates) = connect(symmetric, symmetric, true).await;
+        let (a, b) = (a.unwrap(), b.unwrap());
+        let b_relay = b_candidates
+            .iter()
+            .find(|candidate| candidate.kind == CandidateKind::Relayed)
+            .unwrap()
+            .addr;
+        assert!(a.base == Base::Relayed || a.remote == b_relay, "{:?}", a);
+        assert!(
+            b.base == Base::Relayed || b.remote.ip() == public(5),
+            "{:?}",
+            b
+        );
+    }
+}
@@ -7,16 +7,29 @@
 //! - SOCKS5 and HTTP proxy support
 //! - Reverse tunnel for inbound connections
 //! - P2P connectivity between agents
+//! - DDNS updates when the public IP changes
+//! - Remote task execution within an allow-list policy
+//! - ACME HTTP-01 challenges for the Hub
+//! - TLS certificate probes of hosts in private networks
 
+mod certprobe;
 mod client;
 mod config;
 mod crypto;
+mod ddns;
 mod diagnostic;
+mod http01;
+mod ice;
 mod identity;
+mod net;
 mod proxy;
 mod p2p;
+mod p2p_stream;
 mod protocol;
+mod stun;
+mod task;
 mod tunnel;
+mod turn;
 
 use tracing::{error, info, warn};
 
@@ -32,8 +45,6 @@ fn rand_u64() -> u64 {
 use crate::client::AgentClient;
 use crate::config::AgentConfig;
 use crate::identity::AgentIdentity;
-use crate::p2p::P2pManager;
-use crate::tunnel::{TunnelManager, TunnelType};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -53,17 +64,6 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         config.name, config.hub
     );
 
-    // Create managers
-    let tunnel_manager = TunnelManager::new();
-    let p2p_manager = P2pManager::new(config.p2p_port);
-
-    // Initialize P2P (discover external address, NAT type)
-    if let Err(e) = p2p_manager.initialize().await {
-        warn!("P2P initialization failed (continuing anyway): {}", e);
-    } else if let Some(addr) = p2p_manager.get_external_address().await {
-        info!("P2P external address: {}:{}", addr.ip, addr.port);
-    }
-
     // Load or create persistent identity
     let identity = AgentIdentity::load_or_create(&config.config_dir)
         .map_err(|e| format!("Failed to load identity: {}", e))?;
@@ -71,6 +71,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Create agent client
     let mut client = AgentClient::new(config.clone(), identity);
 
+    // Initialize P2P (listeners, external address, NAT type)
+    let p2p_manager = client.p2p();
+    if let Err(e) = p2p_manager.initialize().await {
+        warn!("P2P initialization failed (continuing anyway): {}", e);
+    } else if let Some(addr) = p2p_manager.get_external_address().await {
+        info!("P2P external address: {}:{}", addr.ip, addr.port);
+    }
+
     // Connect to Hub with retry
     let reconnection = &config.reconnection;
     let mut retries = 0u32;
@@ -130,24 +138,6 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
-    // Start reverse tunnel if configured
-    if config.tunnel_port > 0 {
-        match tunnel_manager
-            .start_tunnel(&client, config.tunnel_port, TunnelType::Tcp)
-            .await
-        {
-            Ok(tunnel_id) => {
-                info!(
-                    "Reverse tunnel {} started on port {}",
-                    tunnel_id, config.tunnel_port
-                );
-            }
-            Err(e) => {
-                error!("Failed to start tunnel: {}", e);
-            }
-        }
-    }
-
     // Run main loop (heartbeat + message handling)
     // This will block until disconnect or error
     if let Err(e) = client.run().await {
@@ -155,7 +145,6 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     // Cleanup
-    tunnel_manager.stop_all().await;
     p2p_manager.close_all().await;
 
     // Graceful disconnect
@@ -0,0 +1,29 @@
+//! Socket helpers shared by the agent's listeners
+
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tracing::warn;
+
+const ACCEPT_BACKOFF_MIN: Duration = Duration::from_millis(100);
+const ACCEPT_BACKOFF_MAX: Duration = Duration::from_secs(5);
+
+/// Accept the next connection on `listener`
+///
+/// Accept errors such as running out of file descriptors persist until
+/// connections are closed, so retry after a delay that doubles with each
+/// consecutive failure instead of spinning. `name` identifies the listener
+/// in the log.
+pub async fn accept_with_backoff(listener: &TcpListener, name: &str) -> (TcpStream, SocketAddr) {
+    let mut delay = ACCEPT_BACKOFF_MIN;
+    loop {
+        match listener.accept().await {
+            Ok(accepted) => return accepted,
+            Err(e) => {
+                warn!("{}: accept failed, retrying in {:?}: {}", name, delay, e);
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(ACCEPT_BACKOFF_MAX);
+            }
+        }
+    }
+}
@@ -1,16 +1,34 @@
 //! P2P connection support for Domain Agent
 //!
 //! This module handles NAT traversal and peer-to-peer connections between agents.
-//! Uses a combination of STUN-like techniques and Hub relay for signaling.
+//! The external address and NAT type come from the RFC 5780 behavior tests
+//! against a STUN server such as `domain-stun`; signaling is relayed by the Hub.
+//!
+//! A connection is set up ICE-style: both sides gather host, server reflexive
+//! and relayed candidates, exchange them with the offer and answer through the
+//! Hub, and run connectivity checks that punch the UDP hole (see `ice`). The
+//! selected path carries an encrypted, reliable stream (see `p2p_stream`)
+//! between a `[[p2p.connect]]` listener and the peer's `expose` target.
 
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
-use tokio::net::{TcpStream, UdpSocket};
-use tokio::sync::{broadcast, RwLock};
+use std::time::Duration;
+use tokio::io::{copy_bidirectional, DuplexStream};
+use tokio::net::{TcpListener, TcpStream, UdpSocket};
+use tokio::sync::{broadcast, mpsc, oneshot, RwLock};
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
+use crate::config::{AgentConfig, P2pConnectConfig, TurnConfig};
+use crate::ice::{self, Agent, Candidate, Credentials, Link, Remote};
+use crate::net::accept_with_backoff;
+use crate::p2p_stream::{self, KeyPair};
+use crate::stun::{self, NatBehavior, NatDiscovery};
+
+/// Time the initiator waits for the answer, the peer gathers its candidates first
+const ANSWER_TIMEOUT: Duration = Duration::from_secs(30);
+
 /// P2P message types for signaling
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(tag = "type", content = "payload")]
@@ -44,45 +62,79 @@ pub enum P2pMessage {
     },
 
     /// P2P connection established
-    P2pConnected {
-        request_id: Uuid,
-    },
+    P2pConnected { request_id: Uuid },
 
     /// P2P connection failed
-    P2pFailed {
-        request_id: Uuid,
-        reason: String,
-    },
+    P2pFailed { request_id: Uuid, reason: String },
 }
 
 /// P2P connection state
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum P2pState {
-    /// Idle - no connection attempt
-    Idle,
-    /// Connecting - initiating connection
+    /// Connecting - gathering candidates for the offer
     Connecting,
-    /// Received offer - waiting for answer
-    ReceivedOffer,
     /// Sent offer - waiting for answer
     SentOffer,
-    /// Connected - P2P connection established
+    /// Received offer - answer sent, checking connectivity
+    ReceivedOffer,
+    /// Connected - the stream is running
     Connected,
-    /// Failed - connection failed
-    Failed(String),
 }
 
-/// A P2P connection
-pub struct P2pConnection {
-    pub id: Uuid,
-    pub peer_agent_id: Uuid,
-    pub state: P2pState,
-    pub direct_socket: Option<TcpStream>,
-    pub created_at: std::time::Instant,
+/// A P2P session, from the offer until its stream closes
+struct Session {
+    peer_agent_id: Uuid,
+    state: P2pState,
+    /// Initiator only: the answer, or the reason the peer gave up
+    answer: Option<oneshot::Sender<Result<String, String>>>,
+    /// Candidates trickled by the peer
+    remote: mpsc::UnboundedSender<Remote>,
+}
+
+/// The `sdp_offer` or `sdp_answer` of a session: the ICE credentials and the
+/// X25519 public key of the stream; candidates are trickled separately
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SessionDescription {
+    credentials: Credentials,
+    key: Vec<u8>,
+}
+
+impl SessionDescription {
+    fn encode(&self) -> String {
+        format!(
+            "v=0\r\na=ice-ufrag:{}\r\na=ice-pwd:{}\r\na=key:{}\r\n",
+            self.credentials.ufrag,
+            self.credentials.pwd,
+            BASE64.encode(&self.key)
+        )
+    }
+
+    fn parse(sdp: &str) -> Result<Self, String> {
+        let (mut ufrag, mut pwd, mut key) = (None, None, None);
+        for line in sdp.lines().map(str::trim) {
+            if let Some(value) = line.strip_prefix("a=ice-ufrag:") {
+                ufrag = Some(value.to_string());
+            } else if let Some(value) = line.strip_prefix("a=ice-pwd:") {
+                pwd = Some(value.to_string());
+            } else if let Some(value) = line.strip_prefix("a=key:") {
+                let value = BASE64
+                    .decode(value)
+                    .map_err(|e| format!("Invalid key in SDP: {}", e))?;
+                key = Some(value);
+            }
+        }
+        match (ufrag, pwd, key) {
+            (Some(ufrag), Some(pwd), Some(key)) => Ok(Self {
+                credentials: Credentials { ufrag, pwd },
+                key,
+            }),
+            _ => Err("SDP lacks ice-ufrag, ice-pwd or key".to_string()),
+        }
+    }
 }
 
 /// NAT type detection result
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum NatType {
     /// Full cone NAT - any external host can send to us
     FullCone,
@@ -94,6 +146,28 @@ pub enum NatType {
     Symmetric,
     /// No NAT - direct connection possible
     NoNat,
+    /// The STUN server cannot run the RFC 5780 behavior tests
+    Unknown,
+}
+
+impl NatType {
+    /// Classify the mapping and filtering behavior found by RFC 5780 discovery
+    pub fn from_discovery(discovery: &NatDiscovery) -> Self {
+        let (Some(mapping), Some(filtering)) = (discovery.mapping, discovery.filtering) else {
+            return NatType::Unknown;
+        };
+        if mapping != NatBehavior::EndpointIndependent {
+            return NatType::Symmetric;
+        }
+        match filtering {
+            NatBehavior::EndpointIndependent if discovery.mapped == discovery.local => {
+                NatType::NoNat
+            }
+            NatBehavior::EndpointIndependent => NatType::FullCone,
+            NatBehavior::AddressDependent => NatType::RestrictedCone,
+            NatBehavior::AddressAndPortDependent => NatType::PortRestrictedCone,
+        }
+    }
 }
 
 /// External address info
@@ -105,259 +179,465 @@ pub struct ExternalAddress {
 
 /// P2P connection manager
 pub struct P2pManager {
-    /// Active P2P connections
-    connections: Arc<RwLock<HashMap<Uuid, P2pConnection>>>,
+    /// Active P2P sessions by request ID
+    sessions: RwLock<HashMap<Uuid, Session>>,
     /// External address (discovered via STUN-like query)
-    external_addr: Arc<RwLock<Option<ExternalAddress>>>,
+    external_addr: RwLock<Option<ExternalAddress>>,
     /// NAT type
-    nat_type: Arc<RwLock<Option<NatType>>>,
-    /// Local listening port for P2P
+    nat_type: RwLock<Option<NatType>>,
+    /// Local port for NAT discovery
     listen_port: u16,
-    /// Shutdown signal
-    shutdown_tx: Arc<RwLock<Option<broadcast::Sender<()>>>>,
+    /// STUN server (host:port) for NAT discovery and server reflexive candidates
+    stun_server: Option<String>,
+    /// TURN server for relayed candidates
+    turn: Option<TurnConfig>,
+    /// Local target of the peers' connections, None refuses them
+    expose: Option<String>,
+    /// Agents allowed to connect to `expose`
+    allowed_peers: Vec<Uuid>,
+    /// Local listeners carried to other agents
+    connect: Vec<P2pConnectConfig>,
+    /// Signaling messages for the Hub
+    signals: mpsc::UnboundedSender<P2pMessage>,
+    /// Shutdown signal for the listeners and streams
+    shutdown_tx: broadcast::Sender<()>,
 }
 
 impl P2pManager {
-    /// Create a new P2P manager
-    pub fn new(listen_port: u16) -> Self {
+    /// Create a new P2P manager, its signaling messages go to `signals`
+    pub fn new(config: &AgentConfig, signals: mpsc::UnboundedSender<P2pMessage>) -> Self {
         Self {
-            connections: Arc::new(RwLock::new(HashMap::new())),
-            external_addr: Arc::new(RwLock::new(None)),
-            nat_type: Arc::new(RwLock::new(None)),
-            listen_port,
-            shutdown_tx: Arc::new(RwLock::new(None)),
+            sessions: RwLock::new(HashMap::new()),
+            external_addr: RwLock::new(None),
+            nat_type: RwLock::new(None),
+            listen_port: config.p2p_port,
+            stun_server: config.p2p_stun_server.clone(),
+            turn: config.p2p_turn.clone(),
+            expose: config.p2p_expose.clone(),
+            allowed_peers: config.p2p_allowed_peers.clone(),
+            connect: config.p2p_connect.clone(),
+            signals,
+            shutdown_tx: broadcast::channel(1).0,
         }
     }
 
-    /// Initialize P2P manager - discover external address and NAT type
-    pub async fn initialize(&self) -> Result<(), String> {
-        info!("Initializing P2P manager on port {}", self.listen_port);
-
-        // Try to discover external address
-        match self.discover_external_address().await {
-            Ok(addr) => {
-                info!("Discovered external address: {}:{}", addr.ip, addr.port);
-                *self.external_addr.write().await = Some(addr);
-            }
-            Err(e) => {
-                warn!("Failed to discover external address: {}", e);
-            }
-        }
-
-        // Try to detect NAT type
-        match self.detect_nat_type().await {
-            Ok(nat) => {
-                info!("Detected NAT type: {:?}", nat);
-                *self.nat_type.write().await = Some(nat);
-            }
-            Err(e) => {
-                warn!("Failed to detect NAT type: {}", e);
-            }
+    /// Initialize P2P manager - start the listeners, discover external address and NAT type
+    pub async fn initialize(self: &Arc<Self>) -> Result<(), String> {
+        for connect in &self.connect {
+            self.listen(connect).await?;
         }
 
-        Ok(())
-    }
+        info!("Initializing P2P manager on port {}", self.listen_port);
 
-    /// Discover external address using a simple STUN-like query
-    async fn discover_external_address(&self) -> Result<ExternalAddress, String> {
-        // Note: In production, use a real STUN server
-        // For now, we use a simple UDP socket to get local address
-        // and assume it's the external address (works for no-NAT scenarios)
+        let Some(server) = &self.stun_server else {
+            debug!("No STUN server configured, skipping NAT discovery");
+            return Ok(());
+        };
 
         let socket = UdpSocket::bind(format!("0.0.0.0:{}", self.listen_port))
             .await
             .map_err(|e| format!("Failed to bind UDP socket: {}", e))?;
+        let discovery = stun::discover_nat(&socket, server).await?;
+        debug!(
+            "NAT mapping {:?}, filtering {:?}",
+            discovery.mapping, discovery.filtering
+        );
 
-        // Connect to a public server to discover our external address
-        // Using a simple echo/whoami service
-        let (ip, port) = match socket.peer_addr() {
-            Ok(peer) => (peer.ip().to_string(), peer.port()),
-            Err(_) => {
-                // Fallback: use local address
-                let local = socket.local_addr()
-                    .map_err(|e| format!("Failed to get local addr: {}", e))?;
-                (local.ip().to_string(), local.port())
-            }
+        let addr = ExternalAddress {
+            ip: discovery.mapped.ip().to_string(),
+            port: discovery.mapped.port(),
         };
+        info!("Discovered external address: {}:{}", addr.ip, addr.port);
+        *self.external_addr.write().await = Some(addr);
 
-        Ok(ExternalAddress { ip, port })
-    }
+        let nat = NatType::from_discovery(&discovery);
+        info!("Detected NAT type: {:?}", nat);
+        *self.nat_type.write().await = Some(nat);
 
-    /// Detect NAT type using simple tests
-    async fn detect_nat_type(&self) -> Result<NatType, String> {
-        // Simplified NAT detection
-        // In production, use multiple STUN servers and perform proper tests
+        Ok(())
+    }
 
-        let socket = UdpSocket::bind("0.0.0.0:0")
+    /// Carry the connections accepted on `connect.bind` to the peer agent
+    async fn listen(self: &Arc<Self>, connect: &P2pConnectConfig) -> Result<(), String> {
+        let listener = TcpListener::bind(&connect.bind)
             .await
-            .map_err(|e| format!("Failed to create UDP socket: {}", e))?;
+            .map_err(|e| format!("Failed to listen on {}: {}", connect.bind, e))?;
+        info!("P2P forward {} -> agent {}", connect.bind, connect.peer);
+
+        let manager = self.clone();
+        let peer = connect.peer;
+        let mut shutdown = self.shutdown_tx.subscribe();
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    (stream, addr) = accept_with_backoff(&listener, "P2P forward") => {
+                        debug!("P2P forward connection from {} to agent {}", addr, peer);
+                        tokio::spawn(manager.clone().forward(peer, stream));
+                    }
+                    _ = shutdown.recv() => break,
+                }
+            }
+        });
+        Ok(())
+    }
 
-        // Try to connect to itself - if it works, no NAT
-        if socket.connect(socket.local_addr().unwrap()).await.is_ok() {
-            return Ok(NatType::NoNat);
+    /// Carry one local connection to the peer agent
+    async fn forward(self: Arc<Self>, peer: Uuid, mut local: TcpStream) {
+        match self.connect(peer).await {
+            Ok((request_id, mut stream)) => self.pump(request_id, &mut local, &mut stream).await,
+            Err(e) => warn!("P2P connection to agent {} failed: {}", peer, e),
         }
-
-        // Simplified: assume symmetric NAT
-        Ok(NatType::Symmetric)
     }
 
-    /// Request a P2P connection with another agent
-    pub async fn request_connection(
-        &self,
-        target_agent_id: Uuid,
-    ) -> Result<Uuid, String> {
+    /// Set up a session with `peer` as the initiator and return its stream
+    pub async fn connect(self: &Arc<Self>, peer: Uuid) -> Result<(Uuid, DuplexStream), String> {
         let request_id = Uuid::new_v4();
+        let (answer_tx, answer_rx) = oneshot::channel();
+        let (remote_tx, mut remote_rx) = mpsc::unbounded_channel();
+        self.sessions.write().await.insert(
+            request_id,
+            Session {
+                peer_agent_id: peer,
+                state: P2pState::Connecting,
+                answer: Some(answer_tx),
+                remote: remote_tx,
+            },
+        );
 
         info!(
             "Requesting P2P connection with agent {} (request_id: {})",
-            target_agent_id, request_id
+            peer, request_id
         );
+        self.signal(P2pMessage::P2pConnectRequest {
+            request_id,
+            target_agent_id: peer,
+        });
 
-        let connection = P2pConnection {
-            id: request_id,
-            peer_agent_id: target_agent_id,
-            state: P2pState::Connecting,
-            direct_socket: None,
-            created_at: std::time::Instant::now(),
-        };
-
-        let mut connections = self.connections.write().await;
-        connections.insert(request_id, connection);
-
-        Ok(request_id)
+        match self.offer(request_id, answer_rx, &mut remote_rx).await {
+            Ok(stream) => Ok((request_id, stream)),
+            Err(e) => {
+                self.fail(request_id, &e).await;
+                Err(e)
+            }
+        }
     }
 
-    /// Handle incoming P2P offer
-    pub async fn handle_offer(
+    /// Initiator: send the offer and our candidates, then check the pairs as
+    /// the controlling agent
+    async fn offer(
         &self,
         request_id: Uuid,
-        source_agent_id: Uuid,
-        sdp_offer: String,
-    ) -> Result<(), String> {
-        debug!(
-            "Received P2P offer from {} for request {}",
-            source_agent_id, request_id
-        );
+        answer_rx: oneshot::Receiver<Result<String, String>>,
+        remote_rx: &mut mpsc::UnboundedReceiver<Remote>,
+    ) -> Result<DuplexStream, String> {
+        let socket = UdpSocket::bind("0.0.0.0:0")
+            .await
+            .map_err(|e| format!("Failed to bind UDP socket: {}", e))?;
+        let gathered =
+            ice::gather(&socket, self.stun_server.as_deref(), self.turn.as_ref()).await?;
+        let credentials = Credentials::generate();
+        let key_pair = KeyPair::generate()?;
+        let offer = SessionDescription {
+            credentials: credentials.clone(),
+            key: key_pair.public_key().to_vec(),
+        };
 
-        let mut connections = self.connections.write().await;
-
-        if let Some(conn) = connections.get_mut(&request_id) {
-            conn.state = P2pState::ReceivedOffer;
-            conn.peer_agent_id = source_agent_id;
-            debug!("P2P connection {} state updated to ReceivedOffer", request_id);
-        } else {
-            // New incoming connection
-            let connection = P2pConnection {
-                id: request_id,
-                peer_agent_id: source_agent_id,
-                state: P2pState::ReceivedOffer,
-                direct_socket: None,
-                created_at: std::time::Instant::now(),
-            };
-            connections.insert(request_id, connection);
-        }
+        self.set_state(request_id, P2pState::SentOffer).await;
+        // The Hub fills in the source agent
+        self.signal(P2pMessage::P2pConnectOffer {
+            request_id,
+            source_agent_id: Uuid::nil(),
+            sdp_offer: offer.encode(),
+        });
+        self.trickle(request_id, &gathered.candidates);
+
+        let answer = match tokio::time::timeout(ANSWER_TIMEOUT, answer_rx).await {
+            Ok(Ok(answer)) => SessionDescription::parse(&answer?)?,
+            Ok(Err(_)) => return Err("Session closed before the answer".to_string()),
+            Err(_) => return Err("Timed out waiting for the answer".to_string()),
+        };
+        let agent = Agent::new(credentials, answer.credentials, true);
+        let mut link = Link::new(socket, gathered.relay);
+        let path = match agent
+            .establish(&mut link, &gathered.candidates, remote_rx)
+            .await
+        {
+            Ok(path) => path,
+            Err(e) => return Err(self.no_path(e).await),
+        };
+        let keys = key_pair.agree(&answer.key, request_id, true)?;
 
-        Ok(())
+        self.signal(P2pMessage::P2pConnected { request_id });
+        self.set_state(request_id, P2pState::Connected).await;
+        Ok(p2p_stream::spawn(link, path, agent, keys))
     }
 
-    /// Handle P2P answer
-    pub async fn handle_answer(
-        &self,
+    /// Answer an offer and carry its stream to the `expose` target
+    async fn answer(
+        self: Arc<Self>,
         request_id: Uuid,
-        _sdp_answer: String,
-    ) -> Result<(), String> {
-        debug!("Received P2P answer for request {}", request_id);
-
-        let mut connections = self.connections.write().await;
-
-        if let Some(conn) = connections.get_mut(&request_id) {
-            conn.state = P2pState::Connected;
-            info!("P2P connection {} established!", request_id);
-            Ok(())
-        } else {
-            Err(format!("Unknown P2P request: {}", request_id))
+        peer: Uuid,
+        sdp_offer: String,
+        expose: String,
+        mut remote_rx: mpsc::UnboundedReceiver<Remote>,
+    ) {
+        let mut stream = match self
+            .accept(request_id, peer, &sdp_offer, &mut remote_rx)
+            .await
+        {
+            Ok(stream) => stream,
+            Err(e) => return self.fail(request_id, &e).await,
+        };
+        match TcpStream::connect(&expose).await {
+            Ok(mut target) => self.pump(request_id, &mut target, &mut stream).await,
+            Err(e) => {
+                warn!("Failed to connect to P2P target {}: {}", expose, e);
+                self.sessions.write().await.remove(&request_id);
+            }
         }
     }
 
-    /// Handle ICE candidate
-    pub async fn handle_ice_candidate(
+    /// Answerer: send the answer and our candidates, then check the pairs as
+    /// the controlled agent
+    async fn accept(
         &self,
         request_id: Uuid,
-        candidate: String,
-    ) -> Result<(), String> {
-        debug!(
-            "Received ICE candidate for request {}: {}",
-            request_id, candidate
-        );
+        peer: Uuid,
+        sdp_offer: &str,
+        remote_rx: &mut mpsc::UnboundedReceiver<Remote>,
+    ) -> Result<DuplexStream, String> {
+        let offer = SessionDescription::parse(sdp_offer)?;
+        let socket = UdpSocket::bind("0.0.0.0:0")
+            .await
+            .map_err(|e| format!("Failed to bind UDP socket: {}", e))?;
+        let gathered =
+            ice::gather(&socket, self.stun_server.as_deref(), self.turn.as_ref()).await?;
+        let credentials = Credentials::generate();
+        let key_pair = KeyPair::generate()?;
+        let answer = SessionDescription {
+            credentials: credentials.clone(),
+            key: key_pair.public_key().to_vec(),
+        };
 
-        // In a full implementation, try to connect using the candidate
-        let mut connections = self.connections.write().await;
+        self.signal(P2pMessage::P2pAnswer {
+            request_id,
+            target_agent_id: peer,
+            sdp_answer: answer.encode(),
+        });
+        self.trickle(request_id, &gathered.candidates);
+
+        let keys = key_pair.agree(&offer.key, request_id, false)?;
+        let agent = Agent::new(credentials, offer.credentials, false);
+        let mut link = Link::new(socket, gathered.relay);
+        let path = match agent
+            .establish(&mut link, &gathered.candidates, remote_rx)
+            .await
+        {
+            Ok(path) => path,
+            Err(e) => return Err(self.no_path(e).await),
+        };
+
+        self.set_state(request_id, P2pState::Connected).await;
+        Ok(p2p_stream::spawn(link, path, agent, keys))
+    }
 
-        if let Some(conn) = connections.get_mut(&request_id) {
-            // Try to establish direct connection using candidate
-            if let Err(e) = self.try_direct_connection(request_id, &candidate).await {
+    /// Copy between a local connection and the stream of a session until
+    /// either side closes, then forget the session
+    async fn pump(&self, request_id: Uuid, local: &mut TcpStream, stream: &mut DuplexStream) {
+        let mut shutdown = self.shutdown_tx.subscribe();
+        tokio::select! {
+            result = copy_bidirectional(local, stream) => match result {
+                Ok((sent, received)) => info!(
+                    "P2P connection {} closed: {} bytes sent, {} bytes received",
+                    request_id, sent, received
+                ),
+                Err(e) => debug!("P2P connection {} closed: {}", request_id, e),
+            },
+            _ = shutdown.recv() => {}
+        }
+        self.sessions.write().await.remove(&request_id);
+    }
+
+    /// Handle a signaling message relayed by the Hub
+    pub async fn handle_message(self: &Arc<Self>, msg: P2pMessage) {
+        match msg {
+            P2pMessage::P2pConnectOffer {
+                request_id,
+                source_agent_id,
+                sdp_offer,
+            } => {
+                debug!(
+                    "Received P2P offer from {} for request {}",
+                    source_agent_id, request_id
+                );
+                let expose = match self.accepts(source_agent_id) {
+                    Ok(expose) => expose,
+                    Err(reason) => {
+                        warn!(
+                            "Refusing P2P connection {} from agent {}: {}",
+                            request_id, source_agent_id, reason
+                        );
+                        self.signal(P2pMessage::P2pFailed { request_id, reason });
+                        return;
+                    }
+                };
+                let (remote_tx, remote_rx) = mpsc::unbounded_channel();
+                self.sessions.write().await.insert(
+                    request_id,
+                    Session {
+                        peer_agent_id: source_agent_id,
+                        state: P2pState::ReceivedOffer,
+                        answer: None,
+                        remote: remote_tx,
+                    },
+                );
+                let manager = self.clone();
+                tokio::spawn(manager.answer(
+                    request_id,
+                    source_agent_id,
+                    sdp_offer,
+                    expose,
+                    remote_rx,
+                ));
+            }
+            P2pMessage::P2pAnswer {
+                request_id,
+                sdp_answer,
+                ..
+            } => {
+                debug!("Received P2P answer for request {}", request_id);
+                let mut sessions = self.sessions.write().await;
+                match sessions.get_mut(&request_id) {
+                    Some(session) if session.state == P2pState::SentOffer => {
+                        if let Some(answer) = session.answer.take() {
+                            let _ = answer.send(Ok(sdp_answer));
+                        }
+                    }
+                    Some(session) => debug!(
+                        "Ignoring P2P answer for {} in state {:?}",
+                        request_id, session.state
+                    ),
+                    None => debug!("Ignoring P2P answer for unknown request {}", request_id),
+                }
+            }
+            P2pMessage::P2pIceCandidate {
+                request_id,
+                candidate,
+                ..
+            } => {
+                debug!(
+                    "Received ICE candidate for request {}: {}",
+                    request_id, candidate
+                );
+                // An empty candidate ends the peer's candidates
+                let remote = match candidate.trim() {
+                    "" => Remote::End,
+                    candidate => match candidate.parse::<Candidate>() {
+                        Ok(candidate) => Remote::Candidate(candidate),
+                        Err(e) => {
+                            debug!("{}", e);
+                            return;
+                        }
+                    },
+                };
+                match self.sessions.read().await.get(&request_id) {
+                    Some(session) => {
+                        let _ = session.remote.send(remote);
+                    }
+                    None => debug!("Ignoring ICE candidate for unknown request {}", request_id),
+                }
+            }
+            P2pMessage::P2pConnected { request_id } => {
+                info!("P2P connection {} established", request_id);
+            }
+            P2pMessage::P2pFailed { request_id, reason } => {
+                warn!(
+                    "P2P connection {} failed on the peer: {}",
+                    request_id, reason
+                );
+                let mut sessions = self.sessions.write().await;
+                // A running stream no longer depends on the Hub or the signaling
+                if sessions.get(&request_id).map(|session| &session.state)
+                    == Some(&P2pState::Connected)
+                {
+                    return;
+                }
+                if let Some(mut session) = sessions.remove(&request_id) {
+                    match session.answer.take() {
+                        Some(answer) => {
+                            let _ = answer.send(Err(reason));
+                        }
+                        None => {
+                            let _ = session.remote.send(Remote::Failed(reason));
+                        }
+                    }
+                }
+            }
+            P2pMessage::P2pConnectRequest { request_id, .. } => {
                 debug!(
-                    "Failed to connect using ICE candidate for {}: {}",
-                    request_id, e
+                    "Ignoring P2P connect request {}, the Hub handles it",
+                    request_id
                 );
             }
-            Ok(())
-        } else {
-            Err(format!("Unknown P2P request: {}", request_id))
         }
     }
 
-    /// Try to establish direct connection using ICE candidate
-    async fn try_direct_connection(
-        &self,
-        request_id: Uuid,
-        candidate: &str,
-    ) -> Result<(), String> {
-        // Parse candidate (simplified: "IP:PORT" format)
-        // Full ICE candidates are more complex (priority, foundation, component, etc.)
-        let parts: Vec<&str> = candidate.split(':').collect();
-        if parts.len() != 2 {
-            return Err("Invalid candidate format".to_string());
+    /// The `expose` target when `peer` may connect to it
+    fn accepts(&self, peer: Uuid) -> Result<String, String> {
+        let expose = self
+            .expose
+            .clone()
+            .ok_or_else(|| "This agent does not accept P2P connections".to_string())?;
+        if !self.allowed_peers.contains(&peer) {
+            return Err(format!("Agent {} is not in allowed_peers", peer));
         }
+        Ok(expose)
+    }
 
-        let ip = parts[0].to_string();
-        let port: u16 = parts[1]
-            .parse()
-            .map_err(|_| "Invalid port in candidate")?;
+    /// Send our candidates to the peer, an empty candidate ends them
+    fn trickle(&self, request_id: Uuid, candidates: &[Candidate]) {
+        let candidates = candidates.iter().map(ToString::to_string);
+        for candidate in candidates.chain([String::new()]) {
+            self.signal(P2pMessage::P2pIceCandidate {
+                request_id,
+                candidate,
+                sdp_mid: Some("0".to_string()),
+                sdp_m_line_index: Some(0),
+            });
+        }
+    }
 
-        info!(
-            "Attempting direct connection to {}:{} for P2P {}",
-            ip, port, request_id
-        );
+    fn signal(&self, msg: P2pMessage) {
+        if self.signals.send(msg).is_err() {
+            debug!("Agent client is gone, dropping P2P signaling message");
+        }
+    }
 
-        let addr = format!("{}:{}", ip, port);
-        match TcpStream::connect(&addr).await {
-            Ok(stream) => {
-                info!("Direct connection established for P2P {}", request_id);
-                let mut connections = self.connections.write().await;
-                if let Some(conn) = connections.get_mut(&request_id) {
-                    conn.direct_socket = Some(stream);
-                    conn.state = P2pState::Connected;
-                }
-                Ok(())
-            }
-            Err(e) => Err(format!("Connection failed: {}", e)),
+    async fn set_state(&self, request_id: Uuid, state: P2pState) {
+        if let Some(session) = self.sessions.write().await.get_mut(&request_id) {
+            session.state = state;
         }
     }
 
-    /// Notify connection failure
-    pub async fn notify_failed(&self, request_id: Uuid, reason: String) {
-        error!("P2P connection {} failed: {}", request_id, reason);
-        let mut connections = self.connections.write().await;
-        if let Some(conn) = connections.get_mut(&request_id) {
-            conn.state = P2pState::Failed(reason.clone());
+    /// Forget a session that failed here and tell the peer
+    async fn fail(&self, request_id: Uuid, reason: &str) {
+        if self.sessions.write().await.remove(&request_id).is_some() {
+            error!("P2P connection {} failed: {}", request_id, reason);
+            self.signal(P2pMessage::P2pFailed {
+                request_id,
+                reason: reason.to_string(),
+            });
         }
     }
 
-    /// Get connection state
-    pub async fn get_connection_state(&self, request_id: Uuid) -> Option<P2pState> {
-        let connections = self.connections.read().await;
-        connections.get(&request_id).map(|c| c.state.clone())
+    /// Explain failed connectivity checks
+    async fn no_path(&self, reason: String) -> String {
+        match self.get_nat_type().await {
+            Some(nat) if self.turn.is_none() => {
+                format!("{} (NAT type {:?}, no TURN server configured)", reason, nat)
+            }
+            _ => reason,
+        }
     }
 
     /// Get external address
@@ -370,41 +650,36 @@ impl P2pManager {
         self.nat_type.read().await.clone()
     }
 
-    /// Close a P2P connection
-    pub async fn close_connection(&self, request_id: Uuid) -> Result<(), String> {
-        let mut connections = self.connections.write().await;
-        if let Some(conn) = connections.remove(&request_id) {
-            if let Some(stream) = conn.direct_socket {
-                drop(stream);
-            }
-            info!("P2P connection {} closed", request_id);
-            Ok(())
-        } else {
-            Err(format!("Connection {} not found", request_id))
-        }
-    }
-
-    /// Close all connections
+    /// Close all connections and listeners
     pub async fn close_all(&self) {
-        let mut connections = self.connections.write().await;
-        for (id, conn) in connections.drain() {
-            if let Some(stream) = conn.direct_socket {
-                drop(stream);
-            }
-            info!("P2P connection {} closed", id);
+        let _ = self.shutdown_tx.send(());
+        let mut sessions = self.sessions.write().await;
+        for (id, session) in sessions.drain() {
+            info!(
+                "P2P connection {} with agent {} closed ({:?})",
+                id, session.peer_agent_id, session.state
+            );
         }
     }
-
-    /// Get active connection count
-    pub async fn connection_count(&self) -> usize {
-        let connections = self.connections.read().await;
-        connections.len()
-    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    fn config() -> AgentConfig {
+        AgentConfig::new(
+            "localhost:8080".to_string(),
+            "agent".to_string(),
+            "".to_string(),
+        )
+    }
+
+    fn manager(config: &AgentConfig) -> (Arc<P2pManager>, mpsc::UnboundedReceiver<P2pMessage>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        (Arc::new(P2pManager::new(config, tx)), rx)
+    }
 
     #[test]
     fn test_p2p_message_serialization() {
@@ -417,9 +692,197 @@ mod tests {
         assert!(json.contains("\"type\":\"P2pConnectRequest\""));
     }
 
+    #[test]
+    fn test_session_description() {
+        let sdp = SessionDescription {
+            credentials: Credentials::generate(),
+            key: vec![7; 32],
+        };
+        assert_eq!(SessionDescription::parse(&sdp.encode()), Ok(sdp));
+        assert!(SessionDescription::parse("v=0\r\na=ice-ufrag:abcd\r\n").is_err());
+    }
+
     #[tokio::test]
     async fn test_p2p_manager() {
-        let manager = P2pManager::new(0);
-        assert_eq!(manager.connection_count().await, 0);
+        let (manager, _signals) = manager(&config());
+        assert!(manager.sessions.read().await.is_empty());
+        manager.initialize().await.unwrap();
+        assert!(manager.get_nat_type().await.is_none());
+    }
+
+    #[test]
+    fn test_nat_type_from_discovery() {
+        use NatBehavior::*;
+
+        let local: std::net::SocketAddr = "192.168.1.10:4000".parse().unwrap();
+        let public: std::net::SocketAddr = "203.0.113.7:62000".parse().unwrap();
+        for (mapped, mapping, filtering, nat) in [
+            (
+                local,
+                EndpointIndependent,
+                EndpointIndependent,
+                NatType::NoNat,
+            ),
+            (
+                local,
+                EndpointIndependent,
+                AddressDependent,
+                NatType::RestrictedCone,
+            ),
+            (
+                public,
+                EndpointIndependent,
+                EndpointIndependent,
+                NatType::FullCone,
+            ),
+            (
+                public,
+                EndpointIndependent,
+                AddressDependent,
+                NatType::RestrictedCone,
+            ),
+            (
+                public,
+                EndpointIndependent,
+                AddressAndPortDependent,
+                NatType::PortRestrictedCone,
+            ),
+            (
+                public,
+                AddressDependent,
+                EndpointIndependent,
+                NatType::Symmetric,
+            ),
+            (
+                public,
+                AddressAndPortDependent,
+                AddressAndPortDependent,
+                NatType::Symmetric,
+            ),
+        ] {
+            let discovery = NatDiscovery {
+                local,
+                mapped,
+                mapping: Some(mapping),
+                filtering: Some(filtering),
+            };
+            assert_eq!(NatType::from_discovery(&discovery), nat, "{:?}", discovery);
+        }
+
+        let discovery = NatDiscovery {
+            local,
+            mapped: public,
+            mapping: None,
+            filtering: None,
+        };
+        assert_eq!(NatType::from_discovery(&discovery), NatType::Unknown);
+    }
+
+    #[tokio::test]
+    async fn test_initialize_with_domain_stun() {
+        let server = stun::start_domain_stun(true).await;
+        let mut config = config();
+        config.p2p_stun_server = Some(server.to_string());
+        let (manager, _signals) = manager(&config);
+        manager.initialize().await.unwrap();
+
+        assert_eq!(manager.get_nat_type().await, Some(NatType::NoNat));
+        let addr = manager.get_external_address().await.unwrap();
+        assert_eq!(addr.ip, "127.0.0.1");
+        assert_ne!(addr.port, 0);
+    }
+
+    /// Without an alternate address only the NAT type stays unknown
+    #[tokio::test]
+    async fn test_initialize_without_alternate_address() {
+        let server = stun::start_domain_stun(false).await;
+        let mut config = config();
+        config.p2p_stun_server = Some(server.to_string());
+        let (manager, _signals) = manager(&config);
+        manager.initialize().await.unwrap();
+
+        assert_eq!(manager.get_nat_type().await, Some(NatType::Unknown));
+        let addr = manager.get_external_address().await.unwrap();
+        assert_eq!(addr.ip, "127.0.0.1");
+        assert_ne!(addr.port, 0);
+    }
+
+    /// Relay the signaling of one agent to the other like the Hub does
+    fn relay(from: Uuid, mut signals: mpsc::UnboundedReceiver<P2pMessage>, to: Arc<P2pManager>) {
+        tokio::spawn(async move {
+            while let Some(msg) = signals.recv().await {
+                let msg = match msg {
+                    P2pMessage::P2pConnectRequest { .. } => continue,
+                    P2pMessage::P2pConnectOffer {
+                        request_id,
+                        sdp_offer,
+                        ..
+                    } => P2pMessage::P2pConnectOffer {
+                        request_id,
+                        source_agent_id: from,
+                        sdp_offer,
+                    },
+                    msg => msg,
+                };
+                to.handle_message(msg).await;
+            }
+        });
+    }
+
+    /// Two agents with signaling between them, `b` exposes `expose` to `a`
+    async fn agents(expose: &str, allowed: bool) -> (Arc<P2pManager>, Uuid, Arc<P2pManager>) {
+        let stun = stun::start_domain_stun(false).await;
+        let (a_id, b_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut a_config = config();
+        a_config.p2p_stun_server = Some(stun.to_string());
+        let mut b_config = a_config.clone();
+        b_config.p2p_expose = Some(expose.to_string());
+        if allowed {
+            b_config.p2p_allowed_peers = vec![a_id];
+        }
+        let (a, a_signals) = manager(&a_config);
+        let (b, b_signals) = manager(&b_config);
+        relay(a_id, a_signals, b.clone());
+        relay(b_id, b_signals, a.clone());
+        (a, b_id, b)
+    }
+
+    #[tokio::test]
+    async fn test_connect_to_exposed_service() {
+        let echo = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let expose = echo.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            let (mut stream, _) = echo.accept().await.unwrap();
+            let (mut reader, mut writer) = stream.split();
+            tokio::io::copy(&mut reader, &mut writer).await.unwrap();
+        });
+
+        let (a, b_id, b) = agents(&expose, true).await;
+        let (request_id, mut stream) = a.connect(b_id).await.unwrap();
+        assert_eq!(
+            a.sessions
+                .read()
+                .await
+                .get(&request_id)
+                .map(|s| s.state.clone()),
+            Some(P2pState::Connected)
+        );
+
+        stream.write_all(b"hello over p2p").await.unwrap();
+        let mut buf = [0u8; 14];
+        stream.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"hello over p2p");
+
+        b.close_all().await;
+        assert!(b.sessions.read().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_connect_refused_by_peer() {
+        let (a, b_id, b) = agents("127.0.0.1:9", false).await;
+        let err = a.connect(b_id).await.unwrap_err();
+        assert!(err.contains("allowed_peers"), "{}", err);
+        assert!(a.sessions.read().await.is_empty());
+        assert!(b.sessions.read().await.is_empty());
     }
 }
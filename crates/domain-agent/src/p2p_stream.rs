@@ -0,0 +1,621 @@
+//! Reliable, encrypted byte stream over the path selected by ICE
+//!
+//! The agents agree on keys with X25519. The public keys travel in the offer
+//! and answer, which the Hub relays between the authenticated agent
+//! connections. HKDF-SHA256 derives one ChaCha20-Poly1305 key per direction,
+//! and every datagram is sealed with its packet number as the nonce. Inside,
+//! the byte stream is cut into numbered segments that are retransmitted
+//! until the peer acknowledges them; a FIN segment ends a direction. The
+//! local end of the stream is a `DuplexStream`.
+
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305};
+use ring::agreement::{self, EphemeralPrivateKey, UnparsedPublicKey, X25519};
+use ring::hkdf::{Salt, HKDF_SHA256};
+use ring::rand::SystemRandom;
+use std::collections::{BTreeMap, VecDeque};
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt, DuplexStream};
+use tokio::time::{interval, interval_at, Instant};
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::ice::{Agent, Base, Link, Path, Received, Transport};
+use crate::stun::{self, Message};
+
+/// First byte of every stream datagram; STUN starts with 0 or 1
+const PACKET_MARKER: u8 = 0x80;
+/// Marker and packet number
+const PACKET_HEADER: usize = 9;
+/// Kind, acknowledgement and sequence number
+const FRAME_HEADER: usize = 17;
+const TAG_LEN: usize = 16;
+
+const DATA: u8 = 0;
+const ACK: u8 = 1;
+const FIN: u8 = 2;
+
+/// Payload of a segment, small enough for any path MTU with TURN overhead
+const SEGMENT_SIZE: usize = 1200;
+/// Segments in flight, and segments buffered by the receiver
+const WINDOW: usize = 128;
+const INITIAL_RTO: Duration = Duration::from_millis(500);
+const MIN_RTO: Duration = Duration::from_millis(200);
+const MAX_RTO: Duration = Duration::from_secs(5);
+/// Retransmissions of a segment before the stream is given up
+const MAX_RETRANSMITS: u32 = 12;
+/// An ACK is sent when nothing else was sent for this long
+const KEEPALIVE: Duration = Duration::from_secs(5);
+/// The stream is given up when nothing arrives for this long
+const IDLE_TIMEOUT: Duration = Duration::from_secs(30);
+/// TURN permissions last 5 minutes, allocations 10
+const TURN_REFRESH: Duration = Duration::from_secs(240);
+/// Time the closed stream keeps acknowledging a retransmitted FIN
+const LINGER: Duration = Duration::from_secs(2);
+const TICK: Duration = Duration::from_millis(50);
+/// Buffer of the local `DuplexStream`
+const BUFFER_SIZE: usize = 256 * 1024;
+
+/// One side's X25519 key for a session
+pub struct KeyPair {
+    private: EphemeralPrivateKey,
+    public: Vec<u8>,
+}
+
+impl KeyPair {
+    pub fn generate() -> Result<Self, String> {
+        let private = EphemeralPrivateKey::generate(&X25519, &SystemRandom::new())
+            .map_err(|_| "Failed to generate X25519 key".to_string())?;
+        let public = private
+            .compute_public_key()
+            .map_err(|_| "Failed to compute X25519 public key".to_string())?
+            .as_ref()
+            .to_vec();
+        Ok(Self { private, public })
+    }
+
+    pub fn public_key(&self) -> &[u8] {
+        &self.public
+    }
+
+    /// Derive the stream keys of session `request_id` from the peer's public
+    /// key; the `initiator` sent the offer
+    pub fn agree(
+        self,
+        peer_public: &[u8],
+        request_id: Uuid,
+        initiator: bool,
+    ) -> Result<Keys, String> {
+        let peer_public = UnparsedPublicKey::new(&X25519, peer_public);
+        agreement::agree_ephemeral(self.private, &peer_public, |shared| {
+            let prk = Salt::new(HKDF_SHA256, request_id.as_bytes()).extract(shared);
+            let key = |label: &[u8]| -> Result<LessSafeKey, ring::error::Unspecified> {
+                let labels = [label];
+                let okm = prk.expand(&labels, &CHACHA20_POLY1305)?;
+                Ok(LessSafeKey::new(UnboundKey::from(okm)))
+            };
+            let initiator_key = key(b"domain-agent p2p initiator")?;
+            let responder_key = key(b"domain-agent p2p responder")?;
+            Ok(match initiator {
+                true => Keys {
+                    seal: initiator_key,
+                    open: responder_key,
+                },
+                false => Keys {
+                    seal: responder_key,
+                    open: initiator_key,
+                },
+            })
+        })
+        .and_then(|keys| keys)
+        .map_err(|_| "X25519 key agreement failed".to_string())
+    }
+}
+
+/// Keys of the two directions of a stream
+pub struct Keys {
+    seal: LessSafeKey,
+    open: LessSafeKey,
+}
+
+impl Keys {
+    /// Encrypt `frame` into datagram number `packet_number`
+    fn seal(&self, packet_number: u64, frame: &[u8]) -> Option<Vec<u8>> {
+        let mut packet = Vec::with_capacity(PACKET_HEADER + frame.len() + TAG_LEN);
+        packet.push(PACKET_MARKER);
+        packet.extend_from_slice(&packet_number.to_be_bytes());
+        let mut body = frame.to_vec();
+        self.seal
+            .seal_in_place_append_tag(nonce(packet_number), Aad::from(&packet[..]), &mut body)
+            .ok()?;
+        packet.extend_from_slice(&body);
+        Some(packet)
+    }
+
+    /// Decrypt a datagram, None if it was not sealed by the peer
+    fn open(&self, packet: &[u8]) -> Option<Vec<u8>> {
+        if packet.len() < PACKET_HEADER + TAG_LEN || packet[0] != PACKET_MARKER {
+            return None;
+        }
+        let packet_number = u64::from_be_bytes(packet[1..PACKET_HEADER].try_into().ok()?);
+        let mut body = packet[PACKET_HEADER..].to_vec();
+        let len = self
+            .open
+            .open_in_place(
+                nonce(packet_number),
+                Aad::from(&packet[..PACKET_HEADER]),
+                &mut body,
+            )
+            .ok()?
+            .len();
+        body.truncate(len);
+        Some(body)
+    }
+}
+
+/// Each direction has its own key, so the packet number is a unique nonce
+fn nonce(packet_number: u64) -> Nonce {
+    let mut nonce = [0u8; 12];
+    nonce[4..].copy_from_slice(&packet_number.to_be_bytes());
+    Nonce::assume_unique_for_key(nonce)
+}
+
+/// Plaintext of a datagram
+#[derive(Debug, PartialEq, Eq)]
+struct Frame {
+    kind: u8,
+    /// Next sequence number the sender expects (cumulative acknowledgement)
+    ack: u64,
+    seq: u64,
+    payload: Vec<u8>,
+}
+
+impl Frame {
+    fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(FRAME_HEADER + self.payload.len());
+        buf.push(self.kind);
+        buf.extend_from_slice(&self.ack.to_be_bytes());
+        buf.extend_from_slice(&self.seq.to_be_bytes());
+        buf.extend_from_slice(&self.payload);
+        buf
+    }
+
+    fn decode(buf: &[u8]) -> Option<Self> {
+        if buf.len() < FRAME_HEADER {
+            return None;
+        }
+        Some(Self {
+            kind: buf[0],
+            ack: u64::from_be_bytes(buf[1..9].try_into().ok()?),
+            seq: u64::from_be_bytes(buf[9..17].try_into().ok()?),
+            payload: buf[FRAME_HEADER..].to_vec(),
+        })
+    }
+}
+
+/// A segment waiting for its acknowledgement
+struct Segment {
+    kind: u8,
+    payload: Vec<u8>,
+    sent_at: Instant,
+    deadline: Instant,
+    retransmits: u32,
+}
+
+/// Start the stream on `path` and return its local end
+///
+/// The stream keeps answering the peer's connectivity checks with `agent`.
+pub fn spawn<T: Transport>(link: Link<T>, path: Path, agent: Agent, keys: Keys) -> DuplexStream {
+    let (stream, local) = tokio::io::duplex(BUFFER_SIZE);
+    let now = Instant::now();
+    let mut connection = Connection {
+        link,
+        path,
+        agent,
+        keys,
+        next_packet: 0,
+        next_seq: 0,
+        unacked: BTreeMap::new(),
+        srtt: None,
+        rttvar: Duration::ZERO,
+        rto: INITIAL_RTO,
+        expected: 0,
+        received: BTreeMap::new(),
+        deliver: VecDeque::new(),
+        fin_received: false,
+        last_received: now,
+        last_sent: now,
+    };
+    tokio::spawn(async move {
+        match connection.run(local).await {
+            Ok(()) => debug!("P2P stream to {} closed", path.remote),
+            Err(e) => warn!("P2P stream to {} failed: {}", path.remote, e),
+        }
+    });
+    stream
+}
+
+struct Connection<T> {
+    link: Link<T>,
+    path: Path,
+    agent: Agent,
+    keys: Keys,
+    next_packet: u64,
+    // Sending
+    next_seq: u64,
+    unacked: BTreeMap<u64, Segment>,
+    srtt: Option<Duration>,
+    rttvar: Duration,
+    rto: Duration,
+    // Receiving
+    expected: u64,
+    received: BTreeMap<u64, (u8, Vec<u8>)>,
+    deliver: VecDeque<Vec<u8>>,
+    fin_received: bool,
+    last_received: Instant,
+    last_sent: Instant,
+}
+
+impl<T: Transport> Connection<T> {
+    async fn run(&mut self, local: DuplexStream) -> Result<(), String> {
+        let (mut reader, mut writer) = tokio::io::split(local);
+        let mut read_buf = vec![0u8; SEGMENT_SIZE];
+        let mut recv_buf = vec![0u8; 2048];
+        let mut ticker = interval(TICK);
+        let mut refresh = interval_at(Instant::now() + TURN_REFRESH, TURN_REFRESH);
+        // Local reads until EOF, local writes until the peer's FIN or until
+        // the local end is dropped
+        let mut reading = true;
+        let mut writing = true;
+        let mut written = 0;
+        let mut closed_at = None;
+        info!(
+            "P2P stream to {} via {:?}",
+            self.path.remote, self.path.base
+        );
+
+        loop {
+            if writing && self.fin_received && self.deliver.is_empty() {
+                writer.shutdown().await.ok();
+                writing = false;
+            }
+            let can_read = reading && self.unacked.len() < WINDOW;
+            let pending = self
+                .deliver
+                .front()
+                .and_then(|data| data.get(written..))
+                .unwrap_or_default();
+
+            tokio::select! {
+                read = reader.read(&mut read_buf), if can_read => match read {
+                    Ok(0) | Err(_) => {
+                        reading = false;
+                        self.queue(FIN, Vec::new()).await;
+                    }
+                    Ok(n) => self.queue(DATA, read_buf[..n].to_vec()).await,
+                },
+                result = writer.write(pending), if writing && !pending.is_empty() => match result {
+                    Ok(n) => {
+                        written += n;
+                        if written == self.deliver.front().map_or(0, Vec::len) {
+                            self.deliver.pop_front();
+                            written = 0;
+                        }
+                    }
+                    Err(_) => {
+                        // The local end is gone, the peer's data is dropped
+                        writing = false;
+                        self.deliver.clear();
+                    }
+                },
+                received = self.link.recv(&mut recv_buf) => match received {
+                    Ok(Received::Peer { base, from, data }) => self.handle(base, from, &data).await,
+                    Ok(Received::Turn(msg)) => {
+                        if self.link.handle_turn(&msg) {
+                            self.link.refresh(self.path.remote).await;
+                        }
+                    }
+                    Err(e) => debug!("Failed to receive: {}", e),
+                },
+                _ = refresh.tick(), if self.path.base == Base::Relayed => {
+                    self.link.refresh(self.path.remote).await;
+                }
+                _ = ticker.tick() => {
+                    let now = Instant::now();
+                    self.retransmit(now).await?;
+                    if now.duration_since(self.last_received) > IDLE_TIMEOUT {
+                        return Err("peer stopped responding".to_string());
+                    }
+                    if now.duration_since(self.last_sent) > KEEPALIVE {
+                        self.send(ACK, 0, Vec::new()).await;
+                    }
+                    let done = !reading && self.unacked.is_empty() && self.fin_received && self.deliver.is_empty();
+                    if done && now.duration_since(*closed_at.get_or_insert(now)) > LINGER {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Send a new segment and keep it until it is acknowledged
+    async fn queue(&mut self, kind: u8, payload: Vec<u8>) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.send(kind, seq, payload.clone()).await;
+        let now = Instant::now();
+        self.unacked.insert(
+            seq,
+            Segment {
+                kind,
+                payload,
+                sent_at: now,
+                deadline: now + self.rto,
+                retransmits: 0,
+            },
+        );
+    }
+
+    async fn send(&mut self, kind: u8, seq: u64, payload: Vec<u8>) {
+        let frame = Frame {
+            kind,
+            ack: self.expected,
+            seq,
+            payload,
+        };
+        let Some(packet) = self.keys.seal(self.next_packet, &frame.encode()) else {
+            return;
+        };
+        self.next_packet += 1;
+        self.last_sent = Instant::now();
+        if let Err(e) = self
+            .link
+            .send(self.path.base, self.path.remote, &packet)
+            .await
+        {
+            debug!("Failed to send to {}: {}", self.path.remote, e);
+        }
+    }
+
+    /// Resend the segments whose acknowledgement is overdue
+    async fn retransmit(&mut self, now: Instant) -> Result<(), String> {
+        let mut due = Vec::new();
+        for (seq, segment) in self.unacked.iter_mut().filter(|(_, s)| s.deadline <= now) {
+            if segment.retransmits >= MAX_RETRANSMITS {
+                return Err(format!("segment {} was never acknowledged", seq));
+            }
+            segment.retransmits += 1;
+            let backoff = self.rto.saturating_mul(1 << segment.retransmits.min(5));
+            segment.deadline = now + backoff.min(MAX_RTO);
+            due.push((*seq, segment.kind, segment.payload.clone()));
+        }
+        for (seq, kind, payload) in due {
+            self.send(kind, seq, payload).await;
+        }
+        Ok(())
+    }
+
+    /// Handle a datagram from the peer
+    async fn handle(&mut self, base: Base, from: SocketAddr, data: &[u8]) {
+        if stun::is_stun(data) {
+            // The peer may still be retransmitting its checks
+            if let Ok(msg) = Message::decode(data) {
+                if let Some(response) = self.agent.respond(&msg, data, from) {
+                    self.link.send(base, from, &response).await.ok();
+                }
+            }
+            return;
+        }
+        if base != self.path.base || from != self.path.remote {
+            debug!("Ignoring datagram from {} outside the selected path", from);
+            return;
+        }
+        let Some(frame) = self.keys.open(data).as_deref().and_then(Frame::decode) else {
+            debug!("Dropping datagram from {} that fails authentication", from);
+            return;
+        };
+        let now = Instant::now();
+        self.last_received = now;
+        self.acknowledged(frame.ack, now);
+
+        if frame.kind == DATA || frame.kind == FIN {
+            let in_window = frame.seq >= self.expected && frame.seq < self.expected + WINDOW as u64;
+            if in_window && self.deliver.len() < WINDOW {
+                self.received.insert(frame.seq, (frame.kind, frame.payload));
+                while let Some((kind, payload)) = self.received.remove(&self.expected) {
+                    self.expected += 1;
+                    match kind {
+                        FIN => self.fin_received = true,
+                        _ => self.deliver.push_back(payload),
+                    }
+                }
+            }
+            // Duplicates are acknowledged too, the earlier ACK may be lost
+            self.send(ACK, 0, Vec::new()).await;
+        }
+    }
+
+    /// Drop the segments before `ack` and update the retransmission timeout
+    fn acknowledged(&mut self, ack: u64, now: Instant) {
+        let mut sample = None;
+        while let Some(entry) = self.unacked.first_entry() {
+            if *entry.key() >= ack {
+                break;
+            }
+            let segment = entry.remove();
+            // Karn's algorithm: only segments sent once give an RTT sample
+            if segment.retransmits == 0 {
+                sample = Some(now.duration_since(segment.sent_at));
+            }
+        }
+        let Some(rtt) = sample else {
+            return;
+        };
+        // RFC 6298
+        let srtt = match self.srtt {
+            None => {
+                self.rttvar = rtt / 2;
+                rtt
+            }
+            Some(srtt) => {
+                let delta = srtt.abs_diff(rtt);
+                self.rttvar = (self.rttvar * 3 + delta) / 4;
+                (srtt * 7 + rtt) / 8
+            }
+        };
+        self.srtt = Some(srtt);
+        self.rto = (srtt + self.rttvar * 4).clamp(MIN_RTO, MAX_RTO);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ice::Credentials;
+    use std::future::Future;
+    use std::io;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::net::UdpSocket;
+
+    fn keys(request_id: Uuid) -> (Keys, Keys) {
+        let (a, b) = (KeyPair::generate().unwrap(), KeyPair::generate().unwrap());
+        let (a_public, b_public) = (a.public_key().to_vec(), b.public_key().to_vec());
+        (
+            a.agree(&b_public, request_id, true).unwrap(),
+            b.agree(&a_public, request_id, false).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_sealed_frames() {
+        let (a, b) = keys(Uuid::new_v4());
+        let frame = Frame {
+            kind: DATA,
+            ack: 3,
+            seq: 7,
+            payload: b"hello".to_vec(),
+        };
+        let packet = a.seal(42, &frame.encode()).unwrap();
+        assert_eq!(packet[0], PACKET_MARKER);
+        assert!(!stun::is_stun(&packet));
+        assert_eq!(Frame::decode(&b.open(&packet).unwrap()), Some(frame));
+
+        // Tampering, the wrong direction and another session's keys fail
+        let mut tampered = packet.clone();
+        tampered[PACKET_HEADER + 2] ^= 1;
+        assert!(b.open(&tampered).is_none());
+        let mut renumbered = packet.clone();
+        renumbered[8] ^= 1;
+        assert!(b.open(&renumbered).is_none());
+        assert!(a.open(&packet).is_none());
+        let (_, other) = keys(Uuid::new_v4());
+        assert!(other.open(&packet).is_none());
+    }
+
+    /// A UDP socket that drops every `nth` datagram it sends
+    struct Lossy {
+        socket: UdpSocket,
+        nth: usize,
+        sent: AtomicUsize,
+    }
+
+    impl Transport for Lossy {
+        fn local_addr(&self) -> io::Result<SocketAddr> {
+            self.socket.local_addr()
+        }
+
+        fn send_to(
+            &self,
+            buf: &[u8],
+            target: SocketAddr,
+        ) -> impl Future<Output = io::Result<usize>> + Send {
+            let drop = (self.sent.fetch_add(1, Ordering::SeqCst) + 1).is_multiple_of(self.nth);
+            async move {
+                match drop {
+                    true => Ok(buf.len()),
+                    false => self.socket.send_to(buf, target).await,
+                }
+            }
+        }
+
+        fn recv_from(
+            &self,
+            buf: &mut [u8],
+        ) -> impl Future<Output = io::Result<(usize, SocketAddr)>> + Send {
+            self.socket.recv_from(buf)
+        }
+    }
+
+    async fn lossy(nth: usize) -> Lossy {
+        Lossy {
+            socket: UdpSocket::bind("127.0.0.1:0").await.unwrap(),
+            nth,
+            sent: AtomicUsize::new(0),
+        }
+    }
+
+    /// A stream between two local sockets, every `nth` datagram is lost
+    async fn pair(nth: usize) -> (DuplexStream, DuplexStream) {
+        let (a_socket, b_socket) = (lossy(nth).await, lossy(nth).await);
+        let (a_addr, b_addr) = (
+            a_socket.local_addr().unwrap(),
+            b_socket.local_addr().unwrap(),
+        );
+        let (a_creds, b_creds) = (Credentials::generate(), Credentials::generate());
+        let (a_keys, b_keys) = keys(Uuid::new_v4());
+        let a_path = Path {
+            base: Base::Direct,
+            remote: b_addr,
+        };
+        let b_path = Path {
+            base: Base::Direct,
+            remote: a_addr,
+        };
+        (
+            spawn(
+                Link::new(a_socket, None),
+                a_path,
+                Agent::new(a_creds.clone(), b_creds.clone(), true),
+                a_keys,
+            ),
+            spawn(
+                Link::new(b_socket, None),
+                b_path,
+                Agent::new(b_creds, a_creds, false),
+                b_keys,
+            ),
+        )
+    }
+
+    async fn transfer(nth: usize) {
+        let (mut a, mut b) = pair(nth).await;
+        let data: Vec<u8> = (0..300_000u32).map(|i| (i * 7 % 251) as u8).collect();
+        let expected = data.clone();
+        let writer = tokio::spawn(async move {
+            a.write_all(&data).await.unwrap();
+            a.shutdown().await.unwrap();
+            // The reply comes back on the same stream
+            let mut reply = String::new();
+            a.read_to_string(&mut reply).await.unwrap();
+            reply
+        });
+
+        let mut received = Vec::new();
+        b.read_to_end(&mut received).await.unwrap();
+        assert_eq!(received.len(), expected.len());
+        assert!(received == expected);
+        b.write_all(b"done").await.unwrap();
+        b.shutdown().await.unwrap();
+        assert_eq!(writer.await.unwrap(), "done");
+    }
+
+    #[tokio::test]
+    async fn test_stream_transfer() {
+        transfer(usize::MAX).await;
+    }
+
+    #[tokio::test]
+    async fn test_stream_retransmits_lost_datagrams() {
+        transfer(7).await;
+    }
+}
@@ -0,0 +1,706 @@
+//! Minimal STUN client (RFC 5389, RFC 5780)
+//!
+//! Sends a Binding request over UDP and reads the mapped address from the
+//! response, which is the public address as seen by the STUN server
+//! (e.g. `domain-stun`). Against a server with an alternate address it also
+//! runs the NAT behavior discovery tests of RFC 5780. The generic message
+//! codec with MESSAGE-INTEGRITY and FINGERPRINT is shared with ICE and TURN.
+
+use ring::hmac;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+use tokio::net::{lookup_host, UdpSocket};
+use tokio::time::{timeout_at, Instant};
+use tracing::debug;
+
+/// Magic cookie in every RFC 5389 message
+pub const MAGIC_COOKIE: u32 = 0x2112_A442;
+
+pub const BINDING_REQUEST: u16 = 0x0001;
+pub const BINDING_SUCCESS: u16 = 0x0101;
+pub const BINDING_ERROR: u16 = 0x0111;
+const ATTR_MAPPED_ADDRESS: u16 = 0x0001;
+const ATTR_CHANGE_REQUEST: u16 = 0x0003;
+pub const ATTR_USERNAME: u16 = 0x0006;
+pub const ATTR_MESSAGE_INTEGRITY: u16 = 0x0008;
+pub const ATTR_ERROR_CODE: u16 = 0x0009;
+pub const ATTR_XOR_PEER_ADDRESS: u16 = 0x0012;
+pub const ATTR_REALM: u16 = 0x0014;
+pub const ATTR_NONCE: u16 = 0x0015;
+pub const ATTR_XOR_RELAYED_ADDRESS: u16 = 0x0016;
+pub const ATTR_XOR_MAPPED_ADDRESS: u16 = 0x0020;
+const ATTR_OTHER_ADDRESS: u16 = 0x802C;
+const ATTR_FINGERPRINT: u16 = 0x8028;
+const HEADER_LEN: usize = 20;
+
+/// FINGERPRINT is the CRC-32 of the message XORed with this value
+const FINGERPRINT_XOR: u32 = 0x5354_554E;
+
+/// Number of requests sent before giving up
+const ATTEMPTS: u32 = 3;
+/// Time to wait for each response
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// CHANGE-REQUEST flag asking for a response from the other IP
+pub const CHANGE_IP: u8 = 0x04;
+/// CHANGE-REQUEST flag asking for a response from the other port
+pub const CHANGE_PORT: u8 = 0x02;
+
+/// Encode a Binding request, with a CHANGE-REQUEST attribute unless `flags` is 0
+pub fn binding_request(transaction_id: &[u8; 12], flags: u8) -> Vec<u8> {
+    let length: u16 = if flags == 0 { 0 } else { 8 };
+    let mut msg = Vec::with_capacity(HEADER_LEN + length as usize);
+    msg.extend_from_slice(&BINDING_REQUEST.to_be_bytes());
+    msg.extend_from_slice(&length.to_be_bytes());
+    msg.extend_from_slice(&MAGIC_COOKIE.to_be_bytes());
+    msg.extend_from_slice(transaction_id);
+    if flags != 0 {
+        msg.extend_from_slice(&ATTR_CHANGE_REQUEST.to_be_bytes());
+        msg.extend_from_slice(&4u16.to_be_bytes());
+        msg.extend_from_slice(&[0, 0, 0, flags]);
+    }
+    msg
+}
+
+/// Addresses from a Binding success response
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BindingResponse {
+    /// Our address as seen by the server
+    pub mapped: SocketAddr,
+    /// The server's alternate address (RFC 5780 OTHER-ADDRESS)
+    pub other: Option<SocketAddr>,
+}
+
+/// Parse a Binding success response
+///
+/// XOR-MAPPED-ADDRESS is preferred; MAPPED-ADDRESS is accepted for old servers.
+pub fn parse_binding(buf: &[u8], transaction_id: &[u8; 12]) -> Result<BindingResponse, String> {
+    if buf.len() < HEADER_LEN {
+        return Err("STUN response too short".to_string());
+    }
+    let msg_type = u16::from_be_bytes([buf[0], buf[1]]);
+    let length = u16::from_be_bytes([buf[2], buf[3]]) as usize;
+    if u32::from_be_bytes([buf[4], buf[5], buf[6], buf[7]]) != MAGIC_COOKIE {
+        return Err("Invalid STUN magic cookie".to_string());
+    }
+    if &buf[8..HEADER_LEN] != transaction_id {
+        return Err("STUN transaction ID mismatch".to_string());
+    }
+    let body = buf
+        .get(HEADER_LEN..HEADER_LEN + length)
+        .ok_or_else(|| "Truncated STUN response".to_string())?;
+    if msg_type != BINDING_SUCCESS && msg_type != BINDING_ERROR {
+        return Err(format!("Unexpected STUN message type 0x{:04x}", msg_type));
+    }
+
+    let mut mapped = None;
+    let mut xor_mapped = None;
+    let mut other = None;
+    let mut error = None;
+    let mut offset = 0;
+    while offset + 4 <= body.len() {
+        let attr_type = u16::from_be_bytes([body[offset], body[offset + 1]]);
+        let attr_len = u16::from_be_bytes([body[offset + 2], body[offset + 3]]) as usize;
+        let value = body
+            .get(offset + 4..offset + 4 + attr_len)
+            .ok_or_else(|| "Truncated STUN attribute".to_string())?;
+        match attr_type {
+            ATTR_XOR_MAPPED_ADDRESS => {
+                xor_mapped = Some(decode_address(value, Some(transaction_id))?)
+            }
+            ATTR_MAPPED_ADDRESS => mapped = Some(decode_address(value, None)?),
+            ATTR_OTHER_ADDRESS => other = Some(decode_address(value, None)?),
+            ATTR_ERROR_CODE if value.len() >= 4 => {
+                let code = u16::from(value[2] & 0x07) * 100 + u16::from(value[3]);
+                let reason = String::from_utf8_lossy(&value[4..]);
+                error = Some(format!("{} {}", code, reason.trim_end_matches('\0')));
+            }
+            _ => {}
+        }
+        // Attributes are padded to a multiple of 4 bytes
+        offset += 4 + attr_len.div_ceil(4) * 4;
+    }
+    if msg_type == BINDING_ERROR {
+        return Err(format!(
+            "STUN error response: {}",
+            error.unwrap_or_else(|| "no error code".to_string())
+        ));
+    }
+    let mapped = xor_mapped
+        .or(mapped)
+        .ok_or_else(|| "STUN response has no mapped address".to_string())?;
+    Ok(BindingResponse { mapped, other })
+}
+
+/// Decode a (XOR-)MAPPED-ADDRESS value, `transaction_id` is set for the XOR variant
+fn decode_address(value: &[u8], transaction_id: Option<&[u8; 12]>) -> Result<SocketAddr, String> {
+    if value.len() < 4 {
+        return Err("Invalid STUN address attribute".to_string());
+    }
+    let cookie = MAGIC_COOKIE.to_be_bytes();
+    let mut port = u16::from_be_bytes([value[2], value[3]]);
+    let mut addr = value[4..].to_vec();
+    if let Some(transaction_id) = transaction_id {
+        port ^= (MAGIC_COOKIE >> 16) as u16;
+        let key: Vec<u8> = cookie
+            .iter()
+            .chain(transaction_id.iter())
+            .copied()
+            .collect();
+        for (byte, key) in addr.iter_mut().zip(key) {
+            *byte ^= key;
+        }
+    }
+
+    let ip = match (value[1], addr.len()) {
+        (0x01, 4) => IpAddr::V4(Ipv4Addr::new(addr[0], addr[1], addr[2], addr[3])),
+        (0x02, 16) => {
+            let octets: [u8; 16] = addr.try_into().unwrap_or([0; 16]);
+            IpAddr::V6(Ipv6Addr::from(octets))
+        }
+        (family, _) => return Err(format!("Invalid STUN address family 0x{:02x}", family)),
+    };
+    Ok(SocketAddr::new(ip, port))
+}
+
+/// Encode a (XOR-)MAPPED-ADDRESS style value, `transaction_id` is set for the XOR variant
+fn encode_address(addr: SocketAddr, transaction_id: Option<&[u8; 12]>) -> Vec<u8> {
+    let (family, mut ip) = match addr.ip() {
+        IpAddr::V4(ip) => (0x01, ip.octets().to_vec()),
+        IpAddr::V6(ip) => (0x02, ip.octets().to_vec()),
+    };
+    let mut port = addr.port();
+    if let Some(transaction_id) = transaction_id {
+        port ^= (MAGIC_COOKIE >> 16) as u16;
+        let key = MAGIC_COOKIE.to_be_bytes().into_iter().chain(*transaction_id);
+        for (byte, key) in ip.iter_mut().zip(key) {
+            *byte ^= key;
+        }
+    }
+    let mut value = vec![0, family];
+    value.extend_from_slice(&port.to_be_bytes());
+    value.extend_from_slice(&ip);
+    value
+}
+
+/// Address attributes that are XORed with the magic cookie and transaction ID
+fn is_xor_address(attr_type: u16) -> bool {
+    matches!(
+        attr_type,
+        ATTR_XOR_MAPPED_ADDRESS | ATTR_XOR_PEER_ADDRESS | ATTR_XOR_RELAYED_ADDRESS
+    )
+}
+
+/// Whether a datagram is a STUN message, as opposed to other traffic on
+/// the same socket (RFC 7983: the first two bits of STUN are zero)
+pub fn is_stun(buf: &[u8]) -> bool {
+    buf.len() >= HEADER_LEN
+        && buf[0] < 0x40
+        && buf[4..8] == MAGIC_COOKIE.to_be_bytes()
+}
+
+/// A STUN message with its attributes in order, used by ICE and TURN
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    pub msg_type: u16,
+    pub transaction_id: [u8; 12],
+    pub attributes: Vec<(u16, Vec<u8>)>,
+}
+
+impl Message {
+    pub fn new(msg_type: u16, transaction_id: [u8; 12]) -> Self {
+        Self {
+            msg_type,
+            transaction_id,
+            attributes: Vec::new(),
+        }
+    }
+
+    /// A request with a random transaction ID
+    pub fn request(msg_type: u16) -> Self {
+        Self::new(msg_type, rand::random())
+    }
+
+    pub fn with_attribute(mut self, attr_type: u16, value: impl Into<Vec<u8>>) -> Self {
+        self.attributes.push((attr_type, value.into()));
+        self
+    }
+
+    /// Add an address attribute, XORed if its type asks for it
+    pub fn with_address(self, attr_type: u16, addr: SocketAddr) -> Self {
+        let transaction_id = self.transaction_id;
+        let xor = is_xor_address(attr_type).then_some(&transaction_id);
+        let value = encode_address(addr, xor);
+        self.with_attribute(attr_type, value)
+    }
+
+    /// Encode the message, followed by MESSAGE-INTEGRITY when an
+    /// `integrity_key` is given and by FINGERPRINT
+    pub fn encode(&self, integrity_key: Option<&[u8]>) -> Vec<u8> {
+        let mut msg = Vec::with_capacity(128);
+        msg.extend_from_slice(&self.msg_type.to_be_bytes());
+        msg.extend_from_slice(&[0, 0]);
+        msg.extend_from_slice(&MAGIC_COOKIE.to_be_bytes());
+        msg.extend_from_slice(&self.transaction_id);
+        for (attr_type, value) in &self.attributes {
+            push_attribute(&mut msg, *attr_type, value);
+        }
+        if let Some(key) = integrity_key {
+            // The length covers MESSAGE-INTEGRITY itself but not FINGERPRINT
+            set_length(&mut msg, 24);
+            let key = hmac::Key::new(hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY, key);
+            let tag = hmac::sign(&key, &msg);
+            push_attribute(&mut msg, ATTR_MESSAGE_INTEGRITY, tag.as_ref());
+        }
+        set_length(&mut msg, 8);
+        let crc = crc32fast::hash(&msg) ^ FINGERPRINT_XOR;
+        push_attribute(&mut msg, ATTR_FINGERPRINT, &crc.to_be_bytes());
+        msg
+    }
+
+    /// Decode a message, checking FINGERPRINT when present
+    pub fn decode(buf: &[u8]) -> Result<Self, String> {
+        if !is_stun(buf) {
+            return Err("Not a STUN message".to_string());
+        }
+        let length = u16::from_be_bytes([buf[2], buf[3]]) as usize;
+        let body = buf
+            .get(HEADER_LEN..HEADER_LEN + length)
+            .ok_or_else(|| "Truncated STUN message".to_string())?;
+        let mut msg = Self::new(
+            u16::from_be_bytes([buf[0], buf[1]]),
+            buf[8..HEADER_LEN].try_into().unwrap_or_default(),
+        );
+        let mut offset = 0;
+        while offset + 4 <= body.len() {
+            let attr_type = u16::from_be_bytes([body[offset], body[offset + 1]]);
+            let attr_len = u16::from_be_bytes([body[offset + 2], body[offset + 3]]) as usize;
+            let value = body
+                .get(offset + 4..offset + 4 + attr_len)
+                .ok_or_else(|| "Truncated STUN attribute".to_string())?;
+            if attr_type == ATTR_FINGERPRINT {
+                let crc = crc32fast::hash(&buf[..HEADER_LEN + offset]) ^ FINGERPRINT_XOR;
+                if value != crc.to_be_bytes() {
+                    return Err("STUN fingerprint mismatch".to_string());
+                }
+            }
+            msg.attributes.push((attr_type, value.to_vec()));
+            offset += 4 + attr_len.div_ceil(4) * 4;
+        }
+        Ok(msg)
+    }
+
+    pub fn attribute(&self, attr_type: u16) -> Option<&[u8]> {
+        self.attributes
+            .iter()
+            .find(|(t, _)| *t == attr_type)
+            .map(|(_, value)| value.as_slice())
+    }
+
+    /// Decode an address attribute, XORed if its type asks for it
+    pub fn address(&self, attr_type: u16) -> Option<SocketAddr> {
+        let xor = is_xor_address(attr_type).then_some(&self.transaction_id);
+        decode_address(self.attribute(attr_type)?, xor).ok()
+    }
+
+    /// The code of an ERROR-CODE attribute
+    pub fn error_code(&self) -> Option<u16> {
+        let value = self.attribute(ATTR_ERROR_CODE)?;
+        (value.len() >= 4).then(|| u16::from(value[2] & 0x07) * 100 + u16::from(value[3]))
+    }
+}
+
+/// Check the MESSAGE-INTEGRITY of an encoded message against `key`
+pub fn check_integrity(buf: &[u8], key: &[u8]) -> bool {
+    let Some(length) = buf.get(2..4).map(|len| u16::from_be_bytes([len[0], len[1]]) as usize)
+    else {
+        return false;
+    };
+    let end = (HEADER_LEN + length).min(buf.len());
+    let mut offset = HEADER_LEN;
+    while offset + 4 <= end {
+        let attr_type = u16::from_be_bytes([buf[offset], buf[offset + 1]]);
+        let attr_len = u16::from_be_bytes([buf[offset + 2], buf[offset + 3]]) as usize;
+        if attr_type == ATTR_MESSAGE_INTEGRITY {
+            let Some(tag) = buf.get(offset + 4..offset + 4 + attr_len) else {
+                return false;
+            };
+            let mut signed = buf[..offset].to_vec();
+            let signed_len = (offset - HEADER_LEN + 24) as u16;
+            signed[2..4].copy_from_slice(&signed_len.to_be_bytes());
+            let key = hmac::Key::new(hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY, key);
+            return hmac::verify(&key, &signed, tag).is_ok();
+        }
+        offset += 4 + attr_len.div_ceil(4) * 4;
+    }
+    false
+}
+
+/// Append an attribute with its padding
+fn push_attribute(msg: &mut Vec<u8>, attr_type: u16, value: &[u8]) {
+    msg.extend_from_slice(&attr_type.to_be_bytes());
+    msg.extend_from_slice(&(value.len() as u16).to_be_bytes());
+    msg.extend_from_slice(value);
+    msg.resize(msg.len().div_ceil(4) * 4, 0);
+}
+
+/// Set the header length to the current body plus `extra` bytes
+fn set_length(msg: &mut [u8], extra: usize) {
+    let length = (msg.len() - HEADER_LEN + extra) as u16;
+    msg[2..4].copy_from_slice(&length.to_be_bytes());
+}
+
+/// Resolve `server` (host:port) to an IPv4 or IPv6 address
+pub async fn resolve(server: &str, ipv6: bool) -> Result<SocketAddr, String> {
+    lookup_host(server)
+        .await
+        .map_err(|e| format!("Failed to resolve STUN server {}: {}", server, e))?
+        .find(|addr| addr.is_ipv6() == ipv6)
+        .ok_or_else(|| {
+            format!(
+                "STUN server {} has no {} address",
+                server,
+                if ipv6 { "IPv6" } else { "IPv4" }
+            )
+        })
+}
+
+/// Send a Binding request to `server` until a response arrives
+///
+/// Responses are matched by transaction ID only, since a CHANGE-REQUEST is
+/// answered from another address. Returns the response and the address it
+/// came from, or `None` when every attempt timed out.
+async fn transact(
+    socket: &UdpSocket,
+    server: SocketAddr,
+    flags: u8,
+) -> Result<Option<(BindingResponse, SocketAddr)>, String> {
+    let transaction_id: [u8; 12] = rand::random();
+    let request = binding_request(&transaction_id, flags);
+    let mut buf = [0u8; 512];
+    for attempt in 1..=ATTEMPTS {
+        socket
+            .send_to(&request, server)
+            .await
+            .map_err(|e| format!("Failed to send STUN request: {}", e))?;
+        let deadline = Instant::now() + RESPONSE_TIMEOUT;
+        loop {
+            match timeout_at(deadline, socket.recv_from(&mut buf)).await {
+                Ok(Ok((len, from)))
+                    if len >= HEADER_LEN && buf[8..HEADER_LEN] == transaction_id =>
+                {
+                    return parse_binding(&buf[..len], &transaction_id).map(|r| Some((r, from)));
+                }
+                Ok(Ok((_, from))) => debug!("Ignoring STUN packet from {}", from),
+                Ok(Err(e)) => return Err(format!("Failed to receive STUN response: {}", e)),
+                Err(_) => {
+                    debug!("STUN request to {} timed out (attempt {})", server, attempt);
+                    break;
+                }
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Ask `server` (host:port) for our public address over IPv4 or IPv6
+pub async fn query_mapped_address(server: &str, ipv6: bool) -> Result<SocketAddr, String> {
+    let server_addr = resolve(server, ipv6).await?;
+    let bind_addr = if ipv6 { "[::]:0" } else { "0.0.0.0:0" };
+    let socket = UdpSocket::bind(bind_addr)
+        .await
+        .map_err(|e| format!("Failed to bind UDP socket: {}", e))?;
+
+    match transact(&socket, server_addr, 0).await? {
+        Some((response, _)) => Ok(response.mapped),
+        None => Err(format!("No STUN response from {}", server_addr)),
+    }
+}
+
+/// How a NAT maps or filters (RFC 5780 section 4)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NatBehavior {
+    /// The same for every remote address
+    EndpointIndependent,
+    /// Depends on the remote IP
+    AddressDependent,
+    /// Depends on the remote IP and port
+    AddressAndPortDependent,
+}
+
+/// Result of the RFC 5780 behavior tests
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NatDiscovery {
+    /// Address of the socket the tests ran on
+    pub local: SocketAddr,
+    /// Our address as seen by the server
+    pub mapped: SocketAddr,
+    /// None when the server has no alternate address to test against
+    pub mapping: Option<NatBehavior>,
+    pub filtering: Option<NatBehavior>,
+}
+
+/// Run the RFC 5780 mapping and filtering tests from `socket` against
+/// `server` (host:port)
+///
+/// Without an OTHER-ADDRESS in the server's response, as from most public
+/// STUN servers, only the mapped address is found and both behaviors are
+/// left unknown.
+///
+/// A filtering test passes when its response arrives, so behind a
+/// restrictive NAT discovery waits for the failing tests to time out.
+pub async fn discover_nat(socket: &UdpSocket, server: &str) -> Result<NatDiscovery, String> {
+    let local = socket
+        .local_addr()
+        .map_err(|e| format!("Failed to get local addr: {}", e))?;
+    let server_addr = resolve(server, local.is_ipv6()).await?;
+    let no_response = |addr: SocketAddr| format!("No STUN response from {}", addr);
+
+    // Test I: our mapped address and the server's alternate address
+    let (first, _) = transact(socket, server_addr, 0)
+        .await?
+        .ok_or_else(|| no_response(server_addr))?;
+    let local = local_address(local, server_addr).await?;
+    let Some(other) = first.other else {
+        debug!(
+            "STUN server {} does not support RFC 5780 (no OTHER-ADDRESS)",
+            server
+        );
+        return Ok(NatDiscovery {
+            local,
+            mapped: first.mapped,
+            mapping: None,
+            filtering: None,
+        });
+    };
+
+    let mapping = if first.mapped == local {
+        NatBehavior::EndpointIndependent
+    } else {
+        // Test II: alternate IP, primary port
+        let alternate_ip = SocketAddr::new(other.ip(), server_addr.port());
+        let (second, _) = transact(socket, alternate_ip, 0)
+            .await?
+            .ok_or_else(|| no_response(alternate_ip))?;
+        if second.mapped == first.mapped {
+            NatBehavior::EndpointIndependent
+        } else {
+            // Test III: alternate IP and port
+            let (third, _) = transact(socket, other, 0)
+                .await?
+                .ok_or_else(|| no_response(other))?;
+            if third.mapped == second.mapped {
+                NatBehavior::AddressDependent
+            } else {
+                NatBehavior::AddressAndPortDependent
+            }
+        }
+    };
+
+    // Filtering test II asks for a response from the alternate IP and port,
+    // test III from the alternate port only
+    let filtering = match transact(socket, server_addr, CHANGE_IP | CHANGE_PORT).await? {
+        Some((_, from)) if from == server_addr => {
+            return Err(format!("STUN server {} ignored CHANGE-REQUEST", server));
+        }
+        Some(_) => NatBehavior::EndpointIndependent,
+        None => match transact(socket, server_addr, CHANGE_PORT).await? {
+            Some(_) => NatBehavior::AddressDependent,
+            None => NatBehavior::AddressAndPortDependent,
+        },
+    };
+
+    Ok(NatDiscovery {
+        local,
+        mapped: first.mapped,
+        mapping: Some(mapping),
+        filtering: Some(filtering),
+    })
+}
+
+/// The address `local` sends from towards `server`, resolving a wildcard IP
+async fn local_address(local: SocketAddr, server: SocketAddr) -> Result<SocketAddr, String> {
+    if !local.ip().is_unspecified() {
+        return Ok(local);
+    }
+    let probe = UdpSocket::bind(SocketAddr::new(local.ip(), 0))
+        .await
+        .map_err(|e| format!("Failed to bind UDP socket: {}", e))?;
+    probe
+        .connect(server)
+        .await
+        .map_err(|e| format!("No route to STUN server {}: {}", server, e))?;
+    let ip = probe
+        .local_addr()
+        .map_err(|e| format!("Failed to get local addr: {}", e))?
+        .ip();
+    Ok(SocketAddr::new(ip, local.port()))
+}
+
+/// Start `domain-stun`'s Binding service on 127.0.0.1, with `alternate` also
+/// on 127.0.0.2, and return its primary address
+#[cfg(test)]
+pub(crate) async fn start_domain_stun(alternate: bool) -> SocketAddr {
+    use std::sync::Arc;
+
+    let primary = SocketAddr::from((Ipv4Addr::LOCALHOST, 0));
+    let alternate = alternate.then(|| SocketAddr::from((Ipv4Addr::new(127, 0, 0, 2), 0)));
+    let server = Arc::new(
+        domain_stun_core::BindingServer::bind(primary, alternate)
+            .await
+            .unwrap(),
+    );
+    for index in 0..server.socket_count() {
+        tokio::spawn(server.clone().serve(index));
+    }
+    server.socket(0).local_addr().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a Binding success response carrying `addr` in the given attribute
+    fn response(transaction_id: &[u8; 12], attr_type: u16, addr: SocketAddr) -> Vec<u8> {
+        let xor = attr_type == ATTR_XOR_MAPPED_ADDRESS;
+        let mut value = vec![0u8];
+        let (family, mut ip) = match addr.ip() {
+            IpAddr::V4(ip) => (0x01, ip.octets().to_vec()),
+            IpAddr::V6(ip) => (0x02, ip.octets().to_vec()),
+        };
+        value.push(family);
+        let mut port = addr.port();
+        if xor {
+            port ^= (MAGIC_COOKIE >> 16) as u16;
+            let key: Vec<u8> = MAGIC_COOKIE
+                .to_be_bytes()
+                .iter()
+                .chain(transaction_id.iter())
+                .copied()
+                .collect();
+            for (byte, key) in ip.iter_mut().zip(key) {
+                *byte ^= key;
+            }
+        }
+        value.extend_from_slice(&port.to_be_bytes());
+        value.extend_from_slice(&ip);
+
+        let mut msg = BINDING_SUCCESS.to_be_bytes().to_vec();
+        msg.extend_from_slice(&((value.len() + 4) as u16).to_be_bytes());
+        msg.extend_from_slice(&MAGIC_COOKIE.to_be_bytes());
+        msg.extend_from_slice(transaction_id);
+        msg.extend_from_slice(&attr_type.to_be_bytes());
+        msg.extend_from_slice(&(value.len() as u16).to_be_bytes());
+        msg.extend_from_slice(&value);
+        msg
+    }
+
+    #[test]
+    fn test_binding_request() {
+        let transaction_id = [7u8; 12];
+        let request = binding_request(&transaction_id, 0);
+        assert_eq!(request.len(), HEADER_LEN);
+        assert_eq!(&request[..4], &[0x00, 0x01, 0x00, 0x00]);
+        assert_eq!(&request[4..8], &MAGIC_COOKIE.to_be_bytes());
+        assert_eq!(&request[8..], &transaction_id);
+    }
+
+    #[test]
+    fn test_parse_binding_response() {
+        let transaction_id = [3u8; 12];
+        for addr in [
+            "203.0.113.7:54321".parse::<SocketAddr>().unwrap(),
+            "[2001:db8::7]:3478".parse().unwrap(),
+        ] {
+            for attr in [ATTR_XOR_MAPPED_ADDRESS, ATTR_MAPPED_ADDRESS] {
+                let msg = response(&transaction_id, attr, addr);
+                assert_eq!(
+                    parse_binding(&msg, &transaction_id).map(|r| r.mapped),
+                    Ok(addr)
+                );
+            }
+        }
+
+        let msg = response(
+            &transaction_id,
+            ATTR_XOR_MAPPED_ADDRESS,
+            "203.0.113.7:1".parse().unwrap(),
+        );
+        assert!(parse_binding(&msg, &[4u8; 12]).is_err());
+        assert!(parse_binding(&msg[..10], &transaction_id).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_query_mapped_address() {
+        // A tiny STUN server that echoes the sender address back
+        let server = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let server_addr = server.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut buf = [0u8; 512];
+            let (len, from) = server.recv_from(&mut buf).await.unwrap();
+            let transaction_id: [u8; 12] = buf[8..len].try_into().unwrap();
+            let msg = response(&transaction_id, ATTR_XOR_MAPPED_ADDRESS, from);
+            server.send_to(&msg, from).await.unwrap();
+        });
+
+        let mapped = query_mapped_address(&server_addr.to_string(), false)
+            .await
+            .unwrap();
+        assert_eq!(mapped.ip(), IpAddr::V4(Ipv4Addr::LOCALHOST));
+    }
+
+    #[test]
+    fn test_change_request() {
+        let transaction_id = [5u8; 12];
+        let request = binding_request(&transaction_id, CHANGE_IP | CHANGE_PORT);
+        assert_eq!(request.len(), HEADER_LEN + 8);
+        assert_eq!(&request[2..4], &[0x00, 0x08]);
+        assert_eq!(
+            &request[HEADER_LEN..],
+            &[0x00, 0x03, 0x00, 0x04, 0, 0, 0, 0x06]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_discover_nat() {
+        let server = start_domain_stun(true).await.to_string();
+        let mapped = query_mapped_address(&server, false).await.unwrap();
+        assert_eq!(mapped.ip(), IpAddr::V4(Ipv4Addr::LOCALHOST));
+
+        // Loopback has no NAT: we are mapped to our own address and every
+        // alternate server address gets through
+        let socket = UdpSocket::bind("0.0.0.0:0").await.unwrap();
+        let port = socket.local_addr().unwrap().port();
+        let discovery = discover_nat(&socket, &server).await.unwrap();
+        let local = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+        assert_eq!(
+            discovery,
+            NatDiscovery {
+                local,
+                mapped: local,
+                mapping: Some(NatBehavior::EndpointIndependent),
+                filtering: Some(NatBehavior::EndpointIndependent),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_discover_nat_without_alternate() {
+        let server = start_domain_stun(false).await;
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let local = socket.local_addr().unwrap();
+        let discovery = discover_nat(&socket, &server.to_string()).await.unwrap();
+        assert_eq!(
+            discovery,
+            NatDiscovery {
+                local,
+                mapped: local,
+                mapping: None,
+                filtering: None,
+            }
+        );
+
+        let err = transact(&socket, server, CHANGE_PORT).await.unwrap_err();
+        assert_eq!(err, "STUN error response: 420 Unknown Attribute");
+    }
+}
@@ -0,0 +1,994 @@
+//! Remote task execution
+//!
+//! Runs the tasks assigned by the Hub within the operator's [`TaskPolicy`].
+//! Commands and scripts are started without a shell in their own process
+//! group, stdout/stderr are streamed back in chunks as `TaskOutput` messages,
+//! and a timeout or a cancellation kills the whole process tree.
+
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use base64::Engine;
+use domain_agent_protocol::task::{
+    OutputStream, TaskAssignment, TaskOutputChunk, TaskResultReport, TaskStatus, TaskType,
+};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::process::{Child, Command};
+use tokio::sync::{mpsc, oneshot, Mutex};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::client::AgentMessage;
+use crate::config::{resolve_program, TaskPolicy};
+use crate::http01::{self, Http01Responder};
+
+/// Size of the chunks read from stdout/stderr
+const CHUNK_SIZE: usize = 4096;
+/// Output kept for the final `TaskResult`, the full output is streamed
+const MAX_RESULT_OUTPUT: usize = 64 * 1024;
+/// Largest file returned by `FileDownload`
+const MAX_DOWNLOAD_SIZE: u64 = 16 * 1024 * 1024;
+
+/// How a task ended
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Outcome {
+    status: TaskStatus,
+    output: String,
+    error: Option<String>,
+    exit_code: Option<i32>,
+}
+
+impl Outcome {
+    fn succeeded(output: String) -> Self {
+        Self {
+            status: TaskStatus::Succeeded,
+            output,
+            error: None,
+            exit_code: None,
+        }
+    }
+
+    fn failed(error: impl Into<String>) -> Self {
+        Self {
+            status: TaskStatus::Failed,
+            output: String::new(),
+            error: Some(error.into()),
+            exit_code: None,
+        }
+    }
+}
+
+/// Runs tasks and reports their output and results
+#[derive(Clone)]
+pub struct TaskExecutor {
+    policy: Arc<TaskPolicy>,
+    /// task ID -> cancellation sender
+    running: Arc<Mutex<HashMap<Uuid, oneshot::Sender<String>>>>,
+    /// `TaskOutput` / `TaskResult` messages for the Hub
+    tx: mpsc::UnboundedSender<AgentMessage>,
+    /// Configured HTTP-01 responder, used by challenges without a web root
+    http01: Option<Http01Responder>,
+}
+
+impl TaskExecutor {
+    pub fn new(policy: TaskPolicy, tx: mpsc::UnboundedSender<AgentMessage>) -> Self {
+        Self {
+            policy: Arc::new(policy),
+            running: Arc::new(Mutex::new(HashMap::new())),
+            tx,
+            http01: None,
+        }
+    }
+
+    /// Answer `SslHttpChallenge` tasks without a web root with this responder
+    pub fn with_http01(mut self, http01: Http01Responder) -> Self {
+        self.http01 = Some(http01);
+        self
+    }
+
+    /// Start a task in the background, its result is sent when it finishes
+    pub async fn spawn(&self, assignment: TaskAssignment) {
+        let task_id = assignment.task_id;
+        let (cancel_tx, cancel_rx) = oneshot::channel();
+        {
+            let mut running = self.running.lock().await;
+            if running.contains_key(&task_id) {
+                warn!("Task {} is already running, ignoring", task_id);
+                return;
+            }
+            running.insert(task_id, cancel_tx);
+        }
+        info!(
+            "Starting task {} ({})",
+            task_id,
+            assignment.task_type.kind()
+        );
+
+        let executor = self.clone();
+        tokio::spawn(async move {
+            let started = Instant::now();
+            let outcome = executor.execute(&assignment, cancel_rx).await;
+            executor.running.lock().await.remove(&task_id);
+            info!("Task {} finished: {}", task_id, outcome.status);
+
+            let report = TaskResultReport {
+                task_id,
+                success: outcome.status == TaskStatus::Succeeded,
+                status: Some(outcome.status),
+                output: outcome.output,
+                error: outcome.error,
+                exit_code: outcome.exit_code,
+                duration_ms: started.elapsed().as_millis() as u64,
+            };
+            if executor
+                .tx
+                .send(AgentMessage::TaskResult { report })
+                .is_err()
+            {
+                warn!("Failed to report result of task {}", task_id);
+            }
+        });
+    }
+
+    /// Cancel a running task, returns false when it is not running
+    pub async fn cancel(&self, task_id: Uuid, reason: String) -> bool {
+        match self.running.lock().await.remove(&task_id) {
+            Some(cancel_tx) => cancel_tx.send(reason).is_ok(),
+            None => false,
+        }
+    }
+
+    async fn execute(
+        &self,
+        assignment: &TaskAssignment,
+        cancel_rx: oneshot::Receiver<String>,
+    ) -> Outcome {
+        let policy = &self.policy;
+        if !policy.enabled {
+            return Outcome::failed("Remote tasks are disabled on this agent");
+        }
+        let timeout = policy.timeout(assignment.timeout_seconds);
+        let task_id = assignment.task_id;
+
+        match &assignment.task_type {
+            TaskType::Shell {
+                command,
+                working_dir,
+                env_vars,
+            } => {
+                let mut argv = match split_command(command) {
+                    Ok(argv) if !argv.is_empty() => argv,
+                    Ok(_) => return Outcome::failed("Empty command"),
+                    Err(e) => return Outcome::failed(e),
+                };
+                match resolve_program(&argv[0]) {
+                    Ok(program) => argv[0] = program.to_string_lossy().into_owned(),
+                    Err(e) => return Outcome::failed(e),
+                }
+                if let Some(name) = env_vars
+                    .iter()
+                    .flat_map(|env_vars| env_vars.keys())
+                    .find(|name| !policy.env_allowed(name))
+                {
+                    return Outcome::failed(format!(
+                        "Environment variable {} not allowed by policy",
+                        name
+                    ));
+                }
+                let working_dir = match working_dir {
+                    Some(dir) => match allowed_dir(policy, dir) {
+                        Ok(dir) => Some(dir),
+                        Err(e) => return Outcome::failed(e),
+                    },
+                    None => None,
+                };
+                if !policy.command_allowed(&argv) {
+                    return Outcome::failed(format!("Command not allowed by policy: {}", argv[0]));
+                }
+                let process = Process {
+                    program: &argv[0],
+                    args: &argv[1..],
+                    working_dir: working_dir.as_deref(),
+                    env_vars: env_vars.as_ref(),
+                };
+                self.run_process(task_id, process, timeout, cancel_rx).await
+            }
+            TaskType::Script { script_id, args } => {
+                let Some(script) = policy.script(script_id) else {
+                    return Outcome::failed(format!("Unknown script {}", script_id));
+                };
+                let process = Process {
+                    program: &script.path,
+                    args,
+                    working_dir: None,
+                    env_vars: None,
+                };
+                self.run_process(task_id, process, timeout, cancel_rx).await
+            }
+            TaskType::FileUpload {
+                remote_path,
+                content_base64,
+                mode,
+            } => match upload_file(policy, remote_path, content_base64, *mode).await {
+                Ok(size) => Outcome::succeeded(format!("{} bytes written", size)),
+                Err(e) => Outcome::failed(e),
+            },
+            TaskType::FileDownload { remote_path } => {
+                match download_file(policy, remote_path).await {
+                    Ok(content) => Outcome::succeeded(content),
+                    Err(e) => Outcome::failed(e),
+                }
+            }
+            TaskType::FileDelete { remote_path } => {
+                let result = match allowed_path(policy, remote_path) {
+                    Ok(path) => tokio::fs::remove_file(&path)
+                        .await
+                        .map_err(|e| format!("Failed to delete {}: {}", path.display(), e)),
+                    Err(e) => Err(e),
+                };
+                match result {
+                    Ok(()) => Outcome::succeeded(String::new()),
+                    Err(e) => Outcome::failed(e),
+                }
+            }
+            TaskType::SslHttpChallenge {
+                token,
+                key_authorization,
+                web_root,
+            } => match self
+                .http_challenge(token, key_authorization, web_root)
+                .await
+            {
+                Ok(output) => Outcome::succeeded(output),
+                Err(e) => Outcome::failed(e),
+            },
+            other => Outcome::failed(format!("Unsupported task type {}", other.kind())),
+        }
+    }
+
+    /// Write an HTTP-01 challenge, or remove it when the key authorization is empty
+    ///
+    /// An explicit web root must be allowed by the policy like any file task.
+    async fn http_challenge(
+        &self,
+        token: &str,
+        key_authorization: &str,
+        web_root: &str,
+    ) -> Result<String, String> {
+        if web_root.is_empty() {
+            let Some(responder) = &self.http01 else {
+                return Err("HTTP-01 challenges are not configured on this agent".to_string());
+            };
+            return match key_authorization.is_empty() {
+                true => responder.cleanup(token).await.map(|_| String::new()),
+                false => responder
+                    .present(token, key_authorization)
+                    .await
+                    .map(|_| String::new()),
+            };
+        }
+
+        let web_root = Path::new(web_root);
+        let path = http01::challenge_file(web_root, token)?;
+        allowed_path(&self.policy, &path.to_string_lossy())?;
+        match key_authorization.is_empty() {
+            true => http01::remove_challenge(web_root, token)
+                .await
+                .map(|_| String::new()),
+            false => http01::write_challenge(web_root, token, key_authorization)
+                .await
+                .map(|path| format!("{} written", path.display())),
+        }
+    }
+
+    /// Run a process, streaming its output until it exits, times out or is cancelled
+    async fn run_process(
+        &self,
+        task_id: Uuid,
+        process: Process<'_>,
+        timeout: Duration,
+        cancel_rx: oneshot::Receiver<String>,
+    ) -> Outcome {
+        let mut command = Command::new(process.program);
+        command
+            .args(process.args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        if let Some(dir) = process.working_dir {
+            command.current_dir(dir);
+        }
+        if let Some(env_vars) = process.env_vars {
+            command.envs(env_vars);
+        }
+        // Own process group, so the whole tree can be killed
+        #[cfg(unix)]
+        command.process_group(0);
+
+        let mut child = match command.spawn() {
+            Ok(child) => child,
+            Err(e) => {
+                return Outcome::failed(format!("Failed to start {}: {}", process.program, e))
+            }
+        };
+        let pid = child.id();
+        let seq = Arc::new(AtomicU64::new(0));
+        let stdout = child.stdout.take().map(|stdout| {
+            tokio::spawn(pump(
+                stdout,
+                OutputStream::Stdout,
+                task_id,
+                seq.clone(),
+                self.tx.clone(),
+            ))
+        });
+        let stderr = child.stderr.take().map(|stderr| {
+            tokio::spawn(pump(
+                stderr,
+                OutputStream::Stderr,
+                task_id,
+                seq.clone(),
+                self.tx.clone(),
+            ))
+        });
+
+        let (status, error, exit_code) = tokio::select! {
+            result = child.wait() => match result {
+                Ok(exit) if exit.success() => (TaskStatus::Succeeded, None, exit.code()),
+                Ok(exit) => (TaskStatus::Failed, Some(format!("Process exited with {}", exit)), exit.code()),
+                Err(e) => (TaskStatus::Failed, Some(format!("Failed to wait for process: {}", e)), None),
+            },
+            _ = tokio::time::sleep(timeout) => {
+                (TaskStatus::TimedOut, Some(format!("Timed out after {}s", timeout.as_secs())), None)
+            }
+            reason = cancel_rx => {
+                let reason = reason.unwrap_or_default();
+                (TaskStatus::Cancelled, Some(format!("Cancelled: {}", reason)), None)
+            }
+        };
+        // Also reaps children left behind by a process that exited normally
+        kill_tree(pid, &mut child).await;
+
+        let stdout = match stdout {
+            Some(handle) => handle.await.unwrap_or_default(),
+            None => String::new(),
+        };
+        let stderr = match stderr {
+            Some(handle) => handle.await.unwrap_or_default(),
+            None => String::new(),
+        };
+        let error = match error {
+            Some(error) if !stderr.trim().is_empty() && status == TaskStatus::Failed => {
+                Some(format!("{}: {}", error, stderr.trim()))
+            }
+            error => error,
+        };
+        Outcome {
+            status,
+            output: stdout,
+            error,
+            exit_code,
+        }
+    }
+}
+
+/// Process started by a Shell or Script task
+struct Process<'a> {
+    program: &'a str,
+    args: &'a [String],
+    working_dir: Option<&'a Path>,
+    env_vars: Option<&'a HashMap<String, String>>,
+}
+
+/// Forward a stream as `TaskOutput` chunks, returns the tail of the output
+async fn pump(
+    mut reader: impl AsyncRead + Unpin,
+    stream: OutputStream,
+    task_id: Uuid,
+    seq: Arc<AtomicU64>,
+    tx: mpsc::UnboundedSender<AgentMessage>,
+) -> String {
+    let mut buf = [0u8; CHUNK_SIZE];
+    // Bytes of a UTF-8 sequence split across two reads
+    let mut pending: Vec<u8> = Vec::new();
+    let mut tail = String::new();
+
+    loop {
+        let n = match reader.read(&mut buf).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => n,
+        };
+        pending.extend_from_slice(&buf[..n]);
+        let valid = match std::str::from_utf8(&pending) {
+            Ok(_) => pending.len(),
+            // Incomplete sequence at the end, keep it for the next read
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(_) => pending.len(),
+        };
+        let data = String::from_utf8_lossy(&pending[..valid]).into_owned();
+        pending.drain(..valid);
+        send_chunk(&tx, task_id, stream, &seq, &data, &mut tail);
+    }
+    if !pending.is_empty() {
+        let data = String::from_utf8_lossy(&pending).into_owned();
+        send_chunk(&tx, task_id, stream, &seq, &data, &mut tail);
+    }
+    tail
+}
+
+fn send_chunk(
+    tx: &mpsc::UnboundedSender<AgentMessage>,
+    task_id: Uuid,
+    stream: OutputStream,
+    seq: &AtomicU64,
+    data: &str,
+    tail: &mut String,
+) {
+    if data.is_empty() {
+        return;
+    }
+    let chunk = TaskOutputChunk {
+        task_id,
+        stream,
+        seq: seq.fetch_add(1, Ordering::SeqCst),
+        data: data.to_string(),
+    };
+    let _ = tx.send(AgentMessage::TaskOutput { chunk });
+
+    tail.push_str(data);
+    if tail.len() > MAX_RESULT_OUTPUT {
+        let mut cut = tail.len() - MAX_RESULT_OUTPUT;
+        while !tail.is_char_boundary(cut) {
+            cut += 1;
+        }
+        tail.drain(..cut);
+    }
+}
+
+/// Kill the process and everything it started
+async fn kill_tree(pid: Option<u32>, child: &mut Child) {
+    #[cfg(unix)]
+    if let Some(pid) = pid {
+        // The process group has the same ID as the process
+        unsafe {
+            libc::kill(-(pid as i32), libc::SIGKILL);
+        }
+    }
+    #[cfg(windows)]
+    if let Some(pid) = pid {
+        let _ = Command::new("taskkill")
+            .args(["/PID", &pid.to_string(), "/T", "/F"])
+            .output()
+            .await;
+    }
+    let _ = child.start_kill();
+    let _ = child.wait().await;
+}
+
+/// Split a command line into arguments, honouring quotes and backslash escapes
+pub fn split_command(command: &str) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut quote: Option<char> = None;
+    let mut chars = command.chars();
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some('\''), '\'') => quote = None,
+            (Some('\''), c) => current.push(c),
+            (Some('"'), '"') => quote = None,
+            (Some('"'), '\\') | (None, '\\') => match chars.next() {
+                Some(escaped) => {
+                    current.push(escaped);
+                    in_arg = true;
+                }
+                None => return Err("Trailing backslash in command".to_string()),
+            },
+            (Some(_), c) => current.push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                in_arg = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            (None, c) => {
+                current.push(c);
+                in_arg = true;
+            }
+        }
+    }
+    if quote.is_some() {
+        return Err("Unterminated quote in command".to_string());
+    }
+    if in_arg {
+        args.push(current);
+    }
+    Ok(args)
+}
+
+/// Resolve a path of a file task and check it against the policy
+///
+/// The path must be absolute without `..`; its parent is canonicalized so a
+/// symlinked directory cannot lead outside the allowed directories, and the
+/// file itself must not be a symlink. The file is still opened with
+/// `O_NOFOLLOW` in case a symlink is swapped in after this check.
+fn allowed_path(policy: &TaskPolicy, remote_path: &str) -> Result<PathBuf, String> {
+    let path = Path::new(remote_path);
+    if !path.is_absolute() || path.components().any(|c| c == Component::ParentDir) {
+        return Err(format!("Invalid path {}", remote_path));
+    }
+    let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+        return Err(format!("Invalid path {}", remote_path));
+    };
+    let resolved = std::fs::canonicalize(parent)
+        .map(|parent| parent.join(name))
+        .unwrap_or_else(|_| path.to_path_buf());
+    if !inside_allowed_paths(policy, &resolved) || !policy.path_allowed(path) {
+        return Err(format!("Path not allowed by policy: {}", remote_path));
+    }
+    if std::fs::symlink_metadata(&resolved).is_ok_and(|m| m.file_type().is_symlink()) {
+        return Err(format!("Path is a symlink: {}", remote_path));
+    }
+    Ok(resolved)
+}
+
+/// Open a file task's path without following a symlink in its last component
+async fn open_no_follow(
+    path: &Path,
+    options: &mut tokio::fs::OpenOptions,
+) -> std::io::Result<tokio::fs::File> {
+    #[cfg(unix)]
+    options.custom_flags(libc::O_NOFOLLOW);
+    options.open(path).await
+}
+
+/// Resolve the working directory of a command and check it against the policy
+///
+/// Unlike a file path the directory itself is canonicalized, so it must exist.
+fn allowed_dir(policy: &TaskPolicy, dir: &str) -> Result<PathBuf, String> {
+    if !Path::new(dir).is_absolute() {
+        return Err(format!("Invalid working directory {}", dir));
+    }
+    let resolved = std::fs::canonicalize(dir)
+        .map_err(|e| format!("Invalid working directory {}: {}", dir, e))?;
+    if !inside_allowed_paths(policy, &resolved) {
+        return Err(format!("Working directory not allowed by policy: {}", dir));
+    }
+    Ok(resolved)
+}
+
+fn inside_allowed_paths(policy: &TaskPolicy, resolved: &Path) -> bool {
+    policy.allowed_paths.iter().any(|allowed| {
+        let allowed = std::fs::canonicalize(allowed).unwrap_or_else(|_| PathBuf::from(allowed));
+        resolved.starts_with(allowed)
+    })
+}
+
+async fn upload_file(
+    policy: &TaskPolicy,
+    remote_path: &str,
+    content_base64: &str,
+    mode: Option<u32>,
+) -> Result<usize, String> {
+    let path = allowed_path(policy, remote_path)?;
+    let content = base64::engine::general_purpose::STANDARD
+        .decode(content_base64)
+        .map_err(|e| format!("Invalid base64 content: {}", e))?;
+    let write_error = |e: std::io::Error| format!("Failed to write {}: {}", path.display(), e);
+    let mut file = open_no_follow(
+        &path,
+        tokio::fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true),
+    )
+    .await
+    .map_err(write_error)?;
+    file.write_all(&content).await.map_err(write_error)?;
+    file.flush().await.map_err(write_error)?;
+
+    #[cfg(unix)]
+    if let Some(mode) = mode {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(mode))
+            .await
+            .map_err(|e| format!("Failed to set mode of {}: {}", path.display(), e))?;
+    }
+    #[cfg(not(unix))]
+    let _ = mode;
+
+    Ok(content.len())
+}
+
+async fn download_file(policy: &TaskPolicy, remote_path: &str) -> Result<String, String> {
+    let path = allowed_path(policy, remote_path)?;
+    let read_error = |e: std::io::Error| format!("Failed to read {}: {}", path.display(), e);
+    let mut file = open_no_follow(&path, tokio::fs::OpenOptions::new().read(true))
+        .await
+        .map_err(read_error)?;
+    let metadata = file.metadata().await.map_err(read_error)?;
+    if metadata.len() > MAX_DOWNLOAD_SIZE {
+        return Err(format!(
+            "{} is larger than {} bytes",
+            path.display(),
+            MAX_DOWNLOAD_SIZE
+        ));
+    }
+    let mut content = Vec::with_capacity(metadata.len() as usize);
+    file.read_to_end(&mut content).await.map_err(read_error)?;
+    Ok(base64::engine::general_purpose::STANDARD.encode(content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(allowed_commands: &[&str], allowed_paths: &[&str]) -> TaskPolicy {
+        TaskPolicy {
+            enabled: true,
+            allowed_commands: allowed_commands.iter().map(|c| c.to_string()).collect(),
+            allowed_paths: allowed_paths.iter().map(|p| p.to_string()).collect(),
+            ..TaskPolicy::default()
+        }
+    }
+
+    fn shell(command: &str) -> TaskType {
+        TaskType::Shell {
+            command: command.to_string(),
+            working_dir: None,
+            env_vars: None,
+        }
+    }
+
+    /// Run a task and collect the streamed output and the result
+    async fn run(
+        executor: &TaskExecutor,
+        rx: &mut mpsc::UnboundedReceiver<AgentMessage>,
+        task_type: TaskType,
+        timeout_seconds: u32,
+    ) -> (String, TaskResultReport) {
+        let task_id = Uuid::new_v4();
+        executor
+            .spawn(TaskAssignment {
+                task_id,
+                task_type,
+                params: serde_json::Value::Null,
+                timeout_seconds,
+            })
+            .await;
+
+        let mut streamed = String::new();
+        loop {
+            match rx.recv().await.unwrap() {
+                AgentMessage::TaskOutput { chunk } => {
+                    assert_eq!(chunk.task_id, task_id);
+                    streamed.push_str(&chunk.data);
+                }
+                AgentMessage::TaskResult { report } => return (streamed, report),
+                other => panic!("unexpected message {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_split_command() {
+        assert_eq!(
+            split_command(r#"echo "hello world" 'a b' c\ d"#).unwrap(),
+            vec!["echo", "hello world", "a b", "c d"]
+        );
+        assert_eq!(split_command("  ").unwrap(), Vec::<String>::new());
+        assert_eq!(split_command("echo ''").unwrap(), vec!["echo", ""]);
+        // No shell: operators are plain arguments
+        assert_eq!(
+            split_command("ls; rm -rf /").unwrap(),
+            vec!["ls;", "rm", "-rf", "/"]
+        );
+        assert!(split_command("echo \"unterminated").is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_shell_task_streams_output() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let executor = TaskExecutor::new(policy(&["sh -c"], &[]), tx);
+
+        let (streamed, report) = run(
+            &executor,
+            &mut rx,
+            shell("sh -c 'echo out; echo err >&2'"),
+            10,
+        )
+        .await;
+        assert_eq!(report.status, Some(TaskStatus::Succeeded));
+        assert_eq!(report.exit_code, Some(0));
+        assert_eq!(report.output, "out\n");
+        assert!(streamed.contains("out\n") && streamed.contains("err\n"));
+
+        let (_, report) = run(
+            &executor,
+            &mut rx,
+            shell("sh -c 'echo boom >&2; exit 3'"),
+            10,
+        )
+        .await;
+        assert_eq!(report.status, Some(TaskStatus::Failed));
+        assert_eq!(report.exit_code, Some(3));
+        assert!(report.error.unwrap().contains("boom"));
+    }
+
+    #[tokio::test]
+    async fn test_policy_rejects_tasks() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let executor = TaskExecutor::new(policy(&["uptime"], &["/nonexistent/allowed"]), tx);
+
+        let (_, report) = run(&executor, &mut rx, shell("rm -rf /"), 10).await;
+        assert_eq!(report.status, Some(TaskStatus::Failed));
+        assert!(report.error.unwrap().contains("not allowed"));
+
+        let task = TaskType::FileDelete {
+            remote_path: "/nonexistent/allowed/../../etc/passwd".to_string(),
+        };
+        let (_, report) = run(&executor, &mut rx, task, 10).await;
+        assert!(report.error.unwrap().contains("Invalid path"));
+
+        let task = TaskType::Script {
+            script_id: Uuid::new_v4(),
+            args: vec![],
+        };
+        let (_, report) = run(&executor, &mut rx, task, 10).await;
+        assert!(report.error.unwrap().contains("Unknown script"));
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let disabled = TaskExecutor::new(TaskPolicy::default(), tx);
+        let (_, report) = run(&disabled, &mut rx, shell("uptime"), 10).await;
+        assert!(report.error.unwrap().contains("disabled"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_timeout_and_cancel_kill_process_tree() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let executor = TaskExecutor::new(policy(&["sh -c"], &[]), tx);
+
+        // The child sleep keeps stdout open; the task only ends once the whole group is killed
+        let started = Instant::now();
+        let (_, report) = run(&executor, &mut rx, shell("sh -c 'sleep 30 & sleep 30'"), 1).await;
+        assert_eq!(report.status, Some(TaskStatus::TimedOut));
+        assert!(started.elapsed() < Duration::from_secs(10));
+
+        let task_id = Uuid::new_v4();
+        executor
+            .spawn(TaskAssignment {
+                task_id,
+                task_type: shell("sh -c 'echo started; sleep 30 & sleep 30'"),
+                params: serde_json::Value::Null,
+                timeout_seconds: 60,
+            })
+            .await;
+        // Wait until the process runs
+        assert!(matches!(
+            rx.recv().await,
+            Some(AgentMessage::TaskOutput { .. })
+        ));
+        assert!(executor.cancel(task_id, "operator".to_string()).await);
+        let report = loop {
+            if let Some(AgentMessage::TaskResult { report }) = rx.recv().await {
+                break report;
+            }
+        };
+        assert_eq!(report.status, Some(TaskStatus::Cancelled));
+        assert!(report.error.unwrap().contains("operator"));
+        assert!(!executor.cancel(task_id, "again".to_string()).await);
+    }
+
+    /// The Hub cannot pick the program through `PATH`, preloaded libraries,
+    /// a relative path or a working directory outside the allowed paths
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_shell_task_policy_bypasses() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("domain-agent-exec-{}", rand::random::<u32>()));
+        let work = dir.join("work");
+        std::fs::create_dir_all(&work).unwrap();
+        // A binary uploaded under an allowed name
+        let fake = work.join("sh");
+        std::fs::write(&fake, "#!/bin/sh\necho fake\n").unwrap();
+        std::fs::set_permissions(&fake, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let outside =
+            std::env::temp_dir().join(format!("domain-agent-link-{}", rand::random::<u32>()));
+        std::os::unix::fs::symlink("/", &outside).unwrap();
+        let escape = work.join("escape");
+        std::os::unix::fs::symlink("/", &escape).unwrap();
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut tasks = policy(&["sh -c"], &[dir.to_str().unwrap()]);
+        tasks.allowed_env = vec!["GREETING".to_string()];
+        let executor = TaskExecutor::new(tasks, tx);
+        let command =
+            |command: &str, working_dir: Option<&Path>, env: &[(&str, &str)]| TaskType::Shell {
+                command: command.to_string(),
+                working_dir: working_dir.map(|dir| dir.to_string_lossy().into_owned()),
+                env_vars: (!env.is_empty()).then(|| {
+                    env.iter()
+                        .map(|(k, v)| (k.to_string(), v.to_string()))
+                        .collect()
+                }),
+            };
+
+        for env in [
+            ("PATH", work.to_str().unwrap()),
+            ("LD_PRELOAD", "/tmp/evil.so"),
+            ("HOME", "/"),
+        ] {
+            let task = command("sh -c 'echo $GREETING'", None, &[env]);
+            let (_, report) = run(&executor, &mut rx, task, 10).await;
+            let error = report.error.unwrap();
+            assert!(
+                error.contains("Environment variable") && error.contains(env.0),
+                "{}",
+                error
+            );
+        }
+
+        let task = command("./sh -c true", Some(&work), &[]);
+        let (_, report) = run(&executor, &mut rx, task, 10).await;
+        assert!(report.error.unwrap().contains("must be absolute"));
+
+        let task = command(&format!("{} -c true", fake.display()), Some(&work), &[]);
+        let (_, report) = run(&executor, &mut rx, task, 10).await;
+        assert!(report.error.unwrap().contains("not allowed by policy"));
+
+        for dir in [
+            Path::new("/"),
+            Path::new("work"),
+            outside.as_path(),
+            escape.as_path(),
+        ] {
+            let task = command("sh -c pwd", Some(dir), &[]);
+            let (_, report) = run(&executor, &mut rx, task, 10).await;
+            assert!(
+                report.error.unwrap().contains("directory"),
+                "{}",
+                dir.display()
+            );
+        }
+
+        let task = command(
+            "sh -c 'echo $GREETING; pwd'",
+            Some(&work),
+            &[("GREETING", "hi")],
+        );
+        let (_, report) = run(&executor, &mut rx, task, 10).await;
+        assert!(report.success, "{:?}", report.error);
+        let work = std::fs::canonicalize(&work).unwrap();
+        assert_eq!(report.output, format!("hi\n{}\n", work.display()));
+
+        std::fs::remove_file(&outside).ok();
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_file_tasks() {
+        let dir = std::env::temp_dir().join(format!("domain-agent-task-{}", rand::random::<u32>()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let executor = TaskExecutor::new(policy(&[], &[dir.to_str().unwrap()]), tx);
+        let file = dir.join("hello.txt").to_string_lossy().into_owned();
+
+        let upload = TaskType::FileUpload {
+            remote_path: file.clone(),
+            content_base64: base64::engine::general_purpose::STANDARD.encode("hello"),
+            mode: Some(0o600),
+        };
+        let (_, report) = run(&executor, &mut rx, upload, 10).await;
+        assert!(report.success, "{:?}", report.error);
+
+        let download = TaskType::FileDownload {
+            remote_path: file.clone(),
+        };
+        let (_, report) = run(&executor, &mut rx, download, 10).await;
+        assert_eq!(
+            base64::engine::general_purpose::STANDARD
+                .decode(report.output)
+                .unwrap(),
+            b"hello"
+        );
+
+        let delete = TaskType::FileDelete {
+            remote_path: file.clone(),
+        };
+        let (_, report) = run(&executor, &mut rx, delete, 10).await;
+        assert!(report.success);
+        assert!(!Path::new(&file).exists());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    /// A symlink inside an allowed directory cannot be used to write, chmod
+    /// or read the file it points to
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_file_tasks_reject_symlinks() {
+        let dir = std::env::temp_dir().join(format!("domain-agent-task-{}", rand::random::<u32>()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let outside =
+            std::env::temp_dir().join(format!("domain-agent-secret-{}", rand::random::<u32>()));
+        std::fs::write(&outside, "secret").unwrap();
+        let link = dir.join("link");
+        std::os::unix::fs::symlink(&outside, &link).unwrap();
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let executor = TaskExecutor::new(policy(&[], &[dir.to_str().unwrap()]), tx);
+        let link = link.to_string_lossy().into_owned();
+
+        let upload = TaskType::FileUpload {
+            remote_path: link.clone(),
+            content_base64: base64::engine::general_purpose::STANDARD.encode("owned"),
+            mode: Some(0o777),
+        };
+        let (_, report) = run(&executor, &mut rx, upload, 10).await;
+        assert!(!report.success);
+        assert!(report.error.unwrap().contains("symlink"));
+        assert_eq!(std::fs::read_to_string(&outside).unwrap(), "secret");
+
+        let download = TaskType::FileDownload { remote_path: link };
+        let (_, report) = run(&executor, &mut rx, download, 10).await;
+        assert!(!report.success);
+        assert!(report.error.unwrap().contains("symlink"));
+        assert!(report.output.is_empty());
+
+        std::fs::remove_file(&outside).ok();
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_http_challenge_task() {
+        let dir = std::env::temp_dir().join(format!("domain-agent-task-{}", rand::random::<u32>()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let executor = TaskExecutor::new(policy(&[], &[dir.to_str().unwrap()]), tx);
+        let challenge = |key_authorization: &str, web_root: &str| TaskType::SslHttpChallenge {
+            token: "token-1".to_string(),
+            key_authorization: key_authorization.to_string(),
+            web_root: web_root.to_string(),
+        };
+        let web_root = dir.to_str().unwrap();
+
+        let (_, report) = run(
+            &executor,
+            &mut rx,
+            challenge("token-1.thumbprint", web_root),
+            10,
+        )
+        .await;
+        assert!(report.success, "{:?}", report.error);
+        let path = dir.join(".well-known/acme-challenge/token-1");
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "token-1.thumbprint"
+        );
+
+        let (_, report) = run(&executor, &mut rx, challenge("", web_root), 10).await;
+        assert!(report.success, "{:?}", report.error);
+        assert!(!path.exists());
+
+        // Outside the allowed paths, or without a configured responder
+        let (_, report) = run(&executor, &mut rx, challenge("value", "/var/www"), 10).await;
+        assert!(report.error.unwrap().contains("not allowed"));
+        let (_, report) = run(&executor, &mut rx, challenge("value", ""), 10).await;
+        assert!(report.error.unwrap().contains("not configured"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
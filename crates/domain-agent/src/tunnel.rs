@@ -1,272 +1,514 @@
 //! Reverse tunnel support for Domain Agent
 //!
-//! This module allows the Agent to listen on a port and forward connections
-//! to the Hub, enabling the Hub to connect to services behind the Agent's NAT.
+//! This module lets the Hub reach services behind the Agent's NAT: the Agent
+//! asks the Hub to expose a local target, the Hub listens on a public port
+//! and opens a multiplexed stream over the agent WebSocket for every
+//! connection, and the Agent forwards each stream to the target. The Hub
+//! can also assign tunnels of its own, e.g. for hostname-based ingress
+//! routes, which it assigns again after every reconnect.
+//!
+//! Named port forwards from the `[[forward]]` config sections are tunnels
+//! too: an `expose` forward makes a local target reachable through the Hub,
+//! a `connect` forward listens locally and opens a stream for every
+//! connection, which the Hub relays to an `expose` forward of another agent.
+//! The Hub starts and stops forwards by name and gets a `ForwardReport`
+//! after every change.
 
 use std::collections::HashMap;
-use std::sync::Arc;
-use tokio::io::{AsyncRead, AsyncWrite};
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use domain_agent_protocol::mux::{Multiplexer, MuxStream, Side};
+use domain_agent_protocol::tunnel::{
+    ForwardDirection, ForwardStatus, TunnelFrame, TunnelMessage, TunnelType,
+};
 use tokio::net::{TcpListener, TcpStream};
-use tokio::sync::{broadcast, RwLock};
-use tracing::{debug, error, info, warn};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
 use uuid::Uuid;
 
-use crate::client::AgentClient;
-
-/// Tunnel message types for communication with Hub
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-#[serde(tag = "type", content = "payload")]
-pub enum TunnelMessage {
-    /// Agent requests a reverse tunnel
-    TunnelRequest {
-        tunnel_id: Uuid,
-        bind_port: u16,
-        tunnel_type: TunnelType,
-    },
-
-    /// Hub responds to tunnel request
-    TunnelResponse {
-        tunnel_id: Uuid,
-        success: bool,
-        public_port: Option<u16>,
-        error: Option<String>,
-    },
-
-    /// Data forwarded through tunnel (Hub -> Agent -> Service)
-    TunnelData {
-        tunnel_id: Uuid,
-        data: Vec<u8>,
-    },
-
-    /// Data forwarded through tunnel (Service -> Agent -> Hub)
-    TunnelDataForward {
-        tunnel_id: Uuid,
-        data: Vec<u8>,
-    },
-
-    /// Close tunnel
-    TunnelClose {
-        tunnel_id: Uuid,
-        reason: Option<String>,
-    },
+use crate::config::ForwardConfig;
+use crate::net::accept_with_backoff;
+
+/// How long to wait for a tunnel target to accept a connection
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A tunnel requested from or assigned by the Hub
+#[derive(Debug, Clone)]
+struct TunnelSpec {
+    /// Address the streams are forwarded to
+    target: String,
+    tunnel_type: TunnelType,
+    /// Public port reported by the Hub
+    public_port: Option<u16>,
+    /// Requested from the Hub on every connection
+    public: bool,
+    /// Defined by the Hub, which assigns it again on every connection
+    assigned: bool,
 }
 
-/// Tunnel type
-#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
-#[serde(rename_all = "snake_case")]
-pub enum TunnelType {
-    /// TCP direct connection
-    Tcp,
-    /// HTTP proxy
-    Http,
-    /// WebSocket
-    WebSocket,
+/// A configured port forward
+struct Forward {
+    config: ForwardConfig,
+    tunnel_id: Uuid,
+    /// Should run, from the config until the Hub starts or stops it
+    wanted: bool,
+    active: bool,
+    error: Option<String>,
+    /// Local listener of a running `connect` forward
+    listener: Option<JoinHandle<()>>,
 }
 
-/// A single tunnel connection (Agent side)
-struct TunnelConnection {
-    id: Uuid,
-    listener_port: u16,
-    client_stream: Option<TcpStream>,
-    tunnel_type: TunnelType,
+/// Multiplexer of the current Hub connection
+struct Connection {
+    mux: Multiplexer,
+    acceptor: JoinHandle<()>,
 }
 
 /// Reverse tunnel manager
 pub struct TunnelManager {
-    /// Active tunnels
-    tunnels: Arc<RwLock<HashMap<Uuid, TunnelConnection>>>,
-    /// Shutdown signal
-    shutdown_tx: Arc<RwLock<Option<broadcast::Sender<()>>>>,
+    /// Configured tunnels, requested again on every connection
+    tunnels: Arc<Mutex<HashMap<Uuid, TunnelSpec>>>,
+    /// Frames for the Hub, sent by the client's main loop
+    frames: mpsc::UnboundedSender<Vec<u8>>,
+    connection: Arc<Mutex<Option<Connection>>>,
+    forwards: Mutex<Vec<Forward>>,
 }
 
 impl TunnelManager {
-    /// Create a new tunnel manager
-    pub fn new() -> Self {
+    /// Create a new tunnel manager that queues its frames on `frames`
+    pub fn new(frames: mpsc::UnboundedSender<Vec<u8>>) -> Self {
         Self {
-            tunnels: Arc::new(RwLock::new(HashMap::new())),
-            shutdown_tx: Arc::new(RwLock::new(None)),
+            tunnels: Arc::new(Mutex::new(HashMap::new())),
+            frames,
+            connection: Arc::new(Mutex::new(None)),
+            forwards: Mutex::new(Vec::new()),
         }
     }
 
-    /// Start a reverse tunnel listener
-    pub async fn start_tunnel(
-        &self,
-        agent: &AgentClient,
-        port: u16,
-        tunnel_type: TunnelType,
-    ) -> Result<Uuid, String> {
+    /// Add a tunnel to a target reachable from the Agent, e.g. `127.0.0.1:8081`
+    pub fn add(&self, target: impl Into<String>, tunnel_type: TunnelType) -> Uuid {
         let tunnel_id = Uuid::new_v4();
-
-        info!("Starting reverse tunnel {} on port {}", tunnel_id, port);
-
-        // Create TCP listener
-        let listener = TcpListener::bind(format!("0.0.0.0:{}", port))
-            .await
-            .map_err(|e| format!("Failed to bind port {}: {}", port, e))?;
-
-        info!("Reverse tunnel listener started on port {}", port);
-
-        // Register tunnel
-        let tunnel = TunnelConnection {
-            id: tunnel_id,
-            listener_port: port,
-            client_stream: None,
+        let spec = TunnelSpec {
+            target: target.into(),
             tunnel_type,
+            public_port: None,
+            public: true,
+            assigned: false,
         };
+        info!("Tunnel {}: forwarding to {}", tunnel_id, spec.target);
+        self.lock_tunnels().insert(tunnel_id, spec);
+        tunnel_id
+    }
 
-        {
-            let mut tunnels = self.tunnels.write().await;
-            tunnels.insert(tunnel_id, tunnel);
-        }
-
-        // Send tunnel request to Hub
-        let request = TunnelMessage::TunnelRequest {
-            tunnel_id,
-            bind_port: port,
-            tunnel_type,
+    /// Add a named port forward, started on the next connection if enabled
+    pub fn add_forward(&self, config: ForwardConfig) {
+        let forward = Forward {
+            tunnel_id: Uuid::new_v4(),
+            wanted: config.enabled,
+            active: false,
+            error: None,
+            listener: None,
+            config,
         };
+        self.lock_forwards().push(forward);
+    }
 
-        let json = serde_json::to_string(&request)
-            .map_err(|e| format!("Failed to serialize tunnel request: {}", e))?;
-
-        // Note: This requires agent to have a method to send messages
-        // For now, we spawn a task to handle connections
-        let tunnels = self.tunnels.clone();
-        let shutdown_tx = self.shutdown_tx.clone();
+    /// Start carrying streams over a new Hub connection, returns the
+    /// requests to send for the configured tunnels and the forward report
+    pub fn attach(&self) -> Vec<TunnelMessage> {
+        self.detach();
+        let (mux, incoming) = Multiplexer::new(Side::Agent, self.frames.clone());
+        let acceptor = tokio::spawn(accept_streams(incoming, self.tunnels.clone()));
+        *self.lock_connection() = Some(Connection { mux, acceptor });
+
+        let mut forwards = self.lock_forwards();
+        for forward in forwards.iter_mut().filter(|forward| forward.wanted) {
+            self.start_forward(forward);
+        }
+        let mut messages: Vec<TunnelMessage> = {
+            let mut tunnels = self.lock_tunnels();
+            tunnels.retain(|_, spec| !spec.assigned);
+            tunnels
+                .iter()
+                .filter(|(_, spec)| spec.public)
+                .map(|(tunnel_id, spec)| request(*tunnel_id, spec))
+                .collect()
+        };
+        if !forwards.is_empty() {
+            messages.push(self.forward_report(&forwards));
+        }
+        messages
+    }
 
-        // Spawn task to accept connections and forward them
-        tokio::spawn(async move {
-            Self::accept_and_forward(listener, tunnel_id, tunnels, shutdown_tx).await;
-        });
+    /// Abort all streams of the current Hub connection
+    pub fn detach(&self) {
+        if let Some(connection) = self.lock_connection().take() {
+            connection.acceptor.abort();
+            connection.mux.close_all("Hub connection closed");
+        }
+    }
 
-        Ok(tunnel_id)
+    /// Handle a frame received from the Hub
+    pub fn handle_frame(&self, frame: TunnelFrame) {
+        match self.lock_connection().as_ref() {
+            Some(connection) => connection.mux.handle_frame(frame),
+            None => debug!("Dropping tunnel frame without a Hub connection"),
+        }
     }
 
-    /// Accept connections and forward data
-    async fn accept_and_forward(
-        listener: TcpListener,
-        tunnel_id: Uuid,
-        tunnels: Arc<RwLock<HashMap<Uuid, TunnelConnection>>>,
-        _shutdown_tx: Arc<RwLock<Option<broadcast::Sender<()>>>>,
-    ) {
-        info!("Tunnel {}: accepting connections", tunnel_id);
-
-        loop {
-            match listener.accept().await {
-                Ok((client_stream, addr)) => {
-                    info!("Tunnel {}: received connection from {}", tunnel_id, addr);
-
-                    // Handle the connection
-                    // In a full implementation, this would:
-                    // 1. Send TunnelData message to Hub with initial data
-                    // 2. Bridge data between client_stream and WebSocket
-                    let tunnel_id_clone = tunnel_id;
-                    let client_addr = addr;
-
-                    tokio::spawn(async move {
-                        if let Err(e) = Self::handle_tunnel_connection(
-                            client_stream,
-                            tunnel_id_clone,
-                            client_addr,
-                        )
-                        .await
-                        {
-                            warn!(
-                                "Tunnel {}: connection handling error: {}",
-                                tunnel_id_clone, e
-                            );
-                        }
-                    });
+    /// Handle a tunnel control message received from the Hub, returns the
+    /// messages to send back
+    pub fn handle_message(&self, msg: TunnelMessage) -> Vec<TunnelMessage> {
+        match msg {
+            TunnelMessage::TunnelResponse {
+                tunnel_id,
+                success: true,
+                public_port,
+                ..
+            } => {
+                {
+                    let mut tunnels = self.lock_tunnels();
+                    if let Some(spec) = tunnels.get_mut(&tunnel_id) {
+                        info!(
+                            "Tunnel {}: {} exposed on Hub port {:?}",
+                            tunnel_id, spec.target, public_port
+                        );
+                        spec.public_port = public_port;
+                    }
                 }
-                Err(e) => {
-                    error!("Tunnel {}: accept error: {}", tunnel_id, e);
-                    break;
+                self.report_if_forward(tunnel_id, None)
+            }
+            TunnelMessage::TunnelResponse {
+                tunnel_id, error, ..
+            } => {
+                warn!("Tunnel {}: rejected by Hub: {:?}", tunnel_id, error);
+                let error = error.unwrap_or_else(|| "Rejected by Hub".to_string());
+                self.report_if_forward(tunnel_id, Some(error))
+            }
+            TunnelMessage::TunnelAssign {
+                tunnel_id,
+                target,
+                tunnel_type,
+            } => {
+                info!(
+                    "Tunnel {}: assigned by Hub, forwarding to {}",
+                    tunnel_id, target
+                );
+                let spec = TunnelSpec {
+                    target,
+                    tunnel_type,
+                    public_port: None,
+                    public: false,
+                    assigned: true,
+                };
+                self.lock_tunnels().insert(tunnel_id, spec);
+                Vec::new()
+            }
+            TunnelMessage::TunnelClose { tunnel_id, reason } => {
+                info!("Tunnel {}: closed by Hub: {:?}", tunnel_id, reason);
+                {
+                    let mut tunnels = self.lock_tunnels();
+                    if tunnels.get(&tunnel_id).is_some_and(|spec| spec.assigned) {
+                        tunnels.remove(&tunnel_id);
+                    }
+                }
+                if let Some(connection) = self.lock_connection().as_ref() {
+                    connection
+                        .mux
+                        .close_tunnel(tunnel_id, "Tunnel closed by Hub");
                 }
+                Vec::new()
+            }
+            TunnelMessage::ForwardStart { name } => self.toggle_forward(&name, true),
+            TunnelMessage::ForwardStop { name } => self.toggle_forward(&name, false),
+            TunnelMessage::TunnelRequest { tunnel_id, .. } => {
+                debug!("Ignoring TunnelRequest {} from Hub", tunnel_id);
+                Vec::new()
+            }
+            TunnelMessage::ForwardReport { .. } => {
+                debug!("Ignoring ForwardReport from Hub");
+                Vec::new()
             }
         }
+    }
 
-        // Clean up tunnel
-        let mut tunnels = tunnels.write().await;
-        tunnels.remove(&tunnel_id);
-        info!("Tunnel {}: closed", tunnel_id);
+    /// Stop all tunnels and forwards
+    pub fn stop_all(&self) {
+        self.detach();
+        for forward in self.lock_forwards().iter_mut() {
+            self.stop_forward(forward);
+        }
+        for (id, _) in self.lock_tunnels().drain() {
+            info!("Tunnel {}: stopped", id);
+        }
     }
 
-    /// Handle a single tunnel connection
-    async fn handle_tunnel_connection(
-        mut client_stream: TcpStream,
-        tunnel_id: Uuid,
-        _client_addr: std::net::SocketAddr,
-    ) -> Result<(), String> {
-        // Buffer for copying data
-        let mut buf = vec![0u8; 8192];
-
-        // For now, just read and log the data
-        // In a full implementation, this would:
-        // 1. Read from client_stream
-        // 2. Send TunnelDataForward to Hub via WebSocket
-        // 3. Read TunnelData from Hub
-        // 4. Write to client_stream
-
-        loop {
-            use tokio::io::AsyncReadExt;
-
-            match client_stream.read(&mut buf).await {
-                Ok(0) => {
-                    debug!("Tunnel {}: client closed connection", tunnel_id);
-                    break;
-                }
-                Ok(n) => {
-                    debug!(
-                        "Tunnel {}: received {} bytes from client",
-                        tunnel_id, n
-                    );
-                    // In full implementation: send to Hub
-                    let _data = &buf[..n];
-
-                    // Echo back for testing (remove in production)
-                    // use tokio::io::AsyncWriteExt;
-                    // client_stream.write_all(&buf[..n]).await?;
-                }
-                Err(e) => {
-                    error!("Tunnel {}: read error: {}", tunnel_id, e);
-                    break;
-                }
+    /// Start or stop a forward on request of the Hub
+    fn toggle_forward(&self, name: &str, start: bool) -> Vec<TunnelMessage> {
+        let mut forwards = self.lock_forwards();
+        let Some(forward) = forwards
+            .iter_mut()
+            .find(|forward| forward.config.name == name)
+        else {
+            warn!("Forward {}: not configured", name);
+            return vec![self.forward_report(&forwards)];
+        };
+        forward.wanted = start;
+        let mut messages = Vec::new();
+        let public =
+            forward.config.direction == ForwardDirection::Expose && forward.config.bind.is_some();
+        if start && !forward.active {
+            self.start_forward(forward);
+            let spec = self.lock_tunnels().get(&forward.tunnel_id).cloned();
+            if let Some(spec) = spec.filter(|_| public) {
+                messages.push(request(forward.tunnel_id, &spec));
+            }
+        } else if !start && forward.active {
+            self.stop_forward(forward);
+            if public {
+                messages.push(TunnelMessage::TunnelClose {
+                    tunnel_id: forward.tunnel_id,
+                    reason: Some("Forward stopped".to_string()),
+                });
             }
         }
-
-        Ok(())
+        messages.push(self.forward_report(&forwards));
+        messages
     }
 
-    /// Stop a tunnel
-    pub async fn stop_tunnel(&self, tunnel_id: Uuid) -> Result<(), String> {
-        let mut tunnels = self.tunnels.write().await;
+    /// Start a forward that is not running
+    fn start_forward(&self, forward: &mut Forward) {
+        if forward.active {
+            return;
+        }
+        let config = &forward.config;
+        match config.direction {
+            ForwardDirection::Expose => {
+                let spec = TunnelSpec {
+                    target: config.target.clone(),
+                    tunnel_type: TunnelType::Tcp,
+                    public_port: config.public_port().filter(|port| *port > 0),
+                    public: config.bind.is_some(),
+                    assigned: false,
+                };
+                self.lock_tunnels().insert(forward.tunnel_id, spec);
+            }
+            ForwardDirection::Connect => {
+                let bind = config.bind.clone().unwrap_or_default();
+                let listener = match bind_listener(&bind) {
+                    Ok(listener) => listener,
+                    Err(e) => {
+                        warn!(
+                            "Forward {}: failed to listen on {}: {}",
+                            config.name, bind, e
+                        );
+                        forward.error = Some(format!("Failed to listen on {}: {}", bind, e));
+                        return;
+                    }
+                };
+                forward.listener = Some(tokio::spawn(accept_local(
+                    listener,
+                    forward.tunnel_id,
+                    config.name.clone(),
+                    config.allowed_sources.clone(),
+                    self.connection.clone(),
+                )));
+            }
+        }
+        info!(
+            "Forward {}: started, {:?} {}",
+            config.name, config.direction, config.target
+        );
+        forward.active = true;
+        forward.error = None;
+    }
 
-        if tunnels.remove(&tunnel_id).is_some() {
-            info!("Tunnel {}: stopped", tunnel_id);
-            Ok(())
-        } else {
-            Err(format!("Tunnel {} not found", tunnel_id))
+    /// Stop a running forward and abort its streams
+    fn stop_forward(&self, forward: &mut Forward) {
+        if !forward.active {
+            return;
+        }
+        if let Some(listener) = forward.listener.take() {
+            listener.abort();
         }
+        self.lock_tunnels().remove(&forward.tunnel_id);
+        if let Some(connection) = self.lock_connection().as_ref() {
+            connection
+                .mux
+                .close_tunnel(forward.tunnel_id, "Forward stopped");
+        }
+        info!("Forward {}: stopped", forward.config.name);
+        forward.active = false;
     }
 
-    /// Stop all tunnels
-    pub async fn stop_all(&self) {
-        let mut tunnels = self.tunnels.write().await;
-        for (id, _) in tunnels.drain() {
-            info!("Tunnel {}: stopped", id);
+    /// Report the forwards again when a tunnel response concerns one of them
+    fn report_if_forward(&self, tunnel_id: Uuid, error: Option<String>) -> Vec<TunnelMessage> {
+        let mut forwards = self.lock_forwards();
+        let Some(forward) = forwards
+            .iter_mut()
+            .find(|forward| forward.tunnel_id == tunnel_id)
+        else {
+            return Vec::new();
+        };
+        forward.error = error;
+        vec![self.forward_report(&forwards)]
+    }
+
+    fn forward_report(&self, forwards: &[Forward]) -> TunnelMessage {
+        let tunnels = self.lock_tunnels();
+        let forwards = forwards
+            .iter()
+            .map(|forward| ForwardStatus {
+                name: forward.config.name.clone(),
+                tunnel_id: forward.tunnel_id,
+                direction: forward.config.direction,
+                bind: forward.config.bind.clone(),
+                target: forward.config.target.clone(),
+                allowed_agents: forward.config.allowed_agents.clone(),
+                allowed_sources: forward.config.allowed_sources.clone(),
+                active: forward.active,
+                public_port: tunnels
+                    .get(&forward.tunnel_id)
+                    .filter(|spec| spec.public)
+                    .and_then(|spec| spec.public_port),
+                error: forward.error.clone(),
+            })
+            .collect();
+        TunnelMessage::ForwardReport { forwards }
+    }
+
+    fn lock_tunnels(&self) -> std::sync::MutexGuard<'_, HashMap<Uuid, TunnelSpec>> {
+        self.tunnels.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn lock_connection(&self) -> std::sync::MutexGuard<'_, Option<Connection>> {
+        self.connection
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn lock_forwards(&self) -> std::sync::MutexGuard<'_, Vec<Forward>> {
+        self.forwards.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+fn request(tunnel_id: Uuid, spec: &TunnelSpec) -> TunnelMessage {
+    TunnelMessage::TunnelRequest {
+        tunnel_id,
+        target: spec.target.clone(),
+        tunnel_type: spec.tunnel_type,
+        public_port: spec.public_port,
+    }
+}
+
+/// Listen synchronously, so forwards start and fail while the Hub waits for the report
+fn bind_listener(bind: &str) -> std::io::Result<TcpListener> {
+    let listener = std::net::TcpListener::bind(bind)?;
+    listener.set_nonblocking(true)?;
+    TcpListener::from_std(listener)
+}
+
+/// Open a stream to the Hub for every local connection to a `connect` forward
+async fn accept_local(
+    listener: TcpListener,
+    tunnel_id: Uuid,
+    name: String,
+    allowed_sources: Vec<IpAddr>,
+    connection: Arc<Mutex<Option<Connection>>>,
+) {
+    loop {
+        let (socket, peer_addr) =
+            accept_with_backoff(&listener, &format!("Forward {}", name)).await;
+        let peer_ip = peer_addr.ip().to_canonical();
+        if !allowed_sources.contains(&peer_ip) {
+            warn!("Forward {}: refused connection from {}", name, peer_addr);
+            continue;
         }
+        let stream = connection
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_ref()
+            .map(|connection| connection.mux.open(tunnel_id));
+        let Some(stream) = stream else {
+            debug!(
+                "Forward {}: no Hub connection, dropping connection from {}",
+                name, peer_addr
+            );
+            continue;
+        };
+        debug!(
+            "Forward {}: connection from {} on stream {}",
+            name,
+            peer_addr,
+            stream.id()
+        );
+        let name = name.clone();
+        tokio::spawn(async move {
+            if let Err(e) = stream.bridge(socket).await {
+                debug!(
+                    "Forward {}: connection from {} aborted: {}",
+                    name, peer_addr, e
+                );
+            }
+        });
     }
+}
 
-    /// Get active tunnel count
-    pub async fn tunnel_count(&self) -> usize {
-        let tunnels = self.tunnels.read().await;
-        tunnels.len()
+/// Forward every stream opened by the Hub to the target of its tunnel
+async fn accept_streams(
+    mut incoming: mpsc::UnboundedReceiver<MuxStream>,
+    tunnels: Arc<Mutex<HashMap<Uuid, TunnelSpec>>>,
+) {
+    while let Some(stream) = incoming.recv().await {
+        let target = tunnels
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&stream.tunnel_id())
+            .map(|spec| spec.target.clone());
+        let Some(target) = target else {
+            warn!(
+                "Stream {} for unknown tunnel {}",
+                stream.id(),
+                stream.tunnel_id()
+            );
+            stream.reset("Unknown tunnel");
+            continue;
+        };
+        tokio::spawn(forward_stream(stream, target));
     }
 }
 
-impl Default for TunnelManager {
-    fn default() -> Self {
-        Self::new()
+/// Connect to the target and copy data until the stream is closed
+async fn forward_stream(stream: MuxStream, target: String) {
+    let tunnel_id = stream.tunnel_id();
+    let connection = match tokio::time::timeout(CONNECT_TIMEOUT, TcpStream::connect(&target)).await
+    {
+        Ok(Ok(connection)) => connection,
+        Ok(Err(e)) => {
+            warn!(
+                "Tunnel {}: failed to connect to {}: {}",
+                tunnel_id, target, e
+            );
+            stream.reset(format!("Failed to connect to target: {}", e));
+            return;
+        }
+        Err(_) => {
+            warn!("Tunnel {}: connecting to {} timed out", tunnel_id, target);
+            stream.reset("Connecting to target timed out");
+            return;
+        }
+    };
+    debug!(
+        "Tunnel {}: stream {} connected to {}",
+        tunnel_id,
+        stream.id(),
+        target
+    );
+    if let Err(e) = stream.bridge(connection).await {
+        debug!("Tunnel {}: stream aborted: {}", tunnel_id, e);
     }
 }
 
@@ -274,22 +516,213 @@ impl Default for TunnelManager {
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_tunnel_message_serialization() {
-        let msg = TunnelMessage::TunnelRequest {
-            tunnel_id: Uuid::new_v4(),
-            bind_port: 8081,
-            tunnel_type: TunnelType::Tcp,
-        };
+    #[tokio::test]
+    async fn test_tunnel_requests() {
+        let (frames, _) = mpsc::unbounded_channel();
+        let manager = TunnelManager::new(frames);
+        let tunnel_id = manager.add("127.0.0.1:8081", TunnelType::Tcp);
+
+        let requests = manager.attach();
+        assert_eq!(
+            requests,
+            vec![TunnelMessage::TunnelRequest {
+                tunnel_id,
+                target: "127.0.0.1:8081".to_string(),
+                tunnel_type: TunnelType::Tcp,
+                public_port: None,
+            }]
+        );
+
+        // The assigned port is requested again after a reconnect
+        manager.handle_message(TunnelMessage::TunnelResponse {
+            tunnel_id,
+            success: true,
+            public_port: Some(20001),
+            error: None,
+        });
+        let requests = manager.attach();
+        assert!(matches!(
+            requests[0],
+            TunnelMessage::TunnelRequest {
+                public_port: Some(20001),
+                ..
+            }
+        ));
+
+        manager.stop_all();
+        assert!(manager.attach().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_assigned_tunnels() {
+        let (frames, _) = mpsc::unbounded_channel();
+        let manager = TunnelManager::new(frames);
+        manager.attach();
+        let tunnel_id = Uuid::new_v4();
+        manager.handle_message(TunnelMessage::TunnelAssign {
+            tunnel_id,
+            target: "127.0.0.1:8080".to_string(),
+            tunnel_type: TunnelType::Http,
+        });
+        assert!(manager.lock_tunnels().contains_key(&tunnel_id));
+
+        // Assigned tunnels are not requested, the Hub assigns them again
+        assert!(manager.attach().is_empty());
+        assert!(manager.lock_tunnels().is_empty());
+
+        manager.handle_message(TunnelMessage::TunnelAssign {
+            tunnel_id,
+            target: "127.0.0.1:8080".to_string(),
+            tunnel_type: TunnelType::Http,
+        });
+        manager.handle_message(TunnelMessage::TunnelClose {
+            tunnel_id,
+            reason: None,
+        });
+        assert!(manager.lock_tunnels().is_empty());
+    }
+
+    fn forward(
+        name: &str,
+        direction: ForwardDirection,
+        bind: Option<&str>,
+        target: &str,
+    ) -> ForwardConfig {
+        ForwardConfig {
+            name: name.to_string(),
+            direction,
+            bind: bind.map(str::to_string),
+            target: target.to_string(),
+            allowed_agents: Vec::new(),
+            allowed_sources: Vec::new(),
+            enabled: true,
+        }
+    }
+
+    fn report(messages: &[TunnelMessage]) -> Vec<ForwardStatus> {
+        match messages.last() {
+            Some(TunnelMessage::ForwardReport { forwards }) => forwards.clone(),
+            other => panic!("expected a ForwardReport, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_expose_forwards() {
+        let (frames, _) = mpsc::unbounded_channel();
+        let manager = TunnelManager::new(frames);
+        manager.add_forward(forward(
+            "web",
+            ForwardDirection::Expose,
+            Some("0.0.0.0:20080"),
+            "127.0.0.1:8080",
+        ));
+        manager.add_forward(forward(
+            "db",
+            ForwardDirection::Expose,
+            None,
+            "127.0.0.1:5432",
+        ));
+
+        // Only forwards with a bind address get a public port
+        let messages = manager.attach();
+        assert_eq!(messages.len(), 2);
+        let web = report(&messages)[0].tunnel_id;
+        assert!(matches!(
+            messages[0],
+            TunnelMessage::TunnelRequest { tunnel_id, public_port: Some(20080), .. } if tunnel_id == web
+        ));
+        assert!(report(&messages).iter().all(|status| status.active));
+
+        let messages = manager.handle_message(TunnelMessage::TunnelResponse {
+            tunnel_id: web,
+            success: true,
+            public_port: Some(20080),
+            error: None,
+        });
+        assert_eq!(report(&messages)[0].public_port, Some(20080));
+
+        let messages = manager.handle_message(TunnelMessage::ForwardStop {
+            name: "web".to_string(),
+        });
+        assert!(
+            matches!(messages[0], TunnelMessage::TunnelClose { tunnel_id, .. } if tunnel_id == web)
+        );
+        assert!(!report(&messages)[0].active);
+        assert!(!manager.lock_tunnels().contains_key(&web));
 
-        let json = serde_json::to_string(&msg).unwrap();
-        assert!(json.contains("\"type\":\"TunnelRequest\""));
-        assert!(json.contains("\"bind_port\":8081"));
+        // A stopped forward stays stopped after a reconnect
+        assert_eq!(manager.attach().len(), 1);
+
+        let messages = manager.handle_message(TunnelMessage::ForwardStart {
+            name: "web".to_string(),
+        });
+        assert!(matches!(messages[0], TunnelMessage::TunnelRequest { .. }));
+        assert!(report(&messages)[0].active);
+
+        let messages = manager.handle_message(TunnelMessage::ForwardStart {
+            name: "unknown".to_string(),
+        });
+        assert_eq!(messages.len(), 1);
     }
 
     #[tokio::test]
-    async fn test_tunnel_manager() {
-        let manager = TunnelManager::new();
-        assert_eq!(manager.tunnel_count().await, 0);
+    async fn test_connect_forward() {
+        let bind = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .to_string();
+        let (frames, mut frames_rx) = mpsc::unbounded_channel();
+        let manager = TunnelManager::new(frames);
+        let mut config = forward("db", ForwardDirection::Connect, Some(&bind), "office/db");
+        config.allowed_sources = vec![IpAddr::from([127, 0, 0, 1])];
+        config.enabled = false;
+        manager.add_forward(config);
+
+        let messages = manager.attach();
+        assert!(!report(&messages)[0].active);
+        let messages = manager.handle_message(TunnelMessage::ForwardStart {
+            name: "db".to_string(),
+        });
+        assert_eq!(messages.len(), 1);
+        let status = &report(&messages)[0];
+        assert!(status.active);
+
+        // Every local connection becomes a stream of the forward's tunnel
+        let _client = TcpStream::connect(&bind).await.unwrap();
+        let frame = TunnelFrame::decode(&frames_rx.recv().await.unwrap()).unwrap();
+        assert_eq!(
+            frame,
+            TunnelFrame::Open {
+                stream_id: 1,
+                tunnel_id: status.tunnel_id
+            }
+        );
+
+        manager.handle_message(TunnelMessage::ForwardStop {
+            name: "db".to_string(),
+        });
+        tokio::task::yield_now().await;
+        assert!(std::net::TcpListener::bind(&bind).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_unknown_tunnel_is_reset() {
+        let (frames, mut frames_rx) = mpsc::unbounded_channel();
+        let manager = TunnelManager::new(frames);
+        manager.attach();
+
+        manager.handle_frame(TunnelFrame::Open {
+            stream_id: 2,
+            tunnel_id: Uuid::new_v4(),
+        });
+        let frame = TunnelFrame::decode(&frames_rx.recv().await.unwrap()).unwrap();
+        assert!(matches!(
+            frame,
+            TunnelFrame::Close {
+                stream_id: 2,
+                error: Some(_)
+            }
+        ));
     }
 }
@@ -0,0 +1,388 @@
+//! Minimal TURN client (RFC 5766)
+//!
+//! Allocates a relayed address on a TURN server for the relayed ICE
+//! candidate. The allocation lives on the same UDP socket as the other
+//! candidates: data to a peer goes out in Send indications, data from a peer
+//! comes back in Data indications, and the server only relays for peers we
+//! created a permission for. Requests are authenticated with the long-term
+//! credentials after the server's 401 challenge.
+
+use md5::{Digest, Md5};
+use std::net::SocketAddr;
+use tracing::debug;
+
+use crate::config::TurnConfig;
+use crate::ice::{request, Transport};
+use crate::stun::{
+    self, Message, ATTR_NONCE, ATTR_REALM, ATTR_USERNAME, ATTR_XOR_MAPPED_ADDRESS,
+    ATTR_XOR_PEER_ADDRESS, ATTR_XOR_RELAYED_ADDRESS,
+};
+
+pub const ALLOCATE: u16 = 0x0003;
+pub const ALLOCATE_SUCCESS: u16 = 0x0103;
+pub const REFRESH: u16 = 0x0004;
+pub const CREATE_PERMISSION: u16 = 0x0008;
+pub const CREATE_PERMISSION_SUCCESS: u16 = 0x0108;
+pub const SEND_INDICATION: u16 = 0x0016;
+pub const DATA_INDICATION: u16 = 0x0017;
+pub const ATTR_LIFETIME: u16 = 0x000D;
+pub const ATTR_DATA: u16 = 0x0013;
+pub const ATTR_REQUESTED_TRANSPORT: u16 = 0x0019;
+
+/// Lifetime asked for in Refresh requests
+const LIFETIME_SECS: u32 = 600;
+/// REQUESTED-TRANSPORT value for UDP
+const TRANSPORT_UDP: [u8; 4] = [17, 0, 0, 0];
+
+/// An allocation on a TURN server
+#[derive(Debug, Clone)]
+pub struct TurnClient {
+    server: SocketAddr,
+    username: String,
+    realm: Vec<u8>,
+    nonce: Vec<u8>,
+    /// MD5(username:realm:password)
+    key: Vec<u8>,
+    password: String,
+    relayed: SocketAddr,
+    mapped: Option<SocketAddr>,
+}
+
+impl TurnClient {
+    /// Allocate a relayed address on the server of `config`
+    pub async fn allocate<T: Transport>(
+        transport: &T,
+        config: &TurnConfig,
+    ) -> Result<Self, String> {
+        let ipv6 = transport
+            .local_addr()
+            .map_err(|e| format!("Failed to get local addr: {}", e))?
+            .is_ipv6();
+        let server = stun::resolve(&config.server, ipv6).await?;
+        let mut client = Self {
+            server,
+            username: config.username.clone(),
+            realm: Vec::new(),
+            nonce: Vec::new(),
+            key: Vec::new(),
+            password: config.password.clone(),
+            relayed: server,
+            mapped: None,
+        };
+
+        let allocate =
+            || Message::request(ALLOCATE).with_attribute(ATTR_REQUESTED_TRANSPORT, TRANSPORT_UDP);
+        let mut response = request(transport, server, &allocate().encode(None)).await?;
+        // The first request is challenged for credentials, a stale nonce
+        // (438) is retried with the new one
+        for _ in 0..2 {
+            match response.error_code() {
+                Some(401 | 438) if client.update_nonce(&response) => {
+                    let msg = client.authenticated(allocate());
+                    response = request(transport, server, &msg).await?;
+                }
+                _ => break,
+            }
+        }
+        if response.msg_type != ALLOCATE_SUCCESS {
+            return Err(format!(
+                "TURN allocation on {} failed: {}",
+                config.server,
+                error_text(&response)
+            ));
+        }
+
+        client.relayed = response
+            .address(ATTR_XOR_RELAYED_ADDRESS)
+            .ok_or_else(|| "TURN response has no relayed address".to_string())?;
+        client.mapped = response.address(ATTR_XOR_MAPPED_ADDRESS);
+        debug!("TURN allocation {} on {}", client.relayed, server);
+        Ok(client)
+    }
+
+    /// Take REALM and NONCE from a challenge, false if it has none
+    fn update_nonce(&mut self, response: &Message) -> bool {
+        let (Some(realm), Some(nonce)) = (
+            response.attribute(ATTR_REALM),
+            response.attribute(ATTR_NONCE),
+        ) else {
+            return false;
+        };
+        self.realm = realm.to_vec();
+        self.nonce = nonce.to_vec();
+        let mut hasher = Md5::new();
+        hasher.update(self.username.as_bytes());
+        hasher.update(b":");
+        hasher.update(&self.realm);
+        hasher.update(b":");
+        hasher.update(self.password.as_bytes());
+        self.key = hasher.finalize().to_vec();
+        true
+    }
+
+    /// Encode a request with the long-term credentials
+    fn authenticated(&self, msg: Message) -> Vec<u8> {
+        msg.with_attribute(ATTR_USERNAME, self.username.as_bytes())
+            .with_attribute(ATTR_REALM, self.realm.clone())
+            .with_attribute(ATTR_NONCE, self.nonce.clone())
+            .encode(Some(&self.key))
+    }
+
+    pub fn server(&self) -> SocketAddr {
+        self.server
+    }
+
+    /// The relayed transport address, the relayed candidate
+    pub fn relayed_address(&self) -> SocketAddr {
+        self.relayed
+    }
+
+    /// Our address as seen by the TURN server
+    pub fn mapped_address(&self) -> Option<SocketAddr> {
+        self.mapped
+    }
+
+    /// A CreatePermission request letting `peers` send to the relayed address
+    pub fn create_permission(&self, peers: &[SocketAddr]) -> Vec<u8> {
+        let msg = peers
+            .iter()
+            .fold(Message::request(CREATE_PERMISSION), |msg, peer| {
+                msg.with_address(ATTR_XOR_PEER_ADDRESS, *peer)
+            });
+        self.authenticated(msg)
+    }
+
+    /// A Refresh request keeping the allocation alive
+    pub fn refresh(&self) -> Vec<u8> {
+        let msg =
+            Message::request(REFRESH).with_attribute(ATTR_LIFETIME, LIFETIME_SECS.to_be_bytes());
+        self.authenticated(msg)
+    }
+
+    /// A Send indication carrying `data` to `peer`
+    pub fn send_indication(&self, peer: SocketAddr, data: &[u8]) -> Vec<u8> {
+        Message::request(SEND_INDICATION)
+            .with_address(ATTR_XOR_PEER_ADDRESS, peer)
+            .with_attribute(ATTR_DATA, data)
+            .encode(None)
+    }
+
+    /// Handle a response from the server outside of a transaction
+    ///
+    /// Returns true when a stale nonce was replaced and the request should
+    /// be sent again.
+    pub fn handle_response(&mut self, response: &Message) -> bool {
+        match response.error_code() {
+            Some(438) => self.update_nonce(response),
+            Some(_) => {
+                debug!("TURN server {}: {}", self.server, error_text(response));
+                false
+            }
+            None if response.msg_type == CREATE_PERMISSION_SUCCESS => {
+                debug!("TURN server {} installed the permissions", self.server);
+                false
+            }
+            None => false,
+        }
+    }
+}
+
+/// The peer and data of a Data indication
+pub fn data_indication(msg: &Message) -> Option<(SocketAddr, Vec<u8>)> {
+    if msg.msg_type != DATA_INDICATION {
+        return None;
+    }
+    Some((
+        msg.address(ATTR_XOR_PEER_ADDRESS)?,
+        msg.attribute(ATTR_DATA)?.to_vec(),
+    ))
+}
+
+/// ERROR-CODE of a response as text
+fn error_text(response: &Message) -> String {
+    match response.attribute(stun::ATTR_ERROR_CODE) {
+        Some(value) if value.len() >= 4 => format!(
+            "{} {}",
+            response.error_code().unwrap_or_default(),
+            String::from_utf8_lossy(&value[4..])
+        ),
+        _ => format!("unexpected response 0x{:04x}", response.msg_type),
+    }
+}
+
+/// Start a TURN server on 127.0.0.5 that relays from 127.0.0.5 and accepts
+/// `username`/`password`, and return its address
+#[cfg(test)]
+pub(crate) async fn start_turn_server(
+    username: &'static str,
+    password: &'static str,
+) -> SocketAddr {
+    use std::collections::{HashMap, HashSet};
+    use std::net::{IpAddr, Ipv4Addr};
+    use std::sync::{Arc, Mutex};
+    use tokio::net::UdpSocket;
+
+    const REALM: &[u8] = b"domain-agent";
+    const NONCE: &[u8] = b"0123456789abcdef";
+    let ip = IpAddr::V4(Ipv4Addr::new(127, 0, 0, 5));
+    let server = Arc::new(UdpSocket::bind((ip, 0)).await.unwrap());
+    let mut hasher = Md5::new();
+    hasher.update(format!("{}:", username).as_bytes());
+    hasher.update(REALM);
+    hasher.update(format!(":{}", password).as_bytes());
+    let key = hasher.finalize().to_vec();
+
+    struct Allocation {
+        relay: Arc<UdpSocket>,
+        permissions: Arc<Mutex<HashSet<IpAddr>>>,
+    }
+    let mut allocations: HashMap<SocketAddr, Allocation> = HashMap::new();
+    let addr = server.local_addr().unwrap();
+    tokio::spawn(async move {
+        let mut buf = vec![0u8; 2048];
+        loop {
+            let (len, from) = server.recv_from(&mut buf).await.unwrap();
+            let Ok(msg) = Message::decode(&buf[..len]) else {
+                continue;
+            };
+            let reply = |msg_type: u16| Message::new(msg_type, msg.transaction_id);
+            let error = |code: u16| {
+                let mut value = vec![0, 0, (code / 100) as u8, (code % 100) as u8];
+                value.extend_from_slice(b"Error");
+                reply(msg.msg_type | 0x0110)
+                    .with_attribute(stun::ATTR_ERROR_CODE, value)
+                    .with_attribute(ATTR_REALM, REALM)
+                    .with_attribute(ATTR_NONCE, NONCE)
+                    .encode(None)
+            };
+            if msg.msg_type == SEND_INDICATION {
+                let Some(allocation) = allocations.get(&from) else {
+                    continue;
+                };
+                if let (Some(peer), Some(data)) =
+                    (msg.address(ATTR_XOR_PEER_ADDRESS), msg.attribute(ATTR_DATA))
+                {
+                    if allocation.permissions.lock().unwrap().contains(&peer.ip()) {
+                        allocation.relay.send_to(data, peer).await.ok();
+                    }
+                }
+                continue;
+            }
+            let user = msg.attribute(ATTR_USERNAME) == Some(username.as_bytes());
+            if !user || !stun::check_integrity(&buf[..len], &key) {
+                server.send_to(&error(401), from).await.ok();
+                continue;
+            }
+            let response = match msg.msg_type {
+                ALLOCATE => {
+                    let relay = Arc::new(UdpSocket::bind((ip, 0)).await.unwrap());
+                    let permissions = Arc::new(Mutex::new(HashSet::new()));
+                    let (relay_rx, permissions_rx, server_tx) =
+                        (relay.clone(), permissions.clone(), server.clone());
+                    tokio::spawn(async move {
+                        let mut buf = vec![0u8; 2048];
+                        while let Ok((len, peer)) = relay_rx.recv_from(&mut buf).await {
+                            if !permissions_rx.lock().unwrap().contains(&peer.ip()) {
+                                continue;
+                            }
+                            let indication = Message::request(DATA_INDICATION)
+                                .with_address(ATTR_XOR_PEER_ADDRESS, peer)
+                                .with_attribute(ATTR_DATA, &buf[..len])
+                                .encode(None);
+                            server_tx.send_to(&indication, from).await.ok();
+                        }
+                    });
+                    let relayed = relay.local_addr().unwrap();
+                    allocations.insert(from, Allocation { relay, permissions });
+                    reply(ALLOCATE_SUCCESS)
+                        .with_address(ATTR_XOR_RELAYED_ADDRESS, relayed)
+                        .with_address(ATTR_XOR_MAPPED_ADDRESS, from)
+                }
+                CREATE_PERMISSION => {
+                    if let Some(allocation) = allocations.get(&from) {
+                        let mut permissions = allocation.permissions.lock().unwrap();
+                        let peers = msg
+                            .attributes
+                            .iter()
+                            .filter(|(t, _)| *t == ATTR_XOR_PEER_ADDRESS);
+                        for (attr_type, value) in peers {
+                            let single = Message::new(0, msg.transaction_id)
+                                .with_attribute(*attr_type, value.clone());
+                            if let Some(peer) = single.address(*attr_type) {
+                                permissions.insert(peer.ip());
+                            }
+                        }
+                    }
+                    reply(CREATE_PERMISSION_SUCCESS)
+                }
+                other => reply(other | 0x0100),
+            };
+            server
+                .send_to(&response.encode(Some(&key)), from)
+                .await
+                .ok();
+        }
+    });
+    addr
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::UdpSocket;
+
+    #[test]
+    fn test_data_indication() {
+        let peer: SocketAddr = "203.0.113.9:40000".parse().unwrap();
+        let msg = Message::request(DATA_INDICATION)
+            .with_address(ATTR_XOR_PEER_ADDRESS, peer)
+            .with_attribute(ATTR_DATA, b"hello".to_vec());
+        let decoded = Message::decode(&msg.encode(None)).unwrap();
+        assert_eq!(data_indication(&decoded), Some((peer, b"hello".to_vec())));
+        assert_eq!(data_indication(&Message::request(SEND_INDICATION)), None);
+    }
+
+    #[tokio::test]
+    async fn test_allocate_and_relay() {
+        let server = start_turn_server("agent", "secret").await;
+        let config = |password: &str| TurnConfig {
+            server: server.to_string(),
+            username: "agent".to_string(),
+            password: password.to_string(),
+        };
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let err = TurnClient::allocate(&socket, &config("wrong"))
+            .await
+            .unwrap_err();
+        assert!(err.contains("401"), "{}", err);
+
+        let client = TurnClient::allocate(&socket, &config("secret"))
+            .await
+            .unwrap();
+        assert_eq!(client.server(), server);
+        assert_eq!(client.mapped_address(), Some(socket.local_addr().unwrap()));
+        let relayed = client.relayed_address();
+
+        // Without a permission the peer is not relayed
+        let peer = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let peer_addr = peer.local_addr().unwrap();
+        let response = request(&socket, server, &client.create_permission(&[peer_addr]))
+            .await
+            .unwrap();
+        assert_eq!(response.msg_type, CREATE_PERMISSION_SUCCESS);
+
+        peer.send_to(b"ping", relayed).await.unwrap();
+        let mut buf = [0u8; 512];
+        let (len, from) = socket.recv_from(&mut buf).await.unwrap();
+        assert_eq!(from, server);
+        let msg = Message::decode(&buf[..len]).unwrap();
+        assert_eq!(data_indication(&msg), Some((peer_addr, b"ping".to_vec())));
+
+        socket
+            .send_to(&client.send_indication(peer_addr, b"pong"), server)
+            .await
+            .unwrap();
+        let (len, from) = peer.recv_from(&mut buf).await.unwrap();
+        assert_eq!((&buf[..len], from), (&b"pong"[..], relayed));
+    }
+}
@@ -0,0 +1,340 @@
+//! AWS Signature Version 4（AWS4-HMAC-SHA256）
+//!
+//! 参考文档：https://docs.aws.amazon.com/IAM/latest/UserGuide/reference_sigv-create-signed-request.html
+
+use crate::{hmac256, read_response, sha256_hex};
+use chrono::DateTime;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use reqwest::{Client, Method, StatusCode};
+use std::collections::BTreeMap;
+
+/// 签名算法名称
+pub const SIGV4_ALGORITHM: &str = "AWS4-HMAC-SHA256";
+
+/// SigV4 中不需要编码的字符：字母、数字以及 `-`、`_`、`.`、`~`
+const SIGV4_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+/// 按 SigV4 规则对字符串进行URL编码
+pub fn sigv4_uri_encode(value: &str) -> String {
+    utf8_percent_encode(value, SIGV4_ENCODE_SET).to_string()
+}
+
+/// 构建规范化查询字符串：先编码再按参数名排序
+pub fn sigv4_canonical_query_string(query_params: &[(&str, &str)]) -> String {
+    let mut encoded: Vec<(String, String)> = query_params
+        .iter()
+        .map(|(k, v)| (sigv4_uri_encode(k), sigv4_uri_encode(v)))
+        .collect();
+    encoded.sort();
+
+    encoded
+        .iter()
+        .map(|(k, v)| format!("{}={}", k, v))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// SigV4 签名器
+///
+/// 一个签名器对应一组凭证以及固定的地域和服务，例如 Route 53 使用 `us-east-1` / `route53`。
+#[derive(Debug, Clone)]
+pub struct SigV4Signer {
+    access_key_id: String,
+    secret_access_key: String,
+    region: String,
+    service: String,
+}
+
+impl SigV4Signer {
+    pub fn new(
+        access_key_id: impl Into<String>,
+        secret_access_key: impl Into<String>,
+        region: impl Into<String>,
+        service: impl Into<String>,
+    ) -> Self {
+        Self {
+            access_key_id: access_key_id.into(),
+            secret_access_key: secret_access_key.into(),
+            region: region.into(),
+            service: service.into(),
+        }
+    }
+
+    /// 构建规范请求，返回 (规范请求, 参与签名的请求头列表)
+    ///
+    /// * `canonical_uri` - 已编码的请求路径，例如 `/2013-04-01/hostedzone`
+    /// * `headers` - 参与签名的请求头，至少包含 `host` 和 `x-amz-date`
+    pub fn canonical_request(
+        method: &str,
+        canonical_uri: &str,
+        query_params: &[(&str, &str)],
+        headers: &[(&str, &str)],
+        payload: &str,
+    ) -> (String, String) {
+        // 请求头名称转小写、值去除首尾空白并合并连续空格，然后按名称排序
+        let sorted_headers: BTreeMap<String, String> = headers
+            .iter()
+            .map(|(k, v)| {
+                (
+                    k.to_lowercase(),
+                    v.split_whitespace().collect::<Vec<_>>().join(" "),
+                )
+            })
+            .collect();
+
+        let canonical_headers: String = sorted_headers
+            .iter()
+            .map(|(k, v)| format!("{}:{}\n", k, v))
+            .collect();
+        let signed_headers = sorted_headers.keys().cloned().collect::<Vec<_>>().join(";");
+
+        let canonical_request = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            method,
+            if canonical_uri.is_empty() {
+                "/"
+            } else {
+                canonical_uri
+            },
+            sigv4_canonical_query_string(query_params),
+            canonical_headers,
+            signed_headers,
+            sha256_hex(payload)
+        );
+
+        (canonical_request, signed_headers)
+    }
+
+    /// 计算签名，`amz_date` 格式为 `20150830T123600Z`
+    pub fn signature(&self, canonical_request: &str, amz_date: &str) -> Result<String, String> {
+        let date = amz_date
+            .get(..8)
+            .ok_or_else(|| format!("Invalid x-amz-date: {}", amz_date))?;
+        let credential_scope = format!("{}/{}/{}/aws4_request", date, self.region, self.service);
+        let string_to_sign = format!(
+            "{}\n{}\n{}\n{}",
+            SIGV4_ALGORITHM,
+            amz_date,
+            credential_scope,
+            sha256_hex(canonical_request)
+        );
+
+        // 派生签名密钥
+        let k_date = hmac256(format!("AWS4{}", self.secret_access_key).as_bytes(), date)?;
+        let k_region = hmac256(&k_date, &self.region)?;
+        let k_service = hmac256(&k_region, &self.service)?;
+        let k_signing = hmac256(&k_service, "aws4_request")?;
+        let signature = hmac256(&k_signing, &string_to_sign)?;
+
+        Ok(hex::encode(signature))
+    }
+
+    /// 生成 `Authorization` 请求头的值
+    pub fn authorization(
+        &self,
+        method: &str,
+        canonical_uri: &str,
+        query_params: &[(&str, &str)],
+        headers: &[(&str, &str)],
+        payload: &str,
+        amz_date: &str,
+    ) -> Result<String, String> {
+        let (canonical_request, signed_headers) =
+            Self::canonical_request(method, canonical_uri, query_params, headers, payload);
+        let signature = self.signature(&canonical_request, amz_date)?;
+
+        Ok(format!(
+            "{} Credential={}/{}/{}/{}/aws4_request, SignedHeaders={}, Signature={}",
+            SIGV4_ALGORITHM,
+            self.access_key_id,
+            &amz_date[..8],
+            self.region,
+            self.service,
+            signed_headers,
+            signature
+        ))
+    }
+}
+
+/// 调用使用 SigV4 签名的 AWS 接口，返回状态码和响应体
+///
+/// * `endpoint` - 接口地址，例如 `https://route53.amazonaws.com`，测试时可指向本地服务
+/// * `canonical_uri` - 请求路径，例如 `/2013-04-01/hostedzone`
+/// * `payload` - 请求体，GET 请求传空字符串
+pub async fn call_aws_api(
+    client: Client,
+    signer: &SigV4Signer,
+    method: Method,
+    endpoint: &str,
+    canonical_uri: &str,
+    query_params: &[(&str, &str)],
+    payload: &str,
+) -> Result<(StatusCode, String), String> {
+    let host = endpoint
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .trim_end_matches('/');
+
+    let now_time =
+        crate::current_timestamp().map_err(|e| format!("Get current timestamp failed: {}", e))?;
+    let amz_date = DateTime::from_timestamp(now_time as i64, 0)
+        .ok_or_else(|| format!("Get datetime from timestamp failed: {}", now_time))?
+        .format("%Y%m%dT%H%M%SZ")
+        .to_string();
+
+    let headers = [("host", host), ("x-amz-date", amz_date.as_str())];
+    let authorization = signer.authorization(
+        method.as_str(),
+        canonical_uri,
+        query_params,
+        &headers,
+        payload,
+        &amz_date,
+    )?;
+
+    let url = format!(
+        "{}{}",
+        endpoint.trim_end_matches('/'),
+        if canonical_uri.is_empty() {
+            "/"
+        } else {
+            canonical_uri
+        }
+    );
+    let query_string = sigv4_canonical_query_string(query_params);
+    let url = if query_string.is_empty() {
+        url
+    } else {
+        format!("{}?{}", url, query_string)
+    };
+
+    let mut request_builder = client
+        .request(method, &url)
+        .header("Authorization", authorization)
+        .header("X-Amz-Date", &amz_date);
+
+    if !payload.is_empty() {
+        request_builder = request_builder
+            .header("Content-Type", "application/xml")
+            .body(payload.to_string());
+    }
+
+    let response = request_builder
+        .send()
+        .await
+        .map_err(|e| format!("execute request fail: {}", e))?;
+
+    read_response(response).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// AWS SigV4 测试套件（aws-sig-v4-test-suite）中使用的凭证
+    fn test_suite_signer() -> SigV4Signer {
+        SigV4Signer::new(
+            "AKIDEXAMPLE",
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            "us-east-1",
+            "service",
+        )
+    }
+
+    const AMZ_DATE: &str = "20150830T123600Z";
+    const HEADERS: [(&str, &str); 2] =
+        [("Host", "example.amazonaws.com"), ("X-Amz-Date", AMZ_DATE)];
+
+    #[test]
+    fn test_get_vanilla() {
+        let authorization = test_suite_signer()
+            .authorization("GET", "/", &[], &HEADERS, "", AMZ_DATE)
+            .unwrap();
+        assert_eq!(
+            authorization,
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+             SignedHeaders=host;x-amz-date, \
+             Signature=5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"
+        );
+    }
+
+    #[test]
+    fn test_post_vanilla() {
+        let (canonical_request, _) = SigV4Signer::canonical_request("POST", "/", &[], &HEADERS, "");
+        let signature = test_suite_signer()
+            .signature(&canonical_request, AMZ_DATE)
+            .unwrap();
+        assert_eq!(
+            signature,
+            "5da7c1a2acd57cee7505fc6676e4e544621c30862966e37dddb68e92efbe5d6b"
+        );
+    }
+
+    #[test]
+    fn test_get_vanilla_query_order_key_case() {
+        let (canonical_request, _) = SigV4Signer::canonical_request(
+            "GET",
+            "/",
+            &[("Param2", "value2"), ("Param1", "value1")],
+            &HEADERS,
+            "",
+        );
+        assert_eq!(
+            canonical_request,
+            "GET\n/\nParam1=value1&Param2=value2\n\
+             host:example.amazonaws.com\nx-amz-date:20150830T123600Z\n\n\
+             host;x-amz-date\n\
+             e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        let signature = test_suite_signer()
+            .signature(&canonical_request, AMZ_DATE)
+            .unwrap();
+        assert_eq!(
+            signature,
+            "b97d918cfa904a5beff61c982a1b6f458b799221646efd99d3219ec94cdf2500"
+        );
+    }
+
+    /// IAM 文档中的 ListUsers 示例
+    #[test]
+    fn test_iam_list_users_example() {
+        let signer = SigV4Signer::new(
+            "AKIDEXAMPLE",
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            "us-east-1",
+            "iam",
+        );
+        let headers = [
+            (
+                "Content-Type",
+                "application/x-www-form-urlencoded; charset=utf-8",
+            ),
+            ("Host", "iam.amazonaws.com"),
+            ("X-Amz-Date", AMZ_DATE),
+        ];
+        let authorization = signer
+            .authorization(
+                "GET",
+                "/",
+                &[("Action", "ListUsers"), ("Version", "2010-05-08")],
+                &headers,
+                "",
+                AMZ_DATE,
+            )
+            .unwrap();
+        assert!(authorization.ends_with(
+            "SignedHeaders=content-type;host;x-amz-date, \
+             Signature=5d672d79c15b13162d9279b0855cfba6789a8edb4c82c400e06b5924a6f2b5d7"
+        ));
+    }
+
+    #[test]
+    fn test_uri_encode_keeps_unreserved_characters() {
+        assert_eq!(sigv4_uri_encode("a-b_c.d~e"), "a-b_c.d~e");
+        assert_eq!(sigv4_uri_encode("a b/c=d"), "a%20b%2Fc%3Dd");
+    }
+}
@@ -13,6 +13,10 @@ use sha2::{Digest, Sha256};
 use std::borrow::Cow;
 use std::collections::{BTreeMap, HashMap};
 use std::time::{SystemTime, SystemTimeError};
+
+pub mod aws;
+pub mod google;
+pub mod tencent;
 // 加载base64 crate
 
 /// 获取当前时间戳（秒）
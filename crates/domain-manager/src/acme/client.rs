@@ -0,0 +1,396 @@
+//! ACME v2 协议客户端（RFC 8555）
+//!
+//! 只实现签发证书需要的部分：目录、随机数、账户注册、订单、授权、挑战、完成订单和下载证书。
+//! 所有请求都是 JWS 签名的 POST，服务端返回 `badNonce` 时使用新的随机数重试一次。
+
+use super::jws::AccountKey;
+use anyhow::{anyhow, bail, Context, Result};
+use reqwest::header::{HeaderMap, ACCEPT, CONTENT_TYPE, LOCATION};
+use reqwest::{Certificate, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::{sleep, Instant};
+use tracing::{debug, info};
+
+/// Let's Encrypt 正式环境
+pub const LETS_ENCRYPT_DIRECTORY: &str = "https://acme-v02.api.letsencrypt.org/directory";
+/// Let's Encrypt 测试环境
+pub const LETS_ENCRYPT_STAGING_DIRECTORY: &str =
+    "https://acme-staging-v02.api.letsencrypt.org/directory";
+
+const JOSE_CONTENT_TYPE: &str = "application/jose+json";
+const PEM_CHAIN_CONTENT_TYPE: &str = "application/pem-certificate-chain";
+const REPLAY_NONCE: &str = "replay-nonce";
+const BAD_NONCE: &str = "urn:ietf:params:acme:error:badNonce";
+/// 默认网络超时
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// ACME 目录
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Directory {
+    pub new_nonce: String,
+    pub new_account: String,
+    pub new_order: String,
+    #[serde(default)]
+    pub meta: Option<DirectoryMeta>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectoryMeta {
+    #[serde(default)]
+    pub terms_of_service: Option<String>,
+}
+
+/// 证书标识，目前只支持 DNS 名称
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Identifier {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub value: String,
+}
+
+impl Identifier {
+    pub fn dns(value: impl Into<String>) -> Self {
+        Self {
+            kind: "dns".to_string(),
+            value: value.into(),
+        }
+    }
+}
+
+/// 订单
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Order {
+    pub status: String,
+    #[serde(default)]
+    pub identifiers: Vec<Identifier>,
+    #[serde(default)]
+    pub authorizations: Vec<String>,
+    pub finalize: String,
+    #[serde(default)]
+    pub certificate: Option<String>,
+    #[serde(default)]
+    pub error: Option<Problem>,
+}
+
+/// 授权
+#[derive(Debug, Clone, Deserialize)]
+pub struct Authorization {
+    pub identifier: Identifier,
+    pub status: String,
+    #[serde(default)]
+    pub challenges: Vec<Challenge>,
+    #[serde(default)]
+    pub wildcard: bool,
+}
+
+/// 挑战
+#[derive(Debug, Clone, Deserialize)]
+pub struct Challenge {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub url: String,
+    #[serde(default)]
+    pub token: String,
+    pub status: String,
+    #[serde(default)]
+    pub error: Option<Problem>,
+}
+
+/// 错误文档（RFC 7807）
+#[derive(Debug, Clone, Deserialize)]
+pub struct Problem {
+    #[serde(rename = "type", default)]
+    pub kind: String,
+    #[serde(default)]
+    pub detail: String,
+}
+
+impl std::fmt::Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.detail, self.kind)
+    }
+}
+
+/// ACME 客户端
+#[derive(Debug)]
+pub struct AcmeClient {
+    http: reqwest::Client,
+    directory: Directory,
+    key: AccountKey,
+    /// 账户 URL，注册后才有
+    kid: Option<String>,
+    nonce: Mutex<Option<String>>,
+    poll_interval: Duration,
+    poll_timeout: Duration,
+}
+
+impl AcmeClient {
+    /// 读取目录并创建客户端
+    ///
+    /// `root_certificate` 为额外信任的 PEM 根证书，用于 Pebble 等使用自签名证书的测试服务器。
+    pub async fn connect(
+        directory_url: &str,
+        key: AccountKey,
+        root_certificate: Option<&[u8]>,
+    ) -> Result<Self> {
+        let mut builder = reqwest::Client::builder().timeout(DEFAULT_TIMEOUT);
+        if let Some(pem) = root_certificate {
+            let certificate = Certificate::from_pem(pem).context("无效的根证书")?;
+            builder = builder.add_root_certificate(certificate);
+        }
+        let http = builder.build().context("创建 HTTP 客户端失败")?;
+
+        let directory: Directory = http
+            .get(directory_url)
+            .send()
+            .await
+            .with_context(|| format!("读取 ACME 目录失败: {}", directory_url))?
+            .error_for_status()?
+            .json()
+            .await
+            .context("解析 ACME 目录失败")?;
+        debug!("ACME 目录: {:?}", directory);
+
+        Ok(Self {
+            http,
+            directory,
+            key,
+            kid: None,
+            nonce: Mutex::new(None),
+            poll_interval: Duration::from_secs(2),
+            poll_timeout: Duration::from_secs(120),
+        })
+    }
+
+    /// 使用已注册的账户 URL
+    pub fn with_account_url(mut self, account_url: impl Into<String>) -> Self {
+        self.kid = Some(account_url.into());
+        self
+    }
+
+    /// 设置轮询订单和授权状态的间隔与超时
+    pub fn with_polling(mut self, interval: Duration, timeout: Duration) -> Self {
+        self.poll_interval = interval;
+        self.poll_timeout = timeout;
+        self
+    }
+
+    pub fn directory(&self) -> &Directory {
+        &self.directory
+    }
+
+    pub fn key(&self) -> &AccountKey {
+        &self.key
+    }
+
+    pub fn account_url(&self) -> Option<&str> {
+        self.kid.as_deref()
+    }
+
+    /// 注册账户（同意服务条款），账户已存在时服务端返回原账户，返回账户 URL
+    pub async fn register_account(&mut self, contact: &[String]) -> Result<String> {
+        let payload = json!({
+            "termsOfServiceAgreed": true,
+            "contact": contact,
+        });
+        let url = self.directory.new_account.clone();
+        let response = self.post(&url, Some(&payload), false).await?;
+        let account_url = location(response.headers())?;
+        info!("ACME 账户已注册: {}", account_url);
+        self.kid = Some(account_url.clone());
+        Ok(account_url)
+    }
+
+    /// 创建订单，返回订单 URL 和订单内容
+    pub async fn new_order(&self, names: &[String]) -> Result<(String, Order)> {
+        let identifiers: Vec<Identifier> = names.iter().map(Identifier::dns).collect();
+        let payload = json!({ "identifiers": identifiers });
+        let url = self.directory.new_order.clone();
+        let response = self.post(&url, Some(&payload), true).await?;
+        let order_url = location(response.headers())?;
+        let order = response.json().await.context("解析订单失败")?;
+        Ok((order_url, order))
+    }
+
+    /// 查询订单
+    pub async fn order(&self, url: &str) -> Result<Order> {
+        self.post_as_get(url)
+            .await?
+            .json()
+            .await
+            .context("解析订单失败")
+    }
+
+    /// 查询授权
+    pub async fn authorization(&self, url: &str) -> Result<Authorization> {
+        self.post_as_get(url)
+            .await?
+            .json()
+            .await
+            .context("解析授权失败")
+    }
+
+    /// 通知服务端验证挑战
+    pub async fn respond_challenge(&self, url: &str) -> Result<Challenge> {
+        self.post(url, Some(&json!({})), true)
+            .await?
+            .json()
+            .await
+            .context("解析挑战失败")
+    }
+
+    /// 等待授权完成验证
+    pub async fn wait_authorization(&self, url: &str) -> Result<Authorization> {
+        let deadline = Instant::now() + self.poll_timeout;
+        loop {
+            let authorization = self.authorization(url).await?;
+            match authorization.status.as_str() {
+                "valid" => return Ok(authorization),
+                "pending" | "processing" => {}
+                status => {
+                    let reason = authorization
+                        .challenges
+                        .iter()
+                        .find_map(|challenge| challenge.error.as_ref())
+                        .map(|problem| problem.to_string())
+                        .unwrap_or_default();
+                    bail!(
+                        "{} 验证失败，状态为 {}: {}",
+                        authorization.identifier.value,
+                        status,
+                        reason
+                    );
+                }
+            }
+            if Instant::now() >= deadline {
+                bail!("等待 {} 验证超时", authorization.identifier.value);
+            }
+            sleep(self.poll_interval).await;
+        }
+    }
+
+    /// 提交 CSR 并等待订单签发，返回签发后的订单
+    pub async fn finalize(&self, order_url: &str, order: &Order, csr_der: &[u8]) -> Result<Order> {
+        let payload = json!({ "csr": super::jws::base64url(csr_der) });
+        self.post(&order.finalize, Some(&payload), true).await?;
+
+        let deadline = Instant::now() + self.poll_timeout;
+        loop {
+            let order = self.order(order_url).await?;
+            match order.status.as_str() {
+                "valid" => return Ok(order),
+                "pending" | "ready" | "processing" => {}
+                status => {
+                    let reason = order.error.map(|e| e.to_string()).unwrap_or_default();
+                    bail!("订单签发失败，状态为 {}: {}", status, reason);
+                }
+            }
+            if Instant::now() >= deadline {
+                bail!("等待订单签发超时");
+            }
+            sleep(self.poll_interval).await;
+        }
+    }
+
+    /// 下载 PEM 格式的证书链
+    pub async fn download_certificate(&self, url: &str) -> Result<String> {
+        self.post_as_get(url)
+            .await?
+            .text()
+            .await
+            .context("读取证书失败")
+    }
+
+    async fn post_as_get(&self, url: &str) -> Result<Response> {
+        self.post(url, None, true).await
+    }
+
+    /// 发送签名请求，返回成功的响应
+    async fn post(&self, url: &str, payload: Option<&Value>, use_kid: bool) -> Result<Response> {
+        let kid = match use_kid {
+            true => Some(
+                self.kid
+                    .as_deref()
+                    .ok_or_else(|| anyhow!("ACME 账户尚未注册"))?,
+            ),
+            false => None,
+        };
+
+        let mut retried = false;
+        loop {
+            let nonce = self.take_nonce().await?;
+            let body = self.key.sign(url, &nonce, kid, payload)?;
+            let response = self
+                .http
+                .post(url)
+                .header(CONTENT_TYPE, JOSE_CONTENT_TYPE)
+                .header(
+                    ACCEPT,
+                    format!("application/json, {}", PEM_CHAIN_CONTENT_TYPE),
+                )
+                .body(serde_json::to_vec(&body)?)
+                .send()
+                .await
+                .with_context(|| format!("ACME 请求失败: {}", url))?;
+            self.save_nonce(response.headers());
+
+            if response.status().is_success() {
+                return Ok(response);
+            }
+            let status = response.status();
+            let problem = response.json::<Problem>().await.ok();
+            match problem {
+                Some(problem) if problem.kind == BAD_NONCE && !retried => {
+                    debug!("ACME 随机数失效，重试: {}", url);
+                    retried = true;
+                }
+                Some(problem) => bail!("ACME 请求 {} 失败: {} {}", url, status, problem),
+                None => bail!("ACME 请求 {} 失败: {}", url, status),
+            }
+        }
+    }
+
+    /// 取出上次响应返回的随机数，没有时向服务端申请
+    async fn take_nonce(&self) -> Result<String> {
+        if let Some(nonce) = self.nonce.lock().ok().and_then(|mut nonce| nonce.take()) {
+            return Ok(nonce);
+        }
+        let response = self
+            .http
+            .head(&self.directory.new_nonce)
+            .send()
+            .await
+            .context("获取 ACME 随机数失败")?;
+        if !matches!(response.status(), StatusCode::OK | StatusCode::NO_CONTENT) {
+            bail!("获取 ACME 随机数失败: {}", response.status());
+        }
+        nonce_header(response.headers()).ok_or_else(|| anyhow!("响应中没有 Replay-Nonce"))
+    }
+
+    fn save_nonce(&self, headers: &HeaderMap) {
+        if let (Some(nonce), Ok(mut current)) = (nonce_header(headers), self.nonce.lock()) {
+            *current = Some(nonce);
+        }
+    }
+}
+
+fn nonce_header(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(REPLAY_NONCE)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+}
+
+fn location(headers: &HeaderMap) -> Result<String> {
+    headers
+        .get(LOCATION)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+        .ok_or_else(|| anyhow!("响应中没有 Location"))
+}
@@ -0,0 +1,226 @@
+//! DNS-01 挑战
+//!
+//! 通过域名所属账户的 `DnsClientTrait` 添加 `_acme-challenge` TXT 记录，
+//! 再直接向指定的 DNS 服务器查询，确认记录已经生效后才通知 ACME 服务端验证。
+
+use super::jws::base64url;
+use super::ChallengeSolver;
+use crate::api::provider::{create_dns_client_for_account, BoxedDnsClient};
+use crate::gui::model::domain::DomainName;
+use crate::model::dns_record_response::Type;
+use crate::propagation::resolver;
+use crate::storage::{accounts, domains};
+use crate::zone::migrate::relative_names;
+use crate::zone::ZoneRecord;
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use hickory_proto::rr::RecordType;
+use sea_orm::DatabaseConnection;
+use sha2::{Digest, Sha256};
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::time::{sleep, Instant};
+use tracing::{debug, info, warn};
+
+/// 挑战记录使用的 TTL，部分服务商不接受更小的值
+pub const CHALLENGE_TTL: u32 = 600;
+
+/// 默认用于确认记录生效的公共 DNS
+pub const DEFAULT_NAMESERVERS: [&str; 2] = ["8.8.8.8:53", "1.1.1.1:53"];
+
+/// 单次 DNS 查询超时
+const QUERY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 挑战记录的完整域名，通配符证书使用去掉 `*.` 后的名称
+pub fn challenge_name(identifier: &str) -> String {
+    format!(
+        "_acme-challenge.{}",
+        identifier.trim_start_matches("*.").trim_end_matches('.')
+    )
+}
+
+/// 挑战记录的值：密钥授权的 SHA-256 摘要
+pub fn challenge_value(key_authorization: &str) -> String {
+    base64url(Sha256::digest(key_authorization.as_bytes()))
+}
+
+/// 使用服务商接口完成 DNS-01 挑战
+pub struct Dns01Solver {
+    conn: DatabaseConnection,
+    /// 用于确认记录生效的 DNS 服务器，为空时不检查
+    nameservers: Vec<SocketAddr>,
+    propagation_timeout: Duration,
+    propagation_interval: Duration,
+}
+
+impl Dns01Solver {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self {
+            conn,
+            nameservers: DEFAULT_NAMESERVERS
+                .iter()
+                .filter_map(|addr| addr.parse().ok())
+                .collect(),
+            propagation_timeout: Duration::from_secs(300),
+            propagation_interval: Duration::from_secs(10),
+        }
+    }
+
+    /// 设置确认记录生效时查询的 DNS 服务器
+    pub fn with_nameservers(mut self, nameservers: Vec<SocketAddr>) -> Self {
+        self.nameservers = nameservers;
+        self
+    }
+
+    /// 设置等待记录生效的超时和查询间隔
+    pub fn with_propagation(mut self, timeout: Duration, interval: Duration) -> Self {
+        self.propagation_timeout = timeout;
+        self.propagation_interval = interval;
+        self
+    }
+
+    /// 找到挑战记录所属的域名，返回主机记录、域名和客户端
+    async fn target(&self, fqdn: &str) -> Result<(String, DomainName, BoxedDnsClient)> {
+        let domain = domains::find_domain_by_fqdn(&self.conn, fqdn)
+            .await
+            .map_err(|e| anyhow!("查询域名失败: {}", e))?
+            .ok_or_else(|| anyhow!("没有找到 {} 所属的域名，请先同步域名", fqdn))?;
+        let account = accounts::get_account_by_id(&self.conn, domain.account_id)
+            .await
+            .map_err(|e| anyhow!("查询账户失败: {}", e))?
+            .ok_or_else(|| anyhow!("账户 ID {} 不存在", domain.account_id))?;
+        let (provider, client) = create_dns_client_for_account(account)?;
+
+        let rr = fqdn[..fqdn.len() - domain.domain_name.len() - 1].to_string();
+        let domain_name = DomainName {
+            name: domain.domain_name,
+            provider,
+            ..Default::default()
+        };
+        Ok((rr, domain_name, client))
+    }
+
+    /// 等待所有 DNS 服务器都能查到挑战记录
+    async fn wait_propagation(&self, fqdn: &str, value: &str) -> Result<()> {
+        if self.nameservers.is_empty() {
+            return Ok(());
+        }
+
+        let deadline = Instant::now() + self.propagation_timeout;
+        let mut pending = self.nameservers.clone();
+        loop {
+            let mut still_pending = Vec::new();
+            for nameserver in pending {
+                match query_txt(nameserver, fqdn).await {
+                    Ok(values) if values.iter().any(|v| v == value) => {
+                        debug!("{} 已在 {} 生效", fqdn, nameserver);
+                    }
+                    Ok(_) => still_pending.push(nameserver),
+                    Err(e) => {
+                        debug!("向 {} 查询 {} 失败: {:#}", nameserver, fqdn, e);
+                        still_pending.push(nameserver);
+                    }
+                }
+            }
+            if still_pending.is_empty() {
+                info!("挑战记录 {} 已生效", fqdn);
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                let servers: Vec<String> = still_pending.iter().map(|s| s.to_string()).collect();
+                bail!("等待 {} 生效超时: {}", fqdn, servers.join(", "));
+            }
+            pending = still_pending;
+            sleep(self.propagation_interval).await;
+        }
+    }
+}
+
+#[async_trait]
+impl ChallengeSolver for Dns01Solver {
+    fn challenge_type(&self) -> &'static str {
+        "dns-01"
+    }
+
+    async fn present(&self, identifier: &str, _token: &str, key_authorization: &str) -> Result<()> {
+        let fqdn = challenge_name(identifier).to_ascii_lowercase();
+        let value = challenge_value(key_authorization);
+        let (rr, domain_name, client) = self.target(&fqdn).await?;
+
+        let record = ZoneRecord::new(rr.as_str(), Type::TXT, value.as_str(), CHALLENGE_TTL);
+        client
+            .add_dns_record(&domain_name, &record.to_record(String::new()))
+            .await
+            .with_context(|| format!("添加挑战记录 {} 失败", fqdn))?;
+        info!("已添加挑战记录 {} TXT {}", fqdn, value);
+
+        self.wait_propagation(&fqdn, &value).await
+    }
+
+    async fn cleanup(&self, identifier: &str, _token: &str, key_authorization: &str) -> Result<()> {
+        let fqdn = challenge_name(identifier).to_ascii_lowercase();
+        let value = challenge_value(key_authorization);
+        let (rr, domain_name, client) = self.target(&fqdn).await?;
+
+        let records = relative_names(
+            &domain_name.name,
+            client.list_dns_records(domain_name.name.clone()).await?,
+        );
+        let matched: Vec<_> = records
+            .iter()
+            .filter(|record| {
+                record.rr.eq_ignore_ascii_case(&rr)
+                    && record.record_type == Type::TXT
+                    && record.value.trim_matches('"') == value
+            })
+            .collect();
+        if matched.is_empty() {
+            warn!("没有找到要删除的挑战记录 {} TXT {}", fqdn, value);
+        }
+        for record in matched {
+            client
+                .delete_dns_record(&domain_name, &record.record_id)
+                .await
+                .with_context(|| format!("删除挑战记录 {} 失败", fqdn))?;
+            info!("已删除挑战记录 {} TXT {}", fqdn, value);
+        }
+        Ok(())
+    }
+}
+
+/// 通过 UDP 向指定 DNS 服务器查询 TXT 记录
+pub async fn query_txt(nameserver: SocketAddr, fqdn: &str) -> Result<Vec<String>> {
+    let response = resolver::query(nameserver, fqdn, RecordType::TXT, true, QUERY_TIMEOUT).await?;
+    Ok(resolver::answer_values(response.answers(), &Type::TXT))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_challenge_name_and_value() {
+        assert_eq!(
+            challenge_name("www.example.com"),
+            "_acme-challenge.www.example.com"
+        );
+        assert_eq!(
+            challenge_name("*.example.com."),
+            "_acme-challenge.example.com"
+        );
+        // SHA-256 摘要的 Base64url 编码固定为 43 个字符
+        let value = challenge_value("token.thumbprint");
+        assert_eq!(value.len(), 43);
+        assert_eq!(value, challenge_value("token.thumbprint"));
+        assert!(!value.contains(['+', '/', '=']));
+    }
+
+    #[tokio::test]
+    async fn test_query_txt_timeout() {
+        // 本地未监听的端口，查询应失败而不是一直等待
+        let socket = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = socket.local_addr().unwrap();
+        drop(socket);
+        assert!(query_txt(addr, "example.com").await.is_err());
+    }
+}
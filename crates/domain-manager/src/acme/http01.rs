@@ -0,0 +1,90 @@
+//! 通过 Agent 完成 HTTP-01 挑战
+//!
+//! 适用于没有 DNS 接口权限的主机：Hub 把 token 和密钥授权发给运行在该主机上的 Agent，
+//! Agent 将其写入网站根目录的 `/.well-known/acme-challenge/<token>`，
+//! 或者在 80 端口临时启动内置的 HTTP 服务提供，确认后 Hub 再通知 ACME 服务验证。
+
+use super::ChallengeSolver;
+use crate::agent::connection::AgentHubHandle;
+use crate::agent::model::Capability;
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use std::time::Duration;
+use tracing::info;
+use uuid::Uuid;
+
+/// 等待 Agent 响应的默认超时
+const DEFAULT_AGENT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// HTTP-01 挑战处理，由指定的 Agent 提供挑战内容
+pub struct Http01Solver {
+    hub: AgentHubHandle,
+    agent_id: Uuid,
+    timeout: Duration,
+}
+
+impl Http01Solver {
+    pub fn new(hub: AgentHubHandle, agent_id: Uuid) -> Self {
+        Self {
+            hub,
+            agent_id,
+            timeout: DEFAULT_AGENT_TIMEOUT,
+        }
+    }
+
+    /// 设置等待 Agent 响应的超时
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// 检查 Agent 在线并且可以处理 SSL 挑战
+    async fn check_agent(&self) -> Result<()> {
+        let agent = self
+            .hub
+            .registry()
+            .get(self.agent_id)
+            .await
+            .ok_or_else(|| anyhow!("Agent {} 未连接", self.agent_id))?;
+        if !agent.has_capability(&Capability::SslValidator) {
+            bail!("Agent {} 没有 SSL 验证能力", agent.name);
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ChallengeSolver for Http01Solver {
+    fn challenge_type(&self) -> &'static str {
+        "http-01"
+    }
+
+    async fn present(&self, identifier: &str, token: &str, key_authorization: &str) -> Result<()> {
+        if identifier.starts_with("*.") {
+            bail!("通配符域名 {} 只能使用 DNS-01 挑战", identifier);
+        }
+        self.check_agent().await?;
+        self.hub
+            .present_http_challenge(
+                self.agent_id,
+                identifier,
+                token,
+                key_authorization,
+                self.timeout,
+            )
+            .await
+            .map_err(|e| anyhow!("Agent 部署 {} 的挑战失败: {}", identifier, e))?;
+        info!(
+            "Agent {} 已部署 {} 的 HTTP-01 挑战",
+            self.agent_id, identifier
+        );
+        Ok(())
+    }
+
+    async fn cleanup(&self, identifier: &str, token: &str, _key_authorization: &str) -> Result<()> {
+        self.hub
+            .cleanup_http_challenge(self.agent_id, identifier, token, self.timeout)
+            .await
+            .map_err(|e| anyhow!("Agent 清理 {} 的挑战失败: {}", identifier, e))
+    }
+}
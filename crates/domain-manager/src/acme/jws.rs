@@ -0,0 +1,188 @@
+//! ACME 请求签名（RFC 7515 JWS，ES256）
+//!
+//! 账户密钥使用 P-256 ECDSA，以 PKCS#8 格式加密保存在数据库中。
+//! 注册账户时受保护头携带公钥 `jwk`，之后的请求改用账户 URL `kid`。
+
+use anyhow::{anyhow, Result};
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use ring::rand::SystemRandom;
+use ring::signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_FIXED_SIGNING};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+/// Base64url 编码（不带填充）
+pub fn base64url(data: impl AsRef<[u8]>) -> String {
+    URL_SAFE_NO_PAD.encode(data)
+}
+
+/// ACME 账户密钥
+pub struct AccountKey {
+    key_pair: EcdsaKeyPair,
+    pkcs8: Vec<u8>,
+    rng: SystemRandom,
+}
+
+impl AccountKey {
+    /// 随机生成账户密钥
+    pub fn generate() -> Result<Self> {
+        let rng = SystemRandom::new();
+        let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &rng)
+            .map_err(|_| anyhow!("生成账户密钥失败"))?;
+        Self::from_pkcs8(pkcs8.as_ref())
+    }
+
+    /// 从 PKCS#8 DER 加载账户密钥
+    pub fn from_pkcs8(pkcs8: &[u8]) -> Result<Self> {
+        let rng = SystemRandom::new();
+        let key_pair = EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, pkcs8, &rng)
+            .map_err(|e| anyhow!("无效的账户密钥: {}", e))?;
+        Ok(Self {
+            key_pair,
+            pkcs8: pkcs8.to_vec(),
+            rng,
+        })
+    }
+
+    /// 从 Base64 编码的 PKCS#8 加载账户密钥
+    pub fn from_base64(value: &str) -> Result<Self> {
+        let pkcs8 = STANDARD
+            .decode(value.trim())
+            .map_err(|e| anyhow!("账户密钥不是有效的Base64: {}", e))?;
+        Self::from_pkcs8(&pkcs8)
+    }
+
+    /// Base64 编码的 PKCS#8，用于加密后保存
+    pub fn to_base64(&self) -> String {
+        STANDARD.encode(&self.pkcs8)
+    }
+
+    /// 公钥的 JWK 表示
+    pub fn jwk(&self) -> Value {
+        // 未压缩的公钥格式为 0x04 || X || Y
+        let point = self.key_pair.public_key().as_ref();
+        json!({
+            "crv": "P-256",
+            "kty": "EC",
+            "x": base64url(&point[1..33]),
+            "y": base64url(&point[33..65]),
+        })
+    }
+
+    /// JWK 指纹（RFC 7638），用于计算挑战的密钥授权
+    pub fn thumbprint(&self) -> String {
+        let jwk = self.jwk();
+        // 指纹要求成员按字典序排列且没有空白
+        let canonical = format!(
+            r#"{{"crv":"P-256","kty":"EC","x":"{}","y":"{}"}}"#,
+            jwk["x"].as_str().unwrap_or_default(),
+            jwk["y"].as_str().unwrap_or_default()
+        );
+        base64url(Sha256::digest(canonical.as_bytes()))
+    }
+
+    /// 挑战的密钥授权：`token.指纹`
+    pub fn key_authorization(&self, token: &str) -> String {
+        format!("{}.{}", token, self.thumbprint())
+    }
+
+    /// 生成 JWS 请求体
+    ///
+    /// `kid` 为空时受保护头携带 `jwk`（仅用于注册账户）；`payload` 为空时是 POST-as-GET 请求。
+    pub fn sign(
+        &self,
+        url: &str,
+        nonce: &str,
+        kid: Option<&str>,
+        payload: Option<&Value>,
+    ) -> Result<Value> {
+        let mut protected = json!({
+            "alg": "ES256",
+            "nonce": nonce,
+            "url": url,
+        });
+        match kid {
+            Some(kid) => protected["kid"] = json!(kid),
+            None => protected["jwk"] = self.jwk(),
+        }
+
+        let protected = base64url(serde_json::to_vec(&protected)?);
+        let payload = match payload {
+            Some(payload) => base64url(serde_json::to_vec(payload)?),
+            None => String::new(),
+        };
+        let signature = self
+            .key_pair
+            .sign(&self.rng, format!("{}.{}", protected, payload).as_bytes())
+            .map_err(|_| anyhow!("签名 ACME 请求失败"))?;
+
+        Ok(json!({
+            "protected": protected,
+            "payload": payload,
+            "signature": base64url(signature.as_ref()),
+        }))
+    }
+}
+
+impl std::fmt::Debug for AccountKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("AccountKey([REDACTED])")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ring::signature::{UnparsedPublicKey, ECDSA_P256_SHA256_FIXED};
+
+    #[test]
+    fn test_key_round_trip() {
+        let key = AccountKey::generate().unwrap();
+        let loaded = AccountKey::from_base64(&key.to_base64()).unwrap();
+        assert_eq!(key.jwk(), loaded.jwk());
+        assert_eq!(key.thumbprint(), loaded.thumbprint());
+        assert_eq!(key.thumbprint().len(), 43);
+        assert!(AccountKey::from_base64("bm90IGEga2V5").is_err());
+    }
+
+    #[test]
+    fn test_signature_verifies() {
+        let key = AccountKey::generate().unwrap();
+        let payload = json!({"termsOfServiceAgreed": true});
+        let jws = key
+            .sign(
+                "https://acme.test/new-acct",
+                "nonce-1",
+                None,
+                Some(&payload),
+            )
+            .unwrap();
+
+        let protected = URL_SAFE_NO_PAD
+            .decode(jws["protected"].as_str().unwrap())
+            .unwrap();
+        let protected: Value = serde_json::from_slice(&protected).unwrap();
+        assert_eq!(protected["alg"], "ES256");
+        assert_eq!(protected["nonce"], "nonce-1");
+        assert_eq!(protected["jwk"], key.jwk());
+        assert!(protected.get("kid").is_none());
+
+        let signing_input = format!(
+            "{}.{}",
+            jws["protected"].as_str().unwrap(),
+            jws["payload"].as_str().unwrap()
+        );
+        let signature = URL_SAFE_NO_PAD
+            .decode(jws["signature"].as_str().unwrap())
+            .unwrap();
+        UnparsedPublicKey::new(&ECDSA_P256_SHA256_FIXED, key.key_pair.public_key().as_ref())
+            .verify(signing_input.as_bytes(), &signature)
+            .unwrap();
+
+        // POST-as-GET 的载荷为空字符串
+        let jws = key
+            .sign("https://acme.test/order/1", "nonce-2", Some("kid"), None)
+            .unwrap();
+        assert_eq!(jws["payload"], "");
+    }
+}
@@ -0,0 +1,323 @@
+//! ACME 证书申请
+//!
+//! - [`client`]：ACME v2 协议客户端（RFC 8555）
+//! - [`jws`]：账户密钥与请求签名
+//! - [`dns01`]：通过域名所属账户的 `DnsClientTrait` 完成 DNS-01 挑战
+//! - [`http01`]：由运行在目标主机上的 Agent 完成 HTTP-01 挑战
+//!
+//! [`issue_certificate`] 串起完整流程：加载或注册 ACME 账户、创建订单、完成挑战、
+//! 提交 CSR、下载证书，最后把证书和加密后的私钥保存到 `storage`。
+//! 无论成功与否，添加的挑战记录都会被清理。
+//!
+//! 本地测试可以使用 [Pebble](https://github.com/letsencrypt/pebble)：
+//! 目录地址为 `https://localhost:14000/dir`，通过 `root_certificate` 信任 Pebble 的测试根证书，
+//! 并让 Pebble 的 `-dnsserver` 指向实际托管该区域的 DNS 服务器（如启用 RFC 2136 的 BIND）。
+//! 验证 HTTP-01 时用 `-httpport` 指定 Agent 提供挑战内容的端口。
+//! `tests::acme_tests` 中的 Pebble 测试在设置 `PEBBLE_DIRECTORY_URL` 后运行。
+
+pub mod client;
+pub mod dns01;
+pub mod http01;
+pub mod jws;
+
+pub use client::{AcmeClient, LETS_ENCRYPT_DIRECTORY};
+pub use dns01::Dns01Solver;
+pub use http01::Http01Solver;
+pub use jws::AccountKey;
+
+use crate::storage::certificates::{self, NewAcmeAccount, NewCertificate};
+use crate::storage::domains;
+use crate::storage::entities::certificate;
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDateTime};
+use rcgen::{CertificateParams, DistinguishedName, DnType, KeyPair};
+use sea_orm::DatabaseConnection;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// 挑战处理方式
+#[async_trait]
+pub trait ChallengeSolver: Send + Sync {
+    /// 挑战类型，如 `dns-01`
+    fn challenge_type(&self) -> &'static str;
+
+    /// 部署挑战，返回后 ACME 服务端应当能够验证
+    async fn present(&self, identifier: &str, token: &str, key_authorization: &str) -> Result<()>;
+
+    /// 清理部署的挑战
+    async fn cleanup(&self, identifier: &str, token: &str, key_authorization: &str) -> Result<()>;
+}
+
+/// 证书申请参数
+#[derive(Debug, Clone, Default)]
+pub struct CertificateRequest {
+    /// 证书包含的域名，第一个为通用名称，支持 `*.example.com`
+    pub names: Vec<String>,
+    /// 联系邮箱，仅在注册新账户时使用
+    pub contact: Vec<String>,
+    pub directory_url: String,
+    /// 额外信任的 PEM 根证书，用于 Pebble 等测试服务器
+    pub root_certificate: Option<Vec<u8>>,
+    /// 轮询订单和授权状态的间隔与超时，为空时使用客户端默认值
+    pub polling: Option<(Duration, Duration)>,
+}
+
+/// 已部署的挑战，结束时清理
+struct Presented {
+    identifier: String,
+    token: String,
+    key_authorization: String,
+}
+
+/// 申请证书并保存到 `storage`
+pub async fn issue_certificate(
+    conn: &DatabaseConnection,
+    request: &CertificateRequest,
+    solver: &dyn ChallengeSolver,
+) -> Result<certificate::Model> {
+    let names = normalize_names(&request.names)?;
+    let client = open_account(conn, request).await?;
+
+    let (order_url, order) = client.new_order(&names).await?;
+    info!("ACME 订单已创建: {} {:?}", order_url, names);
+
+    let mut presented = Vec::new();
+    let result =
+        complete_authorizations(&client, &order.authorizations, solver, &mut presented).await;
+    for challenge in &presented {
+        if let Err(e) = solver
+            .cleanup(
+                &challenge.identifier,
+                &challenge.token,
+                &challenge.key_authorization,
+            )
+            .await
+        {
+            warn!("清理 {} 的挑战失败: {:#}", challenge.identifier, e);
+        }
+    }
+    result?;
+
+    let key_pair = KeyPair::generate().context("生成证书私钥失败")?;
+    let csr = certificate_signing_request(&names, &key_pair)?;
+    let order = client.finalize(&order_url, &order, &csr).await?;
+    let certificate_url = order
+        .certificate
+        .ok_or_else(|| anyhow!("订单已签发但没有证书地址"))?;
+    let certificate_pem = client.download_certificate(&certificate_url).await?;
+    let (not_before, not_after) = certificate_validity(&certificate_pem)?;
+
+    let domain_id = domains::find_domain_by_fqdn(conn, names[0].trim_start_matches("*."))
+        .await
+        .map_err(|e| anyhow!("查询域名失败: {}", e))?
+        .map(|domain| domain.id);
+    let model = certificates::save_certificate(
+        conn,
+        NewCertificate {
+            domain_id,
+            names,
+            directory_url: request.directory_url.clone(),
+            certificate_pem,
+            private_key_pem: key_pair.serialize_pem(),
+            not_before,
+            not_after,
+        },
+    )
+    .await
+    .map_err(|e| anyhow!(e))?;
+    Ok(model)
+}
+
+/// 使用保存的 ACME 账户，没有时注册新账户
+async fn open_account(
+    conn: &DatabaseConnection,
+    request: &CertificateRequest,
+) -> Result<AcmeClient> {
+    let root_certificate = request.root_certificate.as_deref();
+    let saved = certificates::find_acme_account(conn, &request.directory_url)
+        .await
+        .map_err(|e| anyhow!(e))?;
+
+    let mut client = match &saved {
+        Some(account) => {
+            let key = certificates::open_account_key(account).map_err(|e| anyhow!(e))?;
+            AcmeClient::connect(
+                &request.directory_url,
+                AccountKey::from_base64(&key)?,
+                root_certificate,
+            )
+            .await?
+            .with_account_url(account.account_url.clone())
+        }
+        None => {
+            AcmeClient::connect(
+                &request.directory_url,
+                AccountKey::generate()?,
+                root_certificate,
+            )
+            .await?
+        }
+    };
+    if let Some((interval, timeout)) = request.polling {
+        client = client.with_polling(interval, timeout);
+    }
+
+    if saved.is_none() {
+        let contact: Vec<String> = request
+            .contact
+            .iter()
+            .map(|email| match email.starts_with("mailto:") {
+                true => email.clone(),
+                false => format!("mailto:{}", email),
+            })
+            .collect();
+        let account_url = client.register_account(&contact).await?;
+        certificates::save_acme_account(
+            conn,
+            NewAcmeAccount {
+                directory_url: request.directory_url.clone(),
+                contact: (!request.contact.is_empty()).then(|| request.contact.join(",")),
+                account_url,
+                key_data: client.key().to_base64(),
+            },
+        )
+        .await
+        .map_err(|e| anyhow!(e))?;
+    }
+    Ok(client)
+}
+
+/// 部署所有待验证授权的挑战，通知服务端验证并等待结果
+async fn complete_authorizations(
+    client: &AcmeClient,
+    authorizations: &[String],
+    solver: &dyn ChallengeSolver,
+    presented: &mut Vec<Presented>,
+) -> Result<()> {
+    let mut pending = Vec::new();
+    for url in authorizations {
+        let authorization = client.authorization(url).await?;
+        if authorization.status == "valid" {
+            continue;
+        }
+        let identifier = match authorization.wildcard {
+            true => format!("*.{}", authorization.identifier.value),
+            false => authorization.identifier.value.clone(),
+        };
+        let challenge = authorization
+            .challenges
+            .iter()
+            .find(|challenge| challenge.kind == solver.challenge_type())
+            .ok_or_else(|| anyhow!("{} 不支持 {} 挑战", identifier, solver.challenge_type()))?;
+
+        let key_authorization = client.key().key_authorization(&challenge.token);
+        presented.push(Presented {
+            identifier: identifier.clone(),
+            token: challenge.token.clone(),
+            key_authorization: key_authorization.clone(),
+        });
+        solver
+            .present(&identifier, &challenge.token, &key_authorization)
+            .await?;
+        pending.push((url, challenge.url.clone()));
+    }
+
+    for (_, challenge_url) in &pending {
+        client.respond_challenge(challenge_url).await?;
+    }
+    for (url, _) in &pending {
+        client.wait_authorization(url).await?;
+    }
+    Ok(())
+}
+
+/// 去除首尾空白和末尾的点，统一为小写并去重
+fn normalize_names(names: &[String]) -> Result<Vec<String>> {
+    let mut normalized: Vec<String> = Vec::new();
+    for name in names {
+        let name = name.trim().trim_end_matches('.').to_ascii_lowercase();
+        if name.is_empty() {
+            continue;
+        }
+        if name.strip_prefix("*.").unwrap_or(&name).contains('*') {
+            bail!("无效的域名: {}", name);
+        }
+        if !normalized.contains(&name) {
+            normalized.push(name);
+        }
+    }
+    if normalized.is_empty() {
+        bail!("至少需要一个域名");
+    }
+    Ok(normalized)
+}
+
+/// 生成 DER 格式的 CSR
+fn certificate_signing_request(names: &[String], key_pair: &KeyPair) -> Result<Vec<u8>> {
+    let mut params = CertificateParams::new(names.to_vec()).context("无效的证书域名")?;
+    let mut distinguished_name = DistinguishedName::new();
+    distinguished_name.push(DnType::CommonName, names[0].as_str());
+    params.distinguished_name = distinguished_name;
+    let csr = params
+        .serialize_request(key_pair)
+        .context("生成证书签名请求失败")?;
+    Ok(csr.der().to_vec())
+}
+
+/// 读取证书链中第一张证书的有效期
+pub fn certificate_validity(certificate_pem: &str) -> Result<(NaiveDateTime, NaiveDateTime)> {
+    let (_, pem) = x509_parser::pem::parse_x509_pem(certificate_pem.as_bytes())
+        .map_err(|e| anyhow!("无效的 PEM 证书: {}", e))?;
+    let certificate = pem
+        .parse_x509()
+        .map_err(|e| anyhow!("解析证书失败: {}", e))?;
+    let validity = certificate.validity();
+    let timestamp = |time: &x509_parser::time::ASN1Time| {
+        DateTime::from_timestamp(time.timestamp(), 0)
+            .map(|time| time.naive_utc())
+            .ok_or_else(|| anyhow!("证书有效期超出范围"))
+    };
+    Ok((
+        timestamp(&validity.not_before)?,
+        timestamp(&validity.not_after)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_names() {
+        let names = vec![
+            " WWW.Example.com. ".to_string(),
+            "*.example.com".to_string(),
+            "www.example.com".to_string(),
+            String::new(),
+        ];
+        assert_eq!(
+            normalize_names(&names).unwrap(),
+            vec!["www.example.com", "*.example.com"]
+        );
+        assert!(normalize_names(&[String::new()]).is_err());
+        assert!(normalize_names(&["a.*.example.com".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_csr_and_validity() {
+        let names = vec!["example.com".to_string(), "*.example.com".to_string()];
+        let key_pair = KeyPair::generate().unwrap();
+        assert!(!certificate_signing_request(&names, &key_pair)
+            .unwrap()
+            .is_empty());
+
+        let certificate = CertificateParams::new(names)
+            .unwrap()
+            .self_signed(&key_pair)
+            .unwrap();
+        let (not_before, not_after) = certificate_validity(&certificate.pem()).unwrap();
+        assert!(not_before < not_after);
+        assert!(certificate_validity("not a certificate").is_err());
+    }
+}
@@ -5,18 +5,33 @@
 use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 
+use futures_util::stream::{SplitSink, SplitStream};
 use futures_util::{SinkExt, StreamExt};
+use sea_orm::DatabaseConnection;
 use sha2::{Sha256, Digest};
 use tokio::net::{TcpListener, TcpStream};
-use tokio::sync::RwLock;
+use tokio::sync::{oneshot, Mutex, RwLock};
 use tokio_tungstenite::{accept_async, tungstenite::Message, WebSocketStream};
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
-use super::model::{Agent, AgentStatus};
-use super::protocol::{AgentMessage, AgentMessage::*, TunnelType};
+use super::ddns;
+use super::model::{Agent, AgentStatus, Capability};
+use super::protocol::{AgentMessage, AgentMessage::*, CertProbeReport, SslChallengeType, TunnelType};
 use super::registry::AgentRegistry;
+use crate::certmon::{self, CertCheck};
+use crate::storage::ddns_events::{self, NewDdnsEvent};
+
+/// 已连接的 Agent：Agent ID -> 连接
+type Connections = Arc<RwLock<HashMap<Uuid, Arc<RwLock<AgentConnection>>>>>;
+
+/// 等待 Agent 响应的 SSL 挑战：token -> 结果发送端
+type PendingChallenges = Arc<Mutex<HashMap<String, oneshot::Sender<Result<(), String>>>>>;
+
+/// 等待 Agent 上报的证书探测：请求 ID -> 结果发送端
+type PendingProbes = Arc<Mutex<HashMap<Uuid, oneshot::Sender<Vec<CertProbeReport>>>>>;
 
 /// 计算密钥哈希
 fn hash_key(key: &str) -> String {
@@ -37,7 +52,8 @@ pub struct ConnectionInfo {
 pub struct AgentConnection {
     pub agent_id: Uuid,
     pub session_id: String,
-    pub ws_stream: Option<WebSocketStream<TcpStream>>,
+    /// WebSocket 发送端，接收端由连接任务单独读取，发送时不必等待接收
+    pub ws_sink: Option<SplitSink<WebSocketStream<TcpStream>, Message>>,
     pub tunnels: HashMap<Uuid, TunnelInfo>,
     pub p2p_connections: HashMap<Uuid, P2pConnectionInfo>,
 }
@@ -62,7 +78,7 @@ impl AgentConnection {
         Self {
             agent_id,
             session_id,
-            ws_stream: None,
+            ws_sink: None,
             tunnels: HashMap::new(),
             p2p_connections: HashMap::new(),
         }
@@ -70,7 +86,7 @@ impl AgentConnection {
 
     /// 发送消息
     pub async fn send(&mut self, msg: &AgentMessage) -> Result<(), String> {
-        if let Some(ref mut ws) = self.ws_stream {
+        if let Some(ref mut ws) = self.ws_sink {
             let json = serde_json::to_string(msg)
                 .map_err(|e| format!("序列化消息失败: {}", e))?;
             ws.send(Message::Text(json.into()))
@@ -82,36 +98,10 @@ impl AgentConnection {
         }
     }
 
-    /// 接收消息
-    pub async fn recv(&mut self) -> Result<AgentMessage, String> {
-        if let Some(ref mut ws) = self.ws_stream {
-            match ws.next().await {
-                Some(Ok(Message::Text(text))) => {
-                    serde_json::from_str(&text)
-                        .map_err(|e| format!("解析消息失败: {}", e))
-                }
-                Some(Ok(Message::Close(_))) => {
-                    Err("连接已关闭".to_string())
-                }
-                Some(Ok(_)) => {
-                    Err("不支持的消息类型".to_string())
-                }
-                Some(Err(e)) => {
-                    Err(format!("接收消息错误: {}", e))
-                }
-                None => {
-                    Err("流已结束".to_string())
-                }
-            }
-        } else {
-            Err("WebSocket 未连接".to_string())
-        }
-    }
-
     /// 关闭连接
     pub async fn close(mut self) -> Result<(), String> {
-        if let Some(ref mut ws) = self.ws_stream {
-            ws.close(None).await.map_err(|e| format!("关闭连接失败: {}", e))?;
+        if let Some(ref mut ws) = self.ws_sink {
+            ws.close().await.map_err(|e| format!("关闭连接失败: {}", e))?;
         }
         Ok(())
     }
@@ -152,14 +142,42 @@ impl AgentConnection {
     }
 }
 
+/// 接收消息
+async fn recv_message(
+    stream: &mut SplitStream<WebSocketStream<TcpStream>>,
+) -> Result<AgentMessage, String> {
+    match stream.next().await {
+        Some(Ok(Message::Text(text))) => {
+            serde_json::from_str(&text)
+                .map_err(|e| format!("解析消息失败: {}", e))
+        }
+        Some(Ok(Message::Close(_))) => {
+            Err("连接已关闭".to_string())
+        }
+        Some(Ok(_)) => {
+            Err("不支持的消息类型".to_string())
+        }
+        Some(Err(e)) => {
+            Err(format!("接收消息错误: {}", e))
+        }
+        None => {
+            Err("流已结束".to_string())
+        }
+    }
+}
+
 /// Agent Hub - 管理所有 Agent 连接
 pub struct AgentHub {
     registry: Arc<AgentRegistry>,
-    connections: Arc<RwLock<HashMap<Uuid, Arc<RwLock<AgentConnection>>>>>,
+    connections: Connections,
     listener: Option<TcpListener>,
     listen_addr: String,
     /// 已注册的密钥哈希（用于简单验证，生产环境应从数据库加载）
     registered_keys: Arc<RwLock<HashMap<String, Uuid>>>,
+    /// 用于更新 DDNS 记录和保存事件，未设置时拒绝 DDNS 请求
+    database: Option<DatabaseConnection>,
+    challenges: PendingChallenges,
+    probes: PendingProbes,
 }
 
 impl AgentHub {
@@ -171,6 +189,25 @@ impl AgentHub {
             listener: None,
             listen_addr: listen_addr.to_string(),
             registered_keys: Arc::new(RwLock::new(HashMap::new())),
+            database: None,
+            challenges: Arc::new(Mutex::new(HashMap::new())),
+            probes: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// 设置数据库连接，启用 DDNS 更新和保存 Agent 定时上报的证书检查结果
+    pub fn with_database(mut self, database: DatabaseConnection) -> Self {
+        self.database = Some(database);
+        self
+    }
+
+    /// 获取句柄，`run` 占用 Hub 后仍可通过句柄向 Agent 发送请求
+    pub fn handle(&self) -> AgentHubHandle {
+        AgentHubHandle {
+            registry: self.registry.clone(),
+            connections: self.connections.clone(),
+            challenges: self.challenges.clone(),
+            probes: self.probes.clone(),
         }
     }
 
@@ -215,11 +252,14 @@ impl AgentHub {
                     let registry = self.registry.clone();
                     let connections = Arc::clone(&self.connections);
                     let registered_keys = self.registered_keys.clone();
+                    let database = self.database.clone();
+                    let challenges = self.challenges.clone();
+                    let probes = self.probes.clone();
 
                     info!("收到来自 {} 的连接", addr);
 
                     tokio::spawn(async move {
-                        if let Err(e) = handle_new_connection(registry, connections, stream, addr, registered_keys).await {
+                        if let Err(e) = handle_new_connection(registry, connections, stream, addr, registered_keys, database, challenges, probes).await {
                             error!("处理连接失败: {}", e);
                         }
                     });
@@ -233,14 +273,20 @@ impl AgentHub {
 
     /// 向指定 Agent 发送消息
     pub async fn send_to(&self, agent_id: Uuid, msg: &AgentMessage) -> Result<(), String> {
-        let connections = self.connections.read().await;
+        self.handle().send_to(agent_id, msg).await
+    }
 
-        if let Some(conn) = connections.get(&agent_id) {
-            let mut conn = conn.write().await;
-            conn.send(msg).await
-        } else {
-            Err(format!("Agent {} 未连接", agent_id))
-        }
+    /// 请求 Agent 立即检查公网 IP 并更新记录
+    pub async fn request_ddns_update(
+        &self,
+        agent_id: Uuid,
+        domain: &str,
+        record_type: &str,
+    ) -> Result<(), String> {
+        self.send_to(agent_id, &DdnsUpdateRequest {
+            domain: domain.to_string(),
+            record_type: record_type.to_string(),
+        }).await
     }
 
     /// 广播消息到所有连接的 Agent
@@ -268,17 +314,132 @@ impl AgentHub {
     }
 }
 
+/// Agent Hub 句柄
+#[derive(Clone)]
+pub struct AgentHubHandle {
+    registry: Arc<AgentRegistry>,
+    connections: Connections,
+    challenges: PendingChallenges,
+    probes: PendingProbes,
+}
+
+impl AgentHubHandle {
+    /// Agent 注册表
+    pub fn registry(&self) -> &Arc<AgentRegistry> {
+        &self.registry
+    }
+
+    /// 向指定 Agent 发送消息
+    pub async fn send_to(&self, agent_id: Uuid, msg: &AgentMessage) -> Result<(), String> {
+        let conn = self.connections.read().await.get(&agent_id).cloned();
+        match conn {
+            Some(conn) => conn.write().await.send(msg).await,
+            None => Err(format!("Agent {} 未连接", agent_id)),
+        }
+    }
+
+    /// 请求 Agent 提供 HTTP-01 挑战内容，等待 Agent 确认
+    pub async fn present_http_challenge(
+        &self,
+        agent_id: Uuid,
+        domain: &str,
+        token: &str,
+        key_authorization: &str,
+        timeout: Duration,
+    ) -> Result<(), String> {
+        let request = SslChallengeRequest {
+            domain: domain.to_string(),
+            challenge_type: SslChallengeType::Http01,
+            token: token.to_string(),
+            key_authorization: Some(key_authorization.to_string()),
+            cleanup: false,
+        };
+        self.ssl_challenge(agent_id, &request, token, timeout).await
+    }
+
+    /// 请求 Agent 移除 HTTP-01 挑战内容
+    pub async fn cleanup_http_challenge(
+        &self,
+        agent_id: Uuid,
+        domain: &str,
+        token: &str,
+        timeout: Duration,
+    ) -> Result<(), String> {
+        let request = SslChallengeRequest {
+            domain: domain.to_string(),
+            challenge_type: SslChallengeType::Http01,
+            token: token.to_string(),
+            key_authorization: None,
+            cleanup: true,
+        };
+        self.ssl_challenge(agent_id, &request, token, timeout).await
+    }
+
+    /// 发送 SSL 挑战请求，按 token 等待对应的响应
+    async fn ssl_challenge(
+        &self,
+        agent_id: Uuid,
+        request: &AgentMessage,
+        token: &str,
+        timeout: Duration,
+    ) -> Result<(), String> {
+        let (tx, rx) = oneshot::channel();
+        self.challenges.lock().await.insert(token.to_string(), tx);
+        let result = match self.send_to(agent_id, request).await {
+            Ok(()) => match tokio::time::timeout(timeout, rx).await {
+                Ok(Ok(result)) => result,
+                Ok(Err(_)) => Err(format!("Agent {} 没有响应挑战请求", agent_id)),
+                Err(_) => Err(format!("等待 Agent {} 响应挑战请求超时", agent_id)),
+            },
+            Err(e) => Err(e),
+        };
+        self.challenges.lock().await.remove(token);
+        result
+    }
+
+    /// 请求 Agent 探测证书，等待 Agent 上报结果
+    pub async fn probe_certificates(
+        &self,
+        agent_id: Uuid,
+        targets: &[String],
+        timeout: Duration,
+    ) -> Result<Vec<CertProbeReport>, String> {
+        let request_id = Uuid::new_v4();
+        let (tx, rx) = oneshot::channel();
+        self.probes.lock().await.insert(request_id, tx);
+        let request = CertProbeRequest {
+            request_id,
+            targets: targets.to_vec(),
+        };
+        let result = match self.send_to(agent_id, &request).await {
+            Ok(()) => match tokio::time::timeout(timeout, rx).await {
+                Ok(Ok(reports)) => Ok(reports),
+                Ok(Err(_)) => Err(format!("Agent {} 没有上报证书探测结果", agent_id)),
+                Err(_) => Err(format!("等待 Agent {} 上报证书探测结果超时", agent_id)),
+            },
+            Err(e) => Err(e),
+        };
+        self.probes.lock().await.remove(&request_id);
+        result
+    }
+}
+
 /// 处理新的 WebSocket 连接
+#[allow(clippy::too_many_arguments)]
 async fn handle_new_connection(
     registry: Arc<AgentRegistry>,
-    connections: Arc<RwLock<HashMap<Uuid, Arc<RwLock<AgentConnection>>>>>,
+    connections: Connections,
     stream: TcpStream,
     addr: SocketAddr,
     registered_keys: Arc<RwLock<HashMap<String, Uuid>>>,
+    database: Option<DatabaseConnection>,
+    challenges: PendingChallenges,
+    probes: PendingProbes,
 ) -> Result<(), String> {
     let ws_stream = accept_async(stream)
         .await
         .map_err(|e| format!("WebSocket 握手失败: {}", e))?;
+    let (ws_sink, mut ws_stream) = ws_stream.split();
 
     let agent_conn = Arc::new(RwLock::new(AgentConnection::new(
         Uuid::nil(),
@@ -287,21 +448,18 @@ async fn handle_new_connection(
 
     {
         let mut conn_guard = agent_conn.write().await;
-        conn_guard.ws_stream = Some(ws_stream);
+        conn_guard.ws_sink = Some(ws_sink);
     }
 
     // 用于存储验证后的 agent_id
     let mut verified_agent_id: Option<Uuid> = None;
 
     loop {
-        let msg = {
-            let mut conn = agent_conn.write().await;
-            match conn.recv().await {
-                Ok(msg) => msg,
-                Err(e) => {
-                    warn!("从 {} 接收消息失败: {}", addr, e);
-                    break;
-                }
+        let msg = match recv_message(&mut ws_stream).await {
+            Ok(msg) => msg,
+            Err(e) => {
+                warn!("从 {} 接收消息失败: {}", addr, e);
+                break;
             }
         };
 
@@ -352,9 +510,11 @@ async fn handle_new_connection(
 
                 // 保存 agent_id
                 verified_agent_id = Some(agent_id);
+                connections.write().await.insert(agent_id, agent_conn.clone());
 
                 // 在注册表中注册
                 let mut agent = Agent::new(agent_name.clone(), format!("ws://{}", addr));
+                agent.id = agent_id;
                 agent.capabilities = capabilities;
                 agent.version = version;
                 agent.hostname = hostname;
@@ -393,17 +553,109 @@ async fn handle_new_connection(
                     server_time: chrono::Utc::now().timestamp(),
                 }).await;
             }
-            DdnsUpdateResult { domain, success, old_ip, new_ip, error: _ } => {
+            DdnsIpChanged { domain, record_type, old_ip, new_ip } => {
+                let agent_id = agent_conn.read().await.agent_id;
+                info!("DDNS IP 变化 from {}: {} {} {:?} -> {}", agent_id, domain, record_type, old_ip, new_ip);
+
+                let allowed = match registry.get(agent_id).await {
+                    Some(agent) => agent.has_capability(&Capability::DdnsClient),
+                    None => false,
+                };
+                let response = match (&database, allowed) {
+                    (_, false) => DdnsUpdateResult {
+                        domain,
+                        record_type: Some(record_type),
+                        success: false,
+                        old_ip,
+                        new_ip: Some(new_ip),
+                        error: Some("Agent 未注册或没有 DDNS 能力".to_string()),
+                    },
+                    (None, true) => DdnsUpdateResult {
+                        domain,
+                        record_type: Some(record_type),
+                        success: false,
+                        old_ip,
+                        new_ip: Some(new_ip),
+                        error: Some("Hub 未连接数据库，无法更新记录".to_string()),
+                    },
+                    (Some(conn), true) => {
+                        ddns::handle_ip_changed(conn, agent_id, domain, record_type, old_ip, new_ip).await
+                    }
+                };
+                let _ = broadcast_msg(&agent_conn, &response).await;
+            }
+            DdnsUpdateResult { domain, record_type, success, old_ip, new_ip, error } => {
                 let agent_id = agent_conn.read().await.agent_id;
                 info!("DDNS 更新结果 from {}: {} - success={}, old={:?}, new={:?}", agent_id, domain, success, old_ip, new_ip);
+
+                // Agent 直接更新了记录，保存为审计事件
+                if let Some(conn) = &database {
+                    let event = NewDdnsEvent {
+                        agent_id: agent_id.to_string(),
+                        domain,
+                        record_type: record_type.unwrap_or_default(),
+                        old_ip,
+                        new_ip: new_ip.unwrap_or_default(),
+                        mode: ddns::MODE_DIRECT.to_string(),
+                        success,
+                        error,
+                    };
+                    if let Err(e) = ddns_events::record_ddns_event(conn, event).await {
+                        warn!("{}", e);
+                    }
+                }
             }
-            SslChallengeResponse { domain, success, key_authorization: _, error: _ } => {
+            SslChallengeResponse { domain, token, success, key_authorization: _, error } => {
                 let agent_id = agent_conn.read().await.agent_id;
-                info!("SSL 挑战响应 from {}: {} - success={}", agent_id, domain, success);
+                info!("SSL 挑战响应 from {}: {} - success={}, error={:?}", agent_id, domain, success, error);
+
+                let pending = match &token {
+                    Some(token) => challenges.lock().await.remove(token),
+                    None => None,
+                };
+                match pending {
+                    Some(tx) => {
+                        let result = match success {
+                            true => Ok(()),
+                            false => Err(error.unwrap_or_else(|| "Agent 未说明失败原因".to_string())),
+                        };
+                        let _ = tx.send(result);
+                    }
+                    None => debug!("没有等待中的 SSL 挑战: {} {:?}", domain, token),
+                }
             }
-            TaskResult { task_id, success, output: _, error: _, exit_code, duration_ms: _ } => {
+            CertProbeResult { request_id, reports } => {
                 let agent_id = agent_conn.read().await.agent_id;
-                info!("任务结果 from {}: task_id={}, success={}, exit_code={:?}", agent_id, task_id, success, exit_code);
+                info!("证书探测结果 from {}: {} 个目标, request_id={:?}", agent_id, reports.len(), request_id);
+
+                // 请求方收到结果后自行保存，定时探测的结果在这里保存
+                let pending = match request_id {
+                    Some(request_id) => probes.lock().await.remove(&request_id),
+                    None => None,
+                };
+                match (pending, &database) {
+                    (Some(tx), _) => {
+                        let _ = tx.send(reports);
+                    }
+                    (None, Some(conn)) => {
+                        let checks: Vec<CertCheck> = reports
+                            .into_iter()
+                            .map(|report| CertCheck::from_agent_report(agent_id, report, certmon::DEFAULT_WARN_DAYS))
+                            .collect();
+                        if let Err(e) = certmon::save_checks(conn, &checks).await {
+                            warn!("保存证书检查结果失败: {}", e);
+                        }
+                    }
+                    (None, None) => debug!("没有等待中的证书探测: {:?}", request_id),
+                }
+            }
+            TaskOutput { task_id, stream, seq, data } => {
+                let agent_id = agent_conn.read().await.agent_id;
+                debug!("任务输出 from {}: task_id={}, {} #{}: {} 字节", agent_id, task_id, stream, seq, data.len());
+            }
+            TaskResult { task_id, success, status, output: _, error: _, exit_code, duration_ms: _ } => {
+                let agent_id = agent_conn.read().await.agent_id;
+                info!("任务结果 from {}: task_id={}, success={}, status={:?}, exit_code={:?}", agent_id, task_id, success, status, exit_code);
             }
 
             // ==================== Tunnel 消息处理 ====================
@@ -447,27 +699,56 @@ async fn handle_new_connection(
             P2pConnectRequest { request_id, target_agent_id } => {
                 let agent_id = agent_conn.read().await.agent_id;
                 info!("P2P 连接请求: request_id={}, from={}, target={}", request_id, agent_id, target_agent_id);
-                // TODO: 查找目标 Agent 并转发请求
+
+                let target = connections.read().await.get(&target_agent_id).cloned();
+                match target {
+                    Some(target) if agent_id != Uuid::nil() && target_agent_id != agent_id => {
+                        agent_conn.write().await.add_p2p_connection(request_id, target_agent_id);
+                        target.write().await.add_p2p_connection(request_id, agent_id);
+                    }
+                    _ => {
+                        warn!("P2P 目标 Agent {} 不在线", target_agent_id);
+                        let _ = broadcast_msg(&agent_conn, &P2pFailed {
+                            request_id,
+                            reason: format!("目标 Agent {} 不在线", target_agent_id),
+                        }).await;
+                    }
+                }
             }
-            P2pConnectOffer { request_id, source_agent_id, sdp_offer } => {
-                info!("P2P Offer: request_id={}, from={}, sdp_len={}", request_id, source_agent_id, sdp_offer.len());
-                // TODO: 转发 Offer 给目标 Agent
+            P2pConnectOffer { request_id, source_agent_id: _, sdp_offer } => {
+                let agent_id = agent_conn.read().await.agent_id;
+                info!("P2P Offer: request_id={}, from={}, sdp_len={}", request_id, agent_id, sdp_offer.len());
+                // 来源由 Hub 按已验证的连接填写，Agent 不能冒充其他 Agent
+                let offer = P2pConnectOffer { request_id, source_agent_id: agent_id, sdp_offer };
+                relay_p2p_signal(&connections, &agent_conn, request_id, &offer).await;
             }
             P2pAnswer { request_id, target_agent_id, sdp_answer } => {
                 info!("P2P Answer: request_id={}, to={}, sdp_len={}", request_id, target_agent_id, sdp_answer.len());
-                // TODO: 转发 Answer 给源 Agent
+                let answer = P2pAnswer { request_id, target_agent_id, sdp_answer };
+                relay_p2p_signal(&connections, &agent_conn, request_id, &answer).await;
             }
             P2pIceCandidate { request_id, candidate, sdp_mid, sdp_m_line_index } => {
                 debug!("P2P ICE Candidate: request_id={}, candidate={}, mid={:?}, index={:?}", request_id, candidate, sdp_mid, sdp_m_line_index);
-                // TODO: 转发 ICE Candidate 给对等方
+                let candidate = P2pIceCandidate { request_id, candidate, sdp_mid, sdp_m_line_index };
+                if let Err(e) = forward_p2p(&connections, &agent_conn, request_id, &candidate).await {
+                    debug!("转发 ICE Candidate 失败: request_id={}, {}", request_id, e);
+                }
             }
             P2pConnected { request_id } => {
                 let agent_id = agent_conn.read().await.agent_id;
                 info!("P2P 连接已建立: request_id={}, agent={}", request_id, agent_id);
+                // 连接建立后不再经过 Hub，结束会话
+                if let Ok(peer) = forward_p2p(&connections, &agent_conn, request_id, &P2pConnected { request_id }).await {
+                    end_p2p(&connections, peer, request_id).await;
+                }
+                agent_conn.write().await.remove_p2p_connection(&request_id);
             }
             P2pFailed { request_id, reason } => {
                 let agent_id = agent_conn.read().await.agent_id;
                 warn!("P2P 连接失败: request_id={}, agent={}, reason={}", request_id, agent_id, reason);
+                if let Ok(peer) = forward_p2p(&connections, &agent_conn, request_id, &P2pFailed { request_id, reason }).await {
+                    end_p2p(&connections, peer, request_id).await;
+                }
                 {
                     let mut conn = agent_conn.write().await;
                     conn.remove_p2p_connection(&request_id);
@@ -499,6 +780,7 @@ async fn handle_new_connection(
             TaskAssigned { .. } |
             DdnsUpdateRequest { .. } |
             SslChallengeRequest { .. } |
+            CertProbeRequest { .. } |
             TaskCancelled { .. } |
             HeartbeatAck { .. } |
             TunnelResponse { .. } => {
@@ -510,6 +792,20 @@ async fn handle_new_connection(
     // 清理连接
     if let Some(agent_id) = verified_agent_id {
         connections.write().await.remove(&agent_id);
+
+        // 通知尚未建立的 P2P 连接的另一方
+        let sessions: Vec<P2pConnectionInfo> = agent_conn.write().await.p2p_connections.drain().map(|(_, info)| info).collect();
+        for info in sessions {
+            let peer = connections.read().await.get(&info.peer_agent_id).cloned();
+            if let Some(peer) = peer {
+                let mut peer = peer.write().await;
+                peer.remove_p2p_connection(&info.request_id);
+                let _ = peer.send(&P2pFailed {
+                    request_id: info.request_id,
+                    reason: format!("Agent {} 已断开", agent_id),
+                }).await;
+            }
+        }
         let _ = registry.unregister(agent_id).await;
     }
 
@@ -517,6 +813,46 @@ async fn handle_new_connection(
     Ok(())
 }
 
+/// 把 P2P 信令转发给会话的另一方，返回对方的 Agent ID
+async fn forward_p2p(
+    connections: &Connections,
+    agent_conn: &Arc<RwLock<AgentConnection>>,
+    request_id: Uuid,
+    msg: &AgentMessage,
+) -> Result<Uuid, String> {
+    let peer_id = agent_conn.read().await
+        .p2p_connections
+        .get(&request_id)
+        .map(|info| info.peer_agent_id)
+        .ok_or_else(|| format!("未知的 P2P 会话 {}", request_id))?;
+    let peer = connections.read().await.get(&peer_id).cloned()
+        .ok_or_else(|| format!("Agent {} 不在线", peer_id))?;
+    peer.write().await.send(msg).await?;
+    Ok(peer_id)
+}
+
+/// 转发 Offer 或 Answer，失败时通知发送方并结束会话
+async fn relay_p2p_signal(
+    connections: &Connections,
+    agent_conn: &Arc<RwLock<AgentConnection>>,
+    request_id: Uuid,
+    msg: &AgentMessage,
+) {
+    if let Err(e) = forward_p2p(connections, agent_conn, request_id, msg).await {
+        warn!("转发 P2P 信令失败: request_id={}, {}", request_id, e);
+        agent_conn.write().await.remove_p2p_connection(&request_id);
+        let _ = broadcast_msg(agent_conn, &P2pFailed { request_id, reason: e }).await;
+    }
+}
+
+/// 移除另一方记录的 P2P 会话
+async fn end_p2p(connections: &Connections, peer_id: Uuid, request_id: Uuid) {
+    let peer = connections.read().await.get(&peer_id).cloned();
+    if let Some(peer) = peer {
+        peer.write().await.remove_p2p_connection(&request_id);
+    }
+}
+
 /// 向连接发送消息
 async fn broadcast_msg(
     conn: &Arc<RwLock<AgentConnection>>,
@@ -548,7 +884,7 @@ mod tests {
 
         assert_eq!(conn.agent_id, agent_id);
         assert_eq!(conn.session_id, session_id);
-        assert!(conn.ws_stream.is_none());
+        assert!(conn.ws_sink.is_none());
         assert_eq!(conn.tunnel_count(), 0);
     }
 
@@ -0,0 +1,179 @@
+//! Agent 上报公网 IP 后更新 DDNS 记录
+//!
+//! Agent 检测到公网 IP 变化后有两种处理方式：
+//! - `hub`：发送 `DdnsIpChanged`，由 Hub 找到域名所属账户，通过 `DnsClientTrait` 更新记录
+//! - `direct`：Agent 使用只授权了对应区域的凭证自行更新，再通过 `DdnsUpdateResult` 上报结果
+//!
+//! 两种方式的每次更新都保存为 DDNS 事件，便于审计。
+
+use super::protocol::AgentMessage;
+use crate::api::provider::create_dns_client_for_account;
+use crate::api::provider::rrset::parse_record_type;
+use crate::gui::model::domain::DomainName;
+use crate::models::record::NewRecord;
+use crate::storage::ddns_events::{self, NewDdnsEvent};
+use crate::storage::{accounts, domains, records};
+use crate::zone::migrate::relative_names;
+use crate::zone::ZoneRecord;
+use anyhow::{anyhow, bail, Result};
+use sea_orm::DatabaseConnection;
+use std::net::IpAddr;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// 新建 DDNS 记录时使用的 TTL
+pub const DDNS_TTL: i32 = 600;
+
+/// 由 Hub 更新记录
+pub const MODE_HUB: &str = "hub";
+/// Agent 直接更新记录
+pub const MODE_DIRECT: &str = "direct";
+
+/// 更新结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DdnsOutcome {
+    /// 更新前的记录值，记录不存在时为空
+    pub old_ip: Option<String>,
+    /// 是否修改了服务商上的记录
+    pub changed: bool,
+}
+
+/// 将完整域名的 A/AAAA 记录更新为 `new_ip`，记录不存在时新增
+///
+/// 按最长后缀匹配本地保存的域名，使用域名所属账户的凭证操作。
+pub async fn apply_ip_change(
+    conn: &DatabaseConnection,
+    fqdn: &str,
+    record_type: &str,
+    new_ip: &str,
+) -> Result<DdnsOutcome> {
+    let ip: IpAddr = new_ip
+        .parse()
+        .map_err(|_| anyhow!("无效的 IP 地址「{}」", new_ip))?;
+    let record_type = record_type.to_ascii_uppercase();
+    match (record_type.as_str(), ip) {
+        ("A", IpAddr::V4(_)) | ("AAAA", IpAddr::V6(_)) => {}
+        ("A", _) | ("AAAA", _) => bail!("{} 记录与 IP 地址 {} 不匹配", record_type, ip),
+        _ => bail!("DDNS 只支持 A 和 AAAA 记录，收到 {}", record_type),
+    }
+    let ip = ip.to_string();
+    let fqdn = fqdn.trim_end_matches('.').to_ascii_lowercase();
+
+    let domain = domains::find_domain_by_fqdn(conn, &fqdn)
+        .await
+        .map_err(|e| anyhow!("查询域名失败: {}", e))?
+        .ok_or_else(|| anyhow!("没有找到 {} 所属的域名，请先同步域名", fqdn))?;
+    let rr = match fqdn.len() - domain.domain_name.len() {
+        0 => "@".to_string(),
+        len => fqdn[..len - 1].to_string(),
+    };
+    let account = accounts::get_account_by_id(conn, domain.account_id)
+        .await
+        .map_err(|e| anyhow!("查询账户失败: {}", e))?
+        .ok_or_else(|| anyhow!("账户 ID {} 不存在", domain.account_id))?;
+    let (provider, client) = create_dns_client_for_account(account)?;
+    let domain_name = DomainName {
+        name: domain.domain_name.clone(),
+        provider,
+        ..Default::default()
+    };
+
+    let current = relative_names(
+        &domain.domain_name,
+        client.list_dns_records(domain.domain_name.clone()).await?,
+    );
+    let existing = current.iter().find(|record| {
+        record.rr.eq_ignore_ascii_case(&rr) && record.record_type.get_value() == record_type
+    });
+    let outcome = match existing {
+        Some(record) if record.value == ip => DdnsOutcome {
+            old_ip: Some(record.value.clone()),
+            changed: false,
+        },
+        Some(record) => {
+            let mut updated = record.clone();
+            updated.value = ip.clone();
+            client.update_dns_record(&domain_name, &updated).await?;
+            DdnsOutcome {
+                old_ip: Some(record.value.clone()),
+                changed: true,
+            }
+        }
+        None => {
+            let record_type = parse_record_type(&record_type)
+                .ok_or_else(|| anyhow!("不支持的记录类型 {}", record_type))?;
+            let record = ZoneRecord::new(rr.as_str(), record_type, ip.as_str(), DDNS_TTL as u32);
+            client
+                .add_dns_record(&domain_name, &record.to_record(String::new()))
+                .await?;
+            DdnsOutcome {
+                old_ip: None,
+                changed: true,
+            }
+        }
+    };
+
+    if outcome.changed {
+        info!("DDNS 记录 {} {} 已更新为 {}", fqdn, record_type, ip);
+        let records = client.list_dns_records(domain.domain_name.clone()).await?;
+        let new_records = records
+            .iter()
+            .map(|r| NewRecord {
+                domain_id: domain.id,
+                record_name: r.rr.clone(),
+                record_type: r.record_type.to_string(),
+                record_value: r.value.clone(),
+                ttl: r.ttl,
+            })
+            .collect();
+        if let Err(e) = records::replace_domain_records(conn, domain.id, new_records).await {
+            warn!("刷新 {} 的本地解析记录失败: {}", domain.domain_name, e);
+        }
+    }
+    Ok(outcome)
+}
+
+/// 处理 Agent 上报的 IP 变化：更新记录、保存事件，并返回发给 Agent 的结果
+pub async fn handle_ip_changed(
+    conn: &DatabaseConnection,
+    agent_id: Uuid,
+    domain: String,
+    record_type: String,
+    reported_old_ip: Option<String>,
+    new_ip: String,
+) -> AgentMessage {
+    let result = apply_ip_change(conn, &domain, &record_type, &new_ip).await;
+    let (old_ip, error) = match result {
+        Ok(outcome) => (outcome.old_ip, None),
+        Err(e) => {
+            warn!(
+                "Agent {} 的 DDNS 更新失败: {} {}: {:#}",
+                agent_id, domain, record_type, e
+            );
+            (reported_old_ip, Some(format!("{:#}", e)))
+        }
+    };
+
+    let event = NewDdnsEvent {
+        agent_id: agent_id.to_string(),
+        domain: domain.clone(),
+        record_type: record_type.clone(),
+        old_ip: old_ip.clone(),
+        new_ip: new_ip.clone(),
+        mode: MODE_HUB.to_string(),
+        success: error.is_none(),
+        error: error.clone(),
+    };
+    if let Err(e) = ddns_events::record_ddns_event(conn, event).await {
+        warn!("{}", e);
+    }
+
+    AgentMessage::DdnsUpdateResult {
+        domain,
+        record_type: Some(record_type),
+        success: error.is_none(),
+        old_ip,
+        new_ip: Some(new_ip),
+        error,
+    }
+}
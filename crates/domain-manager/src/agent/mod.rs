@@ -3,8 +3,10 @@
 //! 提供分布式 Agent 管理功能，包括：
 //! - Agent 注册与心跳
 //! - 任务分发
+//! - DDNS 记录更新
 //! - WebSocket 通信协议
 
+pub mod ddns;
 pub mod model;
 pub mod protocol;
 pub mod registry;
@@ -22,6 +22,8 @@ pub enum Capability {
     TunnelClient,
     /// P2P 节点能力
     P2pNode,
+    /// 证书监控能力
+    CertMonitor,
 }
 
 impl std::fmt::Display for Capability {
@@ -34,6 +36,7 @@ impl std::fmt::Display for Capability {
             Capability::FileTransfer => write!(f, "文件传输"),
             Capability::TunnelClient => write!(f, "隧道客户端"),
             Capability::P2pNode => write!(f, "P2P节点"),
+            Capability::CertMonitor => write!(f, "证书监控"),
         }
     }
 }
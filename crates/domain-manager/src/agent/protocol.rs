@@ -107,10 +107,22 @@ pub enum AgentMessage {
         timeout_seconds: u32,
     },
     
+    /// 任务输出片段（Agent -> Hub），stdout/stderr 按块流式上报
+    TaskOutput {
+        task_id: Uuid,
+        /// "stdout" 或 "stderr"
+        stream: String,
+        seq: u64,
+        data: String,
+    },
+
     /// 任务结果（Agent -> Hub）
     TaskResult {
         task_id: Uuid,
         success: bool,
+        /// 最终状态（succeeded/failed/cancelled/timed_out），旧版 Agent 不上报
+        #[serde(default)]
+        status: Option<String>,
         output: String,
         error: Option<String>,
         exit_code: Option<i32>,
@@ -131,9 +143,19 @@ pub enum AgentMessage {
         record_type: String,
     },
     
-    /// DDNS 更新结果（Agent -> Hub）
+    /// 公网 IP 变化，请求 Hub 更新记录（Agent -> Hub）
+    DdnsIpChanged {
+        domain: String,
+        record_type: String,
+        old_ip: Option<String>,
+        new_ip: String,
+    },
+    
+    /// DDNS 更新结果（Hub -> Agent：Hub 更新后的结果；Agent -> Hub：Agent 直接更新后上报）
     DdnsUpdateResult {
         domain: String,
+        #[serde(default)]
+        record_type: Option<String>,
         success: bool,
         old_ip: Option<String>,
         new_ip: Option<String>,
@@ -147,15 +169,40 @@ pub enum AgentMessage {
         domain: String,
         challenge_type: SslChallengeType,
         token: String,
+        /// HTTP-01 挑战需要返回的内容
+        #[serde(default)]
+        key_authorization: Option<String>,
+        /// 为 true 时移除之前部署的挑战
+        #[serde(default)]
+        cleanup: bool,
     },
     
     /// SSL 挑战响应（Agent -> Hub）
     SslChallengeResponse {
         domain: String,
+        /// 对应请求的 token，旧版 Agent 不上报
+        #[serde(default)]
+        token: Option<String>,
         success: bool,
         key_authorization: Option<String>,
         error: Option<String>,
     },
+
+    // ==================== 证书监控 ====================
+
+    /// 请求 Agent 探测证书（Hub -> Agent）
+    CertProbeRequest {
+        request_id: Uuid,
+        /// `host`、`host:port` 或 `[v6]:port`，默认端口 443
+        targets: Vec<String>,
+    },
+
+    /// 证书探测结果（Agent -> Hub），定时探测时 `request_id` 为空
+    CertProbeResult {
+        #[serde(default)]
+        request_id: Option<Uuid>,
+        reports: Vec<CertProbeReport>,
+    },
     
     // ==================== 系统相关 ====================
     
@@ -253,6 +300,23 @@ pub enum AgentMessage {
     },
 }
 
+/// Agent 探测一个目标的结果
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CertProbeReport {
+    /// 请求中的目标
+    pub target: String,
+    /// TLS SNI 使用的主机名
+    pub server_name: String,
+    /// Base64 编码的 DER 证书链，叶子证书在前
+    #[serde(default)]
+    pub chain: Vec<String>,
+    /// 连接或握手失败的原因，此时 `chain` 为空
+    #[serde(default)]
+    pub error: Option<String>,
+    /// 探测时间（Unix 时间戳）
+    pub probed_at: i64,
+}
+
 /// 隧道类型
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
@@ -161,6 +161,7 @@ impl Capability {
             Capability::FileTransfer,
             Capability::TunnelClient,
             Capability::P2pNode,
+            Capability::CertMonitor,
         ]
     }
 
@@ -174,6 +175,7 @@ impl Capability {
             "file_transfer" | "file" => Some(Capability::FileTransfer),
             "tunnel_client" | "tunnel" => Some(Capability::TunnelClient),
             "p2p_node" | "p2p" => Some(Capability::P2pNode),
+            "cert_monitor" | "cert" => Some(Capability::CertMonitor),
             _ => None,
         }
     }
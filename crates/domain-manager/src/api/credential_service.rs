@@ -1,4 +1,5 @@
 use crate::api::dns_client::{DnsClient, DnsClientTrait};
+use crate::api::provider::create_dns_client;
 use crate::gui::model::domain::DnsProvider;
 use crate::gui::types::credential::Credential;
 use anyhow::{anyhow, Result};
@@ -20,15 +21,13 @@ impl CredentialService {
                 info!("Cloudflare 凭证验证暂未实现");
                 Ok(())
             }
-            DnsProvider::Dnspod => {
-                // TODO: 实现 DNSPod 凭证验证
-                info!("DNSPod 凭证验证暂未实现");
-                Ok(())
+            DnsProvider::Dnspod | DnsProvider::TencentCloud => {
+                Self::validate_dnspod_credentials(provider, credential).await
             }
-            DnsProvider::Tomato
-            | DnsProvider::TencentCloud
-            | DnsProvider::Aws
-            | DnsProvider::Google => {
+            DnsProvider::Aws => Self::validate_route53_credentials(credential).await,
+            DnsProvider::Google => Self::validate_google_credentials(credential).await,
+            DnsProvider::Rfc2136 => Self::validate_tsig_credentials(credential).await,
+            DnsProvider::Tomato => {
                 // 其他提供商暂不验证
                 info!("{} 提供商跳过凭证验证", provider.name());
                 Ok(())
@@ -51,6 +50,9 @@ impl CredentialService {
                     username_password.password.clone(),
                 )
             }
+            Credential::ServiceAccount(_) | Credential::Tsig(_) => {
+                return Err(anyhow!("阿里云只支持 Access Key 凭证"));
+            }
         };
 
         if access_key_id.is_empty() || access_key_secret.is_empty() {
@@ -71,6 +73,79 @@ impl CredentialService {
         })
     }
 
+    /// 验证腾讯云 / DNSPod 凭证
+    async fn validate_dnspod_credentials(
+        provider: DnsProvider,
+        credential: &Credential,
+    ) -> Result<()> {
+        let Credential::ApiKey(api_key) = credential else {
+            return Err(anyhow!("DNSPod 需要使用 SecretId / SecretKey 凭证"));
+        };
+
+        if api_key.api_key.is_empty() || api_key.api_secret.is_empty() {
+            return Err(anyhow!("SecretId 或 SecretKey 不能为空"));
+        }
+
+        let client = create_dns_client(provider, credential.clone())?;
+        client.validate_credentials().await.map_err(|e| {
+            error!("{}凭证验证失败", provider.name());
+            anyhow!("凭证验证失败: {}", e)
+        })
+    }
+
+    /// 验证 Route 53 凭证
+    async fn validate_route53_credentials(credential: &Credential) -> Result<()> {
+        let Credential::ApiKey(api_key) = credential else {
+            return Err(anyhow!(
+                "Route 53 需要使用 Access Key ID / Secret Access Key 凭证"
+            ));
+        };
+
+        if api_key.api_key.is_empty() || api_key.api_secret.is_empty() {
+            return Err(anyhow!("Access Key ID 或 Secret Access Key 不能为空"));
+        }
+
+        let client = create_dns_client(DnsProvider::Aws, credential.clone())?;
+        client.validate_credentials().await.map_err(|e| {
+            error!("Route 53凭证验证失败");
+            anyhow!("凭证验证失败: {}", e)
+        })
+    }
+
+    /// 验证 Google Cloud DNS 服务账号凭证
+    async fn validate_google_credentials(credential: &Credential) -> Result<()> {
+        let Credential::ServiceAccount(service_account) = credential else {
+            return Err(anyhow!("Google Cloud DNS 需要使用服务账号密钥凭证"));
+        };
+
+        if service_account.key_json.trim().is_empty() {
+            return Err(anyhow!("服务账号密钥不能为空"));
+        }
+
+        let client = create_dns_client(DnsProvider::Google, credential.clone())?;
+        client.validate_credentials().await.map_err(|e| {
+            error!("Google Cloud DNS凭证验证失败");
+            anyhow!("凭证验证失败: {}", e)
+        })
+    }
+
+    /// 验证 RFC 2136 的 TSIG 凭证
+    async fn validate_tsig_credentials(credential: &Credential) -> Result<()> {
+        let Credential::Tsig(tsig) = credential else {
+            return Err(anyhow!("RFC 2136 需要使用 TSIG 凭证"));
+        };
+
+        if tsig.server.is_empty() || tsig.key_name.is_empty() || tsig.secret.is_empty() {
+            return Err(anyhow!("服务器地址、密钥名称和密钥不能为空"));
+        }
+
+        let client = create_dns_client(DnsProvider::Rfc2136, credential.clone())?;
+        client.validate_credentials().await.map_err(|e| {
+            error!("TSIG凭证验证失败");
+            anyhow!("凭证验证失败: {}", e)
+        })
+    }
+
     /// 模拟验证凭证（用于测试和开发环境）
     pub async fn validate_credentials_mock(
         provider: DnsProvider,
@@ -102,6 +177,20 @@ impl CredentialService {
                     Ok(())
                 }
             }
+            Credential::ServiceAccount(service_account) => {
+                if service_account.key_json.trim().is_empty() {
+                    Err(anyhow!("服务账号密钥不能为空"))
+                } else {
+                    Ok(())
+                }
+            }
+            Credential::Tsig(tsig) => {
+                if tsig.key_name.is_empty() || tsig.secret.is_empty() {
+                    Err(anyhow!("TSIG 密钥名称或密钥不能为空"))
+                } else {
+                    Ok(())
+                }
+            }
             Credential::UsernamePassword(username_password) => {
                 if username_password.username.contains("test")
                     || username_password.password.contains("test")
@@ -486,6 +486,7 @@ impl DnsApiUtils {
                 "Status".to_string(),
                 match status {
                     Status::Enable => "Enable".to_string(),
+                    Status::Disable => "Disable".to_string(),
                 },
             );
         }
@@ -3,6 +3,7 @@ use crate::api::provider::aliyun::AliyunDnsClient;
 use crate::gui::model::domain::DnsProvider::Aliyun;
 use crate::gui::model::domain::{DnsProvider, Domain, DomainName, DomainStatus};
 use crate::model::dns_record_response::Record;
+use crate::models::domain::DomainRegistration;
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use reqwest::Client;
@@ -19,6 +20,11 @@ pub trait DnsClientTrait {
     async fn delete_dns_record(&self, domain_name: &DomainName, record_id: &str) -> Result<()>;
     async fn update_dns_record(&self, domain_name: &DomainName, record: &Record) -> Result<()>;
 
+    /// 查询域名的注册时间、到期时间和注册商，服务商不提供注册信息时返回 `None`
+    async fn query_registration(&self, _domain_name: &str) -> Result<Option<DomainRegistration>> {
+        Ok(None)
+    }
+
     /// 验证凭证是否有效
     async fn validate_credentials(&self) -> Result<()>;
 }
@@ -3,14 +3,19 @@ use crate::api::model::domain::DomainQueryResponse;
 use crate::gui::model::domain::DnsProvider::Aliyun;
 use crate::gui::model::domain::{Domain, DomainName};
 use crate::model::dns_record_response::{DnsRecordResponse, Record};
+use crate::models::domain::DomainRegistration;
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
+use chrono::{DateTime, NaiveDateTime};
 use domain_clients::RequestBody;
 use reqwest::{Client, Method};
 use serde_json::json;
 use std::collections::HashMap;
 use tracing::{error, info};
 
+/// 通过阿里云查询到注册信息时记录的注册商
+const ALIYUN_REGISTRAR: &str = "阿里云";
+
 #[derive(Debug, Clone)]
 pub struct AliyunDnsClient {
     client: Client,
@@ -65,7 +70,7 @@ impl AliyunDnsClient {
         .await;
         info!("接口请求结果:{}", json!(&response));
 
-        let resp_str = response.unwrap();
+        let resp_str = response.map_err(|e| anyhow!("调用阿里云接口失败: {}", e))?;
         let json_val: serde_json::Value = serde_json::from_str(&resp_str)?;
 
         if let Some(code) = json_val.get("Code") {
@@ -145,6 +150,27 @@ impl DnsClientTrait for AliyunDnsClient {
         todo!()
     }
 
+    /// 查询域名注册信息，只对在阿里云注册的域名有效
+    async fn query_registration(&self, domain_name: &str) -> Result<Option<DomainRegistration>> {
+        let query_params = &[("DomainName", domain_name)];
+
+        let mut body = HashMap::new();
+        body.insert("DomainName".to_string(), json!(domain_name));
+
+        let response = self
+            .call_ali_api(
+                Method::GET,
+                "domain.aliyuncs.com",
+                "/",
+                query_params,
+                "QueryDomainByDomainName",
+                "2018-01-29",
+                RequestBody::Json(body),
+            )
+            .await?;
+        Ok(Some(parse_domain_registration(&response)))
+    }
+
     /// 查询DNS记录
     async fn list_dns_records(&self, domain_name: String) -> Result<Vec<Record>> {
         let query_params = &[
@@ -187,6 +213,7 @@ impl DnsClientTrait for AliyunDnsClient {
 
     /// 添加DNS记录
     async fn add_dns_record(&self, domain_name: &DomainName, record: &Record) -> Result<()> {
+        let line = String::from(record.line.clone());
         let query_params = &[
             ("RegionId", self.region_id.as_str()),
             ("DomainName", domain_name.name.as_str()),
@@ -194,6 +221,7 @@ impl DnsClientTrait for AliyunDnsClient {
             ("Type", record.record_type.get_value()),
             ("Value", record.value.as_str()),
             ("TTL", &record.ttl.to_string()),
+            ("Line", &line),
         ];
 
         let mut body = HashMap::new();
@@ -202,6 +230,7 @@ impl DnsClientTrait for AliyunDnsClient {
         body.insert("Type".to_string(), json!(record.record_type.get_value()));
         body.insert("Value".to_string(), json!(record.value));
         body.insert("TTL".to_string(), json!(record.ttl));
+        body.insert("Line".to_string(), json!(line));
 
         let response = self
             .call_ali_api(
@@ -247,6 +276,7 @@ impl DnsClientTrait for AliyunDnsClient {
 
     /// 更新DNS记录
     async fn update_dns_record(&self, _domain_name: &DomainName, record: &Record) -> Result<()> {
+        let line = String::from(record.line.clone());
         let query_params = &[
             ("RegionId", self.region_id.as_str()),
             ("RecordId", record.record_id.as_str()),
@@ -254,6 +284,7 @@ impl DnsClientTrait for AliyunDnsClient {
             ("Type", record.record_type.get_value()),
             ("Value", record.value.as_str()),
             ("TTL", &record.ttl.to_string()),
+            ("Line", &line),
         ];
 
         let mut body = HashMap::new();
@@ -262,6 +293,7 @@ impl DnsClientTrait for AliyunDnsClient {
         body.insert("Type".to_string(), json!(record.record_type.get_value()));
         body.insert("Value".to_string(), json!(record.value));
         body.insert("TTL".to_string(), json!(record.ttl));
+        body.insert("Line".to_string(), json!(line));
 
         let response = self
             .call_ali_api(
@@ -296,3 +328,54 @@ impl DnsClientTrait for AliyunDnsClient {
         }
     }
 }
+
+/// 解析 `QueryDomainByDomainName` 的返回结果，优先使用毫秒时间戳
+fn parse_domain_registration(response: &serde_json::Value) -> DomainRegistration {
+    let date = |field: &str| -> Option<NaiveDateTime> {
+        response
+            .get(format!("{}Long", field))
+            .and_then(|value| value.as_i64())
+            .and_then(DateTime::from_timestamp_millis)
+            .map(|date| date.naive_utc())
+            .or_else(|| {
+                response
+                    .get(field)
+                    .and_then(|value| value.as_str())
+                    .and_then(|value| {
+                        NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").ok()
+                    })
+            })
+    };
+    DomainRegistration {
+        registration_date: date("RegistrationDate"),
+        expiration_date: date("ExpirationDate"),
+        registrar: Some(ALIYUN_REGISTRAR.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_parse_domain_registration() {
+        let response = json!({
+            "RequestId": "0F4FEE9A-2A06-4C6A-8A50-3D1B5B0A4C1E",
+            "DomainName": "example.com",
+            "RegistrationDate": "2017-11-02 12:00:45",
+            "RegistrationDateLong": 1509595245000i64,
+            "ExpirationDate": "2027-11-02 12:00:45",
+        });
+        let registration = parse_domain_registration(&response);
+        assert_eq!(
+            registration.registration_date,
+            NaiveDate::from_ymd_opt(2017, 11, 2).and_then(|date| date.and_hms_opt(4, 0, 45))
+        );
+        assert_eq!(
+            registration.expiration_date,
+            NaiveDate::from_ymd_opt(2027, 11, 2).and_then(|date| date.and_hms_opt(12, 0, 45))
+        );
+        assert_eq!(registration.registrar.as_deref(), Some(ALIYUN_REGISTRAR));
+    }
+}
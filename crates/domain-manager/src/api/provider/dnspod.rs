@@ -0,0 +1,520 @@
+use crate::api::dns_client::DnsClientTrait;
+use crate::api::model::domain::DomainQueryResponse;
+use crate::gui::model::domain::{DnsProvider, Domain, DomainName};
+use crate::model::dns_record_response::{Line, Record, Status, Type};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tracing::{error, info};
+
+/// DNSPod API 3.0 默认接口地址
+const DNSPOD_ENDPOINT: &str = "https://dnspod.tencentcloudapi.com";
+const DNSPOD_SERVICE: &str = "dnspod";
+const DNSPOD_VERSION: &str = "2021-03-23";
+
+/// DNSPod 默认线路
+const DEFAULT_RECORD_LINE: &str = "默认";
+
+/// 记录列表为空时 DNSPod 返回的错误码
+const NO_DATA_OF_RECORD: &str = "ResourceNotFound.NoDataOfRecord";
+
+/// DescribeRecordList 单页最多返回的记录数
+const RECORD_PAGE_SIZE: u32 = 3000;
+
+/// 腾讯云 API 通用响应外层结构
+#[derive(Debug, Deserialize)]
+struct TencentResponse<T> {
+    #[serde(rename = "Response")]
+    response: T,
+}
+
+#[derive(Debug, Deserialize)]
+struct TencentError {
+    #[serde(rename = "Code")]
+    code: String,
+    #[serde(rename = "Message")]
+    message: String,
+}
+
+/// DescribeDomainList 响应
+#[derive(Debug, Deserialize)]
+struct DescribeDomainListResponse {
+    #[serde(rename = "DomainList", default)]
+    domain_list: Vec<DnspodDomain>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DnspodDomain {
+    #[serde(rename = "Name")]
+    name: String,
+}
+
+/// DescribeRecordList 响应
+#[derive(Debug, Deserialize)]
+struct DescribeRecordListResponse {
+    #[serde(rename = "RecordCountInfo", default)]
+    record_count_info: RecordCountInfo,
+    #[serde(rename = "RecordList", default)]
+    record_list: Vec<DnspodRecord>,
+}
+
+/// 记录数量统计
+#[derive(Debug, Default, Deserialize)]
+struct RecordCountInfo {
+    #[serde(rename = "TotalCount", default)]
+    total_count: u32,
+}
+
+/// DNSPod 解析记录
+#[derive(Debug, Deserialize)]
+struct DnspodRecord {
+    #[serde(rename = "RecordId")]
+    record_id: u64,
+    #[serde(rename = "Name")]
+    name: String,
+    #[serde(rename = "Type")]
+    record_type: String,
+    #[serde(rename = "Value")]
+    value: String,
+    #[serde(rename = "Status")]
+    status: String,
+    #[serde(rename = "TTL")]
+    ttl: i32,
+    #[serde(rename = "MX", default)]
+    mx: Option<u32>,
+    #[serde(rename = "Weight", default)]
+    weight: Option<i32>,
+    #[serde(rename = "Line", default)]
+    line: String,
+}
+
+/// 腾讯云 DNSPod 客户端
+///
+/// 腾讯云解析（TencentCloud）与 DNSPod 使用同一套 API，二者共用该客户端。
+#[derive(Debug, Clone)]
+pub struct DnspodDnsClient {
+    client: Client,
+    secret_id: String,
+    secret_key: String,
+    endpoint: String,
+    provider: DnsProvider,
+}
+
+impl DnspodDnsClient {
+    /// 创建新的DNSPod客户端
+    ///
+    /// # 参数
+    /// * `secret_id` - 腾讯云 SecretId
+    /// * `secret_key` - 腾讯云 SecretKey
+    pub fn new(secret_id: String, secret_key: String) -> Self {
+        Self {
+            client: Client::new(),
+            secret_id,
+            secret_key,
+            endpoint: DNSPOD_ENDPOINT.to_string(),
+            provider: DnsProvider::Dnspod,
+        }
+    }
+
+    /// 指定接口地址，用于私有化部署或测试
+    pub fn with_endpoint(mut self, endpoint: String) -> Self {
+        self.endpoint = endpoint;
+        self
+    }
+
+    /// 指定返回的域名所属托管商（TencentCloud 或 Dnspod）
+    pub fn with_provider(mut self, provider: DnsProvider) -> Self {
+        self.provider = provider;
+        self
+    }
+
+    /// 内部调用API的通用方法
+    async fn call_dnspod_api<T: DeserializeOwned>(
+        &self,
+        action: &str,
+        payload: Value,
+    ) -> Result<T> {
+        info!(
+            "请求DNSPod接口「{}」，当前密钥：「{:?}」，请求参数：「{}」",
+            action, &self.secret_id, payload
+        );
+
+        let response = domain_clients::tencent::call_tc3_api(
+            self.client.clone(),
+            &self.endpoint,
+            DNSPOD_SERVICE,
+            action,
+            DNSPOD_VERSION,
+            None,
+            &payload.to_string(),
+            &self.secret_id,
+            &self.secret_key,
+        )
+        .await
+        .map_err(|e| anyhow!("DNSPod 接口请求失败: {}", e))?;
+
+        Self::parse_response(&response)
+    }
+
+    /// 解析腾讯云响应，统一处理 `Response.Error`
+    fn parse_response<T: DeserializeOwned>(response: &str) -> Result<T> {
+        let json_val: Value = serde_json::from_str(response)?;
+        let inner = json_val
+            .get("Response")
+            .ok_or_else(|| anyhow!("DNSPod 响应格式错误: {}", response))?;
+
+        if let Some(error) = inner.get("Error") {
+            let error: TencentError = serde_json::from_value(error.clone())?;
+            let request_id = inner
+                .get("RequestId")
+                .and_then(|v| v.as_str())
+                .unwrap_or("");
+            return Err(anyhow!(
+                "DNSPod API Error: {} - {} (RequestId: {})",
+                error.code,
+                error.message,
+                request_id
+            ));
+        }
+
+        let result: TencentResponse<T> = serde_json::from_value(json_val)?;
+        Ok(result.response)
+    }
+
+    /// 将DNSPod记录转换为内部Record格式
+    fn convert_dnspod_record_to_internal(record: &DnspodRecord) -> Record {
+        let record_type = match record.record_type.as_str() {
+            "A" => Type::A,
+            "AAAA" => Type::AAAA,
+            "CNAME" => Type::Cname,
+            "MX" => Type::MX,
+            "TXT" => Type::TXT,
+            "NS" => Type::NS,
+            "PTR" => Type::PTR,
+            "SRV" => Type::SRV,
+            "显性URL" | "隐性URL" => Type::ForwardUrl,
+            _ => Type::A, // 默认类型
+        };
+
+        // MX优先级与值拼接在一起，与 Cloudflare 的处理方式保持一致
+        let value = match (&record_type, record.mx) {
+            (Type::MX, Some(mx)) => format!("{} {}", mx, record.value),
+            _ => record.value.clone(),
+        };
+
+        let mut internal = Record::new(
+            if record.status == "DISABLE" {
+                Status::Disable
+            } else {
+                Status::Enable
+            },
+            record.name.clone(),
+            record_type,
+            value,
+            record.record_id.to_string(),
+            record.ttl,
+        );
+        internal.weight = record.weight;
+        if record.line != DEFAULT_RECORD_LINE {
+            internal.line = Line::from(record.line.clone());
+        }
+        internal
+    }
+
+    /// 将内部Record格式转换为DNSPod的请求参数（不含 Domain 与 RecordId）
+    fn convert_internal_to_dnspod_params(record: &Record) -> Value {
+        let mut value = record.value.clone();
+        let mut mx = None;
+
+        // 对于MX记录，需要拆分出优先级
+        if record.record_type == Type::MX {
+            if let Some((prio, host)) = record.value.split_once(' ') {
+                if let Ok(prio) = prio.parse::<u32>() {
+                    mx = Some(prio);
+                    value = host.to_string();
+                }
+            }
+        }
+
+        let mut params = json!({
+            "SubDomain": record.rr,
+            "RecordType": record.record_type.get_value(),
+            "RecordLine": match &record.line {
+                Line::Default => DEFAULT_RECORD_LINE,
+                Line::Custom(line) => line,
+            },
+            "Value": value,
+            "TTL": record.ttl,
+            "Status": if record.status == Status::Disable { "DISABLE" } else { "ENABLE" },
+        });
+
+        if record.record_type == Type::MX {
+            params["MX"] = json!(mx.unwrap_or(10));
+        }
+
+        params
+    }
+
+    fn parse_record_id(record_id: &str) -> Result<u64> {
+        record_id
+            .parse::<u64>()
+            .map_err(|_| anyhow!("无效的DNSPod记录ID: {}", record_id))
+    }
+}
+
+#[async_trait]
+impl DnsClientTrait for DnspodDnsClient {
+    /// 查询域名列表
+    async fn list_domains(&self, page_num: u32, page_size: u32) -> Result<Vec<DomainName>> {
+        let page_size = page_size.max(1);
+        let offset = page_num.saturating_sub(1) * page_size;
+        let response: DescribeDomainListResponse = self
+            .call_dnspod_api(
+                "DescribeDomainList",
+                json!({ "Offset": offset, "Limit": page_size }),
+            )
+            .await?;
+
+        let domain_list: Vec<DomainName> = response
+            .domain_list
+            .into_iter()
+            .map(|domain| DomainName {
+                name: domain.name,
+                provider: self.provider,
+                ..DomainName::default()
+            })
+            .collect();
+
+        info!("获取到 {} 个DNSPod域名", domain_list.len());
+        Ok(domain_list)
+    }
+
+    async fn query_domain(&self, _domain_name: &Domain) -> Result<DomainQueryResponse> {
+        Err(anyhow!("DNSPod 暂不支持查询域名注册信息"))
+    }
+
+    /// 查询DNS记录
+    async fn list_dns_records(&self, domain_name: String) -> Result<Vec<Record>> {
+        info!("正在获取域名 {} 的DNSPod解析记录...", domain_name);
+
+        // 单次请求最多返回 3000 条记录，按 TotalCount 分页获取全部记录
+        let mut records = Vec::new();
+        let mut offset = 0;
+        loop {
+            let result: Result<DescribeRecordListResponse> = self
+                .call_dnspod_api(
+                    "DescribeRecordList",
+                    json!({ "Domain": domain_name, "Offset": offset, "Limit": RECORD_PAGE_SIZE }),
+                )
+                .await;
+
+            let response = match result {
+                Ok(response) => response,
+                // 没有解析记录时 DNSPod 返回错误而不是空列表
+                Err(err) if err.to_string().contains(NO_DATA_OF_RECORD) => break,
+                Err(err) => {
+                    error!("获取DNSPod解析记录失败：「{:?}」", err);
+                    return Err(err);
+                }
+            };
+
+            let page_len = response.record_list.len() as u32;
+            records.extend(
+                response
+                    .record_list
+                    .iter()
+                    .map(Self::convert_dnspod_record_to_internal),
+            );
+            offset += page_len;
+            if page_len == 0 || offset >= response.record_count_info.total_count {
+                break;
+            }
+        }
+
+        info!("获取到 {} 条DNS记录", records.len());
+        Ok(records)
+    }
+
+    /// 添加DNS记录
+    async fn add_dns_record(&self, domain_name: &DomainName, record: &Record) -> Result<()> {
+        info!("正在添加DNSPod记录: {} -> {}", record.rr, record.value);
+
+        let mut params = Self::convert_internal_to_dnspod_params(record);
+        params["Domain"] = json!(domain_name.name);
+
+        let response: Value = self.call_dnspod_api("CreateRecord", params).await?;
+        info!("添加DNS记录结果：{:?}", response.get("RecordId"));
+        Ok(())
+    }
+
+    /// 删除DNS记录
+    async fn delete_dns_record(&self, domain_name: &DomainName, record_id: &str) -> Result<()> {
+        info!("正在删除DNSPod记录: {}", record_id);
+
+        let params = json!({
+            "Domain": domain_name.name,
+            "RecordId": Self::parse_record_id(record_id)?,
+        });
+
+        let _: Value = self.call_dnspod_api("DeleteRecord", params).await?;
+        info!("DNS记录删除成功");
+        Ok(())
+    }
+
+    /// 更新DNS记录
+    async fn update_dns_record(&self, domain_name: &DomainName, record: &Record) -> Result<()> {
+        info!("正在更新DNSPod记录: {} -> {}", record.rr, record.value);
+
+        let mut params = Self::convert_internal_to_dnspod_params(record);
+        params["Domain"] = json!(domain_name.name);
+        params["RecordId"] = json!(Self::parse_record_id(&record.record_id)?);
+
+        let _: Value = self.call_dnspod_api("ModifyRecord", params).await?;
+        info!("DNS记录更新成功");
+        Ok(())
+    }
+
+    /// 验证凭证是否有效
+    async fn validate_credentials(&self) -> Result<()> {
+        match self.list_domains(1, 1).await {
+            Ok(_) => {
+                info!("DNSPod凭证验证成功");
+                Ok(())
+            }
+            Err(err) => {
+                error!("DNSPod凭证验证失败: {:?}", err);
+                Err(err)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_record_list_response() {
+        let response = r#"{
+            "Response": {
+                "RecordCountInfo": { "SubdomainCount": 2, "ListCount": 2, "TotalCount": 2 },
+                "RecordList": [
+                    {
+                        "RecordId": 556507778, "Value": "f1g1ns1.dnspod.net.", "Status": "ENABLE",
+                        "UpdatedOn": "2021-03-28 11:27:09", "Name": "@", "Line": "默认",
+                        "LineId": "0", "Type": "NS", "MonitorStatus": "", "Remark": "",
+                        "TTL": 86400, "MX": 0
+                    },
+                    {
+                        "RecordId": 556507779, "Value": "mx.example.com.", "Status": "DISABLE",
+                        "UpdatedOn": "2021-03-28 11:27:09", "Name": "mail", "Line": "电信",
+                        "LineId": "0", "Type": "MX", "MonitorStatus": "", "Remark": "",
+                        "TTL": 600, "MX": 5, "Weight": null
+                    }
+                ],
+                "RequestId": "d5ed8d1f-4b4c-4aa5-b8e1-2b5cd2c7d0f6"
+            }
+        }"#;
+
+        let parsed: DescribeRecordListResponse = DnspodDnsClient::parse_response(response).unwrap();
+        let records: Vec<Record> = parsed
+            .record_list
+            .iter()
+            .map(DnspodDnsClient::convert_dnspod_record_to_internal)
+            .collect();
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].record_type, Type::NS);
+        assert_eq!(records[0].record_id, "556507778");
+        assert_eq!(records[0].status, Status::Enable);
+        assert_eq!(records[0].line, Line::Default);
+        assert_eq!(records[1].value, "5 mx.example.com.");
+        assert_eq!(records[1].line, Line::Custom("电信".to_string()));
+        assert_eq!(records[1].status, Status::Disable);
+    }
+
+    /// 服务端每页只返回两条记录时，按 TotalCount 翻页取回全部记录
+    #[tokio::test]
+    async fn test_list_dns_records_pages() {
+        use axum::{routing::post, Json, Router};
+
+        async fn describe_record_list(Json(body): Json<Value>) -> Json<Value> {
+            let offset = body["Offset"].as_u64().unwrap() as usize;
+            let records: Vec<Value> = (0..5)
+                .skip(offset)
+                .take(2)
+                .map(|i| {
+                    json!({
+                        "RecordId": i, "Name": format!("host{}", i), "Type": "A",
+                        "Value": format!("192.0.2.{}", i), "Status": "ENABLE",
+                        "TTL": 600, "Line": "默认"
+                    })
+                })
+                .collect();
+            Json(json!({
+                "Response": {
+                    "RecordCountInfo": { "SubdomainCount": 5, "ListCount": records.len(), "TotalCount": 5 },
+                    "RecordList": records,
+                    "RequestId": "paging"
+                }
+            }))
+        }
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let app = Router::new().route("/", post(describe_record_list));
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let client =
+            DnspodDnsClient::new("id".to_string(), "key".to_string()).with_endpoint(endpoint);
+        let records = client
+            .list_dns_records("example.com".to_string())
+            .await
+            .unwrap();
+        let ids: Vec<&str> = records.iter().map(|r| r.record_id.as_str()).collect();
+        assert_eq!(ids, ["0", "1", "2", "3", "4"]);
+    }
+
+    #[test]
+    fn test_parse_error_response() {
+        let response = r#"{
+            "Response": {
+                "Error": { "Code": "AuthFailure.SignatureFailure", "Message": "签名错误" },
+                "RequestId": "ed93f3cb-f35e-473f-b9f3-0d451b8b79c6"
+            }
+        }"#;
+
+        let err = DnspodDnsClient::parse_response::<Value>(response).unwrap_err();
+        assert!(err.to_string().contains("AuthFailure.SignatureFailure"));
+        assert!(err
+            .to_string()
+            .contains("ed93f3cb-f35e-473f-b9f3-0d451b8b79c6"));
+    }
+
+    #[test]
+    fn test_convert_mx_record_to_params() {
+        let record = Record::new(
+            Status::Enable,
+            "@".to_string(),
+            Type::MX,
+            "10 mxbiz1.qq.com".to_string(),
+            "".to_string(),
+            600,
+        );
+
+        let params = DnspodDnsClient::convert_internal_to_dnspod_params(&record);
+        assert_eq!(params["SubDomain"], "@");
+        assert_eq!(params["RecordType"], "MX");
+        assert_eq!(params["RecordLine"], DEFAULT_RECORD_LINE);
+        assert_eq!(params["Value"], "mxbiz1.qq.com");
+        assert_eq!(params["MX"], 10);
+
+        let mut record = record;
+        record.line = Line::Custom("电信".to_string());
+        let params = DnspodDnsClient::convert_internal_to_dnspod_params(&record);
+        assert_eq!(params["RecordLine"], "电信");
+    }
+}
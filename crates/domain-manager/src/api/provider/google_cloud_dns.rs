@@ -0,0 +1,652 @@
+use crate::api::dns_client::DnsClientTrait;
+use crate::api::model::domain::DomainQueryResponse;
+use crate::api::provider::rrset::{
+    append_value, decode_value, encode_value, parse_record_id, parse_record_type, record_id,
+    remove_value, replace_value, to_fqdn, to_rr,
+};
+use crate::gui::model::domain::{DnsProvider, Domain, DomainName};
+use crate::model::dns_record_response::{Record, Status};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use domain_clients::google::{fetch_access_token, ServiceAccountKey};
+use reqwest::{Client, Method};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, error, info};
+
+/// Cloud DNS 默认接口地址
+const CLOUD_DNS_ENDPOINT: &str = "https://dns.googleapis.com/dns/v1";
+/// Cloud DNS 读写权限
+const CLOUD_DNS_SCOPE: &str = "https://www.googleapis.com/auth/ndev.clouddns.readwrite";
+/// 访问令牌提前刷新的时间
+const TOKEN_REFRESH_MARGIN: Duration = Duration::from_secs(60);
+
+/// managedZones.list 响应
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ManagedZonesListResponse {
+    #[serde(default)]
+    managed_zones: Vec<ManagedZone>,
+    next_page_token: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ManagedZone {
+    name: String,
+    dns_name: String,
+    visibility: Option<String>,
+}
+
+/// resourceRecordSets.list 响应
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ResourceRecordSetsListResponse {
+    #[serde(default)]
+    rrsets: Vec<ResourceRecordSet>,
+    next_page_token: Option<String>,
+}
+
+/// Cloud DNS 记录集：同名同类型的所有记录值
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ResourceRecordSet {
+    name: String,
+    #[serde(rename = "type")]
+    record_type: String,
+    #[serde(default)]
+    ttl: i64,
+    #[serde(default)]
+    rrdatas: Vec<String>,
+    /// 带路由策略的记录集无法映射为普通记录，原样保留
+    #[serde(skip_serializing_if = "Option::is_none")]
+    routing_policy: Option<serde_json::Value>,
+}
+
+impl ResourceRecordSet {
+    fn new(name: String, record_type: String, ttl: i64, rrdatas: Vec<String>) -> Self {
+        Self {
+            name,
+            record_type,
+            ttl,
+            rrdatas,
+            routing_policy: None,
+        }
+    }
+}
+
+/// 一次原子变更：先删除 `deletions` 再添加 `additions`
+///
+/// 删除的记录集必须与云端完全一致，否则整个变更会被拒绝。
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Change {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    additions: Vec<ResourceRecordSet>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    deletions: Vec<ResourceRecordSet>,
+}
+
+impl Change {
+    /// 将现有记录集替换为新的记录值，值为空时只删除
+    fn replace(&mut self, existing: Option<&ResourceRecordSet>, updated: ResourceRecordSet) {
+        if let Some(existing) = existing {
+            self.deletions.push(existing.clone());
+        }
+        if !updated.rrdatas.is_empty() {
+            self.additions.push(updated);
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ChangeResponse {
+    id: String,
+    status: String,
+}
+
+/// Google API 错误响应
+#[derive(Debug, Deserialize)]
+struct ErrorResponse {
+    error: GoogleError,
+}
+
+#[derive(Debug, Deserialize)]
+struct GoogleError {
+    code: u16,
+    message: String,
+    #[serde(default)]
+    status: String,
+}
+
+/// 缓存的访问令牌
+#[derive(Debug, Clone)]
+struct CachedToken {
+    access_token: String,
+    expires_at: Instant,
+}
+
+/// Google Cloud DNS 客户端
+///
+/// 使用服务账号密钥签发 JWT 换取访问令牌，令牌在过期前会被复用。
+/// 记录的写操作通过 Cloud DNS 的 changes 接口原子提交。
+#[derive(Debug, Clone)]
+pub struct GoogleCloudDnsClient {
+    client: Client,
+    key: ServiceAccountKey,
+    project_id: String,
+    endpoint: String,
+    token: Arc<Mutex<Option<CachedToken>>>,
+}
+
+impl GoogleCloudDnsClient {
+    /// 创建新的Cloud DNS客户端
+    ///
+    /// # 参数
+    /// * `key_json` - 服务账号密钥文件内容
+    pub fn new(key_json: &str) -> Result<Self> {
+        let key = ServiceAccountKey::from_json(key_json).map_err(|e| anyhow!(e))?;
+        if key.project_id.is_empty() {
+            return Err(anyhow!("服务账号密钥中缺少 project_id"));
+        }
+
+        Ok(Self {
+            client: Client::new(),
+            project_id: key.project_id.clone(),
+            key,
+            endpoint: CLOUD_DNS_ENDPOINT.to_string(),
+            token: Arc::new(Mutex::new(None)),
+        })
+    }
+
+    /// 指定接口地址，用于测试
+    pub fn with_endpoint(mut self, endpoint: String) -> Self {
+        self.endpoint = endpoint;
+        self
+    }
+
+    /// 获取访问令牌，过期前复用缓存
+    async fn access_token(&self) -> Result<String> {
+        if let Some(token) = self.token.lock().unwrap().as_ref() {
+            if token.expires_at > Instant::now() + TOKEN_REFRESH_MARGIN {
+                return Ok(token.access_token.clone());
+            }
+        }
+
+        debug!("正在获取Google访问令牌：{}", self.key.client_email);
+        let token = fetch_access_token(&self.client, &self.key, CLOUD_DNS_SCOPE)
+            .await
+            .map_err(|e| anyhow!("获取Google访问令牌失败: {}", e))?;
+
+        let access_token = token.access_token.clone();
+        *self.token.lock().unwrap() = Some(CachedToken {
+            access_token: token.access_token,
+            expires_at: Instant::now() + Duration::from_secs(token.expires_in.max(0) as u64),
+        });
+        Ok(access_token)
+    }
+
+    /// 内部调用API的通用方法，`path` 为 `/projects/{project}` 之后的部分
+    async fn call_cloud_dns_api<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        query_params: &[(&str, &str)],
+        body: Option<&Change>,
+    ) -> Result<T> {
+        let url = format!(
+            "{}/projects/{}{}",
+            self.endpoint.trim_end_matches('/'),
+            self.project_id,
+            path
+        );
+        debug!("请求Cloud DNS接口：{} {}", method, url);
+
+        let access_token = self.access_token().await?;
+        let mut request_builder = self
+            .client
+            .request(method, &url)
+            .bearer_auth(access_token)
+            .query(query_params);
+        if let Some(body) = body {
+            request_builder = request_builder.json(body);
+        }
+
+        let response = request_builder
+            .send()
+            .await
+            .map_err(|e| anyhow!("Cloud DNS 接口请求失败: {}", e))?;
+        let (status, body) = domain_clients::read_response(response)
+            .await
+            .map_err(|e| anyhow!("Cloud DNS 接口请求失败: {}", e))?;
+
+        if !status.is_success() {
+            return Err(Self::parse_error(status.as_u16(), &body));
+        }
+
+        serde_json::from_str(&body).map_err(|e| {
+            error!("解析Cloud DNS响应失败：{:?}，响应内容：{}", e, body);
+            anyhow!("解析Cloud DNS响应失败: {}", e)
+        })
+    }
+
+    /// 解析错误响应
+    fn parse_error(status: u16, body: &str) -> anyhow::Error {
+        match serde_json::from_str::<ErrorResponse>(body) {
+            Ok(response) => anyhow!(
+                "Cloud DNS API Error: {} {} - {}",
+                response.error.code,
+                response.error.status,
+                response.error.message
+            ),
+            Err(_) => anyhow!("Cloud DNS API Error: HTTP {} {}", status, body),
+        }
+    }
+
+    /// 根据域名获取托管区域名称
+    async fn get_managed_zone(&self, domain_name: &str) -> Result<String> {
+        let dns_name = to_fqdn("@", domain_name);
+        let response: ManagedZonesListResponse = self
+            .call_cloud_dns_api(
+                Method::GET,
+                "/managedZones",
+                &[("dnsName", dns_name.as_str())],
+                None,
+            )
+            .await?;
+
+        response
+            .managed_zones
+            .into_iter()
+            .find(|zone| zone.dns_name.eq_ignore_ascii_case(&dns_name))
+            .map(|zone| zone.name)
+            .ok_or_else(|| anyhow!("未找到域名对应的托管区域: {}", domain_name))
+    }
+
+    /// 查询托管区域下的全部记录集
+    async fn list_record_sets(&self, zone: &str) -> Result<Vec<ResourceRecordSet>> {
+        let path = format!("/managedZones/{}/rrsets", zone);
+        let mut record_sets = Vec::new();
+        let mut page_token: Option<String> = None;
+
+        loop {
+            let mut query_params = vec![];
+            if let Some(page_token) = &page_token {
+                query_params.push(("pageToken", page_token.as_str()));
+            }
+
+            let response: ResourceRecordSetsListResponse = self
+                .call_cloud_dns_api(Method::GET, &path, &query_params, None)
+                .await?;
+            record_sets.extend(response.rrsets);
+
+            match response.next_page_token {
+                Some(next) if !next.is_empty() => page_token = Some(next),
+                _ => break,
+            }
+        }
+
+        Ok(record_sets)
+    }
+
+    /// 查询指定名称和类型的记录集
+    async fn find_record_set(
+        &self,
+        zone: &str,
+        name: &str,
+        record_type: &str,
+    ) -> Result<Option<ResourceRecordSet>> {
+        let path = format!("/managedZones/{}/rrsets", zone);
+        let response: ResourceRecordSetsListResponse = self
+            .call_cloud_dns_api(
+                Method::GET,
+                &path,
+                &[("name", name), ("type", record_type)],
+                None,
+            )
+            .await?;
+
+        Ok(response
+            .rrsets
+            .into_iter()
+            .find(|set| set.name.eq_ignore_ascii_case(name) && set.record_type == record_type))
+    }
+
+    /// 提交变更
+    async fn create_change(&self, zone: &str, change: &Change) -> Result<()> {
+        let path = format!("/managedZones/{}/changes", zone);
+        let response: ChangeResponse = self
+            .call_cloud_dns_api(Method::POST, &path, &[], Some(change))
+            .await?;
+        info!(
+            "Cloud DNS变更已提交：{}，状态：{}",
+            response.id, response.status
+        );
+        Ok(())
+    }
+}
+
+/// 将记录集转换为内部Record格式，带路由策略的记录集无法表示，会被跳过
+fn convert_record_set_to_internal(
+    record_set: &ResourceRecordSet,
+    domain_name: &str,
+) -> Vec<Record> {
+    if record_set.routing_policy.is_some() {
+        debug!(
+            "跳过带路由策略的记录集：{} {}",
+            record_set.name, record_set.record_type
+        );
+        return vec![];
+    }
+
+    let Some(record_type) = parse_record_type(&record_set.record_type) else {
+        debug!("跳过不支持的记录类型：{}", record_set.record_type);
+        return vec![];
+    };
+
+    let rr = to_rr(&record_set.name, domain_name);
+    record_set
+        .rrdatas
+        .iter()
+        .map(|value| {
+            Record::new(
+                Status::Enable,
+                rr.clone(),
+                record_type.clone(),
+                decode_value(&record_type, value),
+                record_id(&record_set.name, &record_set.record_type, value),
+                record_set.ttl as i32,
+            )
+        })
+        .collect()
+}
+
+/// 构建添加一个记录值的变更
+fn add_value_change(
+    existing: Option<&ResourceRecordSet>,
+    name: &str,
+    record_type: &str,
+    ttl: i64,
+    value: String,
+) -> Change {
+    let values = match existing {
+        Some(record_set) => append_value(&record_set.rrdatas, value),
+        None => vec![value],
+    };
+
+    let mut change = Change::default();
+    change.replace(
+        existing,
+        ResourceRecordSet::new(name.to_string(), record_type.to_string(), ttl, values),
+    );
+    change
+}
+
+/// 构建删除一个记录值的变更，记录集中没有剩余值时整体删除
+fn remove_value_change(record_set: &ResourceRecordSet, value: &str) -> Result<Change> {
+    let remaining = remove_value(&record_set.rrdatas, value)?;
+
+    let mut change = Change::default();
+    change.replace(
+        Some(record_set),
+        ResourceRecordSet::new(
+            record_set.name.clone(),
+            record_set.record_type.clone(),
+            record_set.ttl,
+            remaining,
+        ),
+    );
+    Ok(change)
+}
+
+#[async_trait]
+impl DnsClientTrait for GoogleCloudDnsClient {
+    /// 获取域名列表（Cloud DNS中为公共托管区域列表）
+    async fn list_domains(&self, _page_num: u32, _page_size: u32) -> Result<Vec<DomainName>> {
+        info!("正在获取Cloud DNS托管区域列表...");
+
+        let mut domain_list = Vec::new();
+        let mut page_token: Option<String> = None;
+
+        loop {
+            let mut query_params = vec![];
+            if let Some(page_token) = &page_token {
+                query_params.push(("pageToken", page_token.as_str()));
+            }
+
+            let response: ManagedZonesListResponse = self
+                .call_cloud_dns_api(Method::GET, "/managedZones", &query_params, None)
+                .await?;
+
+            domain_list.extend(
+                response
+                    .managed_zones
+                    .into_iter()
+                    .filter(|zone| zone.visibility.as_deref() != Some("private"))
+                    .map(|zone| DomainName {
+                        name: zone.dns_name.trim_end_matches('.').to_string(),
+                        provider: DnsProvider::Google,
+                        ..DomainName::default()
+                    }),
+            );
+
+            match response.next_page_token {
+                Some(next) if !next.is_empty() => page_token = Some(next),
+                _ => break,
+            }
+        }
+
+        info!("获取到 {} 个Cloud DNS托管区域", domain_list.len());
+        Ok(domain_list)
+    }
+
+    async fn query_domain(&self, _domain_name: &Domain) -> Result<DomainQueryResponse> {
+        Err(anyhow!("Cloud DNS 暂不支持查询域名注册信息"))
+    }
+
+    /// 获取DNS记录列表
+    async fn list_dns_records(&self, domain_name: String) -> Result<Vec<Record>> {
+        info!("正在获取域名 {} 的Cloud DNS记录...", domain_name);
+
+        let zone = self.get_managed_zone(&domain_name).await?;
+        let records: Vec<Record> = self
+            .list_record_sets(&zone)
+            .await?
+            .iter()
+            .flat_map(|record_set| convert_record_set_to_internal(record_set, &domain_name))
+            .collect();
+
+        info!("获取到 {} 条DNS记录", records.len());
+        Ok(records)
+    }
+
+    /// 添加DNS记录
+    async fn add_dns_record(&self, domain_name: &DomainName, record: &Record) -> Result<()> {
+        info!("正在添加Cloud DNS记录: {} -> {}", record.rr, record.value);
+
+        let zone = self.get_managed_zone(&domain_name.name).await?;
+        let name = to_fqdn(&record.rr, &domain_name.name);
+        let record_type = record.record_type.get_value();
+        let existing = self.find_record_set(&zone, &name, record_type).await?;
+
+        let change = add_value_change(
+            existing.as_ref(),
+            &name,
+            record_type,
+            record.ttl as i64,
+            encode_value(&record.record_type, &record.value),
+        );
+        self.create_change(&zone, &change).await
+    }
+
+    /// 删除DNS记录
+    async fn delete_dns_record(&self, domain_name: &DomainName, record_id: &str) -> Result<()> {
+        info!("正在删除Cloud DNS记录: {}", record_id);
+
+        let (name, record_type, value) = parse_record_id(record_id)?;
+        let zone = self.get_managed_zone(&domain_name.name).await?;
+        let record_set = self
+            .find_record_set(&zone, &name, &record_type)
+            .await?
+            .ok_or_else(|| anyhow!("在云端未找到匹配的记录集: {} {}", name, record_type))?;
+
+        let change = remove_value_change(&record_set, &value)?;
+        self.create_change(&zone, &change).await
+    }
+
+    /// 更新DNS记录
+    ///
+    /// 名称和类型不变时替换所在记录集，否则在同一个变更中移除旧值并添加新值。
+    async fn update_dns_record(&self, domain_name: &DomainName, record: &Record) -> Result<()> {
+        info!("正在更新Cloud DNS记录: {} -> {}", record.rr, record.value);
+
+        let (old_name, old_type, old_value) = parse_record_id(&record.record_id)?;
+        let zone = self.get_managed_zone(&domain_name.name).await?;
+        let old_set = self
+            .find_record_set(&zone, &old_name, &old_type)
+            .await?
+            .ok_or_else(|| anyhow!("在云端未找到匹配的记录集: {} {}", old_name, old_type))?;
+
+        let new_name = to_fqdn(&record.rr, &domain_name.name);
+        let new_type = record.record_type.get_value();
+        let new_value = encode_value(&record.record_type, &record.value);
+
+        let change = if old_name.eq_ignore_ascii_case(&new_name) && old_type == new_type {
+            let mut change = Change::default();
+            change.replace(
+                Some(&old_set),
+                ResourceRecordSet::new(
+                    old_set.name.clone(),
+                    old_type,
+                    record.ttl as i64,
+                    replace_value(&old_set.rrdatas, &old_value, &new_value),
+                ),
+            );
+            change
+        } else {
+            let new_set = self.find_record_set(&zone, &new_name, new_type).await?;
+            let removal = remove_value_change(&old_set, &old_value)?;
+            let addition = add_value_change(
+                new_set.as_ref(),
+                &new_name,
+                new_type,
+                record.ttl as i64,
+                new_value,
+            );
+            Change {
+                additions: [removal.additions, addition.additions].concat(),
+                deletions: [removal.deletions, addition.deletions].concat(),
+            }
+        };
+
+        self.create_change(&zone, &change).await
+    }
+
+    /// 验证凭证是否有效
+    async fn validate_credentials(&self) -> Result<()> {
+        let result: Result<ManagedZonesListResponse> = self
+            .call_cloud_dns_api(Method::GET, "/managedZones", &[("maxResults", "1")], None)
+            .await;
+        match result {
+            Ok(_) => {
+                info!("Cloud DNS凭证验证成功");
+                Ok(())
+            }
+            Err(err) => {
+                error!("Cloud DNS凭证验证失败: {:?}", err);
+                Err(err)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::dns_record_response::Type;
+
+    #[test]
+    fn test_convert_record_sets() {
+        let response: ResourceRecordSetsListResponse = serde_json::from_str(
+            r#"{
+                "kind": "dns#resourceRecordSetsListResponse",
+                "rrsets": [
+                    {"kind": "dns#resourceRecordSet", "name": "example.com.", "type": "MX", "ttl": 300,
+                     "rrdatas": ["10 mx1.example.com.", "20 mx2.example.com."]},
+                    {"kind": "dns#resourceRecordSet", "name": "txt.example.com.", "type": "TXT", "ttl": 60,
+                     "rrdatas": ["\"hello\" \"world\""]},
+                    {"kind": "dns#resourceRecordSet", "name": "geo.example.com.", "type": "A", "ttl": 60,
+                     "routingPolicy": {"geo": {"items": []}}}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let records: Vec<Record> = response
+            .rrsets
+            .iter()
+            .flat_map(|set| convert_record_set_to_internal(set, "example.com"))
+            .collect();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[1].rr, "@");
+        assert_eq!(records[1].record_id, "example.com.|MX|20 mx2.example.com.");
+        assert_eq!(records[2].rr, "txt");
+        assert_eq!(records[2].record_type, Type::TXT);
+        assert_eq!(records[2].value, "helloworld");
+    }
+
+    #[test]
+    fn test_change_serialization() {
+        let existing = ResourceRecordSet::new(
+            "www.example.com.".to_string(),
+            "A".to_string(),
+            300,
+            vec!["192.0.2.1".to_string()],
+        );
+
+        let change = add_value_change(
+            Some(&existing),
+            "www.example.com.",
+            "A",
+            600,
+            "192.0.2.2".to_string(),
+        );
+        assert_eq!(
+            serde_json::to_value(&change).unwrap(),
+            serde_json::json!({
+                "additions": [{"name": "www.example.com.", "type": "A", "ttl": 600,
+                               "rrdatas": ["192.0.2.1", "192.0.2.2"]}],
+                "deletions": [{"name": "www.example.com.", "type": "A", "ttl": 300,
+                               "rrdatas": ["192.0.2.1"]}],
+            })
+        );
+
+        // 删除最后一个值时只保留 deletions
+        let change = remove_value_change(&existing, "192.0.2.1").unwrap();
+        assert!(change.additions.is_empty());
+        assert_eq!(change.deletions, vec![existing.clone()]);
+        assert!(remove_value_change(&existing, "192.0.2.9").is_err());
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let err = GoogleCloudDnsClient::parse_error(
+            404,
+            r#"{"error": {"code": 404, "message": "The 'parameters.managedZone' resource named 'demo' does not exist.", "status": "NOT_FOUND"}}"#,
+        );
+        assert!(err.to_string().contains("NOT_FOUND"));
+        assert!(GoogleCloudDnsClient::parse_error(502, "Bad Gateway")
+            .to_string()
+            .contains("502"));
+    }
+
+    #[test]
+    fn test_new_requires_project_id() {
+        assert!(
+            GoogleCloudDnsClient::new(r#"{"private_key": "", "client_email": "a@b"}"#).is_err()
+        );
+        assert!(GoogleCloudDnsClient::new("not json").is_err());
+    }
+}
@@ -1,2 +1,64 @@
 pub mod aliyun;
 pub mod cloudflare_provider;
+pub mod dnspod;
+pub mod google_cloud_dns;
+pub mod rfc2136;
+pub mod route53;
+pub mod rrset;
+
+use crate::api::dns_client::DnsClientTrait;
+use crate::api::provider::aliyun::AliyunDnsClient;
+use crate::api::provider::cloudflare_provider::CloudflareDnsClient;
+use crate::api::provider::dnspod::DnspodDnsClient;
+use crate::api::provider::google_cloud_dns::GoogleCloudDnsClient;
+use crate::api::provider::rfc2136::Rfc2136DnsClient;
+use crate::api::provider::route53::Route53DnsClient;
+use crate::gui::model::domain::DnsProvider;
+use crate::gui::types::credential::Credential;
+use crate::models::account::Account;
+use anyhow::{anyhow, Result};
+
+/// 可在线程间共享的DNS客户端
+pub type BoxedDnsClient = Box<dyn DnsClientTrait + Send + Sync>;
+
+/// 根据托管商类型和凭证创建对应的DNS客户端
+pub fn create_dns_client(provider: DnsProvider, credential: Credential) -> Result<BoxedDnsClient> {
+    match (provider, credential) {
+        (DnsProvider::Aliyun, Credential::ApiKey(key)) => {
+            Ok(Box::new(AliyunDnsClient::new(key.api_key, key.api_secret)))
+        }
+        (DnsProvider::TencentCloud | DnsProvider::Dnspod, Credential::ApiKey(key)) => Ok(Box::new(
+            DnspodDnsClient::new(key.api_key, key.api_secret).with_provider(provider),
+        )),
+        (DnsProvider::Aws, Credential::ApiKey(key)) => {
+            Ok(Box::new(Route53DnsClient::new(key.api_key, key.api_secret)))
+        }
+        (DnsProvider::Google, Credential::ServiceAccount(service_account)) => Ok(Box::new(
+            GoogleCloudDnsClient::new(&service_account.key_json)?,
+        )),
+        (DnsProvider::Rfc2136, Credential::Tsig(tsig)) => {
+            Ok(Box::new(Rfc2136DnsClient::new(&tsig)?))
+        }
+        (DnsProvider::CloudFlare, Credential::Token(token)) => Ok(Box::new(
+            CloudflareDnsClient::new(token.token, String::new())?,
+        )),
+        (provider, credential) => Err(anyhow!(
+            "不支持的托管商或凭据类型：「{}」/「{}」",
+            provider.name(),
+            credential.credential_type()
+        )),
+    }
+}
+
+/// 根据账户信息创建对应的DNS客户端
+///
+/// 早期创建的账户没有记录托管商类型，这类账户按阿里云处理。
+pub fn create_dns_client_for_account(account: Account) -> Result<(DnsProvider, BoxedDnsClient)> {
+    let provider = DnsProvider::ALL
+        .into_iter()
+        .find(|provider| provider.value() == account.provider_type)
+        .unwrap_or(DnsProvider::Aliyun);
+    let credential: Credential = account.try_into()?;
+    let client = create_dns_client(provider, credential)?;
+    Ok((provider, client))
+}
@@ -0,0 +1,495 @@
+use crate::api::dns_client::DnsClientTrait;
+use crate::api::model::domain::DomainQueryResponse;
+use crate::api::provider::rrset::{parse_record_id, parse_record_type, record_id, to_fqdn, to_rr};
+use crate::gui::model::domain::{DnsProvider, Domain, DomainName};
+use crate::gui::types::credential::TsigCredential;
+use crate::model::dns_record_response::{Record, Status, Type};
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use base64::Engine;
+use hickory_proto::op::{Message, MessageType, OpCode, Query, ResponseCode, UpdateMessage};
+use hickory_proto::rr::dnssec::rdata::tsig::TsigAlgorithm;
+use hickory_proto::rr::dnssec::tsig::TSigner;
+use hickory_proto::rr::rdata::{A, AAAA, CNAME, MX, NS, PTR, SRV, TXT};
+use hickory_proto::rr::{DNSClass, Name, RData, Record as DnsRecord, RecordType};
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{lookup_host, TcpStream};
+use tokio::time::timeout;
+use tracing::{debug, error, info};
+
+/// 默认 DNS 端口
+const DEFAULT_DNS_PORT: u16 = 53;
+/// TSIG 允许的时间误差（秒）
+const TSIG_FUDGE: u16 = 300;
+/// 默认网络超时
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// RFC 2136 动态更新客户端
+///
+/// 通过 AXFR 区域传送获取记录，通过 UPDATE 报文增删改记录，所有请求和响应都使用 TSIG 认证。
+/// 由于服务器没有列出区域的接口，需要管理的区域来自凭证中的区域列表。
+/// 记录ID沿用记录集约定 `完整域名|类型|值`，删除时按记录值精确匹配。
+pub struct Rfc2136DnsClient {
+    server: String,
+    signer: TSigner,
+    zones: Vec<String>,
+    timeout: Duration,
+}
+
+impl Rfc2136DnsClient {
+    /// 根据TSIG凭证创建客户端
+    pub fn new(credential: &TsigCredential) -> Result<Self> {
+        if credential.server.trim().is_empty() {
+            return Err(anyhow!("服务器地址不能为空"));
+        }
+
+        let key_name = fqdn_name(credential.key_name.trim())?;
+        let algorithm = TsigAlgorithm::from_name(
+            Name::from_ascii(credential.algorithm.trim().to_ascii_lowercase())
+                .map_err(|e| anyhow!("无效的TSIG算法: {}", e))?,
+        );
+        let secret = base64::engine::general_purpose::STANDARD
+            .decode(credential.secret.trim())
+            .map_err(|e| anyhow!("TSIG密钥不是有效的Base64: {}", e))?;
+
+        let signer = TSigner::new(secret, algorithm, key_name, TSIG_FUDGE)
+            .map_err(|e| anyhow!("不支持的TSIG算法「{}」: {}", credential.algorithm, e))?;
+
+        Ok(Self {
+            server: credential.server.trim().to_string(),
+            signer,
+            zones: credential.zone_list(),
+            timeout: DEFAULT_TIMEOUT,
+        })
+    }
+
+    /// 设置网络超时
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// 解析服务器地址，未指定端口时使用53
+    async fn server_addr(&self) -> Result<SocketAddr> {
+        if let Ok(addr) = self.server.parse::<SocketAddr>() {
+            return Ok(addr);
+        }
+        if let Ok(ip) = self.server.parse::<IpAddr>() {
+            return Ok(SocketAddr::new(ip, DEFAULT_DNS_PORT));
+        }
+
+        let host = if self.server.contains(':') {
+            self.server.clone()
+        } else {
+            format!("{}:{}", self.server, DEFAULT_DNS_PORT)
+        };
+        let mut addrs = lookup_host(host)
+            .await
+            .with_context(|| format!("解析服务器地址失败: {}", self.server))?;
+        addrs
+            .next()
+            .ok_or_else(|| anyhow!("解析服务器地址失败: {}", self.server))
+    }
+
+    /// 通过TCP发送签名后的报文，返回经过TSIG校验的响应
+    ///
+    /// 区域传送的响应可能分为多个报文，读取到第二条SOA记录时结束。
+    async fn exchange(&self, mut message: Message, is_transfer: bool) -> Result<Vec<Message>> {
+        let now = domain_clients::current_timestamp()? as u32;
+        let mut verifier = message
+            .finalize(&self.signer, now)
+            .map_err(|e| anyhow!("TSIG签名失败: {}", e))?
+            .ok_or_else(|| anyhow!("TSIG签名失败"))?;
+        let request = message.to_vec()?;
+
+        let addr = self.server_addr().await?;
+        debug!("发送DNS报文到 {}：{} 字节", addr, request.len());
+
+        let mut stream = timeout(self.timeout, TcpStream::connect(addr))
+            .await
+            .map_err(|_| anyhow!("连接DNS服务器超时: {}", addr))?
+            .with_context(|| format!("连接DNS服务器失败: {}", addr))?;
+
+        let mut frame = Vec::with_capacity(request.len() + 2);
+        frame.extend_from_slice(&(request.len() as u16).to_be_bytes());
+        frame.extend_from_slice(&request);
+        stream.write_all(&frame).await?;
+
+        let mut responses = Vec::new();
+        let mut soa_count = 0;
+        loop {
+            let bytes = timeout(self.timeout, read_frame(&mut stream))
+                .await
+                .map_err(|_| anyhow!("等待DNS服务器响应超时: {}", addr))??;
+
+            let response = match verifier(&bytes) {
+                Ok(response) => response.into_message(),
+                Err(err) => {
+                    // 密钥错误时服务器返回的响应不带签名，优先报告响应码
+                    if let Ok(response) = Message::from_vec(&bytes) {
+                        check_response_code(&response)?;
+                    }
+                    return Err(anyhow!("TSIG校验失败: {}", err));
+                }
+            };
+            check_response_code(&response)?;
+
+            soa_count += response
+                .answers()
+                .iter()
+                .filter(|record| record.record_type() == RecordType::SOA)
+                .count();
+            responses.push(response);
+
+            if !is_transfer || soa_count >= 2 {
+                break;
+            }
+        }
+
+        Ok(responses)
+    }
+
+    /// 通过AXFR获取区域内的全部资源记录
+    async fn zone_transfer(&self, zone: &Name) -> Result<Vec<DnsRecord>> {
+        let mut message = new_message(OpCode::Query);
+        message.add_query(Query::query(zone.clone(), RecordType::AXFR));
+
+        let responses = self.exchange(message, true).await?;
+        Ok(responses
+            .into_iter()
+            .flat_map(|response| response.into_parts().answers)
+            .collect())
+    }
+
+    /// 发送UPDATE报文
+    async fn send_update(&self, zone: &Name, updates: Vec<DnsRecord>) -> Result<()> {
+        let mut message = new_message(OpCode::Update);
+        let mut query = Query::query(zone.clone(), RecordType::SOA);
+        query.set_query_class(DNSClass::IN);
+        message.add_zone(query);
+        message.add_updates(updates);
+
+        self.exchange(message, false).await?;
+        info!("DNS动态更新成功：{}", zone);
+        Ok(())
+    }
+}
+
+/// 创建一个新的请求报文
+fn new_message(op_code: OpCode) -> Message {
+    let mut message = Message::new();
+    message
+        .set_id(rand::random())
+        .set_message_type(MessageType::Query)
+        .set_op_code(op_code)
+        .set_recursion_desired(false);
+    message
+}
+
+/// 读取一个TCP DNS报文（两字节长度前缀）
+async fn read_frame(stream: &mut TcpStream) -> Result<Vec<u8>> {
+    let length = stream
+        .read_u16()
+        .await
+        .context("读取DNS响应失败，服务器已关闭连接")?;
+    let mut buffer = vec![0; length as usize];
+    stream.read_exact(&mut buffer).await?;
+    Ok(buffer)
+}
+
+fn check_response_code(response: &Message) -> Result<()> {
+    match response.response_code() {
+        ResponseCode::NoError => Ok(()),
+        code => Err(anyhow!("DNS服务器返回错误: {}", code)),
+    }
+}
+
+/// 将域名解析为完整域名
+fn fqdn_name(name: &str) -> Result<Name> {
+    let mut name = Name::from_ascii(name).map_err(|e| anyhow!("无效的域名「{}」: {}", name, e))?;
+    name.set_fqdn(true);
+    Ok(name)
+}
+
+/// 将DNS记录数据转换为内部记录值，不支持的类型返回 None
+pub(crate) fn format_rdata(rdata: &RData) -> Option<(Type, String)> {
+    match rdata {
+        RData::A(a) => Some((Type::A, a.0.to_string())),
+        RData::AAAA(aaaa) => Some((Type::AAAA, aaaa.0.to_string())),
+        RData::CNAME(name) => Some((Type::Cname, name.0.to_ascii())),
+        RData::NS(name) => Some((Type::NS, name.0.to_ascii())),
+        RData::PTR(name) => Some((Type::PTR, name.0.to_ascii())),
+        RData::MX(mx) => Some((
+            Type::MX,
+            format!("{} {}", mx.preference(), mx.exchange().to_ascii()),
+        )),
+        RData::SRV(srv) => Some((
+            Type::SRV,
+            format!(
+                "{} {} {} {}",
+                srv.priority(),
+                srv.weight(),
+                srv.port(),
+                srv.target().to_ascii()
+            ),
+        )),
+        RData::TXT(txt) => Some((
+            Type::TXT,
+            txt.txt_data()
+                .iter()
+                .map(|part| String::from_utf8_lossy(part).into_owned())
+                .collect(),
+        )),
+        _ => None,
+    }
+}
+
+/// 解析记录ID中的记录类型
+fn parse_type(record_type: &str) -> Result<Type> {
+    parse_record_type(record_type)
+        .ok_or_else(|| anyhow!("RFC 2136 不支持的记录类型: {}", record_type))
+}
+
+/// 将内部记录值解析为DNS记录数据
+fn parse_rdata(record_type: &Type, value: &str) -> Result<RData> {
+    let value = value.trim();
+    let fields: Vec<&str> = value.split_whitespace().collect();
+    let invalid = || anyhow!("无效的{}记录值: {}", record_type.get_value(), value);
+
+    let rdata = match record_type {
+        Type::A => RData::A(A(value.parse().map_err(|_| invalid())?)),
+        Type::AAAA => RData::AAAA(AAAA(value.parse().map_err(|_| invalid())?)),
+        Type::Cname => RData::CNAME(CNAME(fqdn_name(value)?)),
+        Type::NS => RData::NS(NS(fqdn_name(value)?)),
+        Type::PTR => RData::PTR(PTR(fqdn_name(value)?)),
+        Type::MX => match fields.as_slice() {
+            [preference, exchange] => RData::MX(MX::new(
+                preference.parse().map_err(|_| invalid())?,
+                fqdn_name(exchange)?,
+            )),
+            _ => return Err(invalid()),
+        },
+        Type::SRV => match fields.as_slice() {
+            [priority, weight, port, target] => RData::SRV(SRV::new(
+                priority.parse().map_err(|_| invalid())?,
+                weight.parse().map_err(|_| invalid())?,
+                port.parse().map_err(|_| invalid())?,
+                fqdn_name(target)?,
+            )),
+            _ => return Err(invalid()),
+        },
+        Type::TXT => {
+            // 单个字符串最长 255 个字节
+            let chunks = value
+                .as_bytes()
+                .chunks(255)
+                .map(|chunk| String::from_utf8_lossy(chunk).into_owned())
+                .collect();
+            RData::TXT(TXT::new(chunks))
+        }
+        other => return Err(anyhow!("RFC 2136 不支持的记录类型: {}", other.get_value())),
+    };
+    Ok(rdata)
+}
+
+/// 构建添加记录的更新
+fn add_update(name: Name, ttl: u32, rdata: RData) -> DnsRecord {
+    let mut record = DnsRecord::from_rdata(name, ttl, rdata);
+    record.set_dns_class(DNSClass::IN);
+    record
+}
+
+/// 构建删除指定记录值的更新：CLASS 为 NONE，TTL 为 0
+fn delete_update(name: Name, rdata: RData) -> DnsRecord {
+    let mut record = DnsRecord::from_rdata(name, 0, rdata);
+    record.set_dns_class(DNSClass::NONE);
+    record
+}
+
+/// 将区域传送得到的记录转换为内部Record格式，SOA 及不支持的类型会被跳过
+fn convert_to_internal(records: &[DnsRecord], domain_name: &str) -> Vec<Record> {
+    records
+        .iter()
+        .filter_map(|record| {
+            let (record_type, value) = format_rdata(record.data()?)?;
+            let name = record.name().to_ascii();
+            Some(Record::new(
+                Status::Enable,
+                to_rr(&name, domain_name),
+                record_type.clone(),
+                value.clone(),
+                record_id(&name, record_type.get_value(), &value),
+                record.ttl() as i32,
+            ))
+        })
+        .collect()
+}
+
+#[async_trait]
+impl DnsClientTrait for Rfc2136DnsClient {
+    /// 获取域名列表（凭证中配置的区域）
+    async fn list_domains(&self, _page_num: u32, _page_size: u32) -> Result<Vec<DomainName>> {
+        if self.zones.is_empty() {
+            return Err(anyhow!("未配置需要管理的区域"));
+        }
+
+        Ok(self
+            .zones
+            .iter()
+            .map(|zone| DomainName {
+                name: zone.clone(),
+                provider: DnsProvider::Rfc2136,
+                dns_record: vec![],
+            })
+            .collect())
+    }
+
+    async fn query_domain(&self, _domain_name: &Domain) -> Result<DomainQueryResponse> {
+        Err(anyhow!("RFC 2136 不支持查询域名注册信息"))
+    }
+
+    /// 通过AXFR获取DNS记录列表
+    async fn list_dns_records(&self, domain_name: String) -> Result<Vec<Record>> {
+        info!("正在通过AXFR获取区域 {} 的记录...", domain_name);
+
+        let zone = fqdn_name(&domain_name)?;
+        let records = self.zone_transfer(&zone).await?;
+        let records = convert_to_internal(&records, &domain_name);
+
+        info!("获取到 {} 条DNS记录", records.len());
+        Ok(records)
+    }
+
+    /// 添加DNS记录
+    async fn add_dns_record(&self, domain_name: &DomainName, record: &Record) -> Result<()> {
+        info!("正在添加DNS记录: {} -> {}", record.rr, record.value);
+
+        let zone = fqdn_name(&domain_name.name)?;
+        let name = fqdn_name(&to_fqdn(&record.rr, &domain_name.name))?;
+        let rdata = parse_rdata(&record.record_type, &record.value)?;
+
+        self.send_update(&zone, vec![add_update(name, record.ttl as u32, rdata)])
+            .await
+    }
+
+    /// 删除DNS记录
+    async fn delete_dns_record(&self, domain_name: &DomainName, record_id: &str) -> Result<()> {
+        info!("正在删除DNS记录: {}", record_id);
+
+        let (name, record_type, value) = parse_record_id(record_id)?;
+        let zone = fqdn_name(&domain_name.name)?;
+        let rdata = parse_rdata(&parse_type(&record_type)?, &value)?;
+
+        self.send_update(&zone, vec![delete_update(fqdn_name(&name)?, rdata)])
+            .await
+    }
+
+    /// 更新DNS记录：在同一个UPDATE报文中删除旧值并添加新值
+    async fn update_dns_record(&self, domain_name: &DomainName, record: &Record) -> Result<()> {
+        info!("正在更新DNS记录: {} -> {}", record.rr, record.value);
+
+        let (old_name, old_type, old_value) = parse_record_id(&record.record_id)?;
+        let zone = fqdn_name(&domain_name.name)?;
+        let old_rdata = parse_rdata(&parse_type(&old_type)?, &old_value)?;
+
+        let new_name = fqdn_name(&to_fqdn(&record.rr, &domain_name.name))?;
+        let new_rdata = parse_rdata(&record.record_type, &record.value)?;
+
+        self.send_update(
+            &zone,
+            vec![
+                delete_update(fqdn_name(&old_name)?, old_rdata),
+                add_update(new_name, record.ttl as u32, new_rdata),
+            ],
+        )
+        .await
+    }
+
+    /// 使用签名的SOA查询验证服务器地址和TSIG密钥
+    async fn validate_credentials(&self) -> Result<()> {
+        let zone = self
+            .zones
+            .first()
+            .ok_or_else(|| anyhow!("未配置需要管理的区域"))?;
+
+        let mut message = new_message(OpCode::Query);
+        message.add_query(Query::query(fqdn_name(zone)?, RecordType::SOA));
+
+        match self.exchange(message, false).await {
+            Ok(_) => {
+                info!("TSIG凭证验证成功");
+                Ok(())
+            }
+            Err(err) => {
+                error!("TSIG凭证验证失败: {:?}", err);
+                Err(err)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rdata_round_trip() {
+        let cases = [
+            (Type::A, "192.0.2.1"),
+            (Type::AAAA, "2001:db8::1"),
+            (Type::Cname, "www.example.com."),
+            (Type::MX, "10 mail.example.com."),
+            (Type::SRV, "0 5 5060 sip.example.com."),
+            (Type::TXT, "v=spf1 include:example.net ~all"),
+        ];
+
+        for (record_type, value) in cases {
+            let rdata = parse_rdata(&record_type, value).unwrap();
+            assert_eq!(format_rdata(&rdata), Some((record_type, value.to_string())));
+        }
+
+        // 未以 . 结尾的目标按完整域名处理
+        let rdata = parse_rdata(&Type::Cname, "www.example.com").unwrap();
+        assert_eq!(format_rdata(&rdata).unwrap().1, "www.example.com.");
+
+        assert!(parse_rdata(&Type::MX, "mail.example.com.").is_err());
+        assert!(parse_rdata(&Type::A, "not-an-ip").is_err());
+    }
+
+    #[test]
+    fn test_long_txt_is_split() {
+        let value = "a".repeat(300);
+        let RData::TXT(txt) = parse_rdata(&Type::TXT, &value).unwrap() else {
+            panic!("expected TXT");
+        };
+        assert_eq!(txt.txt_data().len(), 2);
+        assert_eq!(format_rdata(&RData::TXT(txt)).unwrap(), (Type::TXT, value));
+    }
+
+    #[test]
+    fn test_new_validates_credential() {
+        let credential = TsigCredential {
+            server: "127.0.0.1:53".to_string(),
+            key_name: "update-key".to_string(),
+            secret: "c2VjcmV0".to_string(),
+            zones: "example.com, corp.lan.".to_string(),
+            ..TsigCredential::default()
+        };
+        let client = Rfc2136DnsClient::new(&credential).unwrap();
+        assert_eq!(client.zones, vec!["example.com", "corp.lan"]);
+
+        let invalid_secret = TsigCredential {
+            secret: "not base64!".to_string(),
+            ..credential.clone()
+        };
+        assert!(Rfc2136DnsClient::new(&invalid_secret).is_err());
+
+        let unsupported = TsigCredential {
+            algorithm: "hmac-md5".to_string(),
+            ..credential
+        };
+        assert!(Rfc2136DnsClient::new(&unsupported).is_err());
+    }
+}
@@ -0,0 +1,833 @@
+use crate::api::dns_client::DnsClientTrait;
+use crate::api::model::domain::DomainQueryResponse;
+use crate::api::provider::rrset::{
+    append_value, decode_value, encode_value, parse_record_id, parse_record_type, record_id,
+    remove_value, replace_value, to_fqdn, to_rr,
+};
+use crate::gui::model::domain::{DnsProvider, Domain, DomainName};
+use crate::model::dns_record_response::{Record, Status};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use domain_clients::aws::SigV4Signer;
+use quick_xml::escape::escape;
+use reqwest::{Client, Method};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use tracing::{debug, error, info};
+
+/// Route 53 默认接口地址
+const ROUTE53_ENDPOINT: &str = "https://route53.amazonaws.com";
+/// Route 53 是全局服务，签名固定使用 us-east-1
+const ROUTE53_REGION: &str = "us-east-1";
+const ROUTE53_SERVICE: &str = "route53";
+const ROUTE53_API_PREFIX: &str = "/2013-04-01";
+const ROUTE53_XMLNS: &str = "https://route53.amazonaws.com/doc/2013-04-01/";
+
+/// ListHostedZones / ListHostedZonesByName 响应
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ListHostedZonesResponse {
+    hosted_zones: HostedZones,
+    #[serde(default)]
+    is_truncated: bool,
+    next_marker: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct HostedZones {
+    #[serde(rename = "HostedZone", default)]
+    hosted_zone: Vec<HostedZone>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct HostedZone {
+    id: String,
+    name: String,
+}
+
+/// ListResourceRecordSets 响应
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ListResourceRecordSetsResponse {
+    resource_record_sets: ResourceRecordSets,
+    #[serde(default)]
+    is_truncated: bool,
+    next_record_name: Option<String>,
+    next_record_type: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ResourceRecordSets {
+    #[serde(rename = "ResourceRecordSet", default)]
+    resource_record_set: Vec<ResourceRecordSet>,
+}
+
+/// Route 53 记录集：同名同类型的所有记录值
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ResourceRecordSet {
+    name: String,
+    #[serde(rename = "Type")]
+    record_type: String,
+    #[serde(rename = "TTL")]
+    ttl: Option<i64>,
+    set_identifier: Option<String>,
+    resource_records: Option<ResourceRecords>,
+    alias_target: Option<AliasTarget>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+struct ResourceRecords {
+    #[serde(rename = "ResourceRecord", default)]
+    resource_record: Vec<ResourceRecord>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ResourceRecord {
+    value: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct AliasTarget {
+    #[serde(rename = "DNSName")]
+    dns_name: String,
+}
+
+impl ResourceRecordSet {
+    fn new(name: String, record_type: String, ttl: i64, values: Vec<String>) -> Self {
+        Self {
+            name,
+            record_type,
+            ttl: Some(ttl),
+            set_identifier: None,
+            resource_records: Some(ResourceRecords {
+                resource_record: values
+                    .into_iter()
+                    .map(|value| ResourceRecord { value })
+                    .collect(),
+            }),
+            alias_target: None,
+        }
+    }
+
+    fn values(&self) -> Vec<String> {
+        self.resource_records
+            .as_ref()
+            .map(|records| {
+                records
+                    .resource_record
+                    .iter()
+                    .map(|r| r.value.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Route 53 错误响应
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ErrorResponse {
+    error: AwsError,
+    request_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct AwsError {
+    code: String,
+    message: String,
+}
+
+/// ChangeResourceRecordSets 校验失败时的响应
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct InvalidChangeBatch {
+    messages: InvalidChangeBatchMessages,
+}
+
+#[derive(Debug, Deserialize)]
+struct InvalidChangeBatchMessages {
+    #[serde(rename = "Message", default)]
+    message: Vec<String>,
+}
+
+/// 记录集变更动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChangeAction {
+    Create,
+    Delete,
+    Upsert,
+}
+
+impl ChangeAction {
+    fn as_str(&self) -> &str {
+        match self {
+            ChangeAction::Create => "CREATE",
+            ChangeAction::Delete => "DELETE",
+            ChangeAction::Upsert => "UPSERT",
+        }
+    }
+}
+
+/// 一条记录集变更，多条变更组成一个 ChangeBatch 原子提交
+#[derive(Debug, Clone, PartialEq)]
+struct Change {
+    action: ChangeAction,
+    record_set: ResourceRecordSet,
+}
+
+/// AWS Route 53 客户端
+///
+/// Route 53 以记录集（同名同类型）为单位管理记录，而内部 `Record` 一条只有一个值，
+/// 因此每个记录值会映射为一条 `Record`，`record_id` 为 `名称|类型|值`。
+#[derive(Debug, Clone)]
+pub struct Route53DnsClient {
+    client: Client,
+    signer: SigV4Signer,
+    endpoint: String,
+}
+
+impl Route53DnsClient {
+    /// 创建新的Route 53客户端
+    ///
+    /// # 参数
+    /// * `access_key_id` - AWS Access Key ID
+    /// * `secret_access_key` - AWS Secret Access Key
+    pub fn new(access_key_id: String, secret_access_key: String) -> Self {
+        Self {
+            client: Client::new(),
+            signer: SigV4Signer::new(
+                access_key_id,
+                secret_access_key,
+                ROUTE53_REGION,
+                ROUTE53_SERVICE,
+            ),
+            endpoint: ROUTE53_ENDPOINT.to_string(),
+        }
+    }
+
+    /// 指定接口地址，用于测试
+    pub fn with_endpoint(mut self, endpoint: String) -> Self {
+        self.endpoint = endpoint;
+        self
+    }
+
+    /// 内部调用API的通用方法
+    async fn call_route53_api<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        query_params: &[(&str, &str)],
+        payload: &str,
+    ) -> Result<T> {
+        let canonical_uri = format!("{}{}", ROUTE53_API_PREFIX, path);
+        debug!("请求Route 53接口：{} {}", method, canonical_uri);
+
+        let (status, body) = domain_clients::aws::call_aws_api(
+            self.client.clone(),
+            &self.signer,
+            method,
+            &self.endpoint,
+            &canonical_uri,
+            query_params,
+            payload,
+        )
+        .await
+        .map_err(|e| anyhow!("Route 53 接口请求失败: {}", e))?;
+
+        if !status.is_success() {
+            return Err(Self::parse_error(status.as_u16(), &body));
+        }
+
+        quick_xml::de::from_str(&body).map_err(|e| {
+            error!("解析Route 53响应失败：{:?}，响应内容：{}", e, body);
+            anyhow!("解析Route 53响应失败: {}", e)
+        })
+    }
+
+    /// 解析错误响应
+    fn parse_error(status: u16, body: &str) -> anyhow::Error {
+        if let Ok(response) = quick_xml::de::from_str::<ErrorResponse>(body) {
+            return anyhow!(
+                "Route 53 API Error: {} - {} (RequestId: {})",
+                response.error.code,
+                response.error.message,
+                response.request_id.unwrap_or_default()
+            );
+        }
+
+        if let Ok(response) = quick_xml::de::from_str::<InvalidChangeBatch>(body) {
+            return anyhow!(
+                "Route 53 API Error: InvalidChangeBatch - {}",
+                response.messages.message.join("; ")
+            );
+        }
+
+        anyhow!("Route 53 API Error: HTTP {} {}", status, body)
+    }
+
+    /// 根据域名获取托管区域ID（不含 `/hostedzone/` 前缀）
+    async fn get_hosted_zone_id(&self, domain_name: &str) -> Result<String> {
+        let response: ListHostedZonesResponse = self
+            .call_route53_api(
+                Method::GET,
+                "/hostedzonesbyname",
+                &[("dnsname", domain_name), ("maxitems", "1")],
+                "",
+            )
+            .await?;
+
+        let fqdn = to_fqdn("@", domain_name);
+        response
+            .hosted_zones
+            .hosted_zone
+            .into_iter()
+            .find(|zone| zone.name.eq_ignore_ascii_case(&fqdn))
+            .map(|zone| zone.id.trim_start_matches("/hostedzone/").to_string())
+            .ok_or_else(|| anyhow!("未找到域名对应的托管区域: {}", domain_name))
+    }
+
+    /// 查询托管区域下的全部记录集
+    async fn list_record_sets(&self, zone_id: &str) -> Result<Vec<ResourceRecordSet>> {
+        let path = format!("/hostedzone/{}/rrset", zone_id);
+        let mut record_sets = Vec::new();
+        let mut next: Option<(String, String)> = None;
+
+        loop {
+            let mut query_params = vec![("maxitems", "300")];
+            if let Some((name, record_type)) = &next {
+                query_params.push(("name", name.as_str()));
+                query_params.push(("type", record_type.as_str()));
+            }
+
+            let response: ListResourceRecordSetsResponse = self
+                .call_route53_api(Method::GET, &path, &query_params, "")
+                .await?;
+            record_sets.extend(response.resource_record_sets.resource_record_set);
+
+            match (
+                response.is_truncated,
+                response.next_record_name,
+                response.next_record_type,
+            ) {
+                (true, Some(name), Some(record_type)) => next = Some((name, record_type)),
+                _ => break,
+            }
+        }
+
+        Ok(record_sets)
+    }
+
+    /// 查询指定名称和类型的记录集
+    async fn find_record_set(
+        &self,
+        zone_id: &str,
+        name: &str,
+        record_type: &str,
+    ) -> Result<Option<ResourceRecordSet>> {
+        let path = format!("/hostedzone/{}/rrset", zone_id);
+        let response: ListResourceRecordSetsResponse = self
+            .call_route53_api(
+                Method::GET,
+                &path,
+                &[("name", name), ("type", record_type), ("maxitems", "1")],
+                "",
+            )
+            .await?;
+
+        // 接口返回的是从 name/type 开始的第一条，需要再次确认是否匹配
+        Ok(response
+            .resource_record_sets
+            .resource_record_set
+            .into_iter()
+            .find(|set| {
+                set.set_identifier.is_none()
+                    && set.name.eq_ignore_ascii_case(name)
+                    && set.record_type == record_type
+            }))
+    }
+
+    /// 提交一批记录集变更
+    async fn change_record_sets(&self, zone_id: &str, changes: &[Change]) -> Result<()> {
+        let path = format!("/hostedzone/{}/rrset/", zone_id);
+        let body = build_change_batch(changes);
+        debug!("提交Route 53变更：{}", body);
+
+        let response: ChangeResourceRecordSetsResponse = self
+            .call_route53_api(Method::POST, &path, &[], &body)
+            .await?;
+        info!(
+            "Route 53变更已提交：{}，状态：{}",
+            response.change_info.id, response.change_info.status
+        );
+        Ok(())
+    }
+
+    /// 从记录集中移除一个值，返回对应的变更
+    fn remove_value_change(record_set: &ResourceRecordSet, value: &str) -> Result<Change> {
+        let remaining = remove_value(&record_set.values(), value)?;
+        if remaining.is_empty() {
+            // 删除时必须提供与云端完全一致的记录集
+            Ok(Change {
+                action: ChangeAction::Delete,
+                record_set: record_set.clone(),
+            })
+        } else {
+            Ok(Change {
+                action: ChangeAction::Upsert,
+                record_set: ResourceRecordSet::new(
+                    record_set.name.clone(),
+                    record_set.record_type.clone(),
+                    record_set.ttl.unwrap_or(300),
+                    remaining,
+                ),
+            })
+        }
+    }
+
+    /// 向记录集中添加一个值，记录集不存在时创建
+    fn add_value_change(
+        existing: Option<&ResourceRecordSet>,
+        name: &str,
+        record_type: &str,
+        ttl: i64,
+        value: String,
+    ) -> Change {
+        match existing {
+            Some(record_set) => Change {
+                action: ChangeAction::Upsert,
+                record_set: ResourceRecordSet::new(
+                    name.to_string(),
+                    record_type.to_string(),
+                    ttl,
+                    append_value(&record_set.values(), value),
+                ),
+            },
+            None => Change {
+                action: ChangeAction::Create,
+                record_set: ResourceRecordSet::new(
+                    name.to_string(),
+                    record_type.to_string(),
+                    ttl,
+                    vec![value],
+                ),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ChangeResourceRecordSetsResponse {
+    change_info: ChangeInfo,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ChangeInfo {
+    id: String,
+    status: String,
+}
+
+/// 将记录集转换为内部Record格式，别名记录和带路由策略的记录无法表示，会被跳过
+fn convert_record_set_to_internal(
+    record_set: &ResourceRecordSet,
+    domain_name: &str,
+) -> Vec<Record> {
+    if record_set.alias_target.is_some() || record_set.set_identifier.is_some() {
+        debug!(
+            "跳过别名或带路由策略的记录集：{} {}",
+            record_set.name, record_set.record_type
+        );
+        return vec![];
+    }
+
+    let Some(record_type) = parse_record_type(&record_set.record_type) else {
+        debug!("跳过不支持的记录类型：{}", record_set.record_type);
+        return vec![];
+    };
+
+    // Route 53 会将通配符 `*` 转义为 `\052`
+    let rr = to_rr(&record_set.name.replace("\\052", "*"), domain_name);
+    record_set
+        .values()
+        .iter()
+        .map(|value| {
+            Record::new(
+                Status::Enable,
+                rr.clone(),
+                record_type.clone(),
+                decode_value(&record_type, value),
+                record_id(&record_set.name, &record_set.record_type, value),
+                record_set.ttl.unwrap_or(300) as i32,
+            )
+        })
+        .collect()
+}
+
+/// 构建 ChangeResourceRecordSets 请求体
+fn build_change_batch(changes: &[Change]) -> String {
+    let mut xml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+         <ChangeResourceRecordSetsRequest xmlns=\"{}\"><ChangeBatch><Changes>",
+        ROUTE53_XMLNS
+    );
+
+    for change in changes {
+        let record_set = &change.record_set;
+        xml.push_str("<Change>");
+        xml.push_str(&format!("<Action>{}</Action>", change.action.as_str()));
+        xml.push_str("<ResourceRecordSet>");
+        xml.push_str(&format!(
+            "<Name>{}</Name>",
+            escape(record_set.name.as_str())
+        ));
+        xml.push_str(&format!(
+            "<Type>{}</Type>",
+            escape(record_set.record_type.as_str())
+        ));
+        if let Some(ttl) = record_set.ttl {
+            xml.push_str(&format!("<TTL>{}</TTL>", ttl));
+        }
+        xml.push_str("<ResourceRecords>");
+        for value in record_set.values() {
+            xml.push_str(&format!(
+                "<ResourceRecord><Value>{}</Value></ResourceRecord>",
+                escape(value.as_str())
+            ));
+        }
+        xml.push_str("</ResourceRecords>");
+        xml.push_str("</ResourceRecordSet>");
+        xml.push_str("</Change>");
+    }
+
+    xml.push_str("</Changes></ChangeBatch></ChangeResourceRecordSetsRequest>");
+    xml
+}
+
+#[async_trait]
+impl DnsClientTrait for Route53DnsClient {
+    /// 获取域名列表（Route 53中为托管区域列表）
+    async fn list_domains(&self, _page_num: u32, _page_size: u32) -> Result<Vec<DomainName>> {
+        info!("正在获取Route 53托管区域列表...");
+
+        let mut domain_list = Vec::new();
+        let mut marker: Option<String> = None;
+
+        loop {
+            let mut query_params = vec![("maxitems", "100")];
+            if let Some(marker) = &marker {
+                query_params.push(("marker", marker.as_str()));
+            }
+
+            let response: ListHostedZonesResponse = self
+                .call_route53_api(Method::GET, "/hostedzone", &query_params, "")
+                .await?;
+
+            domain_list.extend(response.hosted_zones.hosted_zone.into_iter().map(|zone| {
+                DomainName {
+                    name: zone.name.trim_end_matches('.').to_string(),
+                    provider: DnsProvider::Aws,
+                    ..DomainName::default()
+                }
+            }));
+
+            match (response.is_truncated, response.next_marker) {
+                (true, Some(next_marker)) => marker = Some(next_marker),
+                _ => break,
+            }
+        }
+
+        info!("获取到 {} 个Route 53托管区域", domain_list.len());
+        Ok(domain_list)
+    }
+
+    async fn query_domain(&self, _domain_name: &Domain) -> Result<DomainQueryResponse> {
+        Err(anyhow!("Route 53 暂不支持查询域名注册信息"))
+    }
+
+    /// 获取DNS记录列表
+    async fn list_dns_records(&self, domain_name: String) -> Result<Vec<Record>> {
+        info!("正在获取域名 {} 的Route 53记录...", domain_name);
+
+        let zone_id = self.get_hosted_zone_id(&domain_name).await?;
+        let records: Vec<Record> = self
+            .list_record_sets(&zone_id)
+            .await?
+            .iter()
+            .flat_map(|record_set| convert_record_set_to_internal(record_set, &domain_name))
+            .collect();
+
+        info!("获取到 {} 条DNS记录", records.len());
+        Ok(records)
+    }
+
+    /// 添加DNS记录
+    async fn add_dns_record(&self, domain_name: &DomainName, record: &Record) -> Result<()> {
+        info!("正在添加Route 53记录: {} -> {}", record.rr, record.value);
+
+        let zone_id = self.get_hosted_zone_id(&domain_name.name).await?;
+        let name = to_fqdn(&record.rr, &domain_name.name);
+        let record_type = record.record_type.get_value();
+        let existing = self.find_record_set(&zone_id, &name, record_type).await?;
+
+        let change = Self::add_value_change(
+            existing.as_ref(),
+            &name,
+            record_type,
+            record.ttl as i64,
+            encode_value(&record.record_type, &record.value),
+        );
+        self.change_record_sets(&zone_id, &[change]).await
+    }
+
+    /// 删除DNS记录
+    async fn delete_dns_record(&self, domain_name: &DomainName, record_id: &str) -> Result<()> {
+        info!("正在删除Route 53记录: {}", record_id);
+
+        let (name, record_type, value) = parse_record_id(record_id)?;
+        let zone_id = self.get_hosted_zone_id(&domain_name.name).await?;
+        let record_set = self
+            .find_record_set(&zone_id, &name, &record_type)
+            .await?
+            .ok_or_else(|| anyhow!("在云端未找到匹配的记录集: {} {}", name, record_type))?;
+
+        let change = Self::remove_value_change(&record_set, &value)?;
+        self.change_record_sets(&zone_id, &[change]).await
+    }
+
+    /// 更新DNS记录
+    ///
+    /// 名称和类型不变时只更新所在记录集，否则在同一个 ChangeBatch 中移除旧值并添加新值。
+    async fn update_dns_record(&self, domain_name: &DomainName, record: &Record) -> Result<()> {
+        info!("正在更新Route 53记录: {} -> {}", record.rr, record.value);
+
+        let (old_name, old_type, old_value) = parse_record_id(&record.record_id)?;
+        let zone_id = self.get_hosted_zone_id(&domain_name.name).await?;
+        let old_set = self
+            .find_record_set(&zone_id, &old_name, &old_type)
+            .await?
+            .ok_or_else(|| anyhow!("在云端未找到匹配的记录集: {} {}", old_name, old_type))?;
+
+        let new_name = to_fqdn(&record.rr, &domain_name.name);
+        let new_type = record.record_type.get_value();
+        let new_value = encode_value(&record.record_type, &record.value);
+
+        let changes = if old_name.eq_ignore_ascii_case(&new_name) && old_type == new_type {
+            let values = replace_value(&old_set.values(), &old_value, &new_value);
+            vec![Change {
+                action: ChangeAction::Upsert,
+                record_set: ResourceRecordSet::new(new_name, old_type, record.ttl as i64, values),
+            }]
+        } else {
+            let new_set = self.find_record_set(&zone_id, &new_name, new_type).await?;
+            vec![
+                Self::remove_value_change(&old_set, &old_value)?,
+                Self::add_value_change(
+                    new_set.as_ref(),
+                    &new_name,
+                    new_type,
+                    record.ttl as i64,
+                    new_value,
+                ),
+            ]
+        };
+
+        self.change_record_sets(&zone_id, &changes).await
+    }
+
+    /// 验证凭证是否有效
+    async fn validate_credentials(&self) -> Result<()> {
+        let result: Result<ListHostedZonesResponse> = self
+            .call_route53_api(Method::GET, "/hostedzone", &[("maxitems", "1")], "")
+            .await;
+        match result {
+            Ok(_) => {
+                info!("Route 53凭证验证成功");
+                Ok(())
+            }
+            Err(err) => {
+                error!("Route 53凭证验证失败: {:?}", err);
+                Err(err)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::dns_record_response::Type;
+
+    const LIST_RRSETS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ListResourceRecordSetsResponse xmlns="https://route53.amazonaws.com/doc/2013-04-01/">
+  <ResourceRecordSets>
+    <ResourceRecordSet>
+      <Name>example.com.</Name>
+      <Type>MX</Type>
+      <TTL>300</TTL>
+      <ResourceRecords>
+        <ResourceRecord><Value>10 mx1.example.com.</Value></ResourceRecord>
+        <ResourceRecord><Value>20 mx2.example.com.</Value></ResourceRecord>
+      </ResourceRecords>
+    </ResourceRecordSet>
+    <ResourceRecordSet>
+      <Name>\052.example.com.</Name>
+      <Type>TXT</Type>
+      <TTL>60</TTL>
+      <ResourceRecords>
+        <ResourceRecord><Value>"v=spf1 " "include:example.net ~all"</Value></ResourceRecord>
+      </ResourceRecords>
+    </ResourceRecordSet>
+    <ResourceRecordSet>
+      <Name>www.example.com.</Name>
+      <Type>A</Type>
+      <AliasTarget>
+        <HostedZoneId>Z2FDTNDATAQYW2</HostedZoneId>
+        <DNSName>d111111abcdef8.cloudfront.net.</DNSName>
+        <EvaluateTargetHealth>false</EvaluateTargetHealth>
+      </AliasTarget>
+    </ResourceRecordSet>
+  </ResourceRecordSets>
+  <IsTruncated>true</IsTruncated>
+  <NextRecordName>zz.example.com.</NextRecordName>
+  <NextRecordType>A</NextRecordType>
+  <MaxItems>3</MaxItems>
+</ListResourceRecordSetsResponse>"#;
+
+    #[test]
+    fn test_parse_record_sets() {
+        let response: ListResourceRecordSetsResponse =
+            quick_xml::de::from_str(LIST_RRSETS).unwrap();
+        assert!(response.is_truncated);
+        assert_eq!(
+            response.next_record_name.as_deref(),
+            Some("zz.example.com.")
+        );
+
+        let records: Vec<Record> = response
+            .resource_record_sets
+            .resource_record_set
+            .iter()
+            .flat_map(|set| convert_record_set_to_internal(set, "example.com"))
+            .collect();
+
+        // 别名记录被跳过
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].rr, "@");
+        assert_eq!(records[0].value, "10 mx1.example.com.");
+        assert_eq!(records[0].record_id, "example.com.|MX|10 mx1.example.com.");
+        assert_eq!(records[2].rr, "*");
+        assert_eq!(records[2].record_type, Type::TXT);
+        assert_eq!(records[2].value, "v=spf1 include:example.net ~all");
+        assert_eq!(records[2].ttl, 60);
+    }
+
+    #[test]
+    fn test_parse_hosted_zones() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ListHostedZonesByNameResponse xmlns="https://route53.amazonaws.com/doc/2013-04-01/">
+  <HostedZones>
+    <HostedZone>
+      <Id>/hostedzone/Z111111QQQQQQQ</Id>
+      <Name>example.com.</Name>
+      <CallerReference>2017-03-01T11:22:14Z</CallerReference>
+      <Config><PrivateZone>false</PrivateZone></Config>
+      <ResourceRecordSetCount>17</ResourceRecordSetCount>
+    </HostedZone>
+  </HostedZones>
+  <IsTruncated>false</IsTruncated>
+  <MaxItems>1</MaxItems>
+</ListHostedZonesByNameResponse>"#;
+
+        let response: ListHostedZonesResponse = quick_xml::de::from_str(xml).unwrap();
+        assert!(!response.is_truncated);
+        assert_eq!(response.hosted_zones.hosted_zone.len(), 1);
+        assert_eq!(
+            response.hosted_zones.hosted_zone[0].id,
+            "/hostedzone/Z111111QQQQQQQ"
+        );
+    }
+
+    #[test]
+    fn test_parse_error_response() {
+        let xml = r#"<?xml version="1.0"?>
+<ErrorResponse xmlns="https://route53.amazonaws.com/doc/2013-04-01/">
+  <Error>
+    <Type>Sender</Type>
+    <Code>InvalidClientTokenId</Code>
+    <Message>The security token included in the request is invalid.</Message>
+  </Error>
+  <RequestId>4f3d4d4a-8f8d-4b1e-9f3e-7c2c9d1d0a11</RequestId>
+</ErrorResponse>"#;
+
+        let err = Route53DnsClient::parse_error(403, xml);
+        assert!(err.to_string().contains("InvalidClientTokenId"));
+        assert!(err
+            .to_string()
+            .contains("4f3d4d4a-8f8d-4b1e-9f3e-7c2c9d1d0a11"));
+
+        let xml = r#"<?xml version="1.0"?>
+<InvalidChangeBatch xmlns="https://route53.amazonaws.com/doc/2013-04-01/">
+  <Messages>
+    <Message>Tried to create resource record set [name='www.example.com.', type='A'] but it already exists</Message>
+  </Messages>
+</InvalidChangeBatch>"#;
+        let err = Route53DnsClient::parse_error(400, xml);
+        assert!(err.to_string().contains("already exists"));
+    }
+
+    #[test]
+    fn test_build_change_batch() {
+        let old_set = ResourceRecordSet::new(
+            "www.example.com.".to_string(),
+            "A".to_string(),
+            300,
+            vec!["192.0.2.1".to_string()],
+        );
+        let changes = vec![
+            Route53DnsClient::remove_value_change(&old_set, "192.0.2.1").unwrap(),
+            Route53DnsClient::add_value_change(
+                None,
+                "api.example.com.",
+                "TXT",
+                600,
+                encode_value(&Type::TXT, "a \"quoted\" <value>"),
+            ),
+        ];
+
+        assert_eq!(changes[0].action, ChangeAction::Delete);
+        assert_eq!(changes[1].action, ChangeAction::Create);
+
+        let xml = build_change_batch(&changes);
+        assert!(xml.contains(
+            "<Change><Action>DELETE</Action><ResourceRecordSet><Name>www.example.com.</Name>\
+             <Type>A</Type><TTL>300</TTL><ResourceRecords><ResourceRecord><Value>192.0.2.1</Value>"
+        ));
+        assert!(xml.contains("<Value>&quot;a \\&quot;quoted\\&quot; &lt;value&gt;&quot;</Value>"));
+    }
+
+    #[test]
+    fn test_remove_value_keeps_other_values() {
+        let record_set = ResourceRecordSet::new(
+            "example.com.".to_string(),
+            "MX".to_string(),
+            300,
+            vec![
+                "10 mx1.example.com.".to_string(),
+                "20 mx2.example.com.".to_string(),
+            ],
+        );
+
+        let change =
+            Route53DnsClient::remove_value_change(&record_set, "10 mx1.example.com.").unwrap();
+        assert_eq!(change.action, ChangeAction::Upsert);
+        assert_eq!(change.record_set.values(), vec!["20 mx2.example.com."]);
+
+        assert!(Route53DnsClient::remove_value_change(&record_set, "30 mx3.example.com.").is_err());
+    }
+}
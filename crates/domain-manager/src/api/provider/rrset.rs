@@ -0,0 +1,198 @@
+//! 基于记录集（RRset）的托管商通用转换
+//!
+//! Route 53、Cloud DNS 等托管商以「名称 + 类型」为单位管理记录，一个记录集包含多个值，
+//! 而内部 `Record` 一条只有一个值。这里统一约定：
+//! - 每个记录值映射为一条 `Record`
+//! - `record_id` 为 `完整域名|类型|原始值`，用于写操作时定位记录集
+//! - TXT 记录在云端以带引号的字符串保存，读取时去掉引号，写入时加上引号
+
+use crate::model::dns_record_response::Type;
+use anyhow::{anyhow, Result};
+
+/// 将主机记录转换为完整域名（以 `.` 结尾）
+pub fn to_fqdn(rr: &str, domain_name: &str) -> String {
+    let domain_name = domain_name.trim_end_matches('.');
+    match rr {
+        "" | "@" => format!("{}.", domain_name),
+        rr => format!("{}.{}.", rr, domain_name),
+    }
+}
+
+/// 将完整域名转换为主机记录，根域名返回 `@`
+pub fn to_rr(name: &str, domain_name: &str) -> String {
+    let name = name.trim_end_matches('.');
+    let domain_name = domain_name.trim_end_matches('.');
+
+    if name.eq_ignore_ascii_case(domain_name) {
+        "@".to_string()
+    } else {
+        name.strip_suffix(&format!(".{}", domain_name))
+            .unwrap_or(name)
+            .to_string()
+    }
+}
+
+/// 解析记录类型，不支持的类型返回 None
+pub fn parse_record_type(record_type: &str) -> Option<Type> {
+    match record_type {
+        "A" => Some(Type::A),
+        "AAAA" => Some(Type::AAAA),
+        "CNAME" => Some(Type::Cname),
+        "MX" => Some(Type::MX),
+        "TXT" => Some(Type::TXT),
+        "NS" => Some(Type::NS),
+        "SOA" => Some(Type::SOA),
+        "PTR" => Some(Type::PTR),
+        "SRV" => Some(Type::SRV),
+        _ => None,
+    }
+}
+
+/// 将云端记录值转换为内部格式，TXT 记录会去掉引号并拼接多段字符串
+pub fn decode_value(record_type: &Type, value: &str) -> String {
+    if *record_type != Type::TXT {
+        return value.to_string();
+    }
+
+    let mut result = String::new();
+    let mut in_quotes = false;
+    let mut chars = value.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' => in_quotes = !in_quotes,
+            '\\' if in_quotes => {
+                if let Some(escaped) = chars.next() {
+                    result.push(escaped);
+                }
+            }
+            ch if in_quotes => result.push(ch),
+            _ => {}
+        }
+    }
+    result
+}
+
+/// 将内部记录值转换为云端格式，TXT 记录按 255 个字符拆分并加上引号
+pub fn encode_value(record_type: &Type, value: &str) -> String {
+    if *record_type != Type::TXT {
+        return value.to_string();
+    }
+
+    let escaped: Vec<String> = value
+        .chars()
+        .collect::<Vec<_>>()
+        .chunks(255)
+        .map(|chunk| {
+            let part: String = chunk.iter().collect();
+            format!("\"{}\"", part.replace('\\', "\\\\").replace('"', "\\\""))
+        })
+        .collect();
+
+    if escaped.is_empty() {
+        "\"\"".to_string()
+    } else {
+        escaped.join(" ")
+    }
+}
+
+/// 生成 `完整域名|类型|值` 格式的记录ID
+pub fn record_id(name: &str, record_type: &str, value: &str) -> String {
+    format!("{}|{}|{}", name, record_type, value)
+}
+
+/// 解析 `完整域名|类型|值` 格式的记录ID
+pub fn parse_record_id(record_id: &str) -> Result<(String, String, String)> {
+    let mut parts = record_id.splitn(3, '|');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(name), Some(record_type), Some(value)) => {
+            Ok((name.to_string(), record_type.to_string(), value.to_string()))
+        }
+        _ => Err(anyhow!("无效的记录ID: {}", record_id)),
+    }
+}
+
+/// 从记录值列表中移除一个值，值不存在时返回错误
+pub fn remove_value(values: &[String], value: &str) -> Result<Vec<String>> {
+    if !values.iter().any(|v| v == value) {
+        return Err(anyhow!("在云端未找到匹配的DNS记录值: {}", value));
+    }
+    Ok(values.iter().filter(|v| *v != value).cloned().collect())
+}
+
+/// 向记录值列表中追加一个值，已存在时保持不变
+pub fn append_value(values: &[String], value: String) -> Vec<String> {
+    let mut values = values.to_vec();
+    if !values.contains(&value) {
+        values.push(value);
+    }
+    values
+}
+
+/// 将记录值列表中的旧值替换为新值
+pub fn replace_value(values: &[String], old_value: &str, new_value: &str) -> Vec<String> {
+    values
+        .iter()
+        .map(|v| {
+            if v == old_value {
+                new_value.to_string()
+            } else {
+                v.clone()
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_name_conversion() {
+        assert_eq!(to_fqdn("@", "example.com"), "example.com.");
+        assert_eq!(to_fqdn("www", "example.com."), "www.example.com.");
+        assert_eq!(to_rr("example.com.", "example.com"), "@");
+        assert_eq!(to_rr("a.b.example.com.", "example.com"), "a.b");
+        assert_eq!(to_rr("other.org.", "example.com"), "other.org");
+    }
+
+    #[test]
+    fn test_txt_value_round_trip() {
+        let value = "v=spf1 \"quoted\" ~all";
+        let encoded = encode_value(&Type::TXT, value);
+        assert_eq!(encoded, "\"v=spf1 \\\"quoted\\\" ~all\"");
+        assert_eq!(decode_value(&Type::TXT, &encoded), value);
+
+        let long = "a".repeat(300);
+        let encoded = encode_value(&Type::TXT, &long);
+        assert_eq!(encoded.matches('"').count(), 4);
+        assert_eq!(decode_value(&Type::TXT, &encoded), long);
+
+        assert_eq!(encode_value(&Type::A, "192.0.2.1"), "192.0.2.1");
+    }
+
+    #[test]
+    fn test_record_id() {
+        let id = record_id("www.example.com.", "TXT", "\"a|b\"");
+        assert_eq!(
+            parse_record_id(&id).unwrap(),
+            (
+                "www.example.com.".to_string(),
+                "TXT".to_string(),
+                "\"a|b\"".to_string()
+            )
+        );
+        assert!(parse_record_id("invalid").is_err());
+    }
+
+    #[test]
+    fn test_value_list_operations() {
+        let values = vec!["10 mx1.".to_string(), "20 mx2.".to_string()];
+        assert_eq!(remove_value(&values, "10 mx1.").unwrap(), vec!["20 mx2."]);
+        assert!(remove_value(&values, "30 mx3.").is_err());
+        assert_eq!(append_value(&values, "10 mx1.".to_string()).len(), 2);
+        assert_eq!(
+            replace_value(&values, "20 mx2.", "30 mx3."),
+            vec!["10 mx1.", "30 mx3."]
+        );
+    }
+}
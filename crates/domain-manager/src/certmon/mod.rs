@@ -0,0 +1,544 @@
+//! TLS 证书监控
+//!
+//! 连接托管的主机名或指定的 `host:port`，解析服务器返回的证书链，记录主题、备用名称、
+//! 签发者、有效期和指纹，并标记即将过期、域名不匹配和自签名的证书。
+//!
+//! 私有网络中的主机由 Agent 完成握手后通过 WebSocket 上报证书链（`CertProbeResult`），
+//! Hub 使用同样的规则检查，结果一并保存到 `storage`，在首页的证书清单中展示。
+
+pub mod probe;
+
+use crate::agent::connection::AgentHubHandle;
+use crate::agent::protocol::CertProbeReport;
+use crate::storage::certificate_checks::{self, NewCertificateCheck};
+use crate::storage::entities::certificate_check;
+use crate::storage::{domains, records};
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use futures_util::future::join_all;
+use sea_orm::DatabaseConnection;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::net::IpAddr;
+use std::time::Duration;
+use tracing::{info, warn};
+use uuid::Uuid;
+use x509_parser::extensions::GeneralName;
+use x509_parser::prelude::{FromDer, X509Certificate};
+
+/// 默认在到期前多少天提醒
+pub const DEFAULT_WARN_DAYS: i64 = 30;
+
+/// 默认的连接超时
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 证书问题
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CertIssue {
+    /// 已经过期
+    Expired,
+    /// 在提醒天数内到期
+    ExpiringSoon,
+    /// 尚未生效
+    NotYetValid,
+    /// 证书不包含连接使用的主机名
+    HostnameMismatch,
+    /// 签发者就是证书自身
+    SelfSigned,
+}
+
+impl CertIssue {
+    /// 保存到数据库和 JSON 输出中使用的名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CertIssue::Expired => "expired",
+            CertIssue::ExpiringSoon => "expiring_soon",
+            CertIssue::NotYetValid => "not_yet_valid",
+            CertIssue::HostnameMismatch => "hostname_mismatch",
+            CertIssue::SelfSigned => "self_signed",
+        }
+    }
+
+    /// 解析 [`CertIssue::as_str`] 的结果
+    pub fn parse(value: &str) -> Option<Self> {
+        Some(match value {
+            "expired" => CertIssue::Expired,
+            "expiring_soon" => CertIssue::ExpiringSoon,
+            "not_yet_valid" => CertIssue::NotYetValid,
+            "hostname_mismatch" => CertIssue::HostnameMismatch,
+            "self_signed" => CertIssue::SelfSigned,
+            _ => return None,
+        })
+    }
+}
+
+impl fmt::Display for CertIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CertIssue::Expired => write!(f, "已过期"),
+            CertIssue::ExpiringSoon => write!(f, "即将过期"),
+            CertIssue::NotYetValid => write!(f, "尚未生效"),
+            CertIssue::HostnameMismatch => write!(f, "域名不匹配"),
+            CertIssue::SelfSigned => write!(f, "自签名"),
+        }
+    }
+}
+
+/// 叶子证书的信息
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CertificateInfo {
+    pub subject: String,
+    pub issuer: String,
+    /// DNS 名称和 IP 地址
+    pub sans: Vec<String>,
+    pub not_before: NaiveDateTime,
+    pub not_after: NaiveDateTime,
+    /// SHA-256 指纹，冒号分隔的大写十六进制
+    pub fingerprint: String,
+    pub self_signed: bool,
+    /// 通用名称，没有备用名称时用于匹配主机名
+    common_name: Option<String>,
+}
+
+impl CertificateInfo {
+    /// 解析 DER 格式的证书
+    pub fn parse(der: &[u8]) -> Result<Self> {
+        let (_, certificate) =
+            X509Certificate::from_der(der).map_err(|e| anyhow!("解析证书失败: {}", e))?;
+        let validity = certificate.validity();
+        let timestamp = |time: &x509_parser::time::ASN1Time| {
+            DateTime::from_timestamp(time.timestamp(), 0)
+                .map(|time| time.naive_utc())
+                .ok_or_else(|| anyhow!("证书有效期超出范围"))
+        };
+
+        let mut sans = Vec::new();
+        if let Ok(Some(extension)) = certificate.subject_alternative_name() {
+            for name in &extension.value.general_names {
+                match name {
+                    GeneralName::DNSName(name) => sans.push(name.to_ascii_lowercase()),
+                    GeneralName::IPAddress(bytes) => {
+                        let ip = match bytes.len() {
+                            4 => <[u8; 4]>::try_from(&bytes[..]).ok().map(IpAddr::from),
+                            16 => <[u8; 16]>::try_from(&bytes[..]).ok().map(IpAddr::from),
+                            _ => None,
+                        };
+                        sans.extend(ip.map(|ip| ip.to_string()));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let common_name = certificate
+            .subject()
+            .iter_common_name()
+            .next()
+            .and_then(|name| name.as_str().ok())
+            .map(str::to_ascii_lowercase);
+
+        Ok(Self {
+            subject: certificate.subject().to_string(),
+            issuer: certificate.issuer().to_string(),
+            sans,
+            not_before: timestamp(&validity.not_before)?,
+            not_after: timestamp(&validity.not_after)?,
+            fingerprint: fingerprint(der),
+            self_signed: certificate.subject().as_raw() == certificate.issuer().as_raw(),
+            common_name,
+        })
+    }
+
+    /// 证书是否对主机名有效，没有备用名称时使用通用名称
+    pub fn matches_host(&self, host: &str) -> bool {
+        let host = host.trim_end_matches('.').to_ascii_lowercase();
+        if let Ok(ip) = host.parse::<IpAddr>() {
+            return self.sans.iter().any(|san| san.parse::<IpAddr>() == Ok(ip));
+        }
+        match self.sans.is_empty() {
+            true => self
+                .common_name
+                .as_deref()
+                .is_some_and(|name| hostname_matches(name, &host)),
+            false => self.sans.iter().any(|san| hostname_matches(san, &host)),
+        }
+    }
+
+    /// 检查证书在 `now` 时的问题
+    pub fn issues(&self, host: &str, now: NaiveDateTime, warn_days: i64) -> Vec<CertIssue> {
+        let mut issues = Vec::new();
+        if now > self.not_after {
+            issues.push(CertIssue::Expired);
+        } else if self.not_after - now < chrono::Duration::days(warn_days) {
+            issues.push(CertIssue::ExpiringSoon);
+        }
+        if now < self.not_before {
+            issues.push(CertIssue::NotYetValid);
+        }
+        if !self.matches_host(host) {
+            issues.push(CertIssue::HostnameMismatch);
+        }
+        if self.self_signed {
+            issues.push(CertIssue::SelfSigned);
+        }
+        issues
+    }
+}
+
+/// 通配符只匹配最左侧的一级标签
+fn hostname_matches(pattern: &str, host: &str) -> bool {
+    let pattern = pattern.trim_end_matches('.');
+    match pattern.strip_prefix("*.") {
+        Some(suffix) => host
+            .split_once('.')
+            .is_some_and(|(label, rest)| !label.is_empty() && rest == suffix),
+        None => pattern == host,
+    }
+}
+
+/// SHA-256 指纹，格式与 `openssl x509 -fingerprint -sha256` 一致
+fn fingerprint(der: &[u8]) -> String {
+    Sha256::digest(der)
+        .iter()
+        .map(|byte| format!("{:02X}", byte))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// 一个目标的检查结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CertCheck {
+    /// `host:port`
+    pub target: String,
+    pub server_name: String,
+    /// 执行检查的 Agent，由 Hub 检查时为空
+    pub agent_id: Option<Uuid>,
+    pub certificate: Option<CertificateInfo>,
+    /// 服务器返回的证书数量
+    pub chain_length: usize,
+    pub issues: Vec<CertIssue>,
+    pub error: Option<String>,
+    pub checked_at: NaiveDateTime,
+}
+
+impl CertCheck {
+    /// 检查 DER 格式的证书链，`chain` 为连接失败的原因时只记录错误
+    pub fn from_chain(
+        target: String,
+        server_name: String,
+        chain: Result<Vec<Vec<u8>>>,
+        checked_at: NaiveDateTime,
+        warn_days: i64,
+    ) -> Self {
+        let parsed = chain.and_then(|chain| {
+            let leaf = chain.first().ok_or_else(|| anyhow!("没有返回证书"))?;
+            Ok((CertificateInfo::parse(leaf)?, chain.len()))
+        });
+        let (certificate, chain_length, issues, error) = match parsed {
+            Ok((certificate, chain_length)) => {
+                let issues = certificate.issues(&server_name, checked_at, warn_days);
+                (Some(certificate), chain_length, issues, None)
+            }
+            Err(e) => (None, 0, Vec::new(), Some(format!("{:#}", e))),
+        };
+        Self {
+            target,
+            server_name,
+            agent_id: None,
+            certificate,
+            chain_length,
+            issues,
+            error,
+            checked_at,
+        }
+    }
+
+    /// 检查 Agent 上报的证书链
+    pub fn from_agent_report(agent_id: Uuid, report: CertProbeReport, warn_days: i64) -> Self {
+        let chain = match report.error {
+            Some(error) => Err(anyhow!(error)),
+            None => report
+                .chain
+                .iter()
+                .map(|der| BASE64.decode(der))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| anyhow!("Agent 上报的证书编码无效: {}", e)),
+        };
+        let checked_at = DateTime::from_timestamp(report.probed_at, 0)
+            .unwrap_or_else(Utc::now)
+            .naive_utc();
+        let mut check = Self::from_chain(
+            report.target,
+            report.server_name,
+            chain,
+            checked_at,
+            warn_days,
+        );
+        check.agent_id = Some(agent_id);
+        check
+    }
+
+    /// 是否需要处理：存在问题或无法连接
+    pub fn has_problem(&self) -> bool {
+        !self.issues.is_empty() || self.error.is_some()
+    }
+
+    /// 转换为待保存的检查结果
+    pub fn to_new_check(&self) -> NewCertificateCheck {
+        let certificate = self.certificate.as_ref();
+        NewCertificateCheck {
+            target: self.target.clone(),
+            server_name: self.server_name.clone(),
+            agent_id: self.agent_id.map(|id| id.to_string()),
+            subject: certificate.map(|c| c.subject.clone()),
+            issuer: certificate.map(|c| c.issuer.clone()),
+            sans: certificate.map(|c| c.sans.clone()).unwrap_or_default(),
+            not_before: certificate.map(|c| c.not_before),
+            not_after: certificate.map(|c| c.not_after),
+            fingerprint: certificate.map(|c| c.fingerprint.clone()),
+            chain_length: self.chain_length as i32,
+            issues: self.issues.iter().map(|i| i.as_str().to_string()).collect(),
+            error: self.error.clone(),
+            checked_at: self.checked_at,
+        }
+    }
+}
+
+/// 检查选项
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CheckOptions {
+    /// 到期前多少天标记为即将过期
+    pub warn_days: i64,
+    /// 单个目标的连接和握手超时
+    pub timeout: Duration,
+}
+
+impl Default for CheckOptions {
+    fn default() -> Self {
+        Self {
+            warn_days: DEFAULT_WARN_DAYS,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+}
+
+/// 目标的规范写法 `host:port`，IPv6 地址加方括号
+fn normalize_target(host: &str, port: u16) -> String {
+    match host.contains(':') {
+        true => format!("[{}]:{}", host, port),
+        false => format!("{}:{}", host, port),
+    }
+}
+
+/// 从 Hub 并发检查所有目标
+pub async fn check_targets(targets: &[String], options: CheckOptions) -> Vec<CertCheck> {
+    join_all(targets.iter().map(|target| async move {
+        let checked_at = Utc::now().naive_utc();
+        match probe::parse_target(target) {
+            Ok((host, port)) => {
+                let chain = probe::fetch_chain(&host, port, options.timeout).await;
+                let target = normalize_target(&host, port);
+                CertCheck::from_chain(target, host, chain, checked_at, options.warn_days)
+            }
+            Err(e) => CertCheck::from_chain(
+                target.clone(),
+                String::new(),
+                Err(e),
+                checked_at,
+                options.warn_days,
+            ),
+        }
+    }))
+    .await
+}
+
+/// 请求 Agent 检查目标，等待上报结果
+pub async fn check_via_agent(
+    hub: &AgentHubHandle,
+    agent_id: Uuid,
+    targets: &[String],
+    options: CheckOptions,
+) -> Result<Vec<CertCheck>> {
+    // Agent 并发检查所有目标，额外留出消息往返的时间
+    let reports = hub
+        .probe_certificates(agent_id, targets, options.timeout + DEFAULT_TIMEOUT)
+        .await
+        .map_err(|e| anyhow!("Agent 检查证书失败: {}", e))?;
+    Ok(reports
+        .into_iter()
+        .map(|report| CertCheck::from_agent_report(agent_id, report, options.warn_days))
+        .collect())
+}
+
+/// 托管的主机名：本地保存的域名及其 A、AAAA、CNAME 记录，不含通配符记录
+pub async fn managed_targets(conn: &DatabaseConnection) -> Result<Vec<String>> {
+    let domains = domains::list_domains(conn)
+        .await
+        .map_err(|e| anyhow!("查询域名失败: {}", e))?;
+    let records = records::get_records_by_domain(conn, None)
+        .await
+        .map_err(|e| anyhow!("查询解析记录失败: {}", e))?;
+
+    let mut hosts = BTreeSet::new();
+    for domain in &domains {
+        hosts.insert(domain.domain_name.to_ascii_lowercase());
+        for record in records
+            .iter()
+            .filter(|record| record.domain_id == domain.id)
+        {
+            if !matches!(record.record_type.as_str(), "A" | "AAAA" | "CNAME") {
+                continue;
+            }
+            let rr = record.record_name.trim_end_matches('.');
+            if rr.is_empty() || rr == "@" {
+                continue;
+            }
+            if rr.split('.').any(|label| label == "*") {
+                continue;
+            }
+            hosts.insert(format!("{}.{}", rr, domain.domain_name).to_ascii_lowercase());
+        }
+    }
+    Ok(hosts
+        .into_iter()
+        .map(|host| normalize_target(&host, probe::DEFAULT_PORT))
+        .collect())
+}
+
+/// 保存检查结果，返回保存后的记录
+pub async fn save_checks(
+    conn: &DatabaseConnection,
+    checks: &[CertCheck],
+) -> Result<Vec<certificate_check::Model>> {
+    let mut models = Vec::with_capacity(checks.len());
+    for check in checks {
+        if check.has_problem() {
+            warn!(
+                "证书 {} 存在问题: {:?} {:?}",
+                check.target, check.issues, check.error
+            );
+        }
+        models.push(
+            certificate_checks::save_certificate_check(conn, check.to_new_check())
+                .await
+                .map_err(|e| anyhow!(e))?,
+        );
+    }
+    info!("已保存 {} 个证书检查结果", models.len());
+    Ok(models)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Datelike;
+    use rcgen::{date_time_ymd, CertificateParams, DnType, KeyPair};
+
+    fn certificate(names: &[&str], days: i64) -> Vec<u8> {
+        let names: Vec<String> = names.iter().map(|name| name.to_string()).collect();
+        let mut params = CertificateParams::new(names).unwrap();
+        params
+            .distinguished_name
+            .push(DnType::CommonName, "Test Certificate");
+        let date = |days: i64| {
+            let date = (Utc::now() + chrono::Duration::days(days)).date_naive();
+            date_time_ymd(date.year(), date.month() as u8, date.day() as u8)
+        };
+        params.not_before = date(-1);
+        params.not_after = date(days);
+        let key_pair = KeyPair::generate().unwrap();
+        params.self_signed(&key_pair).unwrap().der().to_vec()
+    }
+
+    #[test]
+    fn test_hostname_matches() {
+        assert!(hostname_matches("www.example.com", "www.example.com"));
+        assert!(hostname_matches("*.example.com", "www.example.com"));
+        assert!(!hostname_matches("*.example.com", "example.com"));
+        assert!(!hostname_matches("*.example.com", "a.b.example.com"));
+        assert!(!hostname_matches("www.example.com", "api.example.com"));
+    }
+
+    #[test]
+    fn test_certificate_issues() {
+        let der = certificate(&["www.example.com", "*.api.example.com", "192.0.2.1"], 10);
+        let info = CertificateInfo::parse(&der).unwrap();
+        assert_eq!(info.subject, "CN=Test Certificate");
+        assert_eq!(
+            info.sans,
+            vec!["www.example.com", "*.api.example.com", "192.0.2.1"]
+        );
+        assert!(info.self_signed);
+        assert_eq!(info.fingerprint.len(), 32 * 3 - 1);
+
+        let now = Utc::now().naive_utc();
+        assert_eq!(
+            info.issues("v1.api.example.com", now, 30),
+            vec![CertIssue::ExpiringSoon, CertIssue::SelfSigned]
+        );
+        assert_eq!(
+            info.issues("WWW.Example.com.", now, 5),
+            vec![CertIssue::SelfSigned]
+        );
+        assert_eq!(
+            info.issues("example.com", now + chrono::Duration::days(20), 5),
+            vec![
+                CertIssue::Expired,
+                CertIssue::HostnameMismatch,
+                CertIssue::SelfSigned
+            ]
+        );
+        assert!(info.matches_host("192.0.2.1"));
+        assert!(!info.matches_host("192.0.2.2"));
+    }
+
+    #[test]
+    fn test_agent_report() {
+        let der = certificate(&["intranet.local"], 90);
+        let agent_id = Uuid::new_v4();
+        let report = CertProbeReport {
+            target: "intranet.local:8443".to_string(),
+            server_name: "intranet.local".to_string(),
+            chain: vec![BASE64.encode(&der)],
+            error: None,
+            probed_at: 1_700_000_000,
+        };
+        let check = CertCheck::from_agent_report(agent_id, report, 30);
+        assert_eq!(check.agent_id, Some(agent_id));
+        assert_eq!(check.chain_length, 1);
+        assert_eq!(check.checked_at.and_utc().timestamp(), 1_700_000_000);
+        let new_check = check.to_new_check();
+        assert_eq!(new_check.agent_id, Some(agent_id.to_string()));
+        assert_eq!(new_check.sans, vec!["intranet.local"]);
+        // 按上报时间检查，证书当时尚未生效
+        assert!(new_check.issues.contains(&"not_yet_valid".to_string()));
+
+        let report = CertProbeReport {
+            target: "db.internal:5443".to_string(),
+            server_name: "db.internal".to_string(),
+            chain: vec![],
+            error: Some("connection refused".to_string()),
+            probed_at: 1_700_000_000,
+        };
+        let check = CertCheck::from_agent_report(agent_id, report, 30);
+        assert!(check.has_problem());
+        assert!(check.certificate.is_none());
+        assert_eq!(check.error.as_deref(), Some("connection refused"));
+    }
+
+    #[test]
+    fn test_issue_names() {
+        for issue in [
+            CertIssue::Expired,
+            CertIssue::ExpiringSoon,
+            CertIssue::NotYetValid,
+            CertIssue::HostnameMismatch,
+            CertIssue::SelfSigned,
+        ] {
+            assert_eq!(CertIssue::parse(issue.as_str()), Some(issue));
+        }
+        assert_eq!(CertIssue::parse("unknown"), None);
+    }
+}
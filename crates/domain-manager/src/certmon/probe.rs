@@ -0,0 +1,152 @@
+//! 连接目标完成 TLS 握手，取得服务器返回的证书链
+//!
+//! 握手时不校验证书：监控需要看到过期、自签名等有问题的证书，检查在取得证书链后进行。
+
+use anyhow::{anyhow, bail, Result};
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio_rustls::rustls::client::danger::{
+    HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier,
+};
+use tokio_rustls::rustls::crypto::{ring, CryptoProvider};
+use tokio_rustls::rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use tokio_rustls::rustls::{ClientConfig, DigitallySignedStruct, Error, SignatureScheme};
+use tokio_rustls::TlsConnector;
+
+/// 未指定端口时使用的端口
+pub const DEFAULT_PORT: u16 = 443;
+
+/// 解析 `host`、`host:port` 或 `[v6]:port` 格式的目标
+pub fn parse_target(target: &str) -> Result<(String, u16)> {
+    let target = target.trim();
+    let (host, port) = if let Some(rest) = target.strip_prefix('[') {
+        let (host, rest) = rest
+            .split_once(']')
+            .ok_or_else(|| anyhow!("无效的目标「{}」", target))?;
+        match rest {
+            "" => (host, None),
+            _ => match rest.strip_prefix(':') {
+                Some(port) => (host, Some(port)),
+                None => bail!("无效的目标「{}」", target),
+            },
+        }
+    } else if target.parse::<IpAddr>().is_ok() {
+        (target, None)
+    } else {
+        match target.split_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (target, None),
+        }
+    };
+    if host.is_empty() {
+        bail!("无效的目标「{}」", target);
+    }
+    let port = match port {
+        Some(port) => port
+            .parse()
+            .map_err(|_| anyhow!("目标「{}」的端口无效", target))?,
+        None => DEFAULT_PORT,
+    };
+    Ok((host.to_string(), port))
+}
+
+/// 与目标握手，返回 DER 格式的证书链，叶子证书在前
+pub async fn fetch_chain(host: &str, port: u16, timeout: Duration) -> Result<Vec<Vec<u8>>> {
+    tokio::time::timeout(timeout, handshake(host, port))
+        .await
+        .map_err(|_| anyhow!("连接 {}:{} 超时（{} 秒）", host, port, timeout.as_secs()))?
+}
+
+async fn handshake(host: &str, port: u16) -> Result<Vec<Vec<u8>>> {
+    let server_name = ServerName::try_from(host.to_string())
+        .map_err(|e| anyhow!("无效的主机名「{}」: {}", host, e))?;
+    let provider = Arc::new(ring::default_provider());
+    let config = ClientConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()?
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(AcceptAnyCertificate(provider)))
+        .with_no_client_auth();
+
+    let stream = TcpStream::connect((host, port))
+        .await
+        .map_err(|e| anyhow!("连接 {}:{} 失败: {}", host, port, e))?;
+    let tls = TlsConnector::from(Arc::new(config))
+        .connect(server_name, stream)
+        .await
+        .map_err(|e| anyhow!("与 {}:{} 握手失败: {}", host, port, e))?;
+    let chain: Vec<Vec<u8>> = tls
+        .get_ref()
+        .1
+        .peer_certificates()
+        .unwrap_or_default()
+        .iter()
+        .map(|certificate| certificate.to_vec())
+        .collect();
+    if chain.is_empty() {
+        bail!("{}:{} 没有返回证书", host, port);
+    }
+    Ok(chain)
+}
+
+/// 接受任何证书，证书链取得后再检查
+#[derive(Debug)]
+struct AcceptAnyCertificate(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for AcceptAnyCertificate {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        _message: &[u8],
+        _cert: &CertificateDer<'_>,
+        _dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, Error> {
+        Ok(HandshakeSignatureValid::assertion())
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        _message: &[u8],
+        _cert: &CertificateDer<'_>,
+        _dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, Error> {
+        Ok(HandshakeSignatureValid::assertion())
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_target() {
+        let parse = |target: &str| parse_target(target).unwrap();
+        assert_eq!(parse("example.com"), ("example.com".to_string(), 443));
+        assert_eq!(parse("example.com:8443"), ("example.com".to_string(), 8443));
+        assert_eq!(parse("192.0.2.1"), ("192.0.2.1".to_string(), 443));
+        assert_eq!(
+            parse("[2001:db8::1]:8443"),
+            ("2001:db8::1".to_string(), 8443)
+        );
+        assert_eq!(parse("2001:db8::1"), ("2001:db8::1".to_string(), 443));
+        assert!(parse_target("").is_err());
+        assert!(parse_target(":443").is_err());
+        assert!(parse_target("example.com:https").is_err());
+        assert!(parse_target("[2001:db8::1]8443").is_err());
+    }
+}
@@ -0,0 +1,1028 @@
+//! 子命令实现
+//!
+//! 读操作直接查询本地数据库（账户、域名）或服务商接口（解析记录）；
+//! 写操作调用服务商接口后刷新本地缓存的解析记录，保证图形界面看到的数据一致。
+
+use super::output::{
+    AccountRow, CertificateCheckRow, CertificateExport, CertificateRow, ChangeRow, CommandOutput,
+    DomainExpiryRow, DomainRow, IngressRouteRow, MigrationOutput, MigrationRow, RecordRow, SyncRow,
+    ZoneExport,
+};
+use super::{
+    AccountsCommand, CertCheckArgs, CertCommand, CertIssueArgs, ChallengeKind, CliError, Command,
+    DomainExpiryArgs, DomainsCommand, HubArgs, IngressAddArgs, IngressCommand, MigrateArgs,
+    MigrateCommand, RecordArgs, RecordsCommand, StateArgs, ZoneCommand, ZoneSource, HUB_TOKEN_ENV,
+};
+use crate::acme::{self, CertificateRequest, Dns01Solver, Http01Solver};
+use crate::agent::connection::{AgentHub, AgentHubHandle};
+use crate::agent::ddns;
+use crate::agent::registry::AgentRegistry;
+use crate::api::provider::{create_dns_client_for_account, BoxedDnsClient};
+use crate::certmon::{self, CheckOptions};
+use crate::client::agent_client::AgentManagementClient;
+use crate::expiry::{self, LookupOptions, ReminderConfig};
+use crate::gui::model::domain::{DnsProvider, DomainName};
+use crate::model::dns_record_response::{Line, Record, Status, Type};
+use crate::models::account::Account;
+use crate::models::domain::{DomainStatus, NewDomain};
+use crate::models::record::NewRecord;
+use crate::storage::{
+    accounts, certificate_checks, certificates, domains, migration_records, records,
+};
+use crate::zone::{self, migrate, Change, DesiredState, ZoneRecord};
+use sea_orm::DatabaseConnection;
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// 同步时每页拉取的域名数量
+const DOMAIN_PAGE_SIZE: u32 = 100;
+
+/// 执行子命令
+pub async fn execute(
+    conn: &DatabaseConnection,
+    command: Command,
+) -> Result<CommandOutput, CliError> {
+    match command {
+        Command::Accounts(AccountsCommand::List) => {
+            let accounts = load_accounts(conn).await?;
+            Ok(CommandOutput::Accounts(
+                accounts.iter().map(AccountRow::from).collect(),
+            ))
+        }
+        Command::Domains(DomainsCommand::List { account }) => {
+            let domains = match account {
+                Some(selector) => {
+                    let account = find_account(conn, &selector).await?;
+                    domains::list_domains_by_account(conn, account.id).await
+                }
+                None => domains::list_domains(conn).await,
+            }
+            .map_err(|e| CliError::Failure(format!("查询域名失败: {}", e)))?;
+            Ok(CommandOutput::Domains(
+                domains.iter().map(DomainRow::from).collect(),
+            ))
+        }
+        Command::Domains(DomainsCommand::Expiry(args)) => domain_expiry(conn, args).await,
+        Command::Records(command) => execute_records(conn, command).await,
+        Command::Sync { account } => sync(conn, account.as_deref()).await,
+        Command::Zone(command) => execute_zone(conn, command).await,
+        Command::Plan(args) => execute_state(conn, args, false).await,
+        Command::Apply(args) => execute_state(conn, args, true).await,
+        Command::Migrate(command) => execute_migrate(conn, command).await,
+        Command::Cert(command) => execute_cert(conn, command).await,
+        Command::Ingress(command) => execute_ingress(conn, command).await,
+    }
+}
+
+/// 管理入口路由，添加时可把主机名解析到入口地址
+async fn execute_ingress(
+    conn: &DatabaseConnection,
+    command: IngressCommand,
+) -> Result<CommandOutput, CliError> {
+    match command {
+        IngressCommand::Add(args) => add_ingress_route(conn, args).await,
+        IngressCommand::List { hub } => {
+            let routes = connect_hub(&hub)
+                .await?
+                .list_ingress_routes()
+                .await
+                .map_err(|e| CliError::Failure(format!("查询入口路由失败: {:#}", e)))?;
+            Ok(CommandOutput::IngressRoutes(
+                routes.iter().map(IngressRouteRow::from).collect(),
+            ))
+        }
+        IngressCommand::Delete { id, hub } => {
+            let mut client = connect_hub(&hub).await?;
+            let route = client
+                .list_ingress_routes()
+                .await
+                .map_err(|e| CliError::Failure(format!("查询入口路由失败: {:#}", e)))?
+                .into_iter()
+                .find(|route| route.id == id)
+                .ok_or_else(|| CliError::NotFound(format!("入口路由 {} 不存在", id)))?;
+            client
+                .delete_ingress_route(&id)
+                .await
+                .map_err(|e| CliError::Failure(format!("删除入口路由失败: {:#}", e)))?;
+            Ok(CommandOutput::IngressRoutes(vec![IngressRouteRow::from(
+                &route,
+            )]))
+        }
+    }
+}
+
+async fn add_ingress_route(
+    conn: &DatabaseConnection,
+    args: IngressAddArgs,
+) -> Result<CommandOutput, CliError> {
+    let route = connect_hub(&args.hub)
+        .await?
+        .create_ingress_route(&args.hostname, &args.agent, &args.target, args.tls)
+        .await
+        .map_err(|e| CliError::Failure(format!("添加入口路由失败: {:#}", e)))?;
+    info!("已添加入口路由 {} -> {}", route.hostname, route.target);
+    let mut row = IngressRouteRow::from(&route);
+
+    if let Some(ip) = args.dns_ip {
+        let record_type = if ip.is_ipv4() { "A" } else { "AAAA" };
+        let outcome = ddns::apply_ip_change(conn, &route.hostname, record_type, &ip.to_string())
+            .await
+            .map_err(|e| {
+                CliError::Provider(format!(
+                    "入口路由已添加，但更新 {} 的解析记录失败: {:#}",
+                    route.hostname, e
+                ))
+            })?;
+        if outcome.changed {
+            info!("已将 {} 解析到 {}", route.hostname, ip);
+        }
+        row.dns_record = Some(format!("{} {}", record_type, ip));
+    }
+    Ok(CommandOutput::IngressRoutes(vec![row]))
+}
+
+/// 连接 Agent 管理服务，令牌默认读取环境变量
+async fn connect_hub(hub: &HubArgs) -> Result<AgentManagementClient, CliError> {
+    let token = hub
+        .token
+        .clone()
+        .or_else(|| std::env::var(HUB_TOKEN_ENV).ok());
+    AgentManagementClient::new(hub.endpoint.clone(), token)
+        .await
+        .map_err(|e| {
+            CliError::Failure(format!(
+                "连接 Agent 管理服务 {} 失败: {:#}",
+                hub.endpoint, e
+            ))
+        })
+}
+
+/// 查看域名到期时间，可先刷新注册信息，并把提醒发送到 Webhook
+async fn domain_expiry(
+    conn: &DatabaseConnection,
+    args: DomainExpiryArgs,
+) -> Result<CommandOutput, CliError> {
+    if args.remind_days.iter().any(|days| *days <= 0) {
+        return Err(CliError::Usage("提醒天数必须是正整数".to_string()));
+    }
+    let config = ReminderConfig {
+        days: args.remind_days,
+        webhook_url: args.webhook,
+    };
+
+    let lookups = match args.refresh {
+        true => {
+            let options = LookupOptions {
+                rdap_url: args.rdap_url,
+                whois_server: args.whois_server,
+                timeout: Duration::from_secs(args.timeout),
+            };
+            expiry::refresh(conn, &options)
+                .await
+                .map_err(|e| CliError::Failure(format!("{:#}", e)))?
+        }
+        false => vec![],
+    };
+
+    if config.webhook_url.is_some() {
+        let reminders = expiry::due_reminders(conn, &config)
+            .await
+            .map_err(|e| CliError::Failure(format!("{:#}", e)))?;
+        expiry::notify(conn, &config, &reminders)
+            .await
+            .map_err(|e| CliError::Failure(format!("{:#}", e)))?;
+    }
+
+    let domain_list = domains::list_domains(conn)
+        .await
+        .map_err(|e| CliError::Failure(format!("查询域名失败: {}", e)))?;
+    let mut rows: Vec<DomainExpiryRow> = domain_list
+        .iter()
+        .map(|domain| {
+            let lookup = lookups.iter().find(|lookup| lookup.domain_id == domain.id);
+            DomainExpiryRow::new(domain, &config, lookup)
+        })
+        .filter(|row| args.all || row.reminder.is_some() || row.error.is_some())
+        .collect();
+    // 按到期时间排序，没有到期时间的排在最后
+    rows.sort_by_key(|row| (row.days_left.is_none(), row.days_left));
+    Ok(CommandOutput::DomainExpiry(rows))
+}
+
+async fn execute_records(
+    conn: &DatabaseConnection,
+    command: RecordsCommand,
+) -> Result<CommandOutput, CliError> {
+    match command {
+        RecordsCommand::List { domain } => {
+            let target = DomainTarget::open(conn, &domain).await?;
+            let records = target.list_records().await?;
+            Ok(CommandOutput::Records(
+                records.iter().map(RecordRow::from).collect(),
+            ))
+        }
+        RecordsCommand::Add { domain, record } => {
+            let target = DomainTarget::open(conn, &domain).await?;
+            let new_record = build_record(&record, String::new());
+            target
+                .client
+                .add_dns_record(&target.domain_name(), &new_record)
+                .await
+                .map_err(|e| CliError::Provider(format!("添加解析记录失败: {:#}", e)))?;
+            let records = target.refresh(conn).await?;
+
+            // 添加接口不返回记录ID，从最新列表中找回新记录
+            let added = records
+                .iter()
+                .find(|r| {
+                    r.rr == record.rr
+                        && r.record_type == record.record_type
+                        && r.value == record.value
+                })
+                .unwrap_or(&new_record);
+            Ok(CommandOutput::Record(RecordRow::from(added)))
+        }
+        RecordsCommand::Update { domain, id, record } => {
+            let target = DomainTarget::open(conn, &domain).await?;
+            let records = target.list_records().await?;
+            let existing = match &id {
+                Some(id) => select_record_by_id(&records, id)?,
+                None => select_record(&records, &record.rr, &record.record_type, None)?,
+            };
+            let updated = build_record(&record, existing.record_id.clone());
+            target
+                .client
+                .update_dns_record(&target.domain_name(), &updated)
+                .await
+                .map_err(|e| CliError::Provider(format!("修改解析记录失败: {:#}", e)))?;
+            target.refresh(conn).await?;
+            Ok(CommandOutput::Record(RecordRow::from(&updated)))
+        }
+        RecordsCommand::Delete {
+            domain,
+            id,
+            rr,
+            record_type,
+            value,
+        } => {
+            let target = DomainTarget::open(conn, &domain).await?;
+            let records = target.list_records().await?;
+            let existing = match (&id, &rr, &record_type) {
+                (Some(id), _, _) => select_record_by_id(&records, id)?,
+                (None, Some(rr), Some(record_type)) => {
+                    select_record(&records, rr, record_type, value.as_deref())?
+                }
+                _ => return Err(CliError::Usage("请指定 --id 或 --rr 与 --type".to_string())),
+            };
+            let deleted = RecordRow::from(existing);
+            target
+                .client
+                .delete_dns_record(&target.domain_name(), &existing.record_id)
+                .await
+                .map_err(|e| CliError::Provider(format!("删除解析记录失败: {:#}", e)))?;
+            target.refresh(conn).await?;
+            Ok(CommandOutput::Record(deleted))
+        }
+    }
+}
+
+/// 待操作的域名及其所属账户的客户端
+struct DomainTarget {
+    name: String,
+    /// 本地数据库中的域名ID，导入到尚未同步的账户时为空
+    domain_id: Option<i64>,
+    provider: DnsProvider,
+    client: BoxedDnsClient,
+}
+
+impl DomainTarget {
+    /// 按本地保存的域名查找所属账户
+    async fn open(conn: &DatabaseConnection, domain_name: &str) -> Result<Self, CliError> {
+        let domain = domains::find_domain_by_name(conn, domain_name)
+            .await
+            .map_err(|e| CliError::Failure(format!("查询域名失败: {}", e)))?
+            .ok_or_else(|| {
+                CliError::NotFound(format!(
+                    "域名 {} 不存在，请先执行 sync 同步域名",
+                    domain_name
+                ))
+            })?;
+        let account = accounts::get_account_by_id(conn, domain.account_id)
+            .await
+            .map_err(|e| CliError::Failure(format!("查询账户失败: {}", e)))?
+            .ok_or_else(|| CliError::NotFound(format!("账户 ID {} 不存在", domain.account_id)))?;
+        Self::new(account, domain.domain_name, Some(domain.id))
+    }
+
+    /// 使用指定账户操作域名
+    async fn for_account(
+        conn: &DatabaseConnection,
+        account: Account,
+        domain_name: &str,
+    ) -> Result<Self, CliError> {
+        let domain_id = domains::find_domain_by_name_and_account(conn, domain_name, account.id)
+            .await
+            .map_err(|e| CliError::Failure(format!("查询域名失败: {}", e)))?
+            .map(|domain| domain.id);
+        Self::new(account, domain_name.to_string(), domain_id)
+    }
+
+    fn new(account: Account, name: String, domain_id: Option<i64>) -> Result<Self, CliError> {
+        let (provider, client) = create_dns_client_for_account(account)
+            .map_err(|e| CliError::Failure(format!("创建服务商客户端失败: {:#}", e)))?;
+        Ok(Self {
+            name,
+            domain_id,
+            provider,
+            client,
+        })
+    }
+
+    fn domain_name(&self) -> DomainName {
+        DomainName {
+            provider: self.provider,
+            name: self.name.clone(),
+            dns_record: vec![],
+        }
+    }
+
+    async fn list_records(&self) -> Result<Vec<Record>, CliError> {
+        self.client
+            .list_dns_records(self.name.clone())
+            .await
+            .map_err(|e| CliError::Provider(format!("查询解析记录失败: {:#}", e)))
+    }
+
+    /// 重新拉取解析记录并更新本地缓存
+    async fn refresh(&self, conn: &DatabaseConnection) -> Result<Vec<Record>, CliError> {
+        let records = self.list_records().await?;
+        if let Some(domain_id) = self.domain_id {
+            records::replace_domain_records(conn, domain_id, to_new_records(domain_id, &records))
+                .await
+                .map_err(CliError::Failure)?;
+        }
+        Ok(records)
+    }
+}
+
+async fn execute_zone(
+    conn: &DatabaseConnection,
+    command: ZoneCommand,
+) -> Result<CommandOutput, CliError> {
+    match command {
+        ZoneCommand::Export {
+            domain,
+            source,
+            file,
+        } => {
+            let target = DomainTarget::open(conn, &domain).await?;
+            let records: Vec<ZoneRecord> = match source {
+                ZoneSource::Live => target
+                    .list_records()
+                    .await?
+                    .iter()
+                    .map(ZoneRecord::from)
+                    .collect(),
+                ZoneSource::Local => records::get_records_by_domain(conn, target.domain_id)
+                    .await
+                    .map_err(|e| CliError::Failure(format!("查询本地解析记录失败: {:#}", e)))?
+                    .iter()
+                    .filter_map(ZoneRecord::from_entity)
+                    .collect(),
+            };
+
+            let content = zone::write_zone(&target.name, &records);
+            if let Some(path) = &file {
+                std::fs::write(path, &content).map_err(|e| {
+                    CliError::Failure(format!("写入文件 {} 失败: {}", path.display(), e))
+                })?;
+            }
+            Ok(CommandOutput::Zone(ZoneExport {
+                domain: target.name,
+                records: records.len(),
+                path: file.map(|path| path.display().to_string()),
+                content,
+            }))
+        }
+        ZoneCommand::Import {
+            domain,
+            file,
+            account,
+            apply,
+        } => {
+            let content = std::fs::read_to_string(&file).map_err(|e| {
+                CliError::Failure(format!("读取文件 {} 失败: {}", file.display(), e))
+            })?;
+            let parsed = zone::parse_zone(&content, &domain)
+                .map_err(|e| CliError::Usage(format!("{:#}", e)))?;
+
+            let target = match account {
+                Some(selector) => {
+                    let account = find_account(conn, &selector).await?;
+                    DomainTarget::for_account(conn, account, &domain).await?
+                }
+                None => DomainTarget::open(conn, &domain).await?,
+            };
+            let changes = zone::plan_import(&target.list_records().await?, &parsed);
+            if !apply {
+                return Ok(CommandOutput::Changes(
+                    changes.iter().map(ChangeRow::from).collect(),
+                ));
+            }
+
+            let results =
+                zone::apply_changes(target.client.as_ref(), &target.domain_name(), changes).await;
+            target.refresh(conn).await?;
+            Ok(CommandOutput::Changes(
+                results.iter().map(ChangeRow::from).collect(),
+            ))
+        }
+    }
+}
+
+/// 对比声明式配置和服务商上的记录，`apply` 为 true 时执行变更
+///
+/// 执行前先校验所有域名的配置，任何一条记录有误都不会调用服务商接口。
+/// 输出不包含无需变更的记录。
+async fn execute_state(
+    conn: &DatabaseConnection,
+    args: StateArgs,
+    apply: bool,
+) -> Result<CommandOutput, CliError> {
+    let content = std::fs::read_to_string(&args.file)
+        .map_err(|e| CliError::Failure(format!("读取文件 {} 失败: {}", args.file.display(), e)))?;
+    let state = DesiredState::parse(&content, &args.file)
+        .map_err(|e| CliError::Usage(format!("{:#}", e)))?;
+
+    let mut domains = Vec::new();
+    for (name, domain) in state.domains() {
+        let records = domain
+            .zone_records(&name)
+            .map_err(|e| CliError::Usage(format!("{:#}", e)))?;
+        let account = domain.account.clone().or_else(|| args.account.clone());
+        let target = match account {
+            Some(selector) => {
+                let account = find_account(conn, &selector).await?;
+                DomainTarget::for_account(conn, account, &name).await?
+            }
+            None => DomainTarget::open(conn, &name).await?,
+        };
+        if !migrate::supports_lines(target.provider) {
+            if let Some(Line::Custom(line)) = records
+                .iter()
+                .map(|record| &record.line)
+                .find(|line| **line != Line::Default)
+            {
+                return Err(CliError::Usage(format!(
+                    "{} 的服务商 {} 不支持解析线路「{}」",
+                    name,
+                    target.provider.name(),
+                    line
+                )));
+            }
+        }
+        domains.push((name, target, records));
+    }
+
+    let mut rows = Vec::new();
+    for (name, target, desired) in domains {
+        let changes: Vec<Change> =
+            zone::plan_state(&target.list_records().await?, &desired, args.prune)
+                .into_iter()
+                .filter(|change| !matches!(change, Change::Skip { .. }))
+                .collect();
+        info!("域名 {} 共有 {} 条待执行的变更", name, changes.len());
+
+        if !apply {
+            rows.extend(
+                changes
+                    .iter()
+                    .map(|change| ChangeRow::from(change).with_domain(&name)),
+            );
+            continue;
+        }
+        if changes.is_empty() {
+            continue;
+        }
+        let results =
+            zone::apply_changes(target.client.as_ref(), &target.domain_name(), changes).await;
+        target.refresh(conn).await?;
+        rows.extend(
+            results
+                .iter()
+                .map(|result| ChangeRow::from(result).with_domain(&name)),
+        );
+    }
+    Ok(CommandOutput::Changes(rows))
+}
+
+async fn execute_migrate(
+    conn: &DatabaseConnection,
+    command: MigrateCommand,
+) -> Result<CommandOutput, CliError> {
+    match command {
+        MigrateCommand::Preview(args) => {
+            let (source, target) = migration_accounts(conn, &args).await?;
+            let source = DomainTarget::for_account(conn, source, &args.domain).await?;
+            let (target, _) = create_dns_client_for_account(target)
+                .map_err(|e| CliError::Failure(format!("创建服务商客户端失败: {:#}", e)))?;
+            let items = migrate::preview_migration(
+                &source.name,
+                source.provider,
+                source.client.as_ref(),
+                target,
+            )
+            .await
+            .map_err(|e| CliError::Provider(format!("{:#}", e)))?;
+            Ok(CommandOutput::Migration(MigrationOutput {
+                id: None,
+                domain: source.name,
+                nameservers: vec![],
+                records: items.iter().map(MigrationRow::from).collect(),
+            }))
+        }
+        MigrateCommand::Run(args) => {
+            let (source, target) = migration_accounts(conn, &args).await?;
+            let report = migrate::run_migration(conn, &args.domain, source, target)
+                .await
+                .map_err(|e| CliError::Provider(format!("{:#}", e)))?;
+            Ok(CommandOutput::Migration(MigrationOutput::from(&report)))
+        }
+        MigrateCommand::Report { id } => {
+            let models = migration_records::get_migration_records(conn, &id)
+                .await
+                .map_err(CliError::Failure)?;
+            let first = models
+                .first()
+                .ok_or_else(|| CliError::NotFound(format!("迁移报告 {} 不存在", id)))?;
+            let domain = first.domain_name.clone();
+            // 报告中只保存了记录，目标上的 NS 从迁移成功的根域名 NS 记录中取出
+            let nameservers = models
+                .iter()
+                .filter(|model| model.rr == "@" && model.record_type == "NS")
+                .filter(|model| matches!(model.status.as_str(), "created" | "updated" | "existing"))
+                .map(|model| model.value.clone())
+                .collect();
+            Ok(CommandOutput::Migration(MigrationOutput {
+                id: Some(id),
+                domain,
+                nameservers,
+                records: models.iter().map(MigrationRow::from).collect(),
+            }))
+        }
+    }
+}
+
+async fn execute_cert(
+    conn: &DatabaseConnection,
+    command: CertCommand,
+) -> Result<CommandOutput, CliError> {
+    match command {
+        CertCommand::Issue(args) => {
+            let model = issue_certificate(conn, args).await?;
+            Ok(CommandOutput::Certificates(vec![CertificateRow::from(
+                &model,
+            )]))
+        }
+        CertCommand::List { domain } => {
+            let domain_id = match domain {
+                Some(name) => Some(
+                    domains::find_domain_by_name(conn, &name)
+                        .await
+                        .map_err(|e| CliError::Failure(format!("查询域名失败: {}", e)))?
+                        .ok_or_else(|| CliError::NotFound(format!("域名 {} 不存在", name)))?
+                        .id,
+                ),
+                None => None,
+            };
+            let models = certificates::list_certificates(conn, domain_id)
+                .await
+                .map_err(CliError::Failure)?;
+            Ok(CommandOutput::Certificates(
+                models.iter().map(CertificateRow::from).collect(),
+            ))
+        }
+        CertCommand::Export { id, cert, key } => {
+            let model = certificates::get_certificate(conn, id)
+                .await
+                .map_err(CliError::Failure)?
+                .ok_or_else(|| CliError::NotFound(format!("证书 {} 不存在", id)))?;
+            let private_key = certificates::open_private_key(&model).map_err(CliError::Locked)?;
+            std::fs::write(&cert, &model.certificate_pem)
+                .map_err(|e| CliError::Failure(format!("写入文件 {:?} 失败: {}", cert, e)))?;
+            write_private_file(&key, &private_key)
+                .map_err(|e| CliError::Failure(format!("写入文件 {:?} 失败: {}", key, e)))?;
+            Ok(CommandOutput::CertificateExport(CertificateExport {
+                id,
+                certificate_path: cert.display().to_string(),
+                key_path: key.display().to_string(),
+            }))
+        }
+        CertCommand::Check(args) => {
+            let models = check_certificates(conn, args).await?;
+            Ok(CommandOutput::CertificateChecks(
+                models.iter().map(CertificateCheckRow::from).collect(),
+            ))
+        }
+        CertCommand::Inventory => {
+            let models = certificate_checks::list_certificate_checks(conn)
+                .await
+                .map_err(CliError::Failure)?;
+            Ok(CommandOutput::CertificateChecks(
+                models.iter().map(CertificateCheckRow::from).collect(),
+            ))
+        }
+    }
+}
+
+/// 检查指定目标和托管主机名的证书，由本机或 Agent 连接，保存检查结果
+async fn check_certificates(
+    conn: &DatabaseConnection,
+    args: CertCheckArgs,
+) -> Result<Vec<crate::storage::entities::certificate_check::Model>, CliError> {
+    let mut targets = args.targets;
+    if args.managed {
+        let managed = certmon::managed_targets(conn)
+            .await
+            .map_err(|e| CliError::Failure(format!("{:#}", e)))?;
+        targets.extend(managed);
+    }
+    let mut seen = HashSet::new();
+    targets.retain(|target| seen.insert(target.clone()));
+    if targets.is_empty() {
+        return Err(CliError::NotFound("没有需要检查的主机".to_string()));
+    }
+    for target in &targets {
+        certmon::probe::parse_target(target).map_err(|e| CliError::Usage(e.to_string()))?;
+    }
+
+    let options = CheckOptions {
+        warn_days: args.warn_days,
+        timeout: Duration::from_secs(args.timeout),
+    };
+    let checks = match &args.agent {
+        Some(agent) => {
+            let (hub, agent_id) = wait_for_agent(
+                &args.hub_listen,
+                agent,
+                Duration::from_secs(args.agent_wait),
+            )
+            .await?;
+            certmon::check_via_agent(&hub, agent_id, &targets, options)
+                .await
+                .map_err(|e| CliError::Failure(format!("{:#}", e)))?
+        }
+        None => certmon::check_targets(&targets, options).await,
+    };
+    certmon::save_checks(conn, &checks)
+        .await
+        .map_err(|e| CliError::Failure(format!("{:#}", e)))
+}
+
+async fn issue_certificate(
+    conn: &DatabaseConnection,
+    args: CertIssueArgs,
+) -> Result<crate::storage::entities::certificate::Model, CliError> {
+    let root_certificate = match &args.ca_cert {
+        Some(path) => Some(
+            std::fs::read(path)
+                .map_err(|e| CliError::Failure(format!("读取根证书 {:?} 失败: {}", path, e)))?,
+        ),
+        None => None,
+    };
+    let request = CertificateRequest {
+        names: args.names,
+        contact: args.email,
+        directory_url: args.directory,
+        root_certificate,
+        polling: None,
+    };
+
+    let result = match args.challenge {
+        ChallengeKind::Dns01 => {
+            let mut solver = Dns01Solver::new(conn.clone()).with_propagation(
+                Duration::from_secs(args.propagation_timeout),
+                Duration::from_secs(10),
+            );
+            if args.skip_propagation_check {
+                solver = solver.with_nameservers(vec![]);
+            } else if !args.nameserver.is_empty() {
+                solver = solver.with_nameservers(args.nameserver);
+            }
+            acme::issue_certificate(conn, &request, &solver).await
+        }
+        ChallengeKind::Http01 => {
+            let agent = args.agent.unwrap_or_default();
+            let (hub, agent_id) = wait_for_agent(
+                &args.hub_listen,
+                &agent,
+                Duration::from_secs(args.agent_wait),
+            )
+            .await?;
+            acme::issue_certificate(conn, &request, &Http01Solver::new(hub, agent_id)).await
+        }
+    };
+    result.map_err(|e| CliError::Provider(format!("申请证书失败: {:#}", e)))
+}
+
+/// 启动 Agent Hub，等待指定的 Agent（ID 或名称）连接
+async fn wait_for_agent(
+    listen: &str,
+    agent: &str,
+    wait: Duration,
+) -> Result<(AgentHubHandle, Uuid), CliError> {
+    let mut hub = AgentHub::new(Arc::new(AgentRegistry::new()), listen);
+    hub.start().await.map_err(CliError::Failure)?;
+    let handle = hub.handle();
+    tokio::spawn(async move { hub.run().await });
+
+    info!("等待 Agent {} 连接到 {}", agent, listen);
+    let deadline = Instant::now() + wait;
+    loop {
+        let found = handle
+            .registry()
+            .get_all()
+            .await
+            .into_iter()
+            .find(|candidate| candidate.name == agent || candidate.id.to_string() == agent);
+        if let Some(found) = found {
+            info!("Agent {} 已连接: {}", found.name, found.id);
+            return Ok((handle, found.id));
+        }
+        if Instant::now() >= deadline {
+            return Err(CliError::NotFound(format!(
+                "Agent {} 没有在 {} 秒内连接到 {}",
+                agent,
+                wait.as_secs(),
+                listen
+            )));
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+}
+
+/// 写入私钥文件，Unix 上只允许当前用户读写
+fn write_private_file(path: &Path, content: &str) -> std::io::Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    std::io::Write::write_all(&mut options.open(path)?, content.as_bytes())
+}
+
+/// 迁移的源账户和目标账户，未指定源账户时使用域名所属的账户
+async fn migration_accounts(
+    conn: &DatabaseConnection,
+    args: &MigrateArgs,
+) -> Result<(Account, Account), CliError> {
+    let source = match &args.from {
+        Some(selector) => find_account(conn, selector).await?,
+        None => {
+            let domain = domains::find_domain_by_name(conn, &args.domain)
+                .await
+                .map_err(|e| CliError::Failure(format!("查询域名失败: {}", e)))?
+                .ok_or_else(|| {
+                    CliError::NotFound(format!(
+                        "域名 {} 不存在，请先执行 sync 同步域名或使用 --from 指定源账户",
+                        args.domain
+                    ))
+                })?;
+            accounts::get_account_by_id(conn, domain.account_id)
+                .await
+                .map_err(|e| CliError::Failure(format!("查询账户失败: {}", e)))?
+                .ok_or_else(|| {
+                    CliError::NotFound(format!("账户 ID {} 不存在", domain.account_id))
+                })?
+        }
+    };
+    let target = find_account(conn, &args.to).await?;
+    if source.id == target.id {
+        return Err(CliError::Usage("源账户和目标账户不能相同".to_string()));
+    }
+    Ok((source, target))
+}
+
+fn build_record(args: &RecordArgs, record_id: String) -> Record {
+    Record::new(
+        Status::Enable,
+        args.rr.clone(),
+        args.record_type.clone(),
+        args.value.clone(),
+        record_id,
+        args.ttl,
+    )
+}
+
+fn to_new_records(domain_id: i64, records: &[Record]) -> Vec<NewRecord> {
+    records
+        .iter()
+        .map(|r| NewRecord {
+            domain_id,
+            record_name: r.rr.clone(),
+            record_type: r.record_type.to_string(),
+            record_value: r.value.clone(),
+            ttl: r.ttl,
+        })
+        .collect()
+}
+
+fn select_record_by_id<'a>(records: &'a [Record], id: &str) -> Result<&'a Record, CliError> {
+    records
+        .iter()
+        .find(|r| r.record_id == id)
+        .ok_or_else(|| CliError::NotFound(format!("解析记录 {} 不存在", id)))
+}
+
+/// 按主机记录、类型（及记录值）匹配唯一的解析记录
+fn select_record<'a>(
+    records: &'a [Record],
+    rr: &str,
+    record_type: &Type,
+    value: Option<&str>,
+) -> Result<&'a Record, CliError> {
+    let matched: Vec<&Record> = records
+        .iter()
+        .filter(|r| {
+            r.rr == rr && &r.record_type == record_type && value.is_none_or(|v| r.value == v)
+        })
+        .collect();
+
+    match matched.as_slice() {
+        [record] => Ok(record),
+        [] => Err(CliError::NotFound(format!(
+            "未找到主机记录为 {} 的 {} 记录",
+            rr, record_type
+        ))),
+        _ => Err(CliError::Usage(format!(
+            "主机记录 {} 的 {} 记录有 {} 条，请使用 --id 或 --value 指定",
+            rr,
+            record_type,
+            matched.len()
+        ))),
+    }
+}
+
+async fn load_accounts(conn: &DatabaseConnection) -> Result<Vec<Account>, CliError> {
+    accounts::list_accounts(conn)
+        .await
+        .map_err(|e| CliError::Failure(format!("查询账户失败: {}", e)))
+}
+
+/// 按账户ID或用户名查找账户
+async fn find_account(conn: &DatabaseConnection, selector: &str) -> Result<Account, CliError> {
+    let id = selector.parse::<i64>().ok();
+    load_accounts(conn)
+        .await?
+        .into_iter()
+        .find(|account| Some(account.id) == id || account.username == selector)
+        .ok_or_else(|| CliError::NotFound(format!("账户 {} 不存在", selector)))
+}
+
+async fn sync(
+    conn: &DatabaseConnection,
+    selector: Option<&str>,
+) -> Result<CommandOutput, CliError> {
+    let accounts = match selector {
+        Some(selector) => vec![find_account(conn, selector).await?],
+        None => load_accounts(conn).await?,
+    };
+
+    let mut rows = Vec::new();
+    for account in accounts {
+        info!("同步账户 {} 的域名", account.username);
+        if let Err(err) = sync_account(conn, &account, &mut rows).await {
+            warn!("同步账户 {} 失败: {}", account.username, err);
+            rows.push(SyncRow {
+                account: account.username.clone(),
+                domain: "-".to_string(),
+                records: 0,
+                error: Some(err),
+            });
+        }
+    }
+    Ok(CommandOutput::Sync(rows))
+}
+
+/// 同步账户下的域名列表，再逐个同步解析记录
+///
+/// 单个域名同步失败只记录在结果中，不影响其他域名。
+async fn sync_account(
+    conn: &DatabaseConnection,
+    account: &Account,
+    rows: &mut Vec<SyncRow>,
+) -> Result<(), String> {
+    let (_, client) = create_dns_client_for_account(account.clone()).map_err(|e| e.to_string())?;
+
+    for name in fetch_all_domains(&client).await? {
+        let existing = domains::find_domain_by_name_and_account(conn, &name, account.id)
+            .await
+            .map_err(|e| e.to_string())?;
+        if existing.is_none() {
+            domains::add_domain(
+                conn,
+                NewDomain {
+                    domain_name: name,
+                    registration_date: None,
+                    expiration_date: None,
+                    registrar: None,
+                    status: DomainStatus::Active,
+                    account_id: account.id,
+                },
+            )
+            .await
+            .map_err(|e| format!("{:#}", e))?;
+        }
+    }
+
+    let local_domains = domains::list_domains_by_account(conn, account.id)
+        .await
+        .map_err(|e| e.to_string())?;
+    for domain in local_domains {
+        let result = match client.list_dns_records(domain.domain_name.clone()).await {
+            Ok(records) => {
+                records::replace_domain_records(
+                    conn,
+                    domain.id,
+                    to_new_records(domain.id, &records),
+                )
+                .await
+            }
+            Err(err) => Err(format!("{:#}", err)),
+        };
+        rows.push(SyncRow {
+            account: account.username.clone(),
+            domain: domain.domain_name,
+            records: *result.as_ref().unwrap_or(&0),
+            error: result.err(),
+        });
+    }
+    Ok(())
+}
+
+/// 分页拉取账户下的全部域名
+///
+/// 部分服务商忽略分页参数总是返回全部域名，某页没有新域名时即停止。
+async fn fetch_all_domains(client: &BoxedDnsClient) -> Result<Vec<String>, String> {
+    let mut names = Vec::new();
+    let mut seen = HashSet::new();
+    let mut page = 1;
+    loop {
+        let domains = client
+            .list_domains(page, DOMAIN_PAGE_SIZE)
+            .await
+            .map_err(|e| format!("查询域名列表失败: {:#}", e))?;
+        let count = domains.len();
+        let mut added = false;
+        for domain in domains {
+            if seen.insert(domain.name.clone()) {
+                names.push(domain.name);
+                added = true;
+            }
+        }
+        if !added || count < DOMAIN_PAGE_SIZE as usize {
+            return Ok(names);
+        }
+        page += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(id: &str, rr: &str, record_type: Type, value: &str) -> Record {
+        Record::new(
+            Status::Enable,
+            rr.to_string(),
+            record_type,
+            value.to_string(),
+            id.to_string(),
+            600,
+        )
+    }
+
+    #[test]
+    fn test_select_record() {
+        let records = vec![
+            record("1", "www", Type::A, "192.0.2.1"),
+            record("2", "www", Type::A, "192.0.2.2"),
+            record("3", "www", Type::AAAA, "2001:db8::1"),
+        ];
+
+        let selected = select_record(&records, "www", &Type::AAAA, None).unwrap();
+        assert_eq!(selected.record_id, "3");
+        let selected = select_record(&records, "www", &Type::A, Some("192.0.2.2")).unwrap();
+        assert_eq!(selected.record_id, "2");
+
+        // 多条匹配时要求用户指定
+        let err = select_record(&records, "www", &Type::A, None).unwrap_err();
+        assert!(matches!(err, CliError::Usage(_)));
+        let err = select_record(&records, "api", &Type::A, None).unwrap_err();
+        assert!(matches!(err, CliError::NotFound(_)));
+        assert!(select_record_by_id(&records, "4").is_err());
+    }
+}
@@ -1,126 +1,806 @@
-use crate::gui::handlers::message_handler::{AppMessage, MessageCategory, WindowMessage};
+//! 命令行接口
+//!
+//! 不带子命令时启动图形界面；带子命令时以无界面模式运行，便于在脚本、CI 和定时任务中使用：
+//!
+//! ```text
+//! domain_manager accounts list
+//! domain_manager domains list [--account <ID|NAME>]
+//! domain_manager domains expiry [--refresh] [--all] [--remind-days 30,7,1] [--webhook <URL>]
+//! domain_manager records list <DOMAIN>
+//! domain_manager records add <DOMAIN> --rr www --type A --value 192.0.2.1 [--ttl 600]
+//! domain_manager records update <DOMAIN> [--id <RECORD_ID>] --rr www --type A --value 192.0.2.2
+//! domain_manager records delete <DOMAIN> (--id <RECORD_ID> | --rr www --type A [--value ...])
+//! domain_manager sync [--account <ID|NAME>]
+//! domain_manager zone export <DOMAIN> [--source live|local] [--file example.com.zone]
+//! domain_manager zone import <DOMAIN> <FILE> [--account <ID|NAME>] [--apply]
+//! domain_manager plan dns.yaml [--account <ID|NAME>] [--prune]
+//! domain_manager apply dns.yaml [--account <ID|NAME>] [--prune]
+//! domain_manager migrate preview <DOMAIN> --to <ID|NAME> [--from <ID|NAME>]
+//! domain_manager migrate run <DOMAIN> --to <ID|NAME> [--from <ID|NAME>]
+//! domain_manager migrate report <MIGRATION_ID>
+//! domain_manager cert issue example.com "*.example.com" --email admin@example.com [--directory <URL>]
+//! domain_manager cert issue www.example.com --challenge http-01 --agent <ID|NAME> [--hub-listen 0.0.0.0:8080]
+//! domain_manager cert list [--domain <DOMAIN>]
+//! domain_manager cert export <ID> --cert fullchain.pem --key privkey.pem
+//! domain_manager cert check [HOST[:PORT]]... [--managed] [--warn-days 30] [--agent <ID|NAME>]
+//! domain_manager cert inventory
+//! domain_manager ingress add app1.example.com --agent <AGENT_ID> --target 127.0.0.1:8080 [--tls] [--dns-ip 203.0.113.10]
+//! domain_manager ingress list
+//! domain_manager ingress delete <ROUTE_ID>
+//! ```
+//!
+//! 所有命令支持 `--output json|table`，日志只输出到标准错误，退出码见 [`exit_code`]。
+
+mod commands;
+mod output;
+
+use crate::acme::LETS_ENCRYPT_DIRECTORY;
+use crate::api::provider::rrset::parse_record_type;
+use crate::configs;
+use crate::model::dns_record_response::Type;
+use crate::storage::encryption::DatabaseKeyManager;
+use crate::storage::init_database;
 use crate::utils::formatted_strings::APP_VERSION;
 use crate::DOMAIN_MANAGER_LOWERCASE;
-use clap::Parser;
-use iced::{window, Task};
+use clap::{Parser, Subcommand, ValueEnum};
+use secrecy::SecretString;
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+use tracing::{warn, Level};
+
+pub use commands::execute;
+pub use output::CommandOutput;
+
+/// 无界面模式下读取主密码的环境变量
+pub const MASTER_PASSWORD_ENV: &str = "DOMAIN_MANAGER_MASTER_PASSWORD";
+
+/// 未指定 `--token` 时读取 Agent 管理服务令牌的环境变量
+pub const HUB_TOKEN_ENV: &str = "DOMAIN_MANAGER_HUB_TOKEN";
+
+/// 进程退出码
+pub mod exit_code {
+    /// 执行成功
+    pub const SUCCESS: i32 = 0;
+    /// 一般错误（数据库、配置等）
+    pub const FAILURE: i32 = 1;
+    /// 参数错误或记录匹配不唯一（与 clap 的参数错误退出码一致）
+    pub const USAGE: i32 = 2;
+    /// 账户、域名或记录不存在
+    pub const NOT_FOUND: i32 = 3;
+    /// 主密钥未解锁
+    pub const LOCKED: i32 = 4;
+    /// 服务商接口调用失败，或同步部分失败
+    pub const PROVIDER: i32 = 5;
+    /// 证书检查发现问题或无法连接
+    pub const CERTIFICATE: i32 = 6;
+    /// 有域名到达到期提醒阈值
+    pub const EXPIRING: i32 = 7;
+}
 
 #[derive(Parser, Debug)]
 #[command(
     name = DOMAIN_MANAGER_LOWERCASE,
     bin_name = DOMAIN_MANAGER_LOWERCASE,
     version = APP_VERSION,
-    about = "Application to comfortably monitor your network traffic"
+    about = "域名与DNS解析记录管理工具，不带子命令时启动图形界面"
 )]
-struct Args {
-    /// Start sniffing packets from the supplied network adapter
-    #[arg(short, long, value_name = "NAME", num_args = 0..=1)]
-    adapter: Option<String>,
-    #[cfg(all(windows, not(debug_assertions)))]
-    /// Show the logs (stdout and stderr) of the most recent application run
-    #[arg(short, long, exclusive = true)]
-    logs: bool,
-    /// Restore default settings
-    #[arg(short, long, exclusive = true)]
-    restore_default: bool,
-}
-
-pub fn handle_cli_args() -> Task<MessageCategory> {
-    let args = Args::parse();
-
-    #[cfg(all(windows, not(debug_assertions)))]
-    if let Some(logs_file) = crate::utils::formatted_strings::get_logs_file_path() {
-        if args.logs {
-            std::process::Command::new("explorer")
-                .arg(logs_file)
-                .spawn()
-                .unwrap()
-                .wait()
-                .unwrap_or_default();
-            std::process::exit(0);
-        } else {
-            // truncate logs file
-            let _ = std::fs::OpenOptions::new()
-                .write(true)
-                .truncate(true)
-                .open(logs_file);
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    /// 输出格式
+    #[arg(short, long, value_enum, global = true, default_value_t = OutputFormat::Table)]
+    pub output: OutputFormat,
+    /// 在标准错误中输出调试日志
+    #[arg(short, long, global = true)]
+    pub verbose: bool,
+}
+
+/// 输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Table,
+    Json,
+}
+
+#[derive(Subcommand, Debug, PartialEq)]
+pub enum Command {
+    /// 账户管理
+    #[command(subcommand)]
+    Accounts(AccountsCommand),
+    /// 域名管理
+    #[command(subcommand)]
+    Domains(DomainsCommand),
+    /// 解析记录管理（直接调用服务商接口）
+    #[command(subcommand)]
+    Records(RecordsCommand),
+    /// 从服务商同步域名和解析记录到本地数据库
+    Sync {
+        /// 只同步指定账户（ID 或用户名）
+        #[arg(long, value_name = "ID|NAME")]
+        account: Option<String>,
+    },
+    /// 区域文件（RFC 1035）导入导出
+    #[command(subcommand)]
+    Zone(ZoneCommand),
+    /// 对比声明式配置和服务商上的解析记录，输出执行计划
+    Plan(StateArgs),
+    /// 按声明式配置执行新增、修改和删除
+    Apply(StateArgs),
+    /// 跨服务商迁移域名解析
+    #[command(subcommand)]
+    Migrate(MigrateCommand),
+    /// 通过 ACME（DNS-01）申请证书，查看和导出保存的证书，检查线上证书
+    #[command(subcommand)]
+    Cert(CertCommand),
+    /// 管理 Agent 管理服务的入口路由，按主机名把 HTTP(S) 请求转发到 Agent 所在网络
+    #[command(subcommand)]
+    Ingress(IngressCommand),
+}
+
+#[derive(Subcommand, Debug, PartialEq)]
+pub enum AccountsCommand {
+    /// 列出所有账户（不输出凭证）
+    List,
+}
+
+#[derive(Subcommand, Debug, PartialEq)]
+pub enum DomainsCommand {
+    /// 列出本地保存的域名
+    List {
+        /// 只列出指定账户（ID 或用户名）的域名
+        #[arg(long, value_name = "ID|NAME")]
+        account: Option<String>,
+    },
+    /// 查看域名的注册和到期时间，列出到达提醒阈值的域名
+    Expiry(DomainExpiryArgs),
+}
+
+/// 域名到期查询参数
+#[derive(clap::Args, Debug, PartialEq)]
+pub struct DomainExpiryArgs {
+    /// 先通过服务商接口、RDAP 或 WHOIS 刷新注册信息
+    #[arg(long)]
+    pub refresh: bool,
+    /// 列出所有域名，而不只是到达提醒阈值的域名
+    #[arg(long)]
+    pub all: bool,
+    /// 提醒阈值（到期前天数），逗号分隔
+    #[arg(long, value_delimiter = ',', default_values_t = crate::expiry::DEFAULT_REMINDER_DAYS)]
+    pub remind_days: Vec<i64>,
+    /// 把还没发送过的提醒以 JSON POST 到该地址
+    #[arg(long, value_name = "URL")]
+    pub webhook: Option<String>,
+    /// RDAP 服务地址
+    #[arg(long, value_name = "URL", default_value = crate::expiry::rdap::DEFAULT_RDAP_URL)]
+    pub rdap_url: String,
+    /// WHOIS 服务器，默认通过 IANA 查找顶级域的服务器
+    #[arg(long, value_name = "HOST[:PORT]")]
+    pub whois_server: Option<String>,
+    /// 单次查询超时（秒）
+    #[arg(long, default_value_t = 10)]
+    pub timeout: u64,
+}
+
+#[derive(Subcommand, Debug, PartialEq)]
+pub enum RecordsCommand {
+    /// 列出域名的解析记录
+    List {
+        /// 域名
+        domain: String,
+    },
+    /// 添加解析记录
+    Add {
+        /// 域名
+        domain: String,
+        #[command(flatten)]
+        record: RecordArgs,
+    },
+    /// 修改解析记录，未指定 --id 时按主机记录和类型匹配唯一的记录
+    Update {
+        /// 域名
+        domain: String,
+        /// 服务商的记录ID
+        #[arg(long)]
+        id: Option<String>,
+        #[command(flatten)]
+        record: RecordArgs,
+    },
+    /// 删除解析记录，按记录ID或主机记录、类型（及记录值）匹配唯一的记录
+    Delete {
+        /// 域名
+        domain: String,
+        /// 服务商的记录ID
+        #[arg(long, conflicts_with_all = ["rr", "record_type", "value"])]
+        id: Option<String>,
+        /// 主机记录
+        #[arg(long, required_unless_present = "id", requires = "record_type")]
+        rr: Option<String>,
+        /// 记录类型
+        #[arg(long = "type", value_name = "TYPE", value_parser = parse_type, requires = "rr")]
+        record_type: Option<Type>,
+        /// 记录值
+        #[arg(long)]
+        value: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug, PartialEq)]
+pub enum ZoneCommand {
+    /// 将域名的解析记录导出为区域文件
+    Export {
+        /// 域名
+        domain: String,
+        /// 记录来源：服务商接口或本地数据库
+        #[arg(long, value_enum, default_value_t = ZoneSource::Live)]
+        source: ZoneSource,
+        /// 写入的文件，未指定时输出到标准输出
+        #[arg(short, long)]
+        file: Option<PathBuf>,
+    },
+    /// 导入区域文件，默认只预览新增、修改和跳过的记录
+    Import {
+        /// 域名（区域文件中相对名称的起点）
+        domain: String,
+        /// 区域文件
+        file: PathBuf,
+        /// 导入到指定账户（ID 或用户名），默认使用域名所属的账户
+        #[arg(long, value_name = "ID|NAME")]
+        account: Option<String>,
+        /// 执行预览中的变更
+        #[arg(long)]
+        apply: bool,
+    },
+}
+
+#[derive(Subcommand, Debug, PartialEq)]
+pub enum MigrateCommand {
+    /// 预览源记录转换到目标服务商后的结果，不修改目标账户
+    Preview(MigrateArgs),
+    /// 在目标账户上创建记录并核对，结果保存为迁移报告
+    Run(MigrateArgs),
+    /// 查看保存的迁移报告
+    Report {
+        /// 迁移ID
+        id: String,
+    },
+}
+
+#[derive(Subcommand, Debug, PartialEq)]
+pub enum CertCommand {
+    /// 申请证书，默认通过域名所属账户添加 `_acme-challenge` TXT 记录完成验证
+    Issue(CertIssueArgs),
+    /// 列出保存的证书
+    List {
+        /// 只列出指定域名的证书
+        #[arg(long)]
+        domain: Option<String>,
+    },
+    /// 导出证书链和私钥
+    Export {
+        /// 证书ID
+        id: i64,
+        /// 证书链写入的文件
+        #[arg(long, value_name = "FILE")]
+        cert: PathBuf,
+        /// 私钥写入的文件
+        #[arg(long, value_name = "FILE")]
+        key: PathBuf,
+    },
+    /// 连接主机检查服务器证书，结果保存到证书清单
+    Check(CertCheckArgs),
+    /// 查看证书清单（每个目标最近一次的检查结果）
+    Inventory,
+}
+
+#[derive(Subcommand, Debug, PartialEq)]
+pub enum IngressCommand {
+    /// 添加入口路由，可同时把主机名解析到入口地址
+    Add(IngressAddArgs),
+    /// 列出入口路由
+    List {
+        #[command(flatten)]
+        hub: HubArgs,
+    },
+    /// 删除入口路由，不会删除解析记录
+    Delete {
+        /// 路由ID
+        id: String,
+        #[command(flatten)]
+        hub: HubArgs,
+    },
+}
+
+/// 入口路由参数
+#[derive(clap::Args, Debug, PartialEq)]
+pub struct IngressAddArgs {
+    /// 主机名，如 app1.example.com
+    pub hostname: String,
+    /// 转发请求的 Agent ID
+    #[arg(long, value_name = "AGENT_ID")]
+    pub agent: String,
+    /// Agent 所在网络中的目标地址
+    #[arg(long, value_name = "HOST:PORT")]
+    pub target: String,
+    /// 按 SNI 透传 TLS 连接，由目标服务提供证书
+    #[arg(long)]
+    pub tls: bool,
+    /// 入口的公网 IP，指定时通过主机名所属账户创建或更新 A/AAAA 记录
+    #[arg(long, value_name = "IP")]
+    pub dns_ip: Option<IpAddr>,
+    #[command(flatten)]
+    pub hub: HubArgs,
+}
+
+/// Agent 管理服务的连接参数
+#[derive(clap::Args, Debug, PartialEq)]
+pub struct HubArgs {
+    /// Agent 管理服务的 gRPC 地址
+    #[arg(long, value_name = "URL", default_value = "http://127.0.0.1:50051")]
+    pub endpoint: String,
+    /// 登录 Agent 管理服务获得的令牌，默认读取环境变量 DOMAIN_MANAGER_HUB_TOKEN
+    #[arg(long)]
+    pub token: Option<String>,
+}
+
+/// 证书检查参数
+#[derive(clap::Args, Debug, PartialEq)]
+pub struct CertCheckArgs {
+    /// 检查的目标，`host`、`host:port` 或 `[v6]:port`，默认端口 443
+    #[arg(value_name = "HOST[:PORT]", required_unless_present = "managed")]
+    pub targets: Vec<String>,
+    /// 同时检查本地保存的域名及其 A、AAAA、CNAME 记录
+    #[arg(long)]
+    pub managed: bool,
+    /// 到期前多少天标记为即将过期
+    #[arg(long, default_value_t = crate::certmon::DEFAULT_WARN_DAYS)]
+    pub warn_days: i64,
+    /// 单个目标的连接超时（秒）
+    #[arg(long, default_value_t = 10)]
+    pub timeout: u64,
+    /// 由私有网络中的 Agent（ID 或名称）执行检查
+    #[arg(long, value_name = "ID|NAME")]
+    pub agent: Option<String>,
+    /// 通过 Agent 检查时 Agent Hub 的监听地址，Agent 需要连接到该地址
+    #[arg(long, value_name = "IP:PORT", default_value = "0.0.0.0:8080")]
+    pub hub_listen: String,
+    /// 等待 Agent 连接的超时（秒）
+    #[arg(long, default_value_t = 300)]
+    pub agent_wait: u64,
+}
+
+/// 证书申请参数
+#[derive(clap::Args, Debug, PartialEq)]
+pub struct CertIssueArgs {
+    /// 证书包含的域名，第一个为通用名称，支持 `*.example.com`
+    #[arg(required = true)]
+    pub names: Vec<String>,
+    /// 联系邮箱，注册 ACME 账户时使用
+    #[arg(long)]
+    pub email: Vec<String>,
+    /// ACME 目录地址，Let's Encrypt 测试环境或本地 Pebble 等
+    #[arg(long, default_value = LETS_ENCRYPT_DIRECTORY)]
+    pub directory: String,
+    /// 额外信任的 PEM 根证书，用于 Pebble 等使用自签名证书的服务器
+    #[arg(long, value_name = "FILE")]
+    pub ca_cert: Option<PathBuf>,
+    /// 确认挑战记录生效时查询的 DNS 服务器，默认使用公共 DNS
+    #[arg(long, value_name = "IP:PORT")]
+    pub nameserver: Vec<SocketAddr>,
+    /// 添加挑战记录后不查询 DNS 服务器，直接通知验证
+    #[arg(long)]
+    pub skip_propagation_check: bool,
+    /// 等待挑战记录生效的超时（秒）
+    #[arg(long, default_value_t = 300)]
+    pub propagation_timeout: u64,
+    /// 挑战类型，没有 DNS 接口权限的主机使用 http-01
+    #[arg(long, value_enum, default_value_t = ChallengeKind::Dns01)]
+    pub challenge: ChallengeKind,
+    /// 提供 HTTP-01 挑战内容的 Agent（ID 或名称）
+    #[arg(long, value_name = "ID|NAME", required_if_eq("challenge", "http-01"))]
+    pub agent: Option<String>,
+    /// HTTP-01 挑战期间 Agent Hub 的监听地址，Agent 需要连接到该地址
+    #[arg(long, value_name = "IP:PORT", default_value = "0.0.0.0:8080")]
+    pub hub_listen: String,
+    /// 等待 Agent 连接的超时（秒）
+    #[arg(long, default_value_t = 300)]
+    pub agent_wait: u64,
+}
+
+/// ACME 挑战类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ChallengeKind {
+    /// 通过域名所属账户添加 TXT 记录
+    #[value(name = "dns-01")]
+    Dns01,
+    /// 由目标主机上的 Agent 提供 HTTP 文件
+    #[value(name = "http-01")]
+    Http01,
+}
+
+/// 迁移参数
+#[derive(clap::Args, Debug, PartialEq)]
+pub struct MigrateArgs {
+    /// 域名
+    pub domain: String,
+    /// 目标账户（ID 或用户名），需要已在目标服务商添加该域名
+    #[arg(long, value_name = "ID|NAME")]
+    pub to: String,
+    /// 源账户（ID 或用户名），默认使用域名所属的账户
+    #[arg(long, value_name = "ID|NAME")]
+    pub from: Option<String>,
+}
+
+/// 导出时的记录来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ZoneSource {
+    /// 从服务商接口实时查询
+    Live,
+    /// 本地数据库中同步过的记录
+    Local,
+}
+
+/// 声明式配置参数
+#[derive(clap::Args, Debug, PartialEq)]
+pub struct StateArgs {
+    /// 配置文件（.yaml、.yml 或 .toml）
+    pub file: PathBuf,
+    /// 配置中未指定账户的域名使用该账户（ID 或用户名），默认使用域名所属的账户
+    #[arg(long, value_name = "ID|NAME")]
+    pub account: Option<String>,
+    /// 同时删除配置中没有的记录（SOA 和根域名的 NS 记录除外）
+    #[arg(long)]
+    pub prune: bool,
+}
+
+/// 解析记录内容
+#[derive(clap::Args, Debug, PartialEq)]
+pub struct RecordArgs {
+    /// 主机记录，根域名使用 @
+    #[arg(long)]
+    pub rr: String,
+    /// 记录类型，如 A、AAAA、CNAME、MX、TXT
+    #[arg(long = "type", value_name = "TYPE", value_parser = parse_type)]
+    pub record_type: Type,
+    /// 记录值
+    #[arg(long)]
+    pub value: String,
+    /// TTL（秒）
+    #[arg(long, default_value_t = 600)]
+    pub ttl: i32,
+}
+
+fn parse_type(value: &str) -> Result<Type, String> {
+    parse_record_type(&value.to_ascii_uppercase())
+        .ok_or_else(|| format!("不支持的记录类型: {}", value))
+}
+
+/// 命令执行错误，每种错误对应一个退出码
+#[derive(Debug, thiserror::Error)]
+pub enum CliError {
+    #[error("{0}")]
+    Failure(String),
+    #[error("{0}")]
+    Usage(String),
+    #[error("{0}")]
+    NotFound(String),
+    #[error("{0}")]
+    Locked(String),
+    #[error("{0}")]
+    Provider(String),
+}
+
+impl CliError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CliError::Failure(_) => exit_code::FAILURE,
+            CliError::Usage(_) => exit_code::USAGE,
+            CliError::NotFound(_) => exit_code::NOT_FOUND,
+            CliError::Locked(_) => exit_code::LOCKED,
+            CliError::Provider(_) => exit_code::PROVIDER,
+        }
+    }
+}
+
+/// 以无界面模式执行子命令，返回进程退出码
+pub async fn run(cli: Cli) -> i32 {
+    let Some(command) = cli.command else {
+        return exit_code::SUCCESS;
+    };
+    init_cli_logging(cli.verbose);
+
+    let result: Result<CommandOutput, CliError> = async {
+        unlock_master_key()?;
+        let conn = init_database(&configs::get().database)
+            .await
+            .map_err(|e| CliError::Failure(format!("初始化数据库失败: {:#}", e)))?;
+        execute(&conn, command).await
+    }
+    .await;
+
+    match result {
+        Ok(output) => {
+            println!("{}", output.render(cli.output));
+            output.exit_code()
+        }
+        Err(err) => {
+            eprintln!("错误: {}", err);
+            err.exit_code()
         }
     }
+}
+
+/// 依次尝试系统钥匙串和环境变量解锁主密钥
+fn unlock_master_key() -> Result<(), CliError> {
+    if DatabaseKeyManager::is_unlocked() {
+        return Ok(());
+    }
+
+    match DatabaseKeyManager::unlock_from_keyring() {
+        Ok(true) => return Ok(()),
+        Ok(false) => {}
+        Err(err) => warn!("从系统钥匙串读取主密钥失败: {}", err),
+    }
+
+    let password = std::env::var(MASTER_PASSWORD_ENV).map_err(|_| {
+        CliError::Locked(format!(
+            "主密钥未解锁，请设置环境变量 {} 或在图形界面中选择记住主密钥",
+            MASTER_PASSWORD_ENV
+        ))
+    })?;
+    DatabaseKeyManager::unlock(&SecretString::from(password), false)
+        .map_err(|e| CliError::Locked(format!("解锁主密钥失败: {}", e)))
+}
+
+/// 命令行模式的日志只输出到标准错误，避免污染 JSON 输出
+fn init_cli_logging(verbose: bool) {
+    let level = if verbose { Level::DEBUG } else { Level::WARN };
+    let _ = tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_max_level(level)
+        .with_target(false)
+        .try_init();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Cli, clap::Error> {
+        Cli::try_parse_from(std::iter::once(DOMAIN_MANAGER_LOWERCASE).chain(args.iter().copied()))
+    }
+
+    #[test]
+    fn test_no_subcommand_starts_gui() {
+        let cli = parse(&[]).unwrap();
+        assert_eq!(cli.command, None);
+        assert_eq!(cli.output, OutputFormat::Table);
+    }
+
+    #[test]
+    fn test_parse_records_commands() {
+        let cli = parse(&[
+            "records",
+            "add",
+            "example.com",
+            "--rr",
+            "www",
+            "--type",
+            "aaaa",
+            "--value",
+            "::1",
+            "-o",
+            "json",
+        ])
+        .unwrap();
+        assert_eq!(cli.output, OutputFormat::Json);
+        assert_eq!(
+            cli.command,
+            Some(Command::Records(RecordsCommand::Add {
+                domain: "example.com".to_string(),
+                record: RecordArgs {
+                    rr: "www".to_string(),
+                    record_type: Type::AAAA,
+                    value: "::1".to_string(),
+                    ttl: 600,
+                },
+            }))
+        );
+
+        let cli = parse(&["records", "delete", "example.com", "--id", "42"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Records(RecordsCommand::Delete { id: Some(ref id), rr: None, .. })) if id == "42"
+        ));
+
+        let cli = parse(&["sync", "--account", "ops"]).unwrap();
+        assert_eq!(
+            cli.command,
+            Some(Command::Sync {
+                account: Some("ops".to_string())
+            })
+        );
+
+        let cli = parse(&["zone", "import", "example.com", "example.zone", "--apply"]).unwrap();
+        assert_eq!(
+            cli.command,
+            Some(Command::Zone(ZoneCommand::Import {
+                domain: "example.com".to_string(),
+                file: PathBuf::from("example.zone"),
+                account: None,
+                apply: true,
+            }))
+        );
+
+        let cli = parse(&["migrate", "run", "example.com", "--to", "cf"]).unwrap();
+        assert_eq!(
+            cli.command,
+            Some(Command::Migrate(MigrateCommand::Run(MigrateArgs {
+                domain: "example.com".to_string(),
+                to: "cf".to_string(),
+                from: None,
+            })))
+        );
+
+        let cli = parse(&["apply", "dns.yaml", "--prune"]).unwrap();
+        assert_eq!(
+            cli.command,
+            Some(Command::Apply(StateArgs {
+                file: PathBuf::from("dns.yaml"),
+                account: None,
+                prune: true,
+            }))
+        );
+
+        let cli = parse(&[
+            "cert",
+            "issue",
+            "example.com",
+            "*.example.com",
+            "--email",
+            "admin@example.com",
+            "--nameserver",
+            "127.0.0.1:8053",
+        ])
+        .unwrap();
+        assert_eq!(
+            cli.command,
+            Some(Command::Cert(CertCommand::Issue(CertIssueArgs {
+                names: vec!["example.com".to_string(), "*.example.com".to_string()],
+                email: vec!["admin@example.com".to_string()],
+                directory: LETS_ENCRYPT_DIRECTORY.to_string(),
+                ca_cert: None,
+                nameserver: vec!["127.0.0.1:8053".parse().unwrap()],
+                skip_propagation_check: false,
+                propagation_timeout: 300,
+                challenge: ChallengeKind::Dns01,
+                agent: None,
+                hub_listen: "0.0.0.0:8080".to_string(),
+                agent_wait: 300,
+            })))
+        );
+        // 至少需要一个域名
+        assert!(parse(&["cert", "issue"]).is_err());
+
+        let cli = parse(&[
+            "cert",
+            "issue",
+            "www.example.com",
+            "--challenge",
+            "http-01",
+            "--agent",
+            "web-1",
+        ])
+        .unwrap();
+        let Some(Command::Cert(CertCommand::Issue(args))) = cli.command else {
+            panic!("unexpected command: {:?}", cli.command);
+        };
+        assert_eq!(args.challenge, ChallengeKind::Http01);
+        assert_eq!(args.agent.as_deref(), Some("web-1"));
+        // HTTP-01 需要指定 Agent
+        assert!(parse(&["cert", "issue", "www.example.com", "--challenge", "http-01"]).is_err());
+
+        let cli = parse(&["cert", "check", "example.com", "db.internal:5443", "--agent", "lan-1"])
+            .unwrap();
+        assert_eq!(
+            cli.command,
+            Some(Command::Cert(CertCommand::Check(CertCheckArgs {
+                targets: vec!["example.com".to_string(), "db.internal:5443".to_string()],
+                managed: false,
+                warn_days: 30,
+                timeout: 10,
+                agent: Some("lan-1".to_string()),
+                hub_listen: "0.0.0.0:8080".to_string(),
+                agent_wait: 300,
+            })))
+        );
+        assert!(parse(&["cert", "check", "--managed", "--warn-days", "14"]).is_ok());
+        // 需要目标或 --managed
+        assert!(parse(&["cert", "check"]).is_err());
+    }
 
-    // if args.restore_default {
-    //     Configs::default().store();
-    //     std::process::exit(0);
-    // }
+    #[test]
+    fn test_parse_domains_expiry() {
+        let cli = parse(&["domains", "expiry"]).unwrap();
+        assert_eq!(
+            cli.command,
+            Some(Command::Domains(DomainsCommand::Expiry(DomainExpiryArgs {
+                refresh: false,
+                all: false,
+                remind_days: vec![30, 7, 1],
+                webhook: None,
+                rdap_url: "https://rdap.org".to_string(),
+                whois_server: None,
+                timeout: 10,
+            })))
+        );
 
-    let mut boot_task_chain =
-        window::get_latest().map(|id| MessageCategory::Window(WindowMessage::WindowId(id)));
-    if let Some(_adapter) = args.adapter {
-        boot_task_chain =
-            boot_task_chain.chain(Task::done(MessageCategory::App(AppMessage::Started)));
+        let cli = parse(&["domains", "expiry", "--refresh", "--remind-days", "60,14"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Domains(DomainsCommand::Expiry(DomainExpiryArgs { refresh: true, ref remind_days, .. })))
+                if remind_days == &vec![60, 14]
+        ));
     }
-    boot_task_chain
-}
-
-// #[cfg(test)]
-// mod tests {
-//     use std::sync::{Arc, Mutex};
-//
-//     use serial_test::serial;
-//
-//     use crate::configs::types::config_window::{PositionTuple, SizeTuple};
-//     use crate::gui::styles::types::custom_palette::ExtraStyles;
-//     use crate::gui::styles::types::gradient_type::GradientType;
-//     use crate::notifications::types::notifications::Notifications;
-//
-//     use super::*;
-//
-//     #[test]
-//     #[serial]
-//     fn test_restore_default_configs() {
-//         // initial configs stored are the default ones
-//         assert_eq!(Configs::load(), Configs::default());
-//         let modified_configs = Configs {
-//             settings: ConfigSettings {
-//                 color_gradient: GradientType::Wild,
-//                 language: Language::ZH,
-//                 scale_factor: 0.65,
-//                 mmdb_country: "countrymmdb".to_string(),
-//                 mmdb_asn: "asnmmdb".to_string(),
-//                 style_path: format!(
-//                     "{}/resources/themes/catppuccin.toml",
-//                     env!("CARGO_MANIFEST_DIR")
-//                 ),
-//                 notifications: Notifications {
-//                     volume: 100,
-//                     packets_notification: Default::default(),
-//                     bytes_notification: Default::default(),
-//                     favorite_notification: Default::default(),
-//                 },
-//                 style: StyleType::Custom(ExtraStyles::DraculaDark),
-//             },
-//             device: ConfigDevice {
-//                 device_name: "hey-hey".to_string(),
-//             },
-//             window: ConfigWindow {
-//                 position: PositionTuple(440.0, 99.0),
-//                 size: SizeTuple(452.0, 870.0),
-//                 thumbnail_position: PositionTuple(20.0, 20.0),
-//             },
-//         };
-//         // we want to be sure that modified config is different from defaults
-//         assert_ne!(Configs::default(), modified_configs);
-//         //store modified configs
-//         modified_configs.clone().store();
-//         // assert they've been stored
-//         assert_eq!(Configs::load(), modified_configs);
-//         // restore defaults
-//         Configs::default().store();
-//         // assert that defaults are stored
-//         assert_eq!(Configs::load(), Configs::default());
-//
-//         // only needed because it will delete config files via its Drop implementation
-//         Sniffer::new(
-//             &Arc::new(Mutex::new(Configs::default())),
-//             Arc::new(Mutex::new(Some(true))),
-//         );
-//     }
-// }
+
+    #[test]
+    fn test_parse_ingress_commands() {
+        let cli = parse(&[
+            "ingress",
+            "add",
+            "app1.example.com",
+            "--agent",
+            "7d6f0a52-3c55-4a8e-9a36-0f1a4a0f6c11",
+            "--target",
+            "127.0.0.1:8080",
+            "--dns-ip",
+            "203.0.113.10",
+        ])
+        .unwrap();
+        assert_eq!(
+            cli.command,
+            Some(Command::Ingress(IngressCommand::Add(IngressAddArgs {
+                hostname: "app1.example.com".to_string(),
+                agent: "7d6f0a52-3c55-4a8e-9a36-0f1a4a0f6c11".to_string(),
+                target: "127.0.0.1:8080".to_string(),
+                tls: false,
+                dns_ip: Some("203.0.113.10".parse().unwrap()),
+                hub: HubArgs {
+                    endpoint: "http://127.0.0.1:50051".to_string(),
+                    token: None,
+                },
+            })))
+        );
+
+        let cli = parse(&["ingress", "delete", "42", "--endpoint", "http://hub:50051", "--token", "t"])
+            .unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Ingress(IngressCommand::Delete { ref id, ref hub }))
+                if id == "42" && hub.endpoint == "http://hub:50051" && hub.token.as_deref() == Some("t")
+        ));
+        // 需要目标地址，IP 必须合法
+        assert!(parse(&["ingress", "add", "a.example.com", "--agent", "1"]).is_err());
+        assert!(parse(&[
+            "ingress", "add", "a.example.com", "--agent", "1", "--target", "h:1", "--dns-ip", "x"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_invalid_arguments() {
+        // 未知的记录类型
+        assert!(parse(&[
+            "records", "add", "a.com", "--rr", "www", "--type", "XYZ", "--value", "1"
+        ])
+        .is_err());
+        // 删除时既没有记录ID也没有主机记录
+        assert!(parse(&["records", "delete", "a.com"]).is_err());
+        // 记录ID与主机记录不能同时指定
+        assert!(
+            parse(&["records", "delete", "a.com", "--id", "1", "--rr", "www", "--type", "A"])
+                .is_err()
+        );
+        let err = parse(&["accounts", "remove"]).unwrap_err();
+        assert_eq!(err.exit_code(), exit_code::USAGE);
+    }
+}
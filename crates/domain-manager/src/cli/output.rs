@@ -0,0 +1,760 @@
+//! 命令输出
+//!
+//! 表格输出面向终端阅读，JSON 输出面向脚本处理，两者使用相同的字段。
+
+use super::{exit_code, OutputFormat};
+use crate::expiry::{ExpiryLookup, ReminderConfig};
+use crate::model::dns_record_response::{Record, Status};
+use crate::models::account::Account;
+use crate::models::domain::DomainEntity;
+use crate::storage::entities::{certificate, certificate_check, migration_record};
+use crate::zone::migrate::{MigrationItem, MigrationReport};
+use crate::zone::{Change, ChangeResult};
+use domain_agent_management_client::proto;
+use serde::Serialize;
+
+/// 账户信息（不包含凭证）
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AccountRow {
+    pub id: i64,
+    pub name: String,
+    pub provider: String,
+    pub credential_type: String,
+    pub created_at: String,
+}
+
+impl From<&Account> for AccountRow {
+    fn from(account: &Account) -> Self {
+        Self {
+            id: account.id,
+            name: account.username.clone(),
+            provider: account.provider_type.clone(),
+            credential_type: account.credential_type.clone(),
+            created_at: account.created_at.clone(),
+        }
+    }
+}
+
+/// 本地保存的域名
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DomainRow {
+    pub id: i64,
+    pub name: String,
+    pub account_id: i64,
+    pub status: String,
+}
+
+impl From<&DomainEntity> for DomainRow {
+    fn from(domain: &DomainEntity) -> Self {
+        Self {
+            id: domain.id,
+            name: domain.domain_name.clone(),
+            account_id: domain.account_id,
+            status: domain.status.to_string().into(),
+        }
+    }
+}
+
+/// 域名的注册信息和到期提醒
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DomainExpiryRow {
+    pub name: String,
+    pub registrar: Option<String>,
+    pub registration_date: Option<String>,
+    pub expiration_date: Option<String>,
+    /// 距离到期的天数，已过期时为负数
+    pub days_left: Option<i64>,
+    /// 到达的提醒阈值（天）
+    pub reminder: Option<i64>,
+    /// 本次刷新使用的查询方式：provider、rdap 或 whois
+    pub source: Option<String>,
+    /// 本次刷新失败的原因
+    pub error: Option<String>,
+}
+
+impl DomainExpiryRow {
+    pub fn new(
+        domain: &DomainEntity,
+        config: &ReminderConfig,
+        lookup: Option<&ExpiryLookup>,
+    ) -> Self {
+        let days_left = domain
+            .expiration_date
+            .map(|date| (date - chrono::Utc::now().naive_utc()).num_days());
+        Self {
+            name: domain.domain_name.clone(),
+            registrar: domain.registrar.clone(),
+            registration_date: domain.registration_date.map(|date| date.to_string()),
+            expiration_date: domain.expiration_date.map(|date| date.to_string()),
+            days_left,
+            reminder: days_left.and_then(|days| config.threshold(days)),
+            source: lookup
+                .and_then(|lookup| lookup.result.as_ref().ok())
+                .map(|(_, source)| source.as_str().to_string()),
+            error: lookup.and_then(|lookup| lookup.result.clone().err()),
+        }
+    }
+}
+
+/// 服务商返回的解析记录
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RecordRow {
+    pub id: String,
+    pub rr: String,
+    #[serde(rename = "type")]
+    pub record_type: String,
+    pub value: String,
+    pub ttl: i32,
+    pub enabled: bool,
+}
+
+impl From<&Record> for RecordRow {
+    fn from(record: &Record) -> Self {
+        Self {
+            id: record.record_id.clone(),
+            rr: record.rr.clone(),
+            record_type: record.record_type.to_string(),
+            value: record.value.clone(),
+            ttl: record.ttl,
+            enabled: record.status == Status::Enable,
+        }
+    }
+}
+
+/// 单个域名的同步结果
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SyncRow {
+    pub account: String,
+    pub domain: String,
+    pub records: usize,
+    pub error: Option<String>,
+}
+
+/// 导出的区域文件
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ZoneExport {
+    pub domain: String,
+    pub records: usize,
+    /// 写入的文件，未指定时区域文件输出到标准输出
+    pub path: Option<String>,
+    pub content: String,
+}
+
+/// 区域文件导入或声明式配置的单条变更
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChangeRow {
+    /// 声明式配置可以包含多个域名，区域文件导入时为空
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub domain: Option<String>,
+    pub action: String,
+    pub rr: String,
+    #[serde(rename = "type")]
+    pub record_type: String,
+    pub value: String,
+    pub ttl: Option<u32>,
+    pub note: Option<String>,
+    pub error: Option<String>,
+}
+
+impl From<&Change> for ChangeRow {
+    fn from(change: &Change) -> Self {
+        let (rr, record_type, value, ttl) = change.target();
+        let note = match change {
+            Change::Add(_) | Change::Delete(_) => None,
+            Change::Update { current, .. } => {
+                Some(format!("原值 {} (TTL {})", current.value, current.ttl))
+            }
+            Change::Skip { reason, .. } => Some(reason.clone()),
+        };
+        Self {
+            domain: None,
+            action: change.action().to_string(),
+            rr: rr.to_string(),
+            record_type: record_type.to_string(),
+            value: value.to_string(),
+            ttl,
+            note,
+            error: None,
+        }
+    }
+}
+
+impl ChangeRow {
+    pub fn with_domain(mut self, domain: impl Into<String>) -> Self {
+        self.domain = Some(domain.into());
+        self
+    }
+}
+
+impl From<&ChangeResult> for ChangeRow {
+    fn from(result: &ChangeResult) -> Self {
+        Self {
+            error: result.error.clone(),
+            ..ChangeRow::from(&result.change)
+        }
+    }
+}
+
+/// 迁移的单条记录
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MigrationRow {
+    pub rr: String,
+    #[serde(rename = "type")]
+    pub record_type: String,
+    pub value: String,
+    pub ttl: Option<u32>,
+    pub status: String,
+    pub warnings: Vec<String>,
+    pub error: Option<String>,
+}
+
+impl From<&MigrationItem> for MigrationRow {
+    fn from(item: &MigrationItem) -> Self {
+        Self {
+            rr: item.rr.clone(),
+            record_type: item.record_type.clone(),
+            value: item.value.clone(),
+            ttl: item.ttl,
+            status: item.status.as_str().to_string(),
+            warnings: item.warnings.clone(),
+            error: item.error.clone(),
+        }
+    }
+}
+
+impl From<&migration_record::Model> for MigrationRow {
+    fn from(model: &migration_record::Model) -> Self {
+        Self {
+            rr: model.rr.clone(),
+            record_type: model.record_type.clone(),
+            value: model.value.clone(),
+            ttl: model.ttl.map(|ttl| ttl.max(0) as u32),
+            status: model.status.clone(),
+            warnings: model
+                .warnings
+                .as_deref()
+                .map(|warnings| warnings.lines().map(str::to_string).collect())
+                .unwrap_or_default(),
+            error: model.error.clone(),
+        }
+    }
+}
+
+/// 迁移预览、执行结果或保存的报告
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MigrationOutput {
+    /// 迁移ID，预览时为空
+    pub id: Option<String>,
+    pub domain: String,
+    /// 目标服务商的 NS，需要到注册商处修改
+    pub nameservers: Vec<String>,
+    pub records: Vec<MigrationRow>,
+}
+
+impl From<&MigrationReport> for MigrationOutput {
+    fn from(report: &MigrationReport) -> Self {
+        Self {
+            id: Some(report.id.clone()),
+            domain: report.domain.clone(),
+            nameservers: report.nameservers.clone(),
+            records: report.items.iter().map(MigrationRow::from).collect(),
+        }
+    }
+}
+
+/// 保存的证书（不包含私钥）
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CertificateRow {
+    pub id: i64,
+    pub domain_id: Option<i64>,
+    pub names: Vec<String>,
+    pub not_before: String,
+    pub not_after: String,
+    pub directory_url: String,
+}
+
+impl From<&certificate::Model> for CertificateRow {
+    fn from(model: &certificate::Model) -> Self {
+        Self {
+            id: model.id,
+            domain_id: model.domain_id,
+            names: model.names.split(',').map(str::to_string).collect(),
+            not_before: model.not_before.to_string(),
+            not_after: model.not_after.to_string(),
+            directory_url: model.directory_url.clone(),
+        }
+    }
+}
+
+/// 导出的证书文件
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CertificateExport {
+    pub id: i64,
+    pub certificate_path: String,
+    pub key_path: String,
+}
+
+/// Agent 管理服务的入口路由
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IngressRouteRow {
+    pub id: String,
+    pub hostname: String,
+    pub agent_id: String,
+    pub target: String,
+    pub tls: bool,
+    pub created_by: Option<String>,
+    pub created_at: String,
+    /// 本次创建或更新的解析记录，如 `A 203.0.113.10`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dns_record: Option<String>,
+}
+
+impl From<&proto::IngressRoute> for IngressRouteRow {
+    fn from(route: &proto::IngressRoute) -> Self {
+        Self {
+            id: route.id.clone(),
+            hostname: route.hostname.clone(),
+            agent_id: route.agent_id.clone(),
+            target: route.target.clone(),
+            tls: route.tls,
+            created_by: route.created_by.clone(),
+            created_at: chrono::DateTime::from_timestamp(route.created_at, 0)
+                .map(|time| time.to_rfc3339())
+                .unwrap_or_default(),
+            dns_record: None,
+        }
+    }
+}
+
+/// 证书检查结果
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CertificateCheckRow {
+    pub target: String,
+    /// 执行检查的 Agent，由本机检查时为空
+    pub agent_id: Option<String>,
+    pub subject: Option<String>,
+    pub issuer: Option<String>,
+    pub sans: Vec<String>,
+    pub not_after: Option<String>,
+    /// 距离到期的天数，已过期时为负数
+    pub days_left: Option<i64>,
+    pub fingerprint: Option<String>,
+    pub issues: Vec<String>,
+    pub error: Option<String>,
+    pub checked_at: String,
+}
+
+impl CertificateCheckRow {
+    /// 存在问题或无法连接
+    fn has_problem(&self) -> bool {
+        !self.issues.is_empty() || self.error.is_some()
+    }
+}
+
+impl From<&certificate_check::Model> for CertificateCheckRow {
+    fn from(model: &certificate_check::Model) -> Self {
+        let split = |value: &str| {
+            value
+                .split(',')
+                .filter(|item| !item.is_empty())
+                .map(str::to_string)
+                .collect()
+        };
+        let now = chrono::Utc::now().naive_utc();
+        Self {
+            target: model.target.clone(),
+            agent_id: model.agent_id.clone(),
+            subject: model.subject.clone(),
+            issuer: model.issuer.clone(),
+            sans: split(&model.sans),
+            not_after: model.not_after.map(|time| time.to_string()),
+            days_left: model.not_after.map(|time| (time - now).num_days()),
+            fingerprint: model.fingerprint.clone(),
+            issues: split(&model.issues),
+            error: model.error.clone(),
+            checked_at: model.checked_at.to_string(),
+        }
+    }
+}
+
+/// 命令执行结果
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommandOutput {
+    Accounts(Vec<AccountRow>),
+    Domains(Vec<DomainRow>),
+    Records(Vec<RecordRow>),
+    /// 写操作后返回受影响的记录
+    Record(RecordRow),
+    Sync(Vec<SyncRow>),
+    Zone(ZoneExport),
+    /// 区域文件导入、声明式配置的变更预览或执行结果
+    Changes(Vec<ChangeRow>),
+    Migration(MigrationOutput),
+    Certificates(Vec<CertificateRow>),
+    CertificateExport(CertificateExport),
+    CertificateChecks(Vec<CertificateCheckRow>),
+    DomainExpiry(Vec<DomainExpiryRow>),
+    IngressRoutes(Vec<IngressRouteRow>),
+}
+
+impl CommandOutput {
+    /// 按指定格式渲染结果
+    pub fn render(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Json => self.to_json(),
+            OutputFormat::Table => self.to_table(),
+        }
+    }
+
+    /// 同步或导入存在失败项时返回服务商错误退出码，证书存在问题时返回证书退出码，
+    /// 域名到达到期提醒阈值时返回到期退出码
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CommandOutput::Sync(rows) if rows.iter().any(|row| row.error.is_some()) => {
+                exit_code::PROVIDER
+            }
+            CommandOutput::Changes(rows) if rows.iter().any(|row| row.error.is_some()) => {
+                exit_code::PROVIDER
+            }
+            CommandOutput::Migration(migration)
+                if migration
+                    .records
+                    .iter()
+                    .any(|row| matches!(row.status.as_str(), "failed" | "unverified")) =>
+            {
+                exit_code::PROVIDER
+            }
+            CommandOutput::CertificateChecks(rows) if rows.iter().any(|row| row.has_problem()) => {
+                exit_code::CERTIFICATE
+            }
+            CommandOutput::DomainExpiry(rows) if rows.iter().any(|row| row.error.is_some()) => {
+                exit_code::PROVIDER
+            }
+            CommandOutput::DomainExpiry(rows) if rows.iter().any(|row| row.reminder.is_some()) => {
+                exit_code::EXPIRING
+            }
+            _ => exit_code::SUCCESS,
+        }
+    }
+
+    fn to_json(&self) -> String {
+        let json = match self {
+            CommandOutput::Accounts(rows) => serde_json::to_string_pretty(rows),
+            CommandOutput::Domains(rows) => serde_json::to_string_pretty(rows),
+            CommandOutput::Records(rows) => serde_json::to_string_pretty(rows),
+            CommandOutput::Record(row) => serde_json::to_string_pretty(row),
+            CommandOutput::Sync(rows) => serde_json::to_string_pretty(rows),
+            CommandOutput::Zone(export) => serde_json::to_string_pretty(export),
+            CommandOutput::Changes(rows) => serde_json::to_string_pretty(rows),
+            CommandOutput::Migration(migration) => serde_json::to_string_pretty(migration),
+            CommandOutput::Certificates(rows) => serde_json::to_string_pretty(rows),
+            CommandOutput::CertificateExport(export) => serde_json::to_string_pretty(export),
+            CommandOutput::CertificateChecks(rows) => serde_json::to_string_pretty(rows),
+            CommandOutput::DomainExpiry(rows) => serde_json::to_string_pretty(rows),
+            CommandOutput::IngressRoutes(rows) => serde_json::to_string_pretty(rows),
+        };
+        json.unwrap_or_default()
+    }
+
+    fn to_table(&self) -> String {
+        match self {
+            CommandOutput::Accounts(rows) => render_table(
+                &["ID", "NAME", "PROVIDER", "CREDENTIAL", "CREATED"],
+                rows.iter().map(|row| {
+                    vec![
+                        row.id.to_string(),
+                        row.name.clone(),
+                        row.provider.clone(),
+                        row.credential_type.clone(),
+                        row.created_at.clone(),
+                    ]
+                }),
+            ),
+            CommandOutput::Domains(rows) => render_table(
+                &["ID", "NAME", "ACCOUNT", "STATUS"],
+                rows.iter().map(|row| {
+                    vec![
+                        row.id.to_string(),
+                        row.name.clone(),
+                        row.account_id.to_string(),
+                        row.status.clone(),
+                    ]
+                }),
+            ),
+            CommandOutput::Records(rows) => {
+                render_table(RECORD_HEADERS, rows.iter().map(record_cells))
+            }
+            CommandOutput::Record(row) => {
+                render_table(RECORD_HEADERS, std::iter::once(record_cells(row)))
+            }
+            CommandOutput::Sync(rows) => render_table(
+                &["ACCOUNT", "DOMAIN", "RECORDS", "RESULT"],
+                rows.iter().map(|row| {
+                    vec![
+                        row.account.clone(),
+                        row.domain.clone(),
+                        row.records.to_string(),
+                        row.error.clone().unwrap_or_else(|| "ok".to_string()),
+                    ]
+                }),
+            ),
+            CommandOutput::Zone(export) => match &export.path {
+                Some(path) => format!("已导出 {} 条记录到 {}", export.records, path),
+                None => export.content.trim_end().to_string(),
+            },
+            CommandOutput::Changes(rows) if rows.iter().any(|row| row.domain.is_some()) => {
+                render_table(
+                    &["DOMAIN", "ACTION", "RR", "TYPE", "VALUE", "TTL", "NOTE"],
+                    rows.iter().map(|row| {
+                        let mut cells = change_cells(row);
+                        cells.insert(0, row.domain.clone().unwrap_or_default());
+                        cells
+                    }),
+                )
+            }
+            CommandOutput::Changes(rows) => render_table(
+                &["ACTION", "RR", "TYPE", "VALUE", "TTL", "NOTE"],
+                rows.iter().map(change_cells),
+            ),
+            CommandOutput::Migration(migration) => {
+                let mut table = render_table(
+                    &["STATUS", "RR", "TYPE", "VALUE", "TTL", "NOTE"],
+                    migration.records.iter().map(|row| {
+                        let notes: Vec<&str> = row
+                            .error
+                            .iter()
+                            .chain(&row.warnings)
+                            .map(String::as_str)
+                            .collect();
+                        vec![
+                            row.status.clone(),
+                            row.rr.clone(),
+                            row.record_type.clone(),
+                            row.value.clone(),
+                            row.ttl.map(|ttl| ttl.to_string()).unwrap_or_default(),
+                            notes.join("; "),
+                        ]
+                    }),
+                );
+                if let Some(id) = &migration.id {
+                    table.push_str(&format!("\n\n迁移ID: {}", id));
+                }
+                if !migration.nameservers.is_empty() {
+                    table.push_str(&format!(
+                        "\n请到注册商处将 {} 的 NS 修改为: {}",
+                        migration.domain,
+                        migration.nameservers.join(", ")
+                    ));
+                }
+                table
+            }
+            CommandOutput::Certificates(rows) => render_table(
+                &["ID", "NAMES", "NOT BEFORE", "NOT AFTER", "DIRECTORY"],
+                rows.iter().map(|row| {
+                    vec![
+                        row.id.to_string(),
+                        row.names.join(","),
+                        row.not_before.clone(),
+                        row.not_after.clone(),
+                        row.directory_url.clone(),
+                    ]
+                }),
+            ),
+            CommandOutput::CertificateExport(export) => format!(
+                "已导出证书 {} 到 {}，私钥到 {}",
+                export.id, export.certificate_path, export.key_path
+            ),
+            CommandOutput::CertificateChecks(rows) => render_table(
+                &["TARGET", "AGENT", "SUBJECT", "NOT AFTER", "DAYS", "ISSUES"],
+                rows.iter().map(|row| {
+                    let issues = match &row.error {
+                        Some(error) => error.clone(),
+                        None => row.issues.join(","),
+                    };
+                    vec![
+                        row.target.clone(),
+                        row.agent_id.clone().unwrap_or_default(),
+                        row.subject.clone().unwrap_or_default(),
+                        row.not_after.clone().unwrap_or_default(),
+                        row.days_left
+                            .map(|days| days.to_string())
+                            .unwrap_or_default(),
+                        issues,
+                    ]
+                }),
+            ),
+            CommandOutput::DomainExpiry(rows) => render_table(
+                &["NAME", "REGISTRAR", "EXPIRES", "DAYS", "REMINDER", "SOURCE"],
+                rows.iter().map(|row| {
+                    vec![
+                        row.name.clone(),
+                        row.registrar.clone().unwrap_or_default(),
+                        row.expiration_date.clone().unwrap_or_default(),
+                        row.days_left
+                            .map(|days| days.to_string())
+                            .unwrap_or_default(),
+                        row.reminder
+                            .map(|days| format!("{}d", days))
+                            .unwrap_or_default(),
+                        row.error
+                            .clone()
+                            .or_else(|| row.source.clone())
+                            .unwrap_or_default(),
+                    ]
+                }),
+            ),
+            CommandOutput::IngressRoutes(rows) => render_table(
+                &["ID", "HOSTNAME", "AGENT", "TARGET", "TLS", "DNS"],
+                rows.iter().map(|row| {
+                    vec![
+                        row.id.clone(),
+                        row.hostname.clone(),
+                        row.agent_id.clone(),
+                        row.target.clone(),
+                        row.tls.to_string(),
+                        row.dns_record.clone().unwrap_or_default(),
+                    ]
+                }),
+            ),
+        }
+    }
+}
+
+fn change_cells(row: &ChangeRow) -> Vec<String> {
+    vec![
+        row.action.clone(),
+        row.rr.clone(),
+        row.record_type.clone(),
+        row.value.clone(),
+        row.ttl.map(|ttl| ttl.to_string()).unwrap_or_default(),
+        row.error
+            .clone()
+            .or_else(|| row.note.clone())
+            .unwrap_or_default(),
+    ]
+}
+
+const RECORD_HEADERS: &[&str] = &["ID", "RR", "TYPE", "VALUE", "TTL", "ENABLED"];
+
+fn record_cells(row: &RecordRow) -> Vec<String> {
+    vec![
+        row.id.clone(),
+        row.rr.clone(),
+        row.record_type.clone(),
+        row.value.clone(),
+        row.ttl.to_string(),
+        row.enabled.to_string(),
+    ]
+}
+
+/// 渲染左对齐的纯文本表格，列之间以两个空格分隔
+fn render_table(headers: &[&str], rows: impl Iterator<Item = Vec<String>>) -> String {
+    let rows: Vec<Vec<String>> = rows.collect();
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let format_line = |cells: Vec<&str>| {
+        let line: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| {
+                let padding = width - cell.chars().count();
+                format!("{}{}", cell, " ".repeat(padding))
+            })
+            .collect();
+        line.join("  ").trim_end().to_string()
+    };
+
+    let mut lines = vec![format_line(headers.to_vec())];
+    lines.extend(
+        rows.iter()
+            .map(|row| format_line(row.iter().map(String::as_str).collect())),
+    );
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record_row(id: &str, rr: &str, value: &str) -> RecordRow {
+        RecordRow {
+            id: id.to_string(),
+            rr: rr.to_string(),
+            record_type: "A".to_string(),
+            value: value.to_string(),
+            ttl: 600,
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn test_render_table() {
+        let output = CommandOutput::Records(vec![
+            record_row("1", "www", "192.0.2.1"),
+            record_row("1024", "@", "192.0.2.100"),
+        ]);
+        assert_eq!(
+            output.render(OutputFormat::Table),
+            "ID    RR   TYPE  VALUE        TTL  ENABLED\n\
+             1     www  A     192.0.2.1    600  true\n\
+             1024  @    A     192.0.2.100  600  true"
+        );
+    }
+
+    #[test]
+    fn test_render_json_and_exit_code() {
+        let output = CommandOutput::Records(vec![record_row("1", "www", "192.0.2.1")]);
+        let json: serde_json::Value =
+            serde_json::from_str(&output.render(OutputFormat::Json)).unwrap();
+        assert_eq!(json[0]["type"], "A");
+        assert_eq!(json[0]["value"], "192.0.2.1");
+        assert_eq!(output.exit_code(), exit_code::SUCCESS);
+
+        let output = CommandOutput::Sync(vec![SyncRow {
+            account: "ops".to_string(),
+            domain: "example.com".to_string(),
+            records: 0,
+            error: Some("timeout".to_string()),
+        }]);
+        assert_eq!(output.exit_code(), exit_code::PROVIDER);
+        assert!(output.render(OutputFormat::Table).ends_with("timeout"));
+
+        let now = chrono::Utc::now().naive_utc();
+        let model = certificate_check::Model {
+            id: 1,
+            target: "www.example.com:443".to_string(),
+            server_name: "www.example.com".to_string(),
+            agent_id: None,
+            subject: Some("CN=www.example.com".to_string()),
+            issuer: Some("CN=R11".to_string()),
+            sans: "www.example.com,example.com".to_string(),
+            not_before: Some(now - chrono::Duration::days(80)),
+            not_after: Some(now + chrono::Duration::days(10) + chrono::Duration::hours(1)),
+            fingerprint: None,
+            chain_length: 2,
+            issues: String::new(),
+            error: None,
+            checked_at: now,
+        };
+        let row = CertificateCheckRow::from(&model);
+        assert_eq!(row.sans, vec!["www.example.com", "example.com"]);
+        assert_eq!(row.days_left, Some(10));
+        assert!(row.issues.is_empty());
+        let output = CommandOutput::CertificateChecks(vec![row.clone()]);
+        assert_eq!(output.exit_code(), exit_code::SUCCESS);
+
+        let output = CommandOutput::CertificateChecks(vec![CertificateCheckRow {
+            issues: vec!["expiring_soon".to_string()],
+            ..row
+        }]);
+        assert_eq!(output.exit_code(), exit_code::CERTIFICATE);
+        assert!(output
+            .render(OutputFormat::Table)
+            .ends_with("expiring_soon"));
+    }
+}
@@ -18,8 +18,14 @@ pub struct AgentManagementClient {
 
 impl AgentManagementClient {
     /// Create a new client connected to the specified endpoint.
-    pub async fn new(endpoint: impl Into<String>) -> Result<Self> {
-        let config = Config::new(endpoint);
+    ///
+    /// The token is the bearer token from a login to the agent management
+    /// service, requests without one are rejected.
+    pub async fn new(endpoint: impl Into<String>, token: Option<String>) -> Result<Self> {
+        let mut config = Config::new(endpoint);
+        if let Some(token) = token {
+            config = config.with_token(token);
+        }
         let inner = InnerClient::connect(config).await?;
         Ok(Self { inner })
     }
@@ -60,4 +66,27 @@ impl AgentManagementClient {
     ) -> Result<proto::Agent> {
         self.inner.deny_agent(agent_id, reason, denied_by).await
     }
+
+    /// Route a hostname to a target behind an agent.
+    pub async fn create_ingress_route(
+        &mut self,
+        hostname: &str,
+        agent_id: &str,
+        target: &str,
+        tls: bool,
+    ) -> Result<proto::IngressRoute> {
+        self.inner
+            .create_ingress_route(hostname, agent_id, target, tls)
+            .await
+    }
+
+    /// List all ingress routes.
+    pub async fn list_ingress_routes(&mut self) -> Result<Vec<proto::IngressRoute>> {
+        Ok(self.inner.list_ingress_routes().await?.routes)
+    }
+
+    /// Delete an ingress route.
+    pub async fn delete_ingress_route(&mut self, route_id: &str) -> Result<()> {
+        self.inner.delete_ingress_route(route_id).await
+    }
 }
@@ -1,5 +1,7 @@
+use crate::expiry::ReminderConfig;
 use crate::gui::model::domain::Domain;
 use crate::gui::styles::types::gradient_type::GradientType;
+use crate::propagation::PropagationConfig;
 use crate::translations::types::language::Language;
 use crate::translations::types::locale::Locale;
 use crate::{StyleType, DOMAIN_MANAGER_LOWERCASE, VERSION};
@@ -109,6 +111,12 @@ pub struct Config {
     /// 背景配置
     #[serde(default)]
     pub background_config: BackgroundConfig,
+    /// 域名到期提醒配置
+    #[serde(default)]
+    pub reminder_config: ReminderConfig,
+    /// 解析生效检查配置
+    #[serde(default)]
+    pub propagation_config: PropagationConfig,
 }
 
 impl From<String> for Config {
@@ -132,6 +140,8 @@ impl From<String> for Config {
             logging_config: None,
             window_state: WindowState::default(),
             background_config: BackgroundConfig::default(),
+            reminder_config: ReminderConfig::default(),
+            propagation_config: PropagationConfig::default(),
         }
     }
 }
@@ -162,6 +172,8 @@ impl Default for Config {
             logging_config: Some("INFO".into()),
             window_state: WindowState::default(),
             background_config: BackgroundConfig::default(),
+            reminder_config: ReminderConfig::default(),
+            propagation_config: PropagationConfig::default(),
         }
     }
 }
@@ -82,6 +82,19 @@ pub fn get_database_path() -> PathBuf {
     PathBuf::from(DB_FILE_NAME)
 }
 
+/// 获取域名区域文件的默认导出路径
+pub fn get_zone_file_path(domain_name: &str) -> PathBuf {
+    let file_name = format!("{}.zone", domain_name.trim_end_matches('.'));
+    if let Some(proj_dirs) = ProjectDirs::from("xyz", "stanic", "DomainManager") {
+        let mut path = proj_dirs.data_dir().to_path_buf();
+        path.push("zones");
+        path.push(file_name);
+        return path;
+    }
+
+    PathBuf::from(file_name)
+}
+
 impl From<&DatabaseConfig> for String {
     fn from(value: &DatabaseConfig) -> Self {
         let encoded_password = utf8_percent_encode(value.password(), NON_ALPHANUMERIC);
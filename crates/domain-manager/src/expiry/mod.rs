@@ -0,0 +1,459 @@
+//! 域名到期提醒
+//!
+//! 优先通过服务商接口（阿里云 `QueryDomainByDomainName`）查询域名的注册时间、到期时间和注册商，
+//! 服务商不提供时依次使用 RDAP 和 WHOIS，结果保存到 `domains` 表。
+//!
+//! 到达提醒阈值（默认到期前 30、7、1 天）的域名在首页展示，并发送到配置的 Webhook，
+//! 每个阈值只发送一次，到期时间变化（如已续费）后重新计算。
+
+pub mod rdap;
+pub mod whois;
+
+use crate::api::provider::{create_dns_client_for_account, BoxedDnsClient};
+use crate::models::domain::{DomainEntity, DomainRegistration};
+use crate::storage::{accounts, domains};
+use anyhow::{anyhow, Result};
+use chrono::{NaiveDateTime, Utc};
+use futures_util::future::join_all;
+use reqwest::Client;
+use sea_orm::DatabaseConnection;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// 默认的提醒阈值（到期前天数）
+pub const DEFAULT_REMINDER_DAYS: [i64; 3] = [30, 7, 1];
+
+/// 默认的查询超时
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 注册信息的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpirySource {
+    /// 服务商接口
+    Provider,
+    Rdap,
+    Whois,
+}
+
+impl ExpirySource {
+    /// JSON 输出中使用的名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExpirySource::Provider => "provider",
+            ExpirySource::Rdap => "rdap",
+            ExpirySource::Whois => "whois",
+        }
+    }
+}
+
+impl fmt::Display for ExpirySource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExpirySource::Provider => write!(f, "服务商"),
+            ExpirySource::Rdap => write!(f, "RDAP"),
+            ExpirySource::Whois => write!(f, "WHOIS"),
+        }
+    }
+}
+
+/// 查询选项
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LookupOptions {
+    /// RDAP 服务地址
+    pub rdap_url: String,
+    /// WHOIS 服务器，为空时通过 IANA 查找
+    pub whois_server: Option<String>,
+    /// 单次查询超时
+    pub timeout: Duration,
+}
+
+impl Default for LookupOptions {
+    fn default() -> Self {
+        Self {
+            rdap_url: rdap::DEFAULT_RDAP_URL.to_string(),
+            whois_server: None,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+}
+
+/// 一个域名的查询结果
+#[derive(Debug, Clone)]
+pub struct ExpiryLookup {
+    pub domain_id: i64,
+    pub domain_name: String,
+    pub result: Result<(DomainRegistration, ExpirySource), String>,
+}
+
+/// 依次通过服务商接口、RDAP 和 WHOIS 查询注册信息，直到查到到期时间
+pub async fn lookup(
+    client: Option<&BoxedDnsClient>,
+    domain_name: &str,
+    options: &LookupOptions,
+) -> Result<(DomainRegistration, ExpirySource)> {
+    let mut errors = Vec::new();
+
+    if let Some(client) = client {
+        match client.query_registration(domain_name).await {
+            Ok(Some(registration)) if registration.expiration_date.is_some() => {
+                return Ok((registration, ExpirySource::Provider));
+            }
+            Ok(_) => {}
+            Err(e) => errors.push(format!("服务商: {:#}", e)),
+        }
+    }
+
+    let http = Client::builder()
+        .timeout(options.timeout)
+        .build()
+        .map_err(|e| anyhow!("创建 HTTP 客户端失败: {}", e))?;
+    match rdap::query(&http, &options.rdap_url, domain_name).await {
+        Ok(registration) if registration.expiration_date.is_some() => {
+            return Ok((registration, ExpirySource::Rdap));
+        }
+        Ok(_) => errors.push("RDAP: 没有到期时间".to_string()),
+        Err(e) => errors.push(format!("RDAP: {}", e)),
+    }
+
+    match whois::query(
+        options.whois_server.as_deref(),
+        domain_name,
+        options.timeout,
+    )
+    .await
+    {
+        Ok(registration) if registration.expiration_date.is_some() => {
+            Ok((registration, ExpirySource::Whois))
+        }
+        Ok(_) => {
+            errors.push("WHOIS: 没有到期时间".to_string());
+            Err(anyhow!(errors.join("；")))
+        }
+        Err(e) => {
+            errors.push(format!("WHOIS: {}", e));
+            Err(anyhow!(errors.join("；")))
+        }
+    }
+}
+
+/// 查询所有本地保存的域名的注册信息并保存，返回每个域名的查询结果
+pub async fn refresh(
+    conn: &DatabaseConnection,
+    options: &LookupOptions,
+) -> Result<Vec<ExpiryLookup>> {
+    let domain_list = domains::list_domains(conn)
+        .await
+        .map_err(|e| anyhow!("查询域名失败: {}", e))?;
+    let accounts = accounts::list_accounts(conn)
+        .await
+        .map_err(|e| anyhow!("查询账户失败: {}", e))?;
+
+    let mut clients: HashMap<i64, BoxedDnsClient> = HashMap::new();
+    for account in accounts {
+        let account_id = account.id;
+        match create_dns_client_for_account(account) {
+            Ok((_, client)) => {
+                clients.insert(account_id, client);
+            }
+            Err(e) => warn!(
+                "创建账户 {} 的客户端失败，只使用 RDAP/WHOIS: {:#}",
+                account_id, e
+            ),
+        }
+    }
+
+    let lookups = join_all(domain_list.iter().map(|domain| {
+        let client = clients.get(&domain.account_id);
+        async move {
+            ExpiryLookup {
+                domain_id: domain.id,
+                domain_name: domain.domain_name.clone(),
+                result: lookup(client, &domain.domain_name, options)
+                    .await
+                    .map_err(|e| e.to_string()),
+            }
+        }
+    }))
+    .await;
+
+    for lookup in &lookups {
+        match &lookup.result {
+            Ok((registration, source)) => {
+                domains::update_domain_registration(conn, lookup.domain_id, registration)
+                    .await
+                    .map_err(|e| {
+                        anyhow!("保存域名 {} 的注册信息失败: {}", lookup.domain_name, e)
+                    })?;
+                info!(
+                    "域名 {} 的到期时间：{:?}（来源：{}）",
+                    lookup.domain_name, registration.expiration_date, source
+                );
+            }
+            Err(e) => warn!("查询域名 {} 的注册信息失败: {}", lookup.domain_name, e),
+        }
+    }
+    Ok(lookups)
+}
+
+/// 到期提醒配置
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ReminderConfig {
+    /// 提醒阈值（到期前天数）
+    #[serde(default = "default_reminder_days")]
+    pub days: Vec<i64>,
+    /// 接收提醒的 Webhook 地址，以 JSON POST 发送
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+}
+
+fn default_reminder_days() -> Vec<i64> {
+    DEFAULT_REMINDER_DAYS.to_vec()
+}
+
+impl Default for ReminderConfig {
+    fn default() -> Self {
+        Self {
+            days: default_reminder_days(),
+            webhook_url: None,
+        }
+    }
+}
+
+impl ReminderConfig {
+    /// 最大的提醒阈值，只需要查询这个天数内到期的域名
+    pub fn max_days(&self) -> i64 {
+        self.days.iter().copied().max().unwrap_or(0)
+    }
+
+    /// 剩余天数对应的提醒阈值：不小于剩余天数的最小阈值，已过期的域名对应最小阈值
+    pub fn threshold(&self, days_left: i64) -> Option<i64> {
+        self.days
+            .iter()
+            .copied()
+            .filter(|days| days_left <= *days)
+            .min()
+    }
+}
+
+/// 解析逗号分隔的提醒阈值，如 `30,7,1`
+pub fn parse_reminder_days(input: &str) -> Result<Vec<i64>, String> {
+    let mut days = input
+        .split([',', '，', ' '])
+        .filter(|value| !value.is_empty())
+        .map(|value| match value.parse::<i64>() {
+            Ok(days) if days > 0 => Ok(days),
+            _ => Err(format!("提醒天数必须是正整数: {}", value)),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if days.is_empty() {
+        return Err("至少需要一个提醒天数".to_string());
+    }
+    days.sort_unstable_by(|a, b| b.cmp(a));
+    days.dedup();
+    Ok(days)
+}
+
+/// 到达提醒阈值的域名
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Reminder {
+    pub domain_id: i64,
+    pub domain_name: String,
+    pub expiration_date: NaiveDateTime,
+    /// 剩余天数，已过期为负数
+    pub days_left: i64,
+    /// 对应的提醒阈值
+    pub threshold: i64,
+    /// 这个阈值的提醒是否已经发送过
+    pub notified: bool,
+}
+
+impl Reminder {
+    /// 提醒内容
+    pub fn message(&self) -> String {
+        match self.expiration_date <= Utc::now().naive_utc() {
+            true => format!(
+                "域名 {} 已于 {} 到期，请尽快续费",
+                self.domain_name,
+                self.expiration_date.format("%Y-%m-%d")
+            ),
+            false => format!(
+                "域名 {} 将在 {} 天后（{}）到期",
+                self.domain_name,
+                self.days_left,
+                self.expiration_date.format("%Y-%m-%d")
+            ),
+        }
+    }
+}
+
+/// 计算域名的提醒，按到期时间排序
+pub fn reminders(
+    domain_list: &[DomainEntity],
+    config: &ReminderConfig,
+    now: NaiveDateTime,
+) -> Vec<Reminder> {
+    let mut reminders: Vec<Reminder> = domain_list
+        .iter()
+        .filter_map(|domain| {
+            let expiration_date = domain.expiration_date?;
+            let days_left = (expiration_date - now).num_days();
+            let threshold = config.threshold(days_left)?;
+            Some(Reminder {
+                domain_id: domain.id,
+                domain_name: domain.domain_name.clone(),
+                expiration_date,
+                days_left,
+                threshold,
+                notified: domain
+                    .reminded_days
+                    .is_some_and(|reminded| i64::from(reminded) <= threshold),
+            })
+        })
+        .collect();
+    reminders.sort_by_key(|reminder| reminder.expiration_date);
+    reminders
+}
+
+/// 查询本地保存的到期时间，返回当前需要提醒的域名
+pub async fn due_reminders(
+    conn: &DatabaseConnection,
+    config: &ReminderConfig,
+) -> Result<Vec<Reminder>> {
+    let domain_list = domains::get_expiring_domains(conn, config.max_days())
+        .await
+        .map_err(|e| anyhow!("查询即将到期的域名失败: {}", e))?;
+    Ok(reminders(&domain_list, config, Utc::now().naive_utc()))
+}
+
+/// 把还没发送过的提醒发送到 Webhook，返回发送成功的数量
+pub async fn notify(
+    conn: &DatabaseConnection,
+    config: &ReminderConfig,
+    reminders: &[Reminder],
+) -> Result<usize> {
+    let Some(webhook_url) = &config.webhook_url else {
+        return Ok(0);
+    };
+    let client = Client::builder()
+        .timeout(DEFAULT_TIMEOUT)
+        .build()
+        .map_err(|e| anyhow!("创建 HTTP 客户端失败: {}", e))?;
+
+    let mut sent = 0;
+    for reminder in reminders.iter().filter(|reminder| !reminder.notified) {
+        let payload = json!({
+            "text": reminder.message(),
+            "domain": reminder.domain_name,
+            "expiration_date": reminder.expiration_date.format("%Y-%m-%d %H:%M:%S").to_string(),
+            "days_left": reminder.days_left,
+            "threshold": reminder.threshold,
+        });
+        let response = client
+            .post(webhook_url)
+            .json(&payload)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        match response {
+            Ok(_) => {
+                domains::set_reminded_days(
+                    conn,
+                    reminder.domain_id,
+                    Some(reminder.threshold as i32),
+                )
+                .await
+                .map_err(|e| anyhow!("记录域名到期提醒失败: {}", e))?;
+                sent += 1;
+            }
+            Err(e) => warn!("发送域名 {} 的到期提醒失败: {}", reminder.domain_name, e),
+        }
+    }
+    info!("已发送 {} 条域名到期提醒", sent);
+    Ok(sent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::domain::DomainStatus;
+    use chrono::Duration as ChronoDuration;
+
+    fn domain(
+        id: i64,
+        name: &str,
+        expires_in: Option<i64>,
+        reminded_days: Option<i32>,
+    ) -> DomainEntity {
+        let now = Utc::now().naive_utc();
+        DomainEntity {
+            id,
+            account_id: 1,
+            domain_name: name.to_string(),
+            registration_date: None,
+            expiration_date: expires_in.map(|days| now + ChronoDuration::hours(days * 24 + 1)),
+            registrar: None,
+            status: DomainStatus::Active,
+            created_at: now.to_string(),
+            updated_at: None,
+            reminded_days,
+        }
+    }
+
+    #[test]
+    fn test_threshold() {
+        let config = ReminderConfig::default();
+        assert_eq!(config.max_days(), 30);
+        assert_eq!(config.threshold(45), None);
+        assert_eq!(config.threshold(30), Some(30));
+        assert_eq!(config.threshold(12), Some(30));
+        assert_eq!(config.threshold(5), Some(7));
+        assert_eq!(config.threshold(0), Some(1));
+        assert_eq!(config.threshold(-3), Some(1));
+    }
+
+    #[test]
+    fn test_reminders() {
+        let config = ReminderConfig::default();
+        let domain_list = vec![
+            domain(1, "later.com", Some(90), None),
+            domain(2, "soon.com", Some(5), Some(30)),
+            domain(3, "month.com", Some(20), Some(30)),
+            domain(4, "unknown.com", None, None),
+            domain(5, "expired.com", Some(-2), None),
+        ];
+        let reminders = reminders(&domain_list, &config, Utc::now().naive_utc());
+        let summary: Vec<(&str, i64, i64, bool)> = reminders
+            .iter()
+            .map(|reminder| {
+                (
+                    reminder.domain_name.as_str(),
+                    reminder.days_left,
+                    reminder.threshold,
+                    reminder.notified,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("expired.com", -1, 1, false),
+                ("soon.com", 5, 7, false),
+                ("month.com", 20, 30, true),
+            ]
+        );
+        assert!(reminders[0].message().contains("已于"));
+        assert!(reminders[1].message().contains("5 天后"));
+    }
+
+    #[test]
+    fn test_parse_reminder_days() {
+        assert_eq!(parse_reminder_days("30,7,1"), Ok(vec![30, 7, 1]));
+        assert_eq!(parse_reminder_days("1， 7,30,7"), Ok(vec![30, 7, 1]));
+        assert!(parse_reminder_days("30,0").is_err());
+        assert!(parse_reminder_days("").is_err());
+    }
+}
@@ -0,0 +1,122 @@
+//! RDAP（RFC 9083）查询
+//!
+//! 默认通过 rdap.org 重定向到顶级域的权威 RDAP 服务器，从 `events` 中读取注册和到期时间，
+//! 从角色为 `registrar` 的实体的 vCard 中读取注册商名称。
+
+use crate::models::domain::DomainRegistration;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, NaiveDateTime};
+use reqwest::{Client, StatusCode};
+use serde_json::Value;
+
+/// 默认的 RDAP 服务地址
+pub const DEFAULT_RDAP_URL: &str = "https://rdap.org";
+
+/// 查询域名的注册信息
+pub async fn query(
+    client: &Client,
+    base_url: &str,
+    domain_name: &str,
+) -> Result<DomainRegistration> {
+    let url = format!("{}/domain/{}", base_url.trim_end_matches('/'), domain_name);
+    let response = client
+        .get(&url)
+        .header(reqwest::header::ACCEPT, "application/rdap+json")
+        .send()
+        .await
+        .map_err(|e| anyhow!("请求 RDAP 服务失败: {}", e))?;
+    match response.status() {
+        StatusCode::NOT_FOUND => Err(anyhow!("RDAP 没有域名 {} 的注册信息", domain_name)),
+        status if !status.is_success() => Err(anyhow!("RDAP 服务返回 HTTP {}", status)),
+        _ => {
+            let body: Value = response
+                .json()
+                .await
+                .map_err(|e| anyhow!("解析 RDAP 响应失败: {}", e))?;
+            Ok(parse_response(&body))
+        }
+    }
+}
+
+/// 解析 RDAP 域名查询结果
+pub fn parse_response(body: &Value) -> DomainRegistration {
+    let event_date = |action: &str| -> Option<NaiveDateTime> {
+        body.get("events")?
+            .as_array()?
+            .iter()
+            .find(|event| event.get("eventAction").and_then(Value::as_str) == Some(action))?
+            .get("eventDate")?
+            .as_str()
+            .and_then(|date| DateTime::parse_from_rfc3339(date).ok())
+            .map(|date| date.naive_utc())
+    };
+    DomainRegistration {
+        registration_date: event_date("registration"),
+        expiration_date: event_date("expiration"),
+        registrar: registrar_name(body),
+    }
+}
+
+/// 角色为 `registrar` 的实体的 vCard `fn` 字段
+fn registrar_name(body: &Value) -> Option<String> {
+    body.get("entities")?
+        .as_array()?
+        .iter()
+        .find(|entity| {
+            entity
+                .get("roles")
+                .and_then(Value::as_array)
+                .is_some_and(|roles| roles.iter().any(|role| role == "registrar"))
+        })?
+        .get("vcardArray")?
+        .get(1)?
+        .as_array()?
+        .iter()
+        .find(|property| property.get(0).and_then(Value::as_str) == Some("fn"))?
+        .get(3)?
+        .as_str()
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_response() {
+        let body = json!({
+            "objectClassName": "domain",
+            "ldhName": "EXAMPLE.COM",
+            "events": [
+                {"eventAction": "registration", "eventDate": "1995-08-14T04:00:00Z"},
+                {"eventAction": "expiration", "eventDate": "2026-08-13T04:00:00Z"},
+                {"eventAction": "last update of RDAP database", "eventDate": "2026-10-18T00:00:00Z"}
+            ],
+            "entities": [{
+                "objectClassName": "entity",
+                "roles": ["registrar"],
+                "vcardArray": ["vcard", [
+                    ["version", {}, "text", "4.0"],
+                    ["fn", {}, "text", "RESERVED-Internet Assigned Numbers Authority"]
+                ]]
+            }]
+        });
+        let registration = parse_response(&body);
+        assert_eq!(
+            registration.registration_date,
+            NaiveDate::from_ymd_opt(1995, 8, 14).and_then(|date| date.and_hms_opt(4, 0, 0))
+        );
+        assert_eq!(
+            registration.expiration_date,
+            NaiveDate::from_ymd_opt(2026, 8, 13).and_then(|date| date.and_hms_opt(4, 0, 0))
+        );
+        assert_eq!(
+            registration.registrar.as_deref(),
+            Some("RESERVED-Internet Assigned Numbers Authority")
+        );
+
+        assert_eq!(parse_response(&json!({})), DomainRegistration::default());
+    }
+}
@@ -0,0 +1,187 @@
+//! WHOIS（RFC 3912）查询
+//!
+//! 用于没有 RDAP 服务的顶级域（如 `.cn`）。未指定服务器时先向 IANA 查询顶级域的 WHOIS
+//! 服务器，再查询域名。各注册局的返回格式不统一，按常见的字段名读取注册和到期时间。
+
+use crate::models::domain::DomainRegistration;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// 查询顶级域 WHOIS 服务器使用的 IANA 服务器
+pub const IANA_WHOIS_SERVER: &str = "whois.iana.org:43";
+
+/// WHOIS 默认端口
+const WHOIS_PORT: u16 = 43;
+
+/// 注册时间字段
+const REGISTRATION_KEYS: &[&str] = &[
+    "creation date",
+    "created",
+    "registration time",
+    "registered on",
+    "domain registration date",
+];
+
+/// 到期时间字段
+const EXPIRATION_KEYS: &[&str] = &[
+    "registry expiry date",
+    "registrar registration expiration date",
+    "expiration time",
+    "expiration date",
+    "expiry date",
+    "expires on",
+    "paid-till",
+];
+
+/// 注册商字段
+const REGISTRAR_KEYS: &[&str] = &["registrar", "sponsoring registrar"];
+
+/// 查询域名的注册信息，`server` 为空时通过 IANA 查找顶级域的 WHOIS 服务器
+pub async fn query(
+    server: Option<&str>,
+    domain_name: &str,
+    timeout: Duration,
+) -> Result<DomainRegistration> {
+    let server = match server {
+        Some(server) => server.to_string(),
+        None => {
+            let tld = domain_name.rsplit('.').next().unwrap_or(domain_name);
+            let response = request(IANA_WHOIS_SERVER, tld, timeout).await?;
+            referral_server(&response)
+                .ok_or_else(|| anyhow!("IANA 没有顶级域 .{} 的 WHOIS 服务器", tld))?
+        }
+    };
+    let response = request(&server, domain_name, timeout).await?;
+    Ok(parse_response(&response))
+}
+
+/// 发送一次 WHOIS 查询，读取服务器关闭连接前返回的全部内容
+async fn request(server: &str, query: &str, timeout: Duration) -> Result<String> {
+    let address = match server.contains(':') {
+        true => server.to_string(),
+        false => format!("{}:{}", server, WHOIS_PORT),
+    };
+    let exchange = async {
+        let mut stream = TcpStream::connect(&address).await?;
+        stream
+            .write_all(format!("{}\r\n", query).as_bytes())
+            .await?;
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response).await?;
+        Ok::<_, std::io::Error>(response)
+    };
+    let response = tokio::time::timeout(timeout, exchange)
+        .await
+        .map_err(|_| anyhow!("查询 WHOIS 服务器 {} 超时", address))?
+        .map_err(|e| anyhow!("查询 WHOIS 服务器 {} 失败: {}", address, e))?;
+    Ok(String::from_utf8_lossy(&response).into_owned())
+}
+
+/// IANA 返回结果中的 `refer` 或 `whois` 字段
+fn referral_server(response: &str) -> Option<String> {
+    fields(response)
+        .find(|(key, _)| key == "refer" || key == "whois")
+        .map(|(_, value)| value.to_string())
+}
+
+/// 解析 WHOIS 返回结果，每个字段取第一次出现的值
+pub fn parse_response(response: &str) -> DomainRegistration {
+    let find = |keys: &[&str]| {
+        fields(response)
+            .find(|(key, value)| keys.contains(&key.as_str()) && !value.is_empty())
+            .map(|(_, value)| value)
+    };
+    DomainRegistration {
+        registration_date: find(REGISTRATION_KEYS).and_then(parse_date),
+        expiration_date: find(EXPIRATION_KEYS).and_then(parse_date),
+        registrar: find(REGISTRAR_KEYS).map(str::to_string),
+    }
+}
+
+/// `字段: 值` 形式的行，字段名转为小写，跳过注释
+fn fields(response: &str) -> impl Iterator<Item = (String, &str)> {
+    response
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('%') && !line.starts_with('#'))
+        .filter_map(|line| line.split_once(':'))
+        .map(|(key, value)| (key.trim().to_ascii_lowercase(), value.trim()))
+}
+
+/// 常见的 WHOIS 日期格式，不带时区的按 UTC 处理
+fn parse_date(value: &str) -> Option<NaiveDateTime> {
+    if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+        return Some(date.naive_utc());
+    }
+    [
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y.%m.%d %H:%M:%S",
+    ]
+    .iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+    .or_else(|| {
+        ["%Y-%m-%d", "%Y.%m.%d", "%d-%b-%Y", "%d.%m.%Y"]
+            .iter()
+            .find_map(|format| NaiveDate::parse_from_str(value, format).ok())
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cnnic_response() {
+        let response = "Domain Name: example.cn\r\n\
+            ROID: 20030311s10001s00033735-cn\r\n\
+            Domain Status: ok\r\n\
+            Registrant: 示例公司\r\n\
+            Sponsoring Registrar: 阿里云计算有限公司（万网）\r\n\
+            Name Server: dns1.example.cn\r\n\
+            Registration Time: 2003-03-17 12:20:05\r\n\
+            Expiration Time: 2027-03-17 12:48:36\r\n\
+            DNSSEC: unsigned\r\n";
+        let registration = parse_response(response);
+        assert_eq!(
+            registration.registration_date,
+            NaiveDate::from_ymd_opt(2003, 3, 17).and_then(|date| date.and_hms_opt(12, 20, 5))
+        );
+        assert_eq!(
+            registration.expiration_date,
+            NaiveDate::from_ymd_opt(2027, 3, 17).and_then(|date| date.and_hms_opt(12, 48, 36))
+        );
+        assert_eq!(
+            registration.registrar.as_deref(),
+            Some("阿里云计算有限公司（万网）")
+        );
+    }
+
+    #[test]
+    fn test_parse_gtld_response() {
+        let response = "   Domain Name: EXAMPLE.COM\n\
+            \x20  Registrar WHOIS Server: whois.iana.org\n\
+            \x20  Registrar URL: http://res-dom.iana.org\n\
+            \x20  Creation Date: 1995-08-14T04:00:00Z\n\
+            \x20  Registry Expiry Date: 2026-08-13T04:00:00Z\n\
+            \x20  Registrar: RESERVED-Internet Assigned Numbers Authority\n\
+            >>> Last update of whois database: 2026-10-18T00:00:00Z <<<\n";
+        let registration = parse_response(response);
+        assert_eq!(
+            registration.expiration_date,
+            NaiveDate::from_ymd_opt(2026, 8, 13).and_then(|date| date.and_hms_opt(4, 0, 0))
+        );
+        assert_eq!(
+            registration.registrar.as_deref(),
+            Some("RESERVED-Internet Assigned Numbers Authority")
+        );
+        assert_eq!(
+            referral_server("% IANA WHOIS server\nrefer:        whois.cnnic.cn\n"),
+            Some("whois.cnnic.cn".to_string())
+        );
+    }
+}
@@ -0,0 +1,112 @@
+//! 证书清单面板
+//!
+//! 在首页展示托管主机名和 Agent 上报的证书，按到期时间排序，标出存在问题的证书
+
+use crate::certmon::CertIssue;
+use crate::gui::handlers::message_handler::{CertificateMessage, MessageCategory};
+use crate::gui::state::pages::certificate_state::CertificatePanelState;
+use crate::gui::styles::button::ButtonType;
+use crate::gui::styles::container::ContainerType;
+use crate::gui::styles::text::TextType;
+use crate::storage::entities::certificate_check;
+use crate::StyleType;
+use chrono::Utc;
+use iced::widget::{button, column, container, row, scrollable, text, Space};
+use iced::{Alignment, Element, Length};
+
+/// 证书清单面板
+pub fn certificate_panel(state: &CertificatePanelState) -> Element<'_, MessageCategory, StyleType> {
+    let reload = button(text("刷新")).on_press_maybe(
+        (!state.in_progress).then_some(MessageCategory::Certificate(CertificateMessage::Load)),
+    );
+    let check = button(text("检查托管域名"))
+        .class(ButtonType::Primary)
+        .on_press_maybe((!state.in_progress).then_some(MessageCategory::Certificate(
+            CertificateMessage::CheckManaged,
+        )));
+    let title = match state.problem_count() {
+        0 => format!("证书清单（{}）", state.checks.len()),
+        problems => format!(
+            "证书清单（{}，{} 个需要处理）",
+            state.checks.len(),
+            problems
+        ),
+    };
+
+    let mut content = column![row![
+        text(title).size(16),
+        Space::with_width(Length::Fill),
+        reload,
+        check,
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center)]
+    .spacing(10)
+    .padding(15);
+
+    if state.in_progress {
+        content = content.push(text("检查中...").size(12));
+    }
+    if let Some(message) = &state.message {
+        content = content.push(text(message).size(12).class(TextType::Danger));
+    }
+
+    if state.checks.is_empty() {
+        content = content
+            .push(text("还没有检查结果，点击「检查托管域名」或使用 cert check 命令").size(12));
+    } else {
+        let rows = state.checks.iter().map(check_row);
+        content = content.push(scrollable(column(rows).spacing(4)).height(Length::Fixed(200.0)));
+    }
+
+    container(content)
+        .class(ContainerType::BorderedRound)
+        .width(Length::Fill)
+        .into()
+}
+
+/// 一个目标的检查结果：目标、来源、到期时间和问题
+fn check_row(check: &certificate_check::Model) -> Element<'_, MessageCategory, StyleType> {
+    let source = match &check.agent_id {
+        Some(agent_id) => format!("Agent {}", agent_id),
+        None => "本机".to_string(),
+    };
+    let expiry = match check.not_after {
+        Some(not_after) => {
+            let days = (not_after - Utc::now().naive_utc()).num_days();
+            format!("{}（剩余 {} 天）", not_after.format("%Y-%m-%d"), days)
+        }
+        None => String::new(),
+    };
+    let (status, class) = match &check.error {
+        Some(error) => (error.clone(), TextType::Danger),
+        None if check.issues.is_empty() => ("正常".to_string(), TextType::Success),
+        None => {
+            let issues: Vec<String> = check
+                .issues
+                .split(',')
+                .map(|code| match CertIssue::parse(code) {
+                    Some(issue) => issue.to_string(),
+                    None => code.to_string(),
+                })
+                .collect();
+            let class = match check.issues.split(',').any(|code| code != "expiring_soon") {
+                true => TextType::Danger,
+                false => TextType::Warning,
+            };
+            (issues.join("、"), class)
+        }
+    };
+
+    row![
+        text(&check.target).size(12).width(Length::FillPortion(3)),
+        text(source).size(12).width(Length::FillPortion(2)),
+        text(expiry).size(12).width(Length::FillPortion(2)),
+        text(status)
+            .size(12)
+            .class(class)
+            .width(Length::FillPortion(3)),
+    ]
+    .spacing(10)
+    .into()
+}
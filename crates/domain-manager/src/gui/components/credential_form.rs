@@ -1,9 +1,10 @@
 use crate::gui::types::credential::{
-    ApiKeyCredential, ApiKeyMessage, Credential, CredentialMessage, TokenCredential, TokenMessage,
+    ApiKeyCredential, ApiKeyMessage, Credential, CredentialMessage, ServiceAccountCredential,
+    ServiceAccountMessage, TokenCredential, TokenMessage, TsigCredential, TsigMessage,
     UsernamePasswordCredential, UsernamePasswordMessage,
 };
 use crate::StyleType;
-use iced::widget::{Column, TextInput};
+use iced::widget::{pick_list, Column, TextInput};
 use iced::Element;
 
 // 凭证表单组件的trait
@@ -132,3 +133,89 @@ impl CredentialForm for ApiKeyCredential {
         }
     }
 }
+
+// 服务账号凭证表单实现
+impl CredentialForm for ServiceAccountCredential {
+    fn view(&self) -> Element<'_, CredentialMessage, StyleType> {
+        Column::new()
+            .spacing(10)
+            .push(
+                TextInput::new("服务账号密钥 JSON 或密钥文件路径", &self.key_json)
+                    .on_input(|key| {
+                        CredentialMessage::ServiceAccountChanged(ServiceAccountMessage::KeyChanged(
+                            key,
+                        ))
+                    })
+                    .padding(10),
+            )
+            .into()
+    }
+
+    fn update(&mut self, message: CredentialMessage) -> Option<Credential> {
+        match message {
+            CredentialMessage::ServiceAccountChanged(msg) => {
+                match msg {
+                    ServiceAccountMessage::KeyChanged(key) => self.set_key_input(key),
+                }
+                Some(Credential::ServiceAccount(self.clone()))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// 可选的 TSIG 算法
+const TSIG_ALGORITHMS: [&str; 3] = ["hmac-sha256", "hmac-sha384", "hmac-sha512"];
+
+// TSIG凭证表单实现
+impl CredentialForm for TsigCredential {
+    fn view(&self) -> Element<'_, CredentialMessage, StyleType> {
+        let selected_algorithm = TSIG_ALGORITHMS
+            .into_iter()
+            .find(|algorithm| *algorithm == self.algorithm);
+
+        Column::new()
+            .spacing(10)
+            .push(
+                TextInput::new("服务器地址（例如 192.0.2.53:53）", &self.server)
+                    .on_input(|server| TsigMessage::ServerChanged(server).into())
+                    .padding(10),
+            )
+            .push(
+                TextInput::new("TSIG 密钥名称", &self.key_name)
+                    .on_input(|key_name| TsigMessage::KeyNameChanged(key_name).into())
+                    .padding(10),
+            )
+            .push(
+                pick_list(TSIG_ALGORITHMS, selected_algorithm, |algorithm| {
+                    TsigMessage::AlgorithmChanged(algorithm.to_string()).into()
+                })
+                .placeholder("TSIG 算法")
+                .padding(10),
+            )
+            .push(
+                TextInput::new("TSIG 密钥（Base64）", &self.secret)
+                    .on_input(|secret| TsigMessage::SecretChanged(secret).into())
+                    .padding(10),
+            )
+            .push(
+                TextInput::new(
+                    "区域列表，逗号分隔（例如 example.com,corp.lan）",
+                    &self.zones,
+                )
+                .on_input(|zones| TsigMessage::ZonesChanged(zones).into())
+                .padding(10),
+            )
+            .into()
+    }
+
+    fn update(&mut self, message: CredentialMessage) -> Option<Credential> {
+        match message {
+            CredentialMessage::TsigChanged(msg) => {
+                self.apply(msg);
+                Some(Credential::Tsig(self.clone()))
+            }
+            _ => None,
+        }
+    }
+}
@@ -5,13 +5,16 @@
 use super::{Component, ComponentConfig, State};
 use crate::gui::handlers::message_handler::{DnsMessage, MessageCategory, NavigationMessage};
 use crate::gui::pages::Page;
+use crate::gui::state::pages::migration_state::MigrationTarget;
 use crate::gui::state::AppState;
 use crate::gui::styles::button::ButtonType;
 use crate::gui::styles::container::ContainerType;
 use crate::model::dns_record_response::Type as RecordType;
-use crate::storage::DnsRecordModal;
+use crate::storage::{DnsRecordModal, DomainModal};
 use crate::StyleType;
-use iced::widget::{button, column, container, mouse_area, pick_list, row, text, text_input};
+use iced::widget::{
+    button, column, container, mouse_area, pick_list, row, scrollable, text, text_input,
+};
 use iced::{Alignment, Element, Length, Padding};
 
 /// DNS记录组件
@@ -248,6 +251,254 @@ impl DnsRecordsComponent {
             .into()
     }
 
+    /// 渲染区域文件导入导出面板
+    fn render_zone_panel<'a>(
+        &'a self,
+        state: &'a State,
+    ) -> Element<'a, MessageCategory, StyleType> {
+        let panel = &state.data.zone_panel;
+        let action = |label: &'a str, message: DnsMessage| {
+            button(text(label))
+                .on_press_maybe((!panel.in_progress).then_some(MessageCategory::Dns(message)))
+        };
+
+        let mut content = column![
+            text("区域文件（RFC 1035）").size(14),
+            row![
+                text_input("区域文件路径", &panel.path)
+                    .on_input(|s| MessageCategory::Dns(DnsMessage::ZonePathChanged(s)))
+                    .width(Length::Fill),
+                action("导出", DnsMessage::ZoneExport).class(ButtonType::Standard),
+                action("预览导入", DnsMessage::ZonePreview).class(ButtonType::Standard),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+        ]
+        .spacing(10)
+        .padding(15);
+
+        if panel.in_progress {
+            content = content.push(text("处理中...").size(12));
+        }
+        if let Some(message) = &panel.message {
+            content = content.push(text(message).size(12));
+        }
+
+        if !panel.preview.is_empty() {
+            let items = panel
+                .preview
+                .iter()
+                .map(|change| text(change.describe()).size(12).into());
+            content = content
+                .push(text(format!("导入预览（{} 项）", panel.preview.len())).size(12))
+                .push(scrollable(column(items).spacing(4)).height(Length::Fixed(160.0)));
+            if panel.has_pending_changes() {
+                content = content
+                    .push(action("确认导入", DnsMessage::ZoneImport).class(ButtonType::Primary));
+            }
+        }
+
+        container(content)
+            .class(ContainerType::BorderedRound)
+            .width(Length::Fill)
+            .into()
+    }
+
+    /// 渲染跨服务商迁移面板
+    fn render_migration_panel<'a>(
+        &'a self,
+        state: &'a State,
+        domain: &DomainModal,
+    ) -> Element<'a, MessageCategory, StyleType> {
+        let panel = &state.data.migration_panel;
+        let action = |label: &'a str, message: DnsMessage| {
+            button(text(label)).on_press_maybe(
+                (!panel.in_progress && panel.target.is_some())
+                    .then_some(MessageCategory::Dns(message)),
+            )
+        };
+        let targets: Vec<MigrationTarget> = state
+            .data
+            .provider_page
+            .providers
+            .iter()
+            .filter(|provider| provider.account_id != domain.provider_id)
+            .map(|provider| MigrationTarget {
+                account_id: provider.account_id,
+                name: format!("{}（{}）", provider.provider_name, provider.provider.name()),
+            })
+            .collect();
+
+        let mut content = column![
+            text("迁移到其他服务商").size(14),
+            text("请先在目标服务商添加域名，迁移只复制解析记录，不会修改注册商的 NS").size(12),
+            row![
+                pick_list(targets, panel.target.clone(), |target| {
+                    MessageCategory::Dns(DnsMessage::MigrationTargetSelected(target))
+                })
+                .placeholder("选择目标账户")
+                .width(Length::Fill),
+                action("预览", DnsMessage::MigrationPreview).class(ButtonType::Standard),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+        ]
+        .spacing(10)
+        .padding(15);
+
+        if panel.in_progress {
+            content = content.push(text("处理中...").size(12));
+        }
+        if let Some(message) = &panel.message {
+            content = content.push(text(message).size(12));
+        }
+
+        let items = panel.items();
+        if !items.is_empty() {
+            let title = match &panel.report {
+                Some(report) => format!("迁移报告 {}（{} 条）", report.id, items.len()),
+                None => format!("迁移预览（{} 条）", items.len()),
+            };
+            let lines = items.iter().map(|item| {
+                let mut line = format!(
+                    "[{}] {} {} {}",
+                    item.status.as_str(),
+                    item.rr,
+                    item.record_type,
+                    item.value
+                );
+                for note in item.error.iter().chain(&item.warnings) {
+                    line.push_str(&format!("；{}", note));
+                }
+                text(line).size(12).into()
+            });
+            content = content
+                .push(text(title).size(12))
+                .push(scrollable(column(lines).spacing(4)).height(Length::Fixed(160.0)));
+            if panel.report.is_none() {
+                content = content.push(
+                    action("开始迁移", DnsMessage::MigrationStart).class(ButtonType::Primary),
+                );
+            }
+        }
+
+        container(content)
+            .class(ContainerType::BorderedRound)
+            .width(Length::Fill)
+            .into()
+    }
+
+    /// 渲染解析生效检查面板，每行是一个 DNS 服务器的查询结果
+    fn render_propagation_panel<'a>(
+        &'a self,
+        state: &'a State,
+    ) -> Element<'a, MessageCategory, StyleType> {
+        let panel = &state.data.propagation_panel;
+        let resolvers = panel
+            .resolvers_input
+            .clone()
+            .unwrap_or_else(|| state.config.propagation_config.resolvers.join(", "));
+
+        let mut content =
+            column![
+                row![
+                    text("解析生效检查").size(14).width(Length::Fill),
+                    button(text("关闭"))
+                        .on_press(MessageCategory::Dns(DnsMessage::PropagationPanelClosed))
+                        .class(ButtonType::Standard),
+                ]
+                .align_y(Alignment::Center),
+                row![
+                    text_input("DNS 服务器，如 8.8.8.8, 1.1.1.1:53", &resolvers)
+                        .on_input(|s| MessageCategory::Dns(
+                            DnsMessage::PropagationResolversChanged(s)
+                        ))
+                        .width(Length::Fill),
+                    button(text("重新检查"))
+                        .on_press_maybe(panel.record_id.filter(|_| !panel.in_progress).map(
+                            |record_id| MessageCategory::Dns(DnsMessage::TestRecord(record_id))
+                        ))
+                        .class(ButtonType::Primary),
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center),
+            ]
+            .spacing(10)
+            .padding(15);
+
+        if panel.in_progress {
+            content = content.push(text("检查中...").size(12));
+        }
+        if let Some(message) = &panel.message {
+            content = content.push(text(message).size(12));
+        }
+
+        if let Some(report) = &panel.report {
+            let expected = match report.expected.is_empty() {
+                true => "无".to_string(),
+                false => report.expected.join(", "),
+            };
+            content = content
+                .push(
+                    text(format!(
+                        "{} {}：已在 {}/{} 个服务器生效",
+                        report.fqdn,
+                        report.record_type,
+                        report.matched_count(),
+                        report.results.len()
+                    ))
+                    .size(12),
+                )
+                .push(text(format!("本地记录值：{}", expected)).size(12));
+
+            let header = row![
+                text("服务器").size(12).width(Length::FillPortion(2)),
+                text("类型").size(12).width(Length::Fixed(40.0)),
+                text("地址").size(12).width(Length::FillPortion(2)),
+                text("状态").size(12).width(Length::Fixed(60.0)),
+                text("应答").size(12).width(Length::FillPortion(3)),
+            ]
+            .spacing(10);
+            let rows = report.results.iter().map(|result| {
+                let answers = match &result.error {
+                    Some(error) => error.clone(),
+                    None if result.answers.is_empty() => "-".to_string(),
+                    None => result.answers.join(", "),
+                };
+                row![
+                    text(result.server.as_str())
+                        .size(12)
+                        .width(Length::FillPortion(2)),
+                    text(result.kind.as_str())
+                        .size(12)
+                        .width(Length::Fixed(40.0)),
+                    text(
+                        result
+                            .address
+                            .map(|address| address.to_string())
+                            .unwrap_or_else(|| "-".to_string())
+                    )
+                    .size(12)
+                    .width(Length::FillPortion(2)),
+                    text(result.status.as_str())
+                        .size(12)
+                        .width(Length::Fixed(60.0)),
+                    text(answers).size(12).width(Length::FillPortion(3)),
+                ]
+                .spacing(10)
+                .into()
+            });
+            content = content
+                .push(header)
+                .push(scrollable(column(rows).spacing(4)).height(Length::Fixed(160.0)));
+        }
+
+        container(content)
+            .class(ContainerType::BorderedRound)
+            .width(Length::Fill)
+            .into()
+    }
+
     /// 渲染过滤器栏
     fn render_filter_bar(&self, state: &State) -> Element<'_, MessageCategory, StyleType> {
         let filters = vec![
@@ -317,6 +568,16 @@ impl DnsRecordsComponent {
                     )
                     .on_press(MessageCategory::Dns(DnsMessage::ProviderSelected(99999))), // 临时使用此消息触发切换
                 );
+            row = row.push(
+                button(text("区域文件"))
+                    .on_press(MessageCategory::Dns(DnsMessage::ZonePanelToggled))
+                    .class(ButtonType::Standard),
+            );
+            row = row.push(
+                button(text("迁移"))
+                    .on_press(MessageCategory::Dns(DnsMessage::MigrationPanelToggled))
+                    .class(ButtonType::Standard),
+            );
         }
 
         row.align_y(Alignment::Center)
@@ -405,6 +666,13 @@ impl DnsRecordsComponent {
                         .padding(Padding::from([2, 8]))
                         .on_press(MessageCategory::Dns(DnsMessage::EditRecord(record.clone()))),
                     )
+                    .push(
+                        iced::widget::Button::<'_, MessageCategory, StyleType>::new(
+                            iced::widget::Text::<'_, StyleType>::new("检测生效").size(10),
+                        )
+                        .padding(Padding::from([2, 8]))
+                        .on_press(MessageCategory::Dns(DnsMessage::TestRecord(record.id))),
+                    )
                     .push(
                         iced::widget::Button::<'_, MessageCategory, StyleType>::new(
                             iced::widget::Text::<'_, StyleType>::new("删除").size(10),
@@ -491,6 +759,19 @@ impl Component<AppState> for DnsRecordsComponent {
             content = content.push(self.render_add_form(state));
         }
 
+        // 区域文件面板
+        if let Some(domain) = &state.data.selected_domain {
+            if state.data.zone_panel.is_visible_for(domain.id) {
+                content = content.push(self.render_zone_panel(state));
+            }
+            if state.data.migration_panel.is_visible_for(domain.id) {
+                content = content.push(self.render_migration_panel(state, domain));
+            }
+            if state.data.propagation_panel.is_visible_for(domain.id) {
+                content = content.push(self.render_propagation_panel(state));
+            }
+        }
+
         // 检查是否选择了域名
         let selected_domain = match &state.data.selected_domain {
             Some(domain) => domain,
@@ -152,6 +152,11 @@ impl DomainListComponent {
                 details_row = details_row.push(text(format!("同步: {}", last_sync)).size(12));
             }
 
+            // 到期时间
+            if !domain.expiry.is_empty() {
+                details_row = details_row.push(text(format!("到期: {}", domain.expiry)).size(12));
+            }
+
             content = content.push(details_row);
         }
 
@@ -305,7 +310,10 @@ impl Component<AppState> for DomainListComponent {
                         id: domain.id.clone(),
                         name: domain.name.clone(),
                         status: DomainStatus::Active,
-                        expiry: "".to_string(),
+                        expiry: domain
+                            .expiration_date
+                            .map(|date| date.format("%Y-%m-%d").to_string())
+                            .unwrap_or_default(),
                         provider: DnsProvider::Aliyun,
                         records: vec![],
                     };
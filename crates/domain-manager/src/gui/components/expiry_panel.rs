@@ -0,0 +1,143 @@
+//! 域名到期提醒面板
+//!
+//! 在首页展示到达提醒阈值的域名，可以刷新注册信息并修改提醒天数和 Webhook 地址
+
+use crate::expiry::{Reminder, ReminderConfig};
+use crate::gui::handlers::message_handler::{ExpiryMessage, MessageCategory};
+use crate::gui::state::pages::expiry_state::ExpiryPanelState;
+use crate::gui::styles::button::ButtonType;
+use crate::gui::styles::container::ContainerType;
+use crate::gui::styles::text::TextType;
+use crate::StyleType;
+use iced::widget::{button, column, container, row, scrollable, text, text_input, Space};
+use iced::{Alignment, Element, Length};
+
+/// 域名到期提醒面板
+pub fn expiry_panel<'a>(
+    state: &'a ExpiryPanelState,
+    config: &ReminderConfig,
+) -> Element<'a, MessageCategory, StyleType> {
+    let refresh = button(text("刷新注册信息"))
+        .class(ButtonType::Primary)
+        .on_press_maybe(
+            (!state.in_progress).then_some(MessageCategory::Expiry(ExpiryMessage::Refresh)),
+        );
+    let title = match state.expired_count() {
+        0 => format!("域名到期提醒（{}）", state.reminders.len()),
+        expired => format!(
+            "域名到期提醒（{}，{} 个已过期）",
+            state.reminders.len(),
+            expired
+        ),
+    };
+
+    let mut content = column![row![
+        text(title).size(16),
+        Space::with_width(Length::Fill),
+        refresh,
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center)]
+    .spacing(10)
+    .padding(15);
+
+    if state.in_progress {
+        content = content.push(text("查询中...").size(12));
+    }
+    if let Some(message) = &state.message {
+        content = content.push(text(message).size(12).class(TextType::Danger));
+    }
+
+    if state.reminders.is_empty() {
+        content = content.push(
+            text(format!(
+                "没有 {} 天内到期的域名，点击「刷新注册信息」从服务商或 RDAP/WHOIS 查询",
+                config.max_days()
+            ))
+            .size(12),
+        );
+    } else {
+        let rows = state.reminders.iter().map(reminder_row);
+        content = content.push(scrollable(column(rows).spacing(4)).height(Length::Fixed(120.0)));
+    }
+
+    container(content.push(settings_row(state, config)))
+        .class(ContainerType::BorderedRound)
+        .width(Length::Fill)
+        .into()
+}
+
+/// 一个域名的提醒：域名、到期时间、剩余天数和阈值
+fn reminder_row(reminder: &Reminder) -> Element<'_, MessageCategory, StyleType> {
+    let (status, class) = match reminder.days_left {
+        days if days < 0 => ("已过期".to_string(), TextType::Danger),
+        days => (
+            format!("剩余 {} 天", days),
+            match reminder.threshold <= 7 {
+                true => TextType::Danger,
+                false => TextType::Warning,
+            },
+        ),
+    };
+    let notified = match reminder.notified {
+        true => "已通知",
+        false => "",
+    };
+
+    row![
+        text(&reminder.domain_name)
+            .size(12)
+            .width(Length::FillPortion(3)),
+        text(reminder.expiration_date.format("%Y-%m-%d").to_string())
+            .size(12)
+            .width(Length::FillPortion(2)),
+        text(status)
+            .size(12)
+            .class(class)
+            .width(Length::FillPortion(2)),
+        text(format!("{} 天提醒", reminder.threshold))
+            .size(12)
+            .width(Length::FillPortion(2)),
+        text(notified).size(12).width(Length::FillPortion(1)),
+    ]
+    .spacing(10)
+    .into()
+}
+
+/// 提醒天数和 Webhook 地址设置
+fn settings_row<'a>(
+    state: &'a ExpiryPanelState,
+    config: &ReminderConfig,
+) -> Element<'a, MessageCategory, StyleType> {
+    let days = state.days_input.clone().unwrap_or_else(|| {
+        config
+            .days
+            .iter()
+            .map(|days| days.to_string())
+            .collect::<Vec<_>>()
+            .join(",")
+    });
+    let webhook = state
+        .webhook_input
+        .clone()
+        .or_else(|| config.webhook_url.clone())
+        .unwrap_or_default();
+
+    row![
+        text("提醒天数").size(12),
+        text_input("30,7,1", &days)
+            .on_input(|input| MessageCategory::Expiry(ExpiryMessage::DaysInputChanged(input)))
+            .size(12)
+            .width(Length::Fixed(100.0)),
+        text("Webhook").size(12),
+        text_input("https://...（可选）", &webhook)
+            .on_input(|input| MessageCategory::Expiry(ExpiryMessage::WebhookInputChanged(input)))
+            .size(12)
+            .width(Length::Fill),
+        button(text("保存").size(12))
+            .on_press(MessageCategory::Expiry(ExpiryMessage::SaveSettings)),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center)
+    .into()
+}
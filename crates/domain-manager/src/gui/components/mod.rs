@@ -6,8 +6,10 @@
 // 现有组件
 pub mod background;
 pub mod button;
+pub mod certificate_panel;
 pub mod console;
 pub mod credential_form;
+pub mod expiry_panel;
 pub mod footer;
 pub mod header;
 pub mod modal;
@@ -3,24 +3,27 @@
 //! 负责处理所有与DNS记录相关的业务逻辑，包括DNS记录的增删改查、
 //! 提供商管理等操作。
 
-use crate::api::dns_client::DnsClientTrait;
-use crate::api::provider::aliyun::AliyunDnsClient;
+use crate::api::provider::{create_dns_client_for_account, BoxedDnsClient};
+use crate::configs;
 use crate::gui::handlers::message_handler::{
     DnsMessage, MessageCategory, NotificationMessage,
 };
 use crate::gui::handlers::{EventHandler, HandlerResult};
-use crate::gui::model::domain::{DnsProvider, DomainName};
+use crate::gui::model::domain::DomainName;
 use crate::gui::model::form::AddDnsField;
 use crate::gui::pages::Page;
 use crate::gui::state::app_state::{StateUpdate, UiUpdate};
 use crate::gui::state::AppState;
-use crate::gui::types::credential::Credential;
 use crate::model::dns_record_response::{Record, Status, Type as RecordType};
 use crate::models::record::NewRecord;
+use crate::propagation::{self, PropagationChecker, PropagationReport};
 use crate::storage::{accounts, domains, records, DnsRecordModal};
 use crate::utils::clipboard::copy_to_clipboard;
+use crate::zone::migrate::{self, MigrationItem, MigrationReport};
+use crate::zone::{self, Change, ChangeResult, ZoneRecord};
 use iced::Task;
 use sea_orm::DatabaseConnection; // Import DbErr
+use std::path::PathBuf;
 use tracing::{debug, info, warn};
 
 /// DNS处理器
@@ -535,14 +538,8 @@ impl DnsHandler {
             .ok_or("账户不存在")?;
 
         // 3. 初始化 API 客户端
-        let credential: Credential = account
-            .try_into()
-            .map_err(|e: anyhow::Error| e.to_string())?;
-
-        let api_client = match credential {
-            Credential::ApiKey(key) => AliyunDnsClient::new(key.api_key, key.api_secret),
-            _ => return Err("不支持的凭据类型".to_string()),
-        };
+        let (provider, api_client) =
+            create_dns_client_for_account(account).map_err(|e| e.to_string())?;
 
         let record_type_enum = match record.record_type.to_uppercase().as_str() {
             "A" => RecordType::A,
@@ -561,7 +558,7 @@ impl DnsHandler {
         // 4. 调用 API 创建记录
         let domain_name = DomainName {
             name: domain.domain_name.clone(),
-            provider: DnsProvider::Aliyun,
+            provider,
             ..Default::default()
         };
 
@@ -624,14 +621,8 @@ impl DnsHandler {
             .ok_or("账户不存在")?;
 
         // 4. 初始化 API 客户端
-        let credential: Credential = account
-            .try_into()
-            .map_err(|e: anyhow::Error| e.to_string())?;
-
-        let api_client = match credential {
-            Credential::ApiKey(key) => AliyunDnsClient::new(key.api_key, key.api_secret),
-            _ => return Err("不支持的凭据类型".to_string()),
-        };
+        let (provider, api_client) =
+            create_dns_client_for_account(account).map_err(|e| e.to_string())?;
 
         // 5. 调用 API 删除记录
         // 注意：阿里云API需要 RecordId，这是阿里云分配的ID，不是本地数据库ID
@@ -652,7 +643,7 @@ impl DnsHandler {
 
         let domain_name = DomainName {
             name: domain.domain_name.clone(),
-            provider: DnsProvider::Aliyun,
+            provider,
             ..Default::default()
         };
 
@@ -720,14 +711,8 @@ impl DnsHandler {
             .ok_or("账户不存在")?;
 
         // 3. 初始化 API 客户端
-        let credential: Credential = account
-            .try_into()
-            .map_err(|e: anyhow::Error| e.to_string())?;
-
-        let api_client = match credential {
-            Credential::ApiKey(key) => AliyunDnsClient::new(key.api_key, key.api_secret),
-            _ => return Err("不支持的凭据类型".to_string()),
-        };
+        let (provider, api_client) =
+            create_dns_client_for_account(account).map_err(|e| e.to_string())?;
 
         // 4. 查找云端记录ID
         let records = api_client
@@ -747,7 +732,7 @@ impl DnsHandler {
         // 5. 调用 API 更新记录
         let domain_name = DomainName {
             name: domain.domain_name.clone(),
-            provider: DnsProvider::Aliyun,
+            provider,
             ..Default::default()
         };
 
@@ -935,6 +920,466 @@ impl DnsHandler {
 
         Ok(())
     }
+
+    /// 切换区域文件面板
+    fn handle_zone_panel_toggled(&self, state: &mut AppState) -> HandlerResult {
+        let domain = match &state.data.selected_domain {
+            Some(domain) => domain,
+            None => return HandlerResult::None,
+        };
+
+        if state.data.zone_panel.is_visible_for(domain.id) {
+            state.data.zone_panel.visible = false;
+        } else {
+            let path = configs::get_zone_file_path(&domain.name);
+            state
+                .data
+                .zone_panel
+                .show(domain.id, path.display().to_string());
+        }
+        HandlerResult::StateUpdated
+    }
+
+    /// 导出区域文件
+    fn handle_zone_export(&self, state: &mut AppState) -> HandlerResult {
+        let (conn, domain_id) = match Self::zone_task_context(state) {
+            Some(context) => context,
+            None => return HandlerResult::StateUpdated,
+        };
+        let path = PathBuf::from(state.data.zone_panel.path.trim());
+        info!("导出域名 {} 的区域文件到 {:?}", domain_id, path);
+
+        state.data.zone_panel.in_progress = true;
+        state.data.zone_panel.message = None;
+        HandlerResult::StateUpdatedWithTask(Task::perform(
+            Self::export_zone_async(conn, domain_id, path),
+            |result| MessageCategory::Dns(DnsMessage::ZoneExported(result)),
+        ))
+    }
+
+    /// 读取区域文件并生成导入预览
+    fn handle_zone_preview(&self, state: &mut AppState) -> HandlerResult {
+        let (conn, domain_id) = match Self::zone_task_context(state) {
+            Some(context) => context,
+            None => return HandlerResult::StateUpdated,
+        };
+        let path = PathBuf::from(state.data.zone_panel.path.trim());
+        info!("预览导入区域文件 {:?} 到域名 {}", path, domain_id);
+
+        state.data.zone_panel.in_progress = true;
+        state.data.zone_panel.preview.clear();
+        state.data.zone_panel.message = None;
+        HandlerResult::StateUpdatedWithTask(Task::perform(
+            Self::preview_zone_async(conn, domain_id, path),
+            |result| MessageCategory::Dns(DnsMessage::ZonePreviewed(result)),
+        ))
+    }
+
+    /// 按预览执行导入
+    fn handle_zone_import(&self, state: &mut AppState) -> HandlerResult {
+        if !state.data.zone_panel.has_pending_changes() {
+            state.data.zone_panel.message = Some("没有需要导入的变更".to_string());
+            return HandlerResult::StateUpdated;
+        }
+        let (conn, domain_id) = match Self::zone_task_context(state) {
+            Some(context) => context,
+            None => return HandlerResult::StateUpdated,
+        };
+
+        let changes: Vec<Change> = std::mem::take(&mut state.data.zone_panel.preview)
+            .into_iter()
+            .filter(|change| !matches!(change, Change::Skip { .. }))
+            .collect();
+        info!("向域名 {} 导入 {} 条变更", domain_id, changes.len());
+
+        state.data.zone_panel.in_progress = true;
+        HandlerResult::StateUpdatedWithTask(Task::perform(
+            Self::import_zone_async(conn, domain_id, changes),
+            move |result| MessageCategory::Dns(DnsMessage::ZoneImported(domain_id, result)),
+        ))
+    }
+
+    /// 处理导入结果，成功后刷新记录列表
+    fn handle_zone_imported(
+        &self,
+        state: &mut AppState,
+        domain_id: i64,
+        result: Result<Vec<ChangeResult>, String>,
+    ) -> HandlerResult {
+        state.data.zone_panel.in_progress = false;
+        match result {
+            Ok(results) => {
+                let failed: Vec<String> = results
+                    .iter()
+                    .filter_map(|result| {
+                        result
+                            .error
+                            .as_ref()
+                            .map(|error| format!("{}：{}", result.change.describe(), error))
+                    })
+                    .collect();
+                let message = if failed.is_empty() {
+                    format!("导入完成，共 {} 条变更", results.len())
+                } else {
+                    format!(
+                        "导入完成，{} 条失败：\n{}",
+                        failed.len(),
+                        failed.join("\n")
+                    )
+                };
+                state.data.zone_panel.message = Some(message.clone());
+                state.update(StateUpdate::Ui(UiUpdate::ShowToast(message)));
+                self.handle_query_record(state, domain_id)
+            }
+            Err(e) => {
+                warn!("导入区域文件失败: {}", e);
+                state.data.zone_panel.message = Some(format!("导入失败: {}", e));
+                HandlerResult::StateUpdated
+            }
+        }
+    }
+
+    /// 区域文件操作需要的数据库连接和当前面板对应的域名
+    fn zone_task_context(state: &mut AppState) -> Option<(DatabaseConnection, i64)> {
+        if state.data.zone_panel.in_progress {
+            return None;
+        }
+        let domain_id = state.data.zone_panel.domain_id?;
+        match &state.database {
+            Some(conn) => Some((conn.clone(), domain_id)),
+            None => {
+                state.ui.set_message("数据库未连接".to_string());
+                None
+            }
+        }
+    }
+
+    /// 创建域名所属账户的 API 客户端
+    async fn zone_client(
+        conn: &DatabaseConnection,
+        domain_id: i64,
+    ) -> Result<(DomainName, BoxedDnsClient), String> {
+        let domain = domains::find_domain_by_id(conn, domain_id)
+            .await
+            .map_err(|e| e.to_string())?
+            .ok_or("域名不存在")?;
+        let account = accounts::get_account_by_id(conn, domain.account_id)
+            .await
+            .map_err(|e| e.to_string())?
+            .ok_or("账户不存在")?;
+        let (provider, client) =
+            create_dns_client_for_account(account).map_err(|e| e.to_string())?;
+
+        let domain_name = DomainName {
+            name: domain.domain_name,
+            provider,
+            ..Default::default()
+        };
+        Ok((domain_name, client))
+    }
+
+    /// 异步导出服务商上的记录到区域文件
+    async fn export_zone_async(
+        conn: DatabaseConnection,
+        domain_id: i64,
+        path: PathBuf,
+    ) -> Result<usize, String> {
+        let (domain_name, client) = Self::zone_client(&conn, domain_id).await?;
+        let records = client
+            .list_dns_records(domain_name.name.clone())
+            .await
+            .map_err(|e| e.to_string())?;
+        let zone_records: Vec<ZoneRecord> = records.iter().map(ZoneRecord::from).collect();
+        let content = zone::write_zone(&domain_name.name, &zone_records);
+
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+        }
+        std::fs::write(&path, content).map_err(|e| format!("写入区域文件失败: {}", e))?;
+        Ok(zone_records.len())
+    }
+
+    /// 异步读取区域文件并对比服务商上的记录
+    async fn preview_zone_async(
+        conn: DatabaseConnection,
+        domain_id: i64,
+        path: PathBuf,
+    ) -> Result<Vec<Change>, String> {
+        let content =
+            std::fs::read_to_string(&path).map_err(|e| format!("读取区域文件失败: {}", e))?;
+        let (domain_name, client) = Self::zone_client(&conn, domain_id).await?;
+        let parsed = zone::parse_zone(&content, &domain_name.name).map_err(|e| e.to_string())?;
+        let current = client
+            .list_dns_records(domain_name.name.clone())
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(zone::plan_import(&current, &parsed))
+    }
+
+    /// 异步执行导入并刷新本地缓存
+    async fn import_zone_async(
+        conn: DatabaseConnection,
+        domain_id: i64,
+        changes: Vec<Change>,
+    ) -> Result<Vec<ChangeResult>, String> {
+        let (domain_name, client) = Self::zone_client(&conn, domain_id).await?;
+        let results = zone::apply_changes(client.as_ref(), &domain_name, changes).await;
+
+        let records = client
+            .list_dns_records(domain_name.name.clone())
+            .await
+            .map_err(|e| e.to_string())?;
+        let new_records: Vec<NewRecord> = records
+            .iter()
+            .map(|r| NewRecord {
+                domain_id,
+                record_name: r.rr.clone(),
+                record_type: r.record_type.to_string(),
+                record_value: r.value.clone(),
+                ttl: r.ttl,
+            })
+            .collect();
+        records::replace_domain_records(&conn, domain_id, new_records).await?;
+        Ok(results)
+    }
+
+    /// 切换跨服务商迁移面板
+    fn handle_migration_panel_toggled(&self, state: &mut AppState) -> HandlerResult {
+        let domain = match &state.data.selected_domain {
+            Some(domain) => domain,
+            None => return HandlerResult::None,
+        };
+
+        if state.data.migration_panel.is_visible_for(domain.id) {
+            state.data.migration_panel.visible = false;
+        } else {
+            state.data.migration_panel.show(domain.id);
+        }
+        HandlerResult::StateUpdated
+    }
+
+    /// 预览迁移到目标账户后的记录
+    fn handle_migration_preview(&self, state: &mut AppState) -> HandlerResult {
+        let (conn, domain_id, target_account_id) = match Self::migration_task_context(state) {
+            Some(context) => context,
+            None => return HandlerResult::StateUpdated,
+        };
+        info!("预览域名 {} 迁移到账户 {}", domain_id, target_account_id);
+
+        let panel = &mut state.data.migration_panel;
+        panel.in_progress = true;
+        panel.preview.clear();
+        panel.report = None;
+        panel.message = None;
+        HandlerResult::StateUpdatedWithTask(Task::perform(
+            Self::preview_migration_async(conn, domain_id, target_account_id),
+            |result| MessageCategory::Dns(DnsMessage::MigrationPreviewed(result)),
+        ))
+    }
+
+    /// 开始迁移
+    fn handle_migration_start(&self, state: &mut AppState) -> HandlerResult {
+        let (conn, domain_id, target_account_id) = match Self::migration_task_context(state) {
+            Some(context) => context,
+            None => return HandlerResult::StateUpdated,
+        };
+        info!("开始迁移域名 {} 到账户 {}", domain_id, target_account_id);
+
+        state.data.migration_panel.in_progress = true;
+        state.data.migration_panel.message = None;
+        HandlerResult::StateUpdatedWithTask(Task::perform(
+            Self::run_migration_async(conn, domain_id, target_account_id),
+            |result| MessageCategory::Dns(DnsMessage::MigrationFinished(result)),
+        ))
+    }
+
+    /// 处理迁移结果
+    fn handle_migration_finished(
+        &self,
+        state: &mut AppState,
+        result: Result<MigrationReport, String>,
+    ) -> HandlerResult {
+        let panel = &mut state.data.migration_panel;
+        panel.in_progress = false;
+        match result {
+            Ok(report) => {
+                let mut message = format!(
+                    "迁移完成，共 {} 条记录，{} 条需要处理",
+                    report.items.len(),
+                    report.problem_count()
+                );
+                if !report.nameservers.is_empty() {
+                    message.push_str(&format!(
+                        "\n请到注册商处将 NS 修改为: {}",
+                        report.nameservers.join(", ")
+                    ));
+                }
+                panel.message = Some(message.clone());
+                panel.preview.clear();
+                panel.report = Some(report);
+                state.update(StateUpdate::Ui(UiUpdate::ShowToast(message)));
+            }
+            Err(e) => {
+                warn!("迁移失败: {}", e);
+                panel.message = Some(format!("迁移失败: {}", e));
+            }
+        }
+        HandlerResult::StateUpdated
+    }
+
+    /// 迁移需要的数据库连接、当前面板对应的域名和目标账户
+    fn migration_task_context(state: &mut AppState) -> Option<(DatabaseConnection, i64, i64)> {
+        let panel = &state.data.migration_panel;
+        if panel.in_progress {
+            return None;
+        }
+        let domain_id = panel.domain_id?;
+        let target_account_id = match &panel.target {
+            Some(target) => target.account_id,
+            None => {
+                state.data.migration_panel.message = Some("请选择目标账户".to_string());
+                return None;
+            }
+        };
+        match &state.database {
+            Some(conn) => Some((conn.clone(), domain_id, target_account_id)),
+            None => {
+                state.ui.set_message("数据库未连接".to_string());
+                None
+            }
+        }
+    }
+
+    /// 异步读取源记录并按目标服务商转换
+    async fn preview_migration_async(
+        conn: DatabaseConnection,
+        domain_id: i64,
+        target_account_id: i64,
+    ) -> Result<Vec<MigrationItem>, String> {
+        let (domain_name, client) = Self::zone_client(&conn, domain_id).await?;
+        let target = accounts::get_account_by_id(&conn, target_account_id)
+            .await
+            .map_err(|e| e.to_string())?
+            .ok_or("目标账户不存在")?;
+        let (target_provider, _) =
+            create_dns_client_for_account(target).map_err(|e| e.to_string())?;
+        migrate::preview_migration(
+            &domain_name.name,
+            domain_name.provider,
+            client.as_ref(),
+            target_provider,
+        )
+        .await
+        .map_err(|e| format!("{:#}", e))
+    }
+
+    /// 异步执行迁移并保存报告
+    async fn run_migration_async(
+        conn: DatabaseConnection,
+        domain_id: i64,
+        target_account_id: i64,
+    ) -> Result<MigrationReport, String> {
+        let domain = domains::find_domain_by_id(&conn, domain_id)
+            .await
+            .map_err(|e| e.to_string())?
+            .ok_or("域名不存在")?;
+        let source = accounts::get_account_by_id(&conn, domain.account_id)
+            .await
+            .map_err(|e| e.to_string())?
+            .ok_or("账户不存在")?;
+        let target = accounts::get_account_by_id(&conn, target_account_id)
+            .await
+            .map_err(|e| e.to_string())?
+            .ok_or("目标账户不存在")?;
+        migrate::run_migration(&conn, &domain.domain_name, source, target)
+            .await
+            .map_err(|e| format!("{:#}", e))
+    }
+
+    /// 检查记录在各 DNS 服务器上是否生效
+    fn handle_test_record(&self, state: &mut AppState, record_id: i64) -> HandlerResult {
+        let domain_id = match &state.data.selected_domain {
+            Some(domain) => domain.id,
+            None => return HandlerResult::None,
+        };
+        if state.data.propagation_panel.in_progress {
+            return HandlerResult::None;
+        }
+        let conn = match &state.database {
+            Some(conn) => conn.clone(),
+            None => {
+                state.ui.set_message("数据库未连接".to_string());
+                return HandlerResult::StateUpdated;
+            }
+        };
+
+        // 修改过的服务器列表在检查时保存到配置
+        if let Some(input) = state.data.propagation_panel.resolvers_input.take() {
+            match propagation::parse_resolvers(&input) {
+                Ok(resolvers) => {
+                    state.config.propagation_config.resolvers = resolvers;
+                    if let Err(e) = state.config.save_to_file("config.json") {
+                        warn!("保存DNS服务器列表失败: {}", e);
+                    }
+                }
+                Err(e) => {
+                    let panel = &mut state.data.propagation_panel;
+                    panel.resolvers_input = Some(input);
+                    panel.message = Some(e);
+                    return HandlerResult::StateUpdated;
+                }
+            }
+        }
+        let checker = match PropagationChecker::from_config(&state.config.propagation_config) {
+            Ok(checker) => checker,
+            Err(e) => {
+                state.data.propagation_panel.message = Some(e.to_string());
+                return HandlerResult::StateUpdated;
+            }
+        };
+
+        info!("检查记录 {} 的生效情况", record_id);
+        state.data.propagation_panel.start(domain_id, record_id);
+        HandlerResult::StateUpdatedWithTask(Task::perform(
+            async move {
+                propagation::check_record(&conn, &checker, record_id)
+                    .await
+                    .map_err(|e| format!("{:#}", e))
+            },
+            move |result| MessageCategory::Dns(DnsMessage::PropagationChecked(record_id, result)),
+        ))
+    }
+
+    /// 处理生效检查结果，忽略已经切换到其他记录的结果
+    fn handle_propagation_checked(
+        &self,
+        state: &mut AppState,
+        record_id: i64,
+        result: Result<PropagationReport, String>,
+    ) -> HandlerResult {
+        let panel = &mut state.data.propagation_panel;
+        if panel.record_id != Some(record_id) {
+            return HandlerResult::None;
+        }
+        panel.in_progress = false;
+        match result {
+            Ok(report) => {
+                info!(
+                    "{} {} 已在 {}/{} 个服务器生效",
+                    report.fqdn,
+                    report.record_type,
+                    report.matched_count(),
+                    report.results.len()
+                );
+                panel.report = Some(report);
+            }
+            Err(e) => {
+                warn!("检查记录 {} 的生效情况失败: {}", record_id, e);
+                panel.message = Some(format!("检查失败: {}", e));
+            }
+        }
+        HandlerResult::StateUpdated
+    }
 }
 
 impl EventHandler<DnsMessage> for DnsHandler {
@@ -984,6 +1429,72 @@ impl EventHandler<DnsMessage> for DnsHandler {
                 }
             }
             DnsMessage::ProviderChange(provider) => self.handle_provider_change(state, provider),
+            DnsMessage::ZonePanelToggled => self.handle_zone_panel_toggled(state),
+            DnsMessage::ZonePathChanged(path) => {
+                state.data.zone_panel.path = path;
+                HandlerResult::StateUpdated
+            }
+            DnsMessage::ZoneExport => self.handle_zone_export(state),
+            DnsMessage::ZoneExported(result) => {
+                state.data.zone_panel.in_progress = false;
+                state.data.zone_panel.message = Some(match result {
+                    Ok(count) => format!("已导出 {} 条记录", count),
+                    Err(e) => format!("导出失败: {}", e),
+                });
+                HandlerResult::StateUpdated
+            }
+            DnsMessage::ZonePreview => self.handle_zone_preview(state),
+            DnsMessage::ZonePreviewed(result) => {
+                state.data.zone_panel.in_progress = false;
+                match result {
+                    Ok(changes) => {
+                        state.data.zone_panel.message = None;
+                        state.data.zone_panel.preview = changes;
+                    }
+                    Err(e) => state.data.zone_panel.message = Some(format!("预览失败: {}", e)),
+                }
+                HandlerResult::StateUpdated
+            }
+            DnsMessage::ZoneImport => self.handle_zone_import(state),
+            DnsMessage::ZoneImported(domain_id, result) => {
+                self.handle_zone_imported(state, domain_id, result)
+            }
+            DnsMessage::MigrationPanelToggled => self.handle_migration_panel_toggled(state),
+            DnsMessage::MigrationTargetSelected(target) => {
+                let panel = &mut state.data.migration_panel;
+                panel.target = Some(target);
+                panel.preview.clear();
+                panel.report = None;
+                panel.message = None;
+                HandlerResult::StateUpdated
+            }
+            DnsMessage::MigrationPreview => self.handle_migration_preview(state),
+            DnsMessage::MigrationPreviewed(result) => {
+                let panel = &mut state.data.migration_panel;
+                panel.in_progress = false;
+                match result {
+                    Ok(items) => {
+                        panel.message = None;
+                        panel.preview = items;
+                    }
+                    Err(e) => panel.message = Some(format!("预览失败: {}", e)),
+                }
+                HandlerResult::StateUpdated
+            }
+            DnsMessage::MigrationStart => self.handle_migration_start(state),
+            DnsMessage::MigrationFinished(result) => self.handle_migration_finished(state, result),
+            DnsMessage::TestRecord(record_id) => self.handle_test_record(state, record_id),
+            DnsMessage::PropagationResolversChanged(input) => {
+                state.data.propagation_panel.resolvers_input = Some(input);
+                HandlerResult::StateUpdated
+            }
+            DnsMessage::PropagationChecked(record_id, result) => {
+                self.handle_propagation_checked(state, record_id, result)
+            }
+            DnsMessage::PropagationPanelClosed => {
+                state.data.propagation_panel.visible = false;
+                HandlerResult::StateUpdated
+            }
             DnsMessage::DnsRecordReloaded(domain_id, records) => {
                 info!(
                     "DNS记录重新加载完成，域名ID: {}，记录数: {}",
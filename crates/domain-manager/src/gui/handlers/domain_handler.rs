@@ -129,6 +129,10 @@ impl DomainHandler {
             status: "active".to_string(),
             created_at: chrono::Utc::now().naive_utc(),
             updated_at: Some(chrono::Utc::now().naive_utc()),
+            registration_date: None,
+            expiration_date: None,
+            registrar: None,
+            reminded_days: None,
         };
 
         // 添加到域名列表
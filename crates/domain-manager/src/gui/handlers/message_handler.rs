@@ -6,6 +6,8 @@
 use super::{
     DnsHandler, DomainHandler, EventHandler, ProviderHandler, SyncHandler, UiHandler, WindowHandler,
 };
+use crate::certmon::{self, CheckOptions};
+use crate::expiry::{self, LookupOptions, Reminder, ReminderConfig};
 use crate::gui::components::console::ConsoleTab;
 use crate::gui::handlers::database_handler::DataStoreHandler;
 use crate::gui::model::domain::{DnsProvider, Domain};
@@ -13,17 +15,24 @@ use crate::gui::model::gui::ReloadModel;
 use crate::gui::pages::domain::VerificationStatus;
 use crate::gui::pages::Page;
 use crate::gui::state::app_state::{StateUpdate, UiUpdate};
+use crate::gui::state::pages::migration_state::MigrationTarget;
 use crate::gui::state::AppState;
 use crate::gui::types::credential::CredentialMessage;
 use crate::model::dns_record_response::{Record, Type};
 use crate::models::account::{Account, NewAccount};
-use crate::storage::{DnsRecordModal, DomainModal, agents};
+use crate::propagation::PropagationReport;
+use crate::storage::encryption::DatabaseKeyManager;
+use crate::storage::entities::certificate_check;
+use crate::storage::{DnsRecordModal, DomainModal, agents, certificate_checks};
 use crate::translations::types::language::Language;
 use crate::translations::types::locale::Locale;
 use crate::utils::types::file_info::FileInfo;
 use crate::utils::types::web_page::WebPage;
+use crate::zone::migrate::{MigrationItem, MigrationReport};
+use crate::zone::{Change, ChangeResult};
 use iced::{window, Point, Size, Task};
 use sea_orm::DatabaseConnection;
+use secrecy::SecretString;
 use std::process;
 use tracing::{error, info};
 use window::Id;
@@ -59,6 +68,10 @@ pub enum MessageCategory {
     Database(DatabaseMessage),
     /// Agent相关消息
     Agent(AgentMessage),
+    /// 证书清单消息
+    Certificate(CertificateMessage),
+    /// 域名到期提醒消息
+    Expiry(ExpiryMessage),
     /// 其他消息
     Other(OtherMessage),
 }
@@ -69,6 +82,16 @@ pub enum AppMessage {
     Started,
     Initialize,
     Shutdown,
+    /// 主密码输入变化
+    UnlockPasswordChanged(String),
+    /// 确认主密码输入变化
+    UnlockConfirmChanged(String),
+    /// 是否记住主密钥
+    UnlockRememberToggled(bool),
+    /// 提交主密码
+    UnlockSubmit,
+    /// 解锁结果：true 表示已解锁，false 表示需要输入主密码
+    Unlocked(Result<bool, String>),
 }
 
 /// 数据库消息
@@ -165,6 +188,29 @@ pub enum DnsMessage {
     ReloadDnsRecord(usize),
     DnsRecordReloaded(i64, Vec<DnsRecordModal>),
     QueryDnsResult(Vec<Record>),
+
+    // 区域文件导入导出
+    ZonePanelToggled,
+    ZonePathChanged(String),
+    ZoneExport,
+    ZoneExported(Result<usize, String>),
+    ZonePreview,
+    ZonePreviewed(Result<Vec<Change>, String>),
+    ZoneImport,
+    ZoneImported(i64, Result<Vec<ChangeResult>, String>),
+
+    // 跨服务商迁移
+    MigrationPanelToggled,
+    MigrationTargetSelected(MigrationTarget),
+    MigrationPreview,
+    MigrationPreviewed(Result<Vec<MigrationItem>, String>),
+    MigrationStart,
+    MigrationFinished(Result<MigrationReport, String>),
+
+    // 解析生效检查，由 TestRecord 触发
+    PropagationResolversChanged(String),
+    PropagationChecked(i64, Result<PropagationReport, String>),
+    PropagationPanelClosed,
 }
 
 /// 同步消息
@@ -312,6 +358,38 @@ pub enum AgentMessage {
     DenyAgent(String),
 }
 
+/// 证书清单消息
+#[derive(Debug, Clone)]
+pub enum CertificateMessage {
+    /// 加载保存的检查结果
+    Load,
+    /// 检查结果已加载
+    Loaded(Result<Vec<certificate_check::Model>, String>),
+    /// 检查所有托管的主机名
+    CheckManaged,
+    /// 检查完成，返回最新的证书清单
+    Checked(Result<Vec<certificate_check::Model>, String>),
+}
+
+/// 域名到期提醒消息
+#[derive(Debug, Clone)]
+pub enum ExpiryMessage {
+    /// 加载到达提醒阈值的域名，并把未发送的提醒发送到 Webhook
+    Load,
+    /// 提醒已加载
+    Loaded(Result<Vec<Reminder>, String>),
+    /// 重新查询所有域名的注册信息
+    Refresh,
+    /// 查询完成，返回最新的提醒和查询失败的域名数量
+    Refreshed(Result<(Vec<Reminder>, usize), String>),
+    /// 设置页提醒天数输入变化
+    DaysInputChanged(String),
+    /// 设置页 Webhook 地址输入变化
+    WebhookInputChanged(String),
+    /// 保存提醒设置
+    SaveSettings,
+}
+
 /// 消息处理器
 ///
 /// 负责将消息分发到对应的专门处理器
@@ -362,6 +440,8 @@ impl MessageHandler {
                 self.database_handler.handle(state, message).into()
             }
             MessageCategory::Agent(msg) => self.handle_agent(state, msg),
+            MessageCategory::Certificate(msg) => self.handle_certificate(state, msg),
+            MessageCategory::Expiry(msg) => self.handle_expiry(state, msg),
             MessageCategory::Console(_) => Task::none(),
             MessageCategory::Notification(_) => Task::none(),
             MessageCategory::Other(_) => Task::none(),
@@ -380,12 +460,77 @@ impl MessageHandler {
             AppMessage::Initialize => {
                 // 执行初始化逻辑
                 state.update(StateUpdate::Ui(UiUpdate::SetLoading(true)));
-                Task::done(MessageCategory::Sync(SyncMessage::Reload))
+                Task::batch([
+                    Task::done(MessageCategory::Sync(SyncMessage::Reload)),
+                    Task::done(MessageCategory::Certificate(CertificateMessage::Load)),
+                    Task::done(MessageCategory::Expiry(ExpiryMessage::Load)),
+                ])
             }
             AppMessage::Shutdown => {
                 // 应用程序关闭时的清理工作
                 Task::none()
             }
+            AppMessage::UnlockPasswordChanged(password) => {
+                state.data.unlock_page.password = password;
+                Task::none()
+            }
+            AppMessage::UnlockConfirmChanged(password) => {
+                state.data.unlock_page.confirm_password = password;
+                Task::none()
+            }
+            AppMessage::UnlockRememberToggled(remember) => {
+                state.data.unlock_page.remember = remember;
+                Task::none()
+            }
+            AppMessage::UnlockSubmit => {
+                let unlock = &mut state.data.unlock_page;
+                if unlock.in_progress {
+                    return Task::none();
+                }
+                if let Err(err) = unlock.validate() {
+                    unlock.error = Some(err);
+                    return Task::none();
+                }
+
+                unlock.in_progress = true;
+                unlock.error = None;
+                let password = SecretString::from(unlock.password.clone());
+                let remember = unlock.remember;
+
+                // Argon2id 派生密钥较耗时，放到阻塞线程中执行
+                Task::perform(
+                    async move {
+                        tokio::task::spawn_blocking(move || {
+                            DatabaseKeyManager::unlock(&password, remember)
+                        })
+                        .await
+                        .map_err(|e| e.to_string())?
+                        .map(|_| true)
+                        .map_err(|e| e.to_string())
+                    },
+                    |result| MessageCategory::App(AppMessage::Unlocked(result)),
+                )
+            }
+            AppMessage::Unlocked(result) => {
+                let first_use = !DatabaseKeyManager::has_vault();
+                let unlock = &mut state.data.unlock_page;
+                match result {
+                    Ok(true) => {
+                        info!("主密钥解锁成功，继续初始化");
+                        unlock.finish();
+                        Task::done(MessageCategory::App(AppMessage::Started))
+                    }
+                    Ok(false) => {
+                        unlock.show(first_use, None);
+                        Task::none()
+                    }
+                    Err(err) => {
+                        error!("解锁主密钥失败: {}", err);
+                        unlock.show(first_use, Some(err));
+                        Task::none()
+                    }
+                }
+            }
         }
     }
 
@@ -403,6 +548,10 @@ impl MessageHandler {
                 match page {
                     Page::Providers => Task::done(MessageCategory::Provider(ProviderMessage::Load)),
                     Page::Agent => Task::done(MessageCategory::Agent(AgentMessage::LoadAgents)),
+                    Page::Dashboard => Task::batch([
+                        Task::done(MessageCategory::Certificate(CertificateMessage::Load)),
+                        Task::done(MessageCategory::Expiry(ExpiryMessage::Load)),
+                    ]),
                     _ => Task::none(),
                 }
             }
@@ -613,6 +762,199 @@ impl MessageHandler {
             }
         }
     }
+
+    /// 处理证书清单消息
+    fn handle_certificate(
+        &self,
+        state: &mut AppState,
+        message: CertificateMessage,
+    ) -> Task<MessageCategory> {
+        let panel = &mut state.data.certificate_panel;
+        match message {
+            CertificateMessage::Load => {
+                let Some(conn) = state.database.clone() else {
+                    return Task::none();
+                };
+                Task::perform(
+                    async move { certificate_checks::list_certificate_checks(&conn).await },
+                    |result| MessageCategory::Certificate(CertificateMessage::Loaded(result)),
+                )
+            }
+            CertificateMessage::CheckManaged => {
+                let Some(conn) = state.database.clone() else {
+                    return Task::none();
+                };
+                if panel.in_progress {
+                    return Task::none();
+                }
+                panel.in_progress = true;
+                panel.message = None;
+                Task::perform(
+                    async move {
+                        let targets = certmon::managed_targets(&conn)
+                            .await
+                            .map_err(|e| format!("{:#}", e))?;
+                        let checks =
+                            certmon::check_targets(&targets, CheckOptions::default()).await;
+                        certmon::save_checks(&conn, &checks)
+                            .await
+                            .map_err(|e| format!("{:#}", e))?;
+                        certificate_checks::list_certificate_checks(&conn).await
+                    },
+                    |result| MessageCategory::Certificate(CertificateMessage::Checked(result)),
+                )
+            }
+            CertificateMessage::Loaded(result) | CertificateMessage::Checked(result) => {
+                panel.in_progress = false;
+                match result {
+                    Ok(checks) => {
+                        panel.checks = checks;
+                        panel.message = None;
+                    }
+                    Err(e) => {
+                        error!("加载证书清单失败: {}", e);
+                        panel.message = Some(e);
+                    }
+                }
+                Task::none()
+            }
+        }
+    }
+
+    /// 处理域名到期提醒消息
+    fn handle_expiry(&self, state: &mut AppState, message: ExpiryMessage) -> Task<MessageCategory> {
+        let config = state.config.reminder_config.clone();
+        let panel = &mut state.data.expiry_panel;
+        match message {
+            ExpiryMessage::Load => {
+                let Some(conn) = state.database.clone() else {
+                    return Task::none();
+                };
+                Task::perform(
+                    async move { load_reminders(&conn, &config).await },
+                    |result| MessageCategory::Expiry(ExpiryMessage::Loaded(result)),
+                )
+            }
+            ExpiryMessage::Refresh => {
+                let Some(conn) = state.database.clone() else {
+                    return Task::none();
+                };
+                if panel.in_progress {
+                    return Task::none();
+                }
+                panel.in_progress = true;
+                panel.message = None;
+                Task::perform(
+                    async move {
+                        let lookups = expiry::refresh(&conn, &LookupOptions::default())
+                            .await
+                            .map_err(|e| format!("{:#}", e))?;
+                        let failed = lookups
+                            .iter()
+                            .filter(|lookup| lookup.result.is_err())
+                            .count();
+                        Ok((load_reminders(&conn, &config).await?, failed))
+                    },
+                    |result| MessageCategory::Expiry(ExpiryMessage::Refreshed(result)),
+                )
+            }
+            ExpiryMessage::Loaded(result) => {
+                panel.in_progress = false;
+                match result {
+                    Ok(reminders) => {
+                        // 只在首次出现提醒时弹出通知，避免每次回到首页都提示
+                        let notify = panel.reminders.is_empty() && !reminders.is_empty();
+                        panel.reminders = reminders;
+                        panel.message = None;
+                        if notify {
+                            let toast = match panel.reminders.as_slice() {
+                                [reminder] => reminder.message(),
+                                reminders => format!("有 {} 个域名即将到期", reminders.len()),
+                            };
+                            state.update(StateUpdate::Ui(UiUpdate::ShowToast(toast)));
+                        }
+                    }
+                    Err(e) => {
+                        error!("加载域名到期提醒失败: {}", e);
+                        panel.message = Some(e);
+                    }
+                }
+                Task::none()
+            }
+            ExpiryMessage::Refreshed(result) => {
+                panel.in_progress = false;
+                match result {
+                    Ok((reminders, failed)) => {
+                        panel.reminders = reminders;
+                        panel.message =
+                            (failed > 0).then(|| format!("{} 个域名没有查到注册信息", failed));
+                    }
+                    Err(e) => {
+                        error!("刷新域名注册信息失败: {}", e);
+                        panel.message = Some(e);
+                    }
+                }
+                Task::none()
+            }
+            ExpiryMessage::DaysInputChanged(input) => {
+                panel.days_input = Some(input);
+                Task::none()
+            }
+            ExpiryMessage::WebhookInputChanged(input) => {
+                panel.webhook_input = Some(input);
+                Task::none()
+            }
+            ExpiryMessage::SaveSettings => {
+                let days = match &panel.days_input {
+                    Some(input) => match expiry::parse_reminder_days(input) {
+                        Ok(days) => days,
+                        Err(e) => {
+                            state.update(StateUpdate::Ui(UiUpdate::ShowToast(e)));
+                            return Task::none();
+                        }
+                    },
+                    None => config.days,
+                };
+                let webhook_url = match &panel.webhook_input {
+                    Some(input) => Some(input.trim().to_string()).filter(|url| !url.is_empty()),
+                    None => config.webhook_url,
+                };
+                panel.days_input = None;
+                panel.webhook_input = None;
+                state.config.reminder_config = ReminderConfig { days, webhook_url };
+                let toast = match state.config.save_to_file("config.json") {
+                    Ok(()) => "提醒设置已保存".to_string(),
+                    Err(e) => {
+                        error!("保存提醒设置失败: {}", e);
+                        format!("保存提醒设置失败: {}", e)
+                    }
+                };
+                state.update(StateUpdate::Ui(UiUpdate::ShowToast(toast)));
+                Task::done(MessageCategory::Expiry(ExpiryMessage::Load))
+            }
+        }
+    }
+}
+
+/// 查询到达提醒阈值的域名，并把未发送的提醒发送到 Webhook
+async fn load_reminders(
+    conn: &DatabaseConnection,
+    config: &ReminderConfig,
+) -> Result<Vec<Reminder>, String> {
+    let reminders = expiry::due_reminders(conn, config)
+        .await
+        .map_err(|e| format!("{:#}", e))?;
+    match expiry::notify(conn, config, &reminders).await {
+        // 重新读取，更新已发送的标记
+        Ok(sent) if sent > 0 => expiry::due_reminders(conn, config)
+            .await
+            .map_err(|e| format!("{:#}", e)),
+        Ok(_) => Ok(reminders),
+        Err(e) => {
+            error!("发送域名到期提醒失败: {:#}", e);
+            Ok(reminders)
+        }
+    }
 }
 
 impl Default for MessageHandler {
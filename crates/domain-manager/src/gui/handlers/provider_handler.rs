@@ -100,6 +100,7 @@ impl ProviderHandler {
             DnsProvider::Dnspod => Some(DnsProvider::Dnspod),
             DnsProvider::Aws => Some(DnsProvider::Aws),
             DnsProvider::Google => Some(DnsProvider::Google),
+            DnsProvider::Rfc2136 => Some(DnsProvider::Rfc2136),
         };
 
         if let Some(provider) = dns_provider {
@@ -156,7 +157,9 @@ impl ProviderHandler {
         state: &mut AppState,
         message: CredentialMessage,
     ) -> HandlerResult {
-        use crate::gui::types::credential::{ApiKeyMessage, TokenMessage, UsernamePasswordMessage};
+        use crate::gui::types::credential::{
+            ApiKeyMessage, ServiceAccountMessage, TokenMessage, UsernamePasswordMessage,
+        };
 
         if let Some(credential) = &mut state.data.provider_page.form.credential {
             match (credential, message) {
@@ -184,6 +187,15 @@ impl ProviderHandler {
                         cred.api_secret = new_cred.api_secret;
                     }
                 },
+                (
+                    Credential::ServiceAccount(cred),
+                    CredentialMessage::ServiceAccountChanged(msg),
+                ) => match msg {
+                    ServiceAccountMessage::KeyChanged(key) => cred.set_key_input(key),
+                },
+                (Credential::Tsig(cred), CredentialMessage::TsigChanged(msg)) => {
+                    cred.apply(msg);
+                }
                 _ => {
                     tracing::warn!("凭证类型与消息类型不匹配");
                 }
@@ -562,6 +574,10 @@ impl ProviderHandler {
                                     )
                                     .unwrap_or_default(),
                                     updated_at: d.updated_at,
+                                    registration_date: d.registration_date,
+                                    expiration_date: d.expiration_date,
+                                    registrar: d.registrar,
+                                    reminded_days: d.reminded_days,
                                 })
                                 .collect()
                         })
@@ -602,7 +618,10 @@ impl ProviderHandler {
                                 .status
                                 .parse()
                                 .unwrap_or(crate::gui::model::domain::DomainStatus::Active),
-                            expiry: "".to_string(), // TODO: 需要添加过期字段
+                            expiry: d
+                                .expiration_date
+                                .map(|date| date.format("%Y-%m-%d").to_string())
+                                .unwrap_or_default(),
                             records: vec![],
                         })
                         .collect();
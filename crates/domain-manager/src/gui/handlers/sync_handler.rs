@@ -5,13 +5,11 @@
 
 use super::message_handler::{MessageCategory, NotificationMessage, SyncMessage};
 use super::{AsyncEventHandler, EventHandler, HandlerResult};
-use crate::api::dns_client::DnsClientTrait;
-use crate::api::provider::aliyun::AliyunDnsClient;
+use crate::api::provider::create_dns_client_for_account;
 use crate::gui::model::gui::ReloadModel;
 use crate::gui::pages::domain::DomainProvider;
 use crate::gui::state::app_state::{StateUpdate, UiUpdate};
 use crate::gui::state::AppState;
-use crate::gui::types::credential::Credential;
 use crate::models::record::NewRecord;
 use crate::storage::{accounts, domains, records, DnsRecordModal, DomainModal};
 use iced::Task;
@@ -185,6 +183,10 @@ impl SyncHandler {
                         created_at: Default::default(),
                         provider_id: 0,
                         updated_at: None,
+                        registration_date: None,
+                        expiration_date: None,
+                        registrar: None,
+                        reminded_days: None,
                     })
                     .collect();
 
@@ -335,14 +337,7 @@ impl SyncHandler {
             .ok_or(format!("账户 ID {} 不存在", domain_entity.account_id))?;
 
         // 3. 初始化 API 客户端
-        let credential: Credential = account
-            .try_into()
-            .map_err(|e: anyhow::Error| e.to_string())?;
-
-        let api_client = match credential {
-            Credential::ApiKey(key) => AliyunDnsClient::new(key.api_key, key.api_secret),
-            _ => return Err("不支持的凭据类型".to_string()),
-        };
+        let (_, api_client) = create_dns_client_for_account(account).map_err(|e| e.to_string())?;
 
         // 4. 调用 API
         let response_records = api_client
@@ -367,29 +362,18 @@ impl SyncHandler {
             })
             .collect();
 
-        // 6. 更新数据库
-        // 先删除旧记录
-        records::delete_records_by_domain(&conn, domain_entity.id)
-            .await
-            .map_err(|e| e.to_string())?;
-
-        // 再插入新记录
-        if !records.is_empty() {
-            let new_records: Vec<NewRecord> = records
-                .iter()
-                .map(|r| NewRecord {
-                    domain_id: r.domain_id,
-                    record_name: r.name.clone(),
-                    record_type: r.record_type.clone(),
-                    record_value: r.value.clone(),
-                    ttl: r.ttl,
-                })
-                .collect();
-
-            records::add_records_many(&conn, new_records)
-                .await
-                .map_err(|e| e.to_string())?;
-        }
+        // 6. 用最新记录替换数据库中的旧记录
+        let new_records: Vec<NewRecord> = records
+            .iter()
+            .map(|r| NewRecord {
+                domain_id: r.domain_id,
+                record_name: r.name.clone(),
+                record_type: r.record_type.clone(),
+                record_value: r.value.clone(),
+                ttl: r.ttl,
+            })
+            .collect();
+        records::replace_domain_records(&conn, domain_entity.id, new_records).await?;
 
         Ok(records)
     }
@@ -489,6 +473,10 @@ impl SyncHandler {
                 )
                 .unwrap_or_default(),
                 updated_at: None,
+                registration_date: domain.registration_date,
+                expiration_date: domain.expiration_date,
+                registrar: domain.registrar.clone(),
+                reminded_days: domain.reminded_days,
             };
             domain_modals.push(modal);
 
@@ -128,6 +128,10 @@ impl UiHandler {
                         status: "active".to_string(),
                         created_at: chrono::Utc::now().naive_utc(),
                         updated_at: Some(chrono::Utc::now().naive_utc()),
+                        registration_date: None,
+                        expiration_date: None,
+                        registrar: None,
+                        reminded_days: None,
                     },
                     crate::storage::entities::domain::Model {
                         id: 2,
@@ -136,22 +140,27 @@ impl UiHandler {
                         status: "active".to_string(),
                         created_at: chrono::Utc::now().naive_utc(),
                         updated_at: Some(chrono::Utc::now().naive_utc()),
+                        registration_date: None,
+                        expiration_date: None,
+                        registrar: None,
+                        reminded_days: None,
                     },
                 ];
 
                 // 将存储实体转换为GUI模型
                 let domains: Vec<DomainModal> = mock_domains
                     .into_iter()
-                    .map(|domain| {
-                        DomainModal {
-                            id: domain.id,
-                            name: domain.name,
-                            provider_id: domain.provider_id,
-                            status: domain.status,
-                            // expiry_date字段在Model中不存在，暂时注释掉
-                            created_at: domain.created_at,
-                            updated_at: domain.updated_at,
-                        }
+                    .map(|domain| DomainModal {
+                        id: domain.id,
+                        name: domain.name,
+                        provider_id: domain.provider_id,
+                        status: domain.status,
+                        created_at: domain.created_at,
+                        updated_at: domain.updated_at,
+                        registration_date: domain.registration_date,
+                        expiration_date: domain.expiration_date,
+                        registrar: domain.registrar,
+                        reminded_days: domain.reminded_days,
                     })
                     .collect();
                 domains
@@ -4,7 +4,8 @@
 
 use crate::configs::gui_config::Config;
 use crate::gui::components::{
-    dns_records::DnsRecordsComponent, domain_list::DomainListComponent, footer, header, Component,
+    certificate_panel::certificate_panel, dns_records::DnsRecordsComponent,
+    domain_list::DomainListComponent, expiry_panel::expiry_panel, footer, header, Component,
 };
 // TODO: 实现Component trait
 use crate::gui::handlers::message_handler::{
@@ -15,6 +16,7 @@ use crate::gui::services::{ServiceManager, ServiceResult};
 use crate::gui::state::app_state::{DataUpdate, StateUpdate, UiUpdate};
 use crate::gui::state::AppState;
 use crate::gui::styles::types::style_type::StyleType;
+use crate::storage::encryption::DatabaseKeyManager;
 use crate::storage::init_database;
 use crate::storage::{DnsRecordModal, DomainModal};
 use crate::{configs, get_text};
@@ -23,6 +25,7 @@ use crate::{configs, get_text};
 use crate::gui::handlers::message_handler::WindowMessage::Resized;
 use crate::gui::pages::names::Page;
 use crate::gui::pages::provider::provider_page;
+use crate::gui::pages::unlock::unlock_page;
 use chrono::{DateTime, Utc};
 use iced::widget::{row, Column, Container, Text};
 use iced::{Element, Length, Task};
@@ -315,6 +318,16 @@ impl DomainManagerV2 {
                         if self.is_initialized() {
                             info!("管理器已初始化，忽略重复的Started消息");
                             Task::none()
+                        } else if !DatabaseKeyManager::is_unlocked() {
+                            // 账户凭证需要主密钥解密，优先尝试系统钥匙串
+                            info!("主密钥未解锁，尝试使用系统钥匙串解锁");
+                            Task::perform(
+                                async {
+                                    DatabaseKeyManager::unlock_from_keyring()
+                                        .map_err(|e| e.to_string())
+                                },
+                                |result| MessageCategory::App(AppMessage::Unlocked(result)),
+                            )
                         } else {
                             info!("收到Started消息，开始初始化管理器");
                             // 启动异步初始化任务（数据库连接）
@@ -351,6 +364,10 @@ impl DomainManagerV2 {
                         info!("收到Shutdown消息，开始关闭管理器");
                         Task::none()
                     }
+                    // 解锁相关消息交给消息处理器
+                    app_message => self
+                        .message_handler
+                        .handle_message(&mut self.state, MessageCategory::App(app_message)),
                 }
             }
             _ => {
@@ -366,6 +383,9 @@ impl DomainManagerV2 {
     pub fn view(&self) -> Element<'_, MessageCategory, StyleType> {
         debug!("view:是否初始化：{}", self.is_initialized());
         if !self.state.initialized {
+            if self.state.data.unlock_page.visible {
+                return unlock_page(&self.state.data.unlock_page);
+            }
             return self.render_loading_screen();
         }
 
@@ -376,7 +396,7 @@ impl DomainManagerV2 {
 
         // 创建主体内容
         let body: Element<MessageCategory, StyleType> = match self.state.ui.current_page {
-            Page::Dashboard => self.render_main_page(),
+            Page::Dashboard => self.render_dashboard(),
             Page::DomainPage => self.render_main_page(),
             Page::DnsRecord => self.render_main_page(), // Fix: Handle DnsRecord page
             Page::Settings(_) => self.render_settings_page(),
@@ -470,6 +490,28 @@ impl DomainManagerV2 {
             .into()
     }
 
+    /// 渲染首页：域名和解析记录，下方为域名到期提醒和证书清单
+    fn render_dashboard(&self) -> Element<'_, MessageCategory, StyleType> {
+        Column::<'_, MessageCategory, StyleType>::new()
+            .push(self.render_main_page())
+            .push(
+                Container::<'_, MessageCategory, StyleType>::new(expiry_panel(
+                    &self.state.data.expiry_panel,
+                    &self.state.config.reminder_config,
+                ))
+                .padding(10),
+            )
+            .push(
+                Container::<'_, MessageCategory, StyleType>::new(certificate_panel(
+                    &self.state.data.certificate_panel,
+                ))
+                .padding(10),
+            )
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    }
+
     /// 渲染设置页面
     fn render_settings_page(&self) -> Element<'_, MessageCategory, StyleType> {
         Container::<'_, MessageCategory, StyleType>::new(
@@ -1,6 +1,7 @@
 use crate::gui::components::credential_form::CredentialForm;
 use crate::gui::types::credential::{
-    ApiKeyCredential, Credential, TokenCredential, UsernamePasswordCredential,
+    ApiKeyCredential, Credential, ServiceAccountCredential, TokenCredential, TsigCredential,
+    UsernamePasswordCredential,
 };
 use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
@@ -14,10 +15,11 @@ pub enum DnsProvider {
     Dnspod,
     Aws,
     Google,
+    Rfc2136,
 }
 
 impl DnsProvider {
-    pub const ALL: [DnsProvider; 7] = [
+    pub const ALL: [DnsProvider; 8] = [
         DnsProvider::Aliyun,
         DnsProvider::TencentCloud,
         DnsProvider::CloudFlare,
@@ -25,6 +27,7 @@ impl DnsProvider {
         DnsProvider::Google,
         DnsProvider::Aws,
         DnsProvider::Dnspod,
+        DnsProvider::Rfc2136,
     ];
 }
 
@@ -38,6 +41,7 @@ impl From<String> for DnsProvider {
             "Google" => DnsProvider::Google,
             "Aws" => DnsProvider::Aws,
             "Dnspod" => DnsProvider::Dnspod,
+            "Rfc2136" => DnsProvider::Rfc2136,
             _ => panic!("Unknown dns provider: {}", s),
         }
     }
@@ -48,8 +52,12 @@ impl DnsProvider {
         match self {
             DnsProvider::Aliyun => Credential::ApiKey(ApiKeyCredential::default()),
             DnsProvider::CloudFlare => Credential::Token(TokenCredential::default()),
+            DnsProvider::TencentCloud | DnsProvider::Dnspod => {
+                Credential::ApiKey(ApiKeyCredential::default())
+            }
             DnsProvider::Aws => Credential::ApiKey(ApiKeyCredential::default()),
-            DnsProvider::Google => Credential::ApiKey(ApiKeyCredential::default()),
+            DnsProvider::Google => Credential::ServiceAccount(ServiceAccountCredential::default()),
+            DnsProvider::Rfc2136 => Credential::Tsig(TsigCredential::default()),
             _ => Credential::UsernamePassword(UsernamePasswordCredential::default()),
         }
     }
@@ -61,6 +69,10 @@ impl DnsProvider {
             }
             Credential::Token(token_credential) => Some(Box::new(token_credential)),
             Credential::ApiKey(api_key_credential) => Some(Box::new(api_key_credential)),
+            Credential::ServiceAccount(service_account_credential) => {
+                Some(Box::new(service_account_credential))
+            }
+            Credential::Tsig(tsig_credential) => Some(Box::new(tsig_credential)),
         }
     }
     pub fn value(&self) -> &str {
@@ -72,6 +84,7 @@ impl DnsProvider {
             DnsProvider::Aws => "Aws",
             DnsProvider::Google => "Google",
             DnsProvider::Tomato => "Tomato",
+            DnsProvider::Rfc2136 => "Rfc2136",
         }
     }
 
@@ -82,8 +95,9 @@ impl DnsProvider {
             DnsProvider::TencentCloud => "腾讯云",
             DnsProvider::Dnspod => "DNSPod",
             DnsProvider::Aws => "Amazon Route 53",
-            DnsProvider::Google => "Google Domains",
+            DnsProvider::Google => "Google Cloud DNS",
             DnsProvider::Tomato => "Tomato DNS",
+            DnsProvider::Rfc2136 => "RFC 2136 (BIND/Knot/PowerDNS)",
         }
     }
 
@@ -96,6 +110,7 @@ impl DnsProvider {
             DnsProvider::Aws => 'S',
             DnsProvider::Google => 'G',
             DnsProvider::Tomato => 'T',
+            DnsProvider::Rfc2136 => 'R',
         }
     }
 
@@ -106,8 +121,9 @@ impl DnsProvider {
             DnsProvider::TencentCloud => vec!["DNS管理", "安全防护", "CDN加速"],
             DnsProvider::Dnspod => vec!["域名解析", "智能解析", "安全监控"],
             DnsProvider::Aws => vec!["路由策略", "健康检查", "地理路由"],
-            DnsProvider::Google => vec!["域名转移", "隐私保护", "DNS配置"],
+            DnsProvider::Google => vec!["DNSSEC", "私有区域", "DNS转发"],
             DnsProvider::Tomato => vec!["域名解析", "安全防护", "DNS缓存"],
+            DnsProvider::Rfc2136 => vec!["动态更新", "TSIG认证", "区域传送"],
         }
     }
 }
@@ -122,6 +138,7 @@ impl Display for DnsProvider {
             DnsProvider::Dnspod => write!(f, "Dnspod"),
             DnsProvider::Aws => write!(f, "Aws"),
             DnsProvider::Google => write!(f, "Google"),
+            DnsProvider::Rfc2136 => write!(f, "Rfc2136"),
         }
     }
 }
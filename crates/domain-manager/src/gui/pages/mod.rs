@@ -7,6 +7,7 @@ pub mod names;
 pub(crate) mod provider;
 pub mod settings;
 pub mod types;
+pub mod unlock;
 
 // 重新导出Page枚举
 pub use names::Page;
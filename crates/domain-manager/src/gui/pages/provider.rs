@@ -372,6 +372,7 @@ fn get_provider_color(provider: &DnsProvider) -> Color {
         DnsProvider::CloudFlare => Color::from_rgb8(243, 128, 32), // Cloudflare 橙
         DnsProvider::Aws => Color::from_rgb8(35, 47, 62),     // AWS 深蓝
         DnsProvider::Google => Color::from_rgb8(66, 133, 244), // Google 蓝
+        DnsProvider::Rfc2136 => Color::from_rgb8(0, 105, 92), // 自建 DNS 墨绿
         _ => Color::from_rgb8(100, 100, 100),                 // 默认灰
     }
 }
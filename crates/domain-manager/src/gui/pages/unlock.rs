@@ -0,0 +1,83 @@
+//! 主密钥解锁页面
+//!
+//! 启动时输入主密码解锁账户凭证，首次使用时设置主密码
+
+use crate::gui::handlers::message_handler::{AppMessage, MessageCategory};
+use crate::gui::state::pages::unlock_state::UnlockPageState;
+use crate::gui::styles::container::ContainerType;
+use crate::gui::styles::text::TextType;
+use crate::StyleType;
+use iced::widget::{button, checkbox, text, text_input, Column, Container};
+use iced::{Alignment, Element, Font, Length};
+
+/// 主密钥解锁页面
+pub fn unlock_page(state: &UnlockPageState) -> Element<'_, MessageCategory, StyleType> {
+    let (title, hint, submit_text) = if state.first_use {
+        (
+            "设置主密码",
+            "账户凭证将使用主密码加密保存，请牢记主密码，遗失后无法恢复已保存的凭证。",
+            "设置并解锁",
+        )
+    } else {
+        ("解锁", "请输入主密码以解密已保存的账户凭证。", "解锁")
+    };
+
+    let password_input = text_input("主密码", &state.password)
+        .font(Font::with_name("Maple Mono NF CN"))
+        .secure(true)
+        .on_input(|password| MessageCategory::App(AppMessage::UnlockPasswordChanged(password)))
+        .on_submit(MessageCategory::App(AppMessage::UnlockSubmit))
+        .padding(8)
+        .width(Length::Fill);
+
+    let mut form = Column::new()
+        .spacing(12)
+        .width(Length::Fixed(360.0))
+        .push(text(title).size(20))
+        .push(text(hint).size(12))
+        .push(password_input);
+
+    if state.first_use {
+        form = form.push(
+            text_input("确认主密码", &state.confirm_password)
+                .font(Font::with_name("Maple Mono NF CN"))
+                .secure(true)
+                .on_input(|password| {
+                    MessageCategory::App(AppMessage::UnlockConfirmChanged(password))
+                })
+                .on_submit(MessageCategory::App(AppMessage::UnlockSubmit))
+                .padding(8)
+                .width(Length::Fill),
+        );
+    }
+
+    form = form.push(
+        checkbox("在系统钥匙串中记住主密钥", state.remember).on_toggle(|remember| {
+            MessageCategory::App(AppMessage::UnlockRememberToggled(remember))
+        }),
+    );
+
+    if let Some(error) = &state.error {
+        form = form.push(text(error).class(TextType::Danger));
+    }
+
+    let submit = if state.in_progress {
+        button(text("正在解锁...").center()).width(Length::Fill)
+    } else {
+        button(text(submit_text).center())
+            .on_press(MessageCategory::App(AppMessage::UnlockSubmit))
+            .width(Length::Fill)
+    };
+    form = form.push(submit);
+
+    Container::new(
+        Container::new(form)
+            .padding(20)
+            .class(ContainerType::BorderedRound),
+    )
+    .align_x(Alignment::Center)
+    .align_y(Alignment::Center)
+    .width(Length::Fill)
+    .height(Length::Fill)
+    .into()
+}
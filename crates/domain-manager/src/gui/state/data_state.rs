@@ -7,7 +7,13 @@ use crate::gui::model::domain::DomainStatus;
 use crate::gui::model::form::AddDnsField;
 use crate::gui::pages::domain::DomainProvider;
 use crate::gui::state::pages::agent_state::AgentPageState;
+use crate::gui::state::pages::certificate_state::CertificatePanelState;
+use crate::gui::state::pages::expiry_state::ExpiryPanelState;
+use crate::gui::state::pages::migration_state::MigrationPanelState;
+use crate::gui::state::pages::propagation_state::PropagationPanelState;
 use crate::gui::state::pages::provider_state::ProviderPageState;
+use crate::gui::state::pages::unlock_state::UnlockPageState;
+use crate::gui::state::pages::zone_state::ZonePanelState;
 use crate::storage::{DnsRecordModal, DomainModal};
 use std::collections::HashMap;
 
@@ -67,6 +73,24 @@ pub struct DataState {
     /// Agent管理页面状态
     pub agent_page: AgentPageState,
 
+    /// 主密钥解锁页面状态
+    pub unlock_page: UnlockPageState,
+
+    /// 区域文件导入导出面板状态
+    pub zone_panel: ZonePanelState,
+
+    /// 跨服务商迁移面板状态
+    pub migration_panel: MigrationPanelState,
+
+    /// 解析生效检查面板状态
+    pub propagation_panel: PropagationPanelState,
+
+    /// 首页证书清单面板状态
+    pub certificate_panel: CertificatePanelState,
+
+    /// 首页域名到期提醒面板状态
+    pub expiry_panel: ExpiryPanelState,
+
     /// 正在删除的DNS记录ID
     pub deleting_dns_record_id: Option<i64>,
 
@@ -146,6 +170,12 @@ impl Default for DataState {
             domain_stats: HashMap::new(),
             provider_page: ProviderPageState::default(),
             agent_page: AgentPageState::default(),
+            unlock_page: UnlockPageState::default(),
+            zone_panel: ZonePanelState::default(),
+            migration_panel: MigrationPanelState::default(),
+            propagation_panel: PropagationPanelState::default(),
+            certificate_panel: CertificatePanelState::default(),
+            expiry_panel: ExpiryPanelState::default(),
             deleting_dns_record_id: None,
             deleting_domain_id: None,
         }
@@ -309,6 +339,11 @@ impl DataState {
         self.domain_stats.clear();
         self.provider_page = ProviderPageState::default();
         self.agent_page = AgentPageState::default();
+        self.zone_panel = ZonePanelState::default();
+        self.migration_panel = MigrationPanelState::default();
+        self.propagation_panel = PropagationPanelState::default();
+        self.certificate_panel = CertificatePanelState::default();
+        self.expiry_panel = ExpiryPanelState::default();
         self.deleting_dns_record_id = None;
         self.deleting_domain_id = None;
     }
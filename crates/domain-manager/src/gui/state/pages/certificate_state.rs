@@ -0,0 +1,24 @@
+//! 首页证书清单面板状态
+
+use crate::storage::entities::certificate_check;
+
+/// 证书清单面板状态
+#[derive(Debug, Clone, Default)]
+pub struct CertificatePanelState {
+    /// 每个目标最近一次的检查结果，按到期时间排序
+    pub checks: Vec<certificate_check::Model>,
+    /// 是否正在加载或检查
+    pub in_progress: bool,
+    /// 最近一次操作的结果
+    pub message: Option<String>,
+}
+
+impl CertificatePanelState {
+    /// 存在问题或无法连接的目标数量
+    pub fn problem_count(&self) -> usize {
+        self.checks
+            .iter()
+            .filter(|check| !check.issues.is_empty() || check.error.is_some())
+            .count()
+    }
+}
@@ -0,0 +1,28 @@
+//! 首页域名到期提醒面板状态
+
+use crate::expiry::Reminder;
+
+/// 域名到期提醒面板状态
+#[derive(Debug, Clone, Default)]
+pub struct ExpiryPanelState {
+    /// 到达提醒阈值的域名，按到期时间排序
+    pub reminders: Vec<Reminder>,
+    /// 是否正在加载或刷新
+    pub in_progress: bool,
+    /// 最近一次操作的结果
+    pub message: Option<String>,
+    /// 设置页中正在编辑的提醒天数，未编辑时显示当前配置
+    pub days_input: Option<String>,
+    /// 设置页中正在编辑的 Webhook 地址
+    pub webhook_input: Option<String>,
+}
+
+impl ExpiryPanelState {
+    /// 已经过期的域名数量
+    pub fn expired_count(&self) -> usize {
+        self.reminders
+            .iter()
+            .filter(|reminder| reminder.days_left < 0)
+            .count()
+    }
+}
@@ -0,0 +1,60 @@
+//! 跨服务商迁移面板状态
+
+use crate::zone::migrate::{MigrationItem, MigrationReport};
+use std::fmt;
+
+/// 可选的目标账户
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationTarget {
+    pub account_id: i64,
+    pub name: String,
+}
+
+impl fmt::Display for MigrationTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// 迁移面板状态
+#[derive(Debug, Clone, Default)]
+pub struct MigrationPanelState {
+    /// 是否显示面板
+    pub visible: bool,
+    /// 面板对应的域名ID
+    pub domain_id: Option<i64>,
+    /// 选择的目标账户
+    pub target: Option<MigrationTarget>,
+    /// 迁移预览
+    pub preview: Vec<MigrationItem>,
+    /// 最近一次迁移的报告
+    pub report: Option<MigrationReport>,
+    /// 是否正在预览或迁移
+    pub in_progress: bool,
+    /// 最近一次操作的结果
+    pub message: Option<String>,
+}
+
+impl MigrationPanelState {
+    /// 显示面板并清空上一次的结果
+    pub fn show(&mut self, domain_id: i64) {
+        *self = Self {
+            visible: true,
+            domain_id: Some(domain_id),
+            ..Self::default()
+        };
+    }
+
+    /// 面板是否属于当前选中的域名
+    pub fn is_visible_for(&self, domain_id: i64) -> bool {
+        self.visible && self.domain_id == Some(domain_id)
+    }
+
+    /// 当前展示的记录：迁移完成后展示报告，否则展示预览
+    pub fn items(&self) -> &[MigrationItem] {
+        match &self.report {
+            Some(report) => &report.items,
+            None => &self.preview,
+        }
+    }
+}
@@ -1,2 +1,8 @@
 pub mod agent_state;
+pub mod certificate_state;
+pub mod expiry_state;
+pub mod migration_state;
+pub mod propagation_state;
 pub mod provider_state;
+pub mod unlock_state;
+pub mod zone_state;
@@ -0,0 +1,41 @@
+//! 解析生效检查面板状态
+
+use crate::propagation::PropagationReport;
+
+/// 解析生效检查面板状态
+#[derive(Debug, Clone, Default)]
+pub struct PropagationPanelState {
+    /// 是否显示面板
+    pub visible: bool,
+    /// 面板对应的域名ID
+    pub domain_id: Option<i64>,
+    /// 检查的记录ID
+    pub record_id: Option<i64>,
+    /// 正在编辑的 DNS 服务器列表，为空时使用配置中的值
+    pub resolvers_input: Option<String>,
+    /// 最近一次检查的结果
+    pub report: Option<PropagationReport>,
+    /// 是否正在检查
+    pub in_progress: bool,
+    /// 检查失败的原因
+    pub message: Option<String>,
+}
+
+impl PropagationPanelState {
+    /// 显示面板并开始检查指定记录
+    pub fn start(&mut self, domain_id: i64, record_id: i64) {
+        self.visible = true;
+        if self.record_id != Some(record_id) {
+            self.report = None;
+        }
+        self.domain_id = Some(domain_id);
+        self.record_id = Some(record_id);
+        self.in_progress = true;
+        self.message = None;
+    }
+
+    /// 面板是否属于当前选中的域名
+    pub fn is_visible_for(&self, domain_id: i64) -> bool {
+        self.visible && self.domain_id == Some(domain_id)
+    }
+}
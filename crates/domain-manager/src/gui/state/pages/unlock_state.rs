@@ -0,0 +1,51 @@
+//! 主密钥解锁页面状态
+
+/// 主密钥解锁页面状态
+#[derive(Debug, Clone, Default)]
+pub struct UnlockPageState {
+    /// 是否显示解锁页面
+    pub visible: bool,
+    /// 是否首次使用（需要设置主密码）
+    pub first_use: bool,
+    /// 主密码
+    pub password: String,
+    /// 确认主密码（仅首次使用）
+    pub confirm_password: String,
+    /// 是否把主密钥保存到系统钥匙串
+    pub remember: bool,
+    /// 是否正在解锁
+    pub in_progress: bool,
+    /// 错误消息
+    pub error: Option<String>,
+}
+
+impl UnlockPageState {
+    /// 显示解锁页面
+    pub fn show(&mut self, first_use: bool, error: Option<String>) {
+        self.visible = true;
+        self.first_use = first_use;
+        self.in_progress = false;
+        self.error = error;
+    }
+
+    /// 校验输入，返回错误消息
+    pub fn validate(&self) -> Result<(), String> {
+        if self.password.is_empty() {
+            return Err("请输入主密码".to_string());
+        }
+        if self.first_use {
+            if self.password.chars().count() < 8 {
+                return Err("主密码至少需要8个字符".to_string());
+            }
+            if self.password != self.confirm_password {
+                return Err("两次输入的主密码不一致".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// 解锁成功后清除输入的密码
+    pub fn finish(&mut self) {
+        *self = UnlockPageState::default();
+    }
+}
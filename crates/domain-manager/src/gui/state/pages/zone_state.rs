@@ -0,0 +1,44 @@
+//! 区域文件导入导出面板状态
+
+use crate::zone::Change;
+
+/// 区域文件面板状态
+#[derive(Debug, Clone, Default)]
+pub struct ZonePanelState {
+    /// 是否显示面板
+    pub visible: bool,
+    /// 面板对应的域名ID
+    pub domain_id: Option<i64>,
+    /// 区域文件路径
+    pub path: String,
+    /// 导入预览
+    pub preview: Vec<Change>,
+    /// 是否正在导入或导出
+    pub in_progress: bool,
+    /// 最近一次操作的结果
+    pub message: Option<String>,
+}
+
+impl ZonePanelState {
+    /// 显示面板并设置默认路径
+    pub fn show(&mut self, domain_id: i64, path: String) {
+        self.visible = true;
+        self.domain_id = Some(domain_id);
+        self.path = path;
+        self.preview.clear();
+        self.in_progress = false;
+        self.message = None;
+    }
+
+    /// 面板是否属于当前选中的域名
+    pub fn is_visible_for(&self, domain_id: i64) -> bool {
+        self.visible && self.domain_id == Some(domain_id)
+    }
+
+    /// 是否有可执行的变更
+    pub fn has_pending_changes(&self) -> bool {
+        self.preview
+            .iter()
+            .any(|change| !matches!(change, Change::Skip { .. }))
+    }
+}
@@ -14,6 +14,8 @@ pub enum Credential {
     UsernamePassword(UsernamePasswordCredential),
     Token(TokenCredential),
     ApiKey(ApiKeyCredential),
+    ServiceAccount(ServiceAccountCredential),
+    Tsig(TsigCredential),
     // 添加其他凭证类型...
 }
 
@@ -23,6 +25,8 @@ impl Credential {
             Credential::UsernamePassword(credential) => credential.view(),
             Credential::Token(cre) => cre.view(),
             Credential::ApiKey(credential) => credential.view(),
+            Credential::ServiceAccount(credential) => credential.view(),
+            Credential::Tsig(credential) => credential.view(),
         }
     }
 
@@ -37,6 +41,8 @@ impl Credential {
             Credential::UsernamePassword(_) => "UsernamePassword".into(),
             Credential::Token(_) => "Token".into(),
             Credential::ApiKey(_) => "ApiKey".into(),
+            Credential::ServiceAccount(_) => "ServiceAccount".into(),
+            Credential::Tsig(_) => "Tsig".into(),
         }
     }
 
@@ -45,6 +51,8 @@ impl Credential {
             Credential::UsernamePassword(credential) => serde_json::to_string(credential).unwrap(),
             Credential::Token(credential) => serde_json::to_string(credential).unwrap(),
             Credential::ApiKey(credential) => serde_json::to_string(credential).unwrap(),
+            Credential::ServiceAccount(credential) => serde_json::to_string(credential).unwrap(),
+            Credential::Tsig(credential) => serde_json::to_string(credential).unwrap(),
         }
     }
 }
@@ -66,6 +74,14 @@ impl TryFrom<Account> for Credential {
                 let api_key = serde_json::from_str(&value.credential_data)?;
                 Ok(Credential::ApiKey(api_key))
             }
+            "ServiceAccount" => {
+                let service_account = serde_json::from_str(&value.credential_data)?;
+                Ok(Credential::ServiceAccount(service_account))
+            }
+            "Tsig" => {
+                let tsig = serde_json::from_str(&value.credential_data)?;
+                Ok(Credential::Tsig(tsig))
+            }
             _ => anyhow::bail!("Unknown credential type: {}", value.credential_type),
         }
     }
@@ -91,12 +107,90 @@ pub struct ApiKeyCredential {
     pub api_secret: String,
 }
 
+// 服务账号凭证（Google Cloud 服务账号密钥文件内容）
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Serialize, Deserialize)]
+pub struct ServiceAccountCredential {
+    pub key_json: String,
+}
+
+impl ServiceAccountCredential {
+    /// 更新密钥内容，输入为密钥文件路径时读取文件内容
+    pub fn set_key_input(&mut self, input: String) {
+        let path = std::path::Path::new(input.trim());
+        if !input.trim_start().starts_with('{') && path.is_file() {
+            match std::fs::read_to_string(path) {
+                Ok(content) => {
+                    self.key_json = content;
+                    return;
+                }
+                Err(err) => debug!("读取服务账号密钥文件失败：{:?}", err),
+            }
+        }
+        self.key_json = input;
+    }
+}
+
+/// TSIG 默认算法
+pub const DEFAULT_TSIG_ALGORITHM: &str = "hmac-sha256";
+
+// TSIG凭证（RFC 2136 动态更新）
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Serialize, Deserialize)]
+pub struct TsigCredential {
+    /// 权威服务器地址，例如 `192.0.2.53:53`
+    pub server: String,
+    /// TSIG 密钥名称
+    pub key_name: String,
+    /// TSIG 算法，例如 `hmac-sha256`
+    pub algorithm: String,
+    /// Base64 编码的密钥
+    pub secret: String,
+    /// 需要管理的区域，逗号分隔
+    #[serde(default)]
+    pub zones: String,
+}
+
+impl Default for TsigCredential {
+    fn default() -> Self {
+        Self {
+            server: String::new(),
+            key_name: String::new(),
+            algorithm: DEFAULT_TSIG_ALGORITHM.to_string(),
+            secret: String::new(),
+            zones: String::new(),
+        }
+    }
+}
+
+impl TsigCredential {
+    pub fn apply(&mut self, message: TsigMessage) {
+        match message {
+            TsigMessage::ServerChanged(server) => self.server = server,
+            TsigMessage::KeyNameChanged(key_name) => self.key_name = key_name,
+            TsigMessage::AlgorithmChanged(algorithm) => self.algorithm = algorithm,
+            TsigMessage::SecretChanged(secret) => self.secret = secret,
+            TsigMessage::ZonesChanged(zones) => self.zones = zones,
+        }
+    }
+
+    /// 解析区域列表
+    pub fn zone_list(&self) -> Vec<String> {
+        self.zones
+            .split([',', ' ', '\n'])
+            .map(|zone| zone.trim().trim_end_matches('.'))
+            .filter(|zone| !zone.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+}
+
 // 凭证专用消息
 #[derive(Debug, Clone)]
 pub enum CredentialMessage {
     UsernamePasswordChanged(UsernamePasswordMessage),
     TokenChanged(TokenMessage),
     ApiKeyChanged(ApiKeyMessage),
+    ServiceAccountChanged(ServiceAccountMessage),
+    TsigChanged(TsigMessage),
 }
 
 // 用户名密码凭证消息
@@ -119,6 +213,22 @@ pub enum ApiKeyMessage {
     ApiSecretChanged(ApiKeyCredential),
 }
 
+// 服务账号凭证消息
+#[derive(Debug, Clone)]
+pub enum ServiceAccountMessage {
+    KeyChanged(String),
+}
+
+// TSIG凭证消息
+#[derive(Debug, Clone)]
+pub enum TsigMessage {
+    ServerChanged(String),
+    KeyNameChanged(String),
+    AlgorithmChanged(String),
+    SecretChanged(String),
+    ZonesChanged(String),
+}
+
 // 实现从子消息到 CredentialMessage 的自动转换
 impl From<UsernamePasswordMessage> for CredentialMessage {
     fn from(msg: UsernamePasswordMessage) -> Self {
@@ -138,6 +248,18 @@ impl From<ApiKeyMessage> for CredentialMessage {
     }
 }
 
+impl From<ServiceAccountMessage> for CredentialMessage {
+    fn from(msg: ServiceAccountMessage) -> Self {
+        CredentialMessage::ServiceAccountChanged(msg)
+    }
+}
+
+impl From<TsigMessage> for CredentialMessage {
+    fn from(msg: TsigMessage) -> Self {
+        CredentialMessage::TsigChanged(msg)
+    }
+}
+
 // 实现从 CredentialMessage 到顶层 Message 的转换
 impl From<CredentialMessage> for MessageCategory {
     fn from(credential_message: CredentialMessage) -> Self {
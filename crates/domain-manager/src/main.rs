@@ -1,19 +1,24 @@
 #![cfg_attr(windows, windows_subsystem = "windows")]
 #![allow(dead_code)]
 
+mod acme;
 mod agent;
 mod api;
+mod certmon;
 mod cli;
 mod client;
 mod configs;
 mod dm_logger;
+mod expiry;
 pub mod error;
 mod gui;
 mod model;
 mod models;
+mod propagation;
 pub mod storage;
 mod translations;
 mod utils;
+mod zone;
 
 #[cfg(test)]
 mod tests;
@@ -25,6 +30,7 @@ use crate::gui::manager_v2::DomainManagerV2;
 use crate::gui::styles::style_constants::{FONT_SIZE_BODY, ICONS_BYTES, MAPLE_MONO_NF_CN_REGULAR};
 pub use crate::gui::styles::types::style_type::StyleType;
 pub use crate::utils::i18_utils::get_text;
+use clap::Parser;
 use iced::window::icon::from_rgba;
 use iced::window::Position;
 use iced::{application, window, Font, Pixels, Point, Settings, Size, Task};
@@ -49,6 +55,12 @@ i18n!("locales", fallback = "en");
 
 #[tokio::main]
 pub async fn main() -> iced::Result {
+    // 带子命令时以无界面模式运行，执行完成后直接退出
+    let args = cli::Cli::parse();
+    if args.command.is_some() {
+        process::exit(cli::run(args).await);
+    }
+
     info!("Application Starting...");
     // 读取配置文件
     let config: Config = Config::new_from_file("config.json");
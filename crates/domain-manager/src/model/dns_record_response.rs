@@ -105,10 +105,33 @@ impl Record {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// 解析线路
+///
+/// 阿里云、DNSPod 等服务商支持按运营商或地区返回不同的记录值，其他服务商只有默认线路。
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(from = "String", into = "String")]
 pub enum Line {
-    #[serde(rename = "default")]
     Default,
+    /// 默认线路以外的线路，如 telecom、unicom、oversea
+    Custom(String),
+}
+
+impl From<String> for Line {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "" | "default" => Line::Default,
+            _ => Line::Custom(value),
+        }
+    }
+}
+
+impl From<Line> for String {
+    fn from(line: Line) -> Self {
+        match line {
+            Line::Default => "default".to_string(),
+            Line::Custom(line) => line,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -168,6 +191,8 @@ impl Display for Type {
 pub enum Status {
     #[serde(rename = "ENABLE")]
     Enable,
+    #[serde(rename = "DISABLE")]
+    Disable,
 }
 
 pub enum TimeUnit {
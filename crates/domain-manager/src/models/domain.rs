@@ -36,12 +36,14 @@ pub struct DomainEntity {
     pub id: i64,
     pub account_id: i64,
     pub domain_name: String,
-    pub registration_date: Option<String>,
-    pub expiration_date: Option<String>,
+    pub registration_date: Option<DateTime>,
+    pub expiration_date: Option<DateTime>,
     pub registrar: Option<String>,
     pub status: DomainStatus,
     pub created_at: String,
     pub updated_at: Option<DateTime>,
+    /// 已发送过的最小提醒阈值（天）
+    pub reminded_days: Option<i32>,
 }
 
 /// 新域名创建模型
@@ -54,3 +56,11 @@ pub struct NewDomain {
     pub status: DomainStatus,
     pub account_id: i64,
 }
+
+/// 域名注册信息，来自服务商接口或 RDAP/WHOIS
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DomainRegistration {
+    pub registration_date: Option<DateTime>,
+    pub expiration_date: Option<DateTime>,
+    pub registrar: Option<String>,
+}
@@ -0,0 +1,494 @@
+//! DNS 解析生效检查
+//!
+//! 修改记录后，向一组递归解析服务器以及域名的权威 NS 服务器分别查询同一主机记录和类型，
+//! 与本地 `storage::records` 中保存的值对比，得到每个服务器的生效情况：
+//! - 递归解析服务器可以配置，默认使用国内外常用的公共 DNS
+//! - 权威服务器先通过递归解析服务器查询域名的 NS 记录和地址，再关闭递归直接查询
+//!
+//! 服务器地址和权威服务器端口都可以指定，测试中使用本地的模拟 DNS 服务。
+
+pub mod resolver;
+
+use crate::api::provider::rrset::parse_record_type;
+use crate::model::dns_record_response::Type;
+use crate::storage::{domains, records};
+use crate::zone::diff::same_value;
+use anyhow::{anyhow, Result};
+use futures_util::future::join_all;
+use hickory_proto::op::{Message, ResponseCode};
+use hickory_proto::rr::{RData, RecordType};
+use sea_orm::DatabaseConnection;
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::time::Duration;
+use tracing::{debug, info};
+
+/// 默认查询的递归解析服务器：Google、Cloudflare、阿里 DNS、腾讯 DNSPod
+pub const DEFAULT_RESOLVERS: [&str; 4] = ["8.8.8.8", "1.1.1.1", "223.5.5.5", "119.29.29.29"];
+
+/// 单次查询超时
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 生效检查配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PropagationConfig {
+    /// 递归解析服务器，`IP` 或 `IP:端口`
+    pub resolvers: Vec<String>,
+}
+
+impl Default for PropagationConfig {
+    fn default() -> Self {
+        Self {
+            resolvers: DEFAULT_RESOLVERS.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+impl PropagationConfig {
+    /// 解析配置中的服务器地址
+    pub fn resolver_addrs(&self) -> Result<Vec<SocketAddr>> {
+        self.resolvers
+            .iter()
+            .map(|server| resolver::parse_server(server))
+            .collect()
+    }
+}
+
+/// 解析逗号或空白分隔的服务器列表
+pub fn parse_resolvers(input: &str) -> Result<Vec<String>, String> {
+    let resolvers: Vec<String> = input
+        .split(|c: char| c == ',' || c == '，' || c.is_whitespace())
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect();
+    if resolvers.is_empty() {
+        return Err("请至少填写一个 DNS 服务器".to_string());
+    }
+    for server in &resolvers {
+        resolver::parse_server(server).map_err(|e| e.to_string())?;
+    }
+    Ok(resolvers)
+}
+
+/// 服务器类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ServerKind {
+    /// 递归解析服务器
+    Resolver,
+    /// 域名的权威 NS 服务器
+    Authoritative,
+}
+
+impl ServerKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ServerKind::Resolver => "递归",
+            ServerKind::Authoritative => "权威",
+        }
+    }
+}
+
+/// 单个服务器上的生效状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PropagationStatus {
+    /// 应答与本地记录一致
+    Matched,
+    /// 有应答但与本地记录不一致
+    Mismatched,
+    /// 没有该类型的记录
+    Missing,
+    /// 查询失败
+    Failed,
+}
+
+impl PropagationStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PropagationStatus::Matched => "已生效",
+            PropagationStatus::Mismatched => "不一致",
+            PropagationStatus::Missing => "无记录",
+            PropagationStatus::Failed => "查询失败",
+        }
+    }
+}
+
+/// 单个服务器的查询结果
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ServerResult {
+    /// 服务器名称，权威服务器为 NS 主机名
+    pub server: String,
+    /// 查询的地址，权威服务器地址查询失败时为空
+    pub address: Option<SocketAddr>,
+    pub kind: ServerKind,
+    pub status: PropagationStatus,
+    /// 应答中的记录值
+    pub answers: Vec<String>,
+    pub error: Option<String>,
+}
+
+impl ServerResult {
+    fn failed(
+        server: String,
+        address: Option<SocketAddr>,
+        kind: ServerKind,
+        error: String,
+    ) -> Self {
+        Self {
+            server,
+            address,
+            kind,
+            status: PropagationStatus::Failed,
+            answers: Vec::new(),
+            error: Some(error),
+        }
+    }
+}
+
+/// 一次生效检查的结果
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PropagationReport {
+    /// 查询的完整域名
+    pub fqdn: String,
+    #[serde(rename = "type")]
+    pub record_type: Type,
+    /// 本地保存的记录值
+    pub expected: Vec<String>,
+    pub results: Vec<ServerResult>,
+}
+
+impl PropagationReport {
+    /// 已生效的服务器数量
+    pub fn matched_count(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|result| result.status == PropagationStatus::Matched)
+            .count()
+    }
+
+    /// 是否所有服务器都已生效
+    pub fn is_propagated(&self) -> bool {
+        !self.results.is_empty() && self.matched_count() == self.results.len()
+    }
+}
+
+/// 生效检查
+#[derive(Debug, Clone)]
+pub struct PropagationChecker {
+    resolvers: Vec<SocketAddr>,
+    /// 查询权威服务器使用的端口
+    authoritative_port: u16,
+    timeout: Duration,
+}
+
+impl PropagationChecker {
+    pub fn new(resolvers: Vec<SocketAddr>) -> Self {
+        Self {
+            resolvers,
+            authoritative_port: resolver::DNS_PORT,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// 使用配置中的递归解析服务器
+    pub fn from_config(config: &PropagationConfig) -> Result<Self> {
+        Ok(Self::new(config.resolver_addrs()?))
+    }
+
+    /// 设置查询权威服务器使用的端口
+    pub fn with_authoritative_port(mut self, port: u16) -> Self {
+        self.authoritative_port = port;
+        self
+    }
+
+    /// 设置单次查询超时
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// 检查 `domain` 下主机记录 `rr` 的记录是否在各服务器生效
+    pub async fn check(
+        &self,
+        domain: &str,
+        rr: &str,
+        record_type: &Type,
+        expected: &[String],
+    ) -> Result<PropagationReport> {
+        if self.resolvers.is_empty() {
+            return Err(anyhow!("没有配置用于查询的 DNS 服务器"));
+        }
+        let fqdn = fqdn(domain, rr);
+        let query_type = RecordType::from_str(record_type.get_value())
+            .map_err(|_| anyhow!("不支持检查 {} 类型的记录", record_type))?;
+        info!("检查 {} {} 的生效情况", fqdn, record_type);
+
+        let mut targets: Vec<(String, SocketAddr, ServerKind)> = self
+            .resolvers
+            .iter()
+            .map(|addr| (addr.to_string(), *addr, ServerKind::Resolver))
+            .collect();
+        let mut results = Vec::new();
+        match self.authoritative_servers(domain).await {
+            Ok(servers) => targets.extend(
+                servers
+                    .into_iter()
+                    .map(|(name, addr)| (name, addr, ServerKind::Authoritative)),
+            ),
+            Err(e) => results.push(ServerResult::failed(
+                format!("{} 的权威服务器", domain),
+                None,
+                ServerKind::Authoritative,
+                format!("{:#}", e),
+            )),
+        }
+
+        let queries = targets.into_iter().map(|(server, addr, kind)| {
+            let fqdn = fqdn.clone();
+            async move {
+                let recursion = kind == ServerKind::Resolver;
+                match resolver::query(addr, &fqdn, query_type, recursion, self.timeout).await {
+                    Ok(response) => compare(server, addr, kind, &response, record_type, expected),
+                    Err(e) => {
+                        debug!("向 {} 查询 {} 失败: {:#}", addr, fqdn, e);
+                        ServerResult::failed(server, Some(addr), kind, format!("{:#}", e))
+                    }
+                }
+            }
+        });
+        let mut queried = join_all(queries).await;
+        queried.append(&mut results);
+
+        Ok(PropagationReport {
+            fqdn,
+            record_type: record_type.clone(),
+            expected: expected.to_vec(),
+            results: queried,
+        })
+    }
+
+    /// 通过递归解析服务器查询域名的 NS 服务器及其地址
+    async fn authoritative_servers(&self, domain: &str) -> Result<Vec<(String, SocketAddr)>> {
+        let mut last_error = anyhow!("没有可用的 DNS 服务器");
+        for resolver in &self.resolvers {
+            match self.nameservers_via(*resolver, domain).await {
+                Ok(servers) => return Ok(servers),
+                Err(e) => {
+                    debug!("通过 {} 查询 {} 的 NS 失败: {:#}", resolver, domain, e);
+                    last_error = e;
+                }
+            }
+        }
+        Err(last_error)
+    }
+
+    async fn nameservers_via(
+        &self,
+        resolver: SocketAddr,
+        domain: &str,
+    ) -> Result<Vec<(String, SocketAddr)>> {
+        let response =
+            resolver::query(resolver, domain, RecordType::NS, true, self.timeout).await?;
+        if response.response_code() != ResponseCode::NoError {
+            return Err(anyhow!("查询 NS 返回 {}", response.response_code()));
+        }
+        let names = resolver::answer_values(response.answers(), &Type::NS);
+        if names.is_empty() {
+            return Err(anyhow!("没有查询到 {} 的 NS 记录", domain));
+        }
+
+        let mut servers = Vec::new();
+        for name in names {
+            // 优先使用应答附带的地址，没有时再单独查询
+            let mut ips: Vec<_> = response
+                .additionals()
+                .iter()
+                .filter(|record| record.name().to_ascii().eq_ignore_ascii_case(&name))
+                .filter_map(|record| match record.data() {
+                    Some(RData::A(a)) => Some(std::net::IpAddr::V4(a.0)),
+                    Some(RData::AAAA(aaaa)) => Some(std::net::IpAddr::V6(aaaa.0)),
+                    _ => None,
+                })
+                .collect();
+            if ips.is_empty() {
+                let answer =
+                    resolver::query(resolver, &name, RecordType::A, true, self.timeout).await?;
+                ips = resolver::answer_values(answer.answers(), &Type::A)
+                    .iter()
+                    .filter_map(|ip| ip.parse().ok())
+                    .collect();
+            }
+            let name = name.trim_end_matches('.').to_string();
+            if ips.is_empty() {
+                return Err(anyhow!("没有查询到 NS 服务器 {} 的地址", name));
+            }
+            servers.extend(
+                ips.into_iter()
+                    .map(|ip| (name.clone(), SocketAddr::new(ip, self.authoritative_port))),
+            );
+        }
+        Ok(servers)
+    }
+}
+
+/// 主机记录对应的完整域名，`@` 和空字符串表示域名本身
+pub fn fqdn(domain: &str, rr: &str) -> String {
+    let domain = domain.trim_end_matches('.');
+    match rr.trim() {
+        "" | "@" => domain.to_string(),
+        rr => format!("{}.{}", rr.trim_end_matches('.'), domain),
+    }
+}
+
+/// 对比服务器应答和本地记录
+fn compare(
+    server: String,
+    address: SocketAddr,
+    kind: ServerKind,
+    response: &Message,
+    record_type: &Type,
+    expected: &[String],
+) -> ServerResult {
+    let answers = resolver::answer_values(response.answers(), record_type);
+    let status = match response.response_code() {
+        ResponseCode::NoError | ResponseCode::NXDomain if answers.is_empty() => {
+            PropagationStatus::Missing
+        }
+        ResponseCode::NoError | ResponseCode::NXDomain => {
+            let all_expected = expected.iter().all(|value| {
+                answers
+                    .iter()
+                    .any(|answer| value_matches(record_type, value, answer))
+            });
+            let no_extra = answers.iter().all(|answer| {
+                expected
+                    .iter()
+                    .any(|value| value_matches(record_type, value, answer))
+            });
+            if all_expected && no_extra {
+                PropagationStatus::Matched
+            } else {
+                PropagationStatus::Mismatched
+            }
+        }
+        code => {
+            return ServerResult::failed(
+                server,
+                Some(address),
+                kind,
+                format!("服务器返回 {}", code),
+            )
+        }
+    };
+    ServerResult {
+        server,
+        address: Some(address),
+        kind,
+        status,
+        answers,
+        error: None,
+    }
+}
+
+/// 本地记录值和应答是否一致，部分服务商的 MX 记录值不包含优先级
+fn value_matches(record_type: &Type, expected: &str, answer: &str) -> bool {
+    if same_value(record_type, expected, answer) {
+        return true;
+    }
+    *record_type == Type::MX
+        && expected.split_whitespace().count() == 1
+        && answer
+            .split_whitespace()
+            .last()
+            .is_some_and(|exchange| same_value(record_type, expected, exchange))
+}
+
+/// 检查本地保存的记录，同一主机记录和类型的所有值一起对比
+pub async fn check_record(
+    conn: &DatabaseConnection,
+    checker: &PropagationChecker,
+    record_id: i64,
+) -> Result<PropagationReport> {
+    let record = records::find_record_by_id(conn, record_id)
+        .await
+        .map_err(|e| anyhow!("查询记录失败: {}", e))?
+        .ok_or_else(|| anyhow!("记录 {} 不存在", record_id))?;
+    let domain = domains::find_domain_by_id(conn, record.domain_id)
+        .await
+        .map_err(|e| anyhow!("查询域名失败: {}", e))?
+        .ok_or_else(|| anyhow!("域名 ID {} 不存在", record.domain_id))?;
+    let record_type = parse_record_type(&record.record_type.to_ascii_uppercase())
+        .filter(|record_type| *record_type != Type::SOA)
+        .ok_or_else(|| anyhow!("不支持检查 {} 类型的记录", record.record_type))?;
+
+    let expected: Vec<String> = records::get_records_by_domain(conn, Some(record.domain_id))
+        .await?
+        .into_iter()
+        .filter(|other| {
+            other.record_name.eq_ignore_ascii_case(&record.record_name)
+                && other.record_type.eq_ignore_ascii_case(&record.record_type)
+        })
+        .map(|other| other.record_value)
+        .collect();
+
+    checker
+        .check(
+            &domain.domain_name,
+            &record.record_name,
+            &record_type,
+            &expected,
+        )
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fqdn_and_value_matches() {
+        assert_eq!(fqdn("example.com", "@"), "example.com");
+        assert_eq!(fqdn("example.com.", ""), "example.com");
+        assert_eq!(fqdn("example.com", "www"), "www.example.com");
+
+        assert!(value_matches(&Type::A, "192.0.2.1", "192.0.2.1"));
+        assert!(value_matches(
+            &Type::Cname,
+            "Target.Example.com",
+            "target.example.com."
+        ));
+        assert!(value_matches(
+            &Type::MX,
+            "mx.example.com",
+            "10 mx.example.com."
+        ));
+        assert!(value_matches(
+            &Type::MX,
+            "10 mx.example.com",
+            "10 mx.example.com."
+        ));
+        assert!(!value_matches(
+            &Type::MX,
+            "20 mx.example.com",
+            "10 mx.example.com."
+        ));
+        assert!(!value_matches(&Type::TXT, "v=spf1 -all", "v=spf1 ~all"));
+    }
+
+    #[test]
+    fn test_parse_resolvers() {
+        assert_eq!(
+            parse_resolvers("8.8.8.8, 1.1.1.1:53，223.5.5.5").unwrap(),
+            vec!["8.8.8.8", "1.1.1.1:53", "223.5.5.5"]
+        );
+        assert!(parse_resolvers(" , ").is_err());
+        assert!(parse_resolvers("8.8.8.8, dns.google").is_err());
+        assert_eq!(
+            PropagationConfig::default().resolver_addrs().unwrap().len(),
+            DEFAULT_RESOLVERS.len()
+        );
+    }
+}
@@ -0,0 +1,107 @@
+//! 最小的 DNS 查询客户端
+//!
+//! 直接通过 UDP 向指定地址发送一次查询，不使用系统解析配置，
+//! 既可以查询递归解析服务器，也可以关闭递归直接查询权威服务器。
+
+use crate::api::provider::rfc2136::format_rdata;
+use crate::model::dns_record_response::Type;
+use anyhow::{anyhow, Result};
+use hickory_proto::op::{Message, MessageType, OpCode, Query};
+use hickory_proto::rr::{Name, Record, RecordType};
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::time::Duration;
+use tokio::net::UdpSocket;
+use tokio::time::timeout;
+
+/// 默认的 DNS 端口
+pub const DNS_PORT: u16 = 53;
+
+/// 解析 `IP` 或 `IP:端口` 形式的服务器地址，未指定端口时使用 53
+pub fn parse_server(server: &str) -> Result<SocketAddr> {
+    let server = server.trim();
+    if let Ok(addr) = server.parse::<SocketAddr>() {
+        return Ok(addr);
+    }
+    server
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse::<std::net::IpAddr>()
+        .map(|ip| SocketAddr::new(ip, DNS_PORT))
+        .map_err(|_| anyhow!("无效的 DNS 服务器地址: {}", server))
+}
+
+/// 查询 `name` 的指定类型记录，`recursion` 为 false 时用于查询权威服务器
+pub async fn query(
+    server: SocketAddr,
+    name: &str,
+    record_type: RecordType,
+    recursion: bool,
+    query_timeout: Duration,
+) -> Result<Message> {
+    let name = Name::from_str(&format!("{}.", name.trim_end_matches('.')))
+        .map_err(|e| anyhow!("无效的域名 {}: {}", name, e))?;
+    let mut request = Message::new();
+    request
+        .set_id(rand::random())
+        .set_message_type(MessageType::Query)
+        .set_op_code(OpCode::Query)
+        .set_recursion_desired(recursion)
+        .add_query(Query::query(name, record_type));
+    let request_bytes = request.to_vec()?;
+
+    let bind_addr = if server.is_ipv4() {
+        "0.0.0.0:0"
+    } else {
+        "[::]:0"
+    };
+    let socket = UdpSocket::bind(bind_addr).await?;
+    socket.connect(server).await?;
+    socket.send(&request_bytes).await?;
+
+    let mut buffer = vec![0u8; 4096];
+    loop {
+        let len = timeout(query_timeout, socket.recv(&mut buffer))
+            .await
+            .map_err(|_| anyhow!("查询超时"))??;
+        let response = Message::from_vec(&buffer[..len])?;
+        // 忽略不属于本次查询的响应
+        if response.id() == request.id() {
+            return Ok(response);
+        }
+    }
+}
+
+/// 应答中指定类型记录的值，格式与本地保存的记录值一致
+///
+/// 查询 A 等类型时应答中可能带有 CNAME 链，这里只保留查询的类型。
+pub fn answer_values(records: &[Record], record_type: &Type) -> Vec<String> {
+    records
+        .iter()
+        .filter_map(|record| record.data().and_then(format_rdata))
+        .filter(|(answer_type, _)| answer_type == record_type)
+        .map(|(_, value)| value)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_server() {
+        assert_eq!(
+            parse_server("8.8.8.8").unwrap(),
+            "8.8.8.8:53".parse().unwrap()
+        );
+        assert_eq!(
+            parse_server(" 127.0.0.1:5353 ").unwrap(),
+            "127.0.0.1:5353".parse().unwrap()
+        );
+        assert_eq!(
+            parse_server("[2001:4860:4860::8888]").unwrap(),
+            "[2001:4860:4860::8888]:53".parse().unwrap()
+        );
+        assert!(parse_server("dns.google").is_err());
+    }
+}
@@ -1,5 +1,5 @@
 use crate::models::account::{Account, ApiKey, NewAccount};
-use crate::storage::encryption::encrypt_data;
+use crate::storage::encryption::{encrypt_data, DatabaseKeyManager, MasterKey};
 use crate::storage::{entities, AccountActiveModel, AccountEntity};
 use iced::futures::TryFutureExt;
 use sea_orm::{ActiveModelTrait, ActiveValue, DatabaseConnection, EntityTrait, QueryOrder};
@@ -13,6 +13,11 @@ pub async fn create_account(
     new_account: NewAccount,
 ) -> Result<Account, String> {
     let id = Default::default();
+    let credential_data = new_account.credential.raw_data();
+    let sealed_data = DatabaseKeyManager::seal(&credential_data).map_err(|err| {
+        error!("加密账号凭证失败:{:?}", err);
+        format!("加密账号凭证失败:{}", err)
+    })?;
 
     let active_model = AccountActiveModel {
         id,
@@ -20,7 +25,7 @@ pub async fn create_account(
         salt: ActiveValue::Set("123123".into()),
         last_login: Default::default(),
         credential_type: ActiveValue::Set(new_account.credential.credential_type()),
-        credential_data: ActiveValue::Set(new_account.credential.raw_data().into()),
+        credential_data: ActiveValue::Set(sealed_data),
         provider_type: ActiveValue::Set(new_account.provider.value().into()),
         created_at: Default::default(),
         updated_at: Default::default(),
@@ -38,7 +43,7 @@ pub async fn create_account(
                 id: model.id,
                 username: model.name.clone(),
                 email: model.name.clone(),
-                credential_data: model.credential_data,
+                credential_data,
                 salt: "salt".to_string(),
                 api_keys: vec![],
                 created_at: model.created_at.to_string(),
@@ -69,21 +74,23 @@ pub async fn list_accounts(
 
     info!("查询到的账号列表:{}", &accounts.len());
 
-    let account_list: Vec<Account> = accounts
+    let account_list = accounts
         .into_iter()
-        .map(|account| Account {
-            id: account.id,
-            username: account.name,
-            email: "".to_string(),
-            salt: "".to_string(),
-            api_keys: vec![],
-            created_at: "".to_string(),
-            last_login: account.last_login.map(|date| date.to_string()),
-            credential_type: account.credential_type,
-            credential_data: account.credential_data,
-            provider_type: account.provider_type,
+        .map(|account| {
+            Ok(Account {
+                id: account.id,
+                username: account.name,
+                email: "".to_string(),
+                salt: "".to_string(),
+                api_keys: vec![],
+                created_at: "".to_string(),
+                last_login: account.last_login.map(|date| date.to_string()),
+                credential_type: account.credential_type,
+                credential_data: open_credential(&account.credential_data)?,
+                provider_type: account.provider_type,
+            })
         })
-        .collect();
+        .collect::<Result<Vec<Account>, Box<dyn Error + Send>>>()?;
 
     Ok(account_list)
 
@@ -123,18 +130,30 @@ pub async fn get_account_by_id(
         Box::new(e) as Box<dyn Error + Send>
     })?;
 
-    Ok(account.map(|model| Account {
-        id: model.id,
-        username: model.name,
-        email: "".to_string(), // 数据库中没有 email 字段
-        salt: "".to_string(),
-        api_keys: vec![],
-        created_at: model.created_at.to_string(),
-        last_login: model.last_login.map(|d| d.to_string()),
-        credential_type: model.credential_type,
-        credential_data: model.credential_data,
-        provider_type: model.provider_type,
-    }))
+    account
+        .map(|model| {
+            Ok(Account {
+                id: model.id,
+                username: model.name,
+                email: "".to_string(), // 数据库中没有 email 字段
+                salt: "".to_string(),
+                api_keys: vec![],
+                created_at: model.created_at.to_string(),
+                last_login: model.last_login.map(|d| d.to_string()),
+                credential_type: model.credential_type,
+                credential_data: open_credential(&model.credential_data)?,
+                provider_type: model.provider_type,
+            })
+        })
+        .transpose()
+}
+
+/// 解密数据库中的账户凭证
+fn open_credential(credential_data: &str) -> Result<String, Box<dyn Error + Send>> {
+    DatabaseKeyManager::open(credential_data).map_err(|e| {
+        error!("解密账户凭证失败: {}", e);
+        Box::new(std::io::Error::other(e.to_string())) as Box<dyn Error + Send>
+    })
 }
 
 /// 验证用户登录
@@ -230,11 +249,12 @@ pub async fn update_account(
     use crate::storage::entities::account;
     use sea_orm::{ActiveModelTrait, ActiveValue};
 
+    let credential_data = DatabaseKeyManager::seal(&account.credential_data)?;
     let active_model = account::ActiveModel {
         id: ActiveValue::Set(account.id),
         name: ActiveValue::Set(account.username.clone()),
         provider_type: ActiveValue::Set(account.provider_type.clone()),
-        credential_data: ActiveValue::Set(account.credential_data.clone()),
+        credential_data: ActiveValue::Set(credential_data),
         credential_type: ActiveValue::Set(account.credential_type.clone()),
         updated_at: ActiveValue::Set(Some(chrono::Utc::now().naive_utc())),
         ..Default::default()
@@ -254,7 +274,7 @@ pub fn add_api_key(
     _account_id: i32,
     _key_name: &str,
     key_value: &SecretString,
-    master_key: &SecretString,
+    master_key: &MasterKey,
 ) -> Result<ApiKey, Box<dyn Error>> {
     let _encrypted_key = encrypt_data(key_value, master_key)?;
 
@@ -0,0 +1,89 @@
+//! 证书监控检查结果的数据访问层
+//!
+//! 每个目标按检查来源（Hub 或某个 Agent）只保留最近一次结果，作为证书清单展示。
+
+use crate::storage::entities::certificate_check::{ActiveModel, Column, Entity, Model};
+use chrono::NaiveDateTime;
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter,
+    QueryOrder,
+};
+use tracing::{error, info};
+use ActiveValue::Set;
+
+/// 待保存的检查结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewCertificateCheck {
+    pub target: String,
+    pub server_name: String,
+    pub agent_id: Option<String>,
+    pub subject: Option<String>,
+    pub issuer: Option<String>,
+    pub sans: Vec<String>,
+    pub not_before: Option<NaiveDateTime>,
+    pub not_after: Option<NaiveDateTime>,
+    pub fingerprint: Option<String>,
+    pub chain_length: i32,
+    pub issues: Vec<String>,
+    pub error: Option<String>,
+    pub checked_at: NaiveDateTime,
+}
+
+/// 保存检查结果，覆盖同一目标和来源的上一次结果
+pub async fn save_certificate_check(
+    conn: &DatabaseConnection,
+    check: NewCertificateCheck,
+) -> Result<Model, String> {
+    let mut query = Entity::find().filter(Column::Target.eq(&check.target));
+    query = match &check.agent_id {
+        Some(agent_id) => query.filter(Column::AgentId.eq(agent_id)),
+        None => query.filter(Column::AgentId.is_null()),
+    };
+    let existing = query
+        .one(conn)
+        .await
+        .map_err(|err| format!("查询证书检查结果失败: {}", err))?;
+
+    let model = ActiveModel {
+        id: match &existing {
+            Some(existing) => Set(existing.id),
+            None => Default::default(),
+        },
+        target: Set(check.target),
+        server_name: Set(check.server_name),
+        agent_id: Set(check.agent_id),
+        subject: Set(check.subject),
+        issuer: Set(check.issuer),
+        sans: Set(check.sans.join(",")),
+        not_before: Set(check.not_before),
+        not_after: Set(check.not_after),
+        fingerprint: Set(check.fingerprint),
+        chain_length: Set(check.chain_length),
+        issues: Set(check.issues.join(",")),
+        error: Set(check.error),
+        checked_at: Set(check.checked_at),
+    };
+    let result = match existing {
+        Some(_) => model.update(conn).await,
+        None => model.insert(conn).await,
+    };
+    let model = result.map_err(|err| {
+        error!("保存证书检查结果发生了异常: {}", err);
+        format!("保存证书检查结果失败: {}", err)
+    })?;
+    info!(
+        "证书检查结果已保存: {} 有效期至 {:?} 问题 [{}]",
+        model.target, model.not_after, model.issues
+    );
+    Ok(model)
+}
+
+/// 查询证书清单，按到期时间排序，连接失败的目标排在最前
+pub async fn list_certificate_checks(conn: &DatabaseConnection) -> Result<Vec<Model>, String> {
+    Entity::find()
+        .order_by_asc(Column::NotAfter)
+        .order_by_asc(Column::Target)
+        .all(conn)
+        .await
+        .map_err(|err| format!("查询证书检查结果失败: {}", err))
+}
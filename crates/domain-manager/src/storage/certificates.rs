@@ -0,0 +1,140 @@
+//! ACME 账户和证书的数据访问层
+//!
+//! 账户私钥和证书私钥与账户凭证一样，使用主密钥加密后保存。
+
+use crate::storage::encryption::DatabaseKeyManager;
+use crate::storage::entities::{acme_account, certificate};
+use chrono::NaiveDateTime;
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter,
+    QueryOrder,
+};
+use tracing::{error, info};
+use ActiveValue::Set;
+
+/// 待保存的 ACME 账户
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewAcmeAccount {
+    pub directory_url: String,
+    pub contact: Option<String>,
+    pub account_url: String,
+    /// Base64 编码的 PKCS#8 私钥（明文，保存前加密）
+    pub key_data: String,
+}
+
+/// 待保存的证书
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewCertificate {
+    pub domain_id: Option<i64>,
+    pub names: Vec<String>,
+    pub directory_url: String,
+    pub certificate_pem: String,
+    /// PEM 格式的私钥（明文，保存前加密）
+    pub private_key_pem: String,
+    pub not_before: NaiveDateTime,
+    pub not_after: NaiveDateTime,
+}
+
+/// 按 ACME 目录查询账户
+pub async fn find_acme_account(
+    conn: &DatabaseConnection,
+    directory_url: &str,
+) -> Result<Option<acme_account::Model>, String> {
+    acme_account::Entity::find()
+        .filter(acme_account::Column::DirectoryUrl.eq(directory_url))
+        .one(conn)
+        .await
+        .map_err(|err| format!("查询 ACME 账户失败: {}", err))
+}
+
+/// 保存 ACME 账户，私钥加密后写入
+pub async fn save_acme_account(
+    conn: &DatabaseConnection,
+    account: NewAcmeAccount,
+) -> Result<acme_account::Model, String> {
+    let key_data = DatabaseKeyManager::seal(&account.key_data)
+        .map_err(|err| format!("加密 ACME 账户私钥失败: {}", err))?;
+    let model = acme_account::ActiveModel {
+        id: Default::default(),
+        directory_url: Set(account.directory_url),
+        contact: Set(account.contact),
+        account_url: Set(account.account_url),
+        key_data: Set(key_data),
+        created_at: Set(chrono::Utc::now().naive_utc()),
+    }
+    .insert(conn)
+    .await
+    .map_err(|err| {
+        error!("保存 ACME 账户发生了异常: {}", err);
+        format!("保存 ACME 账户失败: {}", err)
+    })?;
+    info!("ACME 账户已保存: {}", model.account_url);
+    Ok(model)
+}
+
+/// 解密 ACME 账户私钥
+pub fn open_account_key(account: &acme_account::Model) -> Result<String, String> {
+    DatabaseKeyManager::open(&account.key_data)
+        .map_err(|err| format!("解密 ACME 账户私钥失败: {}", err))
+}
+
+/// 保存证书，私钥加密后写入
+pub async fn save_certificate(
+    conn: &DatabaseConnection,
+    certificate: NewCertificate,
+) -> Result<certificate::Model, String> {
+    let private_key = DatabaseKeyManager::seal(&certificate.private_key_pem)
+        .map_err(|err| format!("加密证书私钥失败: {}", err))?;
+    let model = certificate::ActiveModel {
+        id: Default::default(),
+        domain_id: Set(certificate.domain_id),
+        names: Set(certificate.names.join(",")),
+        directory_url: Set(certificate.directory_url),
+        certificate_pem: Set(certificate.certificate_pem),
+        private_key: Set(private_key),
+        not_before: Set(certificate.not_before),
+        not_after: Set(certificate.not_after),
+        created_at: Set(chrono::Utc::now().naive_utc()),
+    }
+    .insert(conn)
+    .await
+    .map_err(|err| {
+        error!("保存证书发生了异常: {}", err);
+        format!("保存证书失败: {}", err)
+    })?;
+    info!("证书已保存: {} 有效期至 {}", model.names, model.not_after);
+    Ok(model)
+}
+
+/// 按签发时间倒序查询证书，可按域名过滤
+pub async fn list_certificates(
+    conn: &DatabaseConnection,
+    domain_id: Option<i64>,
+) -> Result<Vec<certificate::Model>, String> {
+    let mut query = certificate::Entity::find();
+    if let Some(domain_id) = domain_id {
+        query = query.filter(certificate::Column::DomainId.eq(domain_id));
+    }
+    query
+        .order_by_desc(certificate::Column::Id)
+        .all(conn)
+        .await
+        .map_err(|err| format!("查询证书失败: {}", err))
+}
+
+/// 按ID查询证书
+pub async fn get_certificate(
+    conn: &DatabaseConnection,
+    id: i64,
+) -> Result<Option<certificate::Model>, String> {
+    certificate::Entity::find_by_id(id)
+        .one(conn)
+        .await
+        .map_err(|err| format!("查询证书失败: {}", err))
+}
+
+/// 解密证书私钥
+pub fn open_private_key(certificate: &certificate::Model) -> Result<String, String> {
+    DatabaseKeyManager::open(&certificate.private_key)
+        .map_err(|err| format!("解密证书私钥失败: {}", err))
+}
@@ -120,6 +120,7 @@ pub async fn init_database(database_config: &DatabaseConfig) -> anyhow::Result<D
 /// 创建内存数据库（用于测试）
 #[cfg(test)]
 pub async fn init_memory_database() -> anyhow::Result<DatabaseConnection> {
+    crate::tests::test_utils::init_test_master_key();
     let result = Database::connect("sqlite::memory:")
         .await
         .with_context(|| anyhow::anyhow!("初始化数据库连接失败!"));
@@ -0,0 +1,70 @@
+//! DDNS 更新事件的数据访问层
+
+use crate::storage::entities::ddns_event::{ActiveModel, Column, Entity, Model};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter,
+    QueryOrder, QuerySelect,
+};
+use tracing::{error, info};
+use ActiveValue::Set;
+
+/// 待保存的 DDNS 更新事件
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewDdnsEvent {
+    pub agent_id: String,
+    pub domain: String,
+    pub record_type: String,
+    pub old_ip: Option<String>,
+    pub new_ip: String,
+    pub mode: String,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+/// 保存一条 DDNS 更新事件
+pub async fn record_ddns_event(
+    conn: &DatabaseConnection,
+    event: NewDdnsEvent,
+) -> Result<Model, String> {
+    let model = ActiveModel {
+        id: Default::default(),
+        agent_id: Set(event.agent_id),
+        domain: Set(event.domain),
+        record_type: Set(event.record_type),
+        old_ip: Set(event.old_ip),
+        new_ip: Set(event.new_ip),
+        mode: Set(event.mode),
+        success: Set(event.success),
+        error: Set(event.error),
+        created_at: Set(chrono::Utc::now().naive_utc()),
+    }
+    .insert(conn)
+    .await
+    .map_err(|err| {
+        error!("保存 DDNS 事件发生了异常: {}", err);
+        format!("保存 DDNS 事件失败: {}", err)
+    })?;
+    info!(
+        "DDNS 事件已保存: {} {} {:?} -> {} ({})",
+        model.domain, model.record_type, model.old_ip, model.new_ip, model.mode
+    );
+    Ok(model)
+}
+
+/// 按时间倒序查询 DDNS 事件，可按域名过滤
+pub async fn list_ddns_events(
+    conn: &DatabaseConnection,
+    domain: Option<&str>,
+    limit: u64,
+) -> Result<Vec<Model>, String> {
+    let mut query = Entity::find();
+    if let Some(domain) = domain {
+        query = query.filter(Column::Domain.eq(domain));
+    }
+    query
+        .order_by_desc(Column::Id)
+        .limit(limit)
+        .all(conn)
+        .await
+        .map_err(|err| format!("查询 DDNS 事件失败: {}", err))
+}
@@ -1,7 +1,8 @@
-use crate::models::domain::{DomainEntity, DomainStatus, NewDomain};
+use crate::models::domain::{DomainEntity, DomainRegistration, DomainStatus, NewDomain};
 use crate::storage::entities::domain;
 use crate::storage::{entities, DomainDbEntity};
 use anyhow::Context;
+use chrono::{Duration, Utc};
 use entities::dns_record::Entity as DnsRecord;
 use iced::futures::TryFutureExt;
 use sea_orm::{
@@ -21,8 +22,8 @@ pub async fn add_domain(
         name: Set(new_domain.domain_name),
         provider_id: Set(new_domain.account_id),
         status: Set(new_domain.status.to_string().into()),
-        created_at: Default::default(),
-        updated_at: Default::default(),
+        registrar: Set(new_domain.registrar),
+        ..Default::default()
     };
 
     Ok(new_domain
@@ -38,17 +39,7 @@ pub async fn add_domain(
         })
         .map(|model| {
             info!("数据插入成功");
-            DomainEntity {
-                id: model.id,
-                account_id: model.provider_id,
-                domain_name: model.name,
-                registration_date: None,
-                expiration_date: None,
-                registrar: None,
-                status: DomainStatus::Active,
-                created_at: model.created_at.to_string(),
-                updated_at: model.updated_at,
-            }
+            to_domain_entity(model)
         })
         .context("新增域名操作失败")?)
 }
@@ -70,8 +61,8 @@ pub async fn add_domain_many(
             name: Set(domain.domain_name),
             provider_id: Set(domain.account_id),
             status: Set(String::from(domain.status.to_string())),
-            created_at: Default::default(),
-            updated_at: Default::default(),
+            registrar: Set(domain.registrar),
+            ..Default::default()
         })
         .collect();
 
@@ -152,17 +143,7 @@ pub async fn get_account_domains(
         })
         .await?
         .into_iter()
-        .map(|domain| DomainEntity {
-            id: domain.id,
-            account_id: domain.provider_id,
-            domain_name: domain.name,
-            registration_date: None,
-            expiration_date: None,
-            registrar: None,
-            status: DomainStatus::Active,
-            created_at: domain.created_at.to_string(),
-            updated_at: domain.updated_at,
-        })
+        .map(to_domain_entity)
         .collect();
     info!(
         "查询域名信息成功，账号表示：「{}」，查询结果数量：「{}」",
@@ -183,17 +164,7 @@ pub async fn list_domains(conn: &DatabaseConnection) -> Result<Vec<DomainEntity>
         })
         .await?
         .into_iter()
-        .map(|domain| DomainEntity {
-            id: domain.id,
-            account_id: domain.provider_id,
-            domain_name: domain.name,
-            registration_date: None,
-            expiration_date: None,
-            registrar: None,
-            status: DomainStatus::Active,
-            created_at: domain.created_at.to_string(),
-            updated_at: domain.updated_at,
-        })
+        .map(to_domain_entity)
         .collect();
     Ok(domain_list)
 }
@@ -213,21 +184,28 @@ pub async fn find_domain_by_name(
         })?;
 
     match domain_model {
-        Some(domain) => Ok(Some(DomainEntity {
-            id: domain.id,
-            account_id: domain.provider_id,
-            domain_name: domain.name,
-            registration_date: None,
-            expiration_date: None,
-            registrar: None,
-            status: DomainStatus::Active,
-            created_at: domain.created_at.to_string(),
-            updated_at: domain.updated_at,
-        })),
+        Some(domain) => Ok(Some(to_domain_entity(domain))),
         None => Ok(None),
     }
 }
 
+/// 按最长后缀匹配完整域名所属的域名，如 `www.example.com` 匹配 `example.com`
+pub async fn find_domain_by_fqdn(
+    conn: &DatabaseConnection,
+    fqdn: &str,
+) -> Result<Option<DomainEntity>, Box<dyn Error>> {
+    let fqdn = fqdn.trim_end_matches('.').to_ascii_lowercase();
+    let domain = list_domains(conn)
+        .await?
+        .into_iter()
+        .filter(|domain| {
+            let name = domain.domain_name.to_ascii_lowercase();
+            fqdn == name || fqdn.ends_with(&format!(".{}", name))
+        })
+        .max_by_key(|domain| domain.domain_name.len());
+    Ok(domain)
+}
+
 /// 根据ID查找域名
 pub async fn find_domain_by_id(
     conn: &DatabaseConnection,
@@ -242,17 +220,7 @@ pub async fn find_domain_by_id(
         })?;
 
     match domain_model {
-        Some(domain) => Ok(Some(DomainEntity {
-            id: domain.id,
-            account_id: domain.provider_id,
-            domain_name: domain.name,
-            registration_date: None,
-            expiration_date: None,
-            registrar: None,
-            status: DomainStatus::Active,
-            created_at: domain.created_at.to_string(),
-            updated_at: domain.updated_at,
-        })),
+        Some(domain) => Ok(Some(to_domain_entity(domain))),
         None => Ok(None),
     }
 }
@@ -276,17 +244,7 @@ pub async fn find_domain_by_name_and_account(
     match domain_model {
         Some(domain) => {
             info!("找到域名: {} (ID: {})", domain_name, domain.id);
-            Ok(Some(DomainEntity {
-                id: domain.id,
-                account_id: domain.provider_id,
-                domain_name: domain.name,
-                registration_date: None,
-                expiration_date: None,
-                registrar: None,
-                status: DomainStatus::Active,
-                created_at: domain.created_at.to_string(),
-                updated_at: domain.updated_at,
-            }))
+            Ok(Some(to_domain_entity(domain)))
         }
         None => {
             info!("未找到域名: {}", domain_name);
@@ -307,52 +265,95 @@ pub async fn count_all_domains(conn: &DatabaseConnection) -> Result<u64, Box<dyn
     Ok(count_result)
 }
 
-/// 获取即将过期的域名
-pub fn get_expiring_domains(
-    _conn: &DatabaseConnection,
-    _days: i32,
+/// 获取指定天数内到期的域名，包括已经过期的域名，按到期时间排序
+pub async fn get_expiring_domains(
+    conn: &DatabaseConnection,
+    days: i64,
 ) -> Result<Vec<DomainEntity>, Box<dyn Error>> {
-    // let expiration_threshold = Utc::now() + chrono::Duration::days(days);
-    //
-    // let mut stmt = conn.prepare(
-    //     "SELECT
-    //         id,
-    //         account_id,
-    //         domain_name,
-    //         registration_date,
-    //         expiration_date,
-    //         registrar,
-    //         status,
-    //         created_at,
-    //         updated_at
-    //      FROM domains
-    //      WHERE expiration_date <= ?1
-    //         AND status = 'active'",
-    // )?;
-    //
-    // let domain_iter = stmt.query_map([expiration_threshold.to_string()], |row| {
-    //     let status_str: String = row.get(6)?;
-    //
-    //     Ok(DomainEntity {
-    //         id: row.get(0)?,
-    //         account_id: row.get(1)?,
-    //         domain_name: row.get(2)?,
-    //         registration_date: row.get(3)?,
-    //         expiration_date: row.get(4)?,
-    //         registrar: row.get(5)?,
-    //         status: DomainStatus::from_str(&status_str).unwrap_or(DomainStatus::Active),
-    //         created_at: row.get(7)?,
-    //         updated_at: row.get(8)?,
-    //     })
-    // })?;
-    //
-    // let mut domains = Vec::new();
-    // for domain in domain_iter {
-    //     domains.push(domain?);
-    // }
+    let threshold = Utc::now().naive_utc() + Duration::days(days);
+    let domain_list: Vec<DomainEntity> = DomainDbEntity::find()
+        .filter(domain::Column::ExpirationDate.is_not_null())
+        .filter(domain::Column::ExpirationDate.lte(threshold))
+        .order_by_asc(domain::Column::ExpirationDate)
+        .order_by_asc(domain::Column::Name)
+        .all(conn)
+        .await
+        .map_err(|e| {
+            error!("查询即将过期的域名失败: {}", e);
+            Box::new(e) as Box<dyn Error>
+        })?
+        .into_iter()
+        .map(to_domain_entity)
+        .collect();
+    info!(
+        "查询 {} 天内到期的域名，结果数量：「{}」",
+        days,
+        domain_list.len()
+    );
+    Ok(domain_list)
+}
+
+/// 保存查询到的注册信息，到期时间变化（如已续费）时清空已发送的提醒
+pub async fn update_domain_registration(
+    conn: &DatabaseConnection,
+    domain_id: i64,
+    registration: &DomainRegistration,
+) -> Result<(), Box<dyn Error>> {
+    let model = DomainDbEntity::find_by_id(domain_id)
+        .one(conn)
+        .await?
+        .ok_or_else(|| format!("域名不存在: {}", domain_id))?;
+    let renewed = model.expiration_date != registration.expiration_date;
+
+    let mut active: domain::ActiveModel = model.into();
+    active.registration_date = Set(registration.registration_date);
+    active.expiration_date = Set(registration.expiration_date);
+    active.registrar = Set(registration.registrar.clone());
+    if renewed {
+        active.reminded_days = Set(None);
+    }
+    active.updated_at = Set(Some(Utc::now().naive_utc()));
+    active.update(conn).await.map_err(|e| {
+        error!("保存域名注册信息失败: {}", e);
+        Box::new(e) as Box<dyn Error>
+    })?;
+    Ok(())
+}
 
-    // Ok(domains)
-    Ok(vec![])
+/// 记录已发送的提醒阈值，避免同一阈值重复提醒
+pub async fn set_reminded_days(
+    conn: &DatabaseConnection,
+    domain_id: i64,
+    days: Option<i32>,
+) -> Result<(), Box<dyn Error>> {
+    domain::ActiveModel {
+        id: Set(domain_id),
+        reminded_days: Set(days),
+        ..Default::default()
+    }
+    .update(conn)
+    .await
+    .map_err(|e| {
+        error!("记录域名到期提醒失败: {}", e);
+        Box::new(e) as Box<dyn Error>
+    })?;
+    Ok(())
+}
+
+/// 数据库实体转换为域名模型
+fn to_domain_entity(domain: domain::Model) -> DomainEntity {
+    DomainEntity {
+        id: domain.id,
+        account_id: domain.provider_id,
+        domain_name: domain.name,
+        registration_date: domain.registration_date,
+        expiration_date: domain.expiration_date,
+        registrar: domain.registrar,
+        status: DomainStatus::from_str(&domain.status).unwrap_or(DomainStatus::Active),
+        created_at: domain.created_at.to_string(),
+        updated_at: domain.updated_at,
+        reminded_days: domain.reminded_days,
+    }
 }
 
 /// 删除域名
@@ -389,17 +390,7 @@ pub async fn list_domains_by_account(
             Box::new(e) as Box<dyn Error>
         })?
         .into_iter()
-        .map(|domain| DomainEntity {
-            id: domain.id,
-            account_id: domain.provider_id,
-            domain_name: domain.name,
-            registration_date: None,
-            expiration_date: None,
-            registrar: None,
-            status: DomainStatus::Active, // TODO: 从数据库或状态获取
-            created_at: domain.created_at.to_string(),
-            updated_at: domain.updated_at,
-        })
+        .map(to_domain_entity)
         .collect();
     Ok(domain_list)
 }
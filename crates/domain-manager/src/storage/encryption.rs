@@ -1,79 +1,473 @@
-use anyhow::Result;
+//! 凭证加密
+//!
+//! 账户凭证使用 AES-256-GCM 加密后写入数据库，格式为 `enc:v1:<Base64(随机数 || 密文)>`。
+//! 主密钥由主密码经 Argon2id 派生，盐和校验值保存在数据库旁的密钥库文件中；
+//! 用户也可以选择把主密钥保存到系统钥匙串，启动时免输入主密码。
+
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use anyhow::{anyhow, bail, Context, Result};
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
 use rand::RngCore;
 use secrecy::{ExposeSecret, SecretBox, SecretString};
-use std::error::Error;
-
-/// 密码哈希配置
-// const ARGON2_CONFIG: Config = Config {
-//     variant: Variant::Argon2id,
-//     version: Version::Version13,
-//     mem_cost: 4096,
-//     time_cost: 3,
-//     lanes: 4,
-//     thread_mode: ThreadMode::Parallel,
-//     secret: &[],
-//     ad: &[],
-//     hash_length: 32,
-// };
-
-/// 哈希密码
-pub fn hash_password(_password: &SecretString) -> (SecretString, String) {
-    let mut salt = [0u8; 16];
-    rand::thread_rng().fill_bytes(&mut salt);
-
-    // let hash = argon2::hash_encoded(password.expose_secret().as_bytes(), &salt, &ARGON2_CONFIG)
-    //     .expect("密码哈希失败");
-
-    ("SecretString::from(hash)".into(), "".to_string())
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use tracing::{info, warn};
+
+/// 加密数据的前缀，用于区分历史明文数据
+const ENCRYPTED_PREFIX: &str = "enc:v1:";
+/// AES-GCM 随机数长度
+const NONCE_LEN: usize = 12;
+/// 主密钥长度
+const KEY_LEN: usize = 32;
+/// 盐长度
+const SALT_LEN: usize = 16;
+/// 密钥库文件名
+const VAULT_FILE_NAME: &str = "domain_manager.vault";
+/// 用于校验主密码的固定明文
+const VERIFIER_PLAINTEXT: &str = "domain-manager-vault";
+/// 系统钥匙串中的服务名和账户名
+const KEYRING_SERVICE: &str = "domain-manager";
+const KEYRING_USER: &str = "master-key";
+
+/// 当前已解锁的主密钥
+static MASTER_KEY: RwLock<Option<MasterKey>> = RwLock::new(None);
+
+/// 主密钥
+pub struct MasterKey(SecretBox<[u8; KEY_LEN]>);
+
+impl MasterKey {
+    /// 使用原始字节创建主密钥
+    pub fn from_bytes(bytes: [u8; KEY_LEN]) -> Self {
+        MasterKey(SecretBox::new(Box::new(bytes)))
+    }
+
+    /// 随机生成主密钥
+    pub fn generate() -> Self {
+        let mut bytes = [0u8; KEY_LEN];
+        rand::rng().fill_bytes(&mut bytes);
+        Self::from_bytes(bytes)
+    }
+
+    /// 使用 Argon2id 从主密码派生主密钥
+    pub fn derive(password: &SecretString, salt: &[u8], params: &KdfParams) -> Result<Self> {
+        let params = Params::new(params.m_cost, params.t_cost, params.p_cost, Some(KEY_LEN))
+            .map_err(|e| anyhow!("无效的密钥派生参数: {}", e))?;
+
+        let mut bytes = [0u8; KEY_LEN];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(password.expose_secret().as_bytes(), salt, &mut bytes)
+            .map_err(|e| anyhow!("派生主密钥失败: {}", e))?;
+        Ok(Self::from_bytes(bytes))
+    }
+
+    /// 从Base64字符串解析主密钥
+    fn from_base64(value: &str) -> Result<Self> {
+        let bytes = STANDARD.decode(value).context("主密钥不是有效的Base64")?;
+        let bytes: [u8; KEY_LEN] = bytes
+            .try_into()
+            .map_err(|_| anyhow!("主密钥长度必须为 {} 字节", KEY_LEN))?;
+        Ok(Self::from_bytes(bytes))
+    }
+
+    /// 转换为Base64字符串，用于保存到系统钥匙串
+    fn to_base64(&self) -> SecretString {
+        SecretString::from(STANDARD.encode(self.0.expose_secret()))
+    }
+
+    fn cipher(&self) -> Aes256Gcm {
+        Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(self.0.expose_secret()))
+    }
+}
+
+impl Clone for MasterKey {
+    fn clone(&self) -> Self {
+        Self::from_bytes(*self.0.expose_secret())
+    }
+}
+
+impl std::fmt::Debug for MasterKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("MasterKey([REDACTED])")
+    }
+}
+
+/// Argon2id 参数
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfParams {
+    /// 内存开销（KiB）
+    pub m_cost: u32,
+    /// 迭代次数
+    pub t_cost: u32,
+    /// 并行度
+    pub p_cost: u32,
+}
+
+impl Default for KdfParams {
+    /// OWASP 推荐的 Argon2id 参数
+    fn default() -> Self {
+        Self {
+            m_cost: 19 * 1024,
+            t_cost: 2,
+            p_cost: 1,
+        }
+    }
+}
+
+/// 密钥库：保存派生主密钥所需的盐和参数，以及用于校验主密码的密文
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Vault {
+    pub version: u32,
+    /// Base64 编码的盐
+    pub salt: String,
+    pub kdf: KdfParams,
+    /// 使用主密钥加密的固定明文
+    pub verifier: String,
+}
+
+impl Vault {
+    /// 使用主密码创建新的密钥库
+    pub fn create(password: &SecretString, kdf: KdfParams) -> Result<(Self, MasterKey)> {
+        let mut salt = [0u8; SALT_LEN];
+        rand::rng().fill_bytes(&mut salt);
+
+        let key = MasterKey::derive(password, &salt, &kdf)?;
+        let verifier = encrypt_data(&SecretString::from(VERIFIER_PLAINTEXT), &key)?;
+
+        let vault = Vault {
+            version: 1,
+            salt: STANDARD.encode(salt),
+            kdf,
+            verifier: verifier.expose_secret().to_string(),
+        };
+        Ok((vault, key))
+    }
+
+    /// 使用主密码解锁密钥库
+    pub fn unlock(&self, password: &SecretString) -> Result<MasterKey> {
+        let salt = STANDARD.decode(&self.salt).context("密钥库中的盐无效")?;
+        let key = MasterKey::derive(password, &salt, &self.kdf)?;
+
+        if !self.verify(&key) {
+            return Err(anyhow!("主密码错误"));
+        }
+        Ok(key)
+    }
+
+    /// 检查主密钥是否与密钥库匹配
+    pub fn verify(&self, key: &MasterKey) -> bool {
+        decrypt_data(&SecretString::from(self.verifier.as_str()), key)
+            .map(|plaintext| plaintext.expose_secret() == VERIFIER_PLAINTEXT)
+            .unwrap_or(false)
+    }
+
+    /// 读取密钥库文件，文件不存在时返回 None
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content =
+            fs::read_to_string(path).with_context(|| format!("读取密钥库失败: {:?}", path))?;
+        let vault = serde_json::from_str(&content)
+            .with_context(|| format!("解析密钥库失败: {:?}", path))?;
+        Ok(Some(vault))
+    }
+
+    /// 保存密钥库文件
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(path, content).with_context(|| format!("保存密钥库失败: {:?}", path))
+    }
+}
+
+/// 哈希密码，返回 PHC 格式的哈希和 Base64 编码的盐
+pub fn hash_password(password: &SecretString) -> Result<(SecretString, String)> {
+    let mut salt = [0u8; SALT_LEN];
+    rand::rng().fill_bytes(&mut salt);
+    let salt = SaltString::encode_b64(&salt).map_err(|e| anyhow!("生成密码盐失败: {}", e))?;
+
+    let hash = Argon2::default()
+        .hash_password(password.expose_secret().as_bytes(), &salt)
+        .map_err(|e| anyhow!("密码哈希失败: {}", e))?;
+
+    Ok((SecretString::from(hash.to_string()), salt.to_string()))
 }
 
 /// 验证密码
-pub fn verify_password(_password: &SecretString, _stored_hash: &String, _salt: &String) -> bool {
-    // argon2::verify_encoded_ext(
-    //     stored_hash.expose_secret(),
-    //     password.expose_secret().as_bytes(),
-    //     salt,
-    //     &[],
-    // )
-    // .unwrap_or(false)
-    false
+pub fn verify_password(password: &SecretString, stored_hash: &str, salt: &str) -> bool {
+    let Ok(hash) = PasswordHash::new(stored_hash) else {
+        return false;
+    };
+    if hash.salt.map(|s| s.as_str()) != Some(salt) {
+        return false;
+    }
+
+    Argon2::default()
+        .verify_password(password.expose_secret().as_bytes(), &hash)
+        .is_ok()
+}
+
+/// 判断数据是否已加密
+pub fn is_encrypted(value: &str) -> bool {
+    value.starts_with(ENCRYPTED_PREFIX)
 }
 
 /// 加密数据
-pub fn encrypt_data(
-    data: &SecretString,
-    _key: &SecretString,
-) -> Result<SecretString, Box<dyn Error>> {
-    // 在实际应用中，应使用AES-GCM等加密算法
-    // 这里简化为base64编码
-    let encrypted = base64::encode(data.expose_secret().as_bytes());
-    Ok(SecretString::from(encrypted))
+pub fn encrypt_data(data: &SecretString, key: &MasterKey) -> Result<SecretString> {
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::rng().fill_bytes(&mut nonce);
+
+    let ciphertext = key
+        .cipher()
+        .encrypt(Nonce::from_slice(&nonce), data.expose_secret().as_bytes())
+        .map_err(|_| anyhow!("加密数据失败"))?;
+
+    let mut payload = nonce.to_vec();
+    payload.extend_from_slice(&ciphertext);
+    Ok(SecretString::from(format!(
+        "{}{}",
+        ENCRYPTED_PREFIX,
+        STANDARD.encode(payload)
+    )))
 }
 
 /// 解密数据
-pub fn decrypt_data(
-    _encrypted_data: &SecretBox<String>,
-    _key: &SecretBox<String>,
-) -> Result<SecretString> {
-    // 实际应用中应使用对应加密算法的解密
-    // let decoded = base64::decode(encrypted_data.expose_secret()).map_err(|e| {
-    //     RusqliteError::FromSqlConversionFailure(0, rusqlite::types::Type::Blob, Box::new(e))
-    // })?;
-    //
-    // let decrypted = String::from_utf8(decoded).map_err(|e| {
-    //     RusqliteError::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e))
-    // })?;
-    Ok(SecretString::from("123"))
+pub fn decrypt_data(encrypted_data: &SecretString, key: &MasterKey) -> Result<SecretString> {
+    let encoded = encrypted_data
+        .expose_secret()
+        .strip_prefix(ENCRYPTED_PREFIX)
+        .ok_or_else(|| anyhow!("数据未加密或格式不支持"))?;
+    let payload = STANDARD.decode(encoded).context("密文不是有效的Base64")?;
+    if payload.len() < NONCE_LEN {
+        return Err(anyhow!("密文长度无效"));
+    }
+
+    let (nonce, ciphertext) = payload.split_at(NONCE_LEN);
+    let plaintext = key
+        .cipher()
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| anyhow!("解密数据失败，主密钥错误或数据已被篡改"))?;
+
+    let plaintext = String::from_utf8(plaintext).context("解密结果不是有效的UTF-8")?;
+    Ok(SecretString::from(plaintext))
 }
 
 /// 数据库密钥管理器
+///
+/// 在进程内保存已解锁的主密钥，存储层通过它加解密账户凭证。
 pub struct DatabaseKeyManager;
 
 impl DatabaseKeyManager {
-    /// 获取数据库加密密钥
-    pub fn get_database_key() -> SecretString {
-        // 实际应用中应从安全存储获取密钥
-        // 例如使用keyring crate访问系统密钥环
-        SecretString::from("strong-encryption-key".to_string())
+    /// 密钥库文件路径，与数据库文件放在同一目录
+    pub fn vault_path() -> PathBuf {
+        crate::configs::get_database_path().with_file_name(VAULT_FILE_NAME)
+    }
+
+    /// 是否已经设置过主密码
+    pub fn has_vault() -> bool {
+        Self::vault_path().exists()
+    }
+
+    /// 主密钥是否已解锁
+    pub fn is_unlocked() -> bool {
+        MASTER_KEY.read().map(|key| key.is_some()).unwrap_or(false)
+    }
+
+    /// 获取当前主密钥
+    pub fn current_key() -> Result<MasterKey> {
+        MASTER_KEY
+            .read()
+            .map_err(|_| anyhow!("读取主密钥失败"))?
+            .clone()
+            .ok_or_else(|| anyhow!("主密钥未解锁"))
+    }
+
+    /// 设置当前主密钥
+    pub fn set_key(key: MasterKey) {
+        if let Ok(mut current) = MASTER_KEY.write() {
+            *current = Some(key);
+        }
+    }
+
+    /// 清除内存中的主密钥
+    pub fn lock() {
+        if let Ok(mut current) = MASTER_KEY.write() {
+            *current = None;
+        }
+    }
+
+    /// 使用主密码解锁，首次使用时创建密钥库
+    ///
+    /// `remember` 为 true 时把主密钥保存到系统钥匙串。
+    pub fn unlock(password: &SecretString, remember: bool) -> Result<()> {
+        Self::unlock_at(&Self::vault_path(), password, KdfParams::default())?;
+
+        if remember {
+            if let Err(err) = Self::store_in_keyring() {
+                warn!("保存主密钥到系统钥匙串失败: {}", err);
+            }
+        }
+        Ok(())
+    }
+
+    /// 使用指定路径的密钥库解锁
+    pub fn unlock_at(path: &Path, password: &SecretString, kdf: KdfParams) -> Result<()> {
+        let key = match Vault::load(path)? {
+            Some(vault) => vault.unlock(password)?,
+            None => {
+                if password.expose_secret().is_empty() {
+                    return Err(anyhow!("主密码不能为空"));
+                }
+                info!("首次使用，创建密钥库: {:?}", path);
+                let (vault, key) = Vault::create(password, kdf)?;
+                vault.save(path)?;
+                key
+            }
+        };
+
+        Self::set_key(key);
+        info!("主密钥已解锁");
+        Ok(())
+    }
+
+    /// 尝试使用系统钥匙串中的主密钥解锁，未保存时返回 false
+    pub fn unlock_from_keyring() -> Result<bool> {
+        let Some(vault) = Vault::load(&Self::vault_path())? else {
+            return Ok(false);
+        };
+
+        let entry = keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)?;
+        let secret = match entry.get_password() {
+            Ok(secret) => secret,
+            Err(keyring::Error::NoEntry) => return Ok(false),
+            Err(err) => return Err(err.into()),
+        };
+
+        let key = MasterKey::from_base64(&secret)?;
+        if !vault.verify(&key) {
+            warn!("系统钥匙串中的主密钥与密钥库不匹配");
+            return Ok(false);
+        }
+
+        Self::set_key(key);
+        info!("已使用系统钥匙串解锁主密钥");
+        Ok(true)
+    }
+
+    /// 把当前主密钥保存到系统钥匙串
+    pub fn store_in_keyring() -> Result<()> {
+        let key = Self::current_key()?;
+        keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)?
+            .set_password(key.to_base64().expose_secret())?;
+        Ok(())
+    }
+
+    /// 从系统钥匙串删除主密钥
+    pub fn forget_keyring() -> Result<()> {
+        match keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// 加密准备写入数据库的凭证
+    pub fn seal(plaintext: &str) -> Result<String> {
+        let key = Self::current_key()?;
+        let sealed = encrypt_data(&SecretString::from(plaintext), &key)?;
+        Ok(sealed.expose_secret().to_string())
+    }
+
+    /// 解密从数据库读取的凭证
+    ///
+    /// 历史明文数据在启动时由迁移 `m20251018_000001` 加密，之后读到未加密的数据说明数据被篡改或迁移未执行。
+    pub fn open(stored: &str) -> Result<String> {
+        if !is_encrypted(stored) {
+            bail!("凭证数据未加密");
+        }
+        let key = Self::current_key()?;
+        let plaintext = decrypt_data(&SecretString::from(stored), &key)?;
+        Ok(plaintext.expose_secret().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 测试用的低开销参数
+    fn test_params() -> KdfParams {
+        KdfParams {
+            m_cost: 256,
+            t_cost: 1,
+            p_cost: 1,
+        }
+    }
+
+    #[test]
+    fn test_encrypt_round_trip() {
+        let key = MasterKey::generate();
+        let data = SecretString::from(r#"{"api_key":"ak","api_secret":"sk"}"#);
+
+        let encrypted = encrypt_data(&data, &key).unwrap();
+        assert!(is_encrypted(encrypted.expose_secret()));
+        assert!(!encrypted.expose_secret().contains("api_secret"));
+        // 每次加密使用不同的随机数
+        let again = encrypt_data(&data, &key).unwrap();
+        assert_ne!(encrypted.expose_secret(), again.expose_secret());
+
+        let decrypted = decrypt_data(&encrypted, &key).unwrap();
+        assert_eq!(decrypted.expose_secret(), data.expose_secret());
+
+        // 密钥错误
+        assert!(decrypt_data(&encrypted, &MasterKey::generate()).is_err());
+
+        // 密文被篡改
+        let mut tampered = encrypted.expose_secret().to_string();
+        let last = tampered.pop().unwrap();
+        tampered.push(if last == 'A' { 'B' } else { 'A' });
+        assert!(decrypt_data(&SecretString::from(tampered), &key).is_err());
+
+        // 迁移后不再接受明文凭证
+        assert!(DatabaseKeyManager::open(data.expose_secret()).is_err());
+    }
+
+    #[test]
+    fn test_vault_unlock() {
+        let password = SecretString::from("correct horse");
+        let (vault, key) = Vault::create(&password, test_params()).unwrap();
+        assert!(vault.verify(&key));
+
+        let unlocked = vault.unlock(&password).unwrap();
+        let data = encrypt_data(&SecretString::from("secret"), &key).unwrap();
+        assert_eq!(
+            decrypt_data(&data, &unlocked).unwrap().expose_secret(),
+            "secret"
+        );
+
+        assert!(vault.unlock(&SecretString::from("wrong")).is_err());
+
+        // 主密钥可以经Base64往返保存
+        let restored = MasterKey::from_base64(key.to_base64().expose_secret()).unwrap();
+        assert!(vault.verify(&restored));
+    }
+
+    #[test]
+    fn test_password_hash() {
+        let password = SecretString::from("12123");
+        let (hash, salt) = hash_password(&password).unwrap();
+        let hash = hash.expose_secret().to_string();
+
+        assert!(hash.starts_with("$argon2id$"));
+        assert!(verify_password(&password, &hash, &salt));
+        assert!(!verify_password(&SecretString::from("x"), &hash, &salt));
+        assert!(!verify_password(&password, &hash, "other"));
     }
 }
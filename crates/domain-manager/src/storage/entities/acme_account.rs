@@ -0,0 +1,26 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// ACME 账户，每个 ACME 目录一个
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "acme_accounts")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = true)]
+    pub id: i64,
+    #[sea_orm(unique)]
+    pub directory_url: String,
+    /// 联系邮箱，多个时逗号分隔
+    #[sea_orm(nullable)]
+    pub contact: Option<String>,
+    /// 服务端返回的账户 URL，请求签名时作为 `kid`
+    pub account_url: String,
+    /// 加密后的账户私钥（PKCS#8）
+    #[serde(skip_serializing)]
+    pub key_data: String,
+    pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
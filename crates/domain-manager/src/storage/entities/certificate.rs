@@ -0,0 +1,29 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 通过 ACME 签发的证书
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "certificates")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = true)]
+    pub id: i64,
+    /// 证书主域名所属的本地域名，未托管在本地的主机为空
+    #[sea_orm(nullable)]
+    pub domain_id: Option<i64>,
+    /// 证书包含的域名，逗号分隔，第一个为通用名称
+    pub names: String,
+    pub directory_url: String,
+    /// PEM 格式的证书链
+    pub certificate_pem: String,
+    /// 加密后的 PEM 格式私钥
+    #[serde(skip_serializing)]
+    pub private_key: String,
+    pub not_before: DateTime,
+    pub not_after: DateTime,
+    pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,43 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 证书监控的检查结果，每个目标和检查来源保留最近一次
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "certificate_checks")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = true)]
+    pub id: i64,
+    /// 检查的目标，`host:port`
+    pub target: String,
+    /// 握手时发送的 SNI 名称
+    pub server_name: String,
+    /// 执行检查的 Agent，由 Hub 检查时为空
+    #[sea_orm(nullable)]
+    pub agent_id: Option<String>,
+    #[sea_orm(nullable)]
+    pub subject: Option<String>,
+    #[sea_orm(nullable)]
+    pub issuer: Option<String>,
+    /// 证书的备用名称，逗号分隔
+    pub sans: String,
+    #[sea_orm(nullable)]
+    pub not_before: Option<DateTime>,
+    #[sea_orm(nullable)]
+    pub not_after: Option<DateTime>,
+    /// 叶子证书的 SHA-256 指纹
+    #[sea_orm(nullable)]
+    pub fingerprint: Option<String>,
+    /// 服务器返回的证书数量
+    pub chain_length: i32,
+    /// 发现的问题，逗号分隔，如 `expiring_soon,self_signed`
+    pub issues: String,
+    /// 连接或解析失败的原因
+    #[sea_orm(nullable)]
+    pub error: Option<String>,
+    pub checked_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
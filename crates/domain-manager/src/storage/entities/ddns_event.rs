@@ -0,0 +1,28 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Agent 上报的 DDNS 更新事件
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "ddns_events")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = true)]
+    pub id: i64,
+    pub agent_id: String,
+    /// 完整域名，如 `home.example.com`
+    pub domain: String,
+    pub record_type: String,
+    #[sea_orm(nullable)]
+    pub old_ip: Option<String>,
+    pub new_ip: String,
+    /// `hub`：由 Hub 更新记录；`direct`：Agent 使用自己的凭证直接更新
+    pub mode: String,
+    pub success: bool,
+    #[sea_orm(nullable)]
+    pub error: Option<String>,
+    pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -11,6 +11,14 @@ pub struct Model {
     pub status: String,
     pub created_at: DateTime,
     pub updated_at: Option<DateTime>,
+    /// 注册时间，从服务商接口或 RDAP/WHOIS 查询
+    pub registration_date: Option<DateTime>,
+    /// 到期时间
+    pub expiration_date: Option<DateTime>,
+    /// 注册商
+    pub registrar: Option<String>,
+    /// 已发送过的最小提醒阈值（天），到期时间变化后清空
+    pub reminded_days: Option<i32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -0,0 +1,32 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 跨服务商迁移的单条记录结果
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "migration_records")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = true)]
+    pub id: i64,
+    /// 同一次迁移的记录使用相同的ID
+    pub migration_id: String,
+    pub domain_name: String,
+    pub source_account_id: i64,
+    pub target_account_id: i64,
+    pub rr: String,
+    pub record_type: String,
+    pub value: String,
+    #[sea_orm(nullable)]
+    pub ttl: Option<i32>,
+    pub status: String,
+    /// 转换警告，每行一条
+    #[sea_orm(nullable)]
+    pub warnings: Option<String>,
+    #[sea_orm(nullable)]
+    pub error: Option<String>,
+    pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
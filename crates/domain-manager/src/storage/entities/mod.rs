@@ -1,6 +1,11 @@
 pub mod account;
+pub mod acme_account;
+pub mod certificate;
+pub mod certificate_check;
+pub mod ddns_event;
 pub mod dns_record;
 pub mod domain;
+pub mod migration_record;
 pub mod provider;
 pub mod agents;
 
@@ -0,0 +1,117 @@
+use crate::storage::encryption::{is_encrypted, DatabaseKeyManager};
+use crate::storage::entities::account;
+use sea_orm::{ActiveModelTrait, ActiveValue, EntityTrait};
+use sea_orm_migration::prelude::*;
+use tracing::info;
+
+/// 使用主密钥重新加密 accounts 表中的历史明文凭证
+///
+/// 表中存在明文凭证时需要先解锁主密钥，否则迁移失败并在下次启动时重试。
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        info!("加密accounts表中的凭证。。。");
+        let conn = manager.get_connection();
+
+        let accounts = account::Entity::find().all(conn).await?;
+        let plaintext_accounts: Vec<_> = accounts
+            .into_iter()
+            .filter(|account| !is_encrypted(&account.credential_data))
+            .collect();
+
+        for account in &plaintext_accounts {
+            let sealed = DatabaseKeyManager::seal(&account.credential_data).map_err(|e| {
+                DbErr::Migration(format!("加密账户 {} 的凭证失败: {}", account.name, e))
+            })?;
+
+            account::ActiveModel {
+                id: ActiveValue::Unchanged(account.id),
+                credential_data: ActiveValue::Set(sealed),
+                ..Default::default()
+            }
+            .update(conn)
+            .await?;
+        }
+
+        info!("已加密 {} 个账户的凭证", plaintext_accounts.len());
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let conn = manager.get_connection();
+
+        for account in account::Entity::find().all(conn).await? {
+            if !is_encrypted(&account.credential_data) {
+                continue;
+            }
+
+            let plaintext = DatabaseKeyManager::open(&account.credential_data).map_err(|e| {
+                DbErr::Migration(format!("解密账户 {} 的凭证失败: {}", account.name, e))
+            })?;
+
+            account::ActiveModel {
+                id: ActiveValue::Unchanged(account.id),
+                credential_data: ActiveValue::Set(plaintext),
+                ..Default::default()
+            }
+            .update(conn)
+            .await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{init_memory_database, list_accounts};
+    use sea_orm::DatabaseConnection;
+
+    const LEGACY_CREDENTIAL: &str = r#"{"api_key":"ak","api_secret":"sk"}"#;
+
+    async fn stored_credential(conn: &DatabaseConnection) -> String {
+        account::Entity::find()
+            .one(conn)
+            .await
+            .unwrap()
+            .unwrap()
+            .credential_data
+    }
+
+    #[tokio::test]
+    async fn test_encrypt_legacy_rows() {
+        let conn = init_memory_database().await.unwrap();
+
+        // 模拟旧版本写入的明文凭证
+        account::ActiveModel {
+            name: ActiveValue::Set("legacy".to_string()),
+            salt: ActiveValue::Set(String::new()),
+            provider_type: ActiveValue::Set("Aliyun".to_string()),
+            credential_type: ActiveValue::Set("ApiKey".to_string()),
+            credential_data: ActiveValue::Set(LEGACY_CREDENTIAL.to_string()),
+            ..Default::default()
+        }
+        .insert(&conn)
+        .await
+        .unwrap();
+
+        let manager = SchemaManager::new(&conn);
+        Migration.up(&manager).await.unwrap();
+        let stored = stored_credential(&conn).await;
+        assert!(is_encrypted(&stored));
+        assert!(!stored.contains("api_secret"));
+
+        // 重复执行不会二次加密
+        Migration.up(&manager).await.unwrap();
+        assert_eq!(stored_credential(&conn).await, stored);
+
+        let accounts = list_accounts(&conn).await.unwrap();
+        assert_eq!(accounts[0].credential_data, LEGACY_CREDENTIAL);
+
+        Migration.down(&manager).await.unwrap();
+        assert_eq!(stored_credential(&conn).await, LEGACY_CREDENTIAL);
+    }
+}
@@ -0,0 +1,74 @@
+use sea_orm_migration::{prelude::*, schema::*};
+use tracing::info;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+enum MigrationRecords {
+    #[sea_orm(iden = "migration_records")]
+    Table,
+    Id,
+    MigrationId,
+    DomainName,
+    SourceAccountId,
+    TargetAccountId,
+    Rr,
+    RecordType,
+    Value,
+    Ttl,
+    Status,
+    Warnings,
+    Error,
+    CreatedAt,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        info!("迁移 migration_records 数据库。。。");
+        manager
+            .create_table(
+                Table::create()
+                    .table(MigrationRecords::Table)
+                    .if_not_exists()
+                    .col(pk_auto(MigrationRecords::Id).big_integer())
+                    .col(string(MigrationRecords::MigrationId))
+                    .col(string(MigrationRecords::DomainName))
+                    .col(big_integer(MigrationRecords::SourceAccountId))
+                    .col(big_integer(MigrationRecords::TargetAccountId))
+                    .col(string(MigrationRecords::Rr))
+                    .col(string(MigrationRecords::RecordType))
+                    .col(text(MigrationRecords::Value))
+                    .col(ColumnDef::new(MigrationRecords::Ttl).integer().null())
+                    .col(string(MigrationRecords::Status))
+                    .col(ColumnDef::new(MigrationRecords::Warnings).text().null())
+                    .col(ColumnDef::new(MigrationRecords::Error).text().null())
+                    .col(
+                        ColumnDef::new(MigrationRecords::CreatedAt)
+                            .date_time()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_migration_records_migration_id")
+                    .table(MigrationRecords::Table)
+                    .col(MigrationRecords::MigrationId)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MigrationRecords::Table).to_owned())
+            .await
+    }
+}
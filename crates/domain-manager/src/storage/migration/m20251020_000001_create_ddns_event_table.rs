@@ -0,0 +1,68 @@
+use sea_orm_migration::{prelude::*, schema::*};
+use tracing::info;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+enum DdnsEvents {
+    #[sea_orm(iden = "ddns_events")]
+    Table,
+    Id,
+    AgentId,
+    Domain,
+    RecordType,
+    OldIp,
+    NewIp,
+    Mode,
+    Success,
+    Error,
+    CreatedAt,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        info!("迁移 ddns_events 数据库。。。");
+        manager
+            .create_table(
+                Table::create()
+                    .table(DdnsEvents::Table)
+                    .if_not_exists()
+                    .col(pk_auto(DdnsEvents::Id).big_integer())
+                    .col(string(DdnsEvents::AgentId))
+                    .col(string(DdnsEvents::Domain))
+                    .col(string(DdnsEvents::RecordType))
+                    .col(ColumnDef::new(DdnsEvents::OldIp).string().null())
+                    .col(string(DdnsEvents::NewIp))
+                    .col(string(DdnsEvents::Mode))
+                    .col(boolean(DdnsEvents::Success))
+                    .col(ColumnDef::new(DdnsEvents::Error).text().null())
+                    .col(
+                        ColumnDef::new(DdnsEvents::CreatedAt)
+                            .date_time()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_ddns_events_domain")
+                    .table(DdnsEvents::Table)
+                    .col(DdnsEvents::Domain)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(DdnsEvents::Table).to_owned())
+            .await
+    }
+}
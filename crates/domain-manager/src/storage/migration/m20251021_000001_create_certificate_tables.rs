@@ -0,0 +1,101 @@
+use sea_orm_migration::{prelude::*, schema::*};
+use tracing::info;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+enum AcmeAccounts {
+    #[sea_orm(iden = "acme_accounts")]
+    Table,
+    Id,
+    DirectoryUrl,
+    Contact,
+    AccountUrl,
+    KeyData,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Certificates {
+    #[sea_orm(iden = "certificates")]
+    Table,
+    Id,
+    DomainId,
+    Names,
+    DirectoryUrl,
+    CertificatePem,
+    PrivateKey,
+    NotBefore,
+    NotAfter,
+    CreatedAt,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        info!("迁移 acme_accounts 和 certificates 数据库。。。");
+        manager
+            .create_table(
+                Table::create()
+                    .table(AcmeAccounts::Table)
+                    .if_not_exists()
+                    .col(pk_auto(AcmeAccounts::Id).big_integer())
+                    .col(string_uniq(AcmeAccounts::DirectoryUrl))
+                    .col(ColumnDef::new(AcmeAccounts::Contact).string().null())
+                    .col(string(AcmeAccounts::AccountUrl))
+                    .col(text(AcmeAccounts::KeyData))
+                    .col(
+                        ColumnDef::new(AcmeAccounts::CreatedAt)
+                            .date_time()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Certificates::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Certificates::Id).big_integer())
+                    .col(ColumnDef::new(Certificates::DomainId).big_integer().null())
+                    .col(string(Certificates::Names))
+                    .col(string(Certificates::DirectoryUrl))
+                    .col(text(Certificates::CertificatePem))
+                    .col(text(Certificates::PrivateKey))
+                    .col(date_time(Certificates::NotBefore))
+                    .col(date_time(Certificates::NotAfter))
+                    .col(
+                        ColumnDef::new(Certificates::CreatedAt)
+                            .date_time()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_certificates_domain_id")
+                    .table(Certificates::Table)
+                    .col(Certificates::DomainId)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Certificates::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(AcmeAccounts::Table).to_owned())
+            .await
+    }
+}
@@ -0,0 +1,88 @@
+use sea_orm_migration::{prelude::*, schema::*};
+use tracing::info;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+enum CertificateChecks {
+    #[sea_orm(iden = "certificate_checks")]
+    Table,
+    Id,
+    Target,
+    ServerName,
+    AgentId,
+    Subject,
+    Issuer,
+    Sans,
+    NotBefore,
+    NotAfter,
+    Fingerprint,
+    ChainLength,
+    Issues,
+    Error,
+    CheckedAt,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        info!("迁移 certificate_checks 数据库。。。");
+        manager
+            .create_table(
+                Table::create()
+                    .table(CertificateChecks::Table)
+                    .if_not_exists()
+                    .col(pk_auto(CertificateChecks::Id).big_integer())
+                    .col(string(CertificateChecks::Target))
+                    .col(string(CertificateChecks::ServerName))
+                    .col(ColumnDef::new(CertificateChecks::AgentId).string().null())
+                    .col(ColumnDef::new(CertificateChecks::Subject).text().null())
+                    .col(ColumnDef::new(CertificateChecks::Issuer).text().null())
+                    .col(text(CertificateChecks::Sans))
+                    .col(
+                        ColumnDef::new(CertificateChecks::NotBefore)
+                            .date_time()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(CertificateChecks::NotAfter)
+                            .date_time()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(CertificateChecks::Fingerprint)
+                            .string()
+                            .null(),
+                    )
+                    .col(integer(CertificateChecks::ChainLength))
+                    .col(string(CertificateChecks::Issues))
+                    .col(ColumnDef::new(CertificateChecks::Error).text().null())
+                    .col(
+                        ColumnDef::new(CertificateChecks::CheckedAt)
+                            .date_time()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_certificate_checks_target")
+                    .table(CertificateChecks::Table)
+                    .col(CertificateChecks::Target)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(CertificateChecks::Table).to_owned())
+            .await
+    }
+}
@@ -0,0 +1,70 @@
+use sea_orm_migration::prelude::*;
+use tracing::info;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+enum Domains {
+    Table,
+    RegistrationDate,
+    ExpirationDate,
+    Registrar,
+    RemindedDays,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        info!("迁移 domains 数据库，添加注册和到期时间。。。");
+        // SQLite 的 ALTER TABLE 每次只能添加一列
+        let columns = [
+            ColumnDef::new(Domains::RegistrationDate)
+                .date_time()
+                .null()
+                .to_owned(),
+            ColumnDef::new(Domains::ExpirationDate)
+                .date_time()
+                .null()
+                .to_owned(),
+            ColumnDef::new(Domains::Registrar)
+                .string()
+                .null()
+                .to_owned(),
+            ColumnDef::new(Domains::RemindedDays)
+                .integer()
+                .null()
+                .to_owned(),
+        ];
+        for mut column in columns {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Domains::Table)
+                        .add_column(&mut column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [
+            Domains::RegistrationDate,
+            Domains::ExpirationDate,
+            Domains::Registrar,
+            Domains::RemindedDays,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Domains::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
@@ -1,7 +1,10 @@
 use crate::storage::migration::{
     m20250712_000001_create_account_table, m20250712_000001_create_dns_record_table,
     m20250712_000001_create_domain_table, m20250712_000001_create_provider_table,
-    m20250720_000001_create_agent_table,
+    m20250720_000001_create_agent_table, m20251018_000001_encrypt_account_credentials,
+    m20251019_000001_create_migration_record_table, m20251020_000001_create_ddns_event_table,
+    m20251021_000001_create_certificate_tables, m20251022_000001_create_certificate_check_table,
+    m20251023_000001_add_domain_expiry_columns,
 };
 pub use sea_orm_migration::prelude::*;
 use tracing::info;
@@ -18,6 +21,12 @@ impl MigratorTrait for Migrator {
             Box::new(m20250712_000001_create_account_table::Migration),
             Box::new(m20250712_000001_create_domain_table::Migration),
             Box::new(m20250720_000001_create_agent_table::Migration),
+            Box::new(m20251018_000001_encrypt_account_credentials::Migration),
+            Box::new(m20251019_000001_create_migration_record_table::Migration),
+            Box::new(m20251020_000001_create_ddns_event_table::Migration),
+            Box::new(m20251021_000001_create_certificate_tables::Migration),
+            Box::new(m20251022_000001_create_certificate_check_table::Migration),
+            Box::new(m20251023_000001_add_domain_expiry_columns::Migration),
         ]
     }
 }
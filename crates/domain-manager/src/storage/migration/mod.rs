@@ -3,4 +3,10 @@ mod m20250712_000001_create_dns_record_table;
 mod m20250712_000001_create_domain_table;
 mod m20250712_000001_create_provider_table;
 mod m20250720_000001_create_agent_table;
+mod m20251018_000001_encrypt_account_credentials;
+mod m20251019_000001_create_migration_record_table;
+mod m20251020_000001_create_ddns_event_table;
+mod m20251021_000001_create_certificate_tables;
+mod m20251022_000001_create_certificate_check_table;
+mod m20251023_000001_add_domain_expiry_columns;
 pub mod migration;
@@ -0,0 +1,62 @@
+//! 跨服务商迁移报告的数据访问层
+
+use crate::storage::entities::migration_record::{ActiveModel, Column, Entity, Model};
+use crate::zone::migrate::MigrationReport;
+use sea_orm::{ActiveValue, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
+use tracing::{error, info};
+use ActiveValue::Set;
+
+/// 保存迁移报告，每条源记录一行
+pub async fn save_migration_report(
+    conn: &DatabaseConnection,
+    report: &MigrationReport,
+) -> Result<usize, String> {
+    if report.items.is_empty() {
+        return Ok(0);
+    }
+
+    let created_at = chrono::Utc::now().naive_utc();
+    let models: Vec<ActiveModel> = report
+        .items
+        .iter()
+        .map(|item| ActiveModel {
+            id: Default::default(),
+            migration_id: Set(report.id.clone()),
+            domain_name: Set(report.domain.clone()),
+            source_account_id: Set(report.source_account_id),
+            target_account_id: Set(report.target_account_id),
+            rr: Set(item.rr.clone()),
+            record_type: Set(item.record_type.clone()),
+            value: Set(item.value.clone()),
+            ttl: Set(item.ttl.map(|ttl| ttl as i32)),
+            status: Set(item.status.as_str().to_string()),
+            warnings: Set((!item.warnings.is_empty()).then(|| item.warnings.join("\n"))),
+            error: Set(item.error.clone()),
+            created_at: Set(created_at),
+        })
+        .collect();
+
+    let count = models.len();
+    Entity::insert_many(models)
+        .exec(conn)
+        .await
+        .map_err(|err| {
+            error!("保存迁移报告发生了异常: {}", err);
+            format!("保存迁移报告失败: {}", err)
+        })?;
+    info!("迁移报告 {} 已保存，共 {} 条记录", report.id, count);
+    Ok(count)
+}
+
+/// 查询某次迁移的全部记录
+pub async fn get_migration_records(
+    conn: &DatabaseConnection,
+    migration_id: &str,
+) -> Result<Vec<Model>, String> {
+    Entity::find()
+        .filter(Column::MigrationId.eq(migration_id))
+        .order_by_asc(Column::Id)
+        .all(conn)
+        .await
+        .map_err(|err| format!("查询迁移报告失败: {}", err))
+}
@@ -1,10 +1,14 @@
 pub mod accounts;
 pub mod agents;
+pub mod certificate_checks;
+pub mod certificates;
 pub mod database;
+pub mod ddns_events;
 pub mod domains;
 pub mod encryption;
 pub mod entities;
 pub mod migration;
+pub mod migration_records;
 pub mod records;
 
 pub use agents::*;
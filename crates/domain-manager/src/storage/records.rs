@@ -155,6 +155,17 @@ pub async fn delete_records_by_domain(
     Ok(delete_result.rows_affected)
 }
 
+/// 用服务商返回的记录替换域名的本地DNS记录
+pub async fn replace_domain_records(
+    conn: &DatabaseConnection,
+    domain_id: i64,
+    new_records: Vec<NewRecord>,
+) -> Result<usize, String> {
+    delete_records_by_domain(conn, domain_id).await?;
+    let inserted = add_records_many(conn, new_records).await?;
+    Ok(inserted.len())
+}
+
 /// 获取用户的所有域名
 pub async fn get_records_by_domain(
     conn: &DatabaseConnection,
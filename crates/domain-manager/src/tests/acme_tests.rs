@@ -0,0 +1,379 @@
+//! ACME 证书申请测试
+//!
+//! 使用内存数据库、模拟的RFC 2136服务器和模拟的ACME服务测试完整的 DNS-01 流程：
+//! - 通过域名所属账户添加 `_acme-challenge` TXT 记录，验证通过后签发证书
+//! - 证书和加密后的私钥保存到数据库，挑战记录被清理
+//! - 再次申请时复用保存的 ACME 账户
+//! - 验证失败时同样清理挑战记录
+//! - HTTP-01 挑战由连接到 Hub 的 Agent 提供，完成后通知 Agent 清理
+//! - 设置 `PEBBLE_DIRECTORY_URL` 时对本地 Pebble 运行完整的 DNS-01 流程
+
+use crate::acme::{
+    issue_certificate, CertificateRequest, ChallengeSolver, Dns01Solver, Http01Solver,
+};
+use crate::agent::model::Capability;
+use crate::agent::protocol::AgentMessage;
+use crate::cli::{execute, CertCommand, Command, CommandOutput};
+use crate::storage::certificates::{find_acme_account, list_certificates, open_private_key};
+use crate::tests::mock_acme_server::{MockAcmeServer, TxtLookup};
+use crate::tests::mock_rfc2136_server::MockRfc2136Server;
+use crate::tests::test_utils::{setup_tsig_account, start_hub, text, wait_agent};
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::Router;
+use futures_util::{SinkExt, StreamExt};
+use hickory_proto::rr::{RData, RecordType};
+use sea_orm::DatabaseConnection;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Agent 提供的挑战内容：token -> 密钥授权
+type Served = Arc<Mutex<HashMap<String, String>>>;
+
+/// 模拟ACME服务直接读取模拟DNS服务器上的 TXT 记录
+fn dns_lookup(server: &Arc<MockRfc2136Server>) -> TxtLookup {
+    let server = server.clone();
+    Arc::new(move |name: &str| {
+        server
+            .rdatas(&format!("{}.", name), RecordType::TXT)
+            .into_iter()
+            .filter_map(|rdata| match rdata {
+                RData::TXT(txt) => Some(
+                    txt.txt_data()
+                        .iter()
+                        .map(|chunk| String::from_utf8_lossy(chunk).to_string())
+                        .collect(),
+                ),
+                _ => None,
+            })
+            .collect()
+    })
+}
+
+fn request(acme: &MockAcmeServer, names: &[&str]) -> CertificateRequest {
+    CertificateRequest {
+        names: names.iter().map(|name| name.to_string()).collect(),
+        contact: vec!["admin@example.com".to_string()],
+        directory_url: acme.directory_url.clone(),
+        root_certificate: None,
+        polling: Some((Duration::from_millis(50), Duration::from_secs(5))),
+    }
+}
+
+/// 模拟服务器无法响应 UDP 查询，不检查记录是否生效
+fn solver(conn: &DatabaseConnection) -> Dns01Solver {
+    Dns01Solver::new(conn.clone()).with_nameservers(vec![])
+}
+
+fn challenge_txt_count(server: &MockRfc2136Server) -> usize {
+    server
+        .rdatas("_acme-challenge.example.com.", RecordType::TXT)
+        .len()
+        + server
+            .rdatas("_acme-challenge.www.example.com.", RecordType::TXT)
+            .len()
+}
+
+/// 测试完整的 DNS-01 签发流程
+#[tokio::test]
+async fn test_issue_certificate_dns01() {
+    let (server, conn) = setup_tsig_account().await;
+    let acme = MockAcmeServer::start(dns_lookup(&server)).await;
+
+    let certificate = issue_certificate(
+        &conn,
+        &request(&acme, &["WWW.example.com", "*.example.com"]),
+        &solver(&conn),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(certificate.names, "www.example.com,*.example.com");
+    assert!(certificate.domain_id.is_some());
+    assert!(certificate.not_before < certificate.not_after);
+    assert!(certificate
+        .certificate_pem
+        .starts_with("-----BEGIN CERTIFICATE-----"));
+    assert_eq!(
+        certificate
+            .certificate_pem
+            .matches("BEGIN CERTIFICATE")
+            .count(),
+        2
+    );
+
+    // 私钥加密保存，解密后为 PEM
+    assert!(!certificate.private_key.contains("PRIVATE KEY"));
+    assert!(open_private_key(&certificate)
+        .unwrap()
+        .contains("BEGIN PRIVATE KEY"));
+
+    // 挑战记录已清理
+    assert_eq!(challenge_txt_count(&server), 0);
+
+    let account = find_acme_account(&conn, &acme.directory_url)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.contact.as_deref(), Some("admin@example.com"));
+    assert!(account.account_url.ends_with("/acct/0"));
+    assert!(!account.key_data.contains("MIG"));
+
+    // 再次申请复用保存的账户
+    issue_certificate(&conn, &request(&acme, &["example.com"]), &solver(&conn))
+        .await
+        .unwrap();
+    assert_eq!(acme.with_state(|state| state.new_account_requests), 1);
+    assert_eq!(acme.with_state(|state| state.issued), 2);
+    assert_eq!(
+        list_certificates(&conn, certificate.domain_id)
+            .await
+            .unwrap()
+            .len(),
+        2
+    );
+}
+
+/// 使用 Pebble 测试与真实 ACME 服务的协议一致性
+///
+/// 设置 `PEBBLE_DIRECTORY_URL`（如 `https://localhost:14000/dir`）后运行，未设置时跳过。
+/// `PEBBLE_ROOT_CERT` 为 Pebble 的 `pebble.minica.pem` 路径，用于信任其 HTTPS 证书。
+/// 挑战记录只写入模拟的RFC 2136服务器，Pebble 需以 `PEBBLE_VA_ALWAYS_VALID=1` 启动：
+///
+/// ```text
+/// PEBBLE_VA_ALWAYS_VALID=1 pebble -config test/config/pebble-config.json
+/// PEBBLE_DIRECTORY_URL=https://localhost:14000/dir PEBBLE_ROOT_CERT=test/certs/pebble.minica.pem \
+///     cargo test test_issue_certificate_with_pebble
+/// ```
+#[tokio::test]
+async fn test_issue_certificate_with_pebble() {
+    let Ok(directory_url) = std::env::var("PEBBLE_DIRECTORY_URL") else {
+        eprintln!("未设置 PEBBLE_DIRECTORY_URL，跳过 Pebble 测试");
+        return;
+    };
+    let root_certificate = std::env::var("PEBBLE_ROOT_CERT")
+        .ok()
+        .map(|path| std::fs::read(path).unwrap());
+    let (server, conn) = setup_tsig_account().await;
+    let request = |names: &[&str]| CertificateRequest {
+        names: names.iter().map(|name| name.to_string()).collect(),
+        contact: vec!["admin@example.com".to_string()],
+        directory_url: directory_url.clone(),
+        root_certificate: root_certificate.clone(),
+        polling: Some((Duration::from_secs(1), Duration::from_secs(60))),
+    };
+
+    let certificate = issue_certificate(
+        &conn,
+        &request(&["www.example.com", "*.example.com"]),
+        &solver(&conn),
+    )
+    .await
+    .unwrap();
+    assert_eq!(certificate.names, "www.example.com,*.example.com");
+    assert!(certificate.not_before < certificate.not_after);
+    assert!(
+        certificate
+            .certificate_pem
+            .matches("BEGIN CERTIFICATE")
+            .count()
+            >= 2
+    );
+    assert!(open_private_key(&certificate)
+        .unwrap()
+        .contains("BEGIN PRIVATE KEY"));
+    assert_eq!(challenge_txt_count(&server), 0);
+
+    // 再次申请复用保存的账户
+    let account = find_acme_account(&conn, &directory_url)
+        .await
+        .unwrap()
+        .unwrap();
+    issue_certificate(&conn, &request(&["example.com"]), &solver(&conn))
+        .await
+        .unwrap();
+    let reused = find_acme_account(&conn, &directory_url)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(reused.account_url, account.account_url);
+}
+
+/// 测试验证失败时返回错误并清理挑战记录
+#[tokio::test]
+async fn test_failed_challenge_cleans_up() {
+    let (server, conn) = setup_tsig_account().await;
+    let acme = MockAcmeServer::start(Arc::new(|_: &str| vec!["wrong".to_string()])).await;
+
+    let err = issue_certificate(&conn, &request(&acme, &["www.example.com"]), &solver(&conn))
+        .await
+        .unwrap_err();
+    assert!(format!("{:#}", err).contains("验证失败"), "{:#}", err);
+    assert_eq!(challenge_txt_count(&server), 0);
+    assert!(list_certificates(&conn, None).await.unwrap().is_empty());
+}
+
+/// 测试不属于任何本地域名的挑战直接失败
+#[tokio::test]
+async fn test_present_unknown_domain() {
+    let (_server, conn) = setup_tsig_account().await;
+    let err = solver(&conn)
+        .present("www.example.org", "token", "token.thumbprint")
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("没有找到"));
+}
+
+/// 测试命令行列出和导出证书
+#[tokio::test]
+async fn test_cert_list_and_export() {
+    let (server, conn) = setup_tsig_account().await;
+    let acme = MockAcmeServer::start(dns_lookup(&server)).await;
+    let certificate =
+        issue_certificate(&conn, &request(&acme, &["www.example.com"]), &solver(&conn))
+            .await
+            .unwrap();
+
+    let output = execute(
+        &conn,
+        Command::Cert(CertCommand::List {
+            domain: Some("example.com".to_string()),
+        }),
+    )
+    .await
+    .unwrap();
+    let CommandOutput::Certificates(rows) = output else {
+        panic!("unexpected output: {:?}", output);
+    };
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].names, vec!["www.example.com"]);
+
+    let dir = std::env::temp_dir().join(format!("dm-cert-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let (cert, key) = (dir.join("fullchain.pem"), dir.join("privkey.pem"));
+    execute(
+        &conn,
+        Command::Cert(CertCommand::Export {
+            id: certificate.id,
+            cert: cert.clone(),
+            key: key.clone(),
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&cert).unwrap(),
+        certificate.certificate_pem
+    );
+    assert!(std::fs::read_to_string(&key)
+        .unwrap()
+        .contains("BEGIN PRIVATE KEY"));
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&key).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+/// 模拟目标主机上的 Agent：按 Hub 的请求在本地 HTTP 服务上提供挑战内容，返回 HTTP 端口
+async fn start_agent(hub_addr: &str, served: Served) -> u16 {
+    async fn challenge(
+        State(served): State<Served>,
+        Path(token): Path<String>,
+    ) -> Result<String, StatusCode> {
+        let value = served.lock().unwrap().get(&token).cloned();
+        value.ok_or(StatusCode::NOT_FOUND)
+    }
+    let app = Router::new()
+        .route("/.well-known/acme-challenge/:token", get(challenge))
+        .with_state(served.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let http_port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let (mut ws, _) = tokio_tungstenite::connect_async(format!("ws://{}/ws", hub_addr))
+        .await
+        .unwrap();
+    let register = AgentMessage::RegisterWithSecret {
+        agent_id: None,
+        agent_name: "web-1".to_string(),
+        agent_key: "secret".to_string(),
+        capabilities: vec![Capability::SslValidator],
+        version: None,
+        hostname: None,
+    };
+    ws.send(text(register)).await.unwrap();
+    tokio::spawn(async move {
+        while let Some(Ok(msg)) = ws.next().await {
+            let Ok(msg) = serde_json::from_str::<AgentMessage>(&msg.into_text().unwrap()) else {
+                continue;
+            };
+            if let AgentMessage::SslChallengeRequest {
+                domain,
+                token,
+                key_authorization,
+                cleanup,
+                ..
+            } = msg
+            {
+                match cleanup {
+                    true => served.lock().unwrap().remove(&token),
+                    false => served
+                        .lock()
+                        .unwrap()
+                        .insert(token.clone(), key_authorization.unwrap_or_default()),
+                };
+                let response = AgentMessage::SslChallengeResponse {
+                    domain,
+                    token: Some(token),
+                    success: true,
+                    key_authorization: None,
+                    error: None,
+                };
+                ws.send(text(response)).await.unwrap();
+            }
+        }
+    });
+    http_port
+}
+
+/// 测试通过 Agent 完成 HTTP-01 签发
+#[tokio::test]
+async fn test_issue_certificate_http01_via_agent() {
+    let (_server, conn) = setup_tsig_account().await;
+    let (hub, hub_addr) = start_hub(None).await;
+    let served: Served = Arc::default();
+    let http_port = start_agent(&hub_addr, served.clone()).await;
+    let agent_id = wait_agent(&hub).await;
+
+    let acme = MockAcmeServer::start(Arc::new(|_: &str| Vec::new()))
+        .await
+        .with_http_port(http_port);
+    let solver = Http01Solver::new(hub.clone(), agent_id).with_timeout(Duration::from_secs(5));
+    let certificate = issue_certificate(&conn, &request(&acme, &["www.example.com"]), &solver)
+        .await
+        .unwrap();
+    assert_eq!(certificate.names, "www.example.com");
+    assert!(certificate.domain_id.is_some());
+    // 挑战内容已由 Agent 清理
+    assert!(served.lock().unwrap().is_empty());
+
+    // 通配符域名不能使用 HTTP-01
+    let err = solver
+        .present("*.example.com", "token", "token.thumbprint")
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("DNS-01"));
+
+    // 未连接的 Agent
+    let err = Http01Solver::new(hub, Uuid::new_v4())
+        .present("www.example.com", "token", "token.thumbprint")
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("未连接"));
+}
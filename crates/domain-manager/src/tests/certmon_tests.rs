@@ -0,0 +1,388 @@
+//! 证书监控测试
+//!
+//! 使用 rcgen 签发的证书启动本地 TLS 服务，测试：
+//! - 正常证书、即将过期的自签名证书、域名不匹配的证书和无法连接的目标
+//! - 检查结果按目标和来源覆盖保存，命令行查看证书清单
+//! - 托管的主机名来自本地保存的域名和 A、AAAA、CNAME 记录
+//! - 私有网络中的目标由 Agent 握手后上报证书链，定时上报的结果由 Hub 保存
+
+use crate::agent::model::Capability;
+use crate::agent::protocol::{AgentMessage, CertProbeReport};
+use crate::certmon::{self, probe, CertIssue, CheckOptions};
+use crate::cli::{execute, exit_code, CertCheckArgs, CertCommand, Command, CommandOutput};
+use crate::gui::model::domain::DnsProvider;
+use crate::gui::types::credential::{Credential, UsernamePasswordCredential};
+use crate::models::account::NewAccount;
+use crate::models::domain::{DomainStatus, NewDomain};
+use crate::models::record::NewRecord;
+use crate::storage::certificate_checks::list_certificate_checks;
+use crate::storage::records::add_records_many;
+use crate::storage::{add_domain, create_account, init_memory_database};
+use crate::tests::test_utils::{free_addr, init_test_env, start_hub, text, wait_agent};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use chrono::{Datelike, Utc};
+use futures_util::{SinkExt, StreamExt};
+use rcgen::{
+    date_time_ymd, BasicConstraints, Certificate, CertificateParams, DnType, IsCa, KeyPair,
+};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio_rustls::rustls::crypto::ring;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+use tokio_rustls::rustls::ServerConfig;
+use tokio_rustls::TlsAcceptor;
+use uuid::Uuid;
+
+/// 测试用的证书颁发机构
+struct TestCa {
+    certificate: Certificate,
+    key_pair: KeyPair,
+}
+
+impl TestCa {
+    fn new() -> Self {
+        let mut params = CertificateParams::new(Vec::<String>::new()).unwrap();
+        params
+            .distinguished_name
+            .push(DnType::CommonName, "Test Root CA");
+        params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        let key_pair = KeyPair::generate().unwrap();
+        let certificate = params.self_signed(&key_pair).unwrap();
+        Self {
+            certificate,
+            key_pair,
+        }
+    }
+}
+
+/// 签发证书，未指定颁发机构时自签名，返回证书链和私钥
+fn issue(
+    names: &[&str],
+    days: i64,
+    ca: Option<&TestCa>,
+) -> (Vec<CertificateDer<'static>>, KeyPair) {
+    let names: Vec<String> = names.iter().map(|name| name.to_string()).collect();
+    let mut params = CertificateParams::new(names.clone()).unwrap();
+    params
+        .distinguished_name
+        .push(DnType::CommonName, &names[0]);
+    let date = |days: i64| {
+        let date = (Utc::now() + chrono::Duration::days(days)).date_naive();
+        date_time_ymd(date.year(), date.month() as u8, date.day() as u8)
+    };
+    params.not_before = date(-1);
+    params.not_after = date(days);
+    let key_pair = KeyPair::generate().unwrap();
+    let chain = match ca {
+        Some(ca) => vec![
+            params
+                .signed_by(&key_pair, &ca.certificate, &ca.key_pair)
+                .unwrap()
+                .der()
+                .clone(),
+            ca.certificate.der().clone(),
+        ],
+        None => vec![params.self_signed(&key_pair).unwrap().der().clone()],
+    };
+    (chain, key_pair)
+}
+
+/// 启动使用指定证书的 TLS 服务，握手后关闭连接，返回 `127.0.0.1:端口`
+async fn start_tls_server(chain: Vec<CertificateDer<'static>>, key_pair: KeyPair) -> String {
+    let key = PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(key_pair.serialize_der()));
+    let config = ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_no_client_auth()
+        .with_single_cert(chain, key)
+        .unwrap();
+    let acceptor = TlsAcceptor::from(Arc::new(config));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let acceptor = acceptor.clone();
+            tokio::spawn(async move {
+                let _ = acceptor.accept(stream).await;
+            });
+        }
+    });
+    addr
+}
+
+/// `127.0.0.1:端口` 换成 `localhost:端口`，使证书的域名匹配生效
+fn localhost(addr: &str) -> String {
+    addr.replace("127.0.0.1", "localhost")
+}
+
+fn options() -> CheckOptions {
+    CheckOptions {
+        warn_days: 30,
+        timeout: Duration::from_secs(5),
+    }
+}
+
+/// 测试 Hub 直接检查各种证书
+#[tokio::test]
+async fn test_check_targets() {
+    init_test_env();
+    let ca = TestCa::new();
+    let (chain, key) = issue(&["localhost"], 90, Some(&ca));
+    let valid = localhost(&start_tls_server(chain, key).await);
+    let (chain, key) = issue(&["localhost"], 10, None);
+    let self_signed = localhost(&start_tls_server(chain, key).await);
+    let (chain, key) = issue(&["www.example.com", "*.example.com"], 90, Some(&ca));
+    let mismatch = localhost(&start_tls_server(chain, key).await);
+    let closed = free_addr();
+
+    let targets = vec![valid, self_signed, mismatch, closed.clone()];
+    let checks = certmon::check_targets(&targets, options()).await;
+
+    assert_eq!(checks[0].target, targets[0]);
+    assert_eq!(checks[0].server_name, "localhost");
+    assert_eq!(checks[0].chain_length, 2);
+    assert!(checks[0].issues.is_empty(), "{:?}", checks[0]);
+    let certificate = checks[0].certificate.as_ref().unwrap();
+    assert_eq!(certificate.subject, "CN=localhost");
+    assert_eq!(certificate.issuer, "CN=Test Root CA");
+    assert_eq!(certificate.sans, vec!["localhost"]);
+    assert!(!certificate.self_signed);
+
+    assert_eq!(
+        checks[1].issues,
+        vec![CertIssue::ExpiringSoon, CertIssue::SelfSigned]
+    );
+    assert_eq!(checks[2].issues, vec![CertIssue::HostnameMismatch]);
+
+    assert!(checks[3].certificate.is_none());
+    assert!(checks[3].error.as_deref().unwrap().contains(&closed));
+    assert!(checks[3].has_problem());
+}
+
+/// 测试命令行检查、覆盖保存和查看证书清单
+#[tokio::test]
+async fn test_cert_check_and_inventory() {
+    init_test_env();
+    let conn = init_memory_database().await.unwrap();
+    let (chain, key) = issue(&["localhost"], 90, Some(&TestCa::new()));
+    let valid = localhost(&start_tls_server(chain, key).await);
+    let (chain, key) = issue(&["localhost"], 10, None);
+    let self_signed = localhost(&start_tls_server(chain, key).await);
+
+    let check = |targets: Vec<String>| {
+        Command::Cert(CertCommand::Check(CertCheckArgs {
+            targets,
+            managed: false,
+            warn_days: 30,
+            timeout: 5,
+            agent: None,
+            hub_listen: String::new(),
+            agent_wait: 0,
+        }))
+    };
+    let output = execute(&conn, check(vec![valid.clone()])).await.unwrap();
+    assert_eq!(output.exit_code(), exit_code::SUCCESS);
+
+    let output = execute(&conn, check(vec![valid.clone(), self_signed.clone()]))
+        .await
+        .unwrap();
+    assert_eq!(output.exit_code(), exit_code::CERTIFICATE);
+    let CommandOutput::CertificateChecks(rows) = output else {
+        panic!("unexpected output: {:?}", output);
+    };
+    assert_eq!(rows[1].issues, vec!["expiring_soon", "self_signed"]);
+    assert!(matches!(rows[1].days_left, Some(9..=10)));
+
+    // 同一目标只保留最近一次结果，按到期时间排序
+    let output = execute(&conn, Command::Cert(CertCommand::Inventory))
+        .await
+        .unwrap();
+    let CommandOutput::CertificateChecks(rows) = output else {
+        panic!("unexpected output: {:?}", output);
+    };
+    let targets: Vec<&str> = rows.iter().map(|row| row.target.as_str()).collect();
+    assert_eq!(targets, vec![self_signed.as_str(), valid.as_str()]);
+    assert!(rows[1].fingerprint.is_some());
+
+    // 无效的目标
+    let err = execute(&conn, check(vec!["example.com:https".to_string()]))
+        .await
+        .unwrap_err();
+    assert_eq!(err.exit_code(), exit_code::USAGE);
+}
+
+/// 测试托管的主机名
+#[tokio::test]
+async fn test_managed_targets() {
+    init_test_env();
+    let conn = init_memory_database().await.unwrap();
+    let account = create_account(
+        &conn,
+        NewAccount {
+            provider: DnsProvider::Aliyun,
+            username: "ops".to_string(),
+            email: String::new(),
+            credential: Credential::UsernamePassword(UsernamePasswordCredential {
+                username: "ops".to_string(),
+                password: "secret".to_string(),
+            }),
+        },
+    )
+    .await
+    .unwrap();
+    let domain = add_domain(
+        &conn,
+        NewDomain {
+            domain_name: "example.com".to_string(),
+            registration_date: None,
+            expiration_date: None,
+            registrar: None,
+            status: DomainStatus::Active,
+            account_id: account.id,
+        },
+    )
+    .await
+    .unwrap();
+    let record = |name: &str, record_type: &str, value: &str| NewRecord {
+        domain_id: domain.id,
+        record_name: name.to_string(),
+        record_type: record_type.to_string(),
+        record_value: value.to_string(),
+        ttl: 600,
+    };
+    add_records_many(
+        &conn,
+        vec![
+            record("@", "A", "192.0.2.1"),
+            record("www", "A", "192.0.2.1"),
+            record("v6", "AAAA", "2001:db8::1"),
+            record("cdn", "CNAME", "cdn.example.net."),
+            record("*", "A", "192.0.2.1"),
+            record("@", "MX", "mail.example.com."),
+            record("_dmarc", "TXT", "v=DMARC1; p=none"),
+        ],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        certmon::managed_targets(&conn).await.unwrap(),
+        vec![
+            "cdn.example.com:443",
+            "example.com:443",
+            "v6.example.com:443",
+            "www.example.com:443",
+        ]
+    );
+}
+
+/// 与目标握手，按 Agent 的格式上报证书链
+async fn agent_report(target: &str) -> CertProbeReport {
+    let (host, port) = probe::parse_target(target).unwrap();
+    let chain = probe::fetch_chain(&host, port, Duration::from_secs(5)).await;
+    CertProbeReport {
+        target: target.to_string(),
+        server_name: host,
+        chain: chain
+            .as_deref()
+            .unwrap_or_default()
+            .iter()
+            .map(|der| BASE64.encode(der))
+            .collect(),
+        error: chain.err().map(|e| e.to_string()),
+        probed_at: Utc::now().timestamp(),
+    }
+}
+
+/// 模拟私有网络中的 Agent：注册后先上报一次定时探测结果，再响应 Hub 的探测请求
+async fn start_agent(hub_addr: &str, scheduled: String) {
+    let (mut ws, _) = tokio_tungstenite::connect_async(format!("ws://{}/ws", hub_addr))
+        .await
+        .unwrap();
+    let register = AgentMessage::RegisterWithSecret {
+        agent_id: None,
+        agent_name: "lan-1".to_string(),
+        agent_key: "secret".to_string(),
+        capabilities: vec![Capability::CertMonitor],
+        version: None,
+        hostname: None,
+    };
+    ws.send(text(register)).await.unwrap();
+    let result = AgentMessage::CertProbeResult {
+        request_id: None,
+        reports: vec![agent_report(&scheduled).await],
+    };
+    ws.send(text(result)).await.unwrap();
+
+    tokio::spawn(async move {
+        while let Some(Ok(msg)) = ws.next().await {
+            let Ok(msg) = serde_json::from_str::<AgentMessage>(&msg.into_text().unwrap()) else {
+                continue;
+            };
+            if let AgentMessage::CertProbeRequest {
+                request_id,
+                targets,
+            } = msg
+            {
+                let mut reports = Vec::new();
+                for target in &targets {
+                    reports.push(agent_report(target).await);
+                }
+                let result = AgentMessage::CertProbeResult {
+                    request_id: Some(request_id),
+                    reports,
+                };
+                ws.send(text(result)).await.unwrap();
+            }
+        }
+    });
+}
+
+/// 测试由 Agent 检查私有网络中的目标
+#[tokio::test]
+async fn test_check_via_agent() {
+    init_test_env();
+    let conn = init_memory_database().await.unwrap();
+    let (hub, hub_addr) = start_hub(Some(&conn)).await;
+    let (chain, key) = issue(&["localhost"], 10, None);
+    let scheduled = localhost(&start_tls_server(chain, key).await);
+    let (chain, key) = issue(&["intranet.local"], 90, Some(&TestCa::new()));
+    let requested = start_tls_server(chain, key).await;
+
+    start_agent(&hub_addr, scheduled.clone()).await;
+    let agent_id = wait_agent(&hub).await;
+    assert!(hub.registry().get_all().await[0].has_capability(&Capability::CertMonitor));
+
+    // 定时上报的结果由 Hub 保存
+    let mut saved = Vec::new();
+    for _ in 0..100 {
+        saved = list_certificate_checks(&conn).await.unwrap();
+        if !saved.is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    assert_eq!(saved.len(), 1);
+    assert_eq!(saved[0].target, scheduled);
+    assert_eq!(saved[0].agent_id, Some(agent_id.to_string()));
+    assert_eq!(saved[0].issues, "expiring_soon,self_signed");
+
+    let targets = vec![requested.clone(), free_addr()];
+    let checks = certmon::check_via_agent(&hub, agent_id, &targets, options())
+        .await
+        .unwrap();
+    assert_eq!(checks.len(), 2);
+    assert_eq!(checks[0].agent_id, Some(agent_id));
+    assert_eq!(checks[0].issues, vec![CertIssue::HostnameMismatch]);
+    assert!(checks[1].error.is_some());
+
+    // 请求方保存结果，Hub 不重复保存
+    certmon::save_checks(&conn, &checks).await.unwrap();
+    assert_eq!(list_certificate_checks(&conn).await.unwrap().len(), 3);
+
+    let err = certmon::check_via_agent(&hub, Uuid::new_v4(), &targets, options())
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("未连接"));
+}
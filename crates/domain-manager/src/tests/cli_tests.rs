@@ -0,0 +1,468 @@
+//! 命令行接口测试
+//!
+//! 使用内存数据库和模拟的RFC 2136服务器测试完整的命令流程：
+//! - sync 同步域名和解析记录到本地数据库
+//! - records add/update/delete 调用服务商接口并刷新本地缓存
+//! - zone export/import 导出区域文件以及导入前的预览
+//! - plan/apply 按声明式配置新增、删除记录以及清理未管理的记录
+//! - migrate 在两个账户之间迁移记录并保存迁移报告
+//! - 账户、域名、记录不存在或匹配不唯一时的错误类型
+
+use crate::cli::{
+    execute, exit_code, AccountsCommand, CliError, Command, CommandOutput, DomainsCommand,
+    MigrateArgs, MigrateCommand, RecordArgs, RecordsCommand, StateArgs, ZoneCommand, ZoneSource,
+};
+use crate::gui::model::domain::DnsProvider;
+use crate::model::dns_record_response::Type;
+use crate::storage::{find_domain_by_name, init_memory_database, records};
+use crate::tests::mock_rfc2136_server::MockRfc2136Server;
+use crate::tests::test_utils::{add_tsig_account, init_test_env, setup_tsig_account};
+use hickory_proto::rr::rdata::A;
+use hickory_proto::rr::{RData, RecordType};
+use sea_orm::DatabaseConnection;
+
+fn record_args(rr: &str, record_type: Type, value: &str) -> RecordArgs {
+    RecordArgs {
+        rr: rr.to_string(),
+        record_type,
+        value: value.to_string(),
+        ttl: 300,
+    }
+}
+
+async fn local_record_count(conn: &DatabaseConnection) -> usize {
+    let domain = find_domain_by_name(conn, "example.com")
+        .await
+        .unwrap()
+        .unwrap();
+    records::get_records_by_domain(conn, Some(domain.id))
+        .await
+        .unwrap()
+        .len()
+}
+
+/// 测试同步以及账户、域名列表
+#[tokio::test]
+async fn test_sync_and_list() {
+    init_test_env();
+    let server = MockRfc2136Server::start().await;
+    let conn = init_memory_database().await.unwrap();
+    add_tsig_account(&conn, "bind", &server).await;
+
+    // 同步前本地没有域名
+    let err = execute(
+        &conn,
+        Command::Records(RecordsCommand::List {
+            domain: "example.com".to_string(),
+        }),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(err.exit_code(), exit_code::NOT_FOUND);
+
+    let output = execute(
+        &conn,
+        Command::Sync {
+            account: Some("bind".to_string()),
+        },
+    )
+    .await
+    .unwrap();
+    let CommandOutput::Sync(rows) = &output else {
+        panic!("unexpected output: {:?}", output);
+    };
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].domain, "example.com");
+    assert_eq!(rows[0].records, 3);
+    assert_eq!(output.exit_code(), exit_code::SUCCESS);
+    assert_eq!(local_record_count(&conn).await, 3);
+
+    // 重复同步不会产生重复的域名和记录
+    execute(&conn, Command::Sync { account: None })
+        .await
+        .unwrap();
+    assert_eq!(local_record_count(&conn).await, 3);
+
+    let output = execute(&conn, Command::Accounts(AccountsCommand::List))
+        .await
+        .unwrap();
+    let CommandOutput::Accounts(accounts) = output else {
+        panic!("unexpected output");
+    };
+    assert_eq!(accounts.len(), 1);
+    assert_eq!(accounts[0].provider, DnsProvider::Rfc2136.value());
+
+    let output = execute(
+        &conn,
+        Command::Domains(DomainsCommand::List {
+            account: Some(accounts[0].id.to_string()),
+        }),
+    )
+    .await
+    .unwrap();
+    let CommandOutput::Domains(domains) = output else {
+        panic!("unexpected output");
+    };
+    assert_eq!(domains.len(), 1);
+    assert_eq!(domains[0].name, "example.com");
+
+    let err = execute(
+        &conn,
+        Command::Sync {
+            account: Some("nobody".to_string()),
+        },
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(err, CliError::NotFound(_)));
+}
+
+/// 测试添加、修改、删除解析记录
+#[tokio::test]
+async fn test_record_commands() {
+    let (server, conn) = setup_tsig_account().await;
+    let domain = "example.com".to_string();
+
+    let output = execute(
+        &conn,
+        Command::Records(RecordsCommand::Add {
+            domain: domain.clone(),
+            record: record_args("api", Type::A, "192.0.2.20"),
+        }),
+    )
+    .await
+    .unwrap();
+    let CommandOutput::Record(added) = output else {
+        panic!("unexpected output");
+    };
+    assert!(!added.id.is_empty());
+    assert_eq!(
+        server.rdatas("api.example.com.", RecordType::A),
+        vec![RData::A(A::new(192, 0, 2, 20))]
+    );
+    assert_eq!(local_record_count(&conn).await, 4);
+
+    // 按主机记录和类型修改唯一的记录
+    execute(
+        &conn,
+        Command::Records(RecordsCommand::Update {
+            domain: domain.clone(),
+            id: None,
+            record: record_args("api", Type::A, "192.0.2.21"),
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        server.rdatas("api.example.com.", RecordType::A),
+        vec![RData::A(A::new(192, 0, 2, 21))]
+    );
+
+    // 同一主机记录存在多条时必须指定记录值或记录ID
+    execute(
+        &conn,
+        Command::Records(RecordsCommand::Add {
+            domain: domain.clone(),
+            record: record_args("api", Type::A, "192.0.2.22"),
+        }),
+    )
+    .await
+    .unwrap();
+    let delete = |value: Option<&str>| {
+        Command::Records(RecordsCommand::Delete {
+            domain: domain.clone(),
+            id: None,
+            rr: Some("api".to_string()),
+            record_type: Some(Type::A),
+            value: value.map(str::to_string),
+        })
+    };
+    let err = execute(&conn, delete(None)).await.unwrap_err();
+    assert_eq!(err.exit_code(), exit_code::USAGE);
+
+    execute(&conn, delete(Some("192.0.2.21"))).await.unwrap();
+    assert_eq!(
+        server.rdatas("api.example.com.", RecordType::A),
+        vec![RData::A(A::new(192, 0, 2, 22))]
+    );
+    assert_eq!(local_record_count(&conn).await, 4);
+
+    let err = execute(
+        &conn,
+        Command::Records(RecordsCommand::Delete {
+            domain,
+            id: Some("missing".to_string()),
+            rr: None,
+            record_type: None,
+            value: None,
+        }),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(err.exit_code(), exit_code::NOT_FOUND);
+}
+
+/// 测试区域文件导出和导入
+#[tokio::test]
+async fn test_zone_commands() {
+    let (server, conn) = setup_tsig_account().await;
+    let domain = "example.com".to_string();
+    let dir = std::env::temp_dir().join(format!("domain-manager-zone-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let export_path = dir.join("export.zone");
+    let output = execute(
+        &conn,
+        Command::Zone(ZoneCommand::Export {
+            domain: domain.clone(),
+            source: ZoneSource::Live,
+            file: Some(export_path.clone()),
+        }),
+    )
+    .await
+    .unwrap();
+    let CommandOutput::Zone(export) = output else {
+        panic!("unexpected output");
+    };
+    assert_eq!(export.records, 3);
+    let exported = std::fs::read_to_string(&export_path).unwrap();
+    assert!(exported.contains("$ORIGIN example.com.\n"));
+    assert!(exported.contains("www\t"));
+
+    let import_path = dir.join("import.zone");
+    std::fs::write(
+        &import_path,
+        "$ORIGIN example.com.\n\
+         @    IN SOA ns1 hostmaster 1 3600 600 604800 300\n\
+         ns1  3600 IN A 192.0.2.53\n\
+         www  300 IN A 192.0.2.2\n\
+         api  IN A 192.0.2.30\n",
+    )
+    .unwrap();
+    let import = |apply: bool| {
+        Command::Zone(ZoneCommand::Import {
+            domain: domain.clone(),
+            file: import_path.clone(),
+            account: None,
+            apply,
+        })
+    };
+
+    // 不带 --apply 时只输出预览
+    let output = execute(&conn, import(false)).await.unwrap();
+    let CommandOutput::Changes(rows) = output else {
+        panic!("unexpected output");
+    };
+    let actions: Vec<(&str, &str)> = rows
+        .iter()
+        .map(|row| (row.action.as_str(), row.rr.as_str()))
+        .collect();
+    assert!(actions.contains(&("skip", "ns1")));
+    assert!(actions.contains(&("update", "www")));
+    assert!(actions.contains(&("add", "api")));
+    assert!(actions
+        .iter()
+        .any(|(action, rr)| *action == "skip" && rr.starts_with("example.com")));
+    assert_eq!(
+        server.rdatas("www.example.com.", RecordType::A),
+        vec![RData::A(A::new(192, 0, 2, 1))]
+    );
+
+    let output = execute(&conn, import(true)).await.unwrap();
+    assert_eq!(output.exit_code(), exit_code::SUCCESS);
+    assert_eq!(
+        server.rdatas("www.example.com.", RecordType::A),
+        vec![RData::A(A::new(192, 0, 2, 2))]
+    );
+    assert_eq!(
+        server.rdatas("api.example.com.", RecordType::A),
+        vec![RData::A(A::new(192, 0, 2, 30))]
+    );
+    assert_eq!(local_record_count(&conn).await, 4);
+
+    // 再次导入时全部跳过
+    let output = execute(&conn, import(false)).await.unwrap();
+    let CommandOutput::Changes(rows) = output else {
+        panic!("unexpected output");
+    };
+    assert!(rows.iter().all(|row| row.action == "skip"));
+
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+const STATE: &str = r#"
+domains:
+  example.com.:
+    records:
+      - { rr: www, type: A, value: 192.0.2.1, ttl: 300 }
+      - { rr: www, type: A, value: 192.0.2.5 }
+      - { rr: api, type: a, value: 192.0.2.30 }
+"#;
+
+/// 测试声明式配置的计划和执行
+#[tokio::test]
+async fn test_plan_and_apply() {
+    let (server, conn) = setup_tsig_account().await;
+    let path =
+        std::env::temp_dir().join(format!("domain-manager-state-{}.yaml", std::process::id()));
+    std::fs::write(&path, STATE).unwrap();
+    let args = |prune: bool| StateArgs {
+        file: path.clone(),
+        account: None,
+        prune,
+    };
+    let actions = |output: CommandOutput| -> Vec<(String, String, String)> {
+        let CommandOutput::Changes(rows) = output else {
+            panic!("unexpected output");
+        };
+        assert!(rows
+            .iter()
+            .all(|row| row.domain.as_deref() == Some("example.com")));
+        rows.into_iter()
+            .map(|row| (row.action, row.rr, row.value))
+            .collect()
+    };
+
+    // 未开启清理时只新增记录，计划不会修改服务商的数据
+    let plan = actions(execute(&conn, Command::Plan(args(false))).await.unwrap());
+    assert_eq!(plan.len(), 2);
+    assert!(plan.iter().all(|(action, _, _)| action == "add"));
+
+    let plan = actions(execute(&conn, Command::Plan(args(true))).await.unwrap());
+    assert_eq!(plan.len(), 3);
+    assert_eq!(
+        plan[0],
+        (
+            "delete".to_string(),
+            "ns1".to_string(),
+            "192.0.2.53".to_string()
+        )
+    );
+    assert_eq!(server.rdatas("ns1.example.com.", RecordType::A).len(), 1);
+
+    let output = execute(&conn, Command::Apply(args(true))).await.unwrap();
+    assert_eq!(output.exit_code(), exit_code::SUCCESS);
+    assert!(server.rdatas("ns1.example.com.", RecordType::A).is_empty());
+    assert_eq!(server.rdatas("www.example.com.", RecordType::A).len(), 2);
+    assert_eq!(
+        server.rdatas("api.example.com.", RecordType::A),
+        vec![RData::A(A::new(192, 0, 2, 30))]
+    );
+    // 根域名的 NS 记录不会被清理
+    assert_eq!(server.rdatas("example.com.", RecordType::NS).len(), 1);
+    assert_eq!(local_record_count(&conn).await, 4);
+
+    // 执行后再次计划没有变更
+    let plan = actions(execute(&conn, Command::Plan(args(true))).await.unwrap());
+    assert!(plan.is_empty());
+
+    // RFC 2136 服务器只有默认线路
+    let lines = STATE.replace("ttl: 300", "line: telecom");
+    std::fs::write(&path, lines).unwrap();
+    let err = execute(&conn, Command::Plan(args(false)))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, CliError::Usage(msg) if msg.contains("telecom")),
+        "{:?}",
+        err
+    );
+
+    std::fs::remove_file(&path).ok();
+}
+
+/// 测试迁移预览、执行以及查看保存的迁移报告
+#[tokio::test]
+async fn test_migrate_commands() {
+    let (source, conn) = setup_tsig_account().await;
+    let target = MockRfc2136Server::start().await;
+    add_tsig_account(&conn, "bind-new", &target).await;
+    execute(
+        &conn,
+        Command::Records(RecordsCommand::Add {
+            domain: "example.com".to_string(),
+            record: record_args("api", Type::A, "192.0.2.30"),
+        }),
+    )
+    .await
+    .unwrap();
+
+    let args = || MigrateArgs {
+        domain: "example.com".to_string(),
+        to: "bind-new".to_string(),
+        from: None,
+    };
+    let statuses = |output: &CommandOutput| -> Vec<(String, String)> {
+        let CommandOutput::Migration(migration) = output else {
+            panic!("unexpected output");
+        };
+        let mut statuses: Vec<(String, String)> = migration
+            .records
+            .iter()
+            .map(|row| (row.rr.clone(), row.status.clone()))
+            .collect();
+        statuses.sort();
+        statuses
+    };
+
+    // 预览不修改目标账户，根域名的 NS 由服务商管理
+    let preview = execute(&conn, Command::Migrate(MigrateCommand::Preview(args())))
+        .await
+        .unwrap();
+    assert!(statuses(&preview).contains(&("@".to_string(), "skipped".to_string())));
+    assert!(statuses(&preview).contains(&("api".to_string(), "pending".to_string())));
+    assert!(target.rdatas("api.example.com.", RecordType::A).is_empty());
+
+    let output = execute(&conn, Command::Migrate(MigrateCommand::Run(args())))
+        .await
+        .unwrap();
+    assert_eq!(output.exit_code(), exit_code::SUCCESS);
+    assert_eq!(
+        statuses(&output),
+        vec![
+            ("@".to_string(), "skipped".to_string()),
+            ("api".to_string(), "created".to_string()),
+            ("ns1".to_string(), "existing".to_string()),
+            ("www".to_string(), "existing".to_string()),
+        ]
+    );
+    assert_eq!(
+        target.rdatas("api.example.com.", RecordType::A),
+        vec![RData::A(A::new(192, 0, 2, 30))]
+    );
+    assert_eq!(source.rdatas("api.example.com.", RecordType::A).len(), 1);
+    let CommandOutput::Migration(migration) = &output else {
+        unreachable!();
+    };
+    assert_eq!(migration.nameservers, vec!["ns1.example.com.".to_string()]);
+
+    let report = execute(
+        &conn,
+        Command::Migrate(MigrateCommand::Report {
+            id: migration.id.clone().unwrap(),
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(statuses(&report), statuses(&output));
+
+    let err = execute(
+        &conn,
+        Command::Migrate(MigrateCommand::Run(MigrateArgs {
+            to: "bind".to_string(),
+            ..args()
+        })),
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(err, CliError::Usage(_)));
+    let err = execute(
+        &conn,
+        Command::Migrate(MigrateCommand::Report {
+            id: "missing".to_string(),
+        }),
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(err, CliError::NotFound(_)));
+}
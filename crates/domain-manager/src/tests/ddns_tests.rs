@@ -0,0 +1,160 @@
+//! DDNS 更新测试
+//!
+//! 使用内存数据库和模拟的RFC 2136服务器测试：
+//! - 按完整域名新增、保持不变以及更新 A 记录
+//! - 记录类型与 IP 地址不匹配时拒绝更新
+//! - Agent 通过 WebSocket 上报 IP 变化后 Hub 更新记录并保存 DDNS 事件
+
+use crate::agent::ddns::{apply_ip_change, MODE_DIRECT, MODE_HUB};
+use crate::agent::model::Capability;
+use crate::agent::protocol::AgentMessage;
+use crate::storage::ddns_events::list_ddns_events;
+use crate::tests::test_utils::{setup_tsig_account, start_hub, text};
+use futures_util::{SinkExt, StreamExt};
+use hickory_proto::rr::rdata::A;
+use hickory_proto::rr::{RData, RecordType};
+use std::time::Duration;
+
+fn a(ip: &str) -> RData {
+    RData::A(A(ip.parse().unwrap()))
+}
+
+/// 测试新增、保持不变以及更新记录
+#[tokio::test]
+async fn test_apply_ip_change() {
+    let (server, conn) = setup_tsig_account().await;
+
+    let outcome = apply_ip_change(&conn, "home.example.com", "A", "203.0.113.7")
+        .await
+        .unwrap();
+    assert_eq!(outcome.old_ip, None);
+    assert!(outcome.changed);
+    assert_eq!(
+        server.rdatas("home.example.com.", RecordType::A),
+        vec![a("203.0.113.7")]
+    );
+
+    let outcome = apply_ip_change(&conn, "HOME.example.com.", "a", "203.0.113.7")
+        .await
+        .unwrap();
+    assert_eq!(outcome.old_ip.as_deref(), Some("203.0.113.7"));
+    assert!(!outcome.changed);
+
+    let outcome = apply_ip_change(&conn, "home.example.com", "A", "203.0.113.8")
+        .await
+        .unwrap();
+    assert_eq!(outcome.old_ip.as_deref(), Some("203.0.113.7"));
+    assert!(outcome.changed);
+    assert_eq!(
+        server.rdatas("home.example.com.", RecordType::A),
+        vec![a("203.0.113.8")]
+    );
+}
+
+/// 测试无效的请求
+#[tokio::test]
+async fn test_apply_ip_change_rejects_invalid_requests() {
+    let (_server, conn) = setup_tsig_account().await;
+
+    assert!(
+        apply_ip_change(&conn, "home.example.com", "A", "2001:db8::1")
+            .await
+            .is_err()
+    );
+    assert!(
+        apply_ip_change(&conn, "home.example.com", "TXT", "203.0.113.7")
+            .await
+            .is_err()
+    );
+    assert!(
+        apply_ip_change(&conn, "home.example.org", "A", "203.0.113.7")
+            .await
+            .is_err()
+    );
+}
+
+/// 测试 Agent 上报 IP 变化以及直接更新后的审计事件
+#[tokio::test]
+async fn test_agent_reports_ip_change() {
+    let (server, conn) = setup_tsig_account().await;
+
+    let (_hub, addr) = start_hub(Some(&conn)).await;
+
+    let (mut ws, _) = tokio_tungstenite::connect_async(format!("ws://{}/ws", addr))
+        .await
+        .unwrap();
+    ws.send(text(AgentMessage::RegisterWithSecret {
+        agent_id: None,
+        agent_name: "home".to_string(),
+        agent_key: "secret".to_string(),
+        capabilities: vec![Capability::DdnsClient],
+        version: None,
+        hostname: None,
+    }))
+    .await
+    .unwrap();
+    ws.send(text(AgentMessage::DdnsIpChanged {
+        domain: "home.example.com".to_string(),
+        record_type: "A".to_string(),
+        old_ip: None,
+        new_ip: "203.0.113.7".to_string(),
+    }))
+    .await
+    .unwrap();
+    ws.send(text(AgentMessage::DdnsUpdateResult {
+        domain: "nas.example.com".to_string(),
+        record_type: Some("AAAA".to_string()),
+        success: true,
+        old_ip: None,
+        new_ip: Some("2001:db8::7".to_string()),
+        error: None,
+    }))
+    .await
+    .unwrap();
+
+    let mut result = None;
+    while result.is_none() {
+        let msg = tokio::time::timeout(Duration::from_secs(10), ws.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        let msg: AgentMessage = serde_json::from_str(&msg.into_text().unwrap()).unwrap();
+        if let AgentMessage::DdnsUpdateResult { success, error, .. } = msg {
+            result = Some((success, error));
+        }
+    }
+    assert_eq!(result, Some((true, None)));
+    assert_eq!(
+        server.rdatas("home.example.com.", RecordType::A),
+        vec![a("203.0.113.7")]
+    );
+
+    // 直接更新的结果由连接任务异步保存
+    let mut events = Vec::new();
+    for _ in 0..50 {
+        events = list_ddns_events(&conn, None, 10).await.unwrap();
+        if events.len() == 2 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    let mut modes: Vec<(&str, &str, bool)> = events
+        .iter()
+        .map(|event| (event.domain.as_str(), event.mode.as_str(), event.success))
+        .collect();
+    modes.sort();
+    assert_eq!(
+        modes,
+        vec![
+            ("home.example.com", MODE_HUB, true),
+            ("nas.example.com", MODE_DIRECT, true),
+        ]
+    );
+
+    let events = list_ddns_events(&conn, Some("home.example.com"), 10)
+        .await
+        .unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].new_ip, "203.0.113.7");
+}
@@ -0,0 +1,336 @@
+//! 域名到期提醒测试
+//!
+//! 启动本地 RDAP、WHOIS 和 Webhook 服务，测试：
+//! - 依次通过 RDAP 和 WHOIS 查询注册信息并保存，查不到时返回每种方式的失败原因
+//! - 按提醒阈值查询即将到期的域名，每个阈值只发送一次 Webhook，续费后重新提醒
+//! - 命令行刷新和查看域名到期时间
+
+use crate::cli::{execute, exit_code, Command, CommandOutput, DomainExpiryArgs, DomainsCommand};
+use crate::expiry::{self, LookupOptions, ReminderConfig};
+use crate::gui::model::domain::DnsProvider;
+use crate::gui::types::credential::{Credential, UsernamePasswordCredential};
+use crate::models::account::NewAccount;
+use crate::models::domain::{DomainRegistration, DomainStatus, NewDomain};
+use crate::storage::{add_domain, create_account, domains, init_memory_database};
+use crate::tests::test_utils::init_test_env;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use chrono::{Duration as ChronoDuration, NaiveDateTime, SecondsFormat, Utc};
+use sea_orm::DatabaseConnection;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// 距今指定天数的时间，多留一小时避免跨天
+fn days_from_now(days: i64) -> NaiveDateTime {
+    Utc::now().naive_utc() + ChronoDuration::days(days) + ChronoDuration::hours(1)
+}
+
+/// 启动 RDAP 服务，`domains` 中的域名返回到期时间，其他返回 404
+async fn start_rdap(domains: HashMap<String, NaiveDateTime>) -> String {
+    async fn domain(
+        State(domains): State<Arc<HashMap<String, NaiveDateTime>>>,
+        Path(name): Path<String>,
+    ) -> Result<Json<Value>, StatusCode> {
+        let expiration = domains.get(&name).ok_or(StatusCode::NOT_FOUND)?;
+        Ok(Json(json!({
+            "objectClassName": "domain",
+            "ldhName": name,
+            "events": [
+                {"eventAction": "registration", "eventDate": "2015-06-01T08:00:00Z"},
+                {
+                    "eventAction": "expiration",
+                    "eventDate": expiration.and_utc().to_rfc3339_opts(SecondsFormat::Secs, true)
+                }
+            ],
+            "entities": [{
+                "roles": ["registrar"],
+                "vcardArray": ["vcard", [["fn", {}, "text", "Example Registrar, Inc."]]]
+            }]
+        })))
+    }
+
+    let app = Router::new()
+        .route("/domain/:name", get(domain))
+        .with_state(Arc::new(domains));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{}", addr)
+}
+
+/// 启动 WHOIS 服务，`domains` 中的域名返回 CNNIC 格式的结果，其他返回未找到
+async fn start_whois(domains: HashMap<String, NaiveDateTime>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        loop {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let domains = domains.clone();
+            tokio::spawn(async move {
+                let mut query = Vec::new();
+                let mut buf = [0u8; 256];
+                while !query.ends_with(b"\r\n") {
+                    let n = stream.read(&mut buf).await.unwrap();
+                    if n == 0 {
+                        return;
+                    }
+                    query.extend_from_slice(&buf[..n]);
+                }
+                let name = String::from_utf8_lossy(&query).trim().to_string();
+                let response = match domains.get(&name) {
+                    Some(expiration) => format!(
+                        "Domain Name: {}\r\nSponsoring Registrar: 示例注册商\r\n\
+                         Registration Time: 2010-01-01 00:00:00\r\nExpiration Time: {}\r\n",
+                        name,
+                        expiration.format("%Y-%m-%d %H:%M:%S")
+                    ),
+                    None => "No matching record.\r\n".to_string(),
+                };
+                stream.write_all(response.as_bytes()).await.unwrap();
+            });
+        }
+    });
+    addr.to_string()
+}
+
+/// 启动 Webhook 服务，记录收到的提醒
+async fn start_webhook() -> (String, Arc<Mutex<Vec<Value>>>) {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let app = Router::new()
+        .route(
+            "/hook",
+            post(
+                |State(received): State<Arc<Mutex<Vec<Value>>>>, Json(body): Json<Value>| async move {
+                    received.lock().unwrap().push(body);
+                    StatusCode::OK
+                },
+            ),
+        )
+        .with_state(received.clone());
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (format!("http://{}/hook", addr), received)
+}
+
+/// 创建账户和域名，账户凭证不对应任何服务商客户端，只能通过 RDAP/WHOIS 查询
+async fn setup(names: &[&str]) -> DatabaseConnection {
+    init_test_env();
+    let conn = init_memory_database().await.unwrap();
+    let account = create_account(
+        &conn,
+        NewAccount {
+            provider: DnsProvider::Aliyun,
+            username: "ops".to_string(),
+            email: String::new(),
+            credential: Credential::UsernamePassword(UsernamePasswordCredential {
+                username: "ops".to_string(),
+                password: "secret".to_string(),
+            }),
+        },
+    )
+    .await
+    .unwrap();
+    for name in names {
+        add_domain(
+            &conn,
+            NewDomain {
+                domain_name: name.to_string(),
+                registration_date: None,
+                expiration_date: None,
+                registrar: None,
+                status: DomainStatus::Active,
+                account_id: account.id,
+            },
+        )
+        .await
+        .unwrap();
+    }
+    conn
+}
+
+/// 启动 RDAP 和 WHOIS 服务：example.com 由 RDAP 返回，example.cn 只有 WHOIS
+async fn lookup_options() -> LookupOptions {
+    let rdap = start_rdap(HashMap::from([
+        ("example.com".to_string(), days_from_now(5)),
+        ("later.com".to_string(), days_from_now(200)),
+    ]))
+    .await;
+    let whois = start_whois(HashMap::from([(
+        "example.cn".to_string(),
+        days_from_now(20),
+    )]))
+    .await;
+    LookupOptions {
+        rdap_url: rdap,
+        whois_server: Some(whois),
+        timeout: Duration::from_secs(5),
+    }
+}
+
+/// 测试刷新注册信息和查询即将到期的域名
+#[tokio::test]
+async fn test_refresh_and_expiring_domains() {
+    let conn = setup(&["example.com", "example.cn", "later.com", "missing.org"]).await;
+    let options = lookup_options().await;
+
+    let lookups = expiry::refresh(&conn, &options).await.unwrap();
+    assert_eq!(lookups.len(), 4);
+    let result = |name: &str| {
+        lookups
+            .iter()
+            .find(|lookup| lookup.domain_name == name)
+            .unwrap()
+            .result
+            .clone()
+    };
+    let (registration, source) = result("example.com").unwrap();
+    assert_eq!(source, expiry::ExpirySource::Rdap);
+    assert_eq!(
+        registration.registrar.as_deref(),
+        Some("Example Registrar, Inc.")
+    );
+    let (registration, source) = result("example.cn").unwrap();
+    assert_eq!(source, expiry::ExpirySource::Whois);
+    assert_eq!(registration.registrar.as_deref(), Some("示例注册商"));
+    let error = result("missing.org").unwrap_err();
+    assert!(
+        error.contains("RDAP") && error.contains("WHOIS"),
+        "{}",
+        error
+    );
+
+    let expiring = domains::get_expiring_domains(&conn, 30).await.unwrap();
+    let names: Vec<&str> = expiring
+        .iter()
+        .map(|domain| domain.domain_name.as_str())
+        .collect();
+    assert_eq!(names, vec!["example.com", "example.cn"]);
+    assert!(expiring[0].registration_date.is_some());
+    assert_eq!(
+        domains::get_expiring_domains(&conn, 365)
+            .await
+            .unwrap()
+            .len(),
+        3
+    );
+}
+
+/// 测试每个阈值只发送一次提醒，续费后清空已发送的提醒
+#[tokio::test]
+async fn test_notify_reminders() {
+    let conn = setup(&["example.com", "example.cn", "later.com"]).await;
+    expiry::refresh(&conn, &lookup_options().await)
+        .await
+        .unwrap();
+    let (webhook_url, received) = start_webhook().await;
+    let config = ReminderConfig {
+        days: vec![30, 7, 1],
+        webhook_url: Some(webhook_url),
+    };
+
+    let reminders = expiry::due_reminders(&conn, &config).await.unwrap();
+    let summary: Vec<(&str, i64)> = reminders
+        .iter()
+        .map(|reminder| (reminder.domain_name.as_str(), reminder.threshold))
+        .collect();
+    assert_eq!(summary, vec![("example.com", 7), ("example.cn", 30)]);
+    assert_eq!(expiry::notify(&conn, &config, &reminders).await.unwrap(), 2);
+    {
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 2);
+        assert_eq!(received[0]["domain"], "example.com");
+        assert_eq!(received[0]["threshold"], 7);
+        assert!(received[0]["text"]
+            .as_str()
+            .unwrap()
+            .contains("example.com"));
+    }
+
+    // 已发送过的阈值不再发送
+    let reminders = expiry::due_reminders(&conn, &config).await.unwrap();
+    assert!(reminders.iter().all(|reminder| reminder.notified));
+    assert_eq!(expiry::notify(&conn, &config, &reminders).await.unwrap(), 0);
+
+    // 续费后到期时间变化，到达新阈值时重新提醒
+    let domain = domains::find_domain_by_name(&conn, "example.com")
+        .await
+        .unwrap()
+        .unwrap();
+    domains::update_domain_registration(
+        &conn,
+        domain.id,
+        &DomainRegistration {
+            expiration_date: Some(days_from_now(25)),
+            ..DomainRegistration::default()
+        },
+    )
+    .await
+    .unwrap();
+    let reminders = expiry::due_reminders(&conn, &config).await.unwrap();
+    assert_eq!(expiry::notify(&conn, &config, &reminders).await.unwrap(), 1);
+    assert_eq!(received.lock().unwrap().len(), 3);
+}
+
+/// 测试命令行刷新和查看域名到期时间
+#[tokio::test]
+async fn test_domains_expiry_command() {
+    let conn = setup(&["example.com", "example.cn", "later.com", "missing.org"]).await;
+    let options = lookup_options().await;
+    let args = |refresh: bool, all: bool| {
+        Command::Domains(DomainsCommand::Expiry(DomainExpiryArgs {
+            refresh,
+            all,
+            remind_days: vec![30, 7, 1],
+            webhook: None,
+            rdap_url: options.rdap_url.clone(),
+            whois_server: options.whois_server.clone(),
+            timeout: 5,
+        }))
+    };
+
+    // 刷新时查询失败的域名也会列出
+    let output = execute(&conn, args(true, false)).await.unwrap();
+    assert_eq!(output.exit_code(), exit_code::PROVIDER);
+    let CommandOutput::DomainExpiry(rows) = &output else {
+        panic!("unexpected output: {:?}", output);
+    };
+    let summary: Vec<(&str, Option<i64>, Option<&str>)> = rows
+        .iter()
+        .map(|row| (row.name.as_str(), row.reminder, row.source.as_deref()))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("example.com", Some(7), Some("rdap")),
+            ("example.cn", Some(30), Some("whois")),
+            ("missing.org", None, None),
+        ]
+    );
+    assert_eq!(rows[0].days_left, Some(5));
+    assert!(rows[2].error.is_some());
+
+    // 不刷新时读取保存的到期时间
+    let output = execute(&conn, args(false, false)).await.unwrap();
+    assert_eq!(output.exit_code(), exit_code::EXPIRING);
+    let CommandOutput::DomainExpiry(rows) = &output else {
+        panic!("unexpected output: {:?}", output);
+    };
+    assert_eq!(rows.len(), 2);
+
+    let output = execute(&conn, args(false, true)).await.unwrap();
+    let CommandOutput::DomainExpiry(rows) = &output else {
+        panic!("unexpected output: {:?}", output);
+    };
+    let names: Vec<&str> = rows.iter().map(|row| row.name.as_str()).collect();
+    assert_eq!(
+        names,
+        vec!["example.com", "example.cn", "later.com", "missing.org"]
+    );
+}
@@ -0,0 +1,170 @@
+//! Google Cloud DNS 客户端测试
+//!
+//! 使用本地模拟服务测试完整的请求流程：
+//! - 服务账号JWT换取访问令牌及令牌复用
+//! - 托管区域和记录集查询（含分页）
+//! - 通过 changes 接口添加、更新、删除记录
+
+use crate::api::dns_client::DnsClientTrait;
+use crate::api::provider::create_dns_client;
+use crate::api::provider::google_cloud_dns::GoogleCloudDnsClient;
+use crate::gui::model::domain::{DnsProvider, DomainName};
+use crate::gui::types::credential::{Credential, ServiceAccountCredential};
+use crate::model::dns_record_response::{Record, Status, Type};
+use crate::tests::mock_google_cloud_dns::MockCloudDnsServer;
+use crate::tests::test_utils::init_test_env;
+use anyhow::Result;
+
+fn example_domain() -> DomainName {
+    DomainName {
+        name: "example.com".to_string(),
+        provider: DnsProvider::Google,
+        dns_record: vec![],
+    }
+}
+
+async fn setup() -> (MockCloudDnsServer, GoogleCloudDnsClient) {
+    init_test_env();
+    let server = MockCloudDnsServer::start().await;
+    let client = GoogleCloudDnsClient::new(&server.service_account_json())
+        .unwrap()
+        .with_endpoint(server.endpoint());
+    (server, client)
+}
+
+/// 测试托管区域和记录查询，访问令牌只签发一次
+#[tokio::test]
+async fn test_list_domains_and_records() -> Result<()> {
+    let (server, client) = setup().await;
+
+    let domains = client.list_domains(1, 100).await?;
+    // 私有区域不会出现在域名列表中
+    assert_eq!(domains.len(), 1);
+    assert_eq!(domains[0].name, "example.com");
+    assert_eq!(domains[0].provider, DnsProvider::Google);
+
+    let records = client.list_dns_records("example.com".to_string()).await?;
+    assert_eq!(records.len(), 3);
+    assert!(records
+        .iter()
+        .any(|r| r.rr == "www" && r.record_type == Type::A && r.value == "192.0.2.1"));
+    assert_eq!(
+        records.iter().filter(|r| r.record_type == Type::NS).count(),
+        2
+    );
+
+    assert_eq!(server.state.lock().unwrap().tokens_issued, 1);
+    Ok(())
+}
+
+/// 测试添加、更新、删除记录
+#[tokio::test]
+async fn test_record_changes() -> Result<()> {
+    let (server, client) = setup().await;
+    let domain = example_domain();
+
+    // 向已有记录集追加值
+    let record = Record::new(
+        Status::Enable,
+        "www".to_string(),
+        Type::A,
+        "192.0.2.2".to_string(),
+        String::new(),
+        300,
+    );
+    client.add_dns_record(&domain, &record).await?;
+    assert_eq!(
+        server.rrdatas("example-zone", "www.example.com.", "A"),
+        Some(vec!["192.0.2.1".to_string(), "192.0.2.2".to_string()])
+    );
+
+    // 新建 TXT 记录集
+    let record = Record::new(
+        Status::Enable,
+        "_acme".to_string(),
+        Type::TXT,
+        "token value".to_string(),
+        String::new(),
+        60,
+    );
+    client.add_dns_record(&domain, &record).await?;
+    assert_eq!(
+        server.rrdatas("example-zone", "_acme.example.com.", "TXT"),
+        Some(vec!["\"token value\"".to_string()])
+    );
+
+    // 修改记录值
+    let records = client.list_dns_records("example.com".to_string()).await?;
+    let mut record = records
+        .into_iter()
+        .find(|r| r.rr == "www" && r.value == "192.0.2.1")
+        .unwrap();
+    record.value = "192.0.2.10".to_string();
+    client.update_dns_record(&domain, &record).await?;
+    assert_eq!(
+        server.rrdatas("example-zone", "www.example.com.", "A"),
+        Some(vec!["192.0.2.10".to_string(), "192.0.2.2".to_string()])
+    );
+
+    // 修改主机记录：旧记录集中移除，新记录集中添加
+    let records = client.list_dns_records("example.com".to_string()).await?;
+    let mut record = records
+        .into_iter()
+        .find(|r| r.rr == "www" && r.value == "192.0.2.2")
+        .unwrap();
+    record.rr = "api".to_string();
+    client.update_dns_record(&domain, &record).await?;
+    assert_eq!(
+        server.rrdatas("example-zone", "www.example.com.", "A"),
+        Some(vec!["192.0.2.10".to_string()])
+    );
+    assert_eq!(
+        server.rrdatas("example-zone", "api.example.com.", "A"),
+        Some(vec!["192.0.2.2".to_string()])
+    );
+
+    // 删除最后一个值后记录集被移除
+    client
+        .delete_dns_record(&domain, "_acme.example.com.|TXT|\"token value\"")
+        .await?;
+    assert_eq!(
+        server.rrdatas("example-zone", "_acme.example.com.", "TXT"),
+        None
+    );
+
+    // 删除不存在的值
+    assert!(client
+        .delete_dns_record(&domain, "www.example.com.|A|198.51.100.1")
+        .await
+        .is_err());
+
+    assert_eq!(server.state.lock().unwrap().changes.len(), 5);
+    Ok(())
+}
+
+/// 测试通过凭证创建客户端以及错误处理
+#[tokio::test]
+async fn test_credentials() -> Result<()> {
+    let (server, client) = setup().await;
+    client.validate_credentials().await?;
+
+    let credential = Credential::ServiceAccount(ServiceAccountCredential {
+        key_json: server.service_account_json(),
+    });
+    assert!(create_dns_client(DnsProvider::Google, credential).is_ok());
+
+    // 不存在的托管区域
+    let err = client
+        .list_dns_records("unknown.org".to_string())
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("unknown.org"));
+
+    // 无法签发令牌时返回错误
+    let broken = server
+        .service_account_json()
+        .replace("/token", "/missing-token");
+    let client = GoogleCloudDnsClient::new(&broken)?.with_endpoint(server.endpoint());
+    assert!(client.validate_credentials().await.is_err());
+    Ok(())
+}
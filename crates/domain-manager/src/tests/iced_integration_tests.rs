@@ -69,6 +69,10 @@ fn create_mock_domain_modals() -> Vec<DomainModal> {
             status: "Active".to_string(),
             created_at: Utc::now().naive_utc(),
             updated_at: None,
+            registration_date: None,
+            expiration_date: None,
+            registrar: None,
+            reminded_days: None,
         },
         DomainModal {
             id: 2,
@@ -77,6 +81,10 @@ fn create_mock_domain_modals() -> Vec<DomainModal> {
             status: "Active".to_string(),
             created_at: Utc::now().naive_utc(),
             updated_at: None,
+            registration_date: None,
+            expiration_date: None,
+            registrar: None,
+            reminded_days: None,
         },
     ]
 }
@@ -0,0 +1,440 @@
+//! 模拟ACME服务
+//!
+//! 只实现签发流程需要的接口，用于不依赖外部服务的挑战和存储测试：
+//! - 不校验 JWS 签名（签名由 `acme::jws` 的单元测试覆盖），只读取公钥、账户和载荷
+//! - 第一次创建订单时返回 `badNonce`，覆盖客户端的重试逻辑
+//! - DNS-01 挑战通过注入的查询函数读取 `_acme-challenge` TXT 记录并校验摘要
+//! - HTTP-01 挑战向 `127.0.0.1:<http_port>` 请求 `/.well-known/acme-challenge/<token>`，
+//!   `Host` 为待验证的域名，相当于 Pebble 的 `-httpport`
+//! - 完成订单时使用测试根证书签发 CSR
+//!
+//! 与真实服务的协议一致性由 `acme_tests` 中基于 Pebble 的测试检查。
+
+use axum::body::Bytes;
+use axum::extract::{Path, State};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use rcgen::{
+    BasicConstraints, Certificate, CertificateParams, CertificateSigningRequestParams, IsCa,
+    KeyPair,
+};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpListener;
+use tracing::info;
+
+/// 查询完整域名的 TXT 记录
+pub type TxtLookup = Arc<dyn Fn(&str) -> Vec<String> + Send + Sync>;
+
+#[derive(Debug, Clone)]
+struct MockAuthorization {
+    identifier: String,
+    wildcard: bool,
+    status: &'static str,
+    token: String,
+}
+
+#[derive(Debug, Clone)]
+struct MockOrder {
+    status: &'static str,
+    identifiers: Vec<String>,
+    authorizations: Vec<usize>,
+    certificate: Option<usize>,
+}
+
+/// 模拟服务的内部状态
+#[derive(Default)]
+pub struct MockAcmeState {
+    next_nonce: usize,
+    bad_nonce_sent: bool,
+    /// 账户公钥（JWK）
+    accounts: Vec<Value>,
+    orders: Vec<MockOrder>,
+    authorizations: Vec<MockAuthorization>,
+    /// 签发的 PEM 证书链
+    certificates: Vec<String>,
+    /// 收到的新建账户请求数量
+    pub new_account_requests: usize,
+    /// 签发的证书数量
+    pub issued: usize,
+    /// 验证 HTTP-01 挑战时连接的端口
+    http_port: u16,
+}
+
+struct Shared {
+    base_url: String,
+    state: Mutex<MockAcmeState>,
+    lookup: TxtLookup,
+    ca_key: KeyPair,
+    ca_cert: Certificate,
+}
+
+type AppState = State<Arc<Shared>>;
+
+/// 模拟的ACME服务
+pub struct MockAcmeServer {
+    pub directory_url: String,
+    shared: Arc<Shared>,
+}
+
+impl MockAcmeServer {
+    /// 启动模拟服务，`lookup` 用于验证 DNS-01 挑战
+    pub async fn start(lookup: TxtLookup) -> Self {
+        let ca_key = KeyPair::generate().unwrap();
+        let mut params = CertificateParams::new(Vec::<String>::new()).unwrap();
+        params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        let ca_cert = params.self_signed(&ca_key).unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let shared = Arc::new(Shared {
+            base_url: format!("http://{}", addr),
+            state: Mutex::new(MockAcmeState::default()),
+            lookup,
+            ca_key,
+            ca_cert,
+        });
+
+        let app = Router::new()
+            .route("/dir", get(directory))
+            .route("/nonce", get(new_nonce).head(new_nonce))
+            .route("/new-acct", post(new_account))
+            .route("/new-order", post(new_order))
+            .route("/order/:id", post(get_order))
+            .route("/authz/:id", post(get_authorization))
+            .route("/chall/:id/:kind", post(respond_challenge))
+            .route("/finalize/:id", post(finalize))
+            .route("/cert/:id", post(get_certificate))
+            .with_state(shared.clone());
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        info!("模拟ACME服务已启动：{}", addr);
+
+        Self {
+            directory_url: format!("http://{}/dir", addr),
+            shared,
+        }
+    }
+
+    /// 设置验证 HTTP-01 挑战时连接的本地端口
+    pub fn with_http_port(self, port: u16) -> Self {
+        self.shared.state.lock().unwrap().http_port = port;
+        self
+    }
+
+    /// 读取内部状态
+    pub fn with_state<T>(&self, f: impl FnOnce(&MockAcmeState) -> T) -> T {
+        f(&self.shared.state.lock().unwrap())
+    }
+}
+
+fn nonce(shared: &Shared) -> (&'static str, String) {
+    let mut state = shared.state.lock().unwrap();
+    state.next_nonce += 1;
+    ("replay-nonce", format!("nonce-{}", state.next_nonce))
+}
+
+fn problem(shared: &Shared, status: StatusCode, kind: &str) -> Response {
+    let body = json!({"type": format!("urn:ietf:params:acme:error:{}", kind), "detail": kind});
+    (status, [nonce(shared)], Json(body)).into_response()
+}
+
+fn reply(shared: &Shared, status: StatusCode, location: Option<String>, body: Value) -> Response {
+    let mut headers = HeaderMap::new();
+    let (name, value) = nonce(shared);
+    headers.insert(name, HeaderValue::from_str(&value).unwrap());
+    if let Some(location) = location {
+        headers.insert(header::LOCATION, HeaderValue::from_str(&location).unwrap());
+    }
+    (status, headers, Json(body)).into_response()
+}
+
+/// 读取 JWS 中的账户公钥和载荷，已注册的账户通过 `kid` 查找公钥
+fn decode(shared: &Shared, body: &[u8]) -> (Value, Value) {
+    let jws: Value = serde_json::from_slice(body).unwrap_or_default();
+    let part = |name: &str| -> Value {
+        URL_SAFE_NO_PAD
+            .decode(jws[name].as_str().unwrap_or_default())
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default()
+    };
+    let protected = part("protected");
+    let account = protected["kid"]
+        .as_str()
+        .and_then(|kid| kid.rsplit('/').next()?.parse::<usize>().ok());
+    let jwk = match account {
+        Some(index) => shared.state.lock().unwrap().accounts[index].clone(),
+        None => protected["jwk"].clone(),
+    };
+    (jwk, part("payload"))
+}
+
+/// JWK 指纹
+fn thumbprint(jwk: &Value) -> String {
+    let canonical = format!(
+        r#"{{"crv":"P-256","kty":"EC","x":"{}","y":"{}"}}"#,
+        jwk["x"].as_str().unwrap(),
+        jwk["y"].as_str().unwrap()
+    );
+    URL_SAFE_NO_PAD.encode(Sha256::digest(canonical.as_bytes()))
+}
+
+fn order_json(shared: &Shared, id: usize, order: &MockOrder) -> Value {
+    let base = &shared.base_url;
+    json!({
+        "status": order.status,
+        "identifiers": order.identifiers.iter().map(|value| json!({"type": "dns", "value": value})).collect::<Vec<_>>(),
+        "authorizations": order.authorizations.iter().map(|authz| format!("{}/authz/{}", base, authz)).collect::<Vec<_>>(),
+        "finalize": format!("{}/finalize/{}", base, id),
+        "certificate": order.certificate.map(|cert| format!("{}/cert/{}", base, cert)),
+    })
+}
+
+fn authorization_json(shared: &Shared, id: usize, authz: &MockAuthorization) -> Value {
+    // 通配符域名只提供 DNS-01 挑战
+    let kinds: &[&str] = if authz.wildcard {
+        &["dns-01"]
+    } else {
+        &["dns-01", "http-01"]
+    };
+    let challenges: Vec<Value> = kinds
+        .iter()
+        .map(|kind| {
+            json!({
+                "type": kind,
+                "url": format!("{}/chall/{}/{}", shared.base_url, id, kind),
+                "token": authz.token,
+                "status": authz.status,
+            })
+        })
+        .collect();
+    json!({
+        "identifier": {"type": "dns", "value": authz.identifier},
+        "status": authz.status,
+        "wildcard": authz.wildcard,
+        "challenges": challenges,
+    })
+}
+
+async fn directory(State(shared): AppState) -> Json<Value> {
+    let base = &shared.base_url;
+    Json(json!({
+        "newNonce": format!("{}/nonce", base),
+        "newAccount": format!("{}/new-acct", base),
+        "newOrder": format!("{}/new-order", base),
+    }))
+}
+
+async fn new_nonce(State(shared): AppState) -> impl IntoResponse {
+    (StatusCode::OK, [nonce(&shared)])
+}
+
+async fn new_account(State(shared): AppState, body: Bytes) -> Response {
+    let (jwk, _) = decode(&shared, &body);
+    let (status, index) = {
+        let mut state = shared.state.lock().unwrap();
+        state.new_account_requests += 1;
+        match state.accounts.iter().position(|account| account == &jwk) {
+            Some(index) => (StatusCode::OK, index),
+            None => {
+                state.accounts.push(jwk);
+                (StatusCode::CREATED, state.accounts.len() - 1)
+            }
+        }
+    };
+    let location = format!("{}/acct/{}", shared.base_url, index);
+    reply(&shared, status, Some(location), json!({"status": "valid"}))
+}
+
+async fn new_order(State(shared): AppState, body: Bytes) -> Response {
+    let (_, payload) = decode(&shared, &body);
+    let (id, order) = {
+        let mut state = shared.state.lock().unwrap();
+        if !std::mem::replace(&mut state.bad_nonce_sent, true) {
+            drop(state);
+            return problem(&shared, StatusCode::BAD_REQUEST, "badNonce");
+        }
+        let identifiers: Vec<String> = payload["identifiers"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|identifier| identifier["value"].as_str().map(str::to_string))
+            .collect();
+        let mut authorizations = Vec::new();
+        for identifier in &identifiers {
+            let token = format!("token-{}", state.authorizations.len());
+            state.authorizations.push(MockAuthorization {
+                identifier: identifier.trim_start_matches("*.").to_string(),
+                wildcard: identifier.starts_with("*."),
+                status: "pending",
+                token,
+            });
+            authorizations.push(state.authorizations.len() - 1);
+        }
+        let order = MockOrder {
+            status: "pending",
+            identifiers,
+            authorizations,
+            certificate: None,
+        };
+        state.orders.push(order.clone());
+        (state.orders.len() - 1, order)
+    };
+    let location = format!("{}/order/{}", shared.base_url, id);
+    reply(
+        &shared,
+        StatusCode::CREATED,
+        Some(location),
+        order_json(&shared, id, &order),
+    )
+}
+
+async fn get_order(State(shared): AppState, Path(id): Path<usize>) -> Response {
+    let order = shared.state.lock().unwrap().orders[id].clone();
+    reply(
+        &shared,
+        StatusCode::OK,
+        None,
+        order_json(&shared, id, &order),
+    )
+}
+
+async fn get_authorization(State(shared): AppState, Path(id): Path<usize>) -> Response {
+    let authz = shared.state.lock().unwrap().authorizations[id].clone();
+    reply(
+        &shared,
+        StatusCode::OK,
+        None,
+        authorization_json(&shared, id, &authz),
+    )
+}
+
+/// 从 Agent 提供的 HTTP 服务读取挑战内容
+async fn fetch_http_challenge(port: u16, identifier: &str, token: &str) -> Vec<String> {
+    let url = format!(
+        "http://127.0.0.1:{}/.well-known/acme-challenge/{}",
+        port, token
+    );
+    let response = reqwest::Client::new()
+        .get(&url)
+        .header(header::HOST, identifier)
+        .send()
+        .await;
+    match response {
+        Ok(response) if response.status().is_success() => response
+            .text()
+            .await
+            .map(|body| vec![body.trim().to_string()])
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    }
+}
+
+async fn respond_challenge(
+    State(shared): AppState,
+    Path((id, kind)): Path<(usize, String)>,
+    body: Bytes,
+) -> Response {
+    let (jwk, _) = decode(&shared, &body);
+    let authz = shared.state.lock().unwrap().authorizations[id].clone();
+    let key_authorization = format!("{}.{}", authz.token, thumbprint(&jwk));
+    let valid = match kind.as_str() {
+        "http-01" => {
+            let port = shared.state.lock().unwrap().http_port;
+            fetch_http_challenge(port, &authz.identifier, &authz.token)
+                .await
+                .contains(&key_authorization)
+        }
+        _ => {
+            let expected = URL_SAFE_NO_PAD.encode(Sha256::digest(key_authorization.as_bytes()));
+            (shared.lookup)(&format!("_acme-challenge.{}", authz.identifier)).contains(&expected)
+        }
+    };
+
+    let authz = {
+        let mut state = shared.state.lock().unwrap();
+        state.authorizations[id].status = if valid { "valid" } else { "invalid" };
+        // 所有授权通过后订单进入 ready，任一失败则订单失败
+        let statuses: Vec<&str> = state
+            .authorizations
+            .iter()
+            .map(|authz| authz.status)
+            .collect();
+        for order in state
+            .orders
+            .iter_mut()
+            .filter(|order| order.status == "pending" && order.authorizations.contains(&id))
+        {
+            if order.authorizations.iter().all(|a| statuses[*a] == "valid") {
+                order.status = "ready";
+            } else if order
+                .authorizations
+                .iter()
+                .any(|a| statuses[*a] == "invalid")
+            {
+                order.status = "invalid";
+            }
+        }
+        state.authorizations[id].clone()
+    };
+    let challenge = json!({
+        "type": kind,
+        "url": format!("{}/chall/{}/{}", shared.base_url, id, kind),
+        "token": authz.token,
+        "status": authz.status,
+    });
+    reply(&shared, StatusCode::OK, None, challenge)
+}
+
+async fn finalize(State(shared): AppState, Path(id): Path<usize>, body: Bytes) -> Response {
+    let (_, payload) = decode(&shared, &body);
+    let order = shared.state.lock().unwrap().orders[id].clone();
+    if order.status != "ready" {
+        return problem(&shared, StatusCode::FORBIDDEN, "orderNotReady");
+    }
+    let csr = payload["csr"]
+        .as_str()
+        .and_then(|csr| URL_SAFE_NO_PAD.decode(csr).ok())
+        .map(rustls_pki_types::CertificateSigningRequestDer::from);
+    let Some(Ok(csr)) = csr.map(|csr| CertificateSigningRequestParams::from_der(&csr)) else {
+        return problem(&shared, StatusCode::BAD_REQUEST, "badCSR");
+    };
+    let certificate = csr
+        .signed_by(&shared.ca_cert, &shared.ca_key)
+        .unwrap()
+        .pem();
+
+    let order = {
+        let mut state = shared.state.lock().unwrap();
+        let chain = format!("{}{}", certificate, shared.ca_cert.pem());
+        state.certificates.push(chain);
+        state.issued += 1;
+        let certificate = state.certificates.len() - 1;
+        let order = &mut state.orders[id];
+        order.status = "valid";
+        order.certificate = Some(certificate);
+        order.clone()
+    };
+    reply(
+        &shared,
+        StatusCode::OK,
+        None,
+        order_json(&shared, id, &order),
+    )
+}
+
+async fn get_certificate(State(shared): AppState, Path(id): Path<usize>) -> Response {
+    let chain = shared.state.lock().unwrap().certificates[id].clone();
+    let content_type = (
+        header::CONTENT_TYPE.as_str(),
+        "application/pem-certificate-chain".to_string(),
+    );
+    (StatusCode::OK, [nonce(&shared), content_type], chain).into_response()
+}
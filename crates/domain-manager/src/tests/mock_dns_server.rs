@@ -0,0 +1,93 @@
+//! 模拟 UDP DNS 服务器
+//!
+//! 按预置的记录回答查询：
+//! - 名称存在但没有该类型的记录时返回空应答，名称不存在时返回 NXDOMAIN
+//! - 查询 NS 时在附加段带上 NS 主机的地址（如果预置了）
+//! - 可以随时替换记录，模拟递归解析服务器缓存过期前后的应答
+
+use hickory_proto::op::{Message, MessageType, ResponseCode};
+use hickory_proto::rr::{Name, RData, Record, RecordType};
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use tokio::net::UdpSocket;
+use tracing::warn;
+
+/// 模拟的 DNS 服务器
+pub struct MockDnsServer {
+    pub addr: SocketAddr,
+    records: Arc<Mutex<Vec<Record>>>,
+}
+
+impl MockDnsServer {
+    /// 在本地随机端口启动服务器
+    pub async fn start(records: Vec<Record>) -> Self {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = socket.local_addr().unwrap();
+        let records = Arc::new(Mutex::new(records));
+        let shared = records.clone();
+        tokio::spawn(async move {
+            let mut buffer = vec![0u8; 4096];
+            while let Ok((len, peer)) = socket.recv_from(&mut buffer).await {
+                let response = match Message::from_vec(&buffer[..len]) {
+                    Ok(request) => answer(&request, &shared.lock().unwrap()),
+                    Err(e) => {
+                        warn!("模拟DNS服务器收到无效请求：{}", e);
+                        continue;
+                    }
+                };
+                let _ = socket.send_to(&response.to_vec().unwrap(), peer).await;
+            }
+        });
+        Self { addr, records }
+    }
+
+    /// 替换服务器上的全部记录
+    pub fn set_records(&self, records: Vec<Record>) {
+        *self.records.lock().unwrap() = records;
+    }
+}
+
+/// 构造一条记录，`name` 为完整域名
+pub fn record(name: &str, rdata: RData) -> Record {
+    Record::from_rdata(Name::from_str(name).unwrap(), 300, rdata)
+}
+
+fn answer(request: &Message, records: &[Record]) -> Message {
+    let mut response = Message::new();
+    response
+        .set_id(request.id())
+        .set_message_type(MessageType::Response)
+        .set_op_code(request.op_code())
+        .set_recursion_desired(request.recursion_desired())
+        .set_authoritative(true);
+    let query = match request.queries().first() {
+        Some(query) => query.clone(),
+        None => {
+            response.set_response_code(ResponseCode::FormErr);
+            return response;
+        }
+    };
+    response.add_query(query.clone());
+
+    let matches = |name: &Name, record_type: RecordType| {
+        records
+            .iter()
+            .filter(|record| record.name() == name && record.record_type() == record_type)
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+    let answers = matches(query.name(), query.query_type());
+    if query.query_type() == RecordType::NS {
+        for ns in &answers {
+            if let Some(RData::NS(target)) = ns.data() {
+                response.add_additionals(matches(&target.0, RecordType::A));
+            }
+        }
+    }
+    if answers.is_empty() && !records.iter().any(|record| record.name() == query.name()) {
+        response.set_response_code(ResponseCode::NXDomain);
+    }
+    response.add_answers(answers);
+    response
+}
@@ -0,0 +1,277 @@
+//! 模拟支持RFC 2136动态更新的权威DNS服务器
+//!
+//! 在本地TCP端口上实现：
+//! - TSIG校验请求并签名响应（密钥错误时返回不带签名的 NOTAUTH）
+//! - AXFR 区域传送，响应拆分为多个报文以覆盖多报文签名校验
+//! - UPDATE 报文的添加、按值删除、删除记录集和删除名称
+
+use hickory_proto::op::{Message, MessageType, OpCode, ResponseCode};
+use hickory_proto::rr::dnssec::rdata::tsig::{make_tsig_record, message_tbs, TsigAlgorithm, TSIG};
+use hickory_proto::rr::dnssec::tsig::TSigner;
+use hickory_proto::rr::rdata::{A, NS, SOA};
+use hickory_proto::rr::{DNSClass, Name, RData, Record, RecordType};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{info, warn};
+
+/// 模拟服务器使用的TSIG密钥名称
+pub const MOCK_TSIG_KEY_NAME: &str = "update-key.";
+/// 模拟服务器使用的TSIG密钥（Base64）
+pub const MOCK_TSIG_SECRET: &str = "c2VjcmV0LWtleS1mb3ItcmZjMjEzNi10ZXN0cw==";
+/// 模拟服务器托管的区域
+pub const MOCK_ZONE: &str = "example.com.";
+
+/// 模拟的RFC 2136服务器
+pub struct MockRfc2136Server {
+    pub addr: String,
+    pub records: Arc<Mutex<Vec<Record>>>,
+}
+
+impl MockRfc2136Server {
+    /// 启动模拟服务器，预置 `example.com` 区域
+    pub async fn start() -> Self {
+        let zone = Name::from_str(MOCK_ZONE).unwrap();
+        let soa = Record::from_rdata(
+            zone.clone(),
+            3600,
+            RData::SOA(SOA::new(
+                Name::from_str("ns1.example.com.").unwrap(),
+                Name::from_str("hostmaster.example.com.").unwrap(),
+                2024010101,
+                3600,
+                600,
+                604800,
+                300,
+            )),
+        );
+        let records = vec![
+            soa,
+            Record::from_rdata(
+                zone.clone(),
+                3600,
+                RData::NS(NS(Name::from_str("ns1.example.com.").unwrap())),
+            ),
+            Record::from_rdata(
+                Name::from_str("ns1.example.com.").unwrap(),
+                3600,
+                RData::A(A::new(192, 0, 2, 53)),
+            ),
+            Record::from_rdata(
+                Name::from_str("www.example.com.").unwrap(),
+                300,
+                RData::A(A::new(192, 0, 2, 1)),
+            ),
+        ];
+        let records = Arc::new(Mutex::new(records));
+
+        let signer = TSigner::new(
+            base64::Engine::decode(&base64::engine::general_purpose::STANDARD, MOCK_TSIG_SECRET)
+                .unwrap(),
+            TsigAlgorithm::HmacSha256,
+            Name::from_str(MOCK_TSIG_KEY_NAME).unwrap(),
+            300,
+        )
+        .unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let shared = records.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let records = shared.clone();
+                let signer = signer.clone();
+                tokio::spawn(async move {
+                    if let Err(err) = handle_connection(stream, signer, records).await {
+                        warn!("模拟DNS服务器处理请求失败：{}", err);
+                    }
+                });
+            }
+        });
+        info!("模拟RFC 2136服务器已启动：{}", addr);
+
+        Self {
+            addr: addr.to_string(),
+            records,
+        }
+    }
+
+    /// 获取指定名称和类型的记录数据
+    pub fn rdatas(&self, name: &str, record_type: RecordType) -> Vec<RData> {
+        let name = Name::from_str(name).unwrap();
+        self.records
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|r| r.name() == &name && r.record_type() == record_type)
+            .filter_map(|r| r.data().cloned())
+            .collect()
+    }
+}
+
+async fn read_message(stream: &mut TcpStream) -> std::io::Result<Vec<u8>> {
+    let length = stream.read_u16().await?;
+    let mut buffer = vec![0; length as usize];
+    stream.read_exact(&mut buffer).await?;
+    Ok(buffer)
+}
+
+async fn write_message(stream: &mut TcpStream, bytes: &[u8]) -> std::io::Result<()> {
+    stream.write_u16(bytes.len() as u16).await?;
+    stream.write_all(bytes).await
+}
+
+/// 对响应签名，返回本次签名的MAC
+///
+/// 第一个响应的签名包含完整的TSIG变量，后续响应只包含时间和误差（RFC 8945 5.3.1）。
+fn sign_response(
+    signer: &TSigner,
+    response: &mut Message,
+    previous_mac: &[u8],
+    first: bool,
+    time: u64,
+) -> Vec<u8> {
+    let pre_tsig = TSIG::new(
+        signer.algorithm().clone(),
+        time,
+        signer.fudge(),
+        Vec::new(),
+        response.id(),
+        0,
+        Vec::new(),
+    );
+
+    // 报文需单独编码，否则前缀的MAC会使名称压缩指针偏移
+    let mut tbs = Vec::new();
+    tbs.extend_from_slice(&(previous_mac.len() as u16).to_be_bytes());
+    tbs.extend_from_slice(previous_mac);
+    if first {
+        tbs.extend_from_slice(
+            &message_tbs(None, response, &pre_tsig, signer.signer_name()).unwrap(),
+        );
+    } else {
+        tbs.extend_from_slice(&response.to_vec().unwrap());
+        tbs.extend_from_slice(&((time >> 32) as u16).to_be_bytes());
+        tbs.extend_from_slice(&(time as u32).to_be_bytes());
+        tbs.extend_from_slice(&signer.fudge().to_be_bytes());
+    }
+
+    let mac = signer.sign(&tbs).unwrap();
+    response.add_tsig(make_tsig_record(
+        signer.signer_name().clone(),
+        pre_tsig.set_mac(mac.clone()),
+    ));
+    mac
+}
+
+fn new_response(request: &Message) -> Message {
+    let mut response = Message::new();
+    response
+        .set_id(request.id())
+        .set_message_type(MessageType::Response)
+        .set_op_code(request.op_code())
+        .set_authoritative(true);
+    response.add_queries(request.queries().to_vec());
+    response
+}
+
+async fn handle_connection(
+    mut stream: TcpStream,
+    signer: TSigner,
+    records: Arc<Mutex<Vec<Record>>>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let bytes = read_message(&mut stream).await?;
+    let request = Message::from_vec(&bytes)?;
+
+    let (request_mac, _, time) = match signer.verify_message_byte(None, &bytes, true) {
+        Ok(result) => result,
+        Err(err) => {
+            warn!("TSIG校验失败：{}", err);
+            let mut response = new_response(&request);
+            response.set_response_code(ResponseCode::NotAuth);
+            write_message(&mut stream, &response.to_vec()?).await?;
+            return Ok(());
+        }
+    };
+
+    let mut responses = match request.op_code() {
+        OpCode::Update => {
+            let mut response = new_response(&request);
+            response.set_response_code(apply_update(&request, &mut records.lock().unwrap()));
+            vec![response]
+        }
+        _ => {
+            let query_type = request.queries().first().map(|q| q.query_type());
+            let records = records.lock().unwrap().clone();
+            match query_type {
+                Some(RecordType::AXFR) => {
+                    // SOA + 其余记录 + SOA，拆分为两个报文
+                    let soa = records[0].clone();
+                    let middle = records.len() / 2;
+                    let mut first = new_response(&request);
+                    first.add_answers(records[..middle].to_vec());
+                    let mut second = new_response(&request);
+                    second.add_answers(records[middle..].to_vec());
+                    second.add_answer(soa);
+                    vec![first, second]
+                }
+                _ => {
+                    let mut response = new_response(&request);
+                    response.add_answer(records[0].clone());
+                    vec![response]
+                }
+            }
+        }
+    };
+
+    let mut previous_mac = request_mac;
+    for (index, response) in responses.iter_mut().enumerate() {
+        previous_mac = sign_response(&signer, response, &previous_mac, index == 0, time);
+        write_message(&mut stream, &response.to_vec()?).await?;
+    }
+    Ok(())
+}
+
+/// 按 RFC 2136 3.4.2 处理更新段
+fn apply_update(request: &Message, records: &mut Vec<Record>) -> ResponseCode {
+    let zone = match request.queries().first() {
+        Some(zone) => zone.name().clone(),
+        None => return ResponseCode::FormErr,
+    };
+    if zone != Name::from_str(MOCK_ZONE).unwrap() {
+        return ResponseCode::NotAuth;
+    }
+
+    // UPDATE 报文中更新段位于 Authority 段
+    for update in request.name_servers() {
+        if !zone.zone_of(update.name()) {
+            return ResponseCode::NotZone;
+        }
+
+        match update.dns_class() {
+            DNSClass::IN => {
+                let exists = records.iter().any(|r| {
+                    r.name() == update.name()
+                        && r.record_type() == update.record_type()
+                        && r.data() == update.data()
+                });
+                if !exists {
+                    records.push(update.clone());
+                }
+            }
+            DNSClass::NONE => records.retain(|r| {
+                !(r.name() == update.name()
+                    && r.record_type() == update.record_type()
+                    && r.data() == update.data())
+            }),
+            DNSClass::ANY => records.retain(|r| {
+                !(r.name() == update.name()
+                    && (update.record_type() == RecordType::ANY
+                        || r.record_type() == update.record_type()))
+            }),
+            _ => return ResponseCode::FormErr,
+        }
+    }
+    ResponseCode::NoError
+}
@@ -7,10 +7,32 @@
 //! - 界面渲染测试
 //! - Iced框架集成测试
 //! - 阿里云客户端模拟测试
+//! - Google Cloud DNS 模拟服务测试
+//! - RFC 2136 模拟服务测试
+//! - 命令行接口测试
+//! - DDNS 记录更新测试
+//! - ACME 证书申请测试
+//! - 证书监控测试
+//! - 域名到期提醒测试
+//! - 解析生效检查测试
+//! - Agent P2P 信令转发测试
 
+pub mod acme_tests;
+pub mod certmon_tests;
+pub mod cli_tests;
+pub mod ddns_tests;
 pub mod dns_sync_tests;
+pub mod expiry_tests;
+pub mod google_cloud_dns_tests;
 pub mod i18n_tests;
 pub mod iced_integration_tests;
+pub mod mock_acme_server;
 pub mod mock_aliyun_client;
+pub mod mock_dns_server;
+pub mod mock_google_cloud_dns;
+pub mod mock_rfc2136_server;
+pub mod p2p_tests;
+pub mod propagation_tests;
 pub mod provider_handler_tests;
+pub mod rfc2136_tests;
 pub mod test_utils;
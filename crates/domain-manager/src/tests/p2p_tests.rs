@@ -0,0 +1,150 @@
+//! P2P 信令转发测试
+//!
+//! 两个 Agent 通过 WebSocket 连接 Hub：
+//! - Offer、Answer 和 ICE Candidate 转发给会话的另一方，Offer 的来源由 Hub 填写
+//! - 目标 Agent 不在线时直接返回失败
+//! - 连接建立前一方断开时通知另一方
+
+use crate::agent::protocol::AgentMessage;
+use crate::tests::test_utils::{start_hub, text};
+use futures_util::{SinkExt, StreamExt};
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+use uuid::Uuid;
+
+type Ws = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// 下一条 Agent 消息
+async fn recv(ws: &mut Ws) -> AgentMessage {
+    let msg = tokio::time::timeout(Duration::from_secs(10), ws.next())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    serde_json::from_str(&msg.into_text().unwrap()).unwrap()
+}
+
+/// 用密钥注册一个 Agent，返回连接和 Hub 分配的 Agent ID
+async fn register(addr: &str, key: &str) -> (Ws, Uuid) {
+    let (mut ws, _) = tokio_tungstenite::connect_async(format!("ws://{}/ws", addr))
+        .await
+        .unwrap();
+    ws.send(text(AgentMessage::RegisterWithSecret {
+        agent_id: None,
+        agent_name: key.to_string(),
+        agent_key: key.to_string(),
+        capabilities: Vec::new(),
+        version: None,
+        hostname: None,
+    }))
+    .await
+    .unwrap();
+    match recv(&mut ws).await {
+        AgentMessage::RegisterAccepted { agent_id, .. } => (ws, agent_id),
+        msg => panic!("unexpected message: {:?}", msg),
+    }
+}
+
+/// 测试 Offer、Answer 和 ICE Candidate 的转发
+#[tokio::test]
+async fn test_p2p_signaling_relay() {
+    let (_hub, addr) = start_hub(None).await;
+    let (mut a, a_id) = register(&addr, "agent-a").await;
+    let (mut b, b_id) = register(&addr, "agent-b").await;
+    let request_id = Uuid::new_v4();
+
+    a.send(text(AgentMessage::P2pConnectRequest { request_id, target_agent_id: b_id }))
+        .await
+        .unwrap();
+    // Agent 填写的来源被 Hub 替换为已验证的 ID
+    a.send(text(AgentMessage::P2pConnectOffer {
+        request_id,
+        source_agent_id: Uuid::nil(),
+        sdp_offer: "v=0".to_string(),
+    }))
+    .await
+    .unwrap();
+    a.send(text(AgentMessage::P2pIceCandidate {
+        request_id,
+        candidate: "candidate:1 1 udp 2130706431 192.0.2.1 40000 typ host".to_string(),
+        sdp_mid: Some("0".to_string()),
+        sdp_m_line_index: Some(0),
+    }))
+    .await
+    .unwrap();
+
+    match recv(&mut b).await {
+        AgentMessage::P2pConnectOffer { request_id: id, source_agent_id, sdp_offer } => {
+            assert_eq!(id, request_id);
+            assert_eq!(source_agent_id, a_id);
+            assert_eq!(sdp_offer, "v=0");
+        }
+        msg => panic!("unexpected message: {:?}", msg),
+    }
+    match recv(&mut b).await {
+        AgentMessage::P2pIceCandidate { candidate, .. } => assert!(candidate.contains("typ host")),
+        msg => panic!("unexpected message: {:?}", msg),
+    }
+
+    b.send(text(AgentMessage::P2pAnswer {
+        request_id,
+        target_agent_id: a_id,
+        sdp_answer: "v=0".to_string(),
+    }))
+    .await
+    .unwrap();
+    assert!(matches!(recv(&mut a).await, AgentMessage::P2pAnswer { .. }));
+
+    a.send(text(AgentMessage::P2pConnected { request_id })).await.unwrap();
+    assert!(matches!(recv(&mut b).await, AgentMessage::P2pConnected { .. }));
+
+    // 会话结束后不再转发
+    b.send(text(AgentMessage::P2pAnswer {
+        request_id,
+        target_agent_id: a_id,
+        sdp_answer: "v=0".to_string(),
+    }))
+    .await
+    .unwrap();
+    match recv(&mut b).await {
+        AgentMessage::P2pFailed { request_id: id, .. } => assert_eq!(id, request_id),
+        msg => panic!("unexpected message: {:?}", msg),
+    }
+}
+
+/// 测试目标不在线以及一方断开时的失败通知
+#[tokio::test]
+async fn test_p2p_failures() {
+    let (_hub, addr) = start_hub(None).await;
+    let (mut a, _) = register(&addr, "agent-a").await;
+    let (b, b_id) = register(&addr, "agent-b").await;
+
+    let request_id = Uuid::new_v4();
+    a.send(text(AgentMessage::P2pConnectRequest { request_id, target_agent_id: Uuid::new_v4() }))
+        .await
+        .unwrap();
+    match recv(&mut a).await {
+        AgentMessage::P2pFailed { request_id: id, reason } => {
+            assert_eq!(id, request_id);
+            assert!(reason.contains("不在线"), "{}", reason);
+        }
+        msg => panic!("unexpected message: {:?}", msg),
+    }
+
+    let request_id = Uuid::new_v4();
+    a.send(text(AgentMessage::P2pConnectRequest { request_id, target_agent_id: b_id }))
+        .await
+        .unwrap();
+    // 请求被 Hub 处理后再断开
+    a.send(text(AgentMessage::Ping { timestamp: 0 })).await.unwrap();
+    assert!(matches!(recv(&mut a).await, AgentMessage::Pong { .. }));
+    drop(b);
+    match recv(&mut a).await {
+        AgentMessage::P2pFailed { request_id: id, reason } => {
+            assert_eq!(id, request_id);
+            assert!(reason.contains(&b_id.to_string()), "{}", reason);
+        }
+        msg => panic!("unexpected message: {:?}", msg),
+    }
+}
@@ -0,0 +1,226 @@
+//! 解析生效检查测试
+//!
+//! 启动本地模拟 DNS 服务器分别充当递归解析服务器和权威服务器，测试：
+//! - 通过递归解析服务器找到权威服务器，按服务器列出应答和生效状态
+//! - 与数据库中同一主机记录的所有值对比，缓存过期后重新检查变为已生效
+//! - 服务器无法访问、找不到权威服务器时的结果
+
+use crate::gui::model::domain::DnsProvider;
+use crate::gui::types::credential::{Credential, UsernamePasswordCredential};
+use crate::model::dns_record_response::Type;
+use crate::models::account::NewAccount;
+use crate::models::domain::{DomainStatus, NewDomain};
+use crate::models::record::NewRecord;
+use crate::propagation::{self, PropagationChecker, PropagationStatus, ServerKind, ServerResult};
+use crate::storage::{add_domain, create_account, init_memory_database, records};
+use crate::tests::mock_dns_server::{record, MockDnsServer};
+use crate::tests::test_utils::init_test_env;
+use hickory_proto::rr::rdata::{A, NS, TXT};
+use hickory_proto::rr::{Name, RData, Record};
+use std::str::FromStr;
+use std::time::Duration;
+
+fn a(name: &str, ip: [u8; 4]) -> Record {
+    record(name, RData::A(A::new(ip[0], ip[1], ip[2], ip[3])))
+}
+
+/// example.com 的 NS 记录，`glue` 为 true 时同时返回 NS 主机地址
+fn delegation(glue: bool) -> Vec<Record> {
+    let mut records = vec![record(
+        "example.com.",
+        RData::NS(NS(Name::from_str("ns1.example.com.").unwrap())),
+    )];
+    if glue {
+        records.push(a("ns1.example.com.", [127, 0, 0, 1]));
+    }
+    records
+}
+
+/// 权威服务器上的最新记录
+fn zone() -> Vec<Record> {
+    let mut records = delegation(true);
+    records.push(a("www.example.com.", [192, 0, 2, 10]));
+    records.push(a("www.example.com.", [192, 0, 2, 11]));
+    records.push(record(
+        "example.com.",
+        RData::TXT(TXT::new(vec!["v=spf1 -all".to_string()])),
+    ));
+    records
+}
+
+fn status_of<'a>(results: &'a [ServerResult], server: &str) -> &'a ServerResult {
+    results
+        .iter()
+        .find(|result| result.server == server)
+        .unwrap_or_else(|| panic!("没有服务器 {} 的结果: {:?}", server, results))
+}
+
+#[tokio::test]
+async fn test_propagation_matrix() {
+    init_test_env();
+    let authoritative = MockDnsServer::start(zone()).await;
+    // 缓存了旧值的解析服务器，NS 应答带地址
+    let mut stale_records = delegation(true);
+    stale_records.push(a("www.example.com.", [192, 0, 2, 1]));
+    let stale = MockDnsServer::start(stale_records).await;
+    // 已更新的解析服务器，NS 应答不带地址，需要单独查询 NS 主机
+    let mut fresh_records = zone();
+    fresh_records.retain(|record| record.name().to_ascii() != "ns1.example.com.");
+    fresh_records.push(a("ns1.example.com.", [127, 0, 0, 1]));
+    let fresh = MockDnsServer::start(fresh_records).await;
+
+    let expected = vec!["192.0.2.10".to_string(), "192.0.2.11".to_string()];
+    for resolvers in [vec![stale.addr, fresh.addr], vec![fresh.addr, stale.addr]] {
+        let checker = PropagationChecker::new(resolvers)
+            .with_authoritative_port(authoritative.addr.port())
+            .with_timeout(Duration::from_secs(2));
+        let report = checker
+            .check("example.com", "www", &Type::A, &expected)
+            .await
+            .unwrap();
+
+        assert_eq!(report.fqdn, "www.example.com");
+        assert_eq!(report.results.len(), 3);
+        let stale_result = status_of(&report.results, &stale.addr.to_string());
+        assert_eq!(stale_result.kind, ServerKind::Resolver);
+        assert_eq!(stale_result.status, PropagationStatus::Mismatched);
+        assert_eq!(stale_result.answers, vec!["192.0.2.1"]);
+        let fresh_result = status_of(&report.results, &fresh.addr.to_string());
+        assert_eq!(fresh_result.status, PropagationStatus::Matched);
+        let ns_result = status_of(&report.results, "ns1.example.com");
+        assert_eq!(ns_result.kind, ServerKind::Authoritative);
+        assert_eq!(ns_result.address, Some(authoritative.addr));
+        assert_eq!(ns_result.status, PropagationStatus::Matched);
+        assert_eq!(report.matched_count(), 2);
+        assert!(!report.is_propagated());
+    }
+
+    // 缓存过期后所有服务器一致
+    stale.set_records(zone());
+    let checker = PropagationChecker::new(vec![stale.addr])
+        .with_authoritative_port(authoritative.addr.port());
+    let report = checker
+        .check("example.com", "www", &Type::A, &expected)
+        .await
+        .unwrap();
+    assert!(report.is_propagated(), "{:?}", report.results);
+
+    // 根域名的 TXT 记录和不存在的主机记录
+    let report = checker
+        .check("example.com", "@", &Type::TXT, &["v=spf1 -all".to_string()])
+        .await
+        .unwrap();
+    assert!(report.is_propagated(), "{:?}", report.results);
+    let report = checker
+        .check("example.com", "api", &Type::A, &["192.0.2.20".to_string()])
+        .await
+        .unwrap();
+    assert!(report
+        .results
+        .iter()
+        .all(|result| result.status == PropagationStatus::Missing));
+}
+
+#[tokio::test]
+async fn test_check_record_from_storage() {
+    init_test_env();
+    let conn = init_memory_database().await.unwrap();
+    let account = create_account(
+        &conn,
+        NewAccount {
+            provider: DnsProvider::Aliyun,
+            username: "ops".to_string(),
+            email: String::new(),
+            credential: Credential::UsernamePassword(UsernamePasswordCredential {
+                username: "ops".to_string(),
+                password: "secret".to_string(),
+            }),
+        },
+    )
+    .await
+    .unwrap();
+    let domain = add_domain(
+        &conn,
+        NewDomain {
+            domain_name: "example.com".to_string(),
+            registration_date: None,
+            expiration_date: None,
+            registrar: None,
+            status: DomainStatus::Active,
+            account_id: account.id,
+        },
+    )
+    .await
+    .unwrap();
+    let new_record = |rr: &str, record_type: &str, value: &str| NewRecord {
+        domain_id: domain.id,
+        record_name: rr.to_string(),
+        record_type: record_type.to_string(),
+        record_value: value.to_string(),
+        ttl: 600,
+    };
+    let saved = records::add_records_many(
+        &conn,
+        vec![
+            new_record("www", "A", "192.0.2.10"),
+            new_record("www", "A", "192.0.2.11"),
+            new_record("mail", "A", "192.0.2.25"),
+        ],
+    )
+    .await
+    .unwrap();
+
+    let authoritative = MockDnsServer::start(zone()).await;
+    // 解析服务器只知道其中一个值
+    let mut partial = delegation(true);
+    partial.push(a("www.example.com.", [192, 0, 2, 10]));
+    let resolver = MockDnsServer::start(partial).await;
+    // 无法访问的解析服务器
+    let closed = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+    let closed_addr = closed.local_addr().unwrap();
+    drop(closed);
+
+    let checker = PropagationChecker::new(vec![resolver.addr, closed_addr])
+        .with_authoritative_port(authoritative.addr.port())
+        .with_timeout(Duration::from_millis(500));
+    let report = propagation::check_record(&conn, &checker, saved[0].id)
+        .await
+        .unwrap();
+    assert_eq!(report.expected, vec!["192.0.2.10", "192.0.2.11"]);
+    assert_eq!(
+        status_of(&report.results, &resolver.addr.to_string()).status,
+        PropagationStatus::Mismatched
+    );
+    let failed = status_of(&report.results, &closed_addr.to_string());
+    assert_eq!(failed.status, PropagationStatus::Failed);
+    assert!(failed.error.is_some());
+    assert_eq!(
+        status_of(&report.results, "ns1.example.com").status,
+        PropagationStatus::Matched
+    );
+
+    // mail 只在本地保存，还没有同步到服务商
+    let report = propagation::check_record(&conn, &checker, saved[2].id)
+        .await
+        .unwrap();
+    assert_eq!(report.fqdn, "mail.example.com");
+    assert_eq!(
+        status_of(&report.results, "ns1.example.com").status,
+        PropagationStatus::Missing
+    );
+
+    // 解析服务器查不到 NS 时，权威服务器一行记录失败原因
+    resolver.set_records(vec![a("www.example.com.", [192, 0, 2, 10])]);
+    let checker = PropagationChecker::new(vec![resolver.addr])
+        .with_authoritative_port(authoritative.addr.port());
+    let report = propagation::check_record(&conn, &checker, saved[0].id)
+        .await
+        .unwrap();
+    let ns_result = status_of(&report.results, "example.com 的权威服务器");
+    assert_eq!(ns_result.kind, ServerKind::Authoritative);
+    assert_eq!(ns_result.status, PropagationStatus::Failed);
+
+    assert!(propagation::check_record(&conn, &checker, 9999)
+        .await
+        .is_err());
+}
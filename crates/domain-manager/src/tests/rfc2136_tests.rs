@@ -0,0 +1,162 @@
+//! RFC 2136 客户端测试
+//!
+//! 使用本地模拟的权威DNS服务器测试完整的请求流程：
+//! - TSIG 签名的 SOA 查询验证凭证
+//! - AXFR 区域传送获取记录（多报文签名校验）
+//! - UPDATE 报文添加、更新、删除记录
+//! - 错误密钥被服务器拒绝
+
+use crate::api::dns_client::DnsClientTrait;
+use crate::api::provider::create_dns_client;
+use crate::api::provider::rfc2136::Rfc2136DnsClient;
+use crate::gui::model::domain::{DnsProvider, DomainName};
+use crate::gui::types::credential::{Credential, TsigCredential, DEFAULT_TSIG_ALGORITHM};
+use crate::model::dns_record_response::{Record, Status, Type};
+use crate::tests::mock_rfc2136_server::{MockRfc2136Server, MOCK_TSIG_KEY_NAME, MOCK_TSIG_SECRET};
+use crate::tests::test_utils::init_test_env;
+use anyhow::Result;
+use hickory_proto::rr::rdata::{A, TXT};
+use hickory_proto::rr::{RData, RecordType};
+
+fn example_domain() -> DomainName {
+    DomainName {
+        name: "example.com".to_string(),
+        provider: DnsProvider::Rfc2136,
+        dns_record: vec![],
+    }
+}
+
+fn credential(server: &MockRfc2136Server, secret: &str) -> TsigCredential {
+    TsigCredential {
+        server: server.addr.clone(),
+        key_name: MOCK_TSIG_KEY_NAME.to_string(),
+        algorithm: DEFAULT_TSIG_ALGORITHM.to_string(),
+        secret: secret.to_string(),
+        zones: "example.com".to_string(),
+    }
+}
+
+async fn setup() -> (MockRfc2136Server, Rfc2136DnsClient) {
+    init_test_env();
+    let server = MockRfc2136Server::start().await;
+    let client = Rfc2136DnsClient::new(&credential(&server, MOCK_TSIG_SECRET)).unwrap();
+    (server, client)
+}
+
+/// 测试区域列表和AXFR记录查询
+#[tokio::test]
+async fn test_list_domains_and_records() -> Result<()> {
+    let (_server, client) = setup().await;
+
+    let domains = client.list_domains(1, 100).await?;
+    assert_eq!(domains.len(), 1);
+    assert_eq!(domains[0].name, "example.com");
+    assert_eq!(domains[0].provider, DnsProvider::Rfc2136);
+
+    // SOA 记录不会出现在列表中
+    let records = client.list_dns_records("example.com".to_string()).await?;
+    assert_eq!(records.len(), 3);
+    assert!(records
+        .iter()
+        .any(|r| r.rr == "www" && r.record_type == Type::A && r.value == "192.0.2.1"));
+    assert!(records
+        .iter()
+        .any(|r| r.rr == "@" && r.record_type == Type::NS && r.value == "ns1.example.com."));
+    assert!(records.iter().all(|r| r.record_type != Type::SOA));
+    Ok(())
+}
+
+/// 测试添加、更新、删除记录
+#[tokio::test]
+async fn test_record_updates() -> Result<()> {
+    let (server, client) = setup().await;
+    let domain = example_domain();
+
+    let record = Record::new(
+        Status::Enable,
+        "www".to_string(),
+        Type::A,
+        "192.0.2.2".to_string(),
+        String::new(),
+        300,
+    );
+    client.add_dns_record(&domain, &record).await?;
+    assert_eq!(
+        server.rdatas("www.example.com.", RecordType::A),
+        vec![
+            RData::A(A::new(192, 0, 2, 1)),
+            RData::A(A::new(192, 0, 2, 2))
+        ]
+    );
+
+    let record = Record::new(
+        Status::Enable,
+        "_acme".to_string(),
+        Type::TXT,
+        "token value".to_string(),
+        String::new(),
+        60,
+    );
+    client.add_dns_record(&domain, &record).await?;
+    assert_eq!(
+        server.rdatas("_acme.example.com.", RecordType::TXT),
+        vec![RData::TXT(TXT::new(vec!["token value".to_string()]))]
+    );
+
+    // 修改记录值和主机记录
+    let records = client.list_dns_records("example.com".to_string()).await?;
+    let mut record = records
+        .into_iter()
+        .find(|r| r.rr == "www" && r.value == "192.0.2.2")
+        .unwrap();
+    record.rr = "api".to_string();
+    record.value = "192.0.2.10".to_string();
+    client.update_dns_record(&domain, &record).await?;
+    assert_eq!(
+        server.rdatas("www.example.com.", RecordType::A),
+        vec![RData::A(A::new(192, 0, 2, 1))]
+    );
+    assert_eq!(
+        server.rdatas("api.example.com.", RecordType::A),
+        vec![RData::A(A::new(192, 0, 2, 10))]
+    );
+
+    // 删除记录
+    let records = client.list_dns_records("example.com".to_string()).await?;
+    let record = records.iter().find(|r| r.rr == "_acme").unwrap();
+    client.delete_dns_record(&domain, &record.record_id).await?;
+    assert!(server
+        .rdatas("_acme.example.com.", RecordType::TXT)
+        .is_empty());
+
+    // 区域之外的记录被服务器拒绝
+    let other = DomainName {
+        name: "other.org".to_string(),
+        ..example_domain()
+    };
+    assert!(client
+        .add_dns_record(&other, &record.clone())
+        .await
+        .is_err());
+    Ok(())
+}
+
+/// 测试凭证验证、客户端创建及错误密钥
+#[tokio::test]
+async fn test_credentials() -> Result<()> {
+    let (server, client) = setup().await;
+    client.validate_credentials().await?;
+
+    let cred = Credential::Tsig(credential(&server, MOCK_TSIG_SECRET));
+    let client = create_dns_client(DnsProvider::Rfc2136, cred)?;
+    client.validate_credentials().await?;
+
+    // 密钥错误时服务器返回 NOTAUTH
+    let wrong = Rfc2136DnsClient::new(&credential(&server, "d3Jvbmcta2V5"))?;
+    assert!(wrong.validate_credentials().await.is_err());
+    assert!(wrong
+        .list_dns_records("example.com".to_string())
+        .await
+        .is_err());
+    Ok(())
+}
@@ -2,13 +2,30 @@
 //!
 //! 提供测试中使用的通用工具函数，包括：
 //! - 统一的日志初始化
-//! - 测试数据库设置
+//! - 测试用主密钥
+//! - 测试数据库设置：连接模拟RFC 2136服务器的 TSIG 账户并同步
 //! - 测试数据生成
+//! - 启动 Agent Hub 并等待 Agent 注册
 
-use std::sync::Once;
+use crate::agent::connection::{AgentHub, AgentHubHandle};
+use crate::agent::protocol::AgentMessage;
+use crate::agent::registry::AgentRegistry;
+use crate::cli::{execute, Command};
+use crate::gui::model::domain::DnsProvider;
+use crate::gui::types::credential::{Credential, TsigCredential, DEFAULT_TSIG_ALGORITHM};
+use crate::models::account::NewAccount;
+use crate::storage::encryption::{DatabaseKeyManager, MasterKey};
+use crate::storage::{create_account, init_memory_database};
+use crate::tests::mock_rfc2136_server::{MockRfc2136Server, MOCK_TSIG_KEY_NAME, MOCK_TSIG_SECRET};
+use sea_orm::DatabaseConnection;
+use std::sync::{Arc, Once};
+use std::time::Duration;
+use tokio_tungstenite::tungstenite::Message;
 use tracing::info;
+use uuid::Uuid;
 
 static INIT: Once = Once::new();
+static MASTER_KEY_INIT: Once = Once::new();
 
 /// 初始化测试环境
 ///
@@ -20,4 +37,88 @@ pub fn init_test_env() {
             .try_init();
         info!("测试环境初始化完成");
     });
+    init_test_master_key();
+}
+
+/// 初始化测试用主密钥
+///
+/// 主密钥是进程级状态，所有测试共用同一个随机密钥，避免并行测试互相覆盖
+pub fn init_test_master_key() {
+    MASTER_KEY_INIT.call_once(|| {
+        DatabaseKeyManager::set_key(MasterKey::generate());
+    });
+}
+
+/// 添加管理模拟RFC 2136服务器上 `example.com` 区域的 TSIG 账户
+pub async fn add_tsig_account(
+    conn: &DatabaseConnection,
+    username: &str,
+    server: &MockRfc2136Server,
+) {
+    create_account(
+        conn,
+        NewAccount {
+            provider: DnsProvider::Rfc2136,
+            username: username.to_string(),
+            email: String::new(),
+            credential: Credential::Tsig(TsigCredential {
+                server: server.addr.clone(),
+                key_name: MOCK_TSIG_KEY_NAME.to_string(),
+                algorithm: DEFAULT_TSIG_ALGORITHM.to_string(),
+                secret: MOCK_TSIG_SECRET.to_string(),
+                zones: "example.com".to_string(),
+            }),
+        },
+    )
+    .await
+    .unwrap();
+}
+
+/// 启动模拟RFC 2136服务器，在内存数据库中添加名为 `bind` 的 TSIG 账户并同步
+pub async fn setup_tsig_account() -> (Arc<MockRfc2136Server>, DatabaseConnection) {
+    init_test_env();
+    let server = Arc::new(MockRfc2136Server::start().await);
+    let conn = init_memory_database().await.unwrap();
+    add_tsig_account(&conn, "bind", &server).await;
+    execute(&conn, Command::Sync { account: None })
+        .await
+        .unwrap();
+    (server, conn)
+}
+
+/// 取一个当前没有服务监听的本地地址
+pub fn free_addr() -> String {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.local_addr().unwrap().to_string()
+}
+
+/// 在空闲端口启动 Agent Hub，返回句柄和监听地址
+///
+/// 传入数据库时 Hub 会保存 Agent 上报的结果
+pub async fn start_hub(conn: Option<&DatabaseConnection>) -> (AgentHubHandle, String) {
+    let addr = free_addr();
+    let mut hub = AgentHub::new(Arc::new(AgentRegistry::new()), &addr);
+    if let Some(conn) = conn {
+        hub = hub.with_database(conn.clone());
+    }
+    hub.start().await.unwrap();
+    let handle = hub.handle();
+    tokio::spawn(async move { hub.run().await });
+    (handle, addr)
+}
+
+/// 等待第一个 Agent 注册，返回其 ID
+pub async fn wait_agent(hub: &AgentHubHandle) -> Uuid {
+    for _ in 0..100 {
+        if let Some(agent) = hub.registry().get_all().await.first() {
+            return agent.id;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    panic!("Agent 没有注册");
+}
+
+/// 把 Agent 消息编码为 WebSocket 文本帧
+pub fn text(msg: AgentMessage) -> Message {
+    Message::Text(serde_json::to_string(&msg).unwrap())
 }
@@ -0,0 +1,269 @@
+//! 声明式配置（DNS-as-code）
+//!
+//! 配置按「域名 → 主机记录 + 类型 + 线路」描述期望的解析记录，可以用 YAML 或 TOML 编写：
+//!
+//! ```yaml
+//! domains:
+//!   example.com:
+//!     account: bind   # 可选，账户 ID 或用户名，默认使用域名所属的账户
+//!     ttl: 600        # 可选，记录未指定 TTL 时使用
+//!     records:
+//!       - { rr: "@", type: A, value: 192.0.2.1 }
+//!       - { rr: www, type: CNAME, value: example.com., ttl: 300 }
+//!       - { rr: www, type: A, value: 192.0.2.2, line: telecom }
+//! ```
+//!
+//! `line` 为解析线路，默认为 `default`。其他线路使用服务商自己的线路名称，
+//! 如阿里云的 `telecom`、DNSPod 的 `电信`，只有支持线路的服务商才能使用。
+//!
+//! ```toml
+//! [domains."example.com"]
+//! ttl = 600
+//!
+//! [[domains."example.com".records]]
+//! rr = "@"
+//! type = "A"
+//! value = "192.0.2.1"
+//! ```
+
+use super::{ZoneRecord, DEFAULT_ZONE_TTL};
+use crate::api::provider::rrset::parse_record_type;
+use crate::model::dns_record_response::{Line, Type};
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+/// 默认解析线路
+pub const DEFAULT_LINE: &str = "default";
+
+/// 声明式配置文件
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct DesiredState {
+    /// 域名 → 期望的解析记录
+    #[serde(default)]
+    pub domains: BTreeMap<String, DesiredDomain>,
+}
+
+/// 单个域名的期望状态
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct DesiredDomain {
+    /// 账户 ID 或用户名
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account: Option<String>,
+    /// 记录的默认 TTL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl: Option<u32>,
+    #[serde(default)]
+    pub records: Vec<DesiredRecord>,
+}
+
+/// 单条期望的解析记录
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct DesiredRecord {
+    /// 主机记录，根域名为 `@`
+    pub rr: String,
+    #[serde(rename = "type")]
+    pub record_type: String,
+    pub value: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl: Option<u32>,
+    /// 解析线路
+    #[serde(default = "default_line")]
+    pub line: String,
+}
+
+fn default_line() -> String {
+    DEFAULT_LINE.to_string()
+}
+
+impl DesiredState {
+    /// 解析配置文件内容，按文件扩展名识别格式：`.toml` 为 TOML，其余按 YAML 解析
+    pub fn parse(content: &str, path: &Path) -> Result<Self> {
+        let is_toml = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("toml"));
+        if is_toml {
+            Self::from_toml(content)
+        } else {
+            Self::from_yaml(content)
+        }
+    }
+
+    pub fn from_yaml(content: &str) -> Result<Self> {
+        serde_yaml::from_str(content).map_err(|e| anyhow!("YAML 配置格式错误: {}", e))
+    }
+
+    pub fn from_toml(content: &str) -> Result<Self> {
+        toml::from_str(content).map_err(|e| anyhow!("TOML 配置格式错误: {}", e))
+    }
+
+    /// 按域名列出配置，域名统一为小写且不带结尾的 `.`
+    pub fn domains(&self) -> impl Iterator<Item = (String, &DesiredDomain)> {
+        self.domains
+            .iter()
+            .map(|(name, domain)| (name.trim_end_matches('.').to_ascii_lowercase(), domain))
+    }
+}
+
+impl DesiredDomain {
+    /// 校验配置并转换为目标记录
+    pub fn zone_records(&self, domain: &str) -> Result<Vec<ZoneRecord>> {
+        let default_ttl = self.ttl.unwrap_or(DEFAULT_ZONE_TTL);
+        let mut seen = HashSet::new();
+        let mut records = Vec::with_capacity(self.records.len());
+
+        for (index, record) in self.records.iter().enumerate() {
+            let zone_record = record
+                .to_zone_record(default_ttl)
+                .with_context(|| format!("{} 的第 {} 条记录", domain, index + 1))?;
+            let key = (
+                zone_record.rr.to_ascii_lowercase(),
+                zone_record.record_type.get_value().to_string(),
+                String::from(zone_record.line.clone()),
+                zone_record.value.clone(),
+            );
+            if !seen.insert(key) {
+                bail!(
+                    "{} 的第 {} 条记录重复: {} {} {}",
+                    domain,
+                    index + 1,
+                    zone_record.rr,
+                    zone_record.record_type.get_value(),
+                    zone_record.value
+                );
+            }
+            records.push(zone_record);
+        }
+        Ok(records)
+    }
+}
+
+impl DesiredRecord {
+    fn to_zone_record(&self, default_ttl: u32) -> Result<ZoneRecord> {
+        let record_type = parse_record_type(&self.record_type.trim().to_ascii_uppercase())
+            .ok_or_else(|| anyhow!("不支持的记录类型「{}」", self.record_type))?;
+        if record_type == Type::SOA {
+            bail!("SOA 记录由服务商管理，不能在配置中声明");
+        }
+        let line = self.line.trim();
+        if line.is_empty() {
+            bail!("解析线路不能为空");
+        }
+        let value = self.value.trim();
+        if value.is_empty() {
+            bail!("记录值不能为空");
+        }
+
+        let rr = match self.rr.trim() {
+            "" => "@",
+            rr => rr,
+        };
+        Ok(
+            ZoneRecord::new(rr, record_type, value, self.ttl.unwrap_or(default_ttl))
+                .with_line(Line::from(line.to_string())),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_yaml_and_toml() {
+        let yaml = r#"
+domains:
+  Example.com.:
+    account: bind
+    ttl: 300
+    records:
+      - { rr: "@", type: a, value: 192.0.2.1 }
+      - { rr: www, type: CNAME, value: example.com., ttl: 60, line: default }
+"#;
+        let toml = r#"
+[domains."example.com"]
+account = "bind"
+ttl = 300
+
+[[domains."example.com".records]]
+rr = "@"
+type = "a"
+value = "192.0.2.1"
+
+[[domains."example.com".records]]
+rr = "www"
+type = "CNAME"
+value = "example.com."
+ttl = 60
+"#;
+        for state in [
+            DesiredState::parse(yaml, Path::new("dns.yaml")).unwrap(),
+            DesiredState::parse(toml, Path::new("dns.TOML")).unwrap(),
+        ] {
+            let domains: Vec<(String, &DesiredDomain)> = state.domains().collect();
+            assert_eq!(domains.len(), 1);
+            let (name, domain) = &domains[0];
+            assert_eq!(name, "example.com");
+            assert_eq!(domain.account.as_deref(), Some("bind"));
+            assert_eq!(
+                domain.zone_records(name).unwrap(),
+                vec![
+                    ZoneRecord::new("@", Type::A, "192.0.2.1", 300),
+                    ZoneRecord::new("www", Type::Cname, "example.com.", 60),
+                ]
+            );
+        }
+
+        assert!(DesiredState::from_yaml("domains:\n  example.com:\n    zone: x\n").is_err());
+    }
+
+    #[test]
+    fn test_invalid_records() {
+        let invalid = |record: &str| {
+            let yaml = format!(
+                "domains:\n  example.com:\n    records:\n      - {}\n",
+                record
+            );
+            let state = DesiredState::from_yaml(&yaml).unwrap();
+            let domain = &state.domains["example.com"];
+            format!("{:#}", domain.zone_records("example.com").unwrap_err())
+        };
+
+        assert!(invalid("{ rr: www, type: CAA, value: x }").contains("不支持的记录类型"));
+        assert!(invalid("{ rr: '@', type: SOA, value: x }").contains("SOA"));
+        assert!(invalid("{ rr: www, type: A, value: 192.0.2.1, line: ' ' }").contains("线路"));
+        assert!(invalid("{ rr: www, type: A, value: '' }").contains("记录值不能为空"));
+
+        let yaml = "domains:\n  example.com:\n    records:\n      - { rr: www, type: A, value: 192.0.2.1 }\n      - { rr: WWW, type: A, value: 192.0.2.1 }\n";
+        let state = DesiredState::from_yaml(yaml).unwrap();
+        let err = state.domains["example.com"]
+            .zone_records("example.com")
+            .unwrap_err();
+        assert!(err.to_string().contains("重复"));
+    }
+
+    #[test]
+    fn test_records_on_lines() {
+        let yaml = "domains:\n  example.com:\n    records:\n      - { rr: www, type: A, value: 192.0.2.1 }\n      - { rr: www, type: A, value: 192.0.2.1, line: telecom }\n      - { rr: www, type: A, value: 192.0.2.1, line: default }\n";
+        let state = DesiredState::from_yaml(yaml).unwrap();
+        let err = state.domains["example.com"]
+            .zone_records("example.com")
+            .unwrap_err();
+        assert!(err.to_string().contains("第 3 条记录重复"));
+
+        let yaml = "domains:\n  example.com:\n    records:\n      - { rr: www, type: A, value: 192.0.2.1 }\n      - { rr: www, type: A, value: 192.0.2.1, line: telecom }\n";
+        let state = DesiredState::from_yaml(yaml).unwrap();
+        let records = state.domains["example.com"]
+            .zone_records("example.com")
+            .unwrap();
+        let lines: Vec<Line> = records.into_iter().map(|record| record.line).collect();
+        assert_eq!(
+            lines,
+            vec![Line::Default, Line::Custom("telecom".to_string())]
+        );
+    }
+}
@@ -0,0 +1,468 @@
+//! 记录差异对比
+//!
+//! 以「主机记录 + 类型 + 线路」分组对比服务商现有记录和目标记录：
+//! 1. 值相同的记录：TTL 也相同时跳过，否则修改 TTL
+//! 2. 剩余的目标记录依次替换剩余的现有记录
+//! 3. 仍有剩余的目标记录作为新增
+//! 4. 仍有剩余的现有记录按 [`DeletePolicy`] 决定是否删除
+//!
+//! 这里只做纯计算，不访问服务商接口，区域文件导入和声明式配置共用。
+
+use super::{ParsedZone, ZoneRecord};
+use crate::model::dns_record_response::{Line, Record, Type};
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+
+/// 单条变更
+#[derive(Debug, Clone)]
+pub enum Change {
+    /// 新增记录
+    Add(ZoneRecord),
+    /// 修改现有记录的值或 TTL
+    Update {
+        current: Record,
+        desired: ZoneRecord,
+    },
+    /// 删除现有记录
+    Delete(Record),
+    /// 跳过：记录已存在或无法导入
+    Skip {
+        rr: String,
+        record_type: String,
+        value: String,
+        reason: String,
+    },
+}
+
+impl Change {
+    /// 变更类型：add、update、skip
+    pub fn action(&self) -> &'static str {
+        match self {
+            Change::Add(_) => "add",
+            Change::Update { .. } => "update",
+            Change::Delete(_) => "delete",
+            Change::Skip { .. } => "skip",
+        }
+    }
+
+    /// 变更后的主机记录、类型、值和 TTL，删除时为被删除的记录
+    pub fn target(&self) -> (&str, &str, &str, Option<u32>) {
+        match self {
+            Change::Delete(record) => (
+                &record.rr,
+                record.record_type.get_value(),
+                &record.value,
+                Some(record.ttl.max(0) as u32),
+            ),
+            Change::Add(record)
+            | Change::Update {
+                desired: record, ..
+            } => (
+                &record.rr,
+                record.record_type.get_value(),
+                &record.value,
+                Some(record.ttl),
+            ),
+            Change::Skip {
+                rr,
+                record_type,
+                value,
+                ..
+            } => (rr, record_type, value, None),
+        }
+    }
+
+    /// 变更说明，用于预览和日志
+    pub fn describe(&self) -> String {
+        match self {
+            Change::Add(record) => format!(
+                "新增 {} {} {}{}",
+                record.rr,
+                record.record_type.get_value(),
+                record.value,
+                line_suffix(&record.line)
+            ),
+            Change::Update { current, desired } => format!(
+                "修改 {} {} {} (TTL {}) -> {} (TTL {}){}",
+                desired.rr,
+                desired.record_type.get_value(),
+                current.value,
+                current.ttl,
+                desired.value,
+                desired.ttl,
+                line_suffix(&desired.line)
+            ),
+            Change::Delete(record) => format!(
+                "删除 {} {} {}{}",
+                record.rr,
+                record.record_type.get_value(),
+                record.value,
+                line_suffix(&record.line)
+            ),
+            Change::Skip {
+                rr,
+                record_type,
+                value,
+                reason,
+            } => {
+                let target: Vec<&str> = [rr, record_type, value]
+                    .into_iter()
+                    .map(String::as_str)
+                    .filter(|part| !part.is_empty())
+                    .collect();
+                format!("跳过 {}：{}", target.join(" "), reason)
+            }
+        }
+    }
+}
+
+/// 默认线路以外的线路写在变更说明的末尾
+fn line_suffix(line: &Line) -> String {
+    match line {
+        Line::Default => String::new(),
+        Line::Custom(line) => format!("（线路 {}）", line),
+    }
+}
+
+/// 多余的现有记录的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeletePolicy {
+    /// 不删除任何记录（区域文件导入）
+    Never,
+    /// 只删除目标中出现过的「主机记录 + 类型 + 线路」下多余的记录
+    Managed,
+    /// 同时删除目标中没有的记录，SOA 和根域名的 NS 记录除外
+    Prune,
+}
+
+/// 生成区域文件导入的变更预览，区域文件中无法导入的记录也作为跳过项列出
+pub fn plan_import(current: &[Record], zone: &ParsedZone) -> Vec<Change> {
+    let mut changes = diff_records(current, &zone.records, DeletePolicy::Never);
+    changes.extend(
+        zone.skipped
+            .iter()
+            .map(|(name, record_type, reason)| Change::Skip {
+                rr: name.clone(),
+                record_type: record_type.clone(),
+                value: String::new(),
+                reason: reason.clone(),
+            }),
+    );
+    changes
+}
+
+/// 同一主机记录、类型和线路下的现有记录与目标记录
+type Group<'a> = (Vec<&'a Record>, Vec<&'a ZoneRecord>);
+
+/// 主机记录（小写）、类型和线路
+type GroupKey = (String, String, String);
+
+/// 生成声明式配置的执行计划
+///
+/// 目标中出现的「主机记录 + 类型 + 线路」完全由配置管理，多余的值会被删除；
+/// `prune` 为 true 时还会删除配置中没有的记录。
+/// 计划按删除、修改、新增、跳过的顺序排列，避免 CNAME 等记录在替换时冲突。
+pub fn plan_state(current: &[Record], desired: &[ZoneRecord], prune: bool) -> Vec<Change> {
+    let policy = if prune {
+        DeletePolicy::Prune
+    } else {
+        DeletePolicy::Managed
+    };
+    let mut changes = diff_records(current, desired, policy);
+    changes.sort_by_key(|change| match change {
+        Change::Delete(_) => 0,
+        Change::Update { .. } => 1,
+        Change::Add(_) => 2,
+        Change::Skip { .. } => 3,
+    });
+    changes
+}
+
+/// 对比现有记录和目标记录
+pub fn diff_records(
+    current: &[Record],
+    desired: &[ZoneRecord],
+    policy: DeletePolicy,
+) -> Vec<Change> {
+    let mut groups: BTreeMap<GroupKey, Group> = BTreeMap::new();
+    for record in current {
+        groups
+            .entry(group_key(&record.rr, &record.record_type, &record.line))
+            .or_default()
+            .0
+            .push(record);
+    }
+    for record in desired {
+        let group = &mut groups
+            .entry(group_key(&record.rr, &record.record_type, &record.line))
+            .or_default()
+            .1;
+        // 区域文件中重复的记录只保留一条
+        if !group
+            .iter()
+            .any(|r| same_value(&r.record_type, &r.value, &record.value))
+        {
+            group.push(record);
+        }
+    }
+
+    let mut changes = Vec::new();
+    for ((rr, record_type, _), (mut existing, wanted)) in groups {
+        let managed = !wanted.is_empty();
+        let mut unmatched = Vec::new();
+        for record in wanted {
+            let position = existing
+                .iter()
+                .position(|r| same_value(&record.record_type, &r.value, &record.value));
+            match position {
+                Some(index) => {
+                    let current = existing.remove(index);
+                    if current.ttl.max(0) as u32 == record.ttl {
+                        changes.push(Change::Skip {
+                            rr: record.rr.clone(),
+                            record_type: record.record_type.get_value().to_string(),
+                            value: record.value.clone(),
+                            reason: "记录已存在".to_string(),
+                        });
+                    } else {
+                        changes.push(Change::Update {
+                            current: current.clone(),
+                            desired: record.clone(),
+                        });
+                    }
+                }
+                None => unmatched.push(record),
+            }
+        }
+
+        let mut existing = existing.into_iter();
+        for record in unmatched {
+            match existing.next() {
+                Some(current) => changes.push(Change::Update {
+                    current: current.clone(),
+                    desired: record.clone(),
+                }),
+                None => changes.push(Change::Add(record.clone())),
+            }
+        }
+
+        let delete = match policy {
+            DeletePolicy::Never => false,
+            DeletePolicy::Managed => managed,
+            DeletePolicy::Prune => managed || !is_provider_managed(&rr, &record_type),
+        };
+        if delete {
+            changes.extend(existing.map(|current| Change::Delete(current.clone())));
+        }
+    }
+    changes
+}
+
+/// SOA 和根域名的 NS 记录由服务商维护，清理时保留
+fn is_provider_managed(rr: &str, record_type: &str) -> bool {
+    record_type == "SOA" || (rr == "@" && record_type == "NS")
+}
+
+fn group_key(rr: &str, record_type: &Type, line: &Line) -> GroupKey {
+    let rr = if rr.is_empty() { "@" } else { rr };
+    (
+        rr.to_ascii_lowercase(),
+        record_type.get_value().to_string(),
+        line.clone().into(),
+    )
+}
+
+/// 比较记录值，忽略域名大小写和结尾的 `.` 以及 IP 地址的书写差异
+pub(crate) fn same_value(record_type: &Type, a: &str, b: &str) -> bool {
+    normalize(record_type, a) == normalize(record_type, b)
+}
+
+fn normalize(record_type: &Type, value: &str) -> String {
+    match record_type {
+        Type::TXT => value.to_string(),
+        Type::A | Type::AAAA => value
+            .trim()
+            .parse::<IpAddr>()
+            .map(|ip| ip.to_string())
+            .unwrap_or_else(|_| value.trim().to_string()),
+        _ => value
+            .split_whitespace()
+            .map(|field| field.trim_end_matches('.').to_ascii_lowercase())
+            .collect::<Vec<_>>()
+            .join(" "),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::dns_record_response::Status;
+
+    fn current(id: &str, rr: &str, record_type: Type, value: &str, ttl: i32) -> Record {
+        Record::new(
+            Status::Enable,
+            rr.to_string(),
+            record_type,
+            value.to_string(),
+            id.to_string(),
+            ttl,
+        )
+    }
+
+    #[test]
+    fn test_diff_records() {
+        let existing = vec![
+            current("1", "www", Type::A, "192.0.2.1", 600),
+            current("2", "blog", Type::Cname, "Example.GitHub.io", 600),
+            current("3", "api", Type::A, "192.0.2.10", 600),
+            current("4", "@", Type::MX, "10 mx.example.net", 600),
+            current("5", "old", Type::A, "192.0.2.99", 600),
+        ];
+        let desired = vec![
+            // 完全相同
+            ZoneRecord::new("www", Type::A, "192.0.2.1", 600),
+            ZoneRecord::new("www", Type::A, "192.0.2.1", 600),
+            // 仅大小写和结尾的点不同
+            ZoneRecord::new("blog", Type::Cname, "example.github.io.", 600),
+            // 值变化
+            ZoneRecord::new("api", Type::A, "192.0.2.11", 600),
+            // TTL 变化
+            ZoneRecord::new("@", Type::MX, "10 mx.example.net.", 300),
+            // 新增
+            ZoneRecord::new("www", Type::AAAA, "2001:db8::1", 600),
+        ];
+
+        let changes = diff_records(&existing, &desired, DeletePolicy::Never);
+        let actions: Vec<(&str, &str)> = changes
+            .iter()
+            .map(|change| (change.action(), change.target().0))
+            .collect();
+        assert_eq!(changes.len(), 5);
+        assert!(actions.contains(&("skip", "www")));
+        assert!(actions.contains(&("skip", "blog")));
+        assert!(actions.contains(&("add", "www")));
+
+        let updates: Vec<(&str, &str)> = changes
+            .iter()
+            .filter_map(|change| match change {
+                Change::Update { current, desired } => {
+                    Some((current.record_id.as_str(), desired.value.as_str()))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(updates.len(), 2);
+        assert!(updates.contains(&("3", "192.0.2.11")));
+        assert!(updates.contains(&("4", "10 mx.example.net.")));
+
+        // 导入不会删除区域文件中没有的记录
+        assert!(changes.iter().all(|change| change.target().0 != "old"));
+    }
+
+    #[test]
+    fn test_plan_state() {
+        let existing = vec![
+            current(
+                "1",
+                "@",
+                Type::SOA,
+                "ns1.example.com. hostmaster.example.com.",
+                600,
+            ),
+            current("2", "@", Type::NS, "ns1.example.com", 600),
+            current("3", "www", Type::A, "192.0.2.1", 600),
+            current("4", "www", Type::A, "192.0.2.2", 600),
+            current("5", "legacy", Type::Cname, "old.example.net", 600),
+        ];
+        let desired = vec![
+            ZoneRecord::new("www", Type::A, "192.0.2.1", 600),
+            ZoneRecord::new("api", Type::A, "192.0.2.30", 600),
+        ];
+        let summary = |changes: &[Change]| -> Vec<(&'static str, String)> {
+            changes
+                .iter()
+                .map(|change| (change.action(), change.target().2.to_string()))
+                .collect()
+        };
+
+        // 受管理的 www A 下多余的值被删除，其余记录保留
+        let changes = plan_state(&existing, &desired, false);
+        assert_eq!(
+            summary(&changes),
+            vec![
+                ("delete", "192.0.2.2".to_string()),
+                ("add", "192.0.2.30".to_string()),
+                ("skip", "192.0.2.1".to_string()),
+            ]
+        );
+
+        // 清理时删除未管理的记录，但保留 SOA 和根域名 NS
+        let changes = plan_state(&existing, &desired, true);
+        let deleted: Vec<String> = changes
+            .iter()
+            .filter_map(|change| match change {
+                Change::Delete(record) => Some(record.record_id.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(deleted.len(), 2);
+        assert!(deleted.contains(&"4".to_string()));
+        assert!(deleted.contains(&"5".to_string()));
+        assert_eq!(changes[0].action(), "delete");
+        assert_eq!(changes[1].action(), "delete");
+    }
+
+    #[test]
+    fn test_diff_records_by_line() {
+        let mut telecom = current("2", "www", Type::A, "192.0.2.2", 600);
+        telecom.line = Line::Custom("telecom".to_string());
+        let existing = vec![current("1", "www", Type::A, "192.0.2.1", 600), telecom];
+        let line = |name: &str| Line::Custom(name.to_string());
+
+        // 只有线路不同的记录互不影响
+        let desired = vec![
+            ZoneRecord::new("www", Type::A, "192.0.2.1", 600),
+            ZoneRecord::new("www", Type::A, "192.0.2.2", 600).with_line(line("telecom")),
+            ZoneRecord::new("www", Type::A, "192.0.2.1", 600).with_line(line("unicom")),
+        ];
+        let changes = plan_state(&existing, &desired, false);
+        let actions: Vec<&str> = changes.iter().map(Change::action).collect();
+        assert_eq!(actions, vec!["add", "skip", "skip"]);
+        assert_eq!(changes[0].describe(), "新增 www A 192.0.2.1（线路 unicom）");
+
+        // 值相同但线路不同时，默认线路的记录不会被复用
+        let desired = vec![ZoneRecord::new("www", Type::A, "192.0.2.2", 600)];
+        let changes = plan_state(&existing, &desired, false);
+        match &changes[..] {
+            [Change::Update { current, desired }] => {
+                assert_eq!(current.record_id, "1");
+                assert_eq!(
+                    desired.to_record(current.record_id.clone()).line,
+                    Line::Default
+                );
+            }
+            changes => panic!("unexpected changes: {:?}", changes),
+        }
+
+        // 提交给服务商的记录带上线路
+        let record = ZoneRecord::new("www", Type::A, "192.0.2.2", 600).with_line(line("telecom"));
+        assert_eq!(record.to_record(String::new()).line, line("telecom"));
+        assert_eq!(ZoneRecord::from(&existing[1]), record);
+    }
+
+    #[test]
+    fn test_plan_import_lists_skipped_records() {
+        let zone = ParsedZone {
+            records: vec![ZoneRecord::new("www", Type::A, "192.0.2.1", 600)],
+            skipped: vec![(
+                "example.com.".to_string(),
+                "SOA".to_string(),
+                "由服务商管理".to_string(),
+            )],
+        };
+        let changes = plan_import(&[], &zone);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].action(), "add");
+        assert_eq!(changes[1].describe(), "跳过 example.com. SOA：由服务商管理");
+    }
+}
@@ -0,0 +1,233 @@
+//! BIND 区域文件的读写
+
+use super::{ZoneRecord, DEFAULT_ZONE_TTL};
+use crate::api::provider::rfc2136::format_rdata;
+use crate::api::provider::rrset::{encode_value, to_rr};
+use crate::model::dns_record_response::Type;
+use anyhow::{anyhow, Result};
+use hickory_proto::rr::{Name, RecordType};
+use hickory_proto::serialize::txt::Parser;
+use std::fmt::Write;
+
+/// 从区域文件解析得到的记录
+#[derive(Debug, Clone, Default)]
+pub struct ParsedZone {
+    pub records: Vec<ZoneRecord>,
+    /// 无法导入的记录：(名称, 类型, 原因)
+    pub skipped: Vec<(String, String, String)>,
+}
+
+/// 将记录写成区域文件
+///
+/// 名称类记录值统一写成以 `.` 结尾的完整域名，服务商不支持导出的类型以注释形式保留。
+pub fn write_zone(origin: &str, records: &[ZoneRecord]) -> String {
+    let origin = origin.trim_end_matches('.');
+    let mut records: Vec<&ZoneRecord> = records.iter().collect();
+    records.sort_by(|a, b| {
+        (a.rr != "@", &a.rr, a.record_type.get_value(), &a.value).cmp(&(
+            b.rr != "@",
+            &b.rr,
+            b.record_type.get_value(),
+            &b.value,
+        ))
+    });
+
+    let mut zone = String::new();
+    let _ = writeln!(zone, "; {} 的区域文件，由 domain_manager 导出", origin);
+    let _ = writeln!(zone, "$ORIGIN {}.", origin);
+    let _ = writeln!(zone, "$TTL {}", DEFAULT_ZONE_TTL);
+
+    for record in records {
+        let name = if record.rr.is_empty() {
+            "@"
+        } else {
+            &record.rr
+        };
+        match format_value(record) {
+            Some(value) => {
+                let _ = writeln!(
+                    zone,
+                    "{}\t{}\tIN\t{}\t{}",
+                    name,
+                    record.ttl,
+                    record.record_type.get_value(),
+                    value
+                );
+            }
+            None => {
+                let _ = writeln!(
+                    zone,
+                    "; 不支持导出的记录: {}\t{}\t{}\t{}",
+                    name,
+                    record.ttl,
+                    record.record_type.get_value(),
+                    record.value
+                );
+            }
+        }
+    }
+    zone
+}
+
+/// 将记录值格式化为区域文件中的 RDATA，不支持的类型返回 None
+fn format_value(record: &ZoneRecord) -> Option<String> {
+    let value = record.value.trim();
+    let formatted = match record.record_type {
+        Type::A | Type::AAAA => value.to_string(),
+        Type::Cname | Type::NS | Type::PTR => absolute(value),
+        Type::MX => match value.split_whitespace().collect::<Vec<_>>().as_slice() {
+            [preference, exchange] => format!("{} {}", preference, absolute(exchange)),
+            // 部分服务商单独保存优先级，记录值只有目标主机
+            [exchange] => format!("10 {}", absolute(exchange)),
+            _ => return None,
+        },
+        Type::SRV => match value.split_whitespace().collect::<Vec<_>>().as_slice() {
+            [priority, weight, port, target] => {
+                format!("{} {} {} {}", priority, weight, port, absolute(target))
+            }
+            _ => return None,
+        },
+        Type::TXT => encode_value(&Type::TXT, &record.value),
+        Type::SOA | Type::ForwardUrl => return None,
+    };
+    Some(formatted)
+}
+
+fn absolute(name: &str) -> String {
+    if name.ends_with('.') {
+        name.to_string()
+    } else {
+        format!("{}.", name)
+    }
+}
+
+/// 解析区域文件
+///
+/// 相对名称以 `origin` 补全；SOA、根域名的 NS 记录由服务商管理，不会导入。
+pub fn parse_zone(content: &str, origin: &str) -> Result<ParsedZone> {
+    let origin_name = Name::from_ascii(format!("{}.", origin.trim_end_matches('.')))
+        .map_err(|e| anyhow!("无效的域名「{}」: {}", origin, e))?;
+
+    // 未声明 $TTL 且记录没有 TTL 时使用默认值，文件中的 $TTL 会覆盖它
+    let content = format!("$TTL {}\n{}", DEFAULT_ZONE_TTL, content);
+    let (_, record_sets) = Parser::new(content, None, Some(origin_name.clone()))
+        .parse()
+        .map_err(|e| anyhow!("区域文件格式错误: {}", e))?;
+
+    let mut parsed = ParsedZone::default();
+    for record_set in record_sets.values() {
+        for record in record_set.records_without_rrsigs() {
+            let name = record.name().to_ascii();
+            let record_type = record.record_type().to_string();
+
+            if !origin_name.zone_of(record.name()) {
+                parsed
+                    .skipped
+                    .push((name, record_type, "不属于当前域名".to_string()));
+                continue;
+            }
+            if record.record_type() == RecordType::SOA
+                || (record.record_type() == RecordType::NS && record.name() == &origin_name)
+            {
+                parsed
+                    .skipped
+                    .push((name, record_type, "由服务商管理".to_string()));
+                continue;
+            }
+
+            match record.data().and_then(format_rdata) {
+                Some((record_type, value)) => parsed.records.push(ZoneRecord::new(
+                    to_rr(&name, origin),
+                    record_type,
+                    value,
+                    record.ttl(),
+                )),
+                None => parsed
+                    .skipped
+                    .push((name, record_type, "不支持的记录类型".to_string())),
+            }
+        }
+    }
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ZONE: &str = r#"
+$ORIGIN example.com.
+$TTL 3600
+@       IN  SOA ns1.example.com. hostmaster.example.com. (
+                2024010101 3600 600 604800 300 )
+@       IN  NS  ns1.example.com.
+www         IN  CNAME web
+mail        IN  MX  10 mx1.example.net.
+_sip._tcp   IN  SRV 10 60 5060 sip
+@           IN  TXT "v=spf1 include:_spf.example.net ~all"
+long        IN  TXT "part one " "part two"
+host.other.org. IN A 198.51.100.1
+@           IN  CAA 0 issue "letsencrypt.org"
+@       300 IN  A   192.0.2.1
+"#;
+
+    #[test]
+    fn test_parse_zone() {
+        let parsed = parse_zone(ZONE, "example.com").unwrap();
+        let find = |rr: &str, record_type: Type| {
+            parsed
+                .records
+                .iter()
+                .find(|r| r.rr == rr && r.record_type == record_type)
+                .unwrap()
+        };
+
+        assert_eq!(parsed.records.len(), 6);
+        assert_eq!(find("@", Type::A).ttl, 300);
+        assert_eq!(find("www", Type::Cname).value, "web.example.com.");
+        assert_eq!(find("www", Type::Cname).ttl, 3600);
+        assert_eq!(find("mail", Type::MX).value, "10 mx1.example.net.");
+        assert_eq!(
+            find("_sip._tcp", Type::SRV).value,
+            "10 60 5060 sip.example.com."
+        );
+        assert_eq!(find("long", Type::TXT).value, "part one part two");
+
+        // SOA、根域名NS、域外记录和不支持的类型被跳过
+        let reasons: Vec<&str> = parsed.skipped.iter().map(|s| s.2.as_str()).collect();
+        assert_eq!(parsed.skipped.len(), 4);
+        assert!(reasons.contains(&"不属于当前域名"));
+        assert!(reasons.contains(&"不支持的记录类型"));
+
+        assert!(parse_zone("www IN A not-an-ip", "example.com").is_err());
+    }
+
+    #[test]
+    fn test_write_and_parse_round_trip() {
+        let records = vec![
+            ZoneRecord::new("www", Type::A, "192.0.2.1", 600),
+            ZoneRecord::new("@", Type::MX, "mx.example.net", 600),
+            ZoneRecord::new("blog", Type::Cname, "example.github.io", 300),
+            ZoneRecord::new("@", Type::TXT, "say \"hi\"; ok", 600),
+            ZoneRecord::new("go", Type::ForwardUrl, "https://example.net", 600),
+        ];
+        let zone = write_zone("example.com.", &records);
+        assert!(zone.starts_with("; example.com"));
+        assert!(zone.contains("$ORIGIN example.com.\n"));
+        assert!(zone.contains("blog\t300\tIN\tCNAME\texample.github.io.\n"));
+        assert!(zone.contains("; 不支持导出的记录: go"));
+
+        let parsed = parse_zone(&zone, "example.com").unwrap();
+        assert_eq!(parsed.records.len(), 4);
+        assert!(parsed.skipped.is_empty());
+        assert!(parsed.records.contains(&ZoneRecord::new(
+            "@",
+            Type::MX,
+            "10 mx.example.net.",
+            600
+        )));
+        assert!(parsed
+            .records
+            .contains(&ZoneRecord::new("@", Type::TXT, "say \"hi\"; ok", 600)));
+    }
+}
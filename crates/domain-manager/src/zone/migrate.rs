@@ -0,0 +1,630 @@
+//! 跨服务商迁移域名解析
+//!
+//! 1. 通过源账户的 `DnsClientTrait` 读取记录，按目标服务商的能力转换：
+//!    线路、权重等无法表达的字段给出警告，目标不支持的记录类型跳过
+//! 2. 在目标账户上创建记录（已存在的记录不会重复创建）
+//! 3. 重新列出目标账户的记录，逐条核对是否生效
+//! 4. 每条记录的结果写入 `storage`，便于事后追溯
+//!
+//! 迁移不会在目标服务商创建域名，也不会修改注册商的 NS，需要用户在迁移前后自行处理。
+
+use super::diff::{diff_records, same_value, DeletePolicy};
+use super::{apply_changes, Change, ZoneRecord};
+use crate::api::dns_client::DnsClientTrait;
+use crate::api::provider::create_dns_client_for_account;
+use crate::api::provider::rrset::to_rr;
+use crate::gui::model::domain::{DnsProvider, DomainName};
+use crate::model::dns_record_response::{Line, Record, Status, Type};
+use crate::models::account::Account;
+use crate::models::domain::{DomainStatus, NewDomain};
+use crate::models::record::NewRecord;
+use crate::storage::{domains, migration_records, records};
+use anyhow::{anyhow, Context, Result};
+use sea_orm::DatabaseConnection;
+use serde::Serialize;
+use tracing::{info, warn};
+
+/// MX 记录缺少优先级时使用的默认值
+const DEFAULT_MX_PRIORITY: u16 = 10;
+
+/// 单条记录的迁移状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MigrationStatus {
+    /// 尚未执行（预览）
+    Pending,
+    /// 已在目标账户创建
+    Created,
+    /// 已修改目标账户上的同名记录
+    Updated,
+    /// 目标账户已有相同的记录
+    Existing,
+    /// 无法迁移
+    Skipped,
+    /// 调用目标服务商接口失败
+    Failed,
+    /// 接口调用成功，但重新查询时没有找到记录
+    Unverified,
+}
+
+impl MigrationStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MigrationStatus::Pending => "pending",
+            MigrationStatus::Created => "created",
+            MigrationStatus::Updated => "updated",
+            MigrationStatus::Existing => "existing",
+            MigrationStatus::Skipped => "skipped",
+            MigrationStatus::Failed => "failed",
+            MigrationStatus::Unverified => "unverified",
+        }
+    }
+
+    /// 是否需要用户处理
+    pub fn is_problem(&self) -> bool {
+        matches!(self, MigrationStatus::Failed | MigrationStatus::Unverified)
+    }
+}
+
+/// 单条源记录的迁移结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MigrationItem {
+    pub rr: String,
+    #[serde(rename = "type")]
+    pub record_type: String,
+    /// 转换后的记录值，跳过时为源记录的值
+    pub value: String,
+    pub ttl: Option<u32>,
+    pub status: MigrationStatus,
+    pub warnings: Vec<String>,
+    /// 跳过原因或失败原因
+    pub error: Option<String>,
+}
+
+/// 一次迁移的报告
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationReport {
+    pub id: String,
+    pub domain: String,
+    pub source_account_id: i64,
+    pub target_account_id: i64,
+    pub items: Vec<MigrationItem>,
+    /// 目标服务商分配的 NS，迁移完成后需要到注册商处修改
+    pub nameservers: Vec<String>,
+}
+
+impl MigrationReport {
+    /// 失败或未核对成功的记录数
+    pub fn problem_count(&self) -> usize {
+        self.items
+            .iter()
+            .filter(|item| item.status.is_problem())
+            .count()
+    }
+}
+
+/// 源记录转换为目标服务商记录的结果
+#[derive(Debug, Clone)]
+pub struct Translation {
+    /// 转换后的记录，无法迁移时为空
+    pub record: Option<ZoneRecord>,
+    pub item: MigrationItem,
+}
+
+/// 目标服务商支持写入的记录类型
+pub fn supported_types(provider: DnsProvider) -> &'static [Type] {
+    match provider {
+        DnsProvider::Aliyun
+        | DnsProvider::Tomato
+        | DnsProvider::Dnspod
+        | DnsProvider::TencentCloud => &[
+            Type::A,
+            Type::AAAA,
+            Type::Cname,
+            Type::MX,
+            Type::TXT,
+            Type::NS,
+            Type::PTR,
+            Type::SRV,
+            Type::ForwardUrl,
+        ],
+        DnsProvider::CloudFlare => &[Type::A, Type::AAAA, Type::Cname, Type::MX, Type::TXT],
+        DnsProvider::Aws | DnsProvider::Google | DnsProvider::Rfc2136 => &[
+            Type::A,
+            Type::AAAA,
+            Type::Cname,
+            Type::MX,
+            Type::TXT,
+            Type::NS,
+            Type::PTR,
+            Type::SRV,
+        ],
+    }
+}
+
+/// 服务商是否支持默认线路以外的解析线路
+pub fn supports_lines(provider: DnsProvider) -> bool {
+    matches!(
+        provider,
+        DnsProvider::Aliyun | DnsProvider::Dnspod | DnsProvider::TencentCloud
+    )
+}
+
+/// 目标服务商（免费版）允许的最小 TTL
+pub fn min_ttl(provider: DnsProvider) -> u32 {
+    match provider {
+        DnsProvider::Aliyun | DnsProvider::Dnspod | DnsProvider::TencentCloud => 600,
+        DnsProvider::CloudFlare => 60,
+        _ => 1,
+    }
+}
+
+/// 按目标服务商的能力转换源记录
+pub fn translate(
+    domain: &str,
+    source: DnsProvider,
+    target: DnsProvider,
+    records: &[Record],
+) -> Vec<Translation> {
+    let mut translations: Vec<Translation> = Vec::with_capacity(records.len());
+    for record in records {
+        let mut translation = translate_record(domain, source, target, record);
+
+        // 不同线路的相同记录在目标上只能保留一条
+        if let Some(translated) = &translation.record {
+            let duplicated = translations.iter().any(|other| {
+                other.record.as_ref().is_some_and(|other| {
+                    other.rr.eq_ignore_ascii_case(&translated.rr)
+                        && other.record_type == translated.record_type
+                        && same_value(&other.record_type, &other.value, &translated.value)
+                })
+            });
+            if duplicated {
+                translation.record = None;
+                translation.item.status = MigrationStatus::Skipped;
+                translation.item.error = Some("与其他线路的记录重复".to_string());
+            }
+        }
+        translations.push(translation);
+    }
+    translations
+}
+
+fn translate_record(
+    domain: &str,
+    source: DnsProvider,
+    target: DnsProvider,
+    record: &Record,
+) -> Translation {
+    // Cloudflare 等服务商返回完整域名
+    let rr = match record.rr.trim() {
+        "" | "@" => "@".to_string(),
+        rr => to_rr(rr, domain),
+    };
+    let mut item = MigrationItem {
+        rr: rr.clone(),
+        record_type: record.record_type.get_value().to_string(),
+        value: record.value.clone(),
+        ttl: None,
+        status: MigrationStatus::Pending,
+        warnings: Vec::new(),
+        error: None,
+    };
+    let skip = |mut item: MigrationItem, reason: String| {
+        item.status = MigrationStatus::Skipped;
+        item.error = Some(reason);
+        Translation { record: None, item }
+    };
+
+    if record.record_type == Type::SOA || (record.record_type == Type::NS && rr == "@") {
+        return skip(item, "由服务商管理".to_string());
+    }
+    if record.status == Status::Disable {
+        return skip(item, "源记录已暂停".to_string());
+    }
+    if !supported_types(target).contains(&record.record_type) {
+        return skip(
+            item,
+            format!(
+                "{} 不支持 {} 记录",
+                target.name(),
+                record.record_type.get_value()
+            ),
+        );
+    }
+
+    if let Line::Custom(line) = &record.line {
+        item.warnings.push(format!(
+            "{} 的线路「{}」无法迁移，改为默认线路",
+            source.name(),
+            line
+        ));
+    }
+    if let Some(weight) = record.weight.filter(|weight| *weight != 1) {
+        item.warnings
+            .push(format!("权重 {} 无法迁移，目标记录按普通记录处理", weight));
+    }
+
+    let mut ttl = record.ttl.max(0) as u32;
+    let minimum = min_ttl(target);
+    if ttl < minimum {
+        item.warnings.push(format!(
+            "TTL {} 低于 {} 允许的最小值，改为 {}",
+            ttl,
+            target.name(),
+            minimum
+        ));
+        ttl = minimum;
+    }
+
+    let value = match record.record_type {
+        Type::MX => match translate_mx(&record.value, target, &mut item.warnings) {
+            Some(value) => value,
+            None => return skip(item, "无法解析 MX 记录值".to_string()),
+        },
+        _ => record.value.trim().to_string(),
+    };
+
+    item.value = value.clone();
+    item.ttl = Some(ttl);
+    Translation {
+        record: Some(ZoneRecord::new(rr, record.record_type.clone(), value, ttl)),
+        item,
+    }
+}
+
+/// 统一 MX 记录值的格式
+///
+/// 阿里云的记录值只有目标主机，优先级单独保存且写入时使用默认值；
+/// 其他服务商的记录值为「优先级 目标主机」。
+fn translate_mx(value: &str, target: DnsProvider, warnings: &mut Vec<String>) -> Option<String> {
+    let (priority, exchange) = match value.split_whitespace().collect::<Vec<_>>().as_slice() {
+        [priority, exchange] => (priority.parse::<u16>().ok()?, exchange.to_string()),
+        [exchange] => {
+            warnings.push(format!(
+                "源记录未包含 MX 优先级，按 {} 处理",
+                DEFAULT_MX_PRIORITY
+            ));
+            (DEFAULT_MX_PRIORITY, exchange.to_string())
+        }
+        _ => return None,
+    };
+
+    if target == DnsProvider::Aliyun {
+        if priority != DEFAULT_MX_PRIORITY {
+            warnings.push(format!(
+                "阿里云使用默认优先级 {}，原优先级为 {}",
+                DEFAULT_MX_PRIORITY, priority
+            ));
+        }
+        Some(exchange)
+    } else {
+        Some(format!("{} {}", priority, exchange))
+    }
+}
+
+/// 预览迁移：只读取源记录并转换，不修改目标账户
+pub async fn preview_migration(
+    domain: &str,
+    source: DnsProvider,
+    source_client: &(dyn DnsClientTrait + Send + Sync),
+    target: DnsProvider,
+) -> Result<Vec<MigrationItem>> {
+    let records = source_client
+        .list_dns_records(domain.to_string())
+        .await
+        .context("读取源账户的解析记录失败")?;
+    Ok(translate(domain, source, target, &records)
+        .into_iter()
+        .map(|translation| translation.item)
+        .collect())
+}
+
+/// 将源账户的记录复制到目标账户，返回每条记录的结果和目标上的 NS 记录
+pub async fn migrate_records(
+    domain: &str,
+    source: DnsProvider,
+    source_client: &(dyn DnsClientTrait + Send + Sync),
+    target: DnsProvider,
+    target_client: &(dyn DnsClientTrait + Send + Sync),
+) -> Result<(Vec<MigrationItem>, Vec<Record>)> {
+    let source_records = source_client
+        .list_dns_records(domain.to_string())
+        .await
+        .context("读取源账户的解析记录失败")?;
+    let translations = translate(domain, source, target, &source_records);
+    let desired: Vec<ZoneRecord> = translations
+        .iter()
+        .filter_map(|translation| translation.record.clone())
+        .collect();
+
+    let current = target_client
+        .list_dns_records(domain.to_string())
+        .await
+        .map(|records| relative_names(domain, records))
+        .with_context(|| {
+            format!(
+                "读取目标账户的解析记录失败，请确认已在 {} 添加域名",
+                target.name()
+            )
+        })?;
+    let changes = diff_records(&current, &desired, DeletePolicy::Never);
+    let domain_name = DomainName {
+        name: domain.to_string(),
+        provider: target,
+        ..Default::default()
+    };
+    let results = apply_changes(target_client, &domain_name, changes).await;
+
+    let migrated = target_client
+        .list_dns_records(domain.to_string())
+        .await
+        .map(|records| relative_names(domain, records))
+        .context("重新读取目标账户的解析记录失败")?;
+
+    let items = translations
+        .into_iter()
+        .map(|translation| {
+            let Some(record) = translation.record else {
+                return translation.item;
+            };
+            let mut item = translation.item;
+            let result = results.iter().find(|result| match &result.change {
+                Change::Add(desired) | Change::Update { desired, .. } => {
+                    is_same_record(desired, &record)
+                }
+                Change::Skip {
+                    rr,
+                    record_type,
+                    value,
+                    ..
+                } => {
+                    rr.eq_ignore_ascii_case(&record.rr)
+                        && record_type == record.record_type.get_value()
+                        && same_value(&record.record_type, value, &record.value)
+                }
+                Change::Delete(_) => false,
+            });
+
+            item.status = match result {
+                Some(result) if result.error.is_some() => {
+                    item.error = result.error.clone();
+                    MigrationStatus::Failed
+                }
+                _ if !migrated
+                    .iter()
+                    .any(|r| is_same_record(&ZoneRecord::from(r), &record)) =>
+                {
+                    MigrationStatus::Unverified
+                }
+                Some(result) => match result.change {
+                    Change::Add(_) => MigrationStatus::Created,
+                    Change::Update { .. } => MigrationStatus::Updated,
+                    _ => MigrationStatus::Existing,
+                },
+                None => MigrationStatus::Existing,
+            };
+            item
+        })
+        .collect();
+    Ok((items, migrated))
+}
+
+/// 部分服务商返回完整域名，统一转换为主机记录后再对比
+pub(crate) fn relative_names(domain: &str, mut records: Vec<Record>) -> Vec<Record> {
+    for record in &mut records {
+        record.rr = match record.rr.trim() {
+            "" | "@" => "@".to_string(),
+            rr => to_rr(rr, domain),
+        };
+    }
+    records
+}
+
+fn is_same_record(a: &ZoneRecord, b: &ZoneRecord) -> bool {
+    let rr = |rr: &str| {
+        if rr.is_empty() {
+            "@".to_string()
+        } else {
+            rr.to_ascii_lowercase()
+        }
+    };
+    rr(&a.rr) == rr(&b.rr)
+        && a.record_type == b.record_type
+        && same_value(&a.record_type, &a.value, &b.value)
+}
+
+/// 执行完整的迁移：复制记录、保存报告，并把域名加入目标账户的本地缓存
+pub async fn run_migration(
+    conn: &DatabaseConnection,
+    domain: &str,
+    source_account: Account,
+    target_account: Account,
+) -> Result<MigrationReport> {
+    if source_account.id == target_account.id {
+        return Err(anyhow!("源账户和目标账户不能相同"));
+    }
+    let source_account_id = source_account.id;
+    let target_account_id = target_account.id;
+    let (source, source_client) = create_dns_client_for_account(source_account)?;
+    let (target, target_client) = create_dns_client_for_account(target_account)?;
+    info!(
+        "开始迁移域名 {}：{} -> {}",
+        domain,
+        source.name(),
+        target.name()
+    );
+
+    let (items, migrated) = migrate_records(
+        domain,
+        source,
+        source_client.as_ref(),
+        target,
+        target_client.as_ref(),
+    )
+    .await?;
+    let nameservers = migrated
+        .iter()
+        .filter(|r| r.record_type == Type::NS && r.rr == "@")
+        .map(|r| r.value.clone())
+        .collect();
+    let report = MigrationReport {
+        id: uuid::Uuid::new_v4().to_string(),
+        domain: domain.to_string(),
+        source_account_id,
+        target_account_id,
+        items,
+        nameservers,
+    };
+
+    migration_records::save_migration_report(conn, &report)
+        .await
+        .map_err(|e| anyhow!("保存迁移报告失败: {}", e))?;
+    if let Err(e) = cache_target_domain(conn, domain, target_account_id, &migrated).await {
+        warn!("更新目标账户的本地缓存失败: {}", e);
+    }
+
+    info!(
+        "域名 {} 迁移完成，共 {} 条记录，{} 条需要处理",
+        domain,
+        report.items.len(),
+        report.problem_count()
+    );
+    Ok(report)
+}
+
+/// 把域名和迁移后的记录保存到目标账户下
+async fn cache_target_domain(
+    conn: &DatabaseConnection,
+    domain: &str,
+    account_id: i64,
+    migrated: &[Record],
+) -> Result<()> {
+    let domain_id = match domains::find_domain_by_name_and_account(conn, domain, account_id)
+        .await
+        .map_err(|e| anyhow!("{}", e))?
+    {
+        Some(existing) => existing.id,
+        None => {
+            domains::add_domain(
+                conn,
+                NewDomain {
+                    domain_name: domain.to_string(),
+                    registration_date: None,
+                    expiration_date: None,
+                    registrar: None,
+                    status: DomainStatus::Active,
+                    account_id,
+                },
+            )
+            .await?
+            .id
+        }
+    };
+    let new_records = migrated
+        .iter()
+        .map(|r| NewRecord {
+            domain_id,
+            record_name: r.rr.clone(),
+            record_type: r.record_type.to_string(),
+            record_value: r.value.clone(),
+            ttl: r.ttl,
+        })
+        .collect();
+    records::replace_domain_records(conn, domain_id, new_records)
+        .await
+        .map_err(|e| anyhow!(e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(rr: &str, record_type: Type, value: &str, ttl: i32) -> Record {
+        Record::new(
+            Status::Enable,
+            rr.to_string(),
+            record_type,
+            value.to_string(),
+            format!("{}-{}", rr, value),
+            ttl,
+        )
+    }
+
+    #[test]
+    fn test_translate_aliyun_to_cloudflare() {
+        let mut telecom = record("www", Type::A, "192.0.2.1", 600);
+        telecom.line = Line::Custom("telecom".to_string());
+        let mut weighted = record("api", Type::A, "192.0.2.10", 600);
+        weighted.weight = Some(5);
+        let mut paused = record("old", Type::A, "192.0.2.99", 600);
+        paused.status = Status::Disable;
+
+        let records = vec![
+            record("@", Type::NS, "ns1.alidns.com", 86400),
+            record("www", Type::A, "192.0.2.1", 600),
+            telecom,
+            weighted,
+            paused,
+            record("@", Type::MX, "mx.example.net", 600),
+            record("go", Type::ForwardUrl, "https://example.net", 600),
+            record("_sip._tcp", Type::SRV, "10 60 5060 sip.example.com", 600),
+        ];
+        let translations = translate(
+            "example.com",
+            DnsProvider::Aliyun,
+            DnsProvider::CloudFlare,
+            &records,
+        );
+        let items: Vec<&MigrationItem> = translations.iter().map(|t| &t.item).collect();
+        let status: Vec<MigrationStatus> = items.iter().map(|item| item.status).collect();
+        assert_eq!(
+            status,
+            vec![
+                MigrationStatus::Skipped,
+                MigrationStatus::Pending,
+                MigrationStatus::Skipped,
+                MigrationStatus::Pending,
+                MigrationStatus::Skipped,
+                MigrationStatus::Pending,
+                MigrationStatus::Skipped,
+                MigrationStatus::Skipped,
+            ]
+        );
+        assert_eq!(items[2].error.as_deref(), Some("与其他线路的记录重复"));
+        assert!(items[2].warnings[0].contains("telecom"));
+        assert!(items[3].warnings[0].contains("权重 5"));
+        assert_eq!(items[5].value, "10 mx.example.net");
+        assert!(items[5].warnings[0].contains("优先级"));
+        assert!(items[6].error.as_ref().unwrap().contains("FORWARD_URL"));
+        assert!(items[7].error.as_ref().unwrap().contains("SRV"));
+    }
+
+    #[test]
+    fn test_translate_to_aliyun() {
+        let records = vec![
+            record("www.example.com", Type::A, "192.0.2.1", 60),
+            record("example.com", Type::MX, "20 mx.example.net", 300),
+        ];
+        let translations = translate(
+            "example.com",
+            DnsProvider::CloudFlare,
+            DnsProvider::Aliyun,
+            &records,
+        );
+        let www = translations[0].record.as_ref().unwrap();
+        assert_eq!(www.rr, "www");
+        assert_eq!(www.ttl, 600);
+        assert!(translations[0].item.warnings[0].contains("TTL 60"));
+
+        let mx = translations[1].record.as_ref().unwrap();
+        assert_eq!(mx.rr, "@");
+        assert_eq!(mx.value, "mx.example.net");
+        assert!(translations[1]
+            .item
+            .warnings
+            .iter()
+            .any(|w| w.contains("20")));
+    }
+}
@@ -0,0 +1,151 @@
+//! 区域文件（RFC 1035）导入导出
+//!
+//! - [`file`]：将解析记录写成 BIND 区域文件，或从区域文件解析记录
+//! - [`desired`]：YAML/TOML 格式的声明式配置（DNS-as-code）
+//! - [`diff`]：对比服务商现有记录和目标记录，生成新增、修改、删除、跳过的变更
+//! - [`migrate`]：跨服务商迁移解析记录
+//! - [`apply_changes`]：通过 `DnsClientTrait` 执行变更
+//!
+//! 图形界面和命令行共用这里的逻辑，执行前都会先展示预览。
+
+pub mod desired;
+pub mod diff;
+pub mod file;
+pub mod migrate;
+
+pub use desired::DesiredState;
+pub use diff::{plan_import, plan_state, Change};
+pub use file::{parse_zone, write_zone, ParsedZone};
+
+use crate::api::dns_client::DnsClientTrait;
+use crate::api::provider::rrset::parse_record_type;
+use crate::gui::model::domain::DomainName;
+use crate::model::dns_record_response::{Line, Record, Status, Type};
+use crate::models::record::RecordEntity;
+use serde::Serialize;
+use tracing::{info, warn};
+
+/// 区域文件未指定 TTL 时使用的默认值
+pub const DEFAULT_ZONE_TTL: u32 = 600;
+
+/// 与服务商无关的单条解析记录
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ZoneRecord {
+    /// 主机记录，根域名为 `@`
+    pub rr: String,
+    #[serde(rename = "type")]
+    pub record_type: Type,
+    pub value: String,
+    pub ttl: u32,
+    /// 解析线路，只有阿里云、DNSPod 等服务商支持默认线路以外的线路
+    pub line: Line,
+}
+
+impl ZoneRecord {
+    pub fn new(
+        rr: impl Into<String>,
+        record_type: Type,
+        value: impl Into<String>,
+        ttl: u32,
+    ) -> Self {
+        Self {
+            rr: rr.into(),
+            record_type,
+            value: value.into(),
+            ttl,
+            line: Line::Default,
+        }
+    }
+
+    /// 指定解析线路
+    pub fn with_line(mut self, line: Line) -> Self {
+        self.line = line;
+        self
+    }
+
+    /// 本地数据库中的记录，类型无法识别时返回 None
+    pub fn from_entity(entity: &RecordEntity) -> Option<Self> {
+        let record_type = parse_record_type(&entity.record_type.to_ascii_uppercase())?;
+        Some(Self::new(
+            entity.record_name.clone(),
+            record_type,
+            entity.record_value.clone(),
+            entity.ttl.max(0) as u32,
+        ))
+    }
+
+    /// 转换为提交给服务商的记录
+    pub fn to_record(&self, record_id: String) -> Record {
+        let mut record = Record::new(
+            Status::Enable,
+            self.rr.clone(),
+            self.record_type.clone(),
+            self.value.clone(),
+            record_id,
+            self.ttl as i32,
+        );
+        record.line = self.line.clone();
+        record
+    }
+}
+
+impl From<&Record> for ZoneRecord {
+    fn from(record: &Record) -> Self {
+        Self::new(
+            record.rr.clone(),
+            record.record_type.clone(),
+            record.value.clone(),
+            record.ttl.max(0) as u32,
+        )
+        .with_line(record.line.clone())
+    }
+}
+
+/// 单条变更的执行结果
+#[derive(Debug, Clone)]
+pub struct ChangeResult {
+    pub change: Change,
+    pub error: Option<String>,
+}
+
+/// 依次执行变更，单条失败不影响其余变更
+pub async fn apply_changes(
+    client: &(dyn DnsClientTrait + Send + Sync),
+    domain: &DomainName,
+    changes: Vec<Change>,
+) -> Vec<ChangeResult> {
+    let mut results = Vec::with_capacity(changes.len());
+    for change in changes {
+        let result = match &change {
+            Change::Add(record) => {
+                client
+                    .add_dns_record(domain, &record.to_record(String::new()))
+                    .await
+            }
+            Change::Update { current, desired } => {
+                client
+                    .update_dns_record(domain, &desired.to_record(current.record_id.clone()))
+                    .await
+            }
+            Change::Delete(record) => client.delete_dns_record(domain, &record.record_id).await,
+            Change::Skip { .. } => Ok(()),
+        };
+
+        let error = result.err().map(|err| {
+            warn!("{} 执行失败: {:#}", change.describe(), err);
+            format!("{:#}", err)
+        });
+        results.push(ChangeResult { change, error });
+    }
+
+    info!(
+        "域名 {} 的变更执行完成，共 {} 条，失败 {} 条",
+        domain.name,
+        results.len(),
+        results
+            .iter()
+            .filter(|result| result.error.is_some())
+            .count()
+    );
+    results
+}
@@ -1,4 +1,6 @@
-//! STUN attribute types (RFC 5389)
+//! STUN attribute types (RFC 5389, RFC 5780)
+
+use std::net::{IpAddr, SocketAddr};
 
 pub const MAPPED_ADDRESS: u16 = 0x0001;
 pub const XOR_MAPPED_ADDRESS: u16 = 0x0020;
@@ -17,8 +19,36 @@ pub const CHANNEL_NUMBER: u16 = 0x000C;
 pub const BANDWIDTH: u16 = 0x0010;
 pub const XOR_PEER_ADDRESS: u16 = 0x0012;
 pub const DATA: u16 = 0x0013;
+pub const RESPONSE_ORIGIN: u16 = 0x802B;
+pub const OTHER_ADDRESS: u16 = 0x802C;
 pub const MAGIC_COOKIE: u32 = 0x2112A442;
 
+/// CHANGE-REQUEST flags
+pub const CHANGE_IP: u8 = 0x04;
+pub const CHANGE_PORT: u8 = 0x02;
+
+/// Encode an address attribute value; with `transaction_id` it is XORed as
+/// XOR-MAPPED-ADDRESS requires
+pub fn encode_address(addr: SocketAddr, transaction_id: Option<&[u8; 12]>) -> Vec<u8> {
+    let (family, mut ip) = match addr.ip() {
+        IpAddr::V4(ip) => (0x01, ip.octets().to_vec()),
+        IpAddr::V6(ip) => (0x02, ip.octets().to_vec()),
+    };
+    let mut port = addr.port();
+    if let Some(transaction_id) = transaction_id {
+        port ^= (MAGIC_COOKIE >> 16) as u16;
+        let key = MAGIC_COOKIE.to_be_bytes().into_iter().chain(transaction_id.iter().copied());
+        for (byte, key) in ip.iter_mut().zip(key) {
+            *byte ^= key;
+        }
+    }
+
+    let mut buf = vec![0x00, family];
+    buf.extend_from_slice(&port.to_be_bytes());
+    buf.extend_from_slice(&ip);
+    buf
+}
+
 #[derive(Debug, Clone)]
 pub struct MappedAddressAttr {
     pub family: u8,
@@ -73,7 +103,7 @@ impl XorMappedAddressAttr {
         buf[2..4].copy_from_slice(&xor_port.to_be_bytes());
 
         let mut xor_ip = [0u8; 4];
-        let cookie_bytes = (MAGIC_COOKIE as u32).to_be_bytes();
+        let cookie_bytes = MAGIC_COOKIE.to_be_bytes();
         for i in 0..4 {
             xor_ip[i] = self.ip[i] ^ cookie_bytes[i];
         }
@@ -1,21 +1,30 @@
 //! STUN message handler
 
 use std::net::SocketAddr;
-use std::sync::Arc;
-use tokio::sync::RwLock;
-use std::collections::HashMap;
-use uuid::Uuid;
 
-use super::{StunMessage, StunMessageType, StunAttribute, make_binding_response};
+use super::attributes::{encode_address, MAPPED_ADDRESS, OTHER_ADDRESS, RESPONSE_ORIGIN, XOR_MAPPED_ADDRESS};
+use super::{StunMessage, StunMessageType, StunAttribute};
 
-pub async fn handle_binding_request(
+/// Answer a Binding request sent from `from`
+///
+/// `origin` is the address the response is sent from and `other` the
+/// alternate address of RFC 5780, if the server has one.
+pub fn handle_binding_request(
     msg: &StunMessage,
     from: &SocketAddr,
-    _agents: &Arc<RwLock<HashMap<Uuid, crate::AgentInfo>>>,
-) -> Option<Vec<u8>> {
+    origin: SocketAddr,
+    other: Option<SocketAddr>,
+) -> Vec<u8> {
     tracing::debug!("Handling binding request from {}", from);
-    let response = make_binding_response(msg, *from);
-    Some(response)
+    let mut attrs = vec![
+        StunAttribute::new(XOR_MAPPED_ADDRESS, encode_address(*from, Some(&msg.transaction_id))),
+        StunAttribute::new(MAPPED_ADDRESS, encode_address(*from, None)),
+        StunAttribute::new(RESPONSE_ORIGIN, encode_address(origin, None)),
+    ];
+    if let Some(other) = other {
+        attrs.push(StunAttribute::new(OTHER_ADDRESS, encode_address(other, None)));
+    }
+    make_success_response(msg, attrs)
 }
 
 pub async fn handle_binding_indication(_msg: &StunMessage, from: &SocketAddr) {
@@ -1,8 +1,13 @@
 //! STUN protocol implementation
+//!
+//! Shared by the `domain-stun` server and the tests of `domain-agent`'s STUN
+//! client, which run against the real Binding service.
 
 pub mod message;
 pub mod attributes;
 pub mod handler;
+pub mod server;
 
 pub use message::{StunMessage, StunMessageType, StunAttribute, StunError, make_error_response, make_binding_response};
 pub use handler::{handle_binding_request, handle_binding_indication, make_success_response};
+pub use server::BindingServer;
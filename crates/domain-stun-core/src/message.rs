@@ -1,9 +1,11 @@
 //! STUN message parsing and encoding
 
 use bytes::{Buf, BufMut, BytesMut};
-use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::net::SocketAddr;
 use thiserror::Error;
 
+use super::attributes::{encode_address, MAGIC_COOKIE, MAPPED_ADDRESS, XOR_MAPPED_ADDRESS};
+
 #[derive(Debug, Error)]
 pub enum StunError {
     #[error("Invalid STUN header")]
@@ -111,6 +113,9 @@ impl StunMessage {
         if msg_length > data.len() - 20 {
             return Err(StunError::MessageTooShort(data.len()));
         }
+        if buf.get_u32() != MAGIC_COOKIE {
+            return Err(StunError::InvalidHeader);
+        }
 
         let mut transaction_id = [0u8; 12];
         buf.copy_to_slice(&mut transaction_id);
@@ -145,7 +150,7 @@ impl StunMessage {
     pub fn to_bytes(&self) -> Vec<u8> {
         let mut buf = BytesMut::with_capacity(20 + self.attributes.len() * 64);
 
-        let type_and_class = (self.message_type.to_u16() & 0x3FFF) | 0x0000;
+        let type_and_class = self.message_type.to_u16() & 0x3FFF;
         buf.put_u16(type_and_class);
 
         let attr_bytes: usize = self.attributes.iter()
@@ -153,6 +158,7 @@ impl StunMessage {
             .sum();
         buf.put_u16(attr_bytes as u16);
 
+        buf.put_u32(MAGIC_COOKIE);
         buf.put_slice(&self.transaction_id);
 
         for attr in &self.attributes {
@@ -201,7 +207,7 @@ pub fn make_error_response(msg: &StunMessage, code: u16, reason: &str) -> Vec<u8
         _ => StunMessageType::BindingErrorResponse,
     };
 
-    let mut response = StunMessage {
+    let response = StunMessage {
         message_type: response_type,
         transaction_id: msg.transaction_id,
         attributes: vec![
@@ -213,22 +219,12 @@ pub fn make_error_response(msg: &StunMessage, code: u16, reason: &str) -> Vec<u8
 }
 
 pub fn make_binding_response(msg: &StunMessage, mapped_addr: SocketAddr) -> Vec<u8> {
-    let mut addr_bytes = vec![0u8; 8];
-    addr_bytes[0] = 0x00;
-    addr_bytes[1] = 0x01;
-    addr_bytes[2..4].copy_from_slice(&mapped_addr.port().to_be_bytes());
-
-    let ip_octets = match mapped_addr.ip() {
-        IpAddr::V4(ipv4) => ipv4.octets(),
-        IpAddr::V6(_) => [0, 0, 0, 0],
-    };
-    addr_bytes[4..8].copy_from_slice(&ip_octets);
-
     let response = StunMessage {
         message_type: StunMessageType::BindingResponse,
         transaction_id: msg.transaction_id,
         attributes: vec![
-            StunAttribute::new(0x0020, addr_bytes),
+            StunAttribute::new(XOR_MAPPED_ADDRESS, encode_address(mapped_addr, Some(&msg.transaction_id))),
+            StunAttribute::new(MAPPED_ADDRESS, encode_address(mapped_addr, None)),
         ],
     };
 
@@ -0,0 +1,109 @@
+//! Binding service with RFC 5780 NAT behavior discovery
+//!
+//! With an alternate address the server listens on every combination of the
+//! primary and alternate IP and port. Responses then carry OTHER-ADDRESS, and
+//! a CHANGE-REQUEST makes the server answer from the socket with the other IP
+//! and/or port, which lets clients test how their NAT maps and filters.
+
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::net::UdpSocket;
+
+use super::attributes::{CHANGE_IP, CHANGE_PORT, CHANGE_REQUEST};
+use super::{handle_binding_request, make_error_response, StunMessage, StunMessageType};
+
+/// UDP sockets of the STUN service
+///
+/// Bit 0 of a socket index selects the alternate port and bit 1 the alternate
+/// IP, so socket 0 is the primary address.
+pub struct BindingServer {
+    sockets: Vec<Arc<UdpSocket>>,
+}
+
+impl BindingServer {
+    /// Bind `primary` and, given an `alternate` address, the three other
+    /// combinations of IP and port. Port 0 picks a free port.
+    pub async fn bind(primary: SocketAddr, alternate: Option<SocketAddr>) -> io::Result<Self> {
+        let mut sockets = vec![Arc::new(UdpSocket::bind(primary).await?)];
+        if let Some(alternate) = alternate {
+            let primary_port = sockets[0].local_addr()?.port();
+            let change_port = UdpSocket::bind(SocketAddr::new(primary.ip(), alternate.port())).await?;
+            let alternate_port = change_port.local_addr()?.port();
+            let change_ip = UdpSocket::bind(SocketAddr::new(alternate.ip(), primary_port)).await?;
+            let change_both = UdpSocket::bind(SocketAddr::new(alternate.ip(), alternate_port)).await?;
+            sockets.extend([change_port, change_ip, change_both].map(Arc::new));
+        }
+        Ok(Self { sockets })
+    }
+
+    /// Number of sockets, 1 without and 4 with an alternate address
+    pub fn socket_count(&self) -> usize {
+        self.sockets.len()
+    }
+
+    pub fn socket(&self, index: usize) -> &Arc<UdpSocket> {
+        &self.sockets[index]
+    }
+
+    /// Answer a Binding request that socket `index` received from `from`
+    pub async fn respond(&self, index: usize, msg: &StunMessage, from: SocketAddr) -> io::Result<()> {
+        // The flags are in the last byte of the 32-bit value
+        let change = msg.get_attribute(CHANGE_REQUEST)
+            .and_then(|attr| attr.value.get(3).copied())
+            .unwrap_or(0);
+        let mut reply_index = index;
+        if change & CHANGE_IP != 0 {
+            reply_index ^= 2;
+        }
+        if change & CHANGE_PORT != 0 {
+            reply_index ^= 1;
+        }
+
+        let (socket, response) = match self.sockets.get(reply_index) {
+            Some(socket) => {
+                let other = match self.sockets.get(index ^ 3) {
+                    Some(other) => Some(other.local_addr()?),
+                    None => None,
+                };
+                (socket, handle_binding_request(msg, &from, socket.local_addr()?, other))
+            }
+            // Without an alternate address there is nothing to change to
+            None => (&self.sockets[index], make_error_response(msg, 420, "Unknown Attribute")),
+        };
+        socket.send_to(&response, from).await?;
+        Ok(())
+    }
+
+    /// Answer Binding requests arriving on socket `index`
+    ///
+    /// Used for the alternate sockets; the primary socket also carries TURN.
+    pub async fn serve(self: Arc<Self>, index: usize) {
+        let mut buf = [0u8; 1024];
+        loop {
+            let (len, from) = match self.sockets[index].recv_from(&mut buf).await {
+                Ok(received) => received,
+                Err(e) => {
+                    tracing::warn!("UDP recv error: {}", e);
+                    continue;
+                }
+            };
+            let result = match StunMessage::parse(&buf[..len]) {
+                Ok(msg) if msg.message_type == StunMessageType::BindingRequest => {
+                    self.respond(index, &msg, from).await
+                }
+                Ok(msg) => self.sockets[index]
+                    .send_to(&make_error_response(&msg, 400, "Bad Request"), from)
+                    .await
+                    .map(|_| ()),
+                Err(e) => {
+                    tracing::debug!("Failed to parse STUN message from {}: {}", from, e);
+                    Ok(())
+                }
+            };
+            if let Err(e) = result {
+                tracing::warn!("Failed to send response: {}", e);
+            }
+        }
+    }
+}
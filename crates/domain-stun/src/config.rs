@@ -13,6 +13,10 @@ pub struct StunConfig {
     pub bind_host: String,
     pub bind_port: u16,
     pub realm: String,
+    /// Second IP for RFC 5780 NAT behavior discovery; `bind_host` must then
+    /// be a specific address as well
+    pub alternate_host: Option<String>,
+    pub alternate_port: u16,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -44,9 +48,14 @@ impl Config {
                 port: 3479,
             },
             stun: StunConfig {
-                bind_host: "0.0.0.0".to_string(),
+                bind_host: std::env::var("STUN_BIND_HOST").unwrap_or_else(|_| "0.0.0.0".to_string()),
                 bind_port: 3478,
                 realm: "domain-stun".to_string(),
+                alternate_host: std::env::var("STUN_ALTERNATE_HOST").ok().filter(|host| !host.is_empty()),
+                alternate_port: std::env::var("STUN_ALTERNATE_PORT")
+                    .ok()
+                    .and_then(|port| port.parse().ok())
+                    .unwrap_or(3480),
             },
             turn: TurnConfig {
                 bind_host: "0.0.0.0".to_string(),
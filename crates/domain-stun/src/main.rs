@@ -4,7 +4,6 @@
 
 mod config;
 mod handlers;
-mod stun;
 mod turn;
 mod db;
 
@@ -16,14 +15,13 @@ use actix_web::{web, App, HttpServer, HttpResponse};
 use chrono::{DateTime, Utc};
 use sea_orm::{ConnectionTrait, DatabaseBackend, DatabaseConnection, Statement};
 use serde::{Deserialize, Serialize};
-use tokio::net::UdpSocket;
 use tokio::sync::{broadcast, RwLock};
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
 use crate::config::Config;
 use crate::db::logger;
-use crate::stun::{StunMessage, StunMessageType, handle_binding_request, make_error_response};
+use domain_stun_core::{BindingServer, StunMessage, StunMessageType, make_error_response};
 use crate::turn::TurnHandler;
 
 /// Application state
@@ -408,11 +406,18 @@ fn get_public_ip() -> String {
 
 /// Start STUN/TURN UDP server
 async fn start_stun_server(state: Arc<AppState>) {
-    let addr = format!("0.0.0.0:{}", state.config.stun.bind_port);
+    let stun = &state.config.stun;
+    let addr = format!("{}:{}", stun.bind_host, stun.bind_port);
+    let alternate = stun.alternate_host.as_ref()
+        .map(|host| format!("{}:{}", host, stun.alternate_port));
     info!("Starting STUN server on {}", addr);
 
-    let socket = match UdpSocket::bind(&addr).await {
-        Ok(s) => s,
+    let bound = match (addr.parse::<SocketAddr>(), alternate.as_deref().map(str::parse::<SocketAddr>).transpose()) {
+        (Ok(primary), Ok(alternate)) => BindingServer::bind(primary, alternate).await,
+        _ => Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "invalid STUN address")),
+    };
+    let server = match bound {
+        Ok(s) => Arc::new(s),
         Err(e) => {
             error!("Failed to bind STUN socket: {}", e);
             logger::log_to_db(
@@ -426,10 +431,16 @@ async fn start_stun_server(state: Arc<AppState>) {
             return;
         }
     };
+    if let Some(alternate) = &alternate {
+        info!("RFC 5780 NAT behavior discovery enabled, alternate address {}", alternate);
+    }
+    for index in 1..server.socket_count() {
+        tokio::spawn(server.clone().serve(index));
+    }
 
+    let socket = server.socket(0).clone();
     let mut buf = [0u8; 1024];
     let turn_handler = state.turn_handler.clone();
-    let agents = state.agents.clone();
     let db = state.db.clone();
 
     loop {
@@ -444,7 +455,9 @@ async fn start_stun_server(state: Arc<AppState>) {
 
                         let response: Option<Vec<u8>> = match msg.message_type {
                             StunMessageType::BindingRequest => {
-                                let resp = handle_binding_request(&msg, &from, &agents).await;
+                                if let Err(e) = server.respond(0, &msg, from).await {
+                                    warn!("Failed to send response: {}", e);
+                                }
                                 logger::log_to_db(
                                     &db,
                                     "DEBUG",
@@ -453,7 +466,7 @@ async fn start_stun_server(state: Arc<AppState>) {
                                     &format!("Binding request from {}", from),
                                     None,
                                 ).await;
-                                resp
+                                None
                             }
                             StunMessageType::AllocateRequest => {
                                 let resp = turn_handler.handle_allocate_request(&msg, &from).await;
@@ -6,7 +6,7 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
-use crate::stun::{StunMessage, StunMessageType, StunAttribute, make_error_response};
+use domain_stun_core::{StunMessage, StunMessageType, StunAttribute, make_error_response};
 
 #[derive(Debug, Clone)]
 pub struct TurnAllocation {